@@ -0,0 +1,943 @@
+//! 高频只读 adb 查询的传输层：开启直连时通过 TCP 与本机 adb server（5037 端口）对话，
+//! 省去每条命令启动一次 adb 客户端进程的开销；协议出错时自动回退到命令行。
+//! 安装、推送文件等操作仍走命令行 adb。
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, SocketAddr, TcpStream};
+use std::process::{Child, Command, Stdio};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, RwLock};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{Emitter, Manager};
+
+use crate::input_path;
+use crate::paths::{self, AppPaths};
+
+const SERVER_ADDR: ([u8; 4], u16) = ([127, 0, 0, 1], 5037);
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(500);
+const IO_TIMEOUT: Duration = Duration::from_secs(30);
+/// 命令行模式下设备监视的轮询间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+const SETTINGS_FILE: &str = "adb_transport.json";
+/// 用户指定的 adb 路径
+const ADB_PATH_FILE: &str = "adb_path.json";
+/// 无线调试的默认端口
+const DEFAULT_WIRELESS_PORT: u16 = 5555;
+/// 重启 adb server 后等待设备重新出现的时长
+const RESTART_WAIT: Duration = Duration::from_secs(3);
+/// `adb devices -l` 在状态之后输出的列
+const DEVICE_COLUMNS: [&str; 5] = ["usb", "product", "model", "device", "transport_id"];
+
+static DIRECT_ENABLED: AtomicBool = AtomicBool::new(false);
+/// 设备监视代号，重新启动监视会使旧的监视线程退出
+static MONITOR_GENERATION: AtomicU64 = AtomicU64::new(0);
+/// 当前使用的 adb，启动时与修改设置时确定；为空时使用 PATH 中的 `adb`
+static ADB_PROGRAM: RwLock<Option<AdbProgram>> = RwLock::new(None);
+/// 最近一次直连失败的原因，直连恢复后清空
+static DIRECT_ERROR: Mutex<Option<String>> = Mutex::new(None);
+
+/// 用户指定的 adb 路径设置
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct AdbPathSettings {
+    /// 为空时依次使用内置工具目录中的 adb 与 PATH 中的 adb
+    pub path: Option<String>,
+}
+
+/// 当前使用的 adb 可执行文件
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct AdbProgram {
+    pub path: String,
+    /// `custom`（用户指定）、`bundled`（内置工具目录）或 `path`（PATH 中查找）
+    pub source: String,
+}
+
+/// `check_adb` 的结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct AdbCheck {
+    pub available: bool,
+    pub program: AdbProgram,
+    /// `adb version` 的第一行，如 `Android Debug Bridge version 1.0.41`
+    pub version: Option<String>,
+    pub error: Option<String>,
+    /// 开启直连时最近一次直连失败的原因，此后的查询已改用命令行
+    pub direct_error: Option<String>,
+}
+
+/// adb 传输层设置
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct AdbTransportSettings {
+    /// 高频只读查询直连 adb server
+    pub direct: bool,
+}
+
+/// 设备列表变化事件
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct DevicesChanged {
+    pub devices: Vec<String>,
+    /// 所有设备及其状态，设备在 device / unauthorized / offline 之间变化时也会推送
+    pub details: Vec<DeviceInfo>,
+    /// `direct`（server 长连接）、`track`（`adb track-devices` 子进程）或 `poll`（轮询）
+    pub transport: String,
+}
+
+/// 设备跟踪最近一次观察到的状态，读取时不会调用 adb
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct TrackerSnapshot {
+    pub tracking: bool,
+    /// `direct`、`track` 或 `poll`
+    pub transport: Option<String>,
+    /// 状态为 device 的设备数
+    pub ready_devices: usize,
+    pub total_devices: usize,
+    /// 最近一次跟踪或轮询失败的原因，恢复后清空
+    pub error: Option<String>,
+    /// Unix 时间戳（秒），从未观察到时为空
+    #[cfg_attr(feature = "export-bindings", ts(type = "number | null"))]
+    pub updated_at: Option<u64>,
+}
+
+/// 正在运行的设备跟踪（托管状态），停止或退出时关闭连接与子进程，避免遗留 adb 进程
+#[derive(Default)]
+pub struct DeviceTracker {
+    handle: Mutex<Option<TrackHandle>>,
+    observed: Mutex<TrackerSnapshot>,
+}
+
+enum TrackHandle {
+    Stream(TcpStream),
+    Child(Child),
+}
+
+impl DeviceTracker {
+    fn hold(&self, handle: TrackHandle) {
+        if let Some(previous) = self.handle.lock().unwrap().replace(handle) {
+            close(previous);
+        }
+    }
+
+    fn release(&self) {
+        if let Some(handle) = self.handle.lock().unwrap().take() {
+            close(handle);
+        }
+    }
+
+    fn observe(&self, update: impl FnOnce(&mut TrackerSnapshot)) {
+        let mut observed = self.observed.lock().unwrap();
+        update(&mut observed);
+        observed.updated_at = Some(SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0));
+    }
+
+    /// 最近一次观察到的设备与 adb 状态
+    pub fn snapshot(&self) -> TrackerSnapshot {
+        self.observed.lock().unwrap().clone()
+    }
+}
+
+fn close(handle: TrackHandle) {
+    match handle {
+        TrackHandle::Stream(stream) => {
+            let _ = stream.shutdown(std::net::Shutdown::Both);
+        }
+        TrackHandle::Child(mut child) => {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+/// `adb connect` 的结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct ConnectResult {
+    pub connected: bool,
+    /// 连接前就已连接
+    pub already_connected: bool,
+    /// 设备在 `get_devices` 中的 ID（`host:port`）
+    pub device_id: String,
+    pub message: String,
+}
+
+/// `adb pair` 的结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct PairResult {
+    /// 配对成功的地址
+    pub address: String,
+    /// adb 输出的设备 GUID（`adb-<serial>-<id>`）
+    pub guid: Option<String>,
+    pub message: String,
+}
+
+/// 按 adb server 协议编码请求：4 位十六进制长度 + 内容
+pub fn encode_request(service: &str) -> Vec<u8> {
+    format!("{:04x}{}", service.len(), service).into_bytes()
+}
+
+fn read_exact_string(stream: &mut impl Read, len: usize) -> io::Result<String> {
+    let mut buf = vec![0u8; len];
+    stream.read_exact(&mut buf)?;
+    Ok(String::from_utf8_lossy(&buf).to_string())
+}
+
+/// 读取一段带 4 位十六进制长度前缀的数据
+pub fn read_length_prefixed(stream: &mut impl Read) -> io::Result<String> {
+    let len = read_exact_string(stream, 4)?;
+    let len = usize::from_str_radix(&len, 16)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("非法长度前缀: {:?}", len)))?;
+    read_exact_string(stream, len)
+}
+
+/// 读取 OKAY / FAIL 状态，FAIL 时返回 server 给出的原因
+pub fn read_status(stream: &mut impl Read) -> io::Result<()> {
+    match read_exact_string(stream, 4)?.as_str() {
+        "OKAY" => Ok(()),
+        "FAIL" => {
+            let reason = read_length_prefixed(stream)?;
+            Err(io::Error::other(format!("adb server 拒绝请求: {}", reason)))
+        }
+        other => Err(io::Error::new(io::ErrorKind::InvalidData, format!("未知的响应状态: {:?}", other))),
+    }
+}
+
+/// `adb devices -l` 中的一台设备
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct DeviceInfo {
+    pub id: String,
+    /// `device`、`unauthorized`（需在设备上允许 USB 调试）、`offline` 等
+    pub state: String,
+    pub model: Option<String>,
+    pub product: Option<String>,
+    /// `usb`、`tcp`（无线调试）或 `emulator`
+    pub transport: Option<String>,
+    /// `ro.build.version.release`，仅状态为 device 时读取
+    pub android_version: Option<String>,
+    /// `ro.product.cpu.abi`
+    pub abi: Option<String>,
+}
+
+impl DeviceInfo {
+    pub fn is_ready(&self) -> bool {
+        self.state == "device"
+    }
+}
+
+/// 设备列表查询结果
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct DeviceList {
+    /// 状态为 device 的序列号
+    pub devices: Vec<String>,
+    /// 所有设备（含 unauthorized / offline）及其型号等信息
+    #[serde(default)]
+    pub details: Vec<DeviceInfo>,
+    /// 本次查询启动了 adb server（首次查询较慢的原因）
+    pub daemon_started: bool,
+}
+
+impl DeviceList {
+    fn from_details(details: Vec<DeviceInfo>, daemon_started: bool) -> Self {
+        let devices = details.iter().filter(|d| d.is_ready()).map(|d| d.id.clone()).collect();
+        DeviceList { devices, details, daemon_started }
+    }
+}
+
+/// 解析设备列表中的一行：`序列号 状态 [usb:1-1] [product:x] [model:y] [device:z] [transport_id:n]`。
+/// 状态可能含空格（如 `no permissions (...)`），取到第一个已知的 `key:value` 列之前
+pub fn parse_device_line(line: &str) -> Option<DeviceInfo> {
+    let mut parts = line.split_whitespace();
+    let id = parts.next()?.to_string();
+    let rest: Vec<&str> = parts.collect();
+    let is_column = |part: &&str| part.split_once(':').is_some_and(|(key, _)| DEVICE_COLUMNS.contains(&key));
+    let columns_at = rest.iter().position(is_column).unwrap_or(rest.len());
+    let state = rest[..columns_at].join(" ");
+    if state.is_empty() {
+        return None;
+    }
+    let column = |key: &str| rest[columns_at..].iter().find_map(|part| part.strip_prefix(key)?.strip_prefix(':')).map(str::to_string);
+    let transport = if id.starts_with("emulator-") {
+        Some("emulator")
+    } else if id.contains(':') || id.contains("._tcp") {
+        Some("tcp")
+    } else if column("usb").is_some() {
+        Some("usb")
+    } else {
+        None
+    };
+    Some(DeviceInfo {
+        model: column("model"),
+        product: column("product"),
+        transport: transport.map(str::to_string),
+        id,
+        state,
+        android_version: None,
+        abi: None,
+    })
+}
+
+fn connect() -> io::Result<TcpStream> {
+    let stream = TcpStream::connect_timeout(&SocketAddr::from(SERVER_ADDR), CONNECT_TIMEOUT)?;
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    Ok(stream)
+}
+
+fn request(stream: &mut TcpStream, service: &str) -> io::Result<()> {
+    stream.write_all(&encode_request(service))?;
+    read_status(stream)
+}
+
+fn direct_devices() -> io::Result<Vec<DeviceInfo>> {
+    let mut stream = connect()?;
+    request(&mut stream, "host:devices-l")?;
+    Ok(read_length_prefixed(&mut stream)?.lines().filter_map(parse_device_line).collect())
+}
+
+fn direct_shell(device_id: &str, command: &str) -> io::Result<String> {
+    let mut stream = connect()?;
+    request(&mut stream, &format!("host:transport:{}", device_id))?;
+    request(&mut stream, &format!("shell:{}", command))?;
+    let mut output = Vec::new();
+    stream.read_to_end(&mut output)?;
+    Ok(String::from_utf8_lossy(&output).to_string())
+}
+
+/// 解析 `adb devices -l` 的输出；`devices` 只含状态为 device 的序列号，`details` 含全部设备。
+/// 以 `List of devices attached` 为起点，忽略 adb server 启动时输出的 `* daemon ...` 提示行
+pub fn parse_cli_devices(output: &str) -> Result<DeviceList, String> {
+    let mut lines = output.lines().map(|line| line.trim_end_matches('\r'));
+    let mut daemon_started = false;
+    for line in lines.by_ref() {
+        if line.starts_with("List of devices attached") {
+            let details = lines
+                .filter(|line| !line.trim().is_empty() && !line.starts_with('*'))
+                .filter_map(parse_device_line)
+                .collect();
+            return Ok(DeviceList::from_details(details, daemon_started));
+        }
+        daemon_started |= line.starts_with('*') && line.contains("daemon");
+    }
+    Err(format!("无法识别 adb devices 的输出: {}", output.trim()))
+}
+
+fn cli_devices() -> Result<DeviceList, String> {
+    let output = command()
+        .args(["devices", "-l"])
+        .output()
+        .map_err(|e| e.to_string())?;
+
+    // 不同版本的 adb 把启动提示写到 stdout 或 stderr，合并后解析
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    parse_cli_devices(&format!("{}\n{}", stderr, stdout))
+}
+
+fn cli_shell(device_id: &str, args: &[&str]) -> Result<String, String> {
+    let output = command()
+        .args(["-s", device_id, "shell"])
+        .args(args)
+        .output()
+        .map_err(|e| e.to_string())?;
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+pub fn direct_enabled() -> bool {
+    DIRECT_ENABLED.load(Ordering::SeqCst)
+}
+
+fn direct_error() -> Option<String> {
+    DIRECT_ERROR.lock().unwrap().clone()
+}
+
+/// 记录直连的结果：失败时保留原因供 `check_adb` 显示，成功时清空
+fn note_direct<T, E: std::fmt::Display>(result: Result<T, E>, context: impl FnOnce() -> String) -> Option<T> {
+    match result {
+        Ok(value) => {
+            *DIRECT_ERROR.lock().unwrap() = None;
+            Some(value)
+        }
+        Err(e) => {
+            *DIRECT_ERROR.lock().unwrap() = Some(format!("{}: {}", context(), e));
+            None
+        }
+    }
+}
+
+/// 已连接的设备；没有设备时 `devices` 为空，不视为错误
+pub fn device_list() -> Result<DeviceList, String> {
+    if direct_enabled() {
+        if let Some(details) = note_direct(direct_devices(), || "直连查询设备失败".to_string()) {
+            return Ok(DeviceList::from_details(details, false));
+        }
+    }
+    cli_devices()
+}
+
+/// 已连接的设备序列号
+pub fn devices() -> Result<Vec<String>, String> {
+    device_list().map(|list| list.devices)
+}
+
+/// 在设备上执行只读 shell 命令并返回标准输出
+pub fn shell(device_id: &str, args: &[&str]) -> Result<String, String> {
+    if let Some(output) = crate::injection::intercept(command().args(["-s", device_id, "shell"]).args(args)) {
+        let output = output.map_err(|e| e.to_string())?;
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).to_string());
+        }
+        return Ok(String::from_utf8_lossy(&output.stdout).to_string());
+    }
+    if direct_enabled() {
+        if let Some(output) = note_direct(direct_shell(device_id, &args.join(" ")), || format!("直连执行 {} 失败", args.join(" "))) {
+            return Ok(output);
+        }
+    }
+    cli_shell(device_id, args)
+}
+
+/// 内置工具目录中的 adb
+pub fn bundled_adb(tools_dir: &Path) -> PathBuf {
+    tools_dir.join(if cfg!(target_os = "windows") { "adb.exe" } else { "adb" })
+}
+
+fn load_path_settings(paths: &AppPaths) -> AdbPathSettings {
+    fs::read_to_string(paths.config_dir.join(ADB_PATH_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// 按 用户指定 → 内置工具目录 → PATH 的顺序确定使用的 adb
+fn resolve_program(paths: &AppPaths, settings: &AdbPathSettings) -> AdbProgram {
+    if let Some(path) = settings.path.as_deref().filter(|p| !p.trim().is_empty()) {
+        return AdbProgram { path: path.to_string(), source: "custom".to_string() };
+    }
+    let bundled = bundled_adb(&paths.tools_dir);
+    if bundled.is_file() {
+        return AdbProgram { path: bundled.to_string_lossy().to_string(), source: "bundled".to_string() };
+    }
+    AdbProgram { path: "adb".to_string(), source: "path".to_string() }
+}
+
+/// 当前使用的 adb
+pub fn program() -> AdbProgram {
+    ADB_PROGRAM
+        .read()
+        .unwrap()
+        .clone()
+        .unwrap_or_else(|| AdbProgram { path: "adb".to_string(), source: "path".to_string() })
+}
+
+/// 调用 adb 的命令；所有执行 adb 的地方都应通过这里创建，以使用设置中的路径
+pub fn command() -> Command {
+    Command::new(program().path)
+}
+
+/// 启动时确定使用的 adb
+pub fn init_program(paths: &AppPaths) {
+    *ADB_PROGRAM.write().unwrap() = Some(resolve_program(paths, &load_path_settings(paths)));
+}
+
+fn version_of(program: &AdbProgram) -> Result<String, String> {
+    let output = Command::new(&program.path).arg("version").output().map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => format!("未找到 adb（{}），请安装 Android SDK Platform-Tools 或在设置中指定 adb 路径", program.path),
+        _ => format!("运行 {} 失败: {}", program.path, e),
+    })?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    match stdout.lines().next().map(str::trim).filter(|line| !line.is_empty()) {
+        Some(line) if output.status.success() => Ok(line.to_string()),
+        _ => Err(format!("{} 不是可用的 adb: {}", program.path, String::from_utf8_lossy(&output.stderr).trim())),
+    }
+}
+
+/// 检测 adb 是否可用，返回使用的 adb 路径、来源与版本
+pub fn check() -> AdbCheck {
+    let program = program();
+    match version_of(&program) {
+        Ok(version) => AdbCheck { available: true, program, version: Some(version), error: None, direct_error: direct_error() },
+        Err(e) => AdbCheck { available: false, program, version: None, error: Some(e), direct_error: direct_error() },
+    }
+}
+
+/// 获取用户指定的 adb 路径设置
+#[tauri::command]
+pub fn get_adb_path(app_paths: tauri::State<'_, AppPaths>) -> AdbPathSettings {
+    load_path_settings(&app_paths)
+}
+
+/// 指定 adb 路径（为空时恢复自动查找），保存前先确认能运行 `adb version`；返回检测结果
+#[tauri::command]
+pub fn set_adb_path(app: tauri::AppHandle, app_paths: tauri::State<'_, AppPaths>, path: Option<String>) -> Result<AdbCheck, String> {
+    let result = save_adb_path(&app_paths, path.as_deref());
+    crate::audit::record(&app, "set_adb_path", serde_json::json!({ "path": path }), &result);
+    result
+}
+
+fn save_adb_path(app_paths: &AppPaths, path: Option<&str>) -> Result<AdbCheck, String> {
+    let path = path
+        .filter(|p| !p.trim().is_empty())
+        .map(|p| input_path::arg(p, input_path::Expect::File))
+        .transpose()
+        .map_err(|e| e.to_string())?;
+    let settings = AdbPathSettings { path };
+    let program = resolve_program(app_paths, &settings);
+    version_of(&program)?;
+    let content = serde_json::to_vec_pretty(&settings).map_err(|e| e.to_string())?;
+    paths::write_atomic(&app_paths.config_dir.join(ADB_PATH_FILE), &content).map_err(|e| e.to_string())?;
+    *ADB_PROGRAM.write().unwrap() = Some(program);
+    Ok(check())
+}
+
+/// 启动时从配置目录载入设置
+pub fn load_settings(paths: &AppPaths) -> AdbTransportSettings {
+    let settings: AdbTransportSettings = fs::read_to_string(paths.config_dir.join(SETTINGS_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    DIRECT_ENABLED.store(settings.direct, Ordering::SeqCst);
+    settings
+}
+
+/// 获取 adb 传输层设置
+#[tauri::command]
+pub fn get_adb_transport_settings() -> AdbTransportSettings {
+    AdbTransportSettings { direct: direct_enabled() }
+}
+
+/// 修改 adb 传输层设置并持久化
+#[tauri::command]
+pub fn set_adb_transport_settings(
+    app: tauri::AppHandle,
+    app_paths: tauri::State<'_, AppPaths>,
+    settings: AdbTransportSettings,
+) -> Result<(), String> {
+    let result = save_settings(&app_paths, &settings);
+    crate::audit::record(&app, "set_adb_transport_settings", serde_json::to_value(&settings).unwrap_or_default(), &result);
+    result
+}
+
+fn save_settings(app_paths: &AppPaths, settings: &AdbTransportSettings) -> Result<(), String> {
+    let content = serde_json::to_vec_pretty(settings).map_err(|e| e.to_string())?;
+    paths::write_atomic(&app_paths.config_dir.join(SETTINGS_FILE), &content).map_err(|e| e.to_string())?;
+    DIRECT_ENABLED.store(settings.direct, Ordering::SeqCst);
+    Ok(())
+}
+
+fn tracking(generation: u64) -> bool {
+    MONITOR_GENERATION.load(Ordering::SeqCst) == generation
+}
+
+/// 设备列表有变化时推送事件
+fn emit_if_changed(app: &tauri::AppHandle, last: &mut Option<Vec<DeviceInfo>>, details: Vec<DeviceInfo>, transport: &str) {
+    app.state::<DeviceTracker>().observe(|observed| {
+        observed.tracking = true;
+        observed.transport = Some(transport.to_string());
+        observed.ready_devices = details.iter().filter(|d| d.is_ready()).count();
+        observed.total_devices = details.len();
+        observed.error = None;
+    });
+    if last.as_ref() == Some(&details) {
+        return;
+    }
+    let devices = details.iter().filter(|d| d.is_ready()).map(|d| d.id.clone()).collect();
+    let _ = app.emit("devices-changed", DevicesChanged { devices, details: details.clone(), transport: transport.to_string() });
+    *last = Some(details);
+}
+
+/// 逐帧读取 track-devices 的输出（长度前缀 + 设备列表），直到连接关闭或跟踪被替换
+fn read_track_frames(
+    app: &tauri::AppHandle,
+    generation: u64,
+    reader: &mut impl Read,
+    last: &mut Option<Vec<DeviceInfo>>,
+    transport: &str,
+) -> io::Result<()> {
+    while tracking(generation) {
+        let payload = read_length_prefixed(reader)?;
+        emit_if_changed(app, last, payload.lines().filter_map(parse_device_line).collect(), transport);
+    }
+    Ok(())
+}
+
+/// 通过 server 的 `host:track-devices-l` 长连接跟踪
+fn track_direct(app: &tauri::AppHandle, tracker: &DeviceTracker, generation: u64, last: &mut Option<Vec<DeviceInfo>>) -> io::Result<()> {
+    let mut stream = connect()?;
+    stream.set_read_timeout(None)?;
+    request(&mut stream, "host:track-devices-l")?;
+    tracker.hold(TrackHandle::Stream(stream.try_clone()?));
+    read_track_frames(app, generation, &mut stream, last, "direct")
+}
+
+/// 通过 `adb track-devices -l` 子进程跟踪，输出格式与 server 协议相同
+fn track_child(app: &tauri::AppHandle, tracker: &DeviceTracker, generation: u64, last: &mut Option<Vec<DeviceInfo>>) -> io::Result<()> {
+    let mut child = command().args(["track-devices", "-l"]).stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::null()).spawn()?;
+    let mut stdout = child.stdout.take().ok_or_else(|| io::Error::other("无法读取 adb track-devices 的输出"))?;
+    tracker.hold(TrackHandle::Child(child));
+    read_track_frames(app, generation, &mut stdout, last, "track")
+}
+
+/// 补全无线调试地址的端口：`192.168.1.5` → `192.168.1.5:5555`，IPv6 写作 `[fe80::1]:5555`
+pub fn normalize_address(address: &str) -> Result<String, String> {
+    let address = address.trim();
+    if address.is_empty() || address.contains(char::is_whitespace) {
+        return Err(format!("无效的设备地址: {:?}", address));
+    }
+    if let Ok(addr) = address.parse::<SocketAddr>() {
+        return Ok(addr.to_string());
+    }
+    let bare = address.strip_prefix('[').and_then(|a| a.strip_suffix(']')).unwrap_or(address);
+    if let Ok(ip) = bare.parse::<IpAddr>() {
+        return Ok(SocketAddr::new(ip, DEFAULT_WIRELESS_PORT).to_string());
+    }
+    match address.split_once(':') {
+        Some((host, port)) if !host.is_empty() && !port.contains(':') => match port.parse::<u16>() {
+            Ok(port) if port > 0 => Ok(format!("{}:{}", host, port)),
+            _ => Err(format!("无效的端口: {}", port)),
+        },
+        Some(_) => Err(format!("无效的设备地址: {}", address)),
+        None => Ok(format!("{}:{}", address, DEFAULT_WIRELESS_PORT)),
+    }
+}
+
+/// 解析 `adb connect` 的输出。adb 连接失败时退出码也可能为 0，只能按输出判断
+pub fn parse_connect_output(output: &str, device_id: &str) -> Result<ConnectResult, String> {
+    let text = output.trim();
+    let lower = text.to_lowercase();
+    let result = |already_connected| ConnectResult {
+        connected: true,
+        already_connected,
+        device_id: device_id.to_string(),
+        message: text.to_string(),
+    };
+    if lower.contains("already connected to") {
+        return Ok(result(true));
+    }
+    if lower.contains("failed to authenticate") {
+        return Err(format!("{} 拒绝授权，请在设备上允许调试授权后重试: {}", device_id, text));
+    }
+    if lower.contains("failed to connect") || lower.contains("cannot connect") || lower.contains("unable to connect") {
+        return Err(format!("无法连接 {}，请确认设备已开启无线调试、与电脑在同一网络且端口正确: {}", device_id, text));
+    }
+    if lower.contains("connected to") {
+        return Ok(result(false));
+    }
+    Err(format!("无法识别 adb connect 的输出: {}", text))
+}
+
+fn run_cli(args: &[&str]) -> Result<String, String> {
+    let output = command().args(args).output().map_err(|e| e.to_string())?;
+    Ok(format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr)))
+}
+
+/// 解析 `adb pair` 的输出：`Successfully paired to 192.168.1.5:37123 [guid=adb-XXXX-abc]`
+pub fn parse_pair_output(output: &str, address: &str) -> Result<PairResult, String> {
+    let text = output.trim().trim_start_matches("Enter pairing code:").trim();
+    let re = regex::Regex::new(r"Successfully paired to (\S+)(?: \[guid=([^\]]+)\])?").unwrap();
+    match re.captures(text) {
+        Some(cap) => Ok(PairResult {
+            address: cap[1].to_string(),
+            guid: cap.get(2).map(|m| m.as_str().to_string()),
+            message: text.to_string(),
+        }),
+        None if text.to_lowercase().contains("wrong password") || text.contains("Failed: Unable to start pairing client") => {
+            Err(format!("与 {} 配对失败，请确认配对码与配对端口（不是无线调试端口）正确: {}", address, text))
+        }
+        None => Err(format!("与 {} 配对失败: {}", address, text)),
+    }
+}
+
+/// 与 Android 11+ 的无线调试设备配对。`address` 为设备“使用配对码配对”界面显示的地址与端口，
+/// 配对码通过标准输入交给 `adb pair`；配对成功后再用 `connect_device` 连接
+#[tauri::command]
+pub async fn pair_device(app: tauri::AppHandle, address: String, code: String) -> Result<PairResult, String> {
+    let result = pair(&address, &code);
+    crate::audit::record(&app, "pair_device", serde_json::json!({ "address": address }), &result);
+    result
+}
+
+fn pair(address: &str, code: &str) -> Result<PairResult, String> {
+    let code = code.trim();
+    if code.len() != 6 || !code.chars().all(|c| c.is_ascii_digit()) {
+        return Err("配对码应为 6 位数字".to_string());
+    }
+    // 配对端口每次随机生成，不能套用默认端口
+    let address = address.trim();
+    if address.parse::<SocketAddr>().is_err() && address.rsplit_once(':').is_none_or(|(_, port)| port.parse::<u16>().is_err()) {
+        return Err(format!("配对地址需要包含端口（如 192.168.1.5:37123）: {}", address));
+    }
+    let mut child = command()
+        .args(["pair", address])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("启动 adb pair 失败: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // adb 在提示 `Enter pairing code:` 后才读取，提前写入的内容会留在管道中
+        writeln!(stdin, "{}", code).map_err(|e| format!("写入配对码失败: {}", e))?;
+    }
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    let text = format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+    parse_pair_output(&text, address)
+}
+
+/// 通过 `adb connect` 连接无线调试设备；地址不带端口时使用 5555
+#[tauri::command]
+pub async fn connect_device(app: tauri::AppHandle, address: String) -> Result<ConnectResult, String> {
+    let result = normalize_address(&address).and_then(|device_id| parse_connect_output(&run_cli(&["connect", &device_id])?, &device_id));
+    crate::audit::record(&app, "connect_device", serde_json::json!({ "address": address }), &result);
+    result
+}
+
+/// 断开无线调试设备
+#[tauri::command]
+pub async fn disconnect_device(app: tauri::AppHandle, address: String) -> Result<(), String> {
+    let result = normalize_address(&address).and_then(|device_id| {
+        let output = run_cli(&["disconnect", &device_id])?;
+        if output.contains("disconnected") {
+            Ok(())
+        } else {
+            Err(format!("断开 {} 失败: {}", device_id, output.trim()))
+        }
+    });
+    crate::audit::record(&app, "disconnect_device", serde_json::json!({ "address": address }), &result);
+    result
+}
+
+/// adb server 重启后等待设备重新连上：出现任意设备或超过 `RESTART_WAIT` 时返回当前列表
+pub fn wait_for_devices() -> Result<DeviceList, String> {
+    let deadline = Instant::now() + RESTART_WAIT;
+    loop {
+        let list = cli_devices();
+        let settled = list.as_ref().is_ok_and(|l| !l.details.is_empty());
+        if settled || Instant::now() >= deadline {
+            return list;
+        }
+        thread::sleep(Duration::from_millis(300));
+    }
+}
+
+/// 跟踪设备连接与状态变化并推送 `devices-changed`，返回本次跟踪的代号。
+/// 开启直连时使用 server 长连接，否则使用 `adb track-devices` 子进程；两者都失败时定时轮询并稍后重试
+#[tauri::command]
+pub fn start_device_tracking(app: tauri::AppHandle) -> u64 {
+    let generation = MONITOR_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    app.state::<DeviceTracker>().release();
+    thread::spawn(move || {
+        let tracker = app.state::<DeviceTracker>();
+        let mut last: Option<Vec<DeviceInfo>> = None;
+        while tracking(generation) {
+            let tracked = if direct_enabled() {
+                track_direct(&app, &tracker, generation, &mut last)
+            } else {
+                track_child(&app, &tracker, generation, &mut last)
+            };
+            if !tracking(generation) {
+                break;
+            }
+            if let Err(e) = tracked {
+                crate::emit_progress(&app, "devices", format!("设备跟踪中断，改用轮询: {}", e));
+            }
+            match cli_devices() {
+                Ok(list) => emit_if_changed(&app, &mut last, list.details, "poll"),
+                Err(e) => tracker.observe(|observed| observed.error = Some(e)),
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    });
+    generation
+}
+
+/// 停止设备跟踪，关闭长连接或结束 `adb track-devices` 子进程
+#[tauri::command]
+pub fn stop_device_tracking(tracker: tauri::State<'_, DeviceTracker>) {
+    stop_tracking(&tracker);
+}
+
+fn stop_tracking(tracker: &DeviceTracker) {
+    MONITOR_GENERATION.fetch_add(1, Ordering::SeqCst);
+    tracker.release();
+    tracker.observe(|observed| observed.tracking = false);
+}
+
+/// 应用退出时停止设备跟踪
+pub fn shutdown(app: &tauri::AppHandle) {
+    if let Some(tracker) = app.try_state::<DeviceTracker>() {
+        stop_tracking(&tracker);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn request_is_length_prefixed_in_hex() {
+        assert_eq!(encode_request("host:devices-l"), b"000ehost:devices-l");
+        assert_eq!(encode_request(""), b"0000");
+    }
+
+    #[test]
+    fn okay_status_leaves_the_payload_unread() {
+        let mut stream = Cursor::new(b"OKAY0014emulator-5554\tdevice".to_vec());
+        read_status(&mut stream).unwrap();
+        assert_eq!(read_length_prefixed(&mut stream).unwrap(), "emulator-5554\tdevice");
+    }
+
+    #[test]
+    fn fail_status_carries_the_server_message() {
+        let mut stream = Cursor::new(b"FAIL0014device 'x' not found".to_vec());
+        let err = read_status(&mut stream).unwrap_err();
+        assert!(err.to_string().contains("device 'x' not found"), "{}", err);
+    }
+
+    #[test]
+    fn unknown_status_is_invalid_data() {
+        let err = read_status(&mut Cursor::new(b"WHAT".to_vec())).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn truncated_length_is_an_eof_error() {
+        let err = read_length_prefixed(&mut Cursor::new(b"00".to_vec())).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        let err = read_length_prefixed(&mut Cursor::new(b"0010short".to_vec())).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        let err = read_status(&mut Cursor::new(b"OK".to_vec())).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn non_hex_length_is_invalid_data() {
+        let err = read_length_prefixed(&mut Cursor::new(b"00zzabc".to_vec())).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("00zz"));
+    }
+
+    #[test]
+    fn transport_settings_are_saved_atomically() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = AppPaths::portable_at(dir.path());
+        save_settings(&paths, &AdbTransportSettings { direct: true }).unwrap();
+        let saved: AdbTransportSettings = serde_json::from_str(&fs::read_to_string(paths.config_dir.join(SETTINGS_FILE)).unwrap()).unwrap();
+        assert!(saved.direct);
+        assert!(!paths.config_dir.join(format!("{}.tmp", SETTINGS_FILE)).exists());
+        save_settings(&paths, &AdbTransportSettings { direct: false }).unwrap();
+        assert!(!direct_enabled());
+    }
+
+    const LINUX_COLD_START: &str = "* daemon not running; starting now at tcp:5037\n\
+* daemon started successfully\n\
+List of devices attached\n\
+R58M12345AB            device usb:1-1 product:beyond1lteks model:SM_G973N device:beyond1 transport_id:1\n\
+\n";
+
+    const WINDOWS_COLD_START: &str = "* daemon not running; starting now at tcp:5037\r\n\
+* daemon started successfully\r\n\
+List of devices attached\r\n\
+emulator-5554\tdevice product:sdk_gphone64_x86_64 model:sdk_gphone64_x86_64 device:emu64xa transport_id:2\r\n\
+\r\n";
+
+    const WINDOWS_WARM_START: &str = "List of devices attached\r\n\
+R58M12345AB            device product:beyond1lteks model:SM_G973N device:beyond1 transport_id:1\r\n\
+192.168.1.20:5555      offline transport_id:3\r\n\
+\r\n";
+
+    #[test]
+    fn linux_cold_start_reports_the_daemon_start() {
+        let list = parse_cli_devices(LINUX_COLD_START).unwrap();
+        assert!(list.daemon_started);
+        assert_eq!(list.devices, ["R58M12345AB"]);
+        assert_eq!(list.details[0].model.as_deref(), Some("SM_G973N"));
+        assert_eq!(list.details[0].transport.as_deref(), Some("usb"));
+    }
+
+    #[test]
+    fn windows_cold_start_strips_carriage_returns() {
+        let list = parse_cli_devices(WINDOWS_COLD_START).unwrap();
+        assert!(list.daemon_started);
+        assert_eq!(list.devices, ["emulator-5554"]);
+        assert_eq!(list.details.len(), 1);
+        assert_eq!(list.details[0].state, "device");
+        assert_eq!(list.details[0].model.as_deref(), Some("sdk_gphone64_x86_64"));
+        assert!(list.details.iter().all(|d| !d.id.contains('\r') && !d.state.contains('\r')));
+    }
+
+    #[test]
+    fn warm_start_keeps_devices_that_are_not_ready() {
+        let list = parse_cli_devices(WINDOWS_WARM_START).unwrap();
+        assert!(!list.daemon_started);
+        assert_eq!(list.devices, ["R58M12345AB"]);
+        assert_eq!(list.details.len(), 2);
+        assert_eq!(list.details[1].state, "offline");
+        assert_eq!(list.details[1].transport.as_deref(), Some("tcp"));
+        let linux = parse_cli_devices(&WINDOWS_WARM_START.replace("\r\n", "\n")).unwrap();
+        assert_eq!(linux, list);
+    }
+
+    #[test]
+    fn empty_device_list_is_not_an_error() {
+        let list = parse_cli_devices("List of devices attached\n\n").unwrap();
+        assert!(list.devices.is_empty() && list.details.is_empty());
+    }
+
+    #[test]
+    fn output_without_the_header_is_an_error() {
+        let err = parse_cli_devices("* daemon not running; starting now at tcp:5037\nerror: cannot connect to daemon\n").unwrap_err();
+        assert!(err.contains("cannot connect"));
+    }
+
+    #[test]
+    fn device_line_reads_the_key_value_columns() {
+        let device = parse_device_line("0123456789ABCDEF       device usb:336592896X product:OnePlus7 model:GM1910 device:OnePlus7 transport_id:4").unwrap();
+        assert_eq!(device.id, "0123456789ABCDEF");
+        assert_eq!(device.state, "device");
+        assert_eq!(device.product.as_deref(), Some("OnePlus7"));
+        assert_eq!(device.model.as_deref(), Some("GM1910"));
+        assert_eq!(device.transport.as_deref(), Some("usb"));
+        assert!(device.is_ready());
+    }
+
+    #[test]
+    fn unauthorized_device_has_no_model() {
+        let device = parse_device_line("R58M12345AB            unauthorized usb:1-1 transport_id:5").unwrap();
+        assert_eq!(device.state, "unauthorized");
+        assert_eq!(device.model, None);
+        assert_eq!(device.transport.as_deref(), Some("usb"));
+        assert!(!device.is_ready());
+    }
+
+    #[test]
+    fn offline_wireless_device_is_tcp() {
+        let device = parse_device_line("adb-R58M12345AB-AbCdEf._adb-tls-connect._tcp offline product:beyond1lteks model:SM_G973N device:beyond1 transport_id:6").unwrap();
+        assert_eq!(device.state, "offline");
+        assert_eq!(device.transport.as_deref(), Some("tcp"));
+        assert_eq!(device.model.as_deref(), Some("SM_G973N"));
+    }
+
+    #[test]
+    fn state_with_spaces_is_kept_whole() {
+        let device = parse_device_line("0123456789ABCDEF       no permissions (missing udev rules? user is in the plugdev group); see [http://developer.android.com/tools/device.html] usb:1-1 transport_id:7").unwrap();
+        assert!(device.state.starts_with("no permissions (missing udev rules?"));
+        assert!(device.state.ends_with("device.html]"));
+        assert_eq!(device.transport.as_deref(), Some("usb"));
+    }
+
+    #[test]
+    fn line_without_state_is_skipped() {
+        assert_eq!(parse_device_line("emulator-5554"), None);
+        assert_eq!(parse_device_line(""), None);
+    }
+}
+
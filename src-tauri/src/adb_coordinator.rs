@@ -0,0 +1,274 @@
+//! 协调对共享 adb server 的操作：针对单台设备的传输（安装、拉取等）可以并发，
+//! 重启 server 等全局操作需要独占，会先等待进行中的传输结束，避免中断其他设备上的安装。
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::adb::{self, DeviceList};
+
+/// 等待进行中传输结束的默认时间
+const DEFAULT_SERVER_WAIT: Duration = Duration::from_secs(120);
+const ADB_NOT_FOUND: &str = "未找到 adb，请安装 Android SDK Platform-Tools 并将其目录加入 PATH";
+
+/// 一个进行中的设备传输
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct Transfer {
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub id: u64,
+    pub device_id: String,
+    /// 如 `install`、`pull`、`rollback`
+    pub operation: String,
+    /// 开始时间（Unix 时间戳，秒）
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub started_at: u64,
+}
+
+/// adb 当前的活动
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct AdbActivity {
+    pub transfers: Vec<Transfer>,
+    /// 正在等待或执行的全局操作
+    pub server_operation: Option<String>,
+}
+
+#[derive(Default)]
+struct State {
+    transfers: BTreeMap<u64, Transfer>,
+    server_operation: Option<String>,
+    next_id: u64,
+}
+
+/// adb 操作协调器（托管状态）
+#[derive(Default)]
+pub struct AdbCoordinator {
+    state: Mutex<State>,
+    changed: Condvar,
+}
+
+/// 传输登记，离开作用域时注销
+pub struct TransferGuard<'a> {
+    coordinator: &'a AdbCoordinator,
+    id: u64,
+}
+
+impl Drop for TransferGuard<'_> {
+    fn drop(&mut self) {
+        self.coordinator.state.lock().unwrap().transfers.remove(&self.id);
+        self.coordinator.changed.notify_all();
+    }
+}
+
+/// 全局操作的独占权，离开作用域时释放
+pub struct ServerGuard<'a> {
+    coordinator: &'a AdbCoordinator,
+    /// 强制执行时被中断的传输
+    pub interrupted: Vec<Transfer>,
+}
+
+impl Drop for ServerGuard<'_> {
+    fn drop(&mut self) {
+        self.coordinator.state.lock().unwrap().server_operation = None;
+        self.coordinator.changed.notify_all();
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+impl AdbCoordinator {
+    /// 登记一个设备传输；有全局操作进行时先等待其完成
+    pub fn begin_transfer(&self, device_id: &str, operation: &str) -> TransferGuard<'_> {
+        let mut state = self.changed.wait_while(self.state.lock().unwrap(), |s| s.server_operation.is_some()).unwrap();
+        state.next_id += 1;
+        let id = state.next_id;
+        state.transfers.insert(id, Transfer { id, device_id: device_id.to_string(), operation: operation.to_string(), started_at: now() });
+        TransferGuard { coordinator: self, id }
+    }
+
+    /// 取得全局操作的独占权：阻止新的传输，并等待进行中的传输结束。
+    /// 超时后 `force` 为真则继续并返回会被中断的传输，否则放弃并返回仍在进行的传输
+    pub fn begin_server_operation(&self, operation: &str, timeout: Duration, force: bool) -> Result<ServerGuard<'_>, Vec<Transfer>> {
+        let mut state = self.changed.wait_while(self.state.lock().unwrap(), |s| s.server_operation.is_some()).unwrap();
+        state.server_operation = Some(operation.to_string());
+        let deadline = Instant::now() + timeout;
+        while !state.transfers.is_empty() {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            state = self.changed.wait_timeout(state, remaining).unwrap().0;
+        }
+        let in_flight: Vec<Transfer> = state.transfers.values().cloned().collect();
+        if !in_flight.is_empty() && !force {
+            state.server_operation = None;
+            drop(state);
+            self.changed.notify_all();
+            return Err(in_flight);
+        }
+        Ok(ServerGuard { coordinator: self, interrupted: in_flight })
+    }
+
+    pub fn activity(&self) -> AdbActivity {
+        let state = self.state.lock().unwrap();
+        AdbActivity { transfers: state.transfers.values().cloned().collect(), server_operation: state.server_operation.clone() }
+    }
+}
+
+fn describe(transfers: &[Transfer]) -> String {
+    transfers.iter().map(|t| format!("{}（{}）", t.device_id, t.operation)).collect::<Vec<_>>().join("、")
+}
+
+fn run_adb(args: &[&str]) -> Result<(), String> {
+    let output = adb::command().args(args).output().map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => ADB_NOT_FOUND.to_string(),
+        _ => format!("运行 adb {} 失败: {}", args.join(" "), e),
+    })?;
+    if !output.status.success() {
+        return Err(format!("adb {} 失败: {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(())
+}
+
+/// adb server 重启结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct ServerRestart {
+    /// 强制重启时被中断的传输
+    pub interrupted: Vec<Transfer>,
+    /// 重启后的设备列表，界面可直接刷新
+    pub devices: DeviceList,
+}
+
+/// 经协调器重启 adb server：等待进行中的传输结束，`force` 为真时超时后强制重启。
+/// 重启后等待设备重新连上，返回被中断的传输与新的设备列表
+pub fn restart_server(coordinator: &AdbCoordinator, timeout: Duration, force: bool) -> Result<ServerRestart, String> {
+    let guard = coordinator
+        .begin_server_operation("restart-server", timeout, force)
+        .map_err(|busy| format!("以下设备仍在传输，未重启 adb server: {}", describe(&busy)))?;
+    // server 未运行时 kill-server 会失败，此时直接启动
+    if let Err(e) = run_adb(&["kill-server"]) {
+        if e == ADB_NOT_FOUND {
+            return Err(e);
+        }
+    }
+    run_adb(&["start-server"])?;
+    let interrupted = guard.interrupted.clone();
+    drop(guard);
+    let devices = adb::wait_for_devices()?;
+    Ok(ServerRestart { interrupted, devices })
+}
+
+/// 当前的设备传输与全局操作
+#[tauri::command]
+pub fn get_adb_activity(coordinator: tauri::State<'_, AdbCoordinator>) -> AdbActivity {
+    coordinator.activity()
+}
+
+/// 重启 adb server（设备一直离线或端口被其他工具占用时使用）；有传输进行时先等待，
+/// `force` 为真时超时后强制重启。返回重启后的设备列表
+#[tauri::command]
+pub async fn restart_adb_server(
+    app: tauri::AppHandle,
+    coordinator: tauri::State<'_, AdbCoordinator>,
+    timeout_secs: Option<u64>,
+    force: Option<bool>,
+) -> Result<ServerRestart, String> {
+    let timeout = timeout_secs.map_or(DEFAULT_SERVER_WAIT, Duration::from_secs);
+    let force = force.unwrap_or(false);
+    let result = restart_server(&coordinator, timeout, force);
+    let params = serde_json::json!({ "force": force, "timeout_secs": timeout.as_secs() });
+    crate::audit::record(&app, "restart_adb_server", params, &result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::thread;
+
+    #[test]
+    fn transfers_on_several_devices_run_concurrently() {
+        let coordinator = AdbCoordinator::default();
+        let a = coordinator.begin_transfer("dev-a", "install");
+        let b = coordinator.begin_transfer("dev-b", "pull");
+        let activity = coordinator.activity();
+        assert_eq!(activity.transfers.iter().map(|t| t.device_id.as_str()).collect::<Vec<_>>(), ["dev-a", "dev-b"]);
+        assert_ne!(activity.transfers[0].id, activity.transfers[1].id);
+        drop(a);
+        drop(b);
+        assert!(coordinator.activity().transfers.is_empty());
+    }
+
+    #[test]
+    fn server_operation_waits_for_transfers_to_finish() {
+        let coordinator = AdbCoordinator::default();
+        let transfer = coordinator.begin_transfer("dev-a", "install");
+        thread::scope(|s| {
+            s.spawn(move || {
+                thread::sleep(Duration::from_millis(100));
+                drop(transfer);
+            });
+            let started = Instant::now();
+            let guard = coordinator.begin_server_operation("restart-server", Duration::from_secs(10), false).unwrap();
+            assert!(started.elapsed() >= Duration::from_millis(100));
+            assert!(guard.interrupted.is_empty());
+            assert_eq!(coordinator.activity().server_operation.as_deref(), Some("restart-server"));
+        });
+        assert_eq!(coordinator.activity().server_operation, None);
+    }
+
+    #[test]
+    fn busy_transfers_abort_the_server_operation_unless_forced() {
+        let coordinator = AdbCoordinator::default();
+        let _transfer = coordinator.begin_transfer("dev-a", "install");
+        let busy = coordinator.begin_server_operation("restart-server", Duration::from_millis(20), false).err().unwrap();
+        assert_eq!(busy.len(), 1);
+        assert_eq!(busy[0].operation, "install");
+        assert_eq!(describe(&busy), "dev-a（install）");
+        assert_eq!(coordinator.activity().server_operation, None);
+
+        let guard = coordinator.begin_server_operation("restart-server", Duration::from_millis(20), true).unwrap();
+        assert_eq!(guard.interrupted, busy);
+    }
+
+    #[test]
+    fn new_transfers_wait_for_the_server_operation() {
+        let coordinator = AdbCoordinator::default();
+        let guard = coordinator.begin_server_operation("restart-server", Duration::from_secs(1), false).unwrap();
+        let (tx, rx) = mpsc::channel();
+        thread::scope(|s| {
+            s.spawn(|| {
+                let _transfer = coordinator.begin_transfer("dev-a", "pull");
+                tx.send(coordinator.activity()).unwrap();
+            });
+            assert!(rx.recv_timeout(Duration::from_millis(100)).is_err());
+            drop(guard);
+            let activity = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+            assert_eq!(activity.server_operation, None);
+            assert_eq!(activity.transfers.len(), 1);
+        });
+    }
+
+    #[test]
+    fn server_operations_are_exclusive() {
+        let coordinator = AdbCoordinator::default();
+        let first = coordinator.begin_server_operation("restart-server", Duration::from_secs(1), false).unwrap();
+        let (tx, rx) = mpsc::channel();
+        thread::scope(|s| {
+            s.spawn(|| {
+                let _second = coordinator.begin_server_operation("reconnect", Duration::from_secs(1), false).unwrap();
+                tx.send(coordinator.activity().server_operation).unwrap();
+            });
+            assert!(rx.recv_timeout(Duration::from_millis(100)).is_err());
+            drop(first);
+            assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap().as_deref(), Some("reconnect"));
+        });
+    }
+}
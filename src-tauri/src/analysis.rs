@@ -0,0 +1,631 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+use crate::input_path::{self, Expect};
+use crate::signing::PipelineError;
+
+/// 资源目录中出现的语言 / 地区限定符
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct LocaleInfo {
+    /// 目录名中的原始限定符，如 `zh-rCN`、`b+sr+Latn`
+    pub qualifier: String,
+    pub language: String,
+    pub region: Option<String>,
+    pub resource_file_count: u32,
+}
+
+/// 不是语言代码的 2~3 位小写限定符
+const NON_LOCALE_QUALIFIERS: &[&str] = &["car", "any", "tv"];
+
+fn is_language(token: &str) -> bool {
+    (2..=3).contains(&token.len())
+        && token.chars().all(|c| c.is_ascii_lowercase())
+        && !NON_LOCALE_QUALIFIERS.contains(&token)
+}
+
+fn is_region(token: &str) -> bool {
+    let Some(code) = token.strip_prefix('r') else {
+        return false;
+    };
+    (code.len() == 2 && code.chars().all(|c| c.is_ascii_uppercase()))
+        || (code.len() == 3 && code.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// 从资源目录名（如 `values-zh-rCN-v21`）中解析语言限定符，
+/// 返回 (限定符, 语言, 地区)
+pub fn parse_locale_dir(dir_name: &str) -> Option<(String, String, Option<String>)> {
+    let mut tokens = dir_name.split('-').skip(1).peekable();
+
+    // mcc / mnc 排在语言之前
+    while let Some(token) = tokens.peek() {
+        if token.starts_with("mcc") || token.starts_with("mnc") {
+            tokens.next();
+        } else {
+            break;
+        }
+    }
+
+    let first = tokens.next()?;
+
+    // BCP 47 格式：b+sr+Latn、b+es+419
+    if let Some(tag) = first.strip_prefix("b+") {
+        let mut parts = tag.split('+');
+        let language = parts.next().filter(|l| !l.is_empty())?.to_string();
+        let rest: Vec<&str> = parts.collect();
+        let region = rest
+            .iter()
+            .find(|p| (p.len() == 2 && p.chars().all(|c| c.is_ascii_alphabetic())) || (p.len() == 3 && p.chars().all(|c| c.is_ascii_digit())))
+            .map(|p| p.to_ascii_uppercase());
+        return Some((first.to_string(), language, region));
+    }
+
+    if !is_language(first) {
+        return None;
+    }
+    match tokens.next() {
+        Some(next) if is_region(next) => Some((format!("{}-{}", first, next), first.to_string(), Some(next[1..].to_string()))),
+        _ => Some((first.to_string(), first.to_string(), None)),
+    }
+}
+
+fn collect_locales<'a>(entries: impl Iterator<Item = (&'a str, u32)>) -> Vec<LocaleInfo> {
+    let mut map: BTreeMap<String, LocaleInfo> = BTreeMap::new();
+    for (dir_name, files) in entries {
+        if let Some((qualifier, language, region)) = parse_locale_dir(dir_name) {
+            map.entry(qualifier.clone())
+                .or_insert(LocaleInfo { qualifier, language, region, resource_file_count: 0 })
+                .resource_file_count += files;
+        }
+    }
+    map.into_values().collect()
+}
+
+/// 读取 APK 中所有 `res/<类型>-<限定符>/` 下的文件，统计语言限定符。
+/// 编译后的 values 资源位于 resources.arsc 中，所以这里同时统计 drawable、raw 等目录。
+#[tauri::command]
+pub fn get_apk_locales(apk_path: String) -> Result<Vec<LocaleInfo>, PipelineError> {
+    let apk_path = input_path::arg(&apk_path, Expect::File)?;
+    let archive = zip::ZipArchive::new(fs::File::open(&apk_path)?)?;
+    let dirs: Vec<String> = archive
+        .file_names()
+        .filter_map(|name| {
+            let rest = name.strip_prefix("res/")?;
+            let (dir, file) = rest.split_once('/')?;
+            (!file.is_empty() && !file.contains('/')).then(|| dir.to_string())
+        })
+        .collect();
+    Ok(collect_locales(dirs.iter().map(|d| (d.as_str(), 1))))
+}
+
+/// 对已反编译的工作目录统计语言限定符
+#[tauri::command]
+pub fn get_apk_locales_from_work_dir(work_dir: String) -> Result<Vec<LocaleInfo>, PipelineError> {
+    let work_dir = input_path::arg(&work_dir, Expect::Dir)?;
+    let res_dir = Path::new(&work_dir).join("res");
+    let mut dirs: Vec<(String, u32)> = Vec::new();
+    for entry in fs::read_dir(&res_dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        let count = fs::read_dir(entry.path())?
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().map(|t| t.is_file()).unwrap_or(false))
+            .count() as u32;
+        dirs.push((entry.file_name().to_string_lossy().to_string(), count));
+    }
+    Ok(collect_locales(dirs.iter().map(|(d, c)| (d.as_str(), *c))))
+}
+
+/// 目标市场推断结果
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct MarketDetection {
+    /// 按置信度从高到低排列的市场代码，如 `cn`、`us`
+    pub detected_markets: Vec<String>,
+    pub confidence: HashMap<String, f32>,
+    pub evidence: Vec<String>,
+}
+
+/// 已知 SDK 的类路径与所指向的市场
+const SDK_SIGNATURES: &[(&str, &str, &str)] = &[
+    ("Lcom/alipay/", "cn", "支付宝 SDK"),
+    ("Lcom/tencent/mm/opensdk/", "cn", "微信 SDK"),
+    ("Lcom/tencent/mm/sdk/", "cn", "微信 SDK"),
+    ("Lcom/umeng/", "cn", "友盟 SDK"),
+    ("Lcom/baidu/", "cn", "百度 SDK"),
+    ("Lcom/paypal/", "us", "PayPal SDK"),
+    ("Lcom/stripe/", "us", "Stripe SDK"),
+    ("Lcom/facebook/", "us", "Facebook SDK"),
+    ("Lcom/linecorp/", "jp", "LINE SDK"),
+    ("Lcom/kakao/", "kr", "Kakao SDK"),
+];
+
+/// 字符串资源中的地区相关关键词
+const STRING_TERMS: &[(&str, &str)] = &[
+    ("人民币", "cn"),
+    ("¥", "cn"),
+    ("邮政", "cn"),
+    ("快递", "cn"),
+    ("USD", "us"),
+    ("€", "eu"),
+    ("円", "jp"),
+    ("₩", "kr"),
+];
+
+/// 语言到市场的映射
+fn market_for_language(language: &str, region: Option<&str>) -> Option<&'static str> {
+    match (language, region) {
+        ("zh", Some("TW")) => Some("tw"),
+        ("zh", Some("HK")) => Some("hk"),
+        ("zh", _) => Some("cn"),
+        ("ja", _) => Some("jp"),
+        ("ko", _) => Some("kr"),
+        ("de" | "fr" | "it" | "es" | "nl" | "pl", _) => Some("eu"),
+        ("en", Some("US")) => Some("us"),
+        _ => None,
+    }
+}
+
+fn dex_files(work_dir: &Path) -> Vec<std::path::PathBuf> {
+    fs::read_dir(work_dir)
+        .map(|rd| {
+            rd.filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.extension().map(|e| e == "dex").unwrap_or(false))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub fn contains_bytes(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|w| w == needle)
+}
+
+/// 根据语言目录、字符串资源、内置 SDK 与电话号码格式推断应用的目标市场
+#[tauri::command]
+pub fn detect_app_target_market(work_dir: String) -> Result<MarketDetection, PipelineError> {
+    let work_dir = input_path::arg(&work_dir, Expect::Dir)?;
+    let root = Path::new(&work_dir);
+    let mut scores: HashMap<String, f32> = HashMap::new();
+    let mut evidence = Vec::new();
+    let mut add = |market: &str, weight: f32, reason: String| {
+        *scores.entry(market.to_string()).or_insert(0.0) += weight;
+        evidence.push(reason);
+    };
+
+    // 语言目录
+    for locale in get_apk_locales_from_work_dir(work_dir.clone()).unwrap_or_default() {
+        if let Some(market) = market_for_language(&locale.language, locale.region.as_deref()) {
+            add(market, 0.5, format!("包含语言目录 values-{}", locale.qualifier));
+        }
+    }
+
+    // 默认字符串资源
+    let strings = fs::read_to_string(root.join("res").join("values").join("strings.xml")).unwrap_or_default();
+    if strings.chars().any(|c| ('\u{4e00}'..='\u{9fff}').contains(&c)) {
+        add("cn", 1.0, "默认字符串资源中包含中文".to_string());
+    }
+    for &(term, market) in STRING_TERMS {
+        if strings.contains(term) {
+            add(market, 0.3, format!("字符串资源中出现 \"{}\"", term));
+        }
+    }
+    let cn_phone = regex::Regex::new(r"(?:\+86[- ]?)?\b1[3-9]\d{9}\b").unwrap();
+    let us_phone = regex::Regex::new(r"\(\d{3}\) ?\d{3}-\d{4}|\+1[- ]\d{3}[- ]\d{3}[- ]\d{4}").unwrap();
+    if cn_phone.is_match(&strings) {
+        add("cn", 0.5, "字符串资源中包含中国大陆手机号码".to_string());
+    }
+    if us_phone.is_match(&strings) {
+        add("us", 0.5, "字符串资源中包含北美电话号码".to_string());
+    }
+    // 只认金额形式的 `$`；格式化占位符（`%1$s`、`%1$10s`）的 `$` 前面是数字
+    let dollar_amount = regex::Regex::new(r"(?:^|[^0-9])(?:US)?\$\s?\d+(?:[.,]\d{1,2})?").unwrap();
+    if dollar_amount.is_match(&strings) {
+        add("us", 0.3, "字符串资源中出现美元金额".to_string());
+    }
+
+    // 已知 SDK：smali 目录或 dex 字节中的类路径
+    let dex_data: Vec<Vec<u8>> = dex_files(root).iter().filter_map(|p| fs::read(p).ok()).collect();
+    for &(class_prefix, market, name) in SDK_SIGNATURES {
+        let smali_path = class_prefix.trim_start_matches('L').trim_end_matches('/');
+        let in_smali = fs::read_dir(root)
+            .map(|rd| {
+                rd.filter_map(|e| e.ok())
+                    .filter(|e| e.file_name().to_string_lossy().starts_with("smali"))
+                    .any(|e| e.path().join(smali_path).exists())
+            })
+            .unwrap_or(false);
+        let in_dex = dex_data.iter().any(|d| contains_bytes(d, class_prefix.as_bytes()));
+        if in_smali || in_dex {
+            add(market, 2.0, format!("检测到{}", name));
+        }
+    }
+
+    let total: f32 = scores.values().sum();
+    let confidence: HashMap<String, f32> = scores
+        .into_iter()
+        .map(|(market, score)| (market, if total > 0.0 { score / total } else { 0.0 }))
+        .collect();
+    let mut detected_markets: Vec<String> = confidence.keys().cloned().collect();
+    detected_markets.sort_by(|a, b| confidence[b].partial_cmp(&confidence[a]).unwrap_or(std::cmp::Ordering::Equal));
+
+    Ok(MarketDetection { detected_markets, confidence, evidence })
+}
+
+/// 不借助外部工具即可得到的 APK 复杂度指标
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct ComplexityMetrics {
+    pub dex_method_count: u32,
+    pub dex_class_count: u32,
+    pub string_count: u32,
+    pub resource_count: u32,
+    pub native_methods: u32,
+    pub multidex: bool,
+}
+
+fn le_u32(data: &[u8], pos: usize) -> Option<u32> {
+    data.get(pos..pos + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn le_u16(data: &[u8], pos: usize) -> Option<u16> {
+    data.get(pos..pos + 2).map(|b| u16::from_le_bytes([b[0], b[1]]))
+}
+
+/// 从 DEX 文件头读取 (字符串数, 方法引用数, 类定义数)
+pub fn dex_header_counts(data: &[u8]) -> Option<(u32, u32, u32)> {
+    if !data.starts_with(b"dex\n") {
+        return None;
+    }
+    Some((le_u32(data, 0x38)?, le_u32(data, 0x58)?, le_u32(data, 0x60)?))
+}
+
+/// 累加 resources.arsc 中所有 ResTable_typeSpec 的条目数，即资源总数
+pub fn arsc_entry_count(data: &[u8]) -> Option<u32> {
+    const RES_TABLE_TYPE: u16 = 0x0002;
+    const RES_TABLE_PACKAGE_TYPE: u16 = 0x0200;
+    const RES_TABLE_TYPE_SPEC_TYPE: u16 = 0x0202;
+
+    if le_u16(data, 0)? != RES_TABLE_TYPE {
+        return None;
+    }
+    let mut total = 0u32;
+    let mut pos = le_u16(data, 2)? as usize;
+    while pos + 8 <= data.len() {
+        let chunk_type = le_u16(data, pos)?;
+        let header_size = le_u16(data, pos + 2)? as usize;
+        let chunk_size = le_u32(data, pos + 4)? as usize;
+        if chunk_size < 8 {
+            break;
+        }
+        if chunk_type == RES_TABLE_PACKAGE_TYPE {
+            let end = (pos + chunk_size).min(data.len());
+            let mut inner = pos + header_size;
+            while inner + 8 <= end {
+                let inner_type = le_u16(data, inner)?;
+                let inner_size = le_u32(data, inner + 4)? as usize;
+                if inner_size < 8 {
+                    break;
+                }
+                if inner_type == RES_TABLE_TYPE_SPEC_TYPE {
+                    total += le_u32(data, inner + 12)?;
+                }
+                inner += inner_size;
+            }
+        }
+        pos += chunk_size;
+    }
+    Some(total)
+}
+
+/// 读取字符串池中的长度前缀：UTF-8 为 1~2 字节，UTF-16 为 1~2 个 u16；返回 (长度, 前缀字节数)
+fn pool_len(data: &[u8], pos: usize, utf8: bool) -> Option<(usize, usize)> {
+    if utf8 {
+        let first = *data.get(pos)? as usize;
+        if first & 0x80 != 0 {
+            Some((((first & 0x7f) << 8) | *data.get(pos + 1)? as usize, 2))
+        } else {
+            Some((first, 1))
+        }
+    } else {
+        let first = le_u16(data, pos)? as usize;
+        if first & 0x8000 != 0 {
+            Some((((first & 0x7fff) << 16) | le_u16(data, pos + 2)? as usize, 4))
+        } else {
+            Some((first, 2))
+        }
+    }
+}
+
+/// 解析一个 ResStringPool 块中的所有字符串
+fn string_pool(data: &[u8], chunk: usize) -> Option<Vec<String>> {
+    const UTF8_FLAG: u32 = 0x100;
+    let header_size = le_u16(data, chunk + 2)? as usize;
+    let count = le_u32(data, chunk + 8)? as usize;
+    let utf8 = le_u32(data, chunk + 16)? & UTF8_FLAG != 0;
+    let strings_start = chunk + le_u32(data, chunk + 20)? as usize;
+    let mut strings = Vec::with_capacity(count.min(data.len() / 4));
+    for i in 0..count {
+        let mut pos = strings_start + le_u32(data, chunk + header_size + i * 4)? as usize;
+        if utf8 {
+            // UTF-8 字符串先记录字符数，再记录字节数
+            pos += pool_len(data, pos, true)?.1;
+            let (bytes, prefix) = pool_len(data, pos, true)?;
+            strings.push(String::from_utf8_lossy(data.get(pos + prefix..pos + prefix + bytes)?).into_owned());
+        } else {
+            let (units, prefix) = pool_len(data, pos, false)?;
+            let raw = data.get(pos + prefix..pos + prefix + units * 2)?;
+            let utf16: Vec<u16> = raw.chunks_exact(2).map(|b| u16::from_le_bytes([b[0], b[1]])).collect();
+            strings.push(String::from_utf16_lossy(&utf16));
+        }
+    }
+    Some(strings)
+}
+
+/// resources.arsc 中所有字符串池的内容：全局字符串池以及各资源包的类型名、资源名字符串池
+pub fn arsc_strings(data: &[u8]) -> Option<Vec<String>> {
+    const RES_STRING_POOL_TYPE: u16 = 0x0001;
+    const RES_TABLE_TYPE: u16 = 0x0002;
+    const RES_TABLE_PACKAGE_TYPE: u16 = 0x0200;
+
+    if le_u16(data, 0)? != RES_TABLE_TYPE {
+        return None;
+    }
+    let mut strings = Vec::new();
+    let mut pos = le_u16(data, 2)? as usize;
+    while pos + 8 <= data.len() {
+        let chunk_type = le_u16(data, pos)?;
+        let header_size = le_u16(data, pos + 2)? as usize;
+        let chunk_size = le_u32(data, pos + 4)? as usize;
+        if chunk_size < 8 {
+            break;
+        }
+        if chunk_type == RES_STRING_POOL_TYPE {
+            strings.extend(string_pool(data, pos)?);
+        } else if chunk_type == RES_TABLE_PACKAGE_TYPE {
+            let end = (pos + chunk_size).min(data.len());
+            let mut inner = pos + header_size;
+            while inner + 8 <= end {
+                let inner_type = le_u16(data, inner)?;
+                let inner_size = le_u32(data, inner + 4)? as usize;
+                if inner_size < 8 {
+                    break;
+                }
+                if inner_type == RES_STRING_POOL_TYPE {
+                    strings.extend(string_pool(data, inner)?);
+                }
+                inner += inner_size;
+            }
+        }
+        pos += chunk_size;
+    }
+    Some(strings)
+}
+
+/// 统计原生库中导出的 JNI 符号（`Java_` 开头）数量
+fn count_jni_symbols(data: &[u8]) -> usize {
+    let mut symbols = std::collections::HashSet::new();
+    let mut i = 0;
+    while let Some(offset) = data[i..].windows(5).position(|w| w == b"Java_") {
+        let start = i + offset;
+        let end = data[start..]
+            .iter()
+            .position(|b| !(b.is_ascii_alphanumeric() || *b == b'_'))
+            .map(|p| start + p)
+            .unwrap_or(data.len());
+        symbols.insert(&data[start..end]);
+        i = end;
+    }
+    symbols.len()
+}
+
+/// 直接读取 DEX 头与 resources.arsc 头，快速统计 APK 复杂度
+#[tauri::command]
+pub fn get_apk_complexity_metrics(apk_path: String) -> Result<ComplexityMetrics, PipelineError> {
+    let apk_path = input_path::arg(&apk_path, Expect::File)?;
+    use std::io::Read;
+
+    let mut archive = zip::ZipArchive::new(fs::File::open(&apk_path)?)?;
+    let mut metrics = ComplexityMetrics::default();
+    let mut dex_count = 0;
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        let name = entry.name().to_string();
+        let is_dex = name.starts_with("classes") && name.ends_with(".dex") && !name.contains('/');
+        let is_native = name.starts_with("lib/") && name.ends_with(".so");
+        if !(is_dex || is_native || name == "resources.arsc") {
+            continue;
+        }
+
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data)?;
+        if is_dex {
+            if let Some((strings, methods, classes)) = dex_header_counts(&data) {
+                dex_count += 1;
+                metrics.string_count += strings;
+                metrics.dex_method_count += methods;
+                metrics.dex_class_count += classes;
+            }
+        } else if is_native {
+            metrics.native_methods += count_jni_symbols(&data) as u32;
+        } else {
+            metrics.resource_count = arsc_entry_count(&data).unwrap_or(0);
+        }
+    }
+    metrics.multidex = dex_count > 1;
+    Ok(metrics)
+}
+
+/// 统计已反编译目录中声明为 native 的 smali 方法数
+#[tauri::command]
+pub fn count_native_methods_in_work_dir(work_dir: String) -> Result<u32, PipelineError> {
+    let work_dir = input_path::arg(&work_dir, Expect::Dir)?;
+    let re = regex::Regex::new(r"(?m)^\.method\b[^\n]*\bnative\b").unwrap();
+    let mut count = 0;
+    for entry in fs::read_dir(&work_dir)? {
+        let entry = entry?;
+        if !entry.file_name().to_string_lossy().starts_with("smali") {
+            continue;
+        }
+        for file in walkdir::WalkDir::new(entry.path()).into_iter().filter_map(|e| e.ok()) {
+            if file.path().extension().map(|e| e == "smali").unwrap_or(false) {
+                let content = fs::read_to_string(file.path())?;
+                count += re.find_iter(&content).count() as u32;
+            }
+        }
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn work_dir_with_strings(strings: &str) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let values = dir.path().join("res").join("values");
+        fs::create_dir_all(&values).unwrap();
+        fs::write(values.join("strings.xml"), strings).unwrap();
+        dir
+    }
+
+    /// 只含给定条目的 APK（内容为空），用于按文件名统计的函数
+    fn apk_with_entries(dir: &tempfile::TempDir, names: &[&str]) -> String {
+        let path = dir.path().join("fixture.apk");
+        let mut writer = zip::ZipWriter::new(fs::File::create(&path).unwrap());
+        for name in names {
+            writer.start_file(*name, zip::write::SimpleFileOptions::default()).unwrap();
+        }
+        writer.finish().unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    fn detect(dir: &tempfile::TempDir) -> MarketDetection {
+        detect_app_target_market(dir.path().to_string_lossy().to_string()).unwrap()
+    }
+
+    #[test]
+    fn format_placeholders_are_not_dollar_amounts() {
+        let dir = work_dir_with_strings(r#"<resources><string name="greeting">Hello %1$s, you have %2$d messages, id %3$10s</string></resources>"#);
+        assert!(!detect(&dir).confidence.contains_key("us"));
+    }
+
+    #[test]
+    fn dollar_prices_point_to_us() {
+        let dir = work_dir_with_strings(r#"<resources><string name="price">Upgrade for $4.99 per month</string></resources>"#);
+        let detection = detect(&dir);
+        assert_eq!(detection.detected_markets, ["us"]);
+        assert!(detection.evidence.iter().any(|e| e.contains("美元")));
+    }
+
+    #[test]
+    fn wechat_sdk_raises_cn() {
+        let dir = work_dir_with_strings(r#"<resources><string name="price">$4.99</string></resources>"#);
+        fs::create_dir_all(dir.path().join("smali").join("com/tencent/mm/opensdk")).unwrap();
+        let detection = detect(&dir);
+        assert_eq!(detection.detected_markets.first().map(String::as_str), Some("cn"));
+        assert!(detection.confidence["cn"] > detection.confidence["us"]);
+    }
+
+    #[test]
+    fn locale_dir_forms_are_parsed() {
+        let parsed = |dir: &str| parse_locale_dir(dir).map(|(q, l, r)| (q, l, r.unwrap_or_default()));
+        let locale = |q: &str, l: &str, r: &str| Some((q.to_string(), l.to_string(), r.to_string()));
+        assert_eq!(parsed("values-fr"), locale("fr", "fr", ""));
+        assert_eq!(parsed("values-zh-rCN-v21"), locale("zh-rCN", "zh", "CN"));
+        assert_eq!(parsed("values-es-r419"), locale("es-r419", "es", "419"));
+        assert_eq!(parsed("values-mcc460-mnc01-zh-rTW"), locale("zh-rTW", "zh", "TW"));
+        assert_eq!(parsed("values-b+sr+Latn"), locale("b+sr+Latn", "sr", ""));
+        assert_eq!(parsed("values-b+es+419"), locale("b+es+419", "es", "419"));
+        for not_locale in ["values", "values-v21", "drawable-hdpi", "values-night", "values-car", "values-sw600dp", "values-b+"] {
+            assert_eq!(parse_locale_dir(not_locale), None, "{}", not_locale);
+        }
+    }
+
+    #[test]
+    fn apk_locales_are_collected_from_resource_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let apk = apk_with_entries(
+            &dir,
+            &[
+                "AndroidManifest.xml",
+                "resources.arsc",
+                "res/drawable-hdpi/icon.png",
+                "res/values-v21/styles.xml",
+                "res/drawable-fr/banner.png",
+                "res/raw-fr/intro.ogg",
+                "res/drawable-zh-rCN/banner.png",
+                "res/drawable-zh-rCN-xhdpi/banner.png",
+                "res/raw-b+sr+Latn/intro.ogg",
+                "res/drawable-ja/banner.png",
+                "res/layout-de-land/main.xml",
+                "res/drawable-es-r419/banner.png",
+                "res/raw-ja/nested/ignored.ogg",
+            ],
+        );
+        let locales = get_apk_locales(apk).unwrap();
+        let summary: Vec<(&str, &str, Option<&str>, u32)> =
+            locales.iter().map(|l| (l.qualifier.as_str(), l.language.as_str(), l.region.as_deref(), l.resource_file_count)).collect();
+        assert_eq!(
+            summary,
+            [
+                ("b+sr+Latn", "sr", None, 1),
+                ("de", "de", None, 1),
+                ("es-r419", "es", Some("419"), 1),
+                ("fr", "fr", None, 2),
+                ("ja", "ja", None, 1),
+                ("zh-rCN", "zh", Some("CN"), 2),
+            ]
+        );
+    }
+
+    const COMPLEXITY_APK: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/complexity.apk");
+
+    #[test]
+    fn complexity_metrics_of_the_fixture() {
+        let metrics = get_apk_complexity_metrics(COMPLEXITY_APK.to_string()).unwrap();
+        // 两个 DEX 各有 4 个字符串、1 个方法引用（Object.<init>）、1 个类定义
+        assert_eq!((metrics.string_count, metrics.dex_method_count, metrics.dex_class_count), (8, 2, 2));
+        assert!(metrics.multidex);
+        // libfixture.so 导出 2 个不同的 JNI 符号（JNI_OnLoad 不计）
+        assert_eq!(metrics.native_methods, 2);
+        // drawable 1 项 + string 4 项
+        assert_eq!(metrics.resource_count, 5);
+    }
+
+    #[test]
+    fn arsc_string_pools_of_the_fixture() {
+        use std::io::Read;
+        let mut archive = zip::ZipArchive::new(fs::File::open(COMPLEXITY_APK).unwrap()).unwrap();
+        let mut data = Vec::new();
+        archive.by_name("resources.arsc").unwrap().read_to_end(&mut data).unwrap();
+        let strings = arsc_strings(&data).unwrap();
+        for expected in ["Fixture", "drawable", "string", "app_name", "title"] {
+            assert!(strings.iter().any(|s| s == expected), "{}", expected);
+        }
+        assert_eq!(arsc_entry_count(b"not an arsc"), None);
+    }
+
+    #[test]
+    fn native_methods_are_counted_across_smali_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |rel: &str, content: &str| {
+            let path = dir.path().join(rel);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        };
+        write(
+            "smali/com/apkdisguise/fixture/Native.smali",
+            ".class public Lcom/apkdisguise/fixture/Native;\n.method public static native init()V\n.end method\n.method private native run(I)I\n.end method\n.method public static main()V\n    return-void\n.end method\n",
+        );
+        write("smali_classes2/com/apkdisguise/fixture/Other.smali", ".method public final native declared-synchronized poll()J\n.end method\n# .method native commented\n");
+        write("original/Native.smali", ".method public native ignored()V\n.end method\n");
+        assert_eq!(count_native_methods_in_work_dir(dir.path().to_string_lossy().to_string()).unwrap(), 3);
+    }
+}
+
@@ -0,0 +1,86 @@
+//! 已安装应用的真实名称。设备上没有直接列出应用名的命令，需要拉取 base APK 后用 `aapt2 dump badging`
+//! 读取 `application-label`。拉取较慢，结果（包括没有应用名的）按设备缓存在会话内，重新打开应用列表不会再次执行。
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+
+use tauri::Manager;
+
+use crate::adb_coordinator::AdbCoordinator;
+use crate::device_cache::{self, DeviceCache};
+use crate::input_path;
+use crate::io_pool::{IoPool, Priority};
+use crate::{adb, device_files, pm};
+
+/// 从 `aapt2 dump badging` 输出中取应用名：优先 `application-label-<locale>`，其次默认的 `application-label`
+pub fn parse_badging_label(stdout: &str, locale: Option<&str>) -> Option<String> {
+    let label = |key: &str| {
+        stdout
+            .lines()
+            .find_map(|line| line.strip_prefix(key)?.strip_prefix(":'")?.strip_suffix('\'').map(str::to_string))
+            .filter(|l| !l.is_empty())
+    };
+    locale.and_then(|locale| label(&format!("application-label-{}", locale))).or_else(|| label("application-label"))
+}
+
+/// 拉取设备上的 base APK 并读取应用名
+fn resolve_label(device_id: &str, package: &str, aapt2_path: &str, locale: Option<&str>) -> Result<Option<String>, String> {
+    let remote = device_files::parse_pm_path(&adb::shell(device_id, &["pm", "path", package])?)
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("设备上未安装 {}", package))?;
+    let dir = tempfile::tempdir().map_err(|e| e.to_string())?;
+    let local = dir.path().join("base.apk");
+    let output = adb::command().args(["-s", device_id, "pull", &remote]).arg(&local).output().map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(format!("拉取 {} 失败: {}", remote, String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(badging_label(aapt2_path, &local, locale))
+}
+
+fn badging_label(aapt2_path: &str, apk: &Path, locale: Option<&str>) -> Option<String> {
+    let output = Command::new(aapt2_path).args(["dump", "badging"]).arg(apk).output().ok()?;
+    parse_badging_label(&String::from_utf8_lossy(&output.stdout), locale)
+}
+
+/// 获取应用的真实名称（如 `com.tencent.mm` → 微信）。只处理缓存中没有的包，
+/// 无法读取的包返回 null，界面继续显示由包名推断的名称。`locale` 如 `zh-CN`
+#[tauri::command]
+pub async fn get_app_labels(
+    app: tauri::AppHandle,
+    cache: tauri::State<'_, DeviceCache>,
+    pool: tauri::State<'_, IoPool>,
+    device_id: String,
+    packages: Vec<String>,
+    aapt2_path: String,
+    locale: Option<String>,
+) -> Result<HashMap<String, Option<String>>, String> {
+    let aapt2_path = input_path::tool_arg(&aapt2_path).map_err(|e| e.to_string())?;
+    let mut labels: HashMap<String, Option<String>> = cache.get(&device_id, device_cache::DATASET_APP_LABELS).unwrap_or_default();
+    let missing: Vec<String> = packages.iter().filter(|p| !labels.contains_key(*p) && pm::is_package_name(p)).cloned().collect();
+    if !missing.is_empty() {
+        let serial = device_id.clone();
+        let resolved = pool
+            .run(Priority::Background, "get_app_labels", move || {
+                let coordinator = app.state::<AdbCoordinator>();
+                let _transfer = coordinator.begin_transfer(&serial, "pull");
+                missing
+                    .into_iter()
+                    .map(|package| {
+                        let label = resolve_label(&serial, &package, &aapt2_path, locale.as_deref());
+                        (package, label)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .await?;
+        // 拉取失败（设备断开等）不缓存，下次打开时重试
+        for (package, label) in resolved {
+            if let Ok(label) = label {
+                labels.insert(package, label);
+            }
+        }
+        cache.put(&device_id, device_cache::DATASET_APP_LABELS, &labels);
+    }
+    Ok(packages.into_iter().map(|p| (p.clone(), labels.get(&p).cloned().flatten())).collect())
+}
@@ -0,0 +1,142 @@
+//! 流水线产物的路径规划。中间产物与输入、最终输出（或彼此之间）指向同一文件时，
+//! apksigner 会在读取输入的同时覆盖它，部分版本因此输出损坏的 APK。
+//! 规划时发现冲突就把中间产物改到工作目录下生成的文件名；签名总是先写到独立的临时文件，校验通过后再替换最终输出。
+
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// 按路径字面规范化（去掉 `.`、处理 `..`），不访问文件系统；
+/// Windows 与 macOS 的文件系统默认不区分大小写，比较时统一为小写
+fn normalized(path: &Path) -> String {
+    let mut parts: Vec<Component> = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if matches!(parts.last(), Some(Component::Normal(_))) => {
+                parts.pop();
+            }
+            other => parts.push(other),
+        }
+    }
+    let joined = parts.iter().collect::<PathBuf>().to_string_lossy().replace('\\', "/");
+    if cfg!(any(windows, target_os = "macos")) {
+        joined.to_lowercase()
+    } else {
+        joined
+    }
+}
+
+/// 两个路径是否指向同一个文件
+pub fn same_file(a: &Path, b: &Path) -> bool {
+    normalized(a) == normalized(b)
+}
+
+/// 规划中间产物的路径：与输入、最终输出或前面的中间产物相同的路径，改为 `stage_dir` 下按用途生成的文件名。
+/// 返回规划后的路径（顺序与 `intermediates` 相同）以及改动说明
+pub fn plan<const N: usize>(
+    input: &Path,
+    final_apk: &Path,
+    stage_dir: &Path,
+    intermediates: [(&str, PathBuf); N],
+) -> ([PathBuf; N], Vec<String>) {
+    let mut taken: Vec<PathBuf> = vec![input.to_path_buf(), final_apk.to_path_buf()];
+    let mut diversions = Vec::new();
+    let planned = intermediates.map(|(role, path)| {
+        let mut planned = path.clone();
+        let mut n = 0;
+        while taken.iter().any(|t| same_file(t, &planned)) {
+            n += 1;
+            planned = stage_dir.join(format!("{}-{}.apk", role, n));
+        }
+        if planned != path {
+            diversions.push(format!("{} 与其他产物路径相同（{}），改用 {}", role, path.display(), planned.display()));
+        }
+        taken.push(planned.clone());
+        planned
+    });
+    (planned, diversions)
+}
+
+/// 把签名后的临时文件替换为最终输出：同一文件系统上直接重命名，否则先复制到输出目录下的临时文件再重命名
+pub fn promote(signed: &Path, final_apk: &Path) -> io::Result<()> {
+    if fs::rename(signed, final_apk).is_ok() {
+        return Ok(());
+    }
+    let tmp = final_apk.with_file_name(format!("{}.tmp", final_apk.file_name().unwrap_or_default().to_string_lossy()));
+    fs::copy(signed, &tmp)?;
+    fs::rename(&tmp, final_apk).inspect_err(|_| {
+        let _ = fs::remove_file(&tmp);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_file_compares_normalized_paths() {
+        assert!(same_file(Path::new("/out/./app.apk"), Path::new("/out/app.apk")));
+        assert!(same_file(Path::new("/out/tmp/../app.apk"), Path::new("/out/app.apk")));
+        assert!(!same_file(Path::new("/out/app.apk"), Path::new("/out/app-aligned.apk")));
+        assert_eq!(same_file(Path::new("/out/App.apk"), Path::new("/out/app.apk")), cfg!(any(windows, target_os = "macos")));
+    }
+
+    #[test]
+    fn distinct_intermediates_are_kept() {
+        let stage = Path::new("/work/stage");
+        let (planned, diversions) = plan(
+            Path::new("/in/app.apk"),
+            Path::new("/out/app_signed.apk"),
+            stage,
+            [("unsigned", PathBuf::from("/work/unsigned.apk")), ("aligned", PathBuf::from("/work/aligned.apk"))],
+        );
+        assert_eq!(planned, [PathBuf::from("/work/unsigned.apk"), PathBuf::from("/work/aligned.apk")]);
+        assert!(diversions.is_empty());
+    }
+
+    #[test]
+    fn colliding_intermediates_are_diverted_to_the_stage_dir() {
+        let stage = Path::new("/work/stage");
+        let (planned, diversions) = plan(
+            Path::new("/out/app.apk"),
+            Path::new("/out/app.apk"),
+            stage,
+            [
+                ("unsigned", PathBuf::from("/out/./app.apk")),
+                ("aligned", PathBuf::from("/work/stage/unsigned-1.apk")),
+                ("signed", PathBuf::from("/out/tmp/../app.apk")),
+            ],
+        );
+        assert_eq!(planned, [stage.join("unsigned-1.apk"), stage.join("aligned-1.apk"), stage.join("signed-1.apk")]);
+        assert_eq!(diversions.len(), 3);
+        assert!(diversions[0].starts_with("unsigned 与其他产物路径相同"));
+        assert!(diversions[1].contains("aligned-1.apk"));
+    }
+
+    #[test]
+    fn generated_names_skip_paths_already_taken() {
+        let stage = Path::new("/work");
+        let (planned, _) = plan(
+            Path::new("/work/aligned-1.apk"),
+            Path::new("/out/app.apk"),
+            stage,
+            [("aligned", PathBuf::from("/out/app.apk")), ("aligned", PathBuf::from("/out/app.apk"))],
+        );
+        assert_eq!(planned, [stage.join("aligned-2.apk"), stage.join("aligned-3.apk")]);
+    }
+
+    #[test]
+    fn promote_replaces_the_final_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let signed = dir.path().join("signed.tmp.apk");
+        let final_apk = dir.path().join("app.apk");
+        fs::write(&final_apk, b"old").unwrap();
+        fs::write(&signed, b"new").unwrap();
+        promote(&signed, &final_apk).unwrap();
+        assert_eq!(fs::read(&final_apk).unwrap(), b"new");
+        assert!(!signed.exists());
+        assert!(promote(&signed, &final_apk).is_err());
+        assert_eq!(fs::read(&final_apk).unwrap(), b"new");
+    }
+}
@@ -0,0 +1,289 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use crate::input_path::{self, Expect};
+use crate::signing::PipelineError;
+
+/// 替换内容的来源
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub enum AssetSource {
+    /// 本地文件路径
+    LocalFile(String),
+    /// 直接给出的文本内容
+    InlineText(String),
+}
+
+/// 处理时替换 APK 中的一个资源文件
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct AssetOverride {
+    /// APK 内路径，只能位于 `assets/` 或 `res/raw/` 下
+    pub entry_path: String,
+    pub source: AssetSource,
+}
+
+/// 一次替换的结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct AssetChange {
+    pub entry_path: String,
+    /// 原条目大小，新建的条目为空
+    #[cfg_attr(feature = "export-bindings", ts(type = "number | null"))]
+    pub old_size: Option<u64>,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub new_size: u64,
+}
+
+/// 校验条目路径：只允许 `assets/` 与 `res/raw/` 下的相对路径，不允许 `..`
+pub fn validate_entry_path(entry_path: &str) -> Result<(), PipelineError> {
+    let under_allowed = entry_path.starts_with("assets/") || entry_path.starts_with("res/raw/");
+    // 按 `/` 逐段检查：`Path::components` 会忽略中间的 `.` 与重复的 `/`，而 ZIP 条目名是原样写入的
+    let plain = !entry_path.contains('\\') && entry_path.split('/').all(|segment| !matches!(segment, "" | "." | ".."));
+    if !under_allowed || !plain {
+        return Err(PipelineError::InvalidInput(format!("只能替换 assets/ 或 res/raw/ 下的文件: {}", entry_path)));
+    }
+    Ok(())
+}
+
+fn read_source(source: &AssetSource) -> Result<Vec<u8>, PipelineError> {
+    match source {
+        AssetSource::LocalFile(path) => {
+            fs::read(path).map_err(|e| PipelineError::InvalidInput(format!("读取替换文件 {} 失败: {}", path, e)))
+        }
+        AssetSource::InlineText(text) => Ok(text.as_bytes().to_vec()),
+    }
+}
+
+/// `res/raw/` 下新增文件需要登记资源 ID，只允许替换已有条目
+fn check_creatable(entry_path: &str, exists: bool) -> Result<(), PipelineError> {
+    if !exists && !entry_path.starts_with("assets/") {
+        return Err(PipelineError::InvalidInput(format!("{} 不存在，只能在 assets/ 下新建文件", entry_path)));
+    }
+    Ok(())
+}
+
+/// 在反编译目录中写入替换内容（回编译前调用）
+pub fn apply_to_work_dir(work_dir: &Path, overrides: &[AssetOverride]) -> Result<Vec<AssetChange>, PipelineError> {
+    let mut changes = Vec::new();
+    for item in overrides {
+        validate_entry_path(&item.entry_path)?;
+        let target = work_dir.join(&item.entry_path);
+        let old_size = fs::metadata(&target).ok().filter(|m| m.is_file()).map(|m| m.len());
+        check_creatable(&item.entry_path, old_size.is_some())?;
+
+        let data = read_source(&item.source)?;
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&target, &data)?;
+        changes.push(AssetChange { entry_path: item.entry_path.clone(), old_size, new_size: data.len() as u64 });
+    }
+    Ok(changes)
+}
+
+fn stored_aligned() -> zip::write::SimpleFileOptions {
+    zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored).with_alignment(4)
+}
+
+/// 直接在 ZIP 中替换条目：沿用原条目的压缩方式，未压缩条目（包括未替换的）按 4 字节对齐，其余条目原样拷贝。
+/// 输出 APK 的签名已失效，需要重新签名
+pub fn apply_to_apk(src: &Path, dest: &Path, overrides: &[AssetOverride]) -> Result<Vec<AssetChange>, PipelineError> {
+    let mut archive = zip::ZipArchive::new(fs::File::open(src)?)?;
+    let mut pending: Vec<(&AssetOverride, Vec<u8>)> = Vec::new();
+    for item in overrides {
+        validate_entry_path(&item.entry_path)?;
+        check_creatable(&item.entry_path, archive.index_for_name(&item.entry_path).is_some())?;
+        pending.push((item, read_source(&item.source)?));
+    }
+    let mut writer = zip::ZipWriter::new(fs::File::create(dest)?);
+
+    let mut changes = Vec::new();
+    for i in 0..archive.len() {
+        let entry = archive.by_index_raw(i)?;
+        let Some(pos) = pending.iter().position(|(item, _)| item.entry_path == entry.name()) else {
+            if entry.compression() == zip::CompressionMethod::Stored {
+                // 前面的条目大小变了，原样拷贝会破坏未压缩条目的对齐，重新写入
+                let name = entry.name().to_string();
+                drop(entry);
+                writer.start_file(name, stored_aligned())?;
+                std::io::copy(&mut archive.by_index(i)?, &mut writer)?;
+            } else {
+                writer.raw_copy_file(entry)?;
+            }
+            continue;
+        };
+        let (item, data) = pending.remove(pos);
+        let method = entry.compression();
+        let old_size = entry.size();
+        drop(entry);
+
+        let options = match method {
+            zip::CompressionMethod::Stored => stored_aligned(),
+            _ => zip::write::SimpleFileOptions::default().compression_method(method),
+        };
+        writer.start_file(item.entry_path.as_str(), options)?;
+        writer.write_all(&data)?;
+        changes.push(AssetChange { entry_path: item.entry_path.clone(), old_size: Some(old_size), new_size: data.len() as u64 });
+    }
+
+    for (item, data) in pending {
+        writer.start_file(item.entry_path.as_str(), zip::write::SimpleFileOptions::default())?;
+        writer.write_all(&data)?;
+        changes.push(AssetChange { entry_path: item.entry_path.clone(), old_size: None, new_size: data.len() as u64 });
+    }
+    writer.finish()?;
+    Ok(changes)
+}
+
+/// 不经反编译，直接替换 APK 中的资源文件并输出到新路径（需重新签名后才能安装）
+#[tauri::command]
+pub fn apply_asset_overrides_to_apk(
+    apk_path: String,
+    output_path: String,
+    overrides: Vec<AssetOverride>,
+) -> Result<Vec<AssetChange>, PipelineError> {
+    let apk_path = input_path::arg(&apk_path, Expect::File)?;
+    let output_path = input_path::arg(&output_path, Expect::Any)?;
+    if Path::new(&apk_path) == Path::new(&output_path) {
+        return Err(PipelineError::InvalidInput("输出路径不能与原 APK 相同".to_string()));
+    }
+    apply_to_apk(Path::new(&apk_path), Path::new(&output_path), &overrides)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    /// assets/config.json 与 assets/www/index.html 为 Deflated，res/raw/beep.ogg 与 resources.arsc 为 Stored
+    const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/assets.apk");
+    const CUSTOMER_CONFIG: &str = r#"{"customer": "acme", "endpoint": "https://acme.example.com/api", "features": ["scan", "print"]}"#;
+
+    fn overrides(dir: &Path) -> Vec<AssetOverride> {
+        let sound = dir.join("acme.ogg");
+        fs::write(&sound, b"OggS acme").unwrap();
+        vec![
+            AssetOverride { entry_path: "assets/config.json".to_string(), source: AssetSource::InlineText(CUSTOMER_CONFIG.to_string()) },
+            AssetOverride { entry_path: "res/raw/beep.ogg".to_string(), source: AssetSource::LocalFile(sound.to_string_lossy().to_string()) },
+            AssetOverride { entry_path: "assets/customer/logo.txt".to_string(), source: AssetSource::InlineText("ACME".to_string()) },
+        ]
+    }
+
+    fn sizes(changes: &[AssetChange]) -> Vec<(&str, Option<u64>, u64)> {
+        changes.iter().map(|c| (c.entry_path.as_str(), c.old_size, c.new_size)).collect()
+    }
+
+    fn read_entry(archive: &mut zip::ZipArchive<fs::File>, name: &str) -> Vec<u8> {
+        let mut data = Vec::new();
+        archive.by_name(name).unwrap().read_to_end(&mut data).unwrap();
+        data
+    }
+
+    /// 解压夹具，模拟 apktool 反编译出的目录
+    fn extract_fixture(dir: &Path) {
+        let mut archive = zip::ZipArchive::new(fs::File::open(FIXTURE).unwrap()).unwrap();
+        archive.extract(dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_paths_outside_assets_and_raw() {
+        for path in ["assets/config.json", "assets/a/b/c.bin", "res/raw/beep.ogg"] {
+            assert!(validate_entry_path(path).is_ok(), "{}", path);
+        }
+        for path in ["AndroidManifest.xml", "classes.dex", "res/layout/main.xml", "assets/../classes.dex", "assets/./x", "assets//x", "/assets/x", "assets\\x", "assets/dir/", "res/rawfile"] {
+            assert!(matches!(validate_entry_path(path), Err(PipelineError::InvalidInput(_))), "{}", path);
+        }
+    }
+
+    #[test]
+    fn full_pipeline_writes_overrides_into_the_work_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let work_dir = dir.path().join("work");
+        extract_fixture(&work_dir);
+
+        let changes = apply_to_work_dir(&work_dir, &overrides(dir.path())).unwrap();
+        assert_eq!(
+            sizes(&changes),
+            [
+                ("assets/config.json", Some(63), CUSTOMER_CONFIG.len() as u64),
+                ("res/raw/beep.ogg", Some(64), 9),
+                ("assets/customer/logo.txt", None, 4),
+            ]
+        );
+        assert_eq!(fs::read_to_string(work_dir.join("assets/config.json")).unwrap(), CUSTOMER_CONFIG);
+        assert_eq!(fs::read(work_dir.join("res/raw/beep.ogg")).unwrap(), b"OggS acme");
+        assert_eq!(fs::read_to_string(work_dir.join("assets/customer/logo.txt")).unwrap(), "ACME");
+        assert_eq!(fs::read_to_string(work_dir.join("assets/www/index.html")).unwrap(), "<html>default</html>\n");
+    }
+
+    #[test]
+    fn fast_path_replaces_entries_keeping_method_and_alignment() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("out.apk");
+        let changes = apply_to_apk(Path::new(FIXTURE), &output, &overrides(dir.path())).unwrap();
+        assert_eq!(
+            sizes(&changes),
+            [
+                ("assets/config.json", Some(63), CUSTOMER_CONFIG.len() as u64),
+                ("res/raw/beep.ogg", Some(64), 9),
+                ("assets/customer/logo.txt", None, 4),
+            ]
+        );
+
+        let mut original = zip::ZipArchive::new(fs::File::open(FIXTURE).unwrap()).unwrap();
+        let mut archive = zip::ZipArchive::new(fs::File::open(&output).unwrap()).unwrap();
+        let names: Vec<&str> = archive.file_names().collect();
+        assert_eq!(names.len(), original.len() + 1);
+        assert_eq!(read_entry(&mut archive, "assets/config.json"), CUSTOMER_CONFIG.as_bytes());
+        assert_eq!(read_entry(&mut archive, "res/raw/beep.ogg"), b"OggS acme");
+        assert_eq!(read_entry(&mut archive, "assets/customer/logo.txt"), b"ACME");
+        for unchanged in ["AndroidManifest.xml", "assets/www/index.html", "resources.arsc", "classes.dex"] {
+            assert_eq!(read_entry(&mut archive, unchanged), read_entry(&mut original, unchanged), "{}", unchanged);
+        }
+
+        for i in 0..archive.len() {
+            let entry = archive.by_index(i).unwrap();
+            let before = original.by_name(entry.name()).ok().map(|e| e.compression());
+            if let Some(method) = before {
+                assert_eq!(entry.compression(), method, "{}", entry.name());
+            }
+            if entry.compression() == zip::CompressionMethod::Stored {
+                assert_eq!(entry.data_start() % 4, 0, "{} 未按 4 字节对齐", entry.name());
+            }
+        }
+    }
+
+    #[test]
+    fn raw_resources_cannot_be_created() {
+        let dir = tempfile::tempdir().unwrap();
+        let new_raw = [AssetOverride { entry_path: "res/raw/new.ogg".to_string(), source: AssetSource::InlineText("x".to_string()) }];
+        assert!(apply_to_apk(Path::new(FIXTURE), &dir.path().join("out.apk"), &new_raw).is_err());
+
+        let work_dir = dir.path().join("work");
+        extract_fixture(&work_dir);
+        assert!(apply_to_work_dir(&work_dir, &new_raw).is_err());
+        assert!(!work_dir.join("res/raw/new.ogg").exists());
+    }
+
+    #[test]
+    fn missing_local_source_is_reported_before_writing() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = [AssetOverride {
+            entry_path: "assets/config.json".to_string(),
+            source: AssetSource::LocalFile(dir.path().join("missing.json").to_string_lossy().to_string()),
+        }];
+        let output = dir.path().join("out.apk");
+        let error = apply_to_apk(Path::new(FIXTURE), &output, &missing).unwrap_err();
+        assert!(error.to_string().contains("missing.json"), "{}", error);
+        assert!(!output.exists());
+    }
+
+    #[test]
+    fn command_refuses_to_overwrite_the_input() {
+        let result = apply_asset_overrides_to_apk(FIXTURE.to_string(), FIXTURE.to_string(), Vec::new());
+        assert!(matches!(result, Err(PipelineError::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,225 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tauri::{Emitter, Manager};
+
+use crate::input_path::{self, Expect};
+use crate::paths::AppPaths;
+
+const AUDIT_FILE: &str = "audit.jsonl";
+const OPERATOR_FILE: &str = "operator.json";
+/// 单个日志文件超过该大小后轮转
+const MAX_FILE_BYTES: u64 = 5 * 1024 * 1024;
+/// 保留的历史文件数（audit.1.jsonl ~ audit.N.jsonl）
+const KEEP_ROTATED: u32 = 5;
+
+/// 一条审计记录：只记录会改变设备或设置状态的操作
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct AuditEntry {
+    /// Unix 时间戳（秒）
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub timestamp: u64,
+    pub operator: Option<String>,
+    pub operation: String,
+    /// 参数摘要，如设备、包名
+    #[cfg_attr(feature = "export-bindings", ts(type = "Record<string, unknown>"))]
+    pub params: serde_json::Value,
+    pub success: bool,
+    /// 失败原因或结果说明
+    pub detail: Option<String>,
+}
+
+/// 查询条件，均为可选
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct AuditFilter {
+    pub operator: Option<String>,
+    pub operation: Option<String>,
+    /// 匹配参数中的 `device`
+    pub device: Option<String>,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number | null"))]
+    pub from: Option<u64>,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number | null"))]
+    pub to: Option<u64>,
+}
+
+impl AuditFilter {
+    fn matches(&self, entry: &AuditEntry) -> bool {
+        self.operator.as_ref().is_none_or(|o| entry.operator.as_ref() == Some(o))
+            && self.operation.as_ref().is_none_or(|o| &entry.operation == o)
+            && self.device.as_ref().is_none_or(|d| entry.params.get("device").and_then(|v| v.as_str()) == Some(d.as_str()))
+            && self.from.is_none_or(|from| entry.timestamp >= from)
+            && self.to.is_none_or(|to| entry.timestamp <= to)
+    }
+}
+
+/// 当前操作员与日志写入锁；写入与轮转在同一把锁内完成，轮转不会丢失正在写入的记录
+#[derive(Default)]
+pub struct AuditLog {
+    operator: Mutex<Option<String>>,
+    writer: Mutex<()>,
+    /// 退出过程中已关闭
+    closed: AtomicBool,
+}
+
+fn audit_path(paths: &AppPaths, generation: u32) -> PathBuf {
+    match generation {
+        0 => paths.data_dir.join(AUDIT_FILE),
+        n => paths.data_dir.join(format!("audit.{}.jsonl", n)),
+    }
+}
+
+fn rotate_if_needed(paths: &AppPaths) -> io::Result<()> {
+    let current = audit_path(paths, 0);
+    if fs::metadata(&current).map(|m| m.len()).unwrap_or(0) < MAX_FILE_BYTES {
+        return Ok(());
+    }
+    let _ = fs::remove_file(audit_path(paths, KEEP_ROTATED));
+    for n in (1..KEEP_ROTATED).rev() {
+        let from = audit_path(paths, n);
+        if from.exists() {
+            fs::rename(&from, audit_path(paths, n + 1))?;
+        }
+    }
+    fs::rename(&current, audit_path(paths, 1))
+}
+
+impl AuditLog {
+    /// 启动时载入上次设置的操作员
+    pub fn load(paths: &AppPaths) -> Self {
+        let operator = fs::read_to_string(paths.config_dir.join(OPERATOR_FILE))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok());
+        AuditLog { operator: Mutex::new(operator), ..Default::default() }
+    }
+
+    /// 等待正在写入的记录写完，之后不再写入，避免退出时留下写了一半的行
+    pub fn close(&self) {
+        let _guard = self.writer.lock().unwrap();
+        self.closed.store(true, Ordering::SeqCst);
+    }
+
+    pub fn operator(&self) -> Option<String> {
+        self.operator.lock().unwrap().clone()
+    }
+
+    fn append(&self, paths: &AppPaths, entry: &AuditEntry) -> io::Result<()> {
+        let _guard = self.writer.lock().unwrap();
+        if self.closed.load(Ordering::SeqCst) {
+            return Err(io::Error::other("应用正在退出，审计日志已关闭"));
+        }
+        fs::create_dir_all(&paths.data_dir)?;
+        // 先轮转再写入：本条记录总是完整地落在新文件里
+        rotate_if_needed(paths)?;
+        let line = serde_json::to_string(entry).map_err(io::Error::other)?;
+        let mut file = fs::OpenOptions::new().create(true).append(true).open(audit_path(paths, 0))?;
+        writeln!(file, "{}", line)
+    }
+}
+
+/// 记录一次操作。写入失败不影响操作本身，只推送 `audit-warning` 事件
+pub fn record_outcome(app: &tauri::AppHandle, operation: &str, params: serde_json::Value, success: bool, detail: Option<String>) {
+    let log = app.state::<AuditLog>();
+    let entry = AuditEntry {
+        timestamp: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+        operator: log.operator(),
+        operation: operation.to_string(),
+        params,
+        success,
+        detail,
+    };
+    if let Err(e) = log.append(&app.state::<AppPaths>(), &entry) {
+        let _ = app.emit("audit-warning", format!("写入审计日志失败（{}）: {}", operation, e));
+    }
+}
+
+/// 按命令返回值记录一次操作
+pub fn record<T, E: Display>(app: &tauri::AppHandle, operation: &str, params: serde_json::Value, result: &Result<T, E>) {
+    let detail = result.as_ref().err().map(ToString::to_string);
+    record_outcome(app, operation, params, result.is_ok(), detail);
+}
+
+fn read_entries(path: &Path) -> Vec<AuditEntry> {
+    let Ok(file) = fs::File::open(path) else {
+        return Vec::new();
+    };
+    io::BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect()
+}
+
+/// 按时间顺序读取全部日志（含轮转的历史文件）
+fn all_entries(paths: &AppPaths) -> Vec<AuditEntry> {
+    (0..=KEEP_ROTATED).rev().flat_map(|n| read_entries(&audit_path(paths, n))).collect()
+}
+
+/// 按时间顺序读取 `from` 之后的记录；较早的轮转文件不再读取
+pub fn entries_since(paths: &AppPaths, from: u64) -> Vec<AuditEntry> {
+    let mut files = Vec::new();
+    for n in 0..=KEEP_ROTATED {
+        let entries = read_entries(&audit_path(paths, n));
+        let reached = entries.first().is_some_and(|e| e.timestamp < from);
+        files.push(entries);
+        if reached {
+            break;
+        }
+    }
+    files.into_iter().rev().flatten().filter(|e| e.timestamp >= from).collect()
+}
+
+/// 设置当前操作员，之后的审计记录都归属于该操作员；传空字符串清除
+#[tauri::command]
+pub fn set_operator(
+    app: tauri::AppHandle,
+    paths: tauri::State<'_, AppPaths>,
+    log: tauri::State<'_, AuditLog>,
+    name: String,
+) -> Result<(), String> {
+    let name = Some(name.trim().to_string()).filter(|n| !n.is_empty());
+    let previous = log.operator();
+    fs::create_dir_all(&paths.config_dir).map_err(|e| e.to_string())?;
+    let content = serde_json::to_string(&name).map_err(|e| e.to_string())?;
+    fs::write(paths.config_dir.join(OPERATOR_FILE), content).map_err(|e| e.to_string())?;
+    *log.operator.lock().unwrap() = name.clone();
+    record_outcome(&app, "set_operator", serde_json::json!({ "previous": previous, "operator": name }), true, None);
+    Ok(())
+}
+
+/// 当前操作员
+#[tauri::command]
+pub fn get_operator(log: tauri::State<'_, AuditLog>) -> Option<String> {
+    log.operator()
+}
+
+/// 查询审计日志，按时间从新到旧返回最多 `limit` 条
+#[tauri::command]
+pub fn query_audit_log(paths: tauri::State<'_, AppPaths>, filter: AuditFilter, limit: usize) -> Vec<AuditEntry> {
+    let mut entries: Vec<AuditEntry> = all_entries(&paths).into_iter().filter(|e| filter.matches(e)).collect();
+    entries.reverse();
+    entries.truncate(limit);
+    entries
+}
+
+/// 将时间范围内的审计记录导出为 JSONL，返回导出条数
+#[tauri::command]
+pub fn export_audit_log(paths: tauri::State<'_, AppPaths>, path: String, from: Option<u64>, to: Option<u64>) -> Result<usize, String> {
+    let path = input_path::arg(&path, Expect::Any).map_err(|e| e.to_string())?;
+    let filter = AuditFilter { from, to, ..Default::default() };
+    let entries: Vec<AuditEntry> = all_entries(&paths).into_iter().filter(|e| filter.matches(e)).collect();
+    let mut out = String::new();
+    for entry in &entries {
+        out.push_str(&serde_json::to_string(entry).map_err(|e| e.to_string())?);
+        out.push('\n');
+    }
+    fs::write(&path, out).map_err(|e| e.to_string())?;
+    Ok(entries.len())
+}
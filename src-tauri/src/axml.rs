@@ -0,0 +1,343 @@
+//! 二进制 AndroidManifest.xml（AXML）的最小解析器，只读取元素与属性，不还原文本内容；
+//! 另支持不经 apktool 直接改写字符串属性。
+
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+const CHUNK_STRING_POOL: u16 = 0x0001;
+const CHUNK_RESOURCE_MAP: u16 = 0x0180;
+const CHUNK_START_ELEMENT: u16 = 0x0102;
+const CHUNK_END_ELEMENT: u16 = 0x0103;
+
+const TYPE_REFERENCE: u8 = 0x01;
+const TYPE_STRING: u8 = 0x03;
+const TYPE_INT_DEC: u8 = 0x10;
+const TYPE_INT_HEX: u8 = 0x11;
+const TYPE_BOOLEAN: u8 = 0x12;
+
+/// android:minSdkVersion 的资源 ID
+pub const ATTR_MIN_SDK_VERSION: u32 = 0x0101_020c;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AxmlValue {
+    String(String),
+    Int(i64),
+    Bool(bool),
+    Reference(u32),
+    Other(u8, u32),
+}
+
+impl AxmlValue {
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            AxmlValue::Int(v) => Some(*v),
+            AxmlValue::String(s) => s.trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    pub fn as_string(&self) -> String {
+        match self {
+            AxmlValue::String(s) => s.clone(),
+            AxmlValue::Int(v) => v.to_string(),
+            AxmlValue::Bool(b) => b.to_string(),
+            AxmlValue::Reference(id) => format!("@0x{:08x}", id),
+            AxmlValue::Other(t, d) => format!("(type 0x{:02x}) 0x{:08x}", t, d),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AxmlAttribute {
+    pub name: String,
+    pub resource_id: Option<u32>,
+    pub value: AxmlValue,
+}
+
+#[derive(Debug, Clone)]
+pub struct AxmlElement {
+    pub name: String,
+    /// 元素嵌套深度，根元素 `manifest` 为 0
+    pub depth: usize,
+    pub attributes: Vec<AxmlAttribute>,
+}
+
+impl AxmlElement {
+    /// 按属性名（不含命名空间前缀）查找
+    pub fn attr(&self, name: &str) -> Option<&AxmlValue> {
+        self.attributes.iter().find(|a| a.name == name).map(|a| &a.value)
+    }
+
+    /// 按资源 ID 查找，适用于属性名被混淆的 APK
+    pub fn attr_by_id(&self, id: u32) -> Option<&AxmlValue> {
+        self.attributes.iter().find(|a| a.resource_id == Some(id)).map(|a| &a.value)
+    }
+}
+
+fn u16_at(data: &[u8], pos: usize) -> Result<u16, String> {
+    data.get(pos..pos + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .ok_or_else(|| "AXML 数据被截断".to_string())
+}
+
+fn u32_at(data: &[u8], pos: usize) -> Result<u32, String> {
+    data.get(pos..pos + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| "AXML 数据被截断".to_string())
+}
+
+fn utf8_len_at(data: &[u8], pos: usize) -> Result<(usize, usize), String> {
+    let b0 = *data.get(pos).ok_or("AXML 字符串越界")? as usize;
+    if b0 & 0x80 != 0 {
+        let b1 = *data.get(pos + 1).ok_or("AXML 字符串越界")? as usize;
+        Ok((((b0 & 0x7f) << 8) | b1, 2))
+    } else {
+        Ok((b0, 1))
+    }
+}
+
+fn parse_string_pool(data: &[u8], start: usize) -> Result<Vec<String>, String> {
+    let count = u32_at(data, start + 8)? as usize;
+    let flags = u32_at(data, start + 16)?;
+    let strings_start = start + u32_at(data, start + 20)? as usize;
+    let utf8 = flags & 0x100 != 0;
+
+    let mut strings = Vec::with_capacity(count);
+    for i in 0..count {
+        let mut pos = strings_start + u32_at(data, start + 28 + i * 4)? as usize;
+        if utf8 {
+            // 先是 UTF-16 长度，再是 UTF-8 字节长度，各占 1~2 字节
+            let (_, n) = utf8_len_at(data, pos)?;
+            pos += n;
+            let (len, n) = utf8_len_at(data, pos)?;
+            pos += n;
+            let bytes = data.get(pos..pos + len).ok_or("AXML 字符串越界")?;
+            strings.push(String::from_utf8_lossy(bytes).to_string());
+        } else {
+            let mut len = u16_at(data, pos)? as usize;
+            pos += 2;
+            if len & 0x8000 != 0 {
+                len = ((len & 0x7fff) << 16) | u16_at(data, pos)? as usize;
+                pos += 2;
+            }
+            let units: Vec<u16> = (0..len).map(|j| u16_at(data, pos + j * 2)).collect::<Result<_, _>>()?;
+            strings.push(String::from_utf16_lossy(&units));
+        }
+    }
+    Ok(strings)
+}
+
+/// 解析二进制 XML，按文档顺序返回所有元素
+pub fn parse(data: &[u8]) -> Result<Vec<AxmlElement>, String> {
+    if u16_at(data, 0)? != 0x0003 {
+        return Err("不是二进制 XML 文件".to_string());
+    }
+    let mut pos = u16_at(data, 2)? as usize;
+    let mut strings: Vec<String> = Vec::new();
+    let mut resource_ids: Vec<u32> = Vec::new();
+    let mut elements = Vec::new();
+    let mut depth = 0usize;
+
+    let string = |strings: &Vec<String>, idx: u32| -> String {
+        if idx == u32::MAX { String::new() } else { strings.get(idx as usize).cloned().unwrap_or_default() }
+    };
+
+    while pos + 8 <= data.len() {
+        let chunk_type = u16_at(data, pos)?;
+        let header_size = u16_at(data, pos + 2)? as usize;
+        let chunk_size = u32_at(data, pos + 4)? as usize;
+        if chunk_size < 8 {
+            return Err("AXML 块大小无效".to_string());
+        }
+
+        match chunk_type {
+            CHUNK_STRING_POOL => strings = parse_string_pool(data, pos)?,
+            CHUNK_RESOURCE_MAP => {
+                resource_ids = (0..(chunk_size - header_size) / 4)
+                    .map(|i| u32_at(data, pos + header_size + i * 4))
+                    .collect::<Result<_, _>>()?;
+            }
+            CHUNK_START_ELEMENT => {
+                let ext = pos + header_size;
+                let name = string(&strings, u32_at(data, ext + 4)?);
+                let attr_start = u16_at(data, ext + 8)? as usize;
+                let attr_size = u16_at(data, ext + 10)? as usize;
+                let attr_count = u16_at(data, ext + 12)? as usize;
+
+                let mut attributes = Vec::with_capacity(attr_count);
+                for i in 0..attr_count {
+                    let a = ext + attr_start + i * attr_size;
+                    let name_idx = u32_at(data, a + 4)?;
+                    let raw_idx = u32_at(data, a + 8)?;
+                    let data_type = *data.get(a + 15).ok_or("AXML 属性越界")?;
+                    let value_data = u32_at(data, a + 16)?;
+                    let value = match data_type {
+                        TYPE_STRING => AxmlValue::String(string(&strings, value_data)),
+                        TYPE_INT_DEC | TYPE_INT_HEX => AxmlValue::Int(value_data as i32 as i64),
+                        TYPE_BOOLEAN => AxmlValue::Bool(value_data != 0),
+                        TYPE_REFERENCE => AxmlValue::Reference(value_data),
+                        _ if raw_idx != u32::MAX => AxmlValue::String(string(&strings, raw_idx)),
+                        other => AxmlValue::Other(other, value_data),
+                    };
+                    attributes.push(AxmlAttribute {
+                        name: string(&strings, name_idx),
+                        resource_id: resource_ids.get(name_idx as usize).copied(),
+                        value,
+                    });
+                }
+                elements.push(AxmlElement { name, depth, attributes });
+                depth += 1;
+            }
+            CHUNK_END_ELEMENT => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        pos += chunk_size;
+    }
+    Ok(elements)
+}
+
+fn put_u32(data: &mut [u8], pos: usize, value: u32) {
+    data[pos..pos + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+/// 按字符串池的编码写出一个字符串（含长度前缀与结尾的 0）
+fn encode_pool_string(value: &str, utf8: bool) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    let units: Vec<u16> = value.encode_utf16().collect();
+    if utf8 {
+        let bytes = value.as_bytes();
+        for len in [units.len(), bytes.len()] {
+            match len {
+                0..=0x7f => out.push(len as u8),
+                0x80..=0x7fff => out.extend([0x80 | (len >> 8) as u8, len as u8]),
+                _ => return Err("AXML 字符串过长".to_string()),
+            }
+        }
+        out.extend_from_slice(bytes);
+        out.push(0);
+    } else {
+        if units.len() > 0x7fff {
+            return Err("AXML 字符串过长".to_string());
+        }
+        out.extend((units.len() as u16).to_le_bytes());
+        units.iter().for_each(|u| out.extend(u.to_le_bytes()));
+        out.extend([0, 0]);
+    }
+    Ok(out)
+}
+
+/// 在字符串池末尾追加一个字符串，返回新数据与该字符串的下标。
+/// 追加在末尾不会影响资源 ID 映射；带样式的字符串池不支持
+fn append_pool_string(data: &[u8], pool: usize, value: &str) -> Result<(Vec<u8>, u32), String> {
+    let header_size = u16_at(data, pool + 2)? as usize;
+    let chunk_size = u32_at(data, pool + 4)? as usize;
+    let count = u32_at(data, pool + 8)? as usize;
+    if u32_at(data, pool + 12)? != 0 {
+        return Err("字符串池包含样式，无法直接编辑".to_string());
+    }
+    let utf8 = u32_at(data, pool + 16)? & 0x100 != 0;
+    let strings_start = u32_at(data, pool + 20)? as usize;
+    let offsets_end = pool + header_size + count * 4;
+    let string_data = data.get(pool + strings_start..pool + chunk_size).ok_or("AXML 字符串池越界")?;
+
+    let mut strings = string_data.to_vec();
+    let new_offset = strings.len() as u32;
+    strings.extend(encode_pool_string(value, utf8)?);
+    while strings.len() % 4 != 0 {
+        strings.push(0);
+    }
+
+    let mut chunk = data[pool..offsets_end].to_vec();
+    chunk.extend(new_offset.to_le_bytes());
+    let new_strings_start = chunk.len();
+    chunk.extend(strings);
+    let len = chunk.len() as u32;
+    put_u32(&mut chunk, 4, len);
+    put_u32(&mut chunk, 8, count as u32 + 1);
+    put_u32(&mut chunk, 20, new_strings_start as u32);
+
+    let mut out = data[..pool].to_vec();
+    out.extend(chunk);
+    out.extend_from_slice(&data[pool + chunk_size..]);
+    let total = out.len() as u32;
+    put_u32(&mut out, 4, total);
+    Ok((out, count as u32))
+}
+
+/// 将第一个名为 `element` 的元素上无命名空间的 `attribute` 改为字符串 `value`（如 manifest 的 package）。
+/// 新值追加到字符串池，原字符串保留，避免影响其他引用它的位置
+pub fn set_string_attribute(data: &[u8], element: &str, attribute: &str, value: &str) -> Result<Vec<u8>, String> {
+    if u16_at(data, 0)? != 0x0003 {
+        return Err("不是二进制 XML 文件".to_string());
+    }
+    let mut pos = u16_at(data, 2)? as usize;
+    let mut pool = None;
+    let mut strings: Vec<String> = Vec::new();
+    while pos + 8 <= data.len() {
+        let chunk_type = u16_at(data, pos)?;
+        let header_size = u16_at(data, pos + 2)? as usize;
+        let chunk_size = u32_at(data, pos + 4)? as usize;
+        if chunk_size < 8 {
+            return Err("AXML 块大小无效".to_string());
+        }
+        if chunk_type == CHUNK_STRING_POOL && pool.is_none() {
+            pool = Some(pos);
+            strings = parse_string_pool(data, pos)?;
+        } else if chunk_type == CHUNK_START_ELEMENT {
+            let ext = pos + header_size;
+            let name_idx = u32_at(data, ext + 4)? as usize;
+            if strings.get(name_idx).map(String::as_str) == Some(element) {
+                let attr_start = u16_at(data, ext + 8)? as usize;
+                let attr_size = u16_at(data, ext + 10)? as usize;
+                let attr_count = u16_at(data, ext + 12)? as usize;
+                let target = (0..attr_count).map(|i| ext + attr_start + i * attr_size).find(|a| {
+                    u32_at(data, *a).ok() == Some(u32::MAX)
+                        && u32_at(data, a + 4).ok().and_then(|n| strings.get(n as usize)).map(String::as_str) == Some(attribute)
+                });
+                let Some(attr_pos) = target else {
+                    return Err(format!("<{}> 没有 {} 属性", element, attribute));
+                };
+                let pool = pool.ok_or("AXML 缺少字符串池")?;
+                let (mut out, idx) = append_pool_string(data, pool, value)?;
+                // 字符串池在元素之前，属性位置随池的增长整体后移
+                let shift = out.len() - data.len();
+                let attr_pos = attr_pos + shift;
+                put_u32(&mut out, attr_pos + 8, idx);
+                out[attr_pos + 15] = TYPE_STRING;
+                put_u32(&mut out, attr_pos + 16, idx);
+                return Ok(out);
+            }
+        }
+        pos += chunk_size;
+    }
+    Err(format!("找不到 <{}> 元素", element))
+}
+
+/// 读取 APK 中的 AndroidManifest.xml 原始字节
+pub fn read_manifest_bytes(apk_path: &Path) -> Result<Vec<u8>, String> {
+    let mut archive = zip::ZipArchive::new(fs::File::open(apk_path).map_err(|e| e.to_string())?)
+        .map_err(|e| e.to_string())?;
+    let mut entry = archive.by_name("AndroidManifest.xml").map_err(|e| e.to_string())?;
+    let mut data = Vec::new();
+    entry.read_to_end(&mut data).map_err(|e| e.to_string())?;
+    Ok(data)
+}
+
+/// 从 APK 中读取并解析 AndroidManifest.xml
+pub fn read_manifest_from_apk(apk_path: &Path) -> Result<Vec<AxmlElement>, String> {
+    parse(&read_manifest_bytes(apk_path)?)
+}
+
+/// 读取 APK 的 `<uses-sdk>` 元素；只认 `manifest` 的直接子元素，与系统解析 Manifest 时一致
+pub fn uses_sdk_of_apk(apk_path: &Path) -> Result<Option<AxmlElement>, String> {
+    Ok(read_manifest_from_apk(apk_path)?.into_iter().find(|e| e.name == "uses-sdk" && e.depth == 1))
+}
+
+/// 读取 `<uses-sdk>` 中的 minSdkVersion
+pub fn min_sdk_of(uses_sdk: &AxmlElement) -> Option<i64> {
+    uses_sdk
+        .attr_by_id(ATTR_MIN_SDK_VERSION)
+        .or_else(|| uses_sdk.attr("minSdkVersion"))
+        .and_then(AxmlValue::as_int)
+}
@@ -0,0 +1,453 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tauri::{Emitter, Manager};
+
+use crate::input_path::{self, Expect};
+use crate::jobs::{self, JobRegistry, JobStatus, ResultStatus};
+use crate::paths::AppPaths;
+use crate::pipeline::{self, ProcessConfig};
+use crate::confirm::ConfirmationTokens;
+use crate::device_cache::DeviceCache;
+use crate::policy::PolicyState;
+use crate::{collision, config, key_policy, policy};
+
+const BATCHES_FILE: &str = "batches.json";
+/// 保留的批处理记录数
+const KEEP_BATCHES: usize = 20;
+
+/// 批处理中的一项
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct BatchItem {
+    pub apk_path: String,
+    /// 开始执行后才分配；重复任务为已有任务的 ID
+    pub job_id: Option<String>,
+    pub status: JobStatus,
+    pub message: Option<String>,
+    pub output_path: Option<String>,
+    /// 失败时所在的步骤
+    #[serde(default)]
+    pub step: Option<String>,
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    /// 处理结果的状态，完成后才有值
+    #[serde(default)]
+    pub result_status: Option<ResultStatus>,
+}
+
+/// 摘要中的一组相同问题
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct DigestEntry {
+    /// `failure` 或 `warning`
+    pub kind: String,
+    /// 分组键：失败为步骤名，警告为去掉各项自身路径、文件名与数字后的文本
+    pub code: String,
+    /// 第一项的原始信息
+    pub example: String,
+    /// 涉及的项（`items` 中的下标）
+    pub items: Vec<usize>,
+    /// 如 `18/20 项: ...`
+    pub summary: String,
+}
+
+/// 一次批处理及各项的状态，每次状态变化都会写入磁盘
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct BatchRecord {
+    pub id: String,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub created_at: u64,
+    pub status: JobStatus,
+    pub items: Vec<BatchItem>,
+    /// 跨项合并的失败与警告，失败在前
+    #[serde(default)]
+    pub summary: Vec<DigestEntry>,
+}
+
+/// 批处理记录文件的写入锁
+#[derive(Default)]
+pub struct BatchStore {
+    lock: Mutex<()>,
+}
+
+fn load_all(paths: &AppPaths) -> Vec<BatchRecord> {
+    fs::read_to_string(paths.data_dir.join(BATCHES_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn write_all(paths: &AppPaths, records: &[BatchRecord]) -> Result<(), String> {
+    fs::create_dir_all(&paths.data_dir).map_err(|e| e.to_string())?;
+    let content = serde_json::to_string_pretty(records).map_err(|e| e.to_string())?;
+    crate::paths::write_atomic(&paths.data_dir.join(BATCHES_FILE), content.as_bytes()).map_err(|e| e.to_string())
+}
+
+impl BatchStore {
+    /// 等待正在进行的写入完成（退出时使用）
+    pub fn sync(&self) {
+        drop(self.lock.lock().unwrap());
+    }
+
+    fn save(&self, paths: &AppPaths, record: &BatchRecord) -> Result<(), String> {
+        let _guard = self.lock.lock().unwrap();
+        let mut records = load_all(paths);
+        match records.iter_mut().find(|r| r.id == record.id) {
+            Some(existing) => *existing = record.clone(),
+            None => records.push(record.clone()),
+        }
+        let skip = records.len().saturating_sub(KEEP_BATCHES);
+        write_all(paths, &records[skip..])
+    }
+}
+
+/// 启动时修正上次退出时未完成的批处理：执行中的项标记为中断并清理其工作目录，未开始的项标记为已取消
+pub fn recover(paths: &AppPaths) {
+    let mut records = load_all(paths);
+    let mut changed = false;
+    for record in records.iter_mut().filter(|r| matches!(r.status, JobStatus::Queued | JobStatus::Running)) {
+        for item in &mut record.items {
+            match item.status {
+                JobStatus::Running => {
+                    item.status = JobStatus::Interrupted;
+                    // 清理失败的原因随中断说明一起显示在批处理记录中
+                    let warnings = pipeline::remove_work_dirs(&item.apk_path);
+                    item.message = Some(std::iter::once("应用在处理中途退出".to_string()).chain(warnings).collect::<Vec<_>>().join("\n"));
+                }
+                JobStatus::Queued => {
+                    item.status = JobStatus::Cancelled;
+                    item.result_status = Some(ResultStatus::Cancelled);
+                    item.message = Some("应用退出，未开始".to_string());
+                }
+                _ => {}
+            }
+        }
+        record.status = JobStatus::Interrupted;
+        changed = true;
+    }
+    // 写入失败时记录保持原样，下次启动会再次修正
+    if changed {
+        let _ = write_all(paths, &records);
+    }
+}
+
+/// 进行中的批处理里尚未完成的项所用的源 APK
+pub fn pending_inputs(paths: &AppPaths) -> Vec<String> {
+    load_all(paths)
+        .into_iter()
+        .filter(|r| r.status == JobStatus::Running)
+        .flat_map(|r| r.items)
+        .filter(|i| matches!(i.status, JobStatus::Queued | JobStatus::Running))
+        .map(|i| i.apk_path)
+        .collect()
+}
+
+/// 去掉信息中与具体某一项相关的部分（APK 路径、文件名、数字），使相同的问题得到相同的键
+fn normalize(message: &str, apk_path: &str) -> String {
+    let mut text = message.replace(apk_path, "{apk}");
+    if let Some(stem) = Path::new(apk_path).file_stem().map(|s| s.to_string_lossy().to_string()).filter(|s| !s.is_empty()) {
+        text = text.replace(&stem, "{name}");
+    }
+    regex::Regex::new(r"\d+").unwrap().replace_all(&text, "#").trim().to_string()
+}
+
+/// 合并各项中相同的失败与警告：失败在前，同类中涉及项多的在前；只出现一次的也保留
+pub fn summarize(items: &[BatchItem]) -> Vec<DigestEntry> {
+    let mut groups: Vec<DigestEntry> = Vec::new();
+    let mut add = |kind: &str, code: String, example: &str, index: usize| {
+        match groups.iter_mut().find(|g| g.kind == kind && g.code == code) {
+            Some(group) if group.items.last() != Some(&index) => group.items.push(index),
+            Some(_) => {}
+            None => groups.push(DigestEntry {
+                kind: kind.to_string(),
+                code,
+                example: example.to_string(),
+                items: vec![index],
+                summary: String::new(),
+            }),
+        }
+    };
+    for (index, item) in items.iter().enumerate() {
+        if matches!(item.status, JobStatus::Failed | JobStatus::Interrupted) {
+            let code = item.step.clone().unwrap_or_else(|| format!("{:?}", item.status).to_lowercase());
+            add("failure", code, item.message.as_deref().unwrap_or(""), index);
+        }
+        for warning in &item.warnings {
+            add("warning", normalize(warning, &item.apk_path), warning, index);
+        }
+    }
+    groups.sort_by(|a, b| {
+        (a.kind != "failure")
+            .cmp(&(b.kind != "failure"))
+            .then(b.items.len().cmp(&a.items.len()))
+            .then(a.code.cmp(&b.code))
+    });
+    for group in &mut groups {
+        let label = if group.kind == "failure" { format!("{} 步骤失败", group.code) } else { group.example.clone() };
+        group.summary = format!("{}/{} 项: {}", group.items.len(), items.len(), label);
+    }
+    groups
+}
+
+/// 批处理报告：摘要在前，各项详情在后
+fn render_report(record: &BatchRecord) -> String {
+    let mut out = format!("批处理 {}（{:?}）\n\n摘要:\n", record.id, record.status);
+    if record.summary.is_empty() {
+        out.push_str("  无失败或警告\n");
+    }
+    for entry in &record.summary {
+        let mark = if entry.kind == "failure" { "✗" } else { "⚠" };
+        out.push_str(&format!("  {} {}\n", mark, entry.summary));
+    }
+    out.push_str("\n详情:\n");
+    for (index, item) in record.items.iter().enumerate() {
+        out.push_str(&format!("[{}] {} — {:?}\n", index, item.apk_path, item.status));
+        if let Some(message) = &item.message {
+            out.push_str(&format!("    {}\n", message.replace('\n', "\n    ")));
+        }
+        if let Some(output) = &item.output_path {
+            out.push_str(&format!("    输出: {}\n", output));
+        }
+        for warning in &item.warnings {
+            out.push_str(&format!("    ⚠ {}\n", warning));
+        }
+    }
+    out
+}
+
+fn save_and_emit(app: &tauri::AppHandle, record: &BatchRecord) {
+    if let Err(e) = app.state::<BatchStore>().save(&app.state::<AppPaths>(), record) {
+        crate::emit_progress(app, "batch", format!("写入批处理记录失败: {}", e));
+    }
+    let _ = app.emit("batch-updated", record.clone());
+}
+
+fn fail(item: &mut BatchItem, message: String) {
+    item.status = JobStatus::Failed;
+    item.result_status = Some(ResultStatus::Failed);
+    item.message = Some(message);
+}
+
+/// 执行批处理中的一项，结果写回 `item`
+fn run_item(app: &tauri::AppHandle, batch_id: &str, record: &mut BatchRecord, index: usize, partial_config: ProcessConfig) {
+    let paths = app.state::<AppPaths>();
+    let registry = app.state::<JobRegistry>();
+    let mut config = match config::effective_config(&paths, partial_config) {
+        Ok(config) => config,
+        Err(e) => return fail(&mut record.items[index], e.to_string()),
+    };
+    let prefix_warning = match config::apply_prefix_lock(&paths, &mut config) {
+        Ok(warning) => warning,
+        Err(e) => return fail(&mut record.items[index], e.to_string()),
+    };
+    let plan = match pipeline::plan_package(&config) {
+        Ok(plan) => plan,
+        Err(message) => return fail(&mut record.items[index], message),
+    };
+    if let Some(refused) = policy::gate(&app.state::<PolicyState>(), &config, &plan) {
+        record.items[index].step = refused.step;
+        return fail(&mut record.items[index], refused.message);
+    }
+    if let Some(collision) = collision::gate(&paths, &app.state::<DeviceCache>(), &config, &plan, false) {
+        record.items[index].step = collision.step;
+        return fail(&mut record.items[index], collision.message);
+    }
+    // 批处理无法逐项确认，证书策略冲突的项直接标记为失败
+    match key_policy::gate(&paths, &app.state::<ConfirmationTokens>(), &config, &plan, None) {
+        Ok(None) => {}
+        Ok(Some(pending)) => {
+            record.items[index].step = pending.step;
+            return fail(&mut record.items[index], pending.message);
+        }
+        Err(e) => return fail(&mut record.items[index], e),
+    }
+    let fingerprint = match jobs::fingerprint(&config) {
+        Ok(fingerprint) => fingerprint,
+        Err(e) => return fail(&mut record.items[index], e),
+    };
+    let job_id = match registry.admit(&fingerprint, &config.apk_path, false, Some(batch_id)) {
+        jobs::Admission::Started(id) => id,
+        jobs::Admission::Duplicate(prior) => {
+            let item = &mut record.items[index];
+            item.status = JobStatus::Duplicate;
+            item.result_status = Some(ResultStatus::Skipped);
+            item.job_id = prior.duplicate_of;
+            item.message = Some(prior.message);
+            item.output_path = prior.output_path;
+            return;
+        }
+    };
+
+    record.items[index].job_id = Some(job_id.clone());
+    record.items[index].status = JobStatus::Running;
+    save_and_emit(app, record);
+
+    let result = crate::execute_job(app, &paths, config, &plan, prefix_warning.as_deref(), &job_id, Some(batch_id));
+    registry.finish(&fingerprint, &job_id, result.as_ref().ok());
+    let item = &mut record.items[index];
+    match result {
+        Ok(r) => {
+            item.status = jobs::status_of(&r);
+            item.result_status = Some(r.status);
+            item.message = Some(r.message);
+            item.output_path = r.output_path.filter(|_| r.success);
+            item.step = r.step.filter(|_| !r.success);
+            item.warnings = r.warnings;
+        }
+        Err(e) => fail(item, e),
+    }
+}
+
+/// 依次处理多个 APK（如同一 APK 的多个前缀变体）。返回前推送 `job-status` 事件告知批处理 ID，
+/// 可用 `cancel_job` 取消整个批处理或其中一项；取消批处理时已完成的输出与记录保留，未开始的项不再执行
+#[tauri::command]
+pub async fn process_apk_batch(app: tauri::AppHandle, items: Vec<ProcessConfig>) -> Result<BatchRecord, String> {
+    if items.is_empty() {
+        return Err("批处理中没有任何 APK".to_string());
+    }
+    let registry = app.state::<JobRegistry>();
+    let (batch_id, cancel) = registry.start_batch();
+    let mut record = BatchRecord {
+        id: batch_id.clone(),
+        created_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+        status: JobStatus::Running,
+        items: items
+            .iter()
+            .map(|c| BatchItem {
+                apk_path: c.apk_path.clone(),
+                job_id: None,
+                status: JobStatus::Queued,
+                message: None,
+                output_path: None,
+                step: None,
+                warnings: Vec::new(),
+                result_status: None,
+            })
+            .collect(),
+        summary: Vec::new(),
+    };
+    save_and_emit(&app, &record);
+    jobs::emit_status(&app, &batch_id, None, JobStatus::Running, None);
+
+    for (index, partial_config) in items.into_iter().enumerate() {
+        if cancel.load(Ordering::Relaxed) {
+            break;
+        }
+        run_item(&app, &batch_id, &mut record, index, partial_config);
+        save_and_emit(&app, &record);
+    }
+
+    let cancelled = cancel.load(Ordering::Relaxed);
+    for item in record.items.iter_mut().filter(|i| i.status == JobStatus::Queued) {
+        item.status = JobStatus::Cancelled;
+        item.result_status = Some(ResultStatus::Cancelled);
+        item.message = Some("批处理已取消，未开始".to_string());
+    }
+    record.status = if cancelled {
+        JobStatus::Cancelled
+    } else if record.items.iter().all(|i| matches!(i.status, JobStatus::Succeeded | JobStatus::Duplicate)) {
+        JobStatus::Succeeded
+    } else {
+        JobStatus::Failed
+    };
+    record.summary = summarize(&record.items);
+    registry.finish_batch(&batch_id);
+    save_and_emit(&app, &record);
+    jobs::emit_status(&app, &batch_id, None, record.status, None);
+    crate::audit::record_outcome(
+        &app,
+        "process_apk_batch",
+        serde_json::json!({ "job_id": batch_id, "items": record.items.len() }),
+        record.status == JobStatus::Succeeded,
+        Some(format!("{:?}", record.status)),
+    );
+    Ok(record)
+}
+
+/// 将批处理报告（摘要与各项详情）写入文本文件
+#[tauri::command]
+pub fn export_batch_report(paths: tauri::State<'_, AppPaths>, batch_id: String, path: String) -> Result<(), String> {
+    let path = input_path::arg(&path, Expect::Any).map_err(|e| e.to_string())?;
+    let record = load_all(&paths).into_iter().find(|r| r.id == batch_id).ok_or_else(|| format!("找不到批处理 {}", batch_id))?;
+    fs::write(&path, render_report(&record)).map_err(|e| e.to_string())
+}
+
+/// 最近的批处理记录，从新到旧
+#[tauri::command]
+pub fn list_batches(paths: tauri::State<'_, AppPaths>) -> Vec<BatchRecord> {
+    let mut records = load_all(&paths);
+    records.reverse();
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(apk_path: &str, status: JobStatus, step: Option<&str>, warnings: &[&str]) -> BatchItem {
+        BatchItem {
+            apk_path: apk_path.to_string(),
+            job_id: None,
+            status,
+            message: step.map(|s| format!("{} 失败: {}", s, apk_path)),
+            output_path: None,
+            step: step.map(str::to_string),
+            warnings: warnings.iter().map(|w| w.to_string()).collect(),
+            result_status: None,
+        }
+    }
+
+    #[test]
+    fn normalize_removes_item_specific_parts() {
+        let a = normalize("/in/scanner.apk: 资源 scanner_icon 有 12 个重复", "/in/scanner.apk");
+        let b = normalize("/in/pos.apk: 资源 pos_icon 有 3 个重复", "/in/pos.apk");
+        assert_eq!(a, "{apk}: 资源 {name}_icon 有 # 个重复");
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn groups_failures_by_step_before_warnings() {
+        let items = vec![
+            item("/in/scanner.apk", JobStatus::Failed, Some("build"), &["scanner.apk 缺少 3 个签名文件"]),
+            item("/in/pos.apk", JobStatus::Succeeded, None, &["pos.apk 缺少 5 个签名文件", "pos 的 minSdk 过低"]),
+            item("/in/kiosk.apk", JobStatus::Failed, Some("build"), &[]),
+            item("/in/label.apk", JobStatus::Interrupted, None, &["label.apk 缺少 1 个签名文件"]),
+        ];
+        let digest = summarize(&items);
+        let keys: Vec<(&str, &str, Vec<usize>)> = digest.iter().map(|g| (g.kind.as_str(), g.code.as_str(), g.items.clone())).collect();
+        assert_eq!(
+            keys,
+            vec![
+                ("failure", "build", vec![0, 2]),
+                ("failure", "interrupted", vec![3]),
+                ("warning", "{name}.apk 缺少 # 个签名文件", vec![0, 1, 3]),
+                ("warning", "{name} 的 minSdk 过低", vec![1]),
+            ]
+        );
+        assert_eq!(digest[0].example, "build 失败: /in/scanner.apk");
+        assert_eq!(digest[0].summary, "2/4 项: build 步骤失败");
+        assert_eq!(digest[2].example, "scanner.apk 缺少 3 个签名文件");
+        assert_eq!(digest[2].summary, "3/4 项: scanner.apk 缺少 3 个签名文件");
+    }
+
+    #[test]
+    fn repeated_warnings_in_one_item_count_once() {
+        let items = vec![item("/in/scanner.apk", JobStatus::Succeeded, None, &["第 1 行有误", "第 2 行有误"])];
+        let digest = summarize(&items);
+        assert_eq!(digest.len(), 1);
+        assert_eq!(digest[0].items, vec![0]);
+        assert_eq!(digest[0].summary, "1/1 项: 第 1 行有误");
+    }
+
+    #[test]
+    fn clean_batch_has_no_digest() {
+        let items = vec![item("/in/scanner.apk", JobStatus::Succeeded, None, &[]), item("/in/pos.apk", JobStatus::Queued, None, &[])];
+        assert!(summarize(&items).is_empty());
+    }
+}
@@ -0,0 +1,155 @@
+//! 设备能力探测。新旧设备上可用的命令不同（`cmd package` 与 `pm`、`exec-out` 与 `shell`、安装会话、`logcat --pid`），
+//! 首次使用某台设备时探测一次并缓存在会话内，需要在新旧实现之间选择的功能统一据此判断，并在日志中记录选择的方式。
+
+use serde::{Deserialize, Serialize};
+
+use crate::adb;
+use crate::device_cache::{self, DeviceCache};
+
+/// 一次 shell 调用内完成的探测：`cmd` 是否存在、`ls` 由哪个工具集提供、`cmd package` 能否调用
+const PROBE_SCRIPT: &str = "echo cmd=$(command -v cmd); echo ls=$(readlink /system/bin/ls); echo pkg=$(cmd package path android 2>&1)";
+
+/// 设备 shell 的基础命令集
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "snake_case")]
+pub enum Userland {
+    /// Android 6 起
+    Toybox,
+    /// Android 5 及更早
+    Toolbox,
+    Busybox,
+    Unknown,
+}
+
+/// 设备支持的功能
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct DeviceCapabilities {
+    pub sdk: Option<u32>,
+    /// `adb features` 列出的功能（如 `shell_v2`、`cmd`、`abb_exec`）
+    pub adb_features: Vec<String>,
+    /// 设备上有 `cmd` 命令（Android 7 起）
+    pub has_cmd: bool,
+    /// `cmd package` 可以调用；部分定制系统有 `cmd` 但限制了 package 服务
+    pub cmd_package: bool,
+    pub exec_out: bool,
+    /// 支持 `pm install-create` 安装会话
+    pub install_sessions: bool,
+    /// 支持 `logcat --pid`
+    pub logcat_pid: bool,
+    pub userland: Userland,
+}
+
+fn marker<'a>(probe: &'a str, key: &str) -> Option<&'a str> {
+    probe.lines().find_map(|line| line.trim().strip_prefix(key)).map(str::trim)
+}
+
+/// 由 `adb features`、`ro.build.version.sdk` 与探测脚本的输出得出设备能力
+pub fn parse_capabilities(features: &str, sdk: &str, probe: &str) -> DeviceCapabilities {
+    let sdk: Option<u32> = sdk.trim().parse().ok();
+    let adb_features: Vec<String> = features.split([',', '\n']).map(str::trim).filter(|f| !f.is_empty()).map(str::to_string).collect();
+    let has_cmd = marker(probe, "cmd=").is_some_and(|path| !path.is_empty());
+    let cmd_package = has_cmd && marker(probe, "pkg=").is_some_and(|out| out.starts_with("package:"));
+    let userland = match marker(probe, "ls=").unwrap_or_default() {
+        ls if ls.contains("toybox") => Userland::Toybox,
+        ls if ls.contains("toolbox") => Userland::Toolbox,
+        ls if ls.contains("busybox") => Userland::Busybox,
+        _ => Userland::Unknown,
+    };
+    let at_least = |level: u32| sdk.is_some_and(|sdk| sdk >= level);
+    DeviceCapabilities {
+        sdk,
+        exec_out: at_least(21) || adb_features.iter().any(|f| f == "shell_v2"),
+        install_sessions: at_least(21),
+        logcat_pid: at_least(24),
+        adb_features,
+        has_cmd,
+        cmd_package,
+        userland,
+    }
+}
+
+fn probe(device_id: &str) -> Result<DeviceCapabilities, String> {
+    // 旧版 adb 没有 features 子命令，此时按空列表处理
+    let features = adb::command()
+        .args(["-s", device_id, "features"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+        .unwrap_or_default();
+    let sdk = adb::shell(device_id, &["getprop", "ro.build.version.sdk"])?;
+    let probe = adb::shell(device_id, &[PROBE_SCRIPT]).unwrap_or_default();
+    Ok(parse_capabilities(&features, &sdk, &probe))
+}
+
+/// 设备能力，首次使用时探测并写入会话缓存
+pub fn for_device(cache: &DeviceCache, device_id: &str) -> Result<DeviceCapabilities, String> {
+    if let Some(capabilities) = cache.get(device_id, device_cache::DATASET_CAPABILITIES) {
+        return Ok(capabilities);
+    }
+    let capabilities = probe(device_id)?;
+    cache.put(device_id, device_cache::DATASET_CAPABILITIES, &capabilities);
+    Ok(capabilities)
+}
+
+/// 设备能力，用于设备详情面板
+#[tauri::command]
+pub fn get_device_capabilities(cache: tauri::State<'_, DeviceCache>, device_id: String) -> Result<DeviceCapabilities, String> {
+    for_device(&cache, &device_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn android_5_uses_legacy_paths() {
+        let probe = "cmd=\nls=toolbox\npkg=/system/bin/sh: cmd: not found\n";
+        let capabilities = parse_capabilities("", "21\n", probe);
+        assert_eq!(
+            capabilities,
+            DeviceCapabilities {
+                sdk: Some(21),
+                adb_features: Vec::new(),
+                has_cmd: false,
+                cmd_package: false,
+                exec_out: true,
+                install_sessions: true,
+                logcat_pid: false,
+                userland: Userland::Toolbox,
+            }
+        );
+    }
+
+    #[test]
+    fn android_14_supports_everything() {
+        let features = "shell_v2,cmd,stat_v2,ls_v2,fixed_push_mkdir,apex,abb,abb_exec,remount_shell,track_app,sendrecv_v2\n";
+        let probe = "cmd=/system/bin/cmd\nls=toybox\npkg=package:/system/framework/framework-res.apk\n";
+        let capabilities = parse_capabilities(features, "34", probe);
+        assert_eq!(capabilities.sdk, Some(34));
+        assert_eq!(capabilities.adb_features.len(), 11);
+        assert!(capabilities.adb_features.iter().any(|f| f == "abb_exec"));
+        assert!(capabilities.has_cmd && capabilities.cmd_package);
+        assert!(capabilities.exec_out && capabilities.install_sessions && capabilities.logcat_pid);
+        assert_eq!(capabilities.userland, Userland::Toybox);
+    }
+
+    #[test]
+    fn restricted_package_service_falls_back_from_cmd_package() {
+        let probe = "cmd=/system/bin/cmd\nls=toybox\npkg=Security exception: Shell does not have permission to access package service\n";
+        let capabilities = parse_capabilities("shell_v2,cmd", "29", probe);
+        assert!(capabilities.has_cmd);
+        assert!(!capabilities.cmd_package);
+        assert!(capabilities.logcat_pid);
+    }
+
+    #[test]
+    fn unreadable_sdk_disables_level_gated_features() {
+        let capabilities = parse_capabilities("", "", "ls=/system/xbin/busybox\n");
+        assert_eq!(capabilities.sdk, None);
+        assert!(!capabilities.exec_out && !capabilities.install_sessions && !capabilities.logcat_pid);
+        assert_eq!(capabilities.userland, Userland::Busybox);
+    }
+}
@@ -0,0 +1,268 @@
+//! 新包名与历史分配的冲突检查。默认后缀由截断的文件名生成，`scanner_v2.apk` 与 `scanner_v3.apk`
+//! 都会得到 `前缀.scannerv`，第二个应用会沿用第一个应用的包名身份，在共用的设备上覆盖它。
+//! 这里的检查与候选后缀生成只依赖传入的历史记录，不访问设备或文件。
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::device_cache::{self, DeviceCache};
+use crate::incremental::{self, RunRecord};
+use crate::paths::AppPaths;
+use crate::pipeline::{PackagePlan, ProcessConfig};
+use crate::{adb, pm, AppInfo, ProcessResult};
+
+/// 默认给出的候选后缀数量
+pub const SUGGESTION_COUNT: usize = 3;
+
+/// 新包名曾分配给的另一个应用
+#[derive(Debug, Clone, PartialEq)]
+pub struct Collision {
+    /// 历史记录中的新包名（大小写以历史记录为准）
+    pub package: String,
+    /// 当时的原包名
+    pub original_package: String,
+    pub timestamp: u64,
+}
+
+/// 查找新包名是否曾分配给不同的原包名（不区分大小写）；原包名未知（如加固）时无法判断，不视为冲突
+pub fn find_collision(history: &[RunRecord], new_package: &str, original_package: Option<&str>) -> Option<Collision> {
+    let original = original_package?;
+    history
+        .iter()
+        .filter(|r| r.package.eq_ignore_ascii_case(new_package))
+        .filter_map(|r| {
+            let previous = r.original_package.as_deref()?;
+            (previous != original).then(|| Collision {
+                package: r.package.clone(),
+                original_package: previous.to_string(),
+                timestamp: r.timestamp,
+            })
+        })
+        .max_by_key(|c| c.timestamp)
+}
+
+/// 只保留小写字母和数字，与默认后缀的规则一致
+fn clean(text: &str) -> String {
+    text.to_lowercase().chars().filter(|c| c.is_alphanumeric()).collect()
+}
+
+/// 生成不与历史记录中其他应用、也不与设备上已安装的包冲突的候选后缀。
+/// 依次尝试：未截断的文件名、后缀加原包名最后一段、后缀加序号
+pub fn suggest_suffixes(
+    apk_path: &str,
+    prefix: &str,
+    base_suffix: &str,
+    original_package: Option<&str>,
+    history: &[RunRecord],
+    device_packages: &[String],
+    count: usize,
+) -> Vec<String> {
+    let device: HashSet<String> = device_packages.iter().map(|p| p.to_lowercase()).collect();
+    let file_stem = Path::new(apk_path).file_stem().and_then(|s| s.to_str()).map(clean).unwrap_or_default();
+    let original_tail = original_package.and_then(|p| p.rsplit('.').next()).map(clean).unwrap_or_default();
+
+    let mut candidates = vec![file_stem];
+    if !original_tail.is_empty() && !base_suffix.ends_with(&original_tail) {
+        candidates.push(format!("{}{}", base_suffix, original_tail));
+    }
+    candidates.extend((2..).take(count * 10).map(|n| format!("{}{}", base_suffix, n)));
+
+    let mut suggestions: Vec<String> = Vec::new();
+    for suffix in candidates {
+        if suggestions.len() >= count {
+            break;
+        }
+        if suffix.is_empty() || suffix == base_suffix || suggestions.contains(&suffix) {
+            continue;
+        }
+        let package = format!("{}.{}", prefix, suffix);
+        if !pm::is_package_name(&package)
+            || device.contains(&package.to_lowercase())
+            || find_collision(history, &package, original_package).is_some()
+        {
+            continue;
+        }
+        // 历史中已分配给任何应用的包名也不推荐（原包名未知时同样避开）
+        if original_package.is_none() && history.iter().any(|r| r.package.eq_ignore_ascii_case(&package)) {
+            continue;
+        }
+        suggestions.push(suffix);
+    }
+    suggestions
+}
+
+/// 所选设备上已安装的包名：优先用会话缓存，没有缓存时查询设备
+fn device_packages(cache: &DeviceCache, device_id: Option<&str>) -> Vec<String> {
+    let Some(device_id) = device_id.filter(|d| !d.is_empty()) else {
+        return Vec::new();
+    };
+    if let Some(apps) = cache.get::<Vec<AppInfo>>(device_id, device_cache::DATASET_INSTALLED_APPS) {
+        return apps.into_iter().map(|a| a.package_name).collect();
+    }
+    adb::shell(device_id, &["pm", "list", "packages"])
+        .map(|output| pm::parse_package_list(&output).into_iter().map(|line| line.package).collect())
+        .unwrap_or_default()
+}
+
+/// 处理前检查新包名是否曾分配给其他应用；冲突且未允许时返回带候选后缀的失败结果
+pub fn gate(paths: &AppPaths, cache: &DeviceCache, config: &ProcessConfig, plan: &PackagePlan, allow: bool) -> Option<ProcessResult> {
+    if allow {
+        return None;
+    }
+    let history = incremental::load_records(paths);
+    let collision = find_collision(&history, &plan.new_package, plan.original_package.as_deref())?;
+    let base_suffix = plan
+        .new_package
+        .strip_prefix(&format!("{}.", config.new_prefix))
+        .unwrap_or(&plan.new_package)
+        .to_string();
+    let devices = device_packages(cache, config.device_id.as_deref());
+    let suggested_suffixes = suggest_suffixes(
+        &config.apk_path,
+        &config.new_prefix,
+        &base_suffix,
+        plan.original_package.as_deref(),
+        &history,
+        &devices,
+        SUGGESTION_COUNT,
+    );
+    let mut message = format!(
+        "新包名 {} 已分配给另一个应用 {}，继续会覆盖设备上的该应用",
+        collision.package, collision.original_package
+    );
+    if !suggested_suffixes.is_empty() {
+        message.push_str(&format!("；可改用后缀: {}", suggested_suffixes.join("、")));
+    }
+    Some(ProcessResult {
+        success: false,
+        message,
+        step: Some("package_collision".to_string()),
+        new_package: Some(plan.new_package.clone()),
+        suggested_suffixes,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::fs;
+
+    fn run(package: &str, original: Option<&str>, timestamp: u64) -> RunRecord {
+        serde_json::from_value(json!({
+            "source_sha256": "00",
+            "options": {},
+            "package": package,
+            "output_path": "/out/x.apk",
+            "output_sha256": "00",
+            "timestamp": timestamp,
+            "original_package": original,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn collision_needs_a_different_known_original() {
+        let history = vec![
+            run("com.corp.scannerdevic", Some("com.vendor.scanner.v2"), 10),
+            run("com.corp.scannerdevic", Some("com.vendor.scanner.v3"), 20),
+            run("com.corp.unknown", None, 30),
+        ];
+        let collision = find_collision(&history, "COM.corp.ScannerDevic", Some("com.vendor.scanner.v1")).unwrap();
+        assert_eq!(collision.package, "com.corp.scannerdevic");
+        assert_eq!(collision.original_package, "com.vendor.scanner.v3");
+        assert_eq!(collision.timestamp, 20);
+
+        let same = vec![run("com.corp.scannerdevic", Some("com.vendor.scanner.v2"), 10)];
+        assert_eq!(find_collision(&same, "com.corp.scannerdevic", Some("com.vendor.scanner.v2")), None);
+        assert_eq!(find_collision(&history, "com.corp.scannerdevic", None), None);
+        assert_eq!(find_collision(&history, "com.corp.unknown", Some("com.vendor.pos")), None);
+        assert_eq!(find_collision(&history, "com.corp.other", Some("com.vendor.pos")), None);
+    }
+
+    #[test]
+    fn suggestions_prefer_the_full_file_name_then_the_original_tail() {
+        let history = vec![run("com.corp.scannerdevic", Some("com.vendor.scanner.v2"), 10)];
+        let suggestions = suggest_suffixes(
+            "/in/Scanner_Device_v3.apk",
+            "com.corp",
+            "scannerdevic",
+            Some("com.vendor.Scanner.V3"),
+            &history,
+            &[],
+            SUGGESTION_COUNT,
+        );
+        assert_eq!(suggestions, ["scannerdevicev3", "scannerdevicv3", "scannerdevic2"]);
+    }
+
+    #[test]
+    fn suggestions_skip_device_packages_and_history() {
+        let history = vec![
+            run("com.corp.scannerdevicev3", Some("com.vendor.scanner.v2"), 10),
+            run("com.corp.scannerdevic2", Some("com.vendor.pos"), 20),
+        ];
+        let device = vec!["COM.CORP.SCANNERDEVICV3".to_string(), "com.corp.scannerdevic3".to_string()];
+        let suggestions = suggest_suffixes(
+            "/in/scanner_device_v3.apk",
+            "com.corp",
+            "scannerdevic",
+            Some("com.vendor.scanner.v3"),
+            &history,
+            &device,
+            2,
+        );
+        assert_eq!(suggestions, ["scannerdevic4", "scannerdevic5"]);
+    }
+
+    #[test]
+    fn unknown_original_avoids_every_assigned_package() {
+        let history = vec![run("com.corp.scannerdevicev3", None, 10), run("com.corp.scannerdevic2", Some("com.vendor.pos"), 20)];
+        let suggestions = suggest_suffixes("/in/scanner_device_v3.apk", "com.corp", "scannerdevic", None, &history, &[], 2);
+        assert_eq!(suggestions, ["scannerdevic3", "scannerdevic4"]);
+    }
+
+    #[test]
+    fn invalid_candidates_are_skipped() {
+        // 文件名中的非 ASCII 字符会保留下来，得到不合法的包名
+        let suggestions = suggest_suffixes("/in/扫描器.apk", "com.corp", "scanner", Some("com.vendor.扫描"), &[], &[], 2);
+        assert_eq!(suggestions, ["scanner2", "scanner3"]);
+    }
+
+    #[test]
+    fn gate_reports_the_collision_with_suggestions() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = AppPaths::portable_at(dir.path());
+        fs::create_dir_all(&paths.data_dir).unwrap();
+        let line = serde_json::to_string(&run("com.corp.scannerdevic", Some("com.vendor.scanner.v2"), 10)).unwrap();
+        fs::write(paths.data_dir.join("runs.jsonl"), format!("{}\n", line)).unwrap();
+
+        let cache = DeviceCache::default();
+        let installed = json!([{ "package_name": "com.corp.scannerdevicev3", "app_name": "", "version": "", "is_system": false, "uid": null }]);
+        cache.put("dev-1", device_cache::DATASET_INSTALLED_APPS, &installed);
+        let config: ProcessConfig = serde_json::from_value(json!({
+            "apk_path": "/in/scanner_device_v3.apk",
+            "new_prefix": "com.corp",
+            "install_after": false,
+            "java_path": "",
+            "apktool_path": "",
+            "zipalign_path": "",
+            "apksigner_path": "",
+            "keystore_path": "",
+            "device_id": "dev-1",
+        }))
+        .unwrap();
+        let plan = PackagePlan { original_package: Some("com.vendor.scanner.v3".to_string()), new_package: "com.corp.scannerdevic".to_string() };
+
+        let result = gate(&paths, &cache, &config, &plan, false).unwrap();
+        assert!(!result.success);
+        assert_eq!(result.step.as_deref(), Some("package_collision"));
+        assert_eq!(result.suggested_suffixes, ["scannerdevicv3", "scannerdevic2", "scannerdevic3"]);
+        assert!(result.message.contains("com.vendor.scanner.v2"));
+        assert!(result.message.ends_with("可改用后缀: scannerdevicv3、scannerdevic2、scannerdevic3"));
+
+        assert!(gate(&paths, &cache, &config, &plan, true).is_none());
+        let same = PackagePlan { original_package: Some("com.vendor.scanner.v2".to_string()), ..plan };
+        assert!(gate(&paths, &cache, &config, &same, false).is_none());
+    }
+}
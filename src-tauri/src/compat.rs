@@ -0,0 +1,371 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use crate::axml;
+use crate::input_path::{self, Expect};
+use crate::signing::PipelineError;
+
+/// 引入版本高于声明 minSdk 的 API 调用
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct ApiUsage {
+    /// smali 类型描述符，如 `Landroid/app/NotificationChannel;`
+    pub class_name: String,
+    /// 方法或字段名
+    pub method: String,
+    pub min_sdk_required: u32,
+}
+
+/// 按代码实际使用的 API 推断的最低系统版本
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct ActualMinSdkInfo {
+    pub declared_min_sdk: u32,
+    pub detected_min_sdk: u32,
+    pub api_calls_requiring_higher: Vec<ApiUsage>,
+}
+
+/// 已知 API 的引入版本：(类型描述符, 方法或字段名，`*` 表示该类型的任意成员, API 级别)
+const API_LEVELS: &[(&str, &str, u32)] = &[
+    ("Landroid/os/Build$VERSION_CODES;", "O", 26),
+    ("Landroid/os/Build$VERSION_CODES;", "P", 28),
+    ("Landroid/os/Build$VERSION_CODES;", "Q", 29),
+    ("Landroid/os/Build$VERSION_CODES;", "R", 30),
+    ("Landroid/os/Build$VERSION_CODES;", "S", 31),
+    ("Landroid/os/Build$VERSION_CODES;", "TIRAMISU", 33),
+    ("Landroid/os/Build$VERSION_CODES;", "UPSIDE_DOWN_CAKE", 34),
+    ("Landroid/app/job/JobScheduler;", "*", 21),
+    ("Landroid/net/NetworkCapabilities;", "*", 21),
+    ("Landroid/content/Context;", "checkSelfPermission", 23),
+    ("Landroid/app/Activity;", "requestPermissions", 23),
+    ("Landroid/security/keystore/KeyGenParameterSpec;", "*", 23),
+    ("Landroid/security/keystore/KeyGenParameterSpec$Builder;", "*", 23),
+    ("Landroid/graphics/drawable/Icon;", "*", 23),
+    ("Landroid/app/PendingIntent;", "FLAG_IMMUTABLE", 23),
+    ("Landroid/net/ConnectivityManager;", "registerDefaultNetworkCallback", 24),
+    ("Ljava/util/Optional;", "*", 24),
+    ("Ljava/util/stream/Stream;", "*", 24),
+    ("Landroid/app/NotificationChannel;", "*", 26),
+    ("Landroid/app/NotificationManager;", "createNotificationChannel", 26),
+    ("Landroid/content/Context;", "startForegroundService", 26),
+    ("Landroid/os/VibrationEffect;", "*", 26),
+    ("Landroid/view/autofill/AutofillManager;", "*", 26),
+    ("Ljava/time/LocalDate;", "*", 26),
+    ("Ljava/time/Instant;", "*", 26),
+    ("Landroid/hardware/biometrics/BiometricPrompt;", "*", 28),
+    ("Landroid/hardware/biometrics/BiometricPrompt$Builder;", "*", 28),
+    ("Landroid/view/WindowInsets$Type;", "*", 30),
+    ("Landroid/window/OnBackInvokedDispatcher;", "*", 33),
+    ("Landroid/content/pm/PackageManager$PackageInfoFlags;", "*", 33),
+];
+
+/// 将显式指定的 minSdk / targetSdk 写入 apktool.yml 与 Manifest 中已有的 `<uses-sdk>`
+pub fn apply_sdk_override(work_dir: &Path, min_sdk: Option<u32>, target_sdk: Option<u32>) -> Result<(), String> {
+    if min_sdk.is_none() && target_sdk.is_none() {
+        return Ok(());
+    }
+
+    let yml_path = work_dir.join("apktool.yml");
+    let mut yml = fs::read_to_string(&yml_path).map_err(|e| format!("读取 apktool.yml 失败: {}", e))?;
+    for (key, value) in [("minSdkVersion", min_sdk), ("targetSdkVersion", target_sdk)] {
+        let Some(value) = value else { continue };
+        let re = regex::Regex::new(&format!(r"(?m)^(\s*{}:\s*)'?\d+'?\s*$", key)).unwrap();
+        if re.is_match(&yml) {
+            yml = re.replace(&yml, format!("${{1}}'{}'", value).as_str()).to_string();
+        } else if let Some(pos) = yml.find("sdkInfo:\n") {
+            yml.insert_str(pos + "sdkInfo:\n".len(), &format!("  {}: '{}'\n", key, value));
+        } else {
+            yml.push_str(&format!("sdkInfo:\n  {}: '{}'\n", key, value));
+        }
+    }
+    fs::write(&yml_path, yml).map_err(|e| format!("写入 apktool.yml 失败: {}", e))?;
+
+    let manifest_path = work_dir.join("AndroidManifest.xml");
+    let mut manifest = fs::read_to_string(&manifest_path).map_err(|e| format!("读取 Manifest 失败: {}", e))?;
+    for (name, value) in [("minSdkVersion", min_sdk), ("targetSdkVersion", target_sdk)] {
+        let Some(value) = value else { continue };
+        let attr = crate::manifest::android_attr(&manifest, name);
+        let re = regex::Regex::new(&format!(r#"(<uses-sdk[^>]*\b{}=")[^"]*""#, regex::escape(&attr))).unwrap();
+        manifest = re.replace(&manifest, format!("${{1}}{}\"", value).as_str()).to_string();
+        // <uses-sdk> 上有该属性却没有按实际前缀改到，说明命名空间解析有误
+        let declared = regex::Regex::new(&format!(r#"<uses-sdk[^>]*:{}=""#, name)).unwrap().is_match(&manifest);
+        let applied = regex::Regex::new(&format!(r#"<uses-sdk[^>]*\b{}="{}""#, regex::escape(&attr), value)).unwrap().is_match(&manifest);
+        if declared && !applied {
+            return Err(format!("Manifest 中 <uses-sdk> 的 {} 未能修改为 {}", name, value));
+        }
+    }
+    fs::write(&manifest_path, manifest).map_err(|e| format!("写入 Manifest 失败: {}", e))
+}
+
+/// 读取设备的 SDK 版本
+fn device_sdk(device_id: &str) -> Option<i64> {
+    crate::adb::shell(device_id, &["getprop", "ro.build.version.sdk"]).ok()?.trim().parse().ok()
+}
+
+/// 当前连接的设备中 SDK 低于指定版本的设备
+fn devices_below(floor: i64) -> Vec<String> {
+    crate::adb::devices()
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|id| device_sdk(&id).filter(|sdk| *sdk < floor).map(|sdk| format!("{} (API {})", id, sdk)))
+        .collect()
+}
+
+/// 比较输出 APK 与原 APK 的 minSdk。
+/// 输出抬高了最低版本且未显式指定时返回 Err；显式指定时返回警告
+pub fn check_min_sdk(original_apk: &Path, output_apk: &Path, override_requested: bool) -> Result<Option<String>, String> {
+    compare_min_sdk(original_apk, output_apk, override_requested, devices_below)
+}
+
+/// `devices_below` 按新的最低版本列出无法安装的已连接设备
+fn compare_min_sdk(
+    original_apk: &Path,
+    output_apk: &Path,
+    override_requested: bool,
+    devices_below: impl FnOnce(i64) -> Vec<String>,
+) -> Result<Option<String>, String> {
+    let original = axml::uses_sdk_of_apk(original_apk)?.as_ref().and_then(axml::min_sdk_of).unwrap_or(1);
+    let Some(output_sdk) = axml::uses_sdk_of_apk(output_apk)? else {
+        return Ok(None);
+    };
+    let attrs: Vec<String> = output_sdk.attributes.iter().map(|a| format!("{}={}", a.name, a.value.as_string())).collect();
+    let output = axml::min_sdk_of(&output_sdk).unwrap_or(1);
+    if output <= original {
+        return Ok(None);
+    }
+
+    if !override_requested {
+        return Err(format!(
+            "输出 APK 的 minSdkVersion 从 {} 提高到了 {}（uses-sdk: {}），旧设备将无法安装 (INSTALL_FAILED_OLDER_SDK)。如确需提高，请显式指定 minSdk",
+            original,
+            output,
+            attrs.join(" ")
+        ));
+    }
+
+    let below = devices_below(output);
+    let mut warning = format!("minSdkVersion 已按指定从 {} 提高到 {}", original, output);
+    if !below.is_empty() {
+        warning.push_str(&format!("，以下已连接设备将无法安装: {}", below.join(", ")));
+    }
+    Ok(Some(warning))
+}
+
+/// 从 apktool.yml 的 sdkInfo 读取声明的 minSdkVersion，缺失时为 1
+fn declared_min_sdk(work_dir: &Path) -> u32 {
+    let re = regex::Regex::new(r"(?m)^\s*minSdkVersion:\s*'?(\d+)'?\s*$").unwrap();
+    fs::read_to_string(work_dir.join("apktool.yml"))
+        .ok()
+        .and_then(|yml| re.captures(&yml).and_then(|c| c[1].parse().ok()))
+        .unwrap_or(1)
+}
+
+fn required_api(class_name: &str, member: &str) -> Option<u32> {
+    API_LEVELS
+        .iter()
+        .find(|(class, name, _)| *class == class_name && (*name == "*" || *name == member))
+        .map(|(_, _, level)| *level)
+}
+
+/// 扫描 smali 中的方法调用与字段访问，对照已知 API 的引入版本推断实际需要的最低版本。
+/// 与 Lint 不同，这里不识别 `SDK_INT` 判断保护的分支，结果偏保守
+#[tauri::command]
+pub fn detect_actual_min_sdk(work_dir: String) -> Result<ActualMinSdkInfo, PipelineError> {
+    let work_dir = input_path::arg(&work_dir, Expect::Dir)?;
+    let root = Path::new(&work_dir);
+    if !root.join("AndroidManifest.xml").exists() {
+        return Err(PipelineError::InvalidInput(format!("{} 不是反编译后的工作目录", work_dir)));
+    }
+    let declared = declared_min_sdk(root);
+    let re = regex::Regex::new(r"(?m)^\s*(?:invoke-\S+ \{[^}]*\}|[si](?:get|put)\S* [vp]\d+(?:, [vp]\d+)?), (L[^;\s]+;)->([\w$<>]+)").unwrap();
+
+    let mut found = BTreeSet::new();
+    for entry in fs::read_dir(root)?.filter_map(|e| e.ok()) {
+        if !entry.file_name().to_string_lossy().starts_with("smali") {
+            continue;
+        }
+        for file in walkdir::WalkDir::new(entry.path()).into_iter().filter_map(|e| e.ok()) {
+            if file.path().extension().map(|e| e != "smali").unwrap_or(true) {
+                continue;
+            }
+            let Ok(content) = fs::read_to_string(file.path()) else {
+                continue;
+            };
+            for cap in re.captures_iter(&content) {
+                if let Some(level) = required_api(&cap[1], &cap[2]).filter(|level| *level > declared) {
+                    found.insert((level, cap[1].to_string(), cap[2].to_string()));
+                }
+            }
+        }
+    }
+
+    let api_calls_requiring_higher: Vec<ApiUsage> = found
+        .into_iter()
+        .rev()
+        .map(|(min_sdk_required, class_name, method)| ApiUsage { class_name, method, min_sdk_required })
+        .collect();
+    let detected = api_calls_requiring_higher.iter().map(|u| u.min_sdk_required).max().unwrap_or(declared).max(declared);
+    Ok(ActualMinSdkInfo { declared_min_sdk: declared, detected_min_sdk: detected, api_calls_requiring_higher })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const STRINGS: [&str; 4] = ["minSdkVersion", "manifest", "uses-sdk", "application"];
+
+    enum Node {
+        Start(u32, Option<u32>),
+        End(u32),
+    }
+
+    fn chunk(kind: u16, header_size: u16, body: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&kind.to_le_bytes());
+        out.extend_from_slice(&header_size.to_le_bytes());
+        out.extend_from_slice(&(8 + body.len() as u32).to_le_bytes());
+        out.extend_from_slice(body);
+        out
+    }
+
+    fn words(values: &[u32]) -> Vec<u8> {
+        values.iter().flat_map(|v| v.to_le_bytes()).collect()
+    }
+
+    /// 生成二进制 Manifest：UTF-16 字符串池，`minSdkVersion` 映射到其资源 ID
+    fn axml(nodes: &[Node]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut offsets = Vec::new();
+        for s in STRINGS {
+            offsets.push(data.len() as u32);
+            let units: Vec<u16> = s.encode_utf16().collect();
+            data.extend_from_slice(&(units.len() as u16).to_le_bytes());
+            data.extend(units.iter().flat_map(|u| u.to_le_bytes()));
+            data.extend_from_slice(&[0, 0]);
+        }
+        while data.len() % 4 != 0 {
+            data.push(0);
+        }
+        let strings_start = 28 + 4 * STRINGS.len() as u32;
+        let mut pool = words(&[STRINGS.len() as u32, 0, 0, strings_start, 0]);
+        pool.extend(words(&offsets));
+        pool.extend(data);
+
+        let mut body = chunk(0x0001, 28, &pool);
+        body.extend(chunk(0x0180, 8, &words(&[axml::ATTR_MIN_SDK_VERSION])));
+        for node in nodes {
+            body.extend(match *node {
+                Node::Start(name, min_sdk) => {
+                    let count = min_sdk.is_some() as u32;
+                    let mut element = words(&[1, u32::MAX, u32::MAX, name, 0x0014_0014, count, 0]);
+                    if let Some(value) = min_sdk {
+                        element.extend(words(&[u32::MAX, 0, u32::MAX, 0x1000_0008, value]));
+                    }
+                    chunk(0x0102, 16, &element)
+                }
+                Node::End(name) => chunk(0x0103, 16, &words(&[1, u32::MAX, u32::MAX, name])),
+            });
+        }
+        chunk(0x0003, 8, &body)
+    }
+
+    fn apk(dir: &Path, name: &str, nodes: &[Node]) -> std::path::PathBuf {
+        let path = dir.join(name);
+        let mut writer = zip::ZipWriter::new(fs::File::create(&path).unwrap());
+        writer.start_file("AndroidManifest.xml", zip::write::SimpleFileOptions::default()).unwrap();
+        writer.write_all(&axml(nodes)).unwrap();
+        writer.finish().unwrap();
+        path
+    }
+
+    fn with_min_sdk(dir: &Path, name: &str, min_sdk: u32) -> std::path::PathBuf {
+        apk(dir, name, &[Node::Start(1, None), Node::Start(2, Some(min_sdk)), Node::End(2), Node::End(1)])
+    }
+
+    #[test]
+    fn silent_raise_fails_the_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let original = with_min_sdk(dir.path(), "original.apk", 17);
+        let output = with_min_sdk(dir.path(), "output.apk", 19);
+
+        let error = compare_min_sdk(&original, &output, false, |_| panic!("未指定时不应查询设备")).unwrap_err();
+        assert!(error.contains("从 17 提高到了 19"), "{}", error);
+        assert!(error.contains("minSdkVersion=19"));
+    }
+
+    #[test]
+    fn explicit_override_only_warns_and_names_old_devices() {
+        let dir = tempfile::tempdir().unwrap();
+        let original = with_min_sdk(dir.path(), "original.apk", 17);
+        let output = with_min_sdk(dir.path(), "output.apk", 21);
+
+        let warning = compare_min_sdk(&original, &output, true, |floor| {
+            assert_eq!(floor, 21);
+            vec!["emulator-5554 (API 19)".to_string()]
+        })
+        .unwrap()
+        .unwrap();
+        assert!(warning.contains("从 17 提高到 21"));
+        assert!(warning.contains("emulator-5554 (API 19)"));
+
+        let quiet = compare_min_sdk(&original, &output, true, |_| Vec::new()).unwrap().unwrap();
+        assert!(!quiet.contains("无法安装"));
+    }
+
+    #[test]
+    fn unchanged_or_lowered_floor_passes() {
+        let dir = tempfile::tempdir().unwrap();
+        let original = with_min_sdk(dir.path(), "original.apk", 19);
+        let same = with_min_sdk(dir.path(), "same.apk", 19);
+        let lower = with_min_sdk(dir.path(), "lower.apk", 17);
+        assert_eq!(compare_min_sdk(&original, &same, false, |_| Vec::new()).unwrap(), None);
+        assert_eq!(compare_min_sdk(&original, &lower, false, |_| Vec::new()).unwrap(), None);
+    }
+
+    #[test]
+    fn only_top_level_uses_sdk_counts() {
+        let dir = tempfile::tempdir().unwrap();
+        let original = with_min_sdk(dir.path(), "original.apk", 17);
+        let nested = apk(
+            dir.path(),
+            "nested.apk",
+            &[Node::Start(1, None), Node::Start(3, None), Node::Start(2, Some(30)), Node::End(2), Node::End(3), Node::End(1)],
+        );
+        assert!(axml::uses_sdk_of_apk(&nested).unwrap().is_none());
+        assert_eq!(compare_min_sdk(&original, &nested, false, |_| Vec::new()).unwrap(), None);
+    }
+
+    fn work_dir_with(fixture: &str) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/namespace").join(fixture)).unwrap();
+        fs::write(dir.path().join("AndroidManifest.xml"), manifest).unwrap();
+        fs::write(dir.path().join("apktool.yml"), "version: 2.9.3\nsdkInfo:\n  minSdkVersion: '17'\n").unwrap();
+        dir
+    }
+
+    #[test]
+    fn sdk_override_follows_the_namespace_prefix() {
+        for (fixture, prefix) in [("AndroidManifest.n0.xml", "n0"), ("AndroidManifest.n1.xml", "n1")] {
+            let dir = work_dir_with(fixture);
+            apply_sdk_override(dir.path(), Some(19), Some(33)).unwrap();
+            let manifest = fs::read_to_string(dir.path().join("AndroidManifest.xml")).unwrap();
+            assert!(manifest.contains(&format!(r#"<uses-sdk {0}:minSdkVersion="19" {0}:targetSdkVersion="33"/>"#, prefix)), "{}", manifest);
+            let yml = fs::read_to_string(dir.path().join("apktool.yml")).unwrap();
+            assert!(yml.contains("minSdkVersion: '19'") && yml.contains("targetSdkVersion: '33'"), "{}", yml);
+        }
+    }
+
+    #[test]
+    fn sdk_override_fails_when_the_attribute_cannot_be_matched() {
+        let dir = work_dir_with("AndroidManifest.n1.xml");
+        // 属性写成了未绑定的前缀，按 n1 解析改不到
+        let manifest_path = dir.path().join("AndroidManifest.xml");
+        let manifest = fs::read_to_string(&manifest_path).unwrap().replace("n1:minSdkVersion", "x:minSdkVersion");
+        fs::write(&manifest_path, manifest).unwrap();
+        let error = apply_sdk_override(dir.path(), Some(19), None).unwrap_err();
+        assert!(error.contains("minSdkVersion"), "{}", error);
+    }
+}
\ No newline at end of file
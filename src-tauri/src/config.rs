@@ -0,0 +1,275 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::paths::AppPaths;
+use crate::pipeline::ProcessConfig;
+use crate::signing::PipelineError;
+
+/// 锁定前缀的持久化文件名（位于配置目录）
+const PREFIX_LOCK_FILE: &str = "prefix_lock.json";
+
+/// 企业部署时固定使用的包名前缀
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct PrefixLock {
+    pub prefix: String,
+    pub description: String,
+}
+
+/// 可通过环境变量覆盖的字段
+const ENV_OVERRIDES: &[(&str, &str)] = &[
+    ("APK_DISGUISE_JAVA", "java_path"),
+    ("APK_DISGUISE_APKTOOL", "apktool_path"),
+    ("APK_DISGUISE_ZIPALIGN", "zipalign_path"),
+    ("APK_DISGUISE_APKSIGNER", "apksigner_path"),
+    ("APK_DISGUISE_KEYSTORE", "keystore_path"),
+    ("APK_DISGUISE_PREFIX", "new_prefix"),
+];
+
+fn field_mut<'a>(config: &'a mut ProcessConfig, field: &str) -> Option<&'a mut String> {
+    match field {
+        "java_path" => Some(&mut config.java_path),
+        "apktool_path" => Some(&mut config.apktool_path),
+        "zipalign_path" => Some(&mut config.zipalign_path),
+        "apksigner_path" => Some(&mut config.apksigner_path),
+        "keystore_path" => Some(&mut config.keystore_path),
+        "new_prefix" => Some(&mut config.new_prefix),
+        _ => None,
+    }
+}
+
+/// 应用环境变量中的覆盖值
+pub fn merge_apk_config_from_env(mut config: ProcessConfig) -> ProcessConfig {
+    for (var, field) in ENV_OVERRIDES {
+        if let Ok(value) = std::env::var(var) {
+            if let (false, Some(target)) = (value.trim().is_empty(), field_mut(&mut config, field)) {
+                *target = value;
+            }
+        }
+    }
+    config
+}
+
+/// 检查配置中的工具是否都能找到
+pub fn verify_tools_present(config: &ProcessConfig) -> Result<(), PipelineError> {
+    let java_ok = Command::new(&config.java_path).arg("-version").output().map(|o| o.status.success()).unwrap_or(false);
+    if !java_ok {
+        if config.java_path.contains(['/', '\\']) {
+            crate::quarantine::check_tool("Java", &config.java_path)?;
+        }
+        return Err(PipelineError::InvalidInput(format!("找不到可用的 Java: {}", config.java_path)));
+    }
+    for (name, path) in [
+        ("apktool", &config.apktool_path),
+        ("zipalign", &config.zipalign_path),
+        ("apksigner", &config.apksigner_path),
+        ("keystore", &config.keystore_path),
+    ] {
+        if path.is_empty() || !Path::new(path).exists() {
+            return Err(PipelineError::InvalidInput(format!("找不到 {}: {}", name, path)));
+        }
+        crate::quarantine::check_tool(name, path)?;
+    }
+    Ok(())
+}
+
+/// 合并环境变量、补全内置工具路径、填入默认值并校验
+pub fn effective_config(app_paths: &AppPaths, partial_config: ProcessConfig) -> Result<ProcessConfig, PipelineError> {
+    let mut config = merge_apk_config_from_env(partial_config);
+    crate::input_path::normalize_config(&mut config)?;
+
+    let bundled = crate::bundled_tool_paths(&app_paths.tools_dir);
+    for (key, field) in [
+        ("apktool", "apktool_path"),
+        ("zipalign", "zipalign_path"),
+        ("apksigner", "apksigner_path"),
+        ("keystore", "keystore_path"),
+    ] {
+        if let (Some(target), Some(value)) = (field_mut(&mut config, field), bundled.get(key).and_then(|v| v.as_str())) {
+            if target.is_empty() {
+                *target = value.to_string();
+            }
+        }
+    }
+    if config.java_path.is_empty() {
+        config.java_path = "java".to_string();
+    }
+
+    config.hydrate_source.get_or_insert(true);
+    config.auto_convert_keystore.get_or_insert(false);
+    config.downgrade_permissions.get_or_insert(false);
+    config.post_install_actions.get_or_insert_with(Vec::new);
+    config.required_permissions.get_or_insert_with(Vec::new);
+    config.hooks.get_or_insert_with(Default::default);
+    config.rollback_on_launch_failure.get_or_insert(false);
+    config.strip_debug_info.get_or_insert(false);
+    config.asset_overrides.get_or_insert_with(Vec::new);
+    config.apktool_warning_threshold.get_or_insert(crate::diagnostics::DEFAULT_RESOURCE_WARNING_THRESHOLD);
+    config.tool_versions.get_or_insert_with(Default::default);
+    config.log_queue_depth.get_or_insert(crate::log_batch::DEFAULT_QUEUE_DEPTH as u32);
+    config.min_output_ratio.get_or_insert(crate::entries::DEFAULT_MIN_OUTPUT_RATIO);
+    config.stealth_output.get_or_insert(false);
+    config.strict_resource_warnings.get_or_insert(false);
+    config.install_flags.get_or_insert_with(Default::default);
+    config.key_passwords.get_or_insert_with(Vec::new);
+    config.rewrite_res_xml_references.get_or_insert(false);
+
+    crate::tool_versions::apply_pins(app_paths, &mut config)?;
+    verify_tools_present(&config)?;
+    Ok(config)
+}
+
+/// 返回实际会使用的完整配置
+#[tauri::command]
+pub fn get_effective_config(
+    app_paths: tauri::State<'_, AppPaths>,
+    partial_config: ProcessConfig,
+) -> Result<ProcessConfig, PipelineError> {
+    effective_config(&app_paths, partial_config)
+}
+
+fn prefix_lock_path(paths: &AppPaths) -> PathBuf {
+    paths.config_dir.join(PREFIX_LOCK_FILE)
+}
+
+fn is_valid_prefix(prefix: &str) -> bool {
+    let segment = regex::Regex::new(r"^[A-Za-z][A-Za-z0-9_]*$").unwrap();
+    !prefix.is_empty() && prefix.split('.').all(|s| segment.is_match(s))
+}
+
+/// 读取已锁定的前缀
+pub fn load_prefix_lock(paths: &AppPaths) -> Result<Option<PrefixLock>, PipelineError> {
+    let file = prefix_lock_path(paths);
+    if !file.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(&file)?;
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| PipelineError::Io(format!("解析 {} 失败: {}", file.display(), e)))
+}
+
+/// 存在锁定前缀时强制替换 `new_prefix`，被替换时返回提示信息
+pub fn apply_prefix_lock(paths: &AppPaths, config: &mut ProcessConfig) -> Result<Option<String>, PipelineError> {
+    let Some(lock) = load_prefix_lock(paths)? else {
+        return Ok(None);
+    };
+    if config.new_prefix == lock.prefix {
+        return Ok(None);
+    }
+    let warning = format!(
+        "prefix_locked_warning: 已按锁定前缀 {}（{}）处理，忽略传入的前缀 {}",
+        lock.prefix, lock.description, config.new_prefix
+    );
+    config.new_prefix = lock.prefix;
+    Ok(Some(warning))
+}
+
+/// 锁定包名前缀，之后所有处理都使用该前缀
+#[tauri::command]
+pub fn register_prefix_lock(
+    app: tauri::AppHandle,
+    app_paths: tauri::State<'_, AppPaths>,
+    prefix: String,
+    description: String,
+) -> Result<(), PipelineError> {
+    let params = serde_json::json!({ "prefix": prefix, "description": description });
+    let result = write_prefix_lock(&app_paths, prefix, description);
+    crate::audit::record(&app, "register_prefix_lock", params, &result);
+    result
+}
+
+fn write_prefix_lock(app_paths: &AppPaths, prefix: String, description: String) -> Result<(), PipelineError> {
+    let prefix = prefix.trim().trim_end_matches('.').to_string();
+    if !is_valid_prefix(&prefix) {
+        return Err(PipelineError::InvalidInput(format!("不是合法的包名前缀: {}", prefix)));
+    }
+    fs::create_dir_all(&app_paths.config_dir)?;
+    let content = serde_json::to_string_pretty(&PrefixLock { prefix, description })
+        .map_err(|e| PipelineError::Io(e.to_string()))?;
+    fs::write(prefix_lock_path(app_paths), content)?;
+    Ok(())
+}
+
+/// 获取当前锁定的包名前缀
+#[tauri::command]
+pub fn get_locked_prefix(app_paths: tauri::State<'_, AppPaths>) -> Result<Option<String>, PipelineError> {
+    Ok(load_prefix_lock(&app_paths)?.map(|lock| lock.prefix))
+}
+
+/// 解除包名前缀锁定
+#[tauri::command]
+pub fn clear_prefix_lock(app: tauri::AppHandle, app_paths: tauri::State<'_, AppPaths>) -> Result<(), PipelineError> {
+    let file = prefix_lock_path(&app_paths);
+    let result = if file.exists() { fs::remove_file(file).map_err(PipelineError::from) } else { Ok(()) };
+    crate::audit::record(&app, "clear_prefix_lock", serde_json::json!({}), &result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(prefix: &str) -> ProcessConfig {
+        let request: crate::ProcessRequest = serde_json::from_value(serde_json::json!({
+            "apk_path": "/apks/Scanner App.apk",
+            "new_prefix": prefix,
+            "install_after": false,
+            "java_path": "",
+            "apktool_path": "",
+            "zipalign_path": "",
+            "apksigner_path": "",
+            "keystore_path": "",
+        }))
+        .unwrap();
+        request.config
+    }
+
+    #[test]
+    fn no_lock_leaves_the_prefix_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = AppPaths::portable_at(dir.path());
+        let mut config = request("com.test");
+        assert_eq!(apply_prefix_lock(&paths, &mut config).unwrap(), None);
+        assert_eq!(config.new_prefix, "com.test");
+    }
+
+    /// 与 `process_apk_full` 相同的顺序：先按锁定前缀替换，再确定新包名
+    #[test]
+    fn locked_prefix_overrides_the_requested_prefix() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = AppPaths::portable_at(dir.path());
+        write_prefix_lock(&paths, " com.corp.internal. ".to_string(), "企业内部应用".to_string()).unwrap();
+
+        let mut config = request("com.test");
+        let warning = apply_prefix_lock(&paths, &mut config).unwrap().unwrap();
+        assert!(warning.starts_with("prefix_locked_warning:"));
+        assert!(warning.contains("com.corp.internal") && warning.contains("com.test") && warning.contains("企业内部应用"));
+        assert_eq!(config.new_prefix, "com.corp.internal");
+        assert_eq!(crate::pipeline::plan_package(&config).unwrap().new_package, "com.corp.internal.scannerapp");
+
+        // 传入的就是锁定前缀时不提示
+        assert_eq!(apply_prefix_lock(&paths, &mut config).unwrap(), None);
+    }
+
+    #[test]
+    fn invalid_prefixes_are_not_locked() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = AppPaths::portable_at(dir.path());
+        for prefix in ["", "com..corp", "1com.corp", "com.corp-internal", "com.corp internal"] {
+            assert!(matches!(write_prefix_lock(&paths, prefix.to_string(), String::new()), Err(PipelineError::InvalidInput(_))), "{:?}", prefix);
+        }
+        assert!(load_prefix_lock(&paths).unwrap().is_none());
+    }
+
+    #[test]
+    fn corrupt_lock_file_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = AppPaths::portable_at(dir.path());
+        fs::create_dir_all(&paths.config_dir).unwrap();
+        fs::write(prefix_lock_path(&paths), "{").unwrap();
+        assert!(matches!(apply_prefix_lock(&paths, &mut request("com.test")), Err(PipelineError::Io(_))));
+    }
+}
@@ -0,0 +1,192 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::hash;
+
+/// 确认令牌的有效期
+pub const TOKEN_TTL: Duration = Duration::from_secs(30);
+
+/// 无界面 / 命令行模式下跳过确认的启动参数
+pub const BYPASS_FLAG: &str = "--yes";
+
+/// 破坏性操作将影响的范围，供用户确认
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct DestructiveSummary {
+    pub operation: String,
+    pub device_serial: String,
+    pub device_alias: Option<String>,
+    pub package_count: usize,
+    /// 是否会丢失应用数据
+    pub data_loss: bool,
+    /// 需要用户了解的详细说明
+    #[serde(default)]
+    pub detail: Option<String>,
+}
+
+/// 破坏性操作的返回：首次调用返回令牌，带令牌再次调用才真正执行
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum Confirmation<T> {
+    ConfirmationRequired {
+        token: String,
+        summary: DestructiveSummary,
+        #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+        expires_in_secs: u64,
+    },
+    Done { result: T },
+}
+
+struct Pending {
+    scope: String,
+    expires_at: Instant,
+}
+
+/// 待确认的令牌，一次性使用，且绑定到签发时的操作参数
+pub struct ConfirmationTokens {
+    pending: Mutex<HashMap<String, Pending>>,
+    counter: AtomicU64,
+    ttl: Duration,
+    /// 启动时指定了 `--yes`，所有操作直接执行
+    bypass: bool,
+}
+
+/// 操作名与规范化参数的哈希，参数有任何变化令牌即失效
+fn scope_of<P: Serialize>(operation: &str, params: &P) -> Result<String, String> {
+    let params = serde_json::to_value(params).map_err(|e| e.to_string())?;
+    Ok(hash::sha256_str(&format!("{}:{}", operation, params)))
+}
+
+/// 以 `--yes` 启动时不需要确认
+pub fn bypass_requested() -> bool {
+    std::env::args().any(|a| a == BYPASS_FLAG)
+}
+
+impl ConfirmationTokens {
+    /// `bypass` 通常取 `bypass_requested()`，在启动时确定一次
+    pub fn new(bypass: bool) -> Self {
+        Self::with_ttl(bypass, TOKEN_TTL)
+    }
+
+    /// 指定令牌有效期，测试中用于模拟超时
+    pub fn with_ttl(bypass: bool, ttl: Duration) -> Self {
+        ConfirmationTokens { pending: Mutex::new(HashMap::new()), counter: AtomicU64::new(0), ttl, bypass }
+    }
+
+    fn issue(&self, scope: String) -> String {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+        let seq = self.counter.fetch_add(1, Ordering::Relaxed);
+        let token = hash::sha256_str(&format!("{}:{}:{}", nanos, seq, scope))[..16].to_string();
+        let mut pending = self.pending.lock().unwrap();
+        let now = Instant::now();
+        pending.retain(|_, p| p.expires_at > now);
+        pending.insert(token.clone(), Pending { scope, expires_at: now + self.ttl });
+        token
+    }
+
+    /// 没有令牌时签发新令牌并返回待确认结果；令牌有效时消耗它并返回 `None` 表示可以执行
+    pub fn check<P: Serialize, T>(
+        &self,
+        operation: &str,
+        params: &P,
+        token: Option<String>,
+        summary: impl FnOnce() -> DestructiveSummary,
+    ) -> Result<Option<Confirmation<T>>, String> {
+        if self.bypass {
+            return Ok(None);
+        }
+        let scope = scope_of(operation, params)?;
+        let Some(token) = token else {
+            return Ok(Some(Confirmation::ConfirmationRequired {
+                token: self.issue(scope),
+                summary: summary(),
+                expires_in_secs: self.ttl.as_secs(),
+            }));
+        };
+
+        let entry = self.pending.lock().unwrap().remove(&token);
+        match entry {
+            None => Err("确认令牌无效或已使用，请重新确认".to_string()),
+            Some(p) if p.expires_at <= Instant::now() => Err("确认已超时，请重新确认".to_string()),
+            Some(p) if p.scope != scope => Err("操作参数与确认时不一致，请重新确认".to_string()),
+            Some(_) => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary() -> DestructiveSummary {
+        DestructiveSummary {
+            operation: "卸载应用".to_string(),
+            device_serial: "emulator-5554".to_string(),
+            device_alias: None,
+            package_count: 1,
+            data_loss: true,
+            detail: None,
+        }
+    }
+
+    /// 首次调用，返回签发的令牌
+    fn issue(tokens: &ConfirmationTokens, params: &(&str, &str)) -> String {
+        match tokens.check::<_, ()>("uninstall_app", params, None, summary).unwrap() {
+            Some(Confirmation::ConfirmationRequired { token, summary, expires_in_secs }) => {
+                assert_eq!(summary.package_count, 1);
+                assert_eq!(expires_in_secs, tokens.ttl.as_secs());
+                token
+            }
+            other => panic!("应当要求确认: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn valid_token_allows_the_operation_once() {
+        let tokens = ConfirmationTokens::new(false);
+        let params = ("emulator-5554", "com.a.b");
+        let token = issue(&tokens, &params);
+        assert!(tokens.check::<_, ()>("uninstall_app", &params, Some(token.clone()), summary).unwrap().is_none());
+        let reused = tokens.check::<_, ()>("uninstall_app", &params, Some(token), summary).unwrap_err();
+        assert!(reused.contains("已使用"));
+    }
+
+    #[test]
+    fn expired_token_is_rejected() {
+        let tokens = ConfirmationTokens::with_ttl(false, Duration::from_millis(20));
+        let params = ("emulator-5554", "com.a.b");
+        let token = issue(&tokens, &params);
+        std::thread::sleep(Duration::from_millis(40));
+        let err = tokens.check::<_, ()>("uninstall_app", &params, Some(token), summary).unwrap_err();
+        assert!(err.contains("超时"));
+    }
+
+    #[test]
+    fn token_is_bound_to_operation_and_parameters() {
+        let tokens = ConfirmationTokens::new(false);
+        let token = issue(&tokens, &("emulator-5554", "com.a.b"));
+        let err = tokens.check::<_, ()>("uninstall_app", &("emulator-5554", "com.c.d"), Some(token), summary).unwrap_err();
+        assert!(err.contains("不一致"));
+
+        let token = issue(&tokens, &("emulator-5554", "com.a.b"));
+        let err = tokens.check::<_, ()>("remove_system_app", &("emulator-5554", "com.a.b"), Some(token), summary).unwrap_err();
+        assert!(err.contains("不一致"));
+    }
+
+    #[test]
+    fn unknown_token_is_rejected() {
+        let tokens = ConfirmationTokens::new(false);
+        assert!(tokens.check::<_, ()>("uninstall_app", &("a", "b"), Some("0123456789abcdef".to_string()), summary).is_err());
+    }
+
+    #[test]
+    fn bypass_skips_confirmation() {
+        let tokens = ConfirmationTokens::new(true);
+        assert!(tokens.check::<_, ()>("uninstall_app", &("a", "b"), None, summary).unwrap().is_none());
+        assert!(tokens.pending.lock().unwrap().is_empty());
+    }
+}
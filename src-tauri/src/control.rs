@@ -0,0 +1,510 @@
+//! 本地控制接口：GUI 运行时供部署脚本查询任务、设备、安装历史与包名映射。
+//! 只监听 Unix 套接字（配置目录下的 `control.sock`）或 Windows 命名管道，从不绑定 TCP 端口，默认关闭。
+//!
+//! 协议为按行分隔的 JSON-RPC 2.0。连接后的第一个请求必须是
+//! `{"jsonrpc":"2.0","id":1,"method":"authenticate","params":{"token":"..."}}`，
+//! 令牌在每次启动接口时重新生成，写入配置目录下仅所有者可读的 `control_token`。
+//! 只提供只读方法，返回值与前端使用的类型相同：
+//! `jobs.list`、`devices.list`、`batches.list`、`history.device`（device_id）、
+//! `history.package`（package）、`history.where_is`（apk_sha256）、`mapping.lookup`（package）、`runs.get`（job_id）。
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use tauri::Manager;
+
+use crate::incremental::{self, RunRecord};
+use crate::installs::{self, InstallHistory};
+use crate::jobs::JobRegistry;
+use crate::paths::{self, AppPaths};
+use crate::{adb, batch};
+
+const SETTINGS_FILE: &str = "control_settings.json";
+const TOKEN_FILE: &str = "control_token";
+
+/// 单个请求行的最大长度，超过时断开连接
+const MAX_REQUEST_BYTES: u64 = 64 * 1024;
+
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+const UNAUTHENTICATED: i64 = -32001;
+
+/// 本地控制接口设置
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct ControlSettings {
+    pub enabled: bool,
+}
+
+/// 本地控制接口的当前状态
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct ControlStatus {
+    pub enabled: bool,
+    pub running: bool,
+    /// 套接字路径或命名管道名
+    pub endpoint: Option<String>,
+    pub token_file: String,
+    /// 启动或监听失败的原因
+    pub error: Option<String>,
+}
+
+/// 一次处理的包名映射与输出位置
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct MappingEntry {
+    pub job_id: Option<String>,
+    pub original_package: Option<String>,
+    pub package: String,
+    pub output_path: String,
+    pub output_sha256: String,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub timestamp: u64,
+    pub signing_identity: Option<crate::signing::SigningIdentity>,
+}
+
+impl From<RunRecord> for MappingEntry {
+    fn from(record: RunRecord) -> Self {
+        MappingEntry {
+            job_id: record.job_id,
+            original_package: record.original_package,
+            package: record.package,
+            output_path: record.output_path,
+            output_sha256: record.output_sha256,
+            timestamp: record.timestamp,
+            signing_identity: record.signing_identity,
+        }
+    }
+}
+
+struct Running {
+    endpoint: String,
+    stop: Arc<AtomicBool>,
+}
+
+/// 正在运行的控制接口
+#[derive(Default)]
+pub struct ControlServer {
+    running: Mutex<Option<Running>>,
+    /// 启动时按设置开启失败或监听中断的原因
+    error: Mutex<Option<String>>,
+}
+
+#[derive(Deserialize)]
+struct Request {
+    #[serde(default)]
+    id: serde_json::Value,
+    method: String,
+    #[serde(default)]
+    params: serde_json::Value,
+}
+
+type RpcError = (i64, String);
+
+fn load_settings(paths: &AppPaths) -> ControlSettings {
+    fs::read_to_string(paths.config_dir.join(SETTINGS_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn token_path(paths: &AppPaths) -> PathBuf {
+    paths.config_dir.join(TOKEN_FILE)
+}
+
+#[cfg(unix)]
+fn endpoint(paths: &AppPaths) -> String {
+    paths.config_dir.join("control.sock").to_string_lossy().into_owned()
+}
+
+#[cfg(windows)]
+fn endpoint(_paths: &AppPaths) -> String {
+    let user: String = std::env::var("USERNAME").unwrap_or_default().chars().filter(|c| c.is_ascii_alphanumeric()).collect();
+    format!(r"\\.\pipe\apk-disguise-pro-control-{}", user)
+}
+
+fn new_token() -> io::Result<String> {
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes).map_err(|e| io::Error::other(e.to_string()))?;
+    Ok(hex::encode(bytes))
+}
+
+/// 写入令牌文件；Unix 上权限为 0600，Windows 上配置目录本身只对当前用户可写
+fn write_token(path: &std::path::Path, token: &str) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let _ = fs::remove_file(path);
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(token.as_bytes())
+}
+
+/// 比较时间与不匹配的位置无关
+pub fn token_matches(expected: &str, given: &str) -> bool {
+    expected.len() == given.len() && expected.bytes().zip(given.bytes()).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+fn string_param(params: &serde_json::Value, name: &str) -> Result<String, RpcError> {
+    params
+        .get(name)
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+        .ok_or_else(|| (INVALID_PARAMS, format!("缺少字符串参数 {}", name)))
+}
+
+fn to_value<T: Serialize>(value: T) -> Result<serde_json::Value, RpcError> {
+    serde_json::to_value(value).map_err(|e| (INTERNAL_ERROR, e.to_string()))
+}
+
+/// 执行一个只读方法；修改类操作不在此列出，一律返回“方法不存在”
+fn dispatch(app: &tauri::AppHandle, method: &str, params: &serde_json::Value) -> Result<serde_json::Value, RpcError> {
+    let paths = app.state::<AppPaths>();
+    let history = app.state::<InstallHistory>();
+    match method {
+        "jobs.list" => to_value(app.state::<JobRegistry>().list()),
+        "devices.list" => to_value(adb::device_list().map_err(|e| (INTERNAL_ERROR, e))?),
+        "batches.list" => to_value(batch::list_batches(paths)),
+        "history.device" => to_value(installs::get_device_install_history(paths, history, string_param(params, "device_id")?)),
+        "history.package" => to_value(installs::get_package_install_history(paths, history, string_param(params, "package")?)),
+        "history.where_is" => to_value(installs::where_is(paths, history, string_param(params, "apk_sha256")?)),
+        "mapping.lookup" => {
+            let package = string_param(params, "package")?.to_lowercase();
+            let entries: Vec<MappingEntry> = incremental::load_records(&paths)
+                .into_iter()
+                .filter(|r| r.package.to_lowercase() == package || r.original_package.as_deref().is_some_and(|o| o.to_lowercase() == package))
+                .map(MappingEntry::from)
+                .collect();
+            to_value(entries)
+        }
+        "runs.get" => {
+            let job_id = string_param(params, "job_id")?;
+            let record = incremental::load_records(&paths).into_iter().find(|r| r.job_id.as_deref() == Some(job_id.as_str()));
+            to_value(record.map(MappingEntry::from))
+        }
+        _ => Err((METHOD_NOT_FOUND, format!("方法不存在或不允许通过控制接口调用: {}", method))),
+    }
+}
+
+fn respond(writer: &mut impl Write, id: serde_json::Value, result: Result<serde_json::Value, RpcError>) -> io::Result<()> {
+    let response = match result {
+        Ok(result) => serde_json::json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err((code, message)) => serde_json::json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } }),
+    };
+    writeln!(writer, "{}", response)?;
+    writer.flush()
+}
+
+/// 处理一个连接：先认证，之后逐行交给 `dispatch` 处理；认证失败或请求过长时断开
+fn serve(
+    reader: impl Read,
+    mut writer: impl Write,
+    token: &str,
+    dispatch: impl Fn(&str, &serde_json::Value) -> Result<serde_json::Value, RpcError>,
+) -> io::Result<()> {
+    let mut reader = BufReader::new(reader);
+    let mut authenticated = false;
+    loop {
+        let mut line = String::new();
+        let read = (&mut reader).take(MAX_REQUEST_BYTES).read_line(&mut line)?;
+        if read == 0 {
+            return Ok(());
+        }
+        if !line.ends_with('\n') && read as u64 == MAX_REQUEST_BYTES {
+            return respond(&mut writer, serde_json::Value::Null, Err((PARSE_ERROR, "请求过长".to_string())));
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+        let request: Request = match serde_json::from_str(&line) {
+            Ok(request) => request,
+            Err(e) => {
+                respond(&mut writer, serde_json::Value::Null, Err((PARSE_ERROR, e.to_string())))?;
+                continue;
+            }
+        };
+        if request.method == "authenticate" {
+            authenticated = request.params.get("token").and_then(|t| t.as_str()).is_some_and(|t| token_matches(token, t));
+            if !authenticated {
+                return respond(&mut writer, request.id, Err((UNAUTHENTICATED, "令牌无效".to_string())));
+            }
+            respond(&mut writer, request.id, Ok(serde_json::json!({ "authenticated": true })))?;
+            continue;
+        }
+        if !authenticated {
+            return respond(&mut writer, request.id, Err((UNAUTHENTICATED, "请先调用 authenticate".to_string())));
+        }
+        respond(&mut writer, request.id, dispatch(&request.method, &request.params))?;
+    }
+}
+
+#[cfg(unix)]
+fn listen(app: tauri::AppHandle, endpoint: &str, token: String, stop: Arc<AtomicBool>) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    if UnixStream::connect(endpoint).is_ok() {
+        return Err(io::Error::new(io::ErrorKind::AddrInUse, "另一个实例已在运行控制接口"));
+    }
+    // 上次异常退出留下的套接字文件
+    let _ = fs::remove_file(endpoint);
+    let listener = UnixListener::bind(endpoint)?;
+    fs::set_permissions(endpoint, fs::Permissions::from_mode(0o600))?;
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            if stop.load(Ordering::SeqCst) {
+                break;
+            }
+            let Ok(stream) = stream else { continue };
+            let (app, token) = (app.clone(), token.clone());
+            thread::spawn(move || {
+                if let Ok(reader) = stream.try_clone() {
+                    let _ = serve(reader, stream, &token, |method, params| dispatch(&app, method, params));
+                }
+            });
+        }
+    });
+    Ok(())
+}
+
+#[cfg(windows)]
+fn accept_pipe(name: &str) -> io::Result<fs::File> {
+    use std::os::windows::ffi::OsStrExt;
+    use std::os::windows::io::FromRawHandle;
+    use windows_sys::Win32::Foundation::{ERROR_PIPE_CONNECTED, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::Storage::FileSystem::PIPE_ACCESS_DUPLEX;
+    use windows_sys::Win32::System::Pipes::{
+        ConnectNamedPipe, CreateNamedPipeW, PIPE_READMODE_BYTE, PIPE_REJECT_REMOTE_CLIENTS, PIPE_TYPE_BYTE, PIPE_UNLIMITED_INSTANCES, PIPE_WAIT,
+    };
+
+    let wide: Vec<u16> = std::ffi::OsStr::new(name).encode_wide().chain(Some(0)).collect();
+    let mode = PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT | PIPE_REJECT_REMOTE_CLIENTS;
+    let handle = unsafe { CreateNamedPipeW(wide.as_ptr(), PIPE_ACCESS_DUPLEX, mode, PIPE_UNLIMITED_INSTANCES, 64 * 1024, 64 * 1024, 0, std::ptr::null()) };
+    if handle == INVALID_HANDLE_VALUE {
+        return Err(io::Error::last_os_error());
+    }
+    // 句柄交给 File 管理，出错返回时也会被关闭
+    let file = unsafe { fs::File::from_raw_handle(handle as _) };
+    if unsafe { ConnectNamedPipe(handle, std::ptr::null_mut()) } == 0 {
+        let err = io::Error::last_os_error();
+        if err.raw_os_error() != Some(ERROR_PIPE_CONNECTED as i32) {
+            return Err(err);
+        }
+    }
+    Ok(file)
+}
+
+#[cfg(windows)]
+fn listen(app: tauri::AppHandle, endpoint: &str, token: String, stop: Arc<AtomicBool>) -> io::Result<()> {
+    if fs::OpenOptions::new().read(true).write(true).open(endpoint).is_ok() {
+        return Err(io::Error::new(io::ErrorKind::AddrInUse, "另一个实例已在运行控制接口"));
+    }
+    let name = endpoint.to_string();
+    thread::spawn(move || {
+        while !stop.load(Ordering::SeqCst) {
+            let pipe = match accept_pipe(&name) {
+                Ok(pipe) => pipe,
+                Err(e) => {
+                    *app.state::<ControlServer>().error.lock().unwrap() = Some(format!("命名管道出错，控制接口已停止: {}", e));
+                    break;
+                }
+            };
+            if stop.load(Ordering::SeqCst) {
+                break;
+            }
+            let (app, token) = (app.clone(), token.clone());
+            thread::spawn(move || {
+                if let Ok(reader) = pipe.try_clone() {
+                    let _ = serve(reader, pipe, &token, |method, params| dispatch(&app, method, params));
+                }
+            });
+        }
+    });
+    Ok(())
+}
+
+/// 唤醒阻塞在 accept 上的监听线程，使其看到停止标记后退出
+fn wake(endpoint: &str) {
+    #[cfg(unix)]
+    {
+        let _ = std::os::unix::net::UnixStream::connect(endpoint);
+        let _ = fs::remove_file(endpoint);
+    }
+    #[cfg(windows)]
+    {
+        let _ = fs::OpenOptions::new().read(true).write(true).open(endpoint);
+    }
+}
+
+impl ControlServer {
+    fn start(&self, app: &tauri::AppHandle, paths: &AppPaths) -> Result<(), String> {
+        let mut running = self.running.lock().unwrap();
+        if running.is_some() {
+            return Ok(());
+        }
+        let token = new_token().map_err(|e| format!("生成令牌失败: {}", e))?;
+        write_token(&token_path(paths), &token).map_err(|e| format!("写入令牌文件失败: {}", e))?;
+        let endpoint = endpoint(paths);
+        let stop = Arc::new(AtomicBool::new(false));
+        if let Err(e) = listen(app.clone(), &endpoint, token, stop.clone()) {
+            let _ = fs::remove_file(token_path(paths));
+            return Err(format!("启动控制接口失败（{}）: {}", endpoint, e));
+        }
+        *running = Some(Running { endpoint, stop });
+        Ok(())
+    }
+
+    /// 停止监听并删除令牌文件；已建立的连接在各自的请求结束后关闭
+    fn stop(&self, paths: &AppPaths) {
+        if let Some(running) = self.running.lock().unwrap().take() {
+            running.stop.store(true, Ordering::SeqCst);
+            wake(&running.endpoint);
+            let _ = fs::remove_file(token_path(paths));
+        }
+    }
+
+    fn endpoint(&self) -> Option<String> {
+        self.running.lock().unwrap().as_ref().map(|r| r.endpoint.clone())
+    }
+}
+
+/// 启动时按设置决定是否开启控制接口
+pub fn start_if_enabled(app: &tauri::AppHandle) {
+    let paths = app.state::<AppPaths>();
+    if load_settings(&paths).enabled {
+        let server = app.state::<ControlServer>();
+        let error = server.start(app, &paths).err();
+        *server.error.lock().unwrap() = error;
+    }
+}
+
+/// 应用退出时关闭控制接口
+pub fn shutdown(app: &tauri::AppHandle) {
+    if let (Some(server), Some(paths)) = (app.try_state::<ControlServer>(), app.try_state::<AppPaths>()) {
+        server.stop(&paths);
+    }
+}
+
+fn status(server: &ControlServer, paths: &AppPaths) -> ControlStatus {
+    let endpoint = server.endpoint();
+    ControlStatus {
+        enabled: load_settings(paths).enabled,
+        running: endpoint.is_some(),
+        endpoint,
+        token_file: token_path(paths).to_string_lossy().into_owned(),
+        error: server.error.lock().unwrap().clone(),
+    }
+}
+
+/// 获取本地控制接口的状态
+#[tauri::command]
+pub fn get_control_status(server: tauri::State<'_, ControlServer>, paths: tauri::State<'_, AppPaths>) -> ControlStatus {
+    status(&server, &paths)
+}
+
+/// 开启或关闭本地控制接口并持久化设置；每次开启都会生成新的令牌
+#[tauri::command]
+pub fn set_control_settings(
+    app: tauri::AppHandle,
+    server: tauri::State<'_, ControlServer>,
+    paths: tauri::State<'_, AppPaths>,
+    settings: ControlSettings,
+) -> Result<ControlStatus, String> {
+    let result = serde_json::to_vec_pretty(&settings)
+        .map_err(|e| e.to_string())
+        .and_then(|content| paths::write_atomic(&paths.config_dir.join(SETTINGS_FILE), &content).map_err(|e| e.to_string()))
+        .and_then(|_| {
+            *server.error.lock().unwrap() = None;
+            if settings.enabled {
+                server.start(&app, &paths)
+            } else {
+                server.stop(&paths);
+                Ok(())
+            }
+        });
+    crate::audit::record(&app, "set_control_settings", serde_json::to_value(&settings).unwrap_or_default(), &result);
+    result.map(|_| status(&server, &paths))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jobs::{Admission, JobSummary};
+
+    const TOKEN: &str = "5f2c0d1e9a8b7c6d";
+
+    /// 示例客户端：按顺序发送请求，返回每一行响应
+    fn client(requests: &[serde_json::Value], registry: &JobRegistry) -> Vec<serde_json::Value> {
+        let input: String = requests.iter().map(|r| format!("{}\n", r)).collect();
+        let mut output = Vec::new();
+        serve(input.as_bytes(), &mut output, TOKEN, |method, _| match method {
+            "jobs.list" => to_value(registry.list()),
+            _ => Err((METHOD_NOT_FOUND, method.to_string())),
+        })
+        .unwrap();
+        String::from_utf8(output).unwrap().lines().map(|line| serde_json::from_str(line).unwrap()).collect()
+    }
+
+    fn request(id: u64, method: &str, params: serde_json::Value) -> serde_json::Value {
+        serde_json::json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+    }
+
+    #[test]
+    fn authenticated_client_lists_jobs() {
+        let registry = JobRegistry::default();
+        let (batch, _) = registry.start_batch();
+        let Admission::Started(job) = registry.admit("fingerprint", "/apks/demo.apk", false, Some(&batch)) else {
+            panic!("任务未登记");
+        };
+        let responses = client(
+            &[request(1, "authenticate", serde_json::json!({ "token": TOKEN })), request(2, "jobs.list", serde_json::Value::Null)],
+            &registry,
+        );
+        assert_eq!(responses[0], serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": { "authenticated": true } }));
+        assert_eq!(responses[1]["id"], 2);
+        let jobs = responses[1]["result"].as_array().unwrap();
+        assert_eq!(jobs.len(), 2);
+        for entry in jobs {
+            let mut keys: Vec<&str> = entry.as_object().unwrap().keys().map(String::as_str).collect();
+            keys.sort_unstable();
+            assert_eq!(keys, ["apk_path", "job_id", "message", "parent_job_id", "status"]);
+        }
+        let jobs: Vec<JobSummary> = serde_json::from_value(responses[1]["result"].clone()).unwrap();
+        let child = jobs.iter().find(|j| j.job_id == job).unwrap();
+        assert_eq!(child.parent_job_id.as_deref(), Some(batch.as_str()));
+        assert_eq!(child.apk_path.as_deref(), Some("/apks/demo.apk"));
+    }
+
+    #[test]
+    fn requests_before_authentication_close_the_connection() {
+        let responses = client(
+            &[request(1, "jobs.list", serde_json::Value::Null), request(2, "authenticate", serde_json::json!({ "token": TOKEN }))],
+            &JobRegistry::default(),
+        );
+        assert_eq!(responses.len(), 1);
+        assert_eq!(responses[0]["error"]["code"], UNAUTHENTICATED);
+    }
+
+    #[test]
+    fn wrong_token_and_unknown_methods_are_rejected() {
+        let responses = client(&[request(1, "authenticate", serde_json::json!({ "token": "5f2c0d1e9a8b7c6e" }))], &JobRegistry::default());
+        assert_eq!(responses[0]["error"]["code"], UNAUTHENTICATED);
+        let responses = client(
+            &[request(1, "authenticate", serde_json::json!({ "token": TOKEN })), request(2, "jobs.cancel", serde_json::Value::Null)],
+            &JobRegistry::default(),
+        );
+        assert_eq!(responses[1]["error"]["code"], METHOD_NOT_FOUND);
+    }
+}
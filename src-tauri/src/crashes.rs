@@ -0,0 +1,230 @@
+use serde::{Deserialize, Serialize};
+
+use crate::adb;
+use crate::signing::PipelineError;
+
+/// dropbox 中记录崩溃的标签
+const CRASH_TAGS: &[&str] = &["data_app_crash", "SYSTEM_TOMBSTONE"];
+
+/// 设备上记录的一次崩溃
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct CrashEntry {
+    /// dropbox 记录时间，如 `2024-01-15 10:23:45`
+    pub timestamp: String,
+    /// Java 异常类名；native 崩溃为信号名，如 `SIGSEGV`
+    pub exception_class: String,
+    pub message: Option<String>,
+    pub stack_trace: String,
+}
+
+fn is_separator(line: &str) -> bool {
+    let line = line.trim();
+    line.len() >= 10 && line.chars().all(|c| c == '=')
+}
+
+/// 条目首行：`2024-01-15 10:23:45 data_app_crash (text, 1234 bytes)`
+fn entry_timestamp(header: &str) -> Option<String> {
+    let mut parts = header.split_whitespace();
+    let date = parts.next()?;
+    let time = parts.next()?;
+    (date.len() == 10 && date.as_bytes()[4] == b'-').then(|| format!("{} {}", date, time))
+}
+
+/// Java 崩溃：头部字段之后的第一行为 `异常类: 消息`，其后为调用栈
+fn parse_java_crash(timestamp: String, body: &[&str]) -> Option<CrashEntry> {
+    let start = body.iter().position(|l| l.trim().is_empty())?;
+    let rest: Vec<&str> = body[start..].iter().copied().skip_while(|l| l.trim().is_empty()).collect();
+    let first = rest.first()?.trim();
+    let (exception_class, message) = match first.split_once(": ") {
+        Some((class, message)) => (class.to_string(), Some(message.to_string())),
+        None => (first.to_string(), None),
+    };
+    Some(CrashEntry { timestamp, exception_class, message, stack_trace: rest.join("\n").trim_end().to_string() })
+}
+
+/// native 崩溃：取 `signal 11 (SIGSEGV), code 1 (SEGV_MAPERR), fault addr ...` 与 backtrace
+fn parse_tombstone(timestamp: String, body: &[&str]) -> Option<CrashEntry> {
+    let signal = body.iter().map(|l| l.trim()).find(|l| l.starts_with("signal "))?;
+    let exception_class = signal
+        .split_once('(')
+        .and_then(|(_, rest)| rest.split_once(')'))
+        .map(|(name, _)| name.to_string())
+        .unwrap_or_else(|| signal.to_string());
+    let message = signal.split_once("), ").map(|(_, rest)| rest.to_string());
+    let stack_trace: Vec<&str> = body
+        .iter()
+        .copied()
+        .skip_while(|l| !l.trim().starts_with("backtrace:"))
+        .take_while(|l| !l.trim().is_empty() || l.trim().starts_with("backtrace:"))
+        .collect();
+    Some(CrashEntry { timestamp, exception_class, message, stack_trace: stack_trace.join("\n") })
+}
+
+/// 解析 `dumpsys dropbox --print` 的输出，只保留与指定包相关的崩溃
+pub fn parse_dropbox(dump: &str, package_name: &str) -> Vec<CrashEntry> {
+    let lines: Vec<&str> = dump.lines().collect();
+    lines
+        .split(|l| is_separator(l))
+        .filter_map(|block| {
+            let header_pos = block.iter().position(|l| !l.trim().is_empty())?;
+            let header = block[header_pos];
+            let timestamp = entry_timestamp(header)?;
+            let body = &block[header_pos + 1..];
+            let mentions_package = body.iter().any(|l| {
+                l.trim() == format!("Process: {}", package_name)
+                    || l.contains(&format!(">>> {} <<<", package_name))
+                    || l.trim().starts_with(&format!("Package: {} ", package_name))
+            });
+            if !mentions_package {
+                return None;
+            }
+            if header.contains("SYSTEM_TOMBSTONE") {
+                parse_tombstone(timestamp, body)
+            } else {
+                parse_java_crash(timestamp, body)
+            }
+        })
+        .collect()
+}
+
+/// 读取设备 dropbox 中指定应用的崩溃记录（Java 崩溃与 native tombstone），按时间从新到旧返回最多 `max_entries` 条
+#[tauri::command]
+pub fn get_crash_history(device_id: String, package_name: String, max_entries: u32) -> Result<Vec<CrashEntry>, PipelineError> {
+    let mut entries = Vec::new();
+    for tag in CRASH_TAGS {
+        let dump = adb::shell(&device_id, &["dumpsys", "dropbox", "--print", tag])
+            .map_err(|e| PipelineError::Tool { step: "dumpsys dropbox".to_string(), message: e })?;
+        entries.extend(parse_dropbox(&dump, &package_name));
+    }
+    entries.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    entries.truncate(max_entries as usize);
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `dumpsys dropbox --print data_app_crash`：两个应用各一次崩溃
+    const APP_CRASH_DUMP: &str = "Drop box contents: 2 entries
+Max entries: 1000
+Searching for: data_app_crash
+
+========================================
+2024-01-15 10:23:45 data_app_crash (text, 1523 bytes)
+Process: com.example.target
+PID: 12345
+UID: 10234
+Flags: 0x38c83e44
+Package: com.example.target 42 (1.4.2)
+Foreground: Yes
+Build: google/sdk_gphone64_x86_64/emu64x:14/UE1A.230829.036/10747227:userdebug/dev-keys
+
+java.lang.NullPointerException: Attempt to invoke virtual method 'void android.widget.TextView.setText(java.lang.CharSequence)' on a null object reference
+\tat com.example.target.MainActivity.onCreate(MainActivity.java:42)
+\tat android.app.Activity.performCreate(Activity.java:8595)
+\tat android.app.ActivityThread.main(ActivityThread.java:8177)
+
+========================================
+2024-01-15 11:02:10 data_app_crash (text, 1188 bytes)
+Process: com.example.target2
+PID: 23456
+UID: 10235
+Package: com.example.target2 7 (0.9)
+Foreground: No
+Build: google/sdk_gphone64_x86_64/emu64x:14/UE1A.230829.036/10747227:userdebug/dev-keys
+
+java.lang.IllegalStateException: Not allowed to start service Intent
+\tat android.app.ContextImpl.startServiceCommon(ContextImpl.java:1945)
+
+";
+
+    /// `dumpsys dropbox --print SYSTEM_TOMBSTONE` 中的一次 native 崩溃
+    const TOMBSTONE_DUMP: &str = "Drop box contents: 1 entries
+Max entries: 1000
+Searching for: SYSTEM_TOMBSTONE
+
+========================================
+2024-01-16 08:00:01 SYSTEM_TOMBSTONE (compressed text, 23456 bytes)
+*** *** *** *** *** *** *** *** *** *** *** *** *** *** *** ***
+Build fingerprint: 'google/sdk_gphone64_arm64/emu64a:14/UE1A.230829.036/10747227:userdebug/dev-keys'
+Revision: '0'
+ABI: 'arm64'
+Timestamp: 2024-01-16 08:00:00.123456789+0800
+Cmdline: com.example.target
+pid: 4321, tid: 4350, name: RenderThread  >>> com.example.target <<<
+uid: 10234
+signal 11 (SIGSEGV), code 1 (SEGV_MAPERR), fault addr 0x0000000000000010
+    x0  0000000000000000  x1  0000007fd4b1c8a0  x2  0000000000000001  x3  0000000000000000
+
+backtrace:
+      #00 pc 000000000004f1a4  /data/app/~~abc==/com.example.target-xyz==/lib/arm64/libnative.so (Java_com_example_target_Native_crash+20)
+      #01 pc 0000000000355630  /apex/com.android.art/lib64/libart.so (art_quick_generic_jni_trampoline+144)
+
+memory near x1 ([stack]):
+    0000007fd4b1c890 0000000000000000 0000000000000000  ................
+";
+
+    #[test]
+    fn keeps_only_the_requested_package() {
+        let entries = parse_dropbox(APP_CRASH_DUMP, "com.example.target");
+        assert_eq!(entries.len(), 1);
+        let crash = &entries[0];
+        assert_eq!(crash.timestamp, "2024-01-15 10:23:45");
+        assert_eq!(crash.exception_class, "java.lang.NullPointerException");
+        assert!(crash.message.as_deref().unwrap().starts_with("Attempt to invoke virtual method"));
+        assert!(crash.stack_trace.starts_with("java.lang.NullPointerException"));
+        assert!(crash.stack_trace.ends_with("at android.app.ActivityThread.main(ActivityThread.java:8177)"));
+        assert!(!crash.stack_trace.contains("IllegalStateException"));
+
+        let other = parse_dropbox(APP_CRASH_DUMP, "com.example.target2");
+        assert_eq!(other.len(), 1);
+        assert_eq!(other[0].timestamp, "2024-01-15 11:02:10");
+        assert_eq!(other[0].exception_class, "java.lang.IllegalStateException");
+        assert_eq!(other[0].message.as_deref(), Some("Not allowed to start service Intent"));
+
+        assert!(parse_dropbox(APP_CRASH_DUMP, "com.example").is_empty());
+    }
+
+    #[test]
+    fn parses_native_tombstones() {
+        let entries = parse_dropbox(TOMBSTONE_DUMP, "com.example.target");
+        assert_eq!(
+            entries,
+            [CrashEntry {
+                timestamp: "2024-01-16 08:00:01".to_string(),
+                exception_class: "SIGSEGV".to_string(),
+                message: Some("code 1 (SEGV_MAPERR), fault addr 0x0000000000000010".to_string()),
+                stack_trace: "backtrace:
+      #00 pc 000000000004f1a4  /data/app/~~abc==/com.example.target-xyz==/lib/arm64/libnative.so (Java_com_example_target_Native_crash+20)
+      #01 pc 0000000000355630  /apex/com.android.art/lib64/libart.so (art_quick_generic_jni_trampoline+144)"
+                    .to_string(),
+            }]
+        );
+        assert!(parse_dropbox(TOMBSTONE_DUMP, "com.example.target2").is_empty());
+    }
+
+    #[test]
+    fn exception_without_message() {
+        let dump = "========================================
+2024-02-01 09:00:00 data_app_crash (text, 300 bytes)
+Process: com.example.target
+
+java.lang.StackOverflowError
+\tat com.example.target.Loop.run(Loop.java:3)
+";
+        let entries = parse_dropbox(dump, "com.example.target");
+        assert_eq!(entries[0].exception_class, "java.lang.StackOverflowError");
+        assert_eq!(entries[0].message, None);
+    }
+
+    #[test]
+    fn empty_or_truncated_dumps_yield_nothing() {
+        assert!(parse_dropbox("", "com.example.target").is_empty());
+        assert!(parse_dropbox("Drop box contents: 0 entries\nMax entries: 1000\n", "com.example.target").is_empty());
+        // 没有空行分隔头部与异常的截断条目
+        let truncated = "==========\n2024-02-01 09:00:00 data_app_crash (text, 10 bytes)\nProcess: com.example.target\n";
+        assert!(parse_dropbox(truncated, "com.example.target").is_empty());
+    }
+}
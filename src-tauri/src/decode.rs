@@ -0,0 +1,131 @@
+//! apktool 反编译的策略阶梯：完整解码资源失败时，按顺序尝试其他解码方式。
+//! 每个策略只声明参数与前提条件，新的恢复方式加在 `STRATEGIES` 中即可，不需要改动流水线。
+
+use std::path::Path;
+use std::process::{Command, Output};
+
+use crate::diagnostics;
+use crate::metrics::{Recorder, StepMetrics};
+
+/// 反编译失败的类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureKind {
+    /// public.xml 重复 / 格式错误、资源表无法解析等，跳过资源解码通常可以绕过
+    ResourceTable,
+    Other,
+}
+
+/// 一种反编译方式
+pub struct DecodeStrategy {
+    pub name: &'static str,
+    /// 追加在 `d <apk> -o <dir> -f -s` 之后的参数
+    pub extra_args: &'static [&'static str],
+    /// 上一次失败属于这些类别时才尝试；为空表示总是尝试（只应用于第一项）
+    pub retry_on: &'static [FailureKind],
+    /// 不解码资源，与需要修改资源的选项不兼容
+    pub skips_resources: bool,
+}
+
+/// 按顺序尝试的反编译方式
+pub const STRATEGIES: &[DecodeStrategy] = &[
+    DecodeStrategy { name: "full", extra_args: &[], retry_on: &[], skips_resources: false },
+    // 保留原始 resources.arsc，只解码 Manifest；改包名与权限仍可用
+    DecodeStrategy {
+        name: "no_res",
+        extra_args: &["-r", "--force-manifest"],
+        retry_on: &[FailureKind::ResourceTable],
+        skips_resources: true,
+    },
+];
+
+/// 按输出判断失败类别
+pub fn classify_failure(output: &str) -> FailureKind {
+    let matched = diagnostics::APKTOOL_RESOURCE_TABLE_FAILURES
+        .iter()
+        .any(|pattern| regex::Regex::new(pattern).unwrap().is_match(output));
+    if matched {
+        FailureKind::ResourceTable
+    } else {
+        FailureKind::Other
+    }
+}
+
+/// 策略在处理指标中的步骤名
+fn step_name(strategy: &DecodeStrategy) -> String {
+    if strategy.name == "full" {
+        "decompile".to_string()
+    } else {
+        format!("decompile_{}", strategy.name)
+    }
+}
+
+/// 由处理指标得到成功的反编译方式
+pub fn succeeded_strategy(steps: &[StepMetrics]) -> Option<&'static str> {
+    STRATEGIES
+        .iter()
+        .find(|strategy| {
+            let step = step_name(strategy);
+            steps.iter().any(|s| s.step == step && s.exit_code == Some(0))
+        })
+        .map(|strategy| strategy.name)
+}
+
+/// 策略能否处理上一次的失败
+fn applies(strategy: &DecodeStrategy, last_failure: Option<FailureKind>) -> bool {
+    match last_failure {
+        None => true,
+        Some(failure) => strategy.retry_on.contains(&failure),
+    }
+}
+
+/// 反编译结果
+pub enum Decoded {
+    Success { strategy: &'static str, output: Output },
+    /// 所有方式都失败，消息中说明失败原因以及哪些选项阻止了降级
+    Failed(String),
+}
+
+/// 依次尝试各策略反编译，直到成功或没有可用的策略
+pub fn decompile(
+    recorder: &mut Recorder,
+    java_path: &str,
+    apktool_path: &str,
+    apk_path: &str,
+    work_dir: &Path,
+    resource_options: &[String],
+) -> Result<Decoded, String> {
+    let mut last_failure: Option<(FailureKind, String)> = None;
+    let mut blocked: Vec<&str> = Vec::new();
+    for strategy in STRATEGIES {
+        if !applies(strategy, last_failure.as_ref().map(|(kind, _)| *kind)) {
+            continue;
+        }
+        if strategy.skips_resources && !resource_options.is_empty() {
+            blocked.push(strategy.name);
+            continue;
+        }
+        let output = recorder
+            .run(
+                &step_name(strategy),
+                Command::new(java_path)
+                    .args(["-jar", apktool_path, "d", apk_path, "-o", work_dir.to_str().unwrap(), "-f", "-s"])
+                    .args(strategy.extra_args),
+            )
+            .map_err(|e| format!("反编译命令执行失败: {}", e))?;
+        if output.status.success() {
+            return Ok(Decoded::Success { strategy: strategy.name, output });
+        }
+        let text = format!("{} {}", String::from_utf8_lossy(&output.stderr), String::from_utf8_lossy(&output.stdout));
+        last_failure = Some((classify_failure(&text), text));
+    }
+    let text = last_failure.map_or_else(|| "没有可用的反编译方式".to_string(), |(_, text)| text);
+    let mut message = format!("反编译失败: {}", text);
+    if !blocked.is_empty() {
+        message.push_str(&format!(
+            "\n资源解码失败，但以下选项需要解码资源，未尝试 {} 方式: {}。去掉这些选项后可重试",
+            blocked.join("、"),
+            resource_options.join("、")
+        ));
+    }
+    Ok(Decoded::Failed(message))
+}
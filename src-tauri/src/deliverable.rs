@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::incremental::{self, RunRecord};
+use crate::input_path::{self, Expect};
+use crate::io_pool::{IoPool, Priority};
+use crate::paths::AppPaths;
+use crate::signing::PipelineError;
+use crate::{axml, hash, quarantine};
+
+/// 打包好的交付文件
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct Deliverable {
+    pub zip_path: String,
+    pub sha256: String,
+}
+
+/// 原包名与新包名的对应关系
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct PackageMapping {
+    pub original_package: Option<String>,
+    pub new_package: String,
+    pub version_name: Option<String>,
+}
+
+/// Unix 时间戳对应的 UTC 日期 `YYYY-MM-DD`
+pub fn utc_date(timestamp: u64) -> String {
+    // Howard Hinnant 的 civil_from_days
+    let z = (timestamp / 86_400) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// 按处理记录的 ID 查找，找不到时视为输出 APK 路径，取该路径最近一次的记录
+fn find_record(paths: &AppPaths, run_id_or_apk_path: &str) -> Result<RunRecord, PipelineError> {
+    let records = incremental::load_records(paths);
+    records
+        .iter()
+        .rev()
+        .find(|r| r.job_id.as_deref() == Some(run_id_or_apk_path))
+        .or_else(|| records.iter().rev().find(|r| Path::new(&r.output_path) == Path::new(run_id_or_apk_path)))
+        .cloned()
+        .ok_or_else(|| PipelineError::InvalidInput(format!("找不到 {} 的处理记录", run_id_or_apk_path)))
+}
+
+fn expand_readme(template: &str, mapping: &PackageMapping, date: &str) -> String {
+    template
+        .replace("{package}", &mapping.new_package)
+        .replace("{version}", mapping.version_name.as_deref().unwrap_or(""))
+        .replace("{date}", date)
+}
+
+/// 将输出 APK、处理报告、包名映射、SHA256SUMS 与可选的说明文件打包为一个 ZIP，返回 ZIP 路径与其哈希。
+/// 条目顺序、时间戳与权限固定，相同输入重复导出得到相同的哈希
+/// 整文件哈希与 ZIP 写入在 IO 线程池中执行
+#[tauri::command]
+pub async fn package_deliverable(
+    app_paths: tauri::State<'_, AppPaths>,
+    pool: tauri::State<'_, IoPool>,
+    run_id_or_apk_path: String,
+    output_zip: String,
+    readme_template: Option<String>,
+) -> Result<Deliverable, PipelineError> {
+    let output_zip = input_path::arg(&output_zip, Expect::Any)?;
+    let app_paths = app_paths.inner().clone();
+    pool.run(Priority::Background, "package_deliverable", move || build(&app_paths, &run_id_or_apk_path, output_zip, readme_template))
+        .await
+        .map_err(PipelineError::Io)?
+}
+
+fn build(app_paths: &AppPaths, run_id_or_apk_path: &str, output_zip: String, readme_template: Option<String>) -> Result<Deliverable, PipelineError> {
+    let record = find_record(app_paths, run_id_or_apk_path)?;
+    let apk_path = Path::new(&record.output_path);
+    let apk_sha256 = hash::sha256_file(apk_path).map_err(PipelineError::Io)?;
+    if apk_sha256 != record.output_sha256 {
+        return Err(PipelineError::InvalidInput(format!("{} 在处理之后被修改过，与记录的哈希不一致", record.output_path)));
+    }
+
+    let version_name = axml::read_manifest_from_apk(apk_path)
+        .ok()
+        .and_then(|elements| elements.into_iter().find(|e| e.name == "manifest"))
+        .and_then(|manifest| manifest.attr("versionName").map(axml::AxmlValue::as_string));
+    let mapping = PackageMapping {
+        original_package: record.original_package.clone(),
+        new_package: record.package.clone(),
+        version_name,
+    };
+    let apk_name = apk_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| "app.apk".to_string());
+    let report = serde_json::to_vec_pretty(&record.report.clone().unwrap_or(serde_json::Value::Null))
+        .map_err(|e| PipelineError::Io(e.to_string()))?;
+    let mapping_json = serde_json::to_vec_pretty(&mapping).map_err(|e| PipelineError::Io(e.to_string()))?;
+
+    let mut members: Vec<(String, Vec<u8>)> = vec![
+        ("report.json".to_string(), report),
+        ("mapping.json".to_string(), mapping_json),
+    ];
+    if let Some(template) = &readme_template {
+        members.push(("README.txt".to_string(), expand_readme(template, &mapping, &utc_date(record.timestamp)).into_bytes()));
+    }
+    let mut sums = format!("{}  {}\n", apk_sha256, apk_name);
+    for (name, data) in &members {
+        sums.push_str(&format!("{}  {}\n", hash::sha256_bytes(data), name));
+    }
+    members.push(("SHA256SUMS".to_string(), sums.into_bytes()));
+
+    let fixed = zip::write::SimpleFileOptions::default()
+        .last_modified_time(zip::DateTime::default())
+        .unix_permissions(0o644);
+    let mut writer = zip::ZipWriter::new(fs::File::create(&output_zip)?);
+    writer.start_file(apk_name.as_str(), fixed.compression_method(zip::CompressionMethod::Stored))?;
+    io::copy(&mut fs::File::open(apk_path)?, &mut writer)?;
+    for (name, data) in &members {
+        writer.start_file(name.as_str(), fixed.compression_method(zip::CompressionMethod::Deflated))?;
+        writer.write_all(data)?;
+    }
+    writer.finish()?;
+    quarantine::strip_xattrs(Path::new(&output_zip));
+
+    Ok(Deliverable { sha256: hash::sha256_file(Path::new(&output_zip)).map_err(PipelineError::Io)?, zip_path: output_zip })
+}
@@ -0,0 +1,171 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Instant;
+use tauri::{Emitter, Manager};
+
+/// 同时执行的 adb 查询上限
+const MAX_CONCURRENT_QUERIES: usize = 2;
+
+pub const DATASET_INSTALLED_APPS: &str = "installed_apps";
+pub const DATASET_TRUSTED_PREFIXES: &str = "trusted_prefixes";
+pub const DATASET_PROPS: &str = "props";
+/// 应用名（`app_labels`），不参与预取
+pub const DATASET_APP_LABELS: &str = "app_labels";
+/// 可信前缀深度检查的结果（`prefix_risk`），不参与预取
+pub const DATASET_PREFIX_RISKS: &str = "prefix_risks";
+/// 设备能力（`capabilities`），首次使用时探测，不参与预取
+pub const DATASET_CAPABILITIES: &str = "capabilities";
+
+/// 会话内的设备数据缓存
+#[derive(Default)]
+pub struct DeviceCache {
+    data: Mutex<HashMap<String, HashMap<String, serde_json::Value>>>,
+    generation: AtomicU64,
+}
+
+impl DeviceCache {
+    pub fn get<T: DeserializeOwned>(&self, device_id: &str, dataset: &str) -> Option<T> {
+        let data = self.data.lock().unwrap();
+        let value = data.get(device_id)?.get(dataset)?.clone();
+        serde_json::from_value(value).ok()
+    }
+
+    pub fn put<T: Serialize>(&self, device_id: &str, dataset: &str, value: &T) {
+        if let Ok(value) = serde_json::to_value(value) {
+            self.data
+                .lock()
+                .unwrap()
+                .entry(device_id.to_string())
+                .or_default()
+                .insert(dataset.to_string(), value);
+        }
+    }
+
+    /// 设备状态变化（如卸载应用）后清除对应数据
+    pub fn invalidate(&self, device_id: &str, dataset: &str) {
+        if let Some(sets) = self.data.lock().unwrap().get_mut(device_id) {
+            sets.remove(dataset);
+        }
+    }
+
+    fn is_current(&self, generation: u64) -> bool {
+        self.generation.load(Ordering::SeqCst) == generation
+    }
+}
+
+/// 简单的计数信号量，限制并发的 adb 进程数
+struct Semaphore {
+    permits: Mutex<usize>,
+    cv: Condvar,
+}
+
+impl Semaphore {
+    fn acquire(&self) {
+        let mut permits = self.permits.lock().unwrap();
+        while *permits == 0 {
+            permits = self.cv.wait(permits).unwrap();
+        }
+        *permits -= 1;
+    }
+
+    fn release(&self) {
+        *self.permits.lock().unwrap() += 1;
+        self.cv.notify_one();
+    }
+}
+
+/// 单个数据集预取完成事件
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct DeviceDataReady {
+    pub device_id: String,
+    pub dataset: String,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub generation: u64,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub duration_ms: u64,
+    pub error: Option<String>,
+}
+
+/// 读取设备属性（getprop）
+pub fn load_device_props(device_id: &str) -> Result<HashMap<String, String>, String> {
+    let output = crate::adb::shell(device_id, &["getprop"])?;
+    let re = regex::Regex::new(r"^\[([^\]]+)\]: \[(.*)\]$").unwrap();
+    Ok(output
+        .lines()
+        .filter_map(|line| re.captures(line.trim()).map(|c| (c[1].to_string(), c[2].to_string())))
+        .collect())
+}
+
+fn load_dataset(app: &tauri::AppHandle, device_id: &str, dataset: &str) -> Result<serde_json::Value, String> {
+    let value = match dataset {
+        DATASET_INSTALLED_APPS => serde_json::to_value(crate::load_installed_apps(app, device_id)?),
+        DATASET_TRUSTED_PREFIXES => serde_json::to_value(crate::load_trusted_prefixes(device_id, None)?),
+        DATASET_PROPS => serde_json::to_value(load_device_props(device_id)?),
+        _ => return Err(format!("未知的数据集: {}", dataset)),
+    };
+    value.map_err(|e| e.to_string())
+}
+
+/// 选中设备后并发预取应用列表、前缀与属性，完成一项推送一次 `device-data-ready`。
+/// 返回本次预取的代号；再次调用（切换设备）会使旧代号失效，旧任务的结果将被丢弃
+#[tauri::command]
+pub fn prefetch_device(app: tauri::AppHandle, cache: tauri::State<'_, DeviceCache>, device_id: String) -> u64 {
+    let generation = cache.generation.fetch_add(1, Ordering::SeqCst) + 1;
+    let semaphore = Arc::new(Semaphore { permits: Mutex::new(MAX_CONCURRENT_QUERIES), cv: Condvar::new() });
+
+    for dataset in [DATASET_INSTALLED_APPS, DATASET_TRUSTED_PREFIXES, DATASET_PROPS] {
+        let app = app.clone();
+        let device_id = device_id.clone();
+        let semaphore = semaphore.clone();
+        thread::spawn(move || {
+            let cache = app.state::<DeviceCache>();
+            semaphore.acquire();
+            if !cache.is_current(generation) {
+                semaphore.release();
+                return;
+            }
+            let start = Instant::now();
+            let result = load_dataset(&app, &device_id, dataset);
+            semaphore.release();
+
+            let duration_ms = start.elapsed().as_millis() as u64;
+            crate::emit_progress(&app, "prefetch", format!("{} {} 用时 {} ms", device_id, dataset, duration_ms));
+            if !cache.is_current(generation) {
+                return;
+            }
+            let error = match result {
+                Ok(value) => {
+                    cache.put(&device_id, dataset, &value);
+                    None
+                }
+                Err(e) => Some(e),
+            };
+            let _ = app.emit(
+                "device-data-ready",
+                DeviceDataReady { device_id, dataset: dataset.to_string(), generation, duration_ms, error },
+            );
+        });
+    }
+    generation
+}
+
+/// 设备属性，优先使用缓存，未命中时读取并写入缓存
+pub fn cached_props(cache: &DeviceCache, device_id: &str) -> Result<HashMap<String, String>, String> {
+    if let Some(props) = cache.get(device_id, DATASET_PROPS) {
+        return Ok(props);
+    }
+    let props = load_device_props(device_id)?;
+    cache.put(device_id, DATASET_PROPS, &props);
+    Ok(props)
+}
+
+/// 获取设备属性，优先使用预取缓存
+#[tauri::command]
+pub fn get_device_props(cache: tauri::State<'_, DeviceCache>, device_id: String) -> Result<HashMap<String, String>, String> {
+    cached_props(&cache, &device_id)
+}
@@ -0,0 +1,224 @@
+//! 浏览设备共享存储并拉取文件，用于直接处理设备上（如 /sdcard/Download）的 APK；
+//! 也可以按包名拉取已安装应用的 APK 作为处理的输入。
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Component, Path};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use tauri::{Emitter, Manager};
+
+use crate::adb;
+use crate::adb_coordinator::AdbCoordinator;
+use crate::input_path::{self, Expect};
+
+/// 允许浏览的设备目录
+const ALLOWED_ROOTS: &[&str] = &["/sdcard", "/storage"];
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(300);
+
+/// 设备上的一个文件或目录
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct DeviceFile {
+    pub name: String,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub size: u64,
+    /// 目录，或指向目录的符号链接（如 /sdcard -> /storage/self/primary）
+    pub is_dir: bool,
+    /// `ls` 显示的修改时间，格式随设备而异
+    pub mtime: String,
+}
+
+/// 拉取进度（`device-pull-progress` 事件）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct PullProgress {
+    pub remote_path: String,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub pulled_bytes: u64,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub total_bytes: u64,
+}
+
+/// 校验设备路径：必须是绝对路径、不含 `..`，且位于允许的目录下
+fn check_remote_path(path: &str) -> Result<(), String> {
+    let p = Path::new(path);
+    let valid = path.starts_with('/')
+        && !p.components().any(|c| matches!(c, Component::ParentDir))
+        && ALLOWED_ROOTS.iter().any(|root| p.starts_with(root));
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("只能访问 {} 下的文件: {}", ALLOWED_ROOTS.join("、"), path))
+    }
+}
+
+/// 单引号包裹，供设备 shell 解析；路径中的单引号转义为 `'\''`
+pub(crate) fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+/// 解析 `ls -l` 的一行，兼容 toybox（`2024-05-01 12:34`）与 busybox（`May  1 12:34` / `May  1  2023`）的时间格式。
+/// 文件名取时间之后的全部内容，可以包含空格
+pub fn parse_ls_line(line: &str) -> Option<DeviceFile> {
+    let toybox = regex::Regex::new(r"^([-dlcbps])\S*\s+.*?(\d+)\s+(\d{4}-\d{2}-\d{2}\s+\d{2}:\d{2}(?::\d{2}(?:\.\d+)?)?)\s(.+)$").unwrap();
+    let busybox = regex::Regex::new(r"^([-dlcbps])\S*\s+.*?(\d+)\s+([A-Z][a-z]{2}\s+\d{1,2}\s+(?:\d{2}:\d{2}|\d{4}))\s(.+)$").unwrap();
+    let caps = toybox.captures(line).or_else(|| busybox.captures(line))?;
+    let kind = &caps[1];
+    let mut name = caps[4].to_string();
+    if kind == "l" {
+        if let Some((link, _target)) = name.split_once(" -> ") {
+            name = link.to_string();
+        }
+    }
+    if name == "." || name == ".." {
+        return None;
+    }
+    Some(DeviceFile {
+        name,
+        size: caps[2].parse().unwrap_or(0),
+        is_dir: kind == "d" || kind == "l",
+        mtime: regex::Regex::new(r"\s+").unwrap().replace_all(&caps[3], " ").to_string(),
+    })
+}
+
+/// 列出设备目录内容，目录在前，按名称排序
+#[tauri::command]
+pub fn list_device_files(device_id: String, path: String) -> Result<Vec<DeviceFile>, String> {
+    check_remote_path(&path)?;
+    // 末尾加 `/`，使 /sdcard 这类符号链接列出的是目标目录的内容
+    let dir = format!("{}/", path.trim_end_matches('/'));
+    let output = adb::shell(&device_id, &["ls", "-l", &shell_quote(&dir)])?;
+    let mut files: Vec<DeviceFile> = output.lines().filter_map(|line| parse_ls_line(line.trim_end_matches('\r'))).collect();
+    if files.is_empty() && output.contains("No such file") {
+        return Err(format!("设备上不存在目录: {}", path));
+    }
+    files.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+    Ok(files)
+}
+
+fn remote_size(device_id: &str, remote_path: &str) -> Result<u64, String> {
+    let output = adb::shell(device_id, &["stat", "-c", "%s", &shell_quote(remote_path)])?;
+    output.trim().parse().map_err(|_| format!("无法获取设备文件大小: {}", output.trim()))
+}
+
+/// `adb pull` 单个文件，推送进度事件，完成后核对大小；返回拉取的字节数
+fn pull_with_progress(app: &tauri::AppHandle, device_id: &str, remote_path: &str, local_path: &Path, total_bytes: u64) -> Result<u64, String> {
+    // adb pull 只在终端中显示进度，这里轮询本地文件大小
+    let done = Arc::new(AtomicBool::new(false));
+    let reporter = {
+        let (done, app, local_path, remote_path) = (done.clone(), app.clone(), local_path.to_path_buf(), remote_path.to_string());
+        thread::spawn(move || {
+            while !done.load(Ordering::Relaxed) {
+                let pulled_bytes = fs::metadata(&local_path).map(|m| m.len()).unwrap_or(0);
+                let _ = app.emit("device-pull-progress", PullProgress { remote_path: remote_path.clone(), pulled_bytes, total_bytes });
+                thread::sleep(PROGRESS_INTERVAL);
+            }
+        })
+    };
+    // pull 的参数不经过设备 shell，不需要加引号
+    let output = crate::adb::command().args(["-s", device_id, "pull", remote_path]).arg(local_path).output();
+    done.store(true, Ordering::Relaxed);
+    let _ = reporter.join();
+    let output = output.map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(format!("拉取失败: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+
+    let pulled_bytes = fs::metadata(local_path).map(|m| m.len()).map_err(|e| e.to_string())?;
+    if pulled_bytes != total_bytes {
+        let _ = fs::remove_file(local_path);
+        return Err(format!("拉取的文件不完整: 设备上 {} 字节，本地 {} 字节", total_bytes, pulled_bytes));
+    }
+    let _ = app.emit("device-pull-progress", PullProgress { remote_path: remote_path.to_string(), pulled_bytes, total_bytes });
+    Ok(pulled_bytes)
+}
+
+/// 将设备文件拉取到本地目录，推送进度事件，完成后核对大小；返回本地路径
+#[tauri::command]
+pub async fn pull_device_file(app: tauri::AppHandle, device_id: String, remote_path: String, local_dir: String) -> Result<String, String> {
+    let local_dir = input_path::arg(&local_dir, Expect::Any).map_err(|e| e.to_string())?;
+    check_remote_path(&remote_path)?;
+    let name = Path::new(&remote_path).file_name().ok_or_else(|| format!("无效的文件路径: {}", remote_path))?;
+    let local_path = Path::new(&local_dir).join(name);
+    let total_bytes = remote_size(&device_id, &remote_path)?;
+    fs::create_dir_all(&local_dir).map_err(|e| e.to_string())?;
+
+    let coordinator = app.state::<AdbCoordinator>();
+    let _transfer = coordinator.begin_transfer(&device_id, "pull");
+    pull_with_progress(&app, &device_id, &remote_path, &local_path, total_bytes)?;
+    Ok(local_path.to_string_lossy().to_string())
+}
+
+/// 从设备拉取的一个已安装 APK
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct PulledApk {
+    pub remote_path: String,
+    pub local_path: String,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub size: u64,
+    /// base.apk（其余为 split APK）
+    pub is_base: bool,
+}
+
+/// `pm path` 输出中的 APK 路径，base.apk 在前
+pub fn parse_pm_path(output: &str) -> Vec<String> {
+    let mut paths: Vec<String> = output.lines().filter_map(|line| line.trim().strip_prefix("package:")).map(str::to_string).collect();
+    paths.sort_by_key(|p| !p.ends_with("/base.apk"));
+    paths
+}
+
+/// 本地文件名：base 为 `<包名>.apk`，split 为 `<包名>_<原文件名>`
+fn local_apk_name(package: &str, remote_path: &str, is_base: bool) -> String {
+    let file = remote_path.rsplit('/').next().unwrap_or(remote_path);
+    if is_base {
+        format!("{}.apk", package)
+    } else {
+        format!("{}_{}", package, file)
+    }
+}
+
+fn unreadable(remote_path: &str, detail: &str) -> String {
+    format!("无法读取 {}（系统应用的 APK 可能没有读取权限，需要 root）: {}", remote_path, detail.trim())
+}
+
+/// 将设备上已安装应用的 APK（含全部 split APK）拉取到本地目录，推送进度事件，返回本地文件与大小。
+/// 返回的 base APK 路径可直接作为处理的输入
+#[tauri::command]
+pub async fn pull_apk(app: tauri::AppHandle, device_id: String, package_name: String, dest_dir: String) -> Result<Vec<PulledApk>, String> {
+    let dest_dir = input_path::arg(&dest_dir, Expect::Any).map_err(|e| e.to_string())?;
+    if !crate::pm::is_package_name(&package_name) {
+        return Err(format!("无效的包名: {}", package_name));
+    }
+    let remote_paths = parse_pm_path(&adb::shell(&device_id, &["pm", "path", &package_name])?);
+    if remote_paths.is_empty() {
+        return Err(format!("设备上未安装 {}", package_name));
+    }
+    let mut sizes = Vec::new();
+    for remote in &remote_paths {
+        let size = remote_size(&device_id, remote).map_err(|e| unreadable(remote, &e))?;
+        sizes.push(size);
+    }
+    fs::create_dir_all(&dest_dir).map_err(|e| e.to_string())?;
+
+    let coordinator = app.state::<AdbCoordinator>();
+    let _transfer = coordinator.begin_transfer(&device_id, "pull");
+    let mut pulled = Vec::new();
+    for (i, (remote, total_bytes)) in remote_paths.iter().zip(sizes).enumerate() {
+        let is_base = i == 0;
+        let local_path = Path::new(&dest_dir).join(local_apk_name(&package_name, remote, is_base));
+        let size = pull_with_progress(&app, &device_id, remote, &local_path, total_bytes).map_err(|e| {
+            if e.contains("Permission denied") {
+                unreadable(remote, &e)
+            } else {
+                e
+            }
+        })?;
+        pulled.push(PulledApk { remote_path: remote.clone(), local_path: local_path.to_string_lossy().to_string(), size, is_base });
+    }
+    Ok(pulled)
+}
@@ -0,0 +1,229 @@
+//! 按设备序列号保存的安装偏好：有的设备预装了更高 versionCode 的版本，总是需要允许降级；
+//! 有的设备安装前需要先点亮屏幕才能确认。偏好与处理参数无关，每条安装路径都会叠加：
+//! 内置默认值 → 设备偏好 → 本次处理参数，显式指定的处理参数优先。
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+
+use crate::paths::{self, AppPaths};
+use crate::post_install::PostInstallAction;
+
+const PREFS_FILE: &str = "device_preferences.json";
+
+/// `adb install` 参数；未设置的字段沿用下一层的值
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct InstallFlags {
+    /// `-d`，默认否
+    pub allow_downgrade: Option<bool>,
+    /// `-g`，默认是
+    pub grant_permissions: Option<bool>,
+    /// `-t`，默认是
+    pub allow_test: Option<bool>,
+    /// `--user`，默认不指定
+    pub user_id: Option<u32>,
+}
+
+/// 单台设备的安装偏好
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct DevicePreferences {
+    /// 显示名（如“扫描枪 7 号”）
+    pub alias: Option<String>,
+    #[serde(default)]
+    pub install_flags: InstallFlags,
+    /// 安装前唤醒屏幕，避免确认对话框在熄屏时超时
+    #[serde(default)]
+    pub keep_awake: bool,
+    /// 在处理参数的安装后动作之后追加执行
+    #[serde(default)]
+    pub post_install_actions: Vec<PostInstallAction>,
+}
+
+/// 叠加后的安装参数，`applied` 记录生效的设备偏好
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResolvedInstall {
+    pub flags: InstallFlags,
+    pub keep_awake: bool,
+    pub post_install_actions: Vec<PostInstallAction>,
+    pub applied: Vec<String>,
+}
+
+impl ResolvedInstall {
+    /// `install` 之后、APK 路径之前的参数
+    pub fn install_args(&self) -> Vec<String> {
+        let mut args = vec!["-r".to_string()];
+        if self.flags.allow_test.unwrap_or(true) {
+            args.push("-t".to_string());
+        }
+        if self.flags.grant_permissions.unwrap_or(true) {
+            args.push("-g".to_string());
+        }
+        if self.flags.allow_downgrade.unwrap_or(false) {
+            args.push("-d".to_string());
+        }
+        if let Some(user) = self.flags.user_id {
+            args.extend(["--user".to_string(), user.to_string()]);
+        }
+        args
+    }
+}
+
+/// `upper` 中已设置的字段覆盖 `lower`
+fn overlay<T: Clone>(lower: &Option<T>, upper: &Option<T>) -> Option<T> {
+    upper.clone().or_else(|| lower.clone())
+}
+
+/// 设备偏好中被本次参数保留下来（未被覆盖）的字段，写入日志
+fn applied_overrides(device: &InstallFlags, run: &InstallFlags) -> Vec<String> {
+    let mut applied = Vec::new();
+    let mut note = |name: &str, device: Option<String>, run_set: bool| {
+        if let (Some(value), false) = (device, run_set) {
+            applied.push(format!("{}={}", name, value));
+        }
+    };
+    note("allow_downgrade", device.allow_downgrade.map(|v| v.to_string()), run.allow_downgrade.is_some());
+    note("grant_permissions", device.grant_permissions.map(|v| v.to_string()), run.grant_permissions.is_some());
+    note("allow_test", device.allow_test.map(|v| v.to_string()), run.allow_test.is_some());
+    note("user_id", device.user_id.map(|v| v.to_string()), run.user_id.is_some());
+    applied
+}
+
+/// 叠加设备偏好与本次处理参数：安装参数逐字段取处理参数、其次设备偏好、最后内置默认值；
+/// 安装后动作先执行处理参数中的，再执行设备偏好中的
+pub fn resolve(device: Option<&DevicePreferences>, run_flags: &InstallFlags, run_actions: &[PostInstallAction]) -> ResolvedInstall {
+    let default = DevicePreferences::default();
+    let device = device.unwrap_or(&default);
+    let lower = &device.install_flags;
+    let flags = InstallFlags {
+        allow_downgrade: overlay(&lower.allow_downgrade, &run_flags.allow_downgrade),
+        grant_permissions: overlay(&lower.grant_permissions, &run_flags.grant_permissions),
+        allow_test: overlay(&lower.allow_test, &run_flags.allow_test),
+        user_id: overlay(&lower.user_id, &run_flags.user_id),
+    };
+    let mut applied = applied_overrides(lower, run_flags);
+    if device.keep_awake {
+        applied.push("keep_awake".to_string());
+    }
+    if !device.post_install_actions.is_empty() {
+        applied.push(format!("{} 个设备安装后动作", device.post_install_actions.len()));
+    }
+    let post_install_actions = run_actions.iter().chain(&device.post_install_actions).cloned().collect();
+    ResolvedInstall { flags, keep_awake: device.keep_awake, post_install_actions, applied }
+}
+
+pub fn load_all(paths: &AppPaths) -> BTreeMap<String, DevicePreferences> {
+    fs::read_to_string(paths.config_dir.join(PREFS_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn load(paths: &AppPaths, serial: &str) -> Option<DevicePreferences> {
+    load_all(paths).remove(serial)
+}
+
+/// 按设备偏好叠加本次安装参数
+pub fn resolve_for(paths: &AppPaths, serial: &str, run_flags: &InstallFlags, run_actions: &[PostInstallAction]) -> ResolvedInstall {
+    resolve(load(paths, serial).as_ref(), run_flags, run_actions)
+}
+
+/// 安装前唤醒屏幕；失败不影响安装
+pub fn wake_screen(device_id: &str) {
+    let _ = crate::adb::shell(device_id, &["input", "keyevent", "KEYCODE_WAKEUP"]);
+}
+
+/// 获取设备的安装偏好，未设置时返回 None
+#[tauri::command]
+pub fn get_device_preferences(paths: tauri::State<'_, AppPaths>, serial: String) -> Option<DevicePreferences> {
+    load(&paths, &serial)
+}
+
+/// 所有设置了安装偏好的设备
+#[tauri::command]
+pub fn list_device_preferences(paths: tauri::State<'_, AppPaths>) -> BTreeMap<String, DevicePreferences> {
+    load_all(&paths)
+}
+
+/// 保存设备的安装偏好；传入 None 时删除
+#[tauri::command]
+pub fn set_device_preferences(
+    app: tauri::AppHandle,
+    paths: tauri::State<'_, AppPaths>,
+    serial: String,
+    prefs: Option<DevicePreferences>,
+) -> Result<(), String> {
+    let mut all = load_all(&paths);
+    match &prefs {
+        Some(prefs) => {
+            for action in &prefs.post_install_actions {
+                if let PostInstallAction::Shell { command } = action {
+                    crate::post_install::check_shell_command(command)?;
+                }
+            }
+            all.insert(serial.clone(), prefs.clone())
+        }
+        None => all.remove(&serial),
+    };
+    let result = serde_json::to_vec_pretty(&all)
+        .map_err(|e| e.to_string())
+        .and_then(|content| paths::write_atomic(&paths.config_dir.join(PREFS_FILE), &content).map_err(|e| e.to_string()));
+    crate::audit::record(&app, "set_device_preferences", serde_json::json!({ "serial": serial, "prefs": prefs }), &result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shell(command: &str) -> PostInstallAction {
+        PostInstallAction::Shell { command: command.to_string() }
+    }
+
+    #[test]
+    fn defaults_apply_without_device_or_run_values() {
+        let resolved = resolve(None, &InstallFlags::default(), &[]);
+        assert_eq!(resolved.install_args(), ["-r", "-t", "-g"]);
+        assert!(!resolved.keep_awake);
+        assert!(resolved.applied.is_empty());
+    }
+
+    #[test]
+    fn device_overrides_fill_fields_the_preset_leaves_unset() {
+        let device = DevicePreferences {
+            install_flags: InstallFlags { allow_downgrade: Some(true), user_id: Some(10), ..Default::default() },
+            keep_awake: true,
+            ..Default::default()
+        };
+        let preset = InstallFlags { grant_permissions: Some(false), ..Default::default() };
+        let resolved = resolve(Some(&device), &preset, &[]);
+        assert_eq!(
+            resolved.flags,
+            InstallFlags { allow_downgrade: Some(true), grant_permissions: Some(false), allow_test: None, user_id: Some(10) }
+        );
+        assert_eq!(resolved.install_args(), ["-r", "-t", "-d", "--user", "10"]);
+        assert_eq!(resolved.applied, ["allow_downgrade=true", "user_id=10", "keep_awake"]);
+    }
+
+    #[test]
+    fn explicit_preset_values_win_over_device_overrides() {
+        let device = DevicePreferences {
+            install_flags: InstallFlags { allow_downgrade: Some(true), allow_test: Some(false), ..Default::default() },
+            ..Default::default()
+        };
+        let preset = InstallFlags { allow_downgrade: Some(false), ..Default::default() };
+        let resolved = resolve(Some(&device), &preset, &[]);
+        assert_eq!(resolved.flags.allow_downgrade, Some(false));
+        assert_eq!(resolved.install_args(), ["-r", "-g"]);
+        assert_eq!(resolved.applied, ["allow_test=false"]);
+    }
+
+    #[test]
+    fn device_actions_run_after_preset_actions() {
+        let device = DevicePreferences { post_install_actions: vec![shell("input keyevent KEYCODE_HOME")], ..Default::default() };
+        let resolved = resolve(Some(&device), &InstallFlags::default(), &[shell("am start -n x/.Main")]);
+        assert_eq!(resolved.post_install_actions, [shell("am start -n x/.Main"), shell("input keyevent KEYCODE_HOME")]);
+        assert_eq!(resolved.applied, ["1 个设备安装后动作"]);
+    }
+}
@@ -0,0 +1,97 @@
+//! 设备 /data 分区的存储空间。安装前据此检查空间，避免 `INSTALL_FAILED_INSUFFICIENT_STORAGE` 只给出原始的 adb 输出。
+
+use serde::{Deserialize, Serialize};
+
+use crate::adb;
+
+/// /data 分区的空间，单位字节
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct DeviceStorage {
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub total_bytes: u64,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub used_bytes: u64,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub free_bytes: u64,
+}
+
+/// `12.5G`、`512M`、`1024K` 这类带单位的大小
+fn parse_human_size(value: &str) -> Option<u64> {
+    let (number, multiplier) = match value.chars().last()? {
+        'K' | 'k' => (&value[..value.len() - 1], 1u64 << 10),
+        'M' => (&value[..value.len() - 1], 1 << 20),
+        'G' => (&value[..value.len() - 1], 1 << 30),
+        'T' => (&value[..value.len() - 1], 1 << 40),
+        _ => (value, 1),
+    };
+    number.parse::<f64>().ok().map(|n| (n * multiplier as f64) as u64)
+}
+
+/// 解析 `df /data` 的输出。toybox 与 busybox 的列为 `Filesystem 1K-blocks Used Available Use% Mounted on`，
+/// busybox 在文件系统名过长时会把数字换到下一行；Android 6 之前的 toolbox 为 `Filesystem Size Used Free Blksize`，大小带单位
+pub fn parse_df(output: &str) -> Option<DeviceStorage> {
+    let mut lines = output.lines().map(str::trim).filter(|line| !line.is_empty());
+    let header = lines.next()?;
+    let human = !header.contains("1K-blocks") && !header.contains("1k-blocks");
+    // 文件系统名之后依次为总量、已用、可用；合并剩余行以兼容换行的输出
+    let tokens: Vec<&str> = lines.flat_map(str::split_whitespace).collect();
+    let values: Vec<u64> = tokens
+        .iter()
+        .skip(1)
+        .take(3)
+        .map(|token| if human { parse_human_size(token) } else { token.parse::<u64>().ok().map(|kb| kb * 1024) })
+        .collect::<Option<_>>()?;
+    match values[..] {
+        [total_bytes, used_bytes, free_bytes] => Some(DeviceStorage { total_bytes, used_bytes, free_bytes }),
+        _ => None,
+    }
+}
+
+pub fn query(device_id: &str) -> Result<DeviceStorage, String> {
+    let output = adb::shell(device_id, &["df", "/data"])?;
+    parse_df(&output).ok_or_else(|| format!("无法解析 df 输出: {}", output.trim()))
+}
+
+/// 设备 /data 分区的总量、已用与可用空间
+#[tauri::command]
+pub fn get_device_storage(device_id: String) -> Result<DeviceStorage, String> {
+    query(&device_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toybox_df() {
+        let output = "Filesystem      1K-blocks     Used Available Use% Mounted on\n/dev/block/dm-5 115343360 48502784  66709504  43% /data\n";
+        let storage = parse_df(output).unwrap();
+        assert_eq!(storage, DeviceStorage { total_bytes: 115343360 * 1024, used_bytes: 48502784 * 1024, free_bytes: 66709504 * 1024 });
+    }
+
+    #[test]
+    fn busybox_df_with_wrapped_filesystem_name() {
+        let output = "Filesystem           1k-blocks      Used Available Use% Mounted on\r\n\
+/dev/block/platform/msm_sdcc.1/by-name/userdata\r\n\
+                      12868728   9631688   3237040  75% /data\r\n";
+        let storage = parse_df(output).unwrap();
+        assert_eq!(storage.total_bytes, 12868728 * 1024);
+        assert_eq!(storage.used_bytes, 9631688 * 1024);
+        assert_eq!(storage.free_bytes, 3237040 * 1024);
+    }
+
+    #[test]
+    fn legacy_toolbox_df_with_units() {
+        let output = "Filesystem               Size     Used     Free   Blksize\n/data                   12.2G    9.1G     3.1G   4096\n";
+        let storage = parse_df(output).unwrap();
+        assert_eq!(storage.total_bytes, (12.2 * (1u64 << 30) as f64) as u64);
+        assert_eq!(storage.free_bytes, (3.1 * (1u64 << 30) as f64) as u64);
+    }
+
+    #[test]
+    fn errors_are_not_parsed() {
+        assert_eq!(parse_df("df: /data: Permission denied\n"), None);
+        assert_eq!(parse_df(""), None);
+    }
+}
@@ -0,0 +1,297 @@
+//! DEX 文件的最小改写：去掉调试信息（源文件行号表、局部变量名）并重算头部校验值。
+
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use crate::input_path::{self, Expect};
+use crate::signing::PipelineError;
+
+const TYPE_CODE_ITEM: u16 = 0x2001;
+const TYPE_DEBUG_INFO_ITEM: u16 = 0x2003;
+
+/// 去除调试信息前后的大小
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct SizeReduction {
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub original_bytes: u64,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub new_bytes: u64,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub saved_bytes: u64,
+}
+
+fn u16_at(data: &[u8], pos: usize) -> Result<u16, String> {
+    data.get(pos..pos + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .ok_or_else(|| format!("DEX 在偏移 {} 处被截断", pos))
+}
+
+fn u32_at(data: &[u8], pos: usize) -> Result<u32, String> {
+    data.get(pos..pos + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| format!("DEX 在偏移 {} 处被截断", pos))
+}
+
+/// 读取 uleb128 / sleb128，返回 (值, 新位置)
+fn leb128_at(data: &[u8], mut pos: usize, signed: bool) -> Result<(i64, usize), String> {
+    let mut result: i64 = 0;
+    let mut shift = 0;
+    loop {
+        let byte = *data.get(pos).ok_or_else(|| format!("DEX 在偏移 {} 处被截断", pos))?;
+        pos += 1;
+        result |= ((byte & 0x7f) as i64) << shift;
+        shift += 7;
+        if byte & 0x80 == 0 {
+            if signed && shift < 64 && byte & 0x40 != 0 {
+                result |= -1i64 << shift;
+            }
+            return Ok((result, pos));
+        }
+        if shift >= 35 {
+            return Err(format!("偏移 {} 处的 LEB128 过长", pos));
+        }
+    }
+}
+
+/// code_item 结束位置（不含对齐填充）
+fn code_item_end(data: &[u8], start: usize) -> Result<usize, String> {
+    let tries_size = u16_at(data, start + 6)? as usize;
+    let insns_size = u32_at(data, start + 12)? as usize;
+    let mut pos = start + 16 + insns_size * 2;
+    if tries_size == 0 {
+        return Ok(pos);
+    }
+    if insns_size % 2 == 1 {
+        pos += 2;
+    }
+    pos += tries_size * 8;
+    let (handler_lists, mut pos) = leb128_at(data, pos, false)?;
+    for _ in 0..handler_lists {
+        let (size, next) = leb128_at(data, pos, true)?;
+        pos = next;
+        for _ in 0..size.unsigned_abs() {
+            pos = leb128_at(data, pos, false)?.1;
+            pos = leb128_at(data, pos, false)?.1;
+        }
+        if size <= 0 {
+            pos = leb128_at(data, pos, false)?.1;
+        }
+    }
+    Ok(pos)
+}
+
+/// DEX 头中使用的 Adler-32
+pub fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    (b << 16) | a
+}
+
+/// 重算头部的 SHA-1 签名（偏移 12）与 Adler-32 校验和（偏移 8）
+pub fn update_header_checksums(data: &mut [u8]) {
+    let signature = Sha1::digest(&data[32..]);
+    data[12..32].copy_from_slice(&signature);
+    let checksum = adler32(&data[12..]);
+    data[8..12].copy_from_slice(&checksum.to_le_bytes());
+}
+
+/// 将所有 code_item 的 debug_info_off 置 0，并把 debug_info_item 区段清零（清零后每项都是合法的空调试信息）。
+/// 返回被清除引用的 code_item 数
+pub fn strip_debug_info(data: &mut [u8]) -> Result<u32, String> {
+    if !data.starts_with(b"dex\n") || data.len() < 0x70 {
+        return Err("不是有效的 DEX 文件".to_string());
+    }
+    let map_off = u32_at(data, 0x34)? as usize;
+    let map_size = u32_at(data, map_off)? as usize;
+    let mut sections = Vec::with_capacity(map_size);
+    for i in 0..map_size {
+        let item = map_off + 4 + i * 12;
+        sections.push((u16_at(data, item)?, u32_at(data, item + 4)? as usize, u32_at(data, item + 8)? as usize));
+    }
+
+    let mut stripped = 0;
+    if let Some(&(_, count, offset)) = sections.iter().find(|(t, _, _)| *t == TYPE_CODE_ITEM) {
+        let mut pos = offset;
+        for _ in 0..count {
+            pos = (pos + 3) & !3;
+            if u32_at(data, pos + 8)? != 0 {
+                data[pos + 8..pos + 12].fill(0);
+                stripped += 1;
+            }
+            pos = code_item_end(data, pos)?;
+        }
+    }
+
+    if let Some(&(_, _, start)) = sections.iter().find(|(t, _, _)| *t == TYPE_DEBUG_INFO_ITEM) {
+        if start > data.len() {
+            return Err(format!("debug_info_item 的偏移 {} 超出文件大小 {}", start, data.len()));
+        }
+        let end = sections
+            .iter()
+            .map(|(_, _, offset)| *offset)
+            .filter(|offset| *offset > start)
+            .min()
+            .unwrap_or(data.len())
+            .min(data.len());
+        data[start..end].fill(0);
+    }
+
+    update_header_checksums(data);
+    Ok(stripped)
+}
+
+/// 对 APK 中的每个 DEX 去除调试信息，其余条目原样拷贝。输出需要重新对齐和签名
+pub fn strip_apk(src: &Path, dest: &Path) -> Result<SizeReduction, PipelineError> {
+    let mut archive = zip::ZipArchive::new(fs::File::open(src)?)?;
+    let mut writer = zip::ZipWriter::new(fs::File::create(dest)?);
+
+    for i in 0..archive.len() {
+        let is_dex = {
+            let entry = archive.by_index_raw(i)?;
+            let name = entry.name();
+            name.starts_with("classes") && name.ends_with(".dex") && !name.contains('/')
+        };
+        if !is_dex {
+            writer.raw_copy_file(archive.by_index_raw(i)?)?;
+            continue;
+        }
+
+        let mut entry = archive.by_index(i)?;
+        let name = entry.name().to_string();
+        let options = zip::write::SimpleFileOptions::default().compression_method(entry.compression());
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data)?;
+        strip_debug_info(&mut data).map_err(|e| PipelineError::InvalidInput(format!("{}: {}", name, e)))?;
+        writer.start_file(name, options)?;
+        writer.write_all(&data)?;
+    }
+    writer.finish()?;
+
+    let original_bytes = fs::metadata(src)?.len();
+    let new_bytes = fs::metadata(dest)?.len();
+    Ok(SizeReduction { original_bytes, new_bytes, saved_bytes: original_bytes.saturating_sub(new_bytes) })
+}
+
+/// 去除 APK 中所有 DEX 的调试信息（源文件名、行号表），不调用任何外部工具。
+/// 输出 APK 的签名已失效，需要重新对齐和签名
+#[tauri::command]
+pub fn strip_dex_debug_info(apk_path: String, output_path: String) -> Result<SizeReduction, PipelineError> {
+    let apk_path = input_path::arg(&apk_path, Expect::File)?;
+    let output_path = input_path::arg(&output_path, Expect::Any)?;
+    if Path::new(&apk_path) == Path::new(&output_path) {
+        return Err(PipelineError::InvalidInput("输出路径不能与原 APK 相同".to_string()));
+    }
+    strip_apk(Path::new(&apk_path), Path::new(&output_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SELF_TEST_APK: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/selftest.apk");
+    const DEBUG_INFO_OFF: usize = 0xa4;
+    const MAP_OFF: usize = 0xac;
+
+    /// 两个 code_item（第二个带 try/catch-all）各引用一段 debug_info_item，之后是 map_list
+    fn dex_with_debug_info() -> Vec<u8> {
+        let mut data = vec![0u8; MAP_OFF];
+        data[..8].copy_from_slice(b"dex\n035\0");
+        data[0x34..0x38].copy_from_slice(&(MAP_OFF as u32).to_le_bytes());
+        // code_item 1：无 try，1 个指令单元
+        data[0x78..0x7c].copy_from_slice(&(DEBUG_INFO_OFF as u32).to_le_bytes());
+        data[0x7c..0x80].copy_from_slice(&1u32.to_le_bytes());
+        data[0x80..0x82].copy_from_slice(&[0x0e, 0x00]);
+        // code_item 2：1 个 try，指令数为奇数需要 2 字节填充，handler 列表只有 catch-all
+        data[0x8a] = 1;
+        data[0x8c..0x90].copy_from_slice(&(DEBUG_INFO_OFF as u32 + 4).to_le_bytes());
+        data[0x90..0x94].copy_from_slice(&1u32.to_le_bytes());
+        data[0x94..0x96].copy_from_slice(&[0x0e, 0x00]);
+        data[0xa0..0xa3].copy_from_slice(&[1, 0, 0]);
+        data[DEBUG_INFO_OFF..MAP_OFF].copy_from_slice(&[1, 0, 0x07, 0x0e, 2, 1, 0x01, 0x00]);
+        let items: [(u16, u32, u32); 4] = [(0x0000, 1, 0), (TYPE_CODE_ITEM, 2, 0x70), (TYPE_DEBUG_INFO_ITEM, 2, DEBUG_INFO_OFF as u32), (0x1000, 1, MAP_OFF as u32)];
+        data.extend_from_slice(&(items.len() as u32).to_le_bytes());
+        for (kind, size, offset) in items {
+            data.extend_from_slice(&kind.to_le_bytes());
+            data.extend_from_slice(&[0, 0]);
+            data.extend_from_slice(&size.to_le_bytes());
+            data.extend_from_slice(&offset.to_le_bytes());
+        }
+        data
+    }
+
+    fn fixture_dex() -> Vec<u8> {
+        let mut archive = zip::ZipArchive::new(fs::File::open(SELF_TEST_APK).unwrap()).unwrap();
+        let mut data = Vec::new();
+        archive.by_name("classes.dex").unwrap().read_to_end(&mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn adler32_matches_known_vectors() {
+        assert_eq!(adler32(b""), 1);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+        // 超过 5552 字节时分块取模不能改变结果
+        let long = vec![0xffu8; 100_000];
+        let (mut a, mut b) = (1u64, 0u64);
+        for &byte in &long {
+            a = (a + byte as u64) % 65521;
+            b = (b + a) % 65521;
+        }
+        assert_eq!(adler32(&long), ((b << 16) | a) as u32);
+    }
+
+    #[test]
+    fn header_checksums_of_the_fixture_are_recomputed() {
+        let original = fixture_dex();
+        let mut data = original.clone();
+        data[8..32].fill(0);
+        update_header_checksums(&mut data);
+        assert_eq!(data, original);
+    }
+
+    #[test]
+    fn strip_zeroes_debug_info_offsets_and_section() {
+        let mut data = dex_with_debug_info();
+        assert_eq!(strip_debug_info(&mut data).unwrap(), 2);
+        assert_eq!(u32_at(&data, 0x78).unwrap(), 0);
+        assert_eq!(u32_at(&data, 0x8c).unwrap(), 0);
+        assert!(data[DEBUG_INFO_OFF..MAP_OFF].iter().all(|b| *b == 0));
+        // 指令与 map_list 不受影响
+        assert_eq!(&data[0x80..0x82], &[0x0e, 0x00]);
+        assert_eq!(u32_at(&data, MAP_OFF).unwrap(), 4);
+
+        let signature = Sha1::digest(&data[32..]);
+        assert_eq!(&data[12..32], signature.as_slice());
+        assert_eq!(u32_at(&data, 8).unwrap(), adler32(&data[12..]));
+        // 已去除的文件再次处理不再有引用
+        assert_eq!(strip_debug_info(&mut data).unwrap(), 0);
+    }
+
+    #[test]
+    fn debug_info_offset_past_the_end_is_an_error() {
+        let mut data = dex_with_debug_info();
+        let entry = MAP_OFF + 4 + 2 * 12;
+        data[entry + 8..entry + 12].copy_from_slice(&0x10_0000u32.to_le_bytes());
+        assert!(strip_debug_info(&mut data).unwrap_err().contains("超出文件大小"));
+    }
+
+    #[test]
+    fn truncated_code_item_is_an_error() {
+        let mut data = dex_with_debug_info();
+        let entry = MAP_OFF + 4 + 12;
+        data[entry + 8..entry + 12].copy_from_slice(&0x10_0000u32.to_le_bytes());
+        assert!(strip_debug_info(&mut data).unwrap_err().contains("截断"));
+    }
+}
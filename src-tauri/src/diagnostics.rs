@@ -0,0 +1,330 @@
+//! 外部工具输出的特征表：失败时的错误诊断与成功时仍需关注的警告放在一起维护。
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+/// apksigner 因密钥库算法 / 提供者不兼容而失败时的特征及对应的处理建议
+pub const KEYSTORE_ERROR_SIGNATURES: &[(&str, &str)] = &[
+    ("UnrecoverableKeyException", "密钥无法解密，通常是密钥库使用了旧版 PBE 算法或密钥口令与库口令不同"),
+    ("NoSuchAlgorithmException", "密钥库使用了当前 Java 不支持的算法"),
+    ("NoSuchProviderException", "密钥库依赖的安全提供者不可用"),
+    ("Unsupported key algorithm", "密钥算法不受支持（如 DSA）"),
+    ("KeyStoreException", "密钥库格式无法识别"),
+];
+
+/// apktool 反编译时资源表无法解析的失败特征（public.xml 重复或格式错误、资源表损坏等），
+/// 这类失败可以改用不解码资源的方式绕过
+pub const APKTOOL_RESOURCE_TABLE_FAILURES: &[&str] = &[
+    r"public\.xml",
+    r"(?i)duplicate (resource|entry|file)",
+    r"Multiple res specs",
+    r"(?i)could not decode arsc",
+    r"ResTable",
+    r"(?i)invalid (config|resource) (flags|spec)",
+    r"ARSCDecoder",
+];
+
+/// 资源类警告超过该数量时，即使 apktool 成功也视为输出可能有问题
+pub const DEFAULT_RESOURCE_WARNING_THRESHOLD: u32 = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+}
+
+impl Severity {
+    fn label(self) -> &'static str {
+        match self {
+            Severity::Low => "低",
+            Severity::Medium => "中",
+            Severity::High => "高",
+        }
+    }
+}
+
+/// apktool 退出码为 0 但提示输出可能损坏的特征：(正则, 说明, 是否为资源类, 严重程度)
+pub const APKTOOL_WARNING_PATTERNS: &[(&str, &str, bool, Severity)] = &[
+    (r"Could not decode attr value", "无法解码属性值", true, Severity::Medium),
+    (r"(?i)invalid resource (id|ID)", "资源 ID 无效", true, Severity::High),
+    (r"Cannot find ResPackage", "找不到资源包", true, Severity::High),
+    (r"Could not decode file, replacing by FALSE value", "资源文件无法解码，已替换为 FALSE", true, Severity::High),
+    (r"(?i)(couldn't|could not) find resource", "找不到引用的资源", true, Severity::High),
+    (r"No resource identifier found", "回编译时找不到资源标识", true, Severity::High),
+    (r"(?i)skipp(ed|ing) \d+ files?", "跳过了部分文件", false, Severity::High),
+    (r"Unknown file type, ignoring", "忽略了未知类型的文件", false, Severity::Low),
+    (r"(?i)warning: string '[^']*' has no default translation", "字符串缺少默认翻译", true, Severity::Low),
+];
+
+/// 某类 apktool 警告在一次运行中出现的次数
+#[derive(Debug, Clone)]
+pub struct ToolWarning {
+    pub stage: String,
+    pub description: &'static str,
+    pub resource: bool,
+    pub severity: Severity,
+    pub count: u32,
+}
+
+impl fmt::Display for ToolWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[apktool {} / {}级] {} ×{}", self.stage, self.severity.label(), self.description, self.count)
+    }
+}
+
+/// 统计 apktool 成功运行时输出中的警告，按特征分组
+pub fn scan_apktool_output(stage: &str, output: &str) -> Vec<ToolWarning> {
+    APKTOOL_WARNING_PATTERNS
+        .iter()
+        .filter_map(|(pattern, description, resource, severity)| {
+            let re = regex::Regex::new(pattern).unwrap();
+            let count = output.lines().filter(|line| re.is_match(line)).count() as u32;
+            (count > 0).then(|| ToolWarning {
+                stage: stage.to_string(),
+                description,
+                resource: *resource,
+                severity: *severity,
+                count,
+            })
+        })
+        .collect()
+}
+
+/// 资源类警告总数
+pub fn resource_warning_count(warnings: &[ToolWarning]) -> u32 {
+    warnings.iter().filter(|w| w.resource).map(|w| w.count).sum()
+}
+
+/// apktool 无法解析资源时生成的占位资源名前缀
+pub const APKTOOL_DUMMY_PREFIX: &str = "APKTOOL_DUMMY";
+
+/// 警告中列出的被引用最多的占位资源数
+const DUMMY_TOP_REFERENCED: usize = 5;
+
+/// 反编译目录中的 APKTOOL_DUMMY 占位资源。回编译后它们仍会存在，
+/// 使用到这些资源的界面可能在运行时崩溃
+#[derive(Debug, Clone, Default)]
+pub struct DummyResources {
+    /// 资源类型 -> 占位资源数
+    pub per_type: BTreeMap<String, u32>,
+    /// 占位资源名
+    pub names: BTreeSet<String>,
+    /// 被引用最多的占位资源：(`类型/名称`, 引用次数)
+    pub most_referenced: Vec<(String, u32)>,
+}
+
+impl DummyResources {
+    pub fn total(&self) -> u32 {
+        self.per_type.values().sum()
+    }
+
+    /// 按类型的数量与被引用最多的占位资源
+    pub fn summary(&self) -> String {
+        let per_type: Vec<String> = self.per_type.iter().map(|(kind, count)| format!("{} {}", kind, count)).collect();
+        let mut text = format!("apktool 生成了 {} 个 APKTOOL_DUMMY 占位资源（{}）", self.total(), per_type.join("，"));
+        if !self.most_referenced.is_empty() {
+            let top: Vec<String> = self.most_referenced.iter().map(|(name, count)| format!("{} ×{}", name, count)).collect();
+            text.push_str(&format!("，引用最多: {}", top.join("，")));
+        }
+        text
+    }
+
+    pub fn tool_warning(&self) -> Option<ToolWarning> {
+        let count = self.total();
+        (count > 0).then(|| ToolWarning {
+            stage: "decompile".to_string(),
+            description: "生成了 APKTOOL_DUMMY 占位资源",
+            resource: true,
+            severity: Severity::High,
+            count,
+        })
+    }
+}
+
+fn xml_files(dir: &Path) -> impl Iterator<Item = walkdir::DirEntry> {
+    walkdir::WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && e.path().extension().is_some_and(|ext| ext == "xml"))
+}
+
+/// 扫描 res/values*/ 下的声明（含 public.xml）与 res、Manifest 中对占位资源的引用
+pub fn scan_dummy_resources(work_dir: &Path) -> DummyResources {
+    let declaration_re = regex::Regex::new(&format!(r#"<(\w+)\b[^>]*\bname="({}[^"]*)"[^>]*>"#, APKTOOL_DUMMY_PREFIX)).unwrap();
+    let type_re = regex::Regex::new(r#"\btype="([^"]+)""#).unwrap();
+    let reference_re = regex::Regex::new(&format!(r"@(?:[\w.]+:)?(\w+)/({}\w*)", APKTOOL_DUMMY_PREFIX)).unwrap();
+
+    let res_dir = work_dir.join("res");
+    let mut declared: BTreeSet<(String, String)> = BTreeSet::new();
+    let mut references: HashMap<String, u32> = HashMap::new();
+    let manifest = work_dir.join("AndroidManifest.xml");
+    let files = xml_files(&res_dir).map(|e| e.into_path()).chain(manifest.is_file().then_some(manifest));
+    for path in files {
+        let Ok(content) = fs::read_to_string(&path) else { continue };
+        if !content.contains(APKTOOL_DUMMY_PREFIX) {
+            continue;
+        }
+        let in_values = path.parent().and_then(|p| p.file_name()).is_some_and(|d| d.to_string_lossy().starts_with("values"));
+        if in_values {
+            for cap in declaration_re.captures_iter(&content) {
+                // <item type="drawable" .../>、<public type="attr" .../> 的类型写在属性里
+                let kind = match &cap[1] {
+                    "item" | "public" => type_re.captures(&cap[0]).map_or_else(|| cap[1].to_string(), |t| t[1].to_string()),
+                    tag => tag.to_string(),
+                };
+                declared.insert((kind, cap[2].to_string()));
+            }
+        }
+        for cap in reference_re.captures_iter(&content) {
+            *references.entry(format!("{}/{}", &cap[1], &cap[2])).or_default() += 1;
+        }
+    }
+
+    let mut result = DummyResources::default();
+    for (kind, name) in declared {
+        *result.per_type.entry(kind).or_default() += 1;
+        result.names.insert(name);
+    }
+    let mut most_referenced: Vec<(String, u32)> = references.into_iter().collect();
+    most_referenced.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    most_referenced.truncate(DUMMY_TOP_REFERENCED);
+    result.most_referenced = most_referenced;
+    result
+}
+
+/// 输出 APK 的 resources.arsc 字符串池中出现的占位资源名
+pub fn dummy_names_in_apk(apk: &Path, names: &BTreeSet<String>) -> Result<Vec<String>, String> {
+    let file = fs::File::open(apk).map_err(|e| e.to_string())?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| e.to_string())?;
+    let mut data = Vec::new();
+    match archive.by_name("resources.arsc") {
+        Ok(mut entry) => entry.read_to_end(&mut data).map_err(|e| e.to_string())?,
+        Err(_) => return Ok(Vec::new()),
+    };
+    let strings = crate::analysis::arsc_strings(&data).ok_or("无法解析 resources.arsc 的字符串池")?;
+    let mut leaked: Vec<String> = strings.into_iter().filter(|s| names.contains(s)).collect();
+    leaked.sort();
+    leaked.dedup();
+    Ok(leaked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 加固过的扫码枪应用：反编译成功（退出码 0），但大量属性值无法解码
+    const DECODE_WITH_WARNINGS: &str = "I: Using Apktool 2.9.3 on scanner.apk
+I: Loading resource table...
+W: Could not decode attr value, using undecoded value instead: ns=android, name=drawable, value=0x7f080123
+W: Could not decode attr value, using undecoded value instead: ns=android, name=textColor, value=0x7f060011
+W: Could not decode attr value, using undecoded value instead: ns=android, name=src, value=0x7f0800aa
+W: Invalid resource ID 0x7f0a01ff
+W: Cannot find ResPackage for id 0x02
+I: Decoding AndroidManifest.xml with resources...
+I: Loading resource table from file: /root/.local/share/apktool/framework/1.apk
+I: Regular manifest package...
+I: Decoding file-resources...
+W: Could not decode file, replacing by FALSE value: res/drawable/ic_scan.xml
+I: Decoding values */* XMLs...
+W: warning: string 'scan_hint' has no default translation.
+I: Baksmaling classes.dex...
+I: Copying assets and libs...
+I: Copying unknown files...
+I: Copying original files...
+Unknown file type, ignoring: META-INF/services/com.vendor.Plugin
+I: Skipping 3 files
+";
+
+    /// 回编译成功，aapt2 报告找不到资源
+    const BUILD_WITH_WARNINGS: &str = "I: Using Apktool 2.9.3
+I: Checking whether sources has changed...
+I: Smaling smali folder into classes.dex...
+I: Checking whether resources has changed...
+I: Building resources...
+W: /tmp/work/res/layout/activity_main.xml:12: error: No resource identifier found for attribute 'scanMode' in package 'com.vendor.scanner'
+W: /tmp/work/res/values/styles.xml:4: couldn't find resource: @style/Theme.Vendor
+I: Building apk file...
+I: Copying unknown files/dir...
+I: Built apk into: /tmp/work/dist/scanner.apk
+";
+
+    const CLEAN_DECODE: &str = "I: Using Apktool 2.9.3 on app.apk
+I: Loading resource table...
+I: Decoding AndroidManifest.xml with resources...
+I: Baksmaling classes.dex...
+I: Copying assets and libs...
+";
+
+    /// public.xml 重复定义导致反编译失败
+    const DECODE_FAILURE: &str = "I: Using Apktool 2.9.3 on broken.apk
+I: Loading resource table...
+Exception in thread \"main\" brut.androlib.exceptions.AndrolibException: Multiple res specs: attr/scanMode
+\tat brut.androlib.res.data.ResTypeSpec.addResSpec(ResTypeSpec.java:78)
+\tat brut.androlib.res.decoder.ARSCDecoder.readEntry(ARSCDecoder.java:248)
+";
+
+    fn counts(warnings: &[ToolWarning]) -> Vec<(&str, u32)> {
+        warnings.iter().map(|w| (w.description, w.count)).collect()
+    }
+
+    #[test]
+    fn groups_decode_warnings_by_pattern() {
+        let warnings = scan_apktool_output("decompile", DECODE_WITH_WARNINGS);
+        assert_eq!(
+            counts(&warnings),
+            [
+                ("无法解码属性值", 3),
+                ("资源 ID 无效", 1),
+                ("找不到资源包", 1),
+                ("资源文件无法解码，已替换为 FALSE", 1),
+                ("跳过了部分文件", 1),
+                ("忽略了未知类型的文件", 1),
+                ("字符串缺少默认翻译", 1),
+            ]
+        );
+        assert!(warnings.iter().all(|w| w.stage == "decompile"));
+        // 跳过文件与未知文件类型不是资源类警告
+        assert_eq!(resource_warning_count(&warnings), 7);
+        assert_eq!(warnings[0].to_string(), "[apktool decompile / 中级] 无法解码属性值 ×3");
+    }
+
+    #[test]
+    fn groups_rebuild_warnings_by_pattern() {
+        let warnings = scan_apktool_output("rebuild", BUILD_WITH_WARNINGS);
+        assert_eq!(counts(&warnings), [("找不到引用的资源", 1), ("回编译时找不到资源标识", 1)]);
+        assert!(warnings.iter().all(|w| w.resource && w.severity == Severity::High));
+    }
+
+    #[test]
+    fn clean_output_has_no_warnings() {
+        assert!(scan_apktool_output("decompile", CLEAN_DECODE).is_empty());
+        assert_eq!(resource_warning_count(&[]), 0);
+    }
+
+    #[test]
+    fn threshold_is_exceeded_only_by_resource_warnings() {
+        let many = DECODE_WITH_WARNINGS.repeat(3);
+        let warnings = scan_apktool_output("decompile", &many);
+        assert_eq!(resource_warning_count(&warnings), 21);
+        assert!(resource_warning_count(&warnings) > DEFAULT_RESOURCE_WARNING_THRESHOLD);
+        assert!(resource_warning_count(&scan_apktool_output("decompile", DECODE_WITH_WARNINGS)) <= DEFAULT_RESOURCE_WARNING_THRESHOLD);
+    }
+
+    #[test]
+    fn resource_table_failures_match_the_failing_transcript() {
+        let matches = |output: &str| APKTOOL_RESOURCE_TABLE_FAILURES.iter().any(|p| regex::Regex::new(p).unwrap().is_match(output));
+        assert!(matches(DECODE_FAILURE));
+        assert!(!matches(CLEAN_DECODE));
+        // 成功但有警告的输出不应被当作资源表失败
+        assert!(!matches(BUILD_WITH_WARNINGS));
+    }
+
+    #[test]
+    fn severities_are_ordered() {
+        assert!(Severity::High > Severity::Medium && Severity::Medium > Severity::Low);
+    }
+}
@@ -0,0 +1,321 @@
+//! 从 HTTP(S) 地址下载 APK：流式写入、断点续传、可选 SHA-256 校验，
+//! 并记录下载来源，之后安装该文件时写入安装记录。
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, BufRead, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use tauri::{Emitter, Manager};
+
+use crate::input_path::{self, Expect};
+use crate::{hash, http};
+use crate::log_batch::{LogBatcher, LogChunk, DEFAULT_MAX_INTERVAL};
+use crate::paths::AppPaths;
+
+const DOWNLOADS_FILE: &str = "downloads.jsonl";
+const MAX_REDIRECTS: u32 = 5;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+const READ_TIMEOUT: Duration = Duration::from_secs(60);
+const BUF_SIZE: usize = 64 * 1024;
+
+/// 一个下载的进度；所有进行中的下载分批合并为 `download-progress` 事件
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct DownloadProgress {
+    pub url: String,
+    pub path: String,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub downloaded: u64,
+    /// 服务器返回 Content-Length 时可用
+    #[cfg_attr(feature = "export-bindings", ts(type = "number | null"))]
+    pub total: Option<u64>,
+    pub percent: Option<f64>,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub bytes_per_sec: u64,
+    pub done: bool,
+}
+
+/// 下载记录，用于把本地文件关联回来源地址
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct DownloadRecord {
+    url: String,
+    path: String,
+    sha256: Option<String>,
+    timestamp: u64,
+}
+
+/// 一批下载进度（`download-progress` 事件），与处理日志使用相同的分批与降采样
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct DownloadProgressEvent {
+    /// 处理完后用 `ack_download_events` 确认，否则之后的进度只推送省略条数
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub seq: u64,
+    /// 每个下载只保留这一批中的最新进度
+    pub downloads: Vec<DownloadProgress>,
+    pub omitted: usize,
+}
+
+/// 所有进行中的下载共用一个分批器
+#[derive(Default)]
+struct Active {
+    batcher: LogBatcher<DownloadProgress>,
+    /// 进行中的下载数，为 0 时定时发送线程退出
+    downloads: usize,
+}
+
+fn active() -> &'static Mutex<Active> {
+    static ACTIVE: OnceLock<Mutex<Active>> = OnceLock::new();
+    ACTIVE.get_or_init(Default::default)
+}
+
+fn emit(app: &tauri::AppHandle, chunk: Option<LogChunk<DownloadProgress>>) {
+    let Some(LogChunk { seq, lines, omitted }) = chunk else {
+        return;
+    };
+    let mut downloads: Vec<DownloadProgress> = Vec::new();
+    for progress in lines {
+        match downloads.iter_mut().find(|p| p.path == progress.path) {
+            Some(latest) => *latest = progress,
+            None => downloads.push(progress),
+        }
+    }
+    let _ = app.emit("download-progress", DownloadProgressEvent { seq, downloads, omitted });
+}
+
+/// 登记一个下载；第一个下载开始时启动定时发送线程，没有新进度时也按时间间隔发出积压的进度
+fn begin(app: &tauri::AppHandle) {
+    let mut state = active().lock().unwrap();
+    state.downloads += 1;
+    if state.downloads > 1 {
+        return;
+    }
+    drop(state);
+    let app = app.clone();
+    thread::spawn(move || loop {
+        thread::sleep(DEFAULT_MAX_INTERVAL);
+        let mut state = active().lock().unwrap();
+        if state.downloads == 0 {
+            break;
+        }
+        let chunk = state.batcher.tick(Instant::now());
+        drop(state);
+        emit(&app, chunk);
+    });
+}
+
+/// 下载结束（含失败），发出剩余的进度
+fn end(app: &tauri::AppHandle) {
+    let mut state = active().lock().unwrap();
+    state.downloads = state.downloads.saturating_sub(1);
+    let chunk = state.batcher.flush();
+    drop(state);
+    emit(app, chunk);
+}
+
+fn report(app: &tauri::AppHandle, progress: DownloadProgress) {
+    let chunk = active().lock().unwrap().batcher.push(progress, Instant::now());
+    emit(app, chunk);
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// 由 URL 最后一段得到文件名，去掉查询参数和文件名中不允许的字符
+pub fn file_name_from_url(url: &str) -> String {
+    let without_query = url.split(['?', '#']).next().unwrap_or("");
+    let without_scheme = without_query.split_once("://").map_or(without_query, |(_, rest)| rest);
+    // 去掉主机名，只看路径部分
+    let path = without_scheme.split_once('/').map_or("", |(_, path)| path);
+    let name: String = path
+        .rsplit('/')
+        .next()
+        .unwrap_or("")
+        .chars()
+        .map(|c| if c.is_control() || r#"\/:*?"<>|"#.contains(c) { '_' } else { c })
+        .collect();
+    if name.is_empty() || name.starts_with('.') {
+        "download.apk".to_string()
+    } else {
+        name
+    }
+}
+
+fn is_disk_full(e: &io::Error) -> bool {
+    // ENOSPC / ERROR_DISK_FULL / ERROR_HANDLE_DISK_FULL
+    if cfg!(windows) {
+        matches!(e.raw_os_error(), Some(112) | Some(39))
+    } else {
+        e.raw_os_error() == Some(28)
+    }
+}
+
+fn io_error(e: io::Error, path: &Path) -> String {
+    if is_disk_full(&e) {
+        format!("磁盘空间不足，无法写入 {}", path.display())
+    } else {
+        format!("写入 {} 失败: {}", path.display(), e)
+    }
+}
+
+fn request_error(url: &str, e: ureq::Error) -> String {
+    match e {
+        ureq::Error::Status(404, _) => format!("文件不存在（HTTP 404）: {}", url),
+        ureq::Error::Status(code, response) => format!("服务器返回 HTTP {} {}: {}", code, response.status_text(), url),
+        ureq::Error::Transport(t) if t.kind() == ureq::ErrorKind::TooManyRedirects => {
+            format!("重定向超过 {} 次: {}", MAX_REDIRECTS, url)
+        }
+        ureq::Error::Transport(t) => {
+            let text = t.to_string();
+            let lower = text.to_lowercase();
+            if lower.contains("tls") || lower.contains("certificate") || lower.contains("handshake") {
+                format!("TLS 连接失败（证书无效或不受信任）: {}", text)
+            } else {
+                format!("下载失败: {}", text)
+            }
+        }
+    }
+}
+
+/// 已存在的部分文件请求续传；服务器不支持 Range 时从头下载。返回响应与续传起点
+fn open(agent: &ureq::Agent, url: &str, partial: &Path) -> Result<(ureq::Response, u64), String> {
+    let offset = fs::metadata(partial).map(|m| m.len()).unwrap_or(0);
+    if offset > 0 {
+        match agent.get(url).set("Range", &format!("bytes={}-", offset)).call() {
+            Ok(response) if response.status() == 206 => return Ok((response, offset)),
+            Ok(response) => return Ok((response, 0)),
+            // 部分文件已不匹配（如服务器上的文件变小了），丢弃后重新下载
+            Err(ureq::Error::Status(416, _)) => {
+                let _ = fs::remove_file(partial);
+            }
+            Err(e) => return Err(request_error(url, e)),
+        }
+    }
+    agent.get(url).call().map(|response| (response, 0)).map_err(|e| request_error(url, e))
+}
+
+fn record(paths: &AppPaths, record: &DownloadRecord) -> Result<(), String> {
+    fs::create_dir_all(&paths.data_dir).map_err(|e| e.to_string())?;
+    let line = serde_json::to_string(record).map_err(|e| e.to_string())?;
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(paths.data_dir.join(DOWNLOADS_FILE))
+        .map_err(|e| e.to_string())?;
+    writeln!(file, "{}", line).map_err(|e| e.to_string())
+}
+
+/// 本地文件的下载来源（最近一次下载到该路径的地址）
+pub fn source_url(paths: &AppPaths, apk_path: &str) -> Option<String> {
+    let file = fs::File::open(paths.data_dir.join(DOWNLOADS_FILE)).ok()?;
+    io::BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str::<DownloadRecord>(&line).ok())
+        .filter(|r| Path::new(&r.path) == Path::new(apk_path))
+        .last()
+        .map(|r| r.url)
+}
+
+fn download(app: &tauri::AppHandle, url: &str, output_dir: &str, expected_sha256: Option<&str>) -> Result<PathBuf, String> {
+    if !(url.starts_with("https://") || url.starts_with("http://")) {
+        return Err(format!("只支持 http / https 地址: {}", url));
+    }
+    fs::create_dir_all(output_dir).map_err(|e| io_error(e, Path::new(output_dir)))?;
+    let target = Path::new(output_dir).join(file_name_from_url(url));
+    let partial = target.with_file_name(format!("{}.part", target.file_name().unwrap_or_default().to_string_lossy()));
+    let path = target.to_string_lossy().to_string();
+
+    let agent = http::agent_builder(&app.state::<AppPaths>(), url)?
+        .redirects(MAX_REDIRECTS)
+        .timeout_connect(CONNECT_TIMEOUT)
+        .timeout_read(READ_TIMEOUT)
+        .build();
+    let (response, offset) = open(&agent, url, &partial)?;
+    let total = response.header("Content-Length").and_then(|len| len.parse::<u64>().ok()).map(|len| len + offset);
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(offset > 0)
+        .truncate(offset == 0)
+        .open(&partial)
+        .map_err(|e| io_error(e, &partial))?;
+    if offset > 0 {
+        crate::emit_progress(app, "download", format!("从 {} 字节处继续下载 {}", offset, url));
+    }
+
+    let started = Instant::now();
+    let mut downloaded = offset;
+    let mut reader = response.into_reader();
+    let mut buf = vec![0u8; BUF_SIZE];
+    let progress = |downloaded: u64, done: bool| {
+        let elapsed = started.elapsed().as_secs_f64().max(0.001);
+        DownloadProgress {
+            url: url.to_string(),
+            path: path.clone(),
+            downloaded,
+            total,
+            percent: total.filter(|t| *t > 0).map(|t| downloaded as f64 * 100.0 / t as f64),
+            bytes_per_sec: ((downloaded - offset) as f64 / elapsed) as u64,
+            done,
+        }
+    };
+    loop {
+        let n = reader.read(&mut buf).map_err(|e| format!("下载中断（已保存 {} 字节，可重试续传）: {}", downloaded, e))?;
+        if n == 0 {
+            break;
+        }
+        file.write_all(&buf[..n]).map_err(|e| io_error(e, &partial))?;
+        downloaded += n as u64;
+        report(app, progress(downloaded, false));
+    }
+    file.flush().map_err(|e| io_error(e, &partial))?;
+    drop(file);
+    report(app, progress(downloaded, true));
+    if let Some(total) = total.filter(|t| downloaded < *t) {
+        return Err(format!("下载不完整（{}/{} 字节），可重试续传", downloaded, total));
+    }
+
+    let sha256 = hash::sha256_file(&partial)?;
+    if let Some(expected) = expected_sha256.map(str::trim).filter(|s| !s.is_empty()) {
+        if !sha256.eq_ignore_ascii_case(expected) {
+            let _ = fs::remove_file(&partial);
+            return Err(format!("SHA-256 校验失败: 期望 {}，实际 {}", expected, sha256));
+        }
+    }
+    // 同名文件已存在时先删除，某些 Windows 文件系统上 rename 不会覆盖已有文件
+    if target.exists() {
+        fs::remove_file(&target).map_err(|e| io_error(e, &target))?;
+    }
+    fs::rename(&partial, &target).map_err(|e| io_error(e, &target))?;
+    let entry = DownloadRecord { url: url.to_string(), path, sha256: Some(sha256), timestamp: now() };
+    if let Err(e) = record(&app.state::<AppPaths>(), &entry) {
+        crate::emit_progress(app, "download", format!("写入下载记录失败: {}", e));
+    }
+    Ok(target)
+}
+
+/// 下载 APK 到 `output_dir`，返回本地路径；可用于后续的处理或安装
+#[tauri::command]
+pub async fn download_apk(app: tauri::AppHandle, url: String, output_dir: String, expected_sha256: Option<String>) -> Result<String, String> {
+    let output_dir = input_path::arg(&output_dir, Expect::Any).map_err(|e| e.to_string())?;
+    let url = url.trim().to_string();
+    begin(&app);
+    let result = download(&app, &url, &output_dir, expected_sha256.as_deref());
+    end(&app);
+    if let Err(e) = &result {
+        crate::emit_progress(&app, "download", e.clone());
+    }
+    result.map(|path| path.to_string_lossy().to_string())
+}
+
+/// 前端确认已处理到 `seq` 的下载进度批次，积压消除后恢复完整推送
+#[tauri::command]
+pub fn ack_download_events(seq: u64) {
+    active().lock().unwrap().batcher.ack(seq);
+}
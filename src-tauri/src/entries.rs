@@ -0,0 +1,139 @@
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use crate::input_path::{self, Expect};
+use crate::signing::PipelineError;
+
+/// 单次读取条目内容的上限，防止解压炸弹
+pub const MAX_READ_BYTES: u64 = 16 * 1024 * 1024;
+/// 重建的 APK 至少应为输入 APK 大小的比例
+pub const DEFAULT_MIN_OUTPUT_RATIO: f64 = 0.2;
+
+/// APK 中的单个 ZIP 条目
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct ApkEntry {
+    pub name: String,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub size: u64,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub compressed_size: u64,
+    /// 压缩方式，如 `Stored`、`Deflated`
+    pub compression: String,
+    pub is_dir: bool,
+    /// 条目名包含 `..`、绝对路径等可能逃逸解压目录的写法
+    pub unsafe_path: bool,
+}
+
+/// 条目内容，文本以 UTF-8 返回，二进制以 base64 返回
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct EntryContent {
+    pub name: String,
+    /// 条目解压后的完整大小
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub size: u64,
+    /// `text` 或 `base64`
+    pub encoding: String,
+    pub content: String,
+    /// 内容超过读取上限被截断
+    pub truncated: bool,
+}
+
+/// 将简单通配符转为正则：`**` 匹配任意路径，`*` 不跨越 `/`，`?` 匹配单个字符
+fn glob_regex(glob: &str) -> Result<regex::Regex, PipelineError> {
+    let mut pattern = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                pattern.push_str(".*");
+            }
+            '*' => pattern.push_str("[^/]*"),
+            '?' => pattern.push_str("[^/]"),
+            _ => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    pattern.push('$');
+    regex::Regex::new(&pattern).map_err(|e| PipelineError::InvalidInput(format!("无效的通配符 {}: {}", glob, e)))
+}
+
+/// 列出 APK 中的条目，可用通配符过滤（如 `assets/*.json`）。只读取中央目录，不解压
+#[tauri::command]
+pub fn list_apk_entries(apk_path: String, glob: Option<String>) -> Result<Vec<ApkEntry>, PipelineError> {
+    let apk_path = input_path::arg(&apk_path, Expect::File)?;
+    let filter = glob.as_deref().filter(|g| !g.trim().is_empty()).map(glob_regex).transpose()?;
+    let mut archive = zip::ZipArchive::new(fs::File::open(&apk_path)?)?;
+    let mut entries = Vec::new();
+    for i in 0..archive.len() {
+        let entry = archive.by_index_raw(i)?;
+        if filter.as_ref().is_some_and(|re| !re.is_match(entry.name())) {
+            continue;
+        }
+        entries.push(ApkEntry {
+            name: entry.name().to_string(),
+            size: entry.size(),
+            compressed_size: entry.compressed_size(),
+            compression: format!("{:?}", entry.compression()),
+            is_dir: entry.is_dir(),
+            unsafe_path: entry.enclosed_name().is_none() || entry.name().contains('\\'),
+        });
+    }
+    Ok(entries)
+}
+
+/// 检查重建的 APK：文件存在、大小不低于输入的 `min_ratio`、中央目录可解析、包含 Manifest 与 DEX。
+/// 只读取中央目录；不通过时返回具体是哪一项
+pub fn verify_rebuilt_apk(output: &Path, input_size: u64, min_ratio: f64) -> Result<(), String> {
+    let size = fs::metadata(output).map_err(|_| format!("输出文件不存在: {}", output.display()))?.len();
+    let min_size = (input_size as f64 * min_ratio) as u64;
+    if size < min_size {
+        return Err(format!("输出只有 {} 字节，低于输入 APK（{} 字节）的 {:.0}%", size, input_size, min_ratio * 100.0));
+    }
+    let file = fs::File::open(output).map_err(|e| format!("无法读取输出文件: {}", e))?;
+    let archive = zip::ZipArchive::new(file).map_err(|e| format!("输出不是有效的 ZIP（中央目录无法解析）: {}", e))?;
+    if archive.index_for_name("AndroidManifest.xml").is_none() {
+        return Err("输出中缺少 AndroidManifest.xml".to_string());
+    }
+    let has_dex = archive.file_names().any(|name| name.starts_with("classes") && name.ends_with(".dex") && !name.contains('/'));
+    if !has_dex {
+        return Err("输出中没有 classes.dex".to_string());
+    }
+    Ok(())
+}
+
+/// 流式读取单个条目的前 `max_bytes` 字节（不超过 16 MB）
+#[tauri::command]
+pub fn read_apk_entry(apk_path: String, entry: String, max_bytes: Option<u64>) -> Result<EntryContent, PipelineError> {
+    let apk_path = input_path::arg(&apk_path, Expect::File)?;
+    let limit = max_bytes.unwrap_or(MAX_READ_BYTES).min(MAX_READ_BYTES);
+    let mut archive = zip::ZipArchive::new(fs::File::open(&apk_path)?)?;
+    let file = archive
+        .by_name(&entry)
+        .map_err(|_| PipelineError::InvalidInput(format!("APK 中没有条目 {}", entry)))?;
+    if file.is_dir() {
+        return Err(PipelineError::InvalidInput(format!("{} 是目录", entry)));
+    }
+    let size = file.size();
+
+    let mut data = Vec::new();
+    file.take(limit + 1).read_to_end(&mut data)?;
+    let truncated = data.len() as u64 > limit;
+    data.truncate(limit as usize);
+
+    // 截断可能切在多字节字符中间，末尾不完整的字符不视为二进制
+    let text = match std::str::from_utf8(&data) {
+        Ok(text) => Some(text),
+        Err(e) if truncated && e.error_len().is_none() => std::str::from_utf8(&data[..e.valid_up_to()]).ok(),
+        Err(_) => None,
+    };
+    let (encoding, content) = match text.filter(|t| !t.contains('\0')) {
+        Some(text) => ("text", text.to_string()),
+        None => ("base64", base64::engine::general_purpose::STANDARD.encode(&data)),
+    };
+    Ok(EntryContent { name: entry, size, encoding: encoding.to_string(), content, truncated })
+}
@@ -0,0 +1,247 @@
+//! 开始处理前的预估：耗时、临时空间、输出大小与风险。只读取 APK 的中央目录和二进制 Manifest，
+//! 不解压 DEX、不调用外部工具，2GB 的 APK 也能在两秒内完成。预估结果随本次处理写入性能记录，便于事后对比预估与实际。
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+
+use crate::input_path::{self, Expect};
+use crate::metrics::{self, DurationPrediction};
+use crate::paths::AppPaths;
+use crate::pipeline::ProcessConfig;
+use crate::{axml, safe_path};
+
+/// 反编译后 smali 文本相对 DEX 的膨胀倍数（经验值）
+const SMALI_EXPANSION: u64 = 3;
+/// 同一分组的历史记录达到该数量时，耗时预估视为可信
+const CONFIDENT_RUNS: usize = 5;
+
+/// 常见加固方案留在 APK 中的特征文件（文件名，方案）
+const PACKER_SIGNATURES: &[(&str, &str)] = &[
+    ("libjiagu.so", "360 加固"),
+    ("libjiagu_x86.so", "360 加固"),
+    ("libshella", "腾讯乐固"),
+    ("libshellx", "腾讯乐固"),
+    ("libsecexe.so", "梆梆加固"),
+    ("libsecmain.so", "梆梆加固"),
+    ("libDexHelper.so", "梆梆加固"),
+    ("libexec.so", "爱加密"),
+    ("libexecmain.so", "爱加密"),
+    ("ijiami.dat", "爱加密"),
+    ("libbaiduprotect.so", "百度加固"),
+    ("libnesec.so", "网易易盾"),
+    ("libprotectClass.so", "通付盾"),
+];
+
+/// 预估所需的处理选项，与 `ProcessConfig` 中的同名字段含义相同
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct EstimateOptions {
+    #[serde(default)]
+    pub install_after: bool,
+    #[serde(default)]
+    pub strip_debug_info: bool,
+    #[serde(default)]
+    pub stealth_output: bool,
+}
+
+impl From<&ProcessConfig> for EstimateOptions {
+    fn from(config: &ProcessConfig) -> Self {
+        EstimateOptions {
+            install_after: config.install_after,
+            strip_debug_info: config.strip_debug_info.unwrap_or(false),
+            stealth_output: config.stealth_output.unwrap_or(false),
+        }
+    }
+}
+
+/// 预估的可信程度
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "snake_case")]
+pub enum Confidence {
+    High,
+    Medium,
+    Low,
+    /// 没有可用的依据，未给出数值
+    None,
+}
+
+/// 风险等级，按从低到高的顺序声明
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "snake_case")]
+pub enum RiskLevel {
+    Low,
+    Medium,
+    High,
+}
+
+/// 处理前的预估
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct Estimate {
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub apk_size_bytes: u64,
+    /// 预计耗时；没有历史记录时为 null
+    pub duration: Option<DurationPrediction>,
+    pub duration_confidence: Confidence,
+    /// 工作目录与中间产物的峰值占用
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub temp_space_bytes: u64,
+    /// 工作目录所在磁盘的可用空间，无法读取时为 null
+    #[cfg_attr(feature = "export-bindings", ts(type = "number | null"))]
+    pub available_space_bytes: Option<u64>,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub output_size_bytes: u64,
+    pub risk: RiskLevel,
+    /// 风险来源，如「检测到 360 加固」「2 个 ContentProvider 需要改写」
+    pub risk_factors: Vec<String>,
+    pub packer: Option<String>,
+    pub provider_count: u32,
+    /// 影响可信程度的说明，如「该大小分组没有历史记录」
+    pub notes: Vec<String>,
+    /// 本次预估自身的耗时
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub elapsed_ms: u64,
+}
+
+/// 按文件名匹配加固特征
+pub fn detect_packer<'a>(entry_names: impl IntoIterator<Item = &'a str>) -> Option<&'static str> {
+    entry_names.into_iter().find_map(|name| {
+        let file = name.rsplit('/').next().unwrap_or(name);
+        PACKER_SIGNATURES.iter().find(|(signature, _)| file.starts_with(signature)).map(|(_, packer)| *packer)
+    })
+}
+
+#[cfg(unix)]
+fn available_space(dir: &Path) -> Option<u64> {
+    // `df -Pk` 的第二行第 4 列为可用的 KB 数
+    let output = std::process::Command::new("df").arg("-Pk").arg(dir).output().ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let kb: u64 = text.lines().nth(1)?.split_whitespace().nth(3)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+#[cfg(windows)]
+fn available_space(dir: &Path) -> Option<u64> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    let wide: Vec<u16> = dir.as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+    let mut available = 0u64;
+    let ok = unsafe { GetDiskFreeSpaceExW(wide.as_ptr(), &mut available, std::ptr::null_mut(), std::ptr::null_mut()) };
+    (ok != 0).then_some(available)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn available_space(_dir: &Path) -> Option<u64> {
+    None
+}
+
+/// 计算预估；`paths` 用于读取历史耗时
+pub fn estimate(paths: &AppPaths, apk_path: &Path, options: &EstimateOptions) -> Result<Estimate, String> {
+    let started = Instant::now();
+    let apk_size_bytes = fs::metadata(apk_path).map_err(|e| format!("读取 APK 信息失败: {}", e))?.len();
+    let mut archive = zip::ZipArchive::new(fs::File::open(apk_path).map_err(|e| e.to_string())?).map_err(|e| format!("无法读取 APK: {}", e))?;
+    let mut notes = Vec::new();
+
+    // 中央目录中已记录解压后大小，无需解压
+    let mut dex_bytes = 0u64;
+    let mut other_bytes = 0u64;
+    let mut names = Vec::with_capacity(archive.len());
+    for i in 0..archive.len() {
+        let Ok(entry) = archive.by_index_raw(i) else {
+            continue;
+        };
+        let name = entry.name().to_string();
+        if name.ends_with(".dex") && !name.contains('/') {
+            dex_bytes += entry.size();
+        } else {
+            other_bytes += entry.size();
+        }
+        names.push(name);
+    }
+    let packer = detect_packer(names.iter().map(String::as_str)).map(str::to_string);
+
+    // 中间产物：回编译、对齐、签名各一份，去除调试信息与规范化输出时各多一份
+    let intermediates = 3 + u64::from(options.strip_debug_info) + u64::from(options.stealth_output);
+    let temp_space_bytes = other_bytes + dex_bytes * SMALI_EXPANSION + apk_size_bytes * intermediates;
+    let work_root = safe_path::work_root();
+    let available_space_bytes = available_space(work_root.ancestors().find(|dir| dir.exists()).unwrap_or(&work_root));
+    if available_space_bytes.is_none() {
+        notes.push("无法读取工作目录所在磁盘的可用空间".to_string());
+    }
+
+    let mut risk_factors = Vec::new();
+    let mut risk = RiskLevel::Low;
+    if let Some(packer) = &packer {
+        risk = RiskLevel::High;
+        risk_factors.push(format!("检测到{}，反编译后通常无法正常运行", packer));
+    }
+    let mut provider_count = 0;
+    match axml::read_manifest_from_apk(apk_path) {
+        Ok(elements) => {
+            provider_count = elements.iter().filter(|e| e.name == "provider").count() as u32;
+            if provider_count > 0 {
+                risk = risk.max(RiskLevel::Medium);
+                risk_factors.push(format!("{} 个 ContentProvider 需要改写 authorities", provider_count));
+            }
+            if let Some(shared) = elements.iter().find(|e| e.name == "manifest").and_then(|m| m.attr("sharedUserId")) {
+                risk = risk.max(RiskLevel::Medium);
+                risk_factors.push(format!("使用 sharedUserId {}", shared.as_string()));
+            }
+        }
+        Err(e) => {
+            risk = RiskLevel::High;
+            risk_factors.push(format!("无法直接解析 Manifest（{}），可能经过加固或混淆", e));
+        }
+    }
+    if packer.is_none() {
+        notes.push("未检测到已知加固特征".to_string());
+    }
+
+    let duration = metrics::predict_duration(paths, apk_size_bytes, options.install_after);
+    let duration_confidence = match &duration {
+        None => {
+            notes.push("没有历史处理记录，无法预估耗时".to_string());
+            Confidence::None
+        }
+        Some(d) if !d.same_bucket => {
+            notes.push("该大小分组没有历史记录，按其他大小的记录折算".to_string());
+            Confidence::Low
+        }
+        Some(d) if d.runs < CONFIDENT_RUNS => {
+            notes.push(format!("该大小分组只有 {} 次历史记录", d.runs));
+            Confidence::Medium
+        }
+        Some(_) => Confidence::High,
+    };
+    if duration.as_ref().is_some_and(|d| d.install_unknown) {
+        notes.push("没有历史安装记录，耗时未包含安装".to_string());
+    }
+
+    Ok(Estimate {
+        apk_size_bytes,
+        duration,
+        duration_confidence,
+        temp_space_bytes,
+        available_space_bytes,
+        // 回编译后的大小与原 APK 接近
+        output_size_bytes: apk_size_bytes,
+        risk,
+        risk_factors,
+        packer,
+        provider_count,
+        notes,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+/// 开始处理前预估耗时、临时空间、输出大小与风险，不执行任何耗时操作
+#[tauri::command]
+pub fn estimate_run(paths: tauri::State<'_, AppPaths>, apk_path: String, options: Option<EstimateOptions>) -> Result<Estimate, String> {
+    let apk_path = input_path::arg(&apk_path, Expect::File).map_err(|e| e.to_string())?;
+    estimate(&paths, Path::new(&apk_path), &options.unwrap_or_default())
+}
@@ -0,0 +1,249 @@
+//! 设备上本工具生成的应用是否为最新：把处理记录中每个新包名最新的输出版本，
+//! 与各设备上已安装的 versionCode 对照，并可把最新输出安装到版本落后的设备上。
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::thread;
+
+use tauri::Manager;
+
+use crate::adb_coordinator::AdbCoordinator;
+use crate::device_cache::{self, DeviceCache};
+use crate::incremental::{self, RunRecord};
+use crate::installs::{self, InstallHistory};
+use crate::paths::AppPaths;
+use crate::{adb, axml, device_prefs, hash, pm, post_install};
+
+/// 某台设备上某个应用的状态
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "snake_case")]
+pub enum FleetStatus {
+    UpToDate,
+    Outdated,
+    NotInstalled,
+    /// 最新一次处理的输出文件已被删除，无法确定或安装最新版本
+    OutputMissing,
+    /// 最新一次处理的输出文件在处理后被修改过
+    OutputModified,
+}
+
+impl FleetStatus {
+    fn describe(self) -> &'static str {
+        match self {
+            FleetStatus::UpToDate => "已是最新版本",
+            FleetStatus::Outdated => "版本落后",
+            FleetStatus::NotInstalled => "未安装",
+            FleetStatus::OutputMissing => "最新输出已被删除",
+            FleetStatus::OutputModified => "最新输出被修改过",
+        }
+    }
+}
+
+/// 一台设备上的一个应用
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct FleetEntry {
+    pub device_id: String,
+    pub package: String,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number | null"))]
+    pub installed_version_code: Option<i64>,
+    /// 最新一次处理输出的 versionCode
+    #[cfg_attr(feature = "export-bindings", ts(type = "number | null"))]
+    pub latest_version_code: Option<i64>,
+    pub output_path: String,
+    pub status: FleetStatus,
+}
+
+/// 对一台设备执行更新的结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct FleetUpdate {
+    pub device_id: String,
+    /// `installed`、`skipped` 或 `failed`
+    pub outcome: String,
+    pub message: String,
+}
+
+/// 一个新包名最新的输出
+struct LatestOutput {
+    record: RunRecord,
+    version_code: Option<i64>,
+    state: Result<(), FleetStatus>,
+}
+
+fn version_code(apk: &Path) -> Option<i64> {
+    axml::read_manifest_from_apk(apk)
+        .ok()
+        .and_then(|elements| elements.into_iter().find(|e| e.name == "manifest"))
+        .and_then(|manifest| manifest.attr("versionCode").and_then(axml::AxmlValue::as_int))
+}
+
+/// 每个新包名最近一次处理的输出，并检查输出文件是否仍存在且未被修改
+fn latest_outputs(records: Vec<RunRecord>, package_filter: Option<&str>) -> BTreeMap<String, LatestOutput> {
+    let filter = package_filter.map(str::to_lowercase).filter(|f| !f.is_empty());
+    let mut latest: BTreeMap<String, RunRecord> = BTreeMap::new();
+    for record in records {
+        if filter.as_ref().is_some_and(|f| !record.package.to_lowercase().contains(f)) {
+            continue;
+        }
+        match latest.get(&record.package) {
+            Some(existing) if existing.timestamp > record.timestamp => {}
+            _ => {
+                latest.insert(record.package.clone(), record);
+            }
+        }
+    }
+    latest
+        .into_iter()
+        .map(|(package, record)| {
+            let output = Path::new(&record.output_path);
+            let state = if !output.is_file() {
+                Err(FleetStatus::OutputMissing)
+            } else if hash::sha256_file_cached(output).ok().as_deref() != Some(record.output_sha256.as_str()) {
+                Err(FleetStatus::OutputModified)
+            } else {
+                Ok(())
+            };
+            let version_code = state.is_ok().then(|| version_code(output)).flatten();
+            (package, LatestOutput { record, version_code, state })
+        })
+        .collect()
+}
+
+/// 设备上已安装包的 versionCode
+fn installed_versions(device_id: &str) -> Result<BTreeMap<String, Option<i64>>, String> {
+    let output = adb::shell(device_id, &["pm", "list", "packages", "--show-versioncode"])?;
+    Ok(pm::parse_package_list(&output).into_iter().map(|line| (line.package, line.version_code)).collect())
+}
+
+fn status(latest: &LatestOutput, installed: Option<Option<i64>>) -> FleetStatus {
+    if let Err(status) = latest.state {
+        return status;
+    }
+    match (installed, latest.version_code) {
+        (None, _) => FleetStatus::NotInstalled,
+        (Some(Some(installed)), Some(latest)) if installed < latest => FleetStatus::Outdated,
+        _ => FleetStatus::UpToDate,
+    }
+}
+
+fn fleet_status(paths: &AppPaths, devices: &[String], package_filter: Option<&str>) -> Result<Vec<FleetEntry>, String> {
+    let latest = latest_outputs(incremental::load_records(paths), package_filter);
+    let mut entries = Vec::new();
+    for device_id in devices {
+        let installed = installed_versions(device_id)?;
+        for (package, output) in &latest {
+            let installed_version = installed.get(package).copied();
+            entries.push(FleetEntry {
+                device_id: device_id.clone(),
+                package: package.clone(),
+                installed_version_code: installed_version.flatten(),
+                latest_version_code: output.version_code,
+                output_path: output.record.output_path.clone(),
+                status: status(output, installed_version),
+            });
+        }
+    }
+    Ok(entries)
+}
+
+/// 所有已连接设备上、本工具生成的应用（可按包名子串过滤）是否为最新
+#[tauri::command]
+pub async fn get_fleet_status(paths: tauri::State<'_, AppPaths>, package_filter: Option<String>) -> Result<Vec<FleetEntry>, String> {
+    let devices = adb::devices()?;
+    fleet_status(&paths, &devices, package_filter.as_deref())
+}
+
+fn install_latest(app: &tauri::AppHandle, device_id: &str, package: &str, apk: &Path) -> FleetUpdate {
+    let paths = app.state::<AppPaths>();
+    let coordinator = app.state::<AdbCoordinator>();
+    let _transfer = coordinator.begin_transfer(device_id, "install");
+    let resolved = device_prefs::resolve_for(&paths, device_id, &Default::default(), &[]);
+    if resolved.keep_awake {
+        device_prefs::wake_screen(device_id);
+    }
+    let output = adb::command().args(["-s", device_id, "install"]).args(resolved.install_args()).arg(apk).output();
+    app.state::<DeviceCache>().invalidate(device_id, device_cache::DATASET_INSTALLED_APPS);
+    let (outcome, message) = match output {
+        Ok(out) if out.status.success() && String::from_utf8_lossy(&out.stdout).contains("Success") => {
+            let record = installs::install_record(device_id, package, apk, Some("update_outdated".to_string()));
+            let failed_actions = post_install::run_actions(device_id, package, &resolved.post_install_actions).iter().filter(|r| !r.success).count();
+            match app.state::<InstallHistory>().append(&paths, record) {
+                Ok(()) if failed_actions > 0 => ("installed", format!("已更新，{} 个安装后动作失败", failed_actions)),
+                Ok(()) => ("installed", "已更新到最新版本".to_string()),
+                Err(e) => ("installed", format!("已更新，但写入安装记录失败: {}", e)),
+            }
+        }
+        Ok(out) => ("failed", format!("安装失败: {}", String::from_utf8_lossy(&out.stdout).trim())),
+        Err(e) => ("failed", format!("安装命令执行失败: {}", e)),
+    };
+    let message = if resolved.applied.is_empty() {
+        message
+    } else {
+        format!("{}（设备偏好: {}）", message, resolved.applied.join("，"))
+    };
+    FleetUpdate { device_id: device_id.to_string(), outcome: outcome.to_string(), message }
+}
+
+/// 把 `package` 最新的输出安装到 `device_ids`（默认所有已连接设备）中版本落后的设备上，
+/// 各设备并行安装；输出文件已删除或被修改时不安装
+#[tauri::command]
+pub async fn update_outdated(app: tauri::AppHandle, device_ids: Option<Vec<String>>, package: String) -> Result<Vec<FleetUpdate>, String> {
+    let paths = app.state::<AppPaths>();
+    let devices = match device_ids {
+        Some(ids) if !ids.is_empty() => ids,
+        _ => adb::devices()?,
+    };
+    let latest = latest_outputs(incremental::load_records(&paths), None)
+        .remove(&package)
+        .ok_or_else(|| format!("没有 {} 的处理记录", package))?;
+    if let Err(status) = latest.state {
+        return Err(format!("{}: {}", status.describe(), latest.record.output_path));
+    }
+    let apk = Path::new(&latest.record.output_path);
+
+    let results: Vec<FleetUpdate> = thread::scope(|scope| {
+        let handles: Vec<_> = devices
+            .iter()
+            .map(|device_id| {
+                let app = &app;
+                let latest = &latest;
+                let package = package.as_str();
+                scope.spawn(move || {
+                    let installed = match installed_versions(device_id) {
+                        Ok(installed) => installed.get(package).copied(),
+                        Err(e) => {
+                            return FleetUpdate { device_id: device_id.clone(), outcome: "failed".to_string(), message: e };
+                        }
+                    };
+                    match status(latest, installed) {
+                        FleetStatus::Outdated => install_latest(app, device_id, package, apk),
+                        other => FleetUpdate {
+                            device_id: device_id.clone(),
+                            outcome: "skipped".to_string(),
+                            message: other.describe().to_string(),
+                        },
+                    }
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .zip(&devices)
+            .map(|(handle, device_id)| {
+                handle.join().unwrap_or_else(|_| FleetUpdate {
+                    device_id: device_id.clone(),
+                    outcome: "failed".to_string(),
+                    message: "安装线程异常退出".to_string(),
+                })
+            })
+            .collect()
+    });
+
+    let params = serde_json::json!({ "package": package, "devices": devices });
+    let failed: Vec<String> = results.iter().filter(|r| r.outcome == "failed").map(|r| format!("{}: {}", r.device_id, r.message)).collect();
+    crate::audit::record_outcome(&app, "update_outdated", params, failed.is_empty(), (!failed.is_empty()).then(|| failed.join("; ")));
+    Ok(results)
+}
@@ -0,0 +1,272 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::BufWriter;
+use std::path::Path;
+
+use crate::input_path::{self, Expect};
+use crate::signing::PipelineError;
+
+/// 类级依赖图：类名（`com.example.Foo`）到其引用的类
+pub type ClassGraph = BTreeMap<String, BTreeSet<String>>;
+
+/// 导出依赖图的统计信息
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct GraphStats {
+    pub node_count: u32,
+    pub edge_count: u32,
+    pub strongly_connected_components: u32,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub output_size_bytes: u64,
+}
+
+fn descriptor_to_class(descriptor: &str) -> String {
+    descriptor.trim_start_matches('L').trim_end_matches(';').replace('/', ".")
+}
+
+/// 遍历工作目录下所有 smali 目录（smali、smali_classes2 …），构建类级依赖图。
+/// 只保留指向 APK 内部类的边
+pub fn compute_class_dependency_graph(work_dir: &Path) -> Result<ClassGraph, PipelineError> {
+    let class_re = regex::Regex::new(r"(?m)^\.class[^\n]*?(L[\w/$\-]+;)").unwrap();
+    let ref_re = regex::Regex::new(r"L[\w/$\-]+;").unwrap();
+
+    let mut raw: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for entry in fs::read_dir(work_dir)? {
+        let entry = entry?;
+        if !entry.file_name().to_string_lossy().starts_with("smali") {
+            continue;
+        }
+        for file in walkdir::WalkDir::new(entry.path()).into_iter().filter_map(|e| e.ok()) {
+            if file.path().extension().map(|e| e != "smali").unwrap_or(true) {
+                continue;
+            }
+            let content = fs::read_to_string(file.path())?;
+            let Some(class) = class_re.captures(&content).map(|c| descriptor_to_class(&c[1])) else {
+                continue;
+            };
+            let refs: BTreeSet<String> = ref_re
+                .find_iter(&content)
+                .map(|m| descriptor_to_class(m.as_str()))
+                .filter(|r| *r != class)
+                .collect();
+            raw.insert(class, refs);
+        }
+    }
+
+    let known: BTreeSet<String> = raw.keys().cloned().collect();
+    Ok(raw
+        .into_iter()
+        .map(|(class, refs)| {
+            let internal = refs.into_iter().filter(|r| known.contains(r)).collect();
+            (class, internal)
+        })
+        .collect())
+}
+
+/// 迭代式 Tarjan 算法统计强连通分量数量
+pub fn count_strongly_connected(graph: &ClassGraph) -> u32 {
+    let names: Vec<&String> = graph.keys().collect();
+    let index_of: BTreeMap<&String, usize> = names.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+    let adj: Vec<Vec<usize>> = names
+        .iter()
+        .map(|n| graph[*n].iter().filter_map(|t| index_of.get(t).copied()).collect())
+        .collect();
+
+    let n = names.len();
+    let mut index = vec![usize::MAX; n];
+    let mut low = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack = Vec::new();
+    let mut next_index = 0;
+    let mut components = 0;
+
+    for root in 0..n {
+        if index[root] != usize::MAX {
+            continue;
+        }
+        let mut work = vec![(root, 0usize)];
+        while let Some((v, child)) = work.pop() {
+            if child == 0 {
+                index[v] = next_index;
+                low[v] = next_index;
+                next_index += 1;
+                stack.push(v);
+                on_stack[v] = true;
+            }
+            if let Some(&w) = adj[v].get(child) {
+                work.push((v, child + 1));
+                if index[w] == usize::MAX {
+                    work.push((w, 0));
+                } else if on_stack[w] {
+                    low[v] = low[v].min(index[w]);
+                }
+                continue;
+            }
+            if low[v] == index[v] {
+                while let Some(w) = stack.pop() {
+                    on_stack[w] = false;
+                    if w == v {
+                        break;
+                    }
+                }
+                components += 1;
+            }
+            if let Some(&(parent, _)) = work.last() {
+                low[parent] = low[parent].min(low[v]);
+            }
+        }
+    }
+    components
+}
+
+#[derive(Serialize)]
+struct NodeData<'a> {
+    id: &'a str,
+}
+
+#[derive(Serialize)]
+struct EdgeData<'a> {
+    id: String,
+    source: &'a str,
+    target: &'a str,
+}
+
+#[derive(Serialize)]
+struct Element<T> {
+    data: T,
+}
+
+#[derive(Serialize)]
+struct Elements<'a> {
+    nodes: Vec<Element<NodeData<'a>>>,
+    edges: Vec<Element<EdgeData<'a>>>,
+}
+
+#[derive(Serialize)]
+struct CytoscapeGraph<'a> {
+    elements: Elements<'a>,
+}
+
+/// 将类依赖图导出为 Cytoscape.js 格式的 JSON 文件。
+/// 指定 `filter_package` 时只保留两端都在该包内的边
+#[tauri::command]
+pub fn export_dependency_graph(
+    work_dir: String,
+    output_path: String,
+    filter_package: Option<String>,
+) -> Result<GraphStats, PipelineError> {
+    let work_dir = input_path::arg(&work_dir, Expect::Dir)?;
+    let output_path = input_path::arg(&output_path, Expect::Any)?;
+    let mut graph = compute_class_dependency_graph(Path::new(&work_dir))?;
+    if let Some(pkg) = filter_package.filter(|p| !p.is_empty()) {
+        let prefix = format!("{}.", pkg.trim_end_matches('.'));
+        graph.retain(|class, _| class.starts_with(&prefix));
+        for refs in graph.values_mut() {
+            refs.retain(|r| r.starts_with(&prefix));
+        }
+    }
+
+    let nodes = graph.keys().map(|id| Element { data: NodeData { id } }).collect();
+    let edges: Vec<Element<EdgeData>> = graph
+        .iter()
+        .flat_map(|(source, targets)| {
+            targets.iter().map(move |target| Element {
+                data: EdgeData { id: format!("{}->{}", source, target), source, target },
+            })
+        })
+        .collect();
+    let edge_count = edges.len() as u32;
+
+    let file = fs::File::create(&output_path)?;
+    let doc = CytoscapeGraph { elements: Elements { nodes, edges } };
+    serde_json::to_writer(BufWriter::new(file), &doc).map_err(|e| PipelineError::Io(e.to_string()))?;
+
+    Ok(GraphStats {
+        node_count: graph.len() as u32,
+        edge_count,
+        strongly_connected_components: count_strongly_connected(&graph),
+        output_size_bytes: fs::metadata(&output_path)?.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn smali(dir: &Path, smali_dir: &str, class: &str, refs: &[&str]) {
+        let descriptor = format!("L{};", class.replace('.', "/"));
+        let path = dir.join(smali_dir).join(format!("{}.smali", class.replace('.', "/")));
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let mut body = format!(".class public {}\n.super Ljava/lang/Object;\n", descriptor);
+        for r in refs {
+            body.push_str(&format!("    invoke-static {{}}, L{};->run()V\n", r.replace('.', "/")));
+        }
+        fs::write(path, body).unwrap();
+    }
+
+    /// a.A ⇄ a.B 成环，a.B → a.C，b.D → a.A；跨 smali 目录，另有指向框架类的引用
+    fn work_dir() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        smali(dir.path(), "smali", "com.example.a.A", &["com.example.a.B", "android.app.Activity"]);
+        smali(dir.path(), "smali", "com.example.a.B", &["com.example.a.A", "com.example.a.C"]);
+        smali(dir.path(), "smali_classes2", "com.example.a.C", &[]);
+        smali(dir.path(), "smali_classes2", "com.example.b.D", &["com.example.a.A"]);
+        fs::create_dir_all(dir.path().join("res")).unwrap();
+        fs::write(dir.path().join("res/ignored.smali"), ".class public Lcom/example/Ignored;").unwrap();
+        dir
+    }
+
+    fn export(dir: &Path, filter: Option<&str>) -> (GraphStats, serde_json::Value) {
+        let output = dir.join("graph.json");
+        let stats = export_dependency_graph(dir.to_string_lossy().to_string(), output.to_string_lossy().to_string(), filter.map(str::to_string)).unwrap();
+        let json = serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+        (stats, json)
+    }
+
+    #[test]
+    fn builds_internal_edges_only() {
+        let dir = work_dir();
+        let graph = compute_class_dependency_graph(dir.path()).unwrap();
+        assert_eq!(graph.keys().collect::<Vec<_>>(), ["com.example.a.A", "com.example.a.B", "com.example.a.C", "com.example.b.D"]);
+        assert_eq!(graph["com.example.a.A"].iter().collect::<Vec<_>>(), ["com.example.a.B"]);
+        assert!(graph["com.example.a.C"].is_empty());
+    }
+
+    #[test]
+    fn counts_strongly_connected_components() {
+        let dir = work_dir();
+        let graph = compute_class_dependency_graph(dir.path()).unwrap();
+        // {A, B}、{C}、{D}
+        assert_eq!(count_strongly_connected(&graph), 3);
+        assert_eq!(count_strongly_connected(&ClassGraph::new()), 0);
+    }
+
+    #[test]
+    fn exported_json_matches_the_reported_counts() {
+        let dir = work_dir();
+        let (stats, json) = export(dir.path(), None);
+        let nodes = json["elements"]["nodes"].as_array().unwrap();
+        let edges = json["elements"]["edges"].as_array().unwrap();
+        assert_eq!(stats.node_count as usize, nodes.len());
+        assert_eq!(stats.node_count, 4);
+        assert_eq!(stats.edge_count as usize, edges.len());
+        assert_eq!(stats.edge_count, 4);
+        assert_eq!(stats.strongly_connected_components, 3);
+        assert_eq!(stats.output_size_bytes, fs::metadata(dir.path().join("graph.json")).unwrap().len());
+        assert_eq!(nodes[0]["data"]["id"], "com.example.a.A");
+        assert_eq!(edges[0]["data"], serde_json::json!({ "id": "com.example.a.A->com.example.a.B", "source": "com.example.a.A", "target": "com.example.a.B" }));
+    }
+
+    #[test]
+    fn package_filter_keeps_edges_inside_the_package() {
+        let dir = work_dir();
+        let (stats, json) = export(dir.path(), Some("com.example.a."));
+        assert_eq!(stats.node_count, 3);
+        assert_eq!(stats.edge_count, 3);
+        assert_eq!(json["elements"]["nodes"].as_array().unwrap().len(), 3);
+        // `com.example.a` 不能匹配到 `com.example.ab`
+        smali(dir.path(), "smali", "com.example.ab.E", &[]);
+        assert_eq!(export(dir.path(), Some("com.example.a")).0.node_count, 3);
+    }
+}
@@ -0,0 +1,51 @@
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+
+type CacheKey = (PathBuf, Option<SystemTime>, u64);
+
+fn cache() -> &'static Mutex<HashMap<CacheKey, String>> {
+    static CACHE: OnceLock<Mutex<HashMap<CacheKey, String>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// 流式计算文件的 SHA-256（十六进制小写），不会把整个文件读入内存
+pub fn sha256_file(path: &Path) -> Result<String, String> {
+    let mut file = fs::File::open(path).map_err(|e| format!("打开文件失败: {}", e))?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 1024 * 1024];
+    loop {
+        let n = file.read(&mut buf).map_err(|e| format!("读取文件失败: {}", e))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// 带缓存的 SHA-256：以路径 + 修改时间 + 大小为键，文件未变化时直接返回
+pub fn sha256_file_cached(path: &Path) -> Result<String, String> {
+    let meta = fs::metadata(path).map_err(|e| format!("读取文件信息失败: {}", e))?;
+    let key = (path.to_path_buf(), meta.modified().ok(), meta.len());
+    if let Some(hash) = cache().lock().unwrap().get(&key) {
+        return Ok(hash.clone());
+    }
+    let hash = sha256_file(path)?;
+    cache().lock().unwrap().insert(key, hash.clone());
+    Ok(hash)
+}
+
+/// 计算字符串的 SHA-256
+pub fn sha256_str(data: &str) -> String {
+    hex::encode(Sha256::digest(data.as_bytes()))
+}
+
+/// 计算字节数据的 SHA-256
+pub fn sha256_bytes(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
@@ -0,0 +1,501 @@
+//! 只读的 HTTP 健康检查，供运维监控信息亭电脑上运行的实例。默认关闭，
+//! 只监听设置中的地址（默认 127.0.0.1），请求需带 `Authorization: Bearer <令牌>`，令牌保存在系统钥匙串。
+//! 只有 `GET /health` 一个路由，返回版本、运行时长、adb 状态、设备数、最近一次处理结果、当天成功与失败次数与待清理的磁盘占用。
+//! 所有数据取自设备跟踪的最近状态、任务登记表与审计日志，处理请求时从不调用 adb，监控不会影响正在进行的操作。
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use tauri::Manager;
+
+use crate::adb::{DeviceTracker, TrackerSnapshot};
+use crate::control::token_matches;
+use crate::jobs::JobRegistry;
+use crate::paths::{self, AppPaths};
+use crate::{audit, inbox, metrics, safe_path};
+
+const SETTINGS_FILE: &str = "health_settings.json";
+/// 令牌在系统钥匙串中的服务名与账户名
+const KEYRING_SERVICE: &str = "apk-disguise-pro-health";
+const KEYRING_USER: &str = "health";
+const MIN_TOKEN_LEN: usize = 16;
+/// 请求头的最大长度，超过时断开连接
+const MAX_HEAD_BYTES: u64 = 8 * 1024;
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+/// 审计日志统计与目录大小的缓存时长，频繁轮询时不重复遍历磁盘
+const SLOW_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// 健康检查设置，令牌不写入文件而是保存在系统钥匙串
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct HealthSettings {
+    pub enabled: bool,
+    /// 监听地址，默认 127.0.0.1；设为局域网地址时监控机才能访问
+    pub bind_address: String,
+    pub port: u16,
+    /// 计算「当天」使用的时区，相对 UTC 的分钟数（东八区为 480）
+    pub utc_offset_minutes: i32,
+}
+
+impl Default for HealthSettings {
+    fn default() -> Self {
+        HealthSettings { enabled: false, bind_address: "127.0.0.1".to_string(), port: 8765, utc_offset_minutes: 0 }
+    }
+}
+
+/// 健康检查的当前状态
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct HealthStatus {
+    pub settings: HealthSettings,
+    pub running: bool,
+    /// 正在监听的地址，如 `127.0.0.1:8765`
+    pub endpoint: Option<String>,
+    pub token_set: bool,
+    /// 启动时按设置开启失败的原因
+    pub error: Option<String>,
+}
+
+/// 最近一次处理的结果
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct LastJob {
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub timestamp: u64,
+    pub success: bool,
+    pub message: Option<String>,
+}
+
+/// 当天的处理次数
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct DayCounts {
+    /// 当天开始的 Unix 时间戳（秒）
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub since: u64,
+    pub succeeded: usize,
+    pub failed: usize,
+}
+
+/// `GET /health` 返回的文档
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct HealthDocument {
+    /// adb 跟踪正常时为 `ok`，否则为 `degraded`
+    pub status: String,
+    pub version: String,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub uptime_secs: u64,
+    pub adb: TrackerSnapshot,
+    pub connected_devices: usize,
+    pub running_jobs: usize,
+    pub last_job: Option<LastJob>,
+    pub today: DayCounts,
+    /// 残留的工作目录与收件箱占用
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub pending_cleanup_bytes: u64,
+}
+
+/// 需要读磁盘的部分，缓存 `SLOW_CACHE_TTL`
+#[derive(Clone)]
+struct SlowPart {
+    at: Instant,
+    last_job: Option<LastJob>,
+    today: DayCounts,
+    pending_cleanup_bytes: u64,
+}
+
+struct Running {
+    endpoint: SocketAddr,
+    stop: Arc<AtomicBool>,
+}
+
+/// 正在运行的健康检查（托管状态）
+pub struct HealthServer {
+    started: Instant,
+    running: Mutex<Option<Running>>,
+    slow: Mutex<Option<SlowPart>>,
+    /// 启动时按设置开启失败的原因
+    start_error: Mutex<Option<String>>,
+}
+
+impl Default for HealthServer {
+    fn default() -> Self {
+        HealthServer { started: Instant::now(), running: Mutex::new(None), slow: Mutex::new(None), start_error: Mutex::new(None) }
+    }
+}
+
+fn load_settings(paths: &AppPaths) -> HealthSettings {
+    fs::read_to_string(paths.config_dir.join(SETTINGS_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn stored_token() -> Option<String> {
+    keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER).ok()?.get_password().ok().filter(|t| !t.is_empty())
+}
+
+/// 校验设置；开启时必须已有令牌（或本次提供）
+pub fn validate(settings: &HealthSettings, token_available: bool) -> Result<SocketAddr, String> {
+    let ip: IpAddr = settings.bind_address.trim().parse().map_err(|_| format!("无效的监听地址: {}", settings.bind_address))?;
+    if settings.port == 0 {
+        return Err("端口不能为 0".to_string());
+    }
+    if !(-14 * 60..=14 * 60).contains(&settings.utc_offset_minutes) {
+        return Err(format!("时区偏移超出范围: {} 分钟", settings.utc_offset_minutes));
+    }
+    if settings.enabled && !token_available {
+        return Err("开启健康检查前需要设置访问令牌".to_string());
+    }
+    Ok(SocketAddr::new(ip, settings.port))
+}
+
+/// 按时区偏移计算的当天开始时间
+fn day_start(now: u64, utc_offset_minutes: i32) -> u64 {
+    let offset = i64::from(utc_offset_minutes) * 60;
+    let local = now as i64 + offset;
+    (local - local.rem_euclid(86_400) - offset).max(0) as u64
+}
+
+/// 从审计日志统计当天的处理结果；批处理的子任务各自计入
+fn job_outcomes(paths: &AppPaths, since: u64) -> (Option<LastJob>, DayCounts) {
+    let mut today = DayCounts { since, ..Default::default() };
+    let mut last_job = None;
+    for entry in audit::entries_since(paths, since).into_iter().filter(|e| e.operation == "process_apk") {
+        if entry.success {
+            today.succeeded += 1;
+        } else {
+            today.failed += 1;
+        }
+        last_job = Some(LastJob { timestamp: entry.timestamp, success: entry.success, message: entry.detail });
+    }
+    (last_job, today)
+}
+
+impl HealthServer {
+    fn slow_part(&self, paths: &AppPaths, utc_offset_minutes: i32) -> SlowPart {
+        let mut slow = self.slow.lock().unwrap();
+        if let Some(cached) = slow.as_ref().filter(|s| s.at.elapsed() < SLOW_CACHE_TTL) {
+            return cached.clone();
+        }
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        let (last_job, today) = job_outcomes(paths, day_start(now, utc_offset_minutes));
+        let part = SlowPart {
+            at: Instant::now(),
+            last_job,
+            today,
+            pending_cleanup_bytes: metrics::dir_size(&safe_path::work_root()) + metrics::dir_size(&inbox::inbox_dir(paths)),
+        };
+        *slow = Some(part.clone());
+        part
+    }
+
+    /// 由托管状态组装健康文档
+    fn document(&self, app: &tauri::AppHandle, utc_offset_minutes: i32) -> HealthDocument {
+        let adb = app.state::<DeviceTracker>().snapshot();
+        let running_jobs = app.state::<JobRegistry>().running_count();
+        let slow = self.slow_part(&app.state::<AppPaths>(), utc_offset_minutes);
+        build_document(adb, running_jobs, app.package_info().version.to_string(), self.started.elapsed().as_secs(), slow)
+    }
+}
+
+fn build_document(adb: TrackerSnapshot, running_jobs: usize, version: String, uptime_secs: u64, slow: SlowPart) -> HealthDocument {
+    HealthDocument {
+        status: if adb.tracking && adb.error.is_none() { "ok" } else { "degraded" }.to_string(),
+        version,
+        uptime_secs,
+        connected_devices: adb.ready_devices,
+        adb,
+        running_jobs,
+        last_job: slow.last_job,
+        today: slow.today,
+        pending_cleanup_bytes: slow.pending_cleanup_bytes,
+    }
+}
+
+/// 读取请求行与请求头，返回方法、路径与 Bearer 令牌
+fn read_head(stream: &TcpStream) -> io::Result<(String, String, Option<String>)> {
+    let mut reader = BufReader::new(stream.take(MAX_HEAD_BYTES));
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = (parts.next().unwrap_or_default().to_string(), parts.next().unwrap_or_default().to_string());
+    let mut token = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("authorization") {
+                token = value.trim().strip_prefix("Bearer ").map(|t| t.trim().to_string());
+            }
+        }
+    }
+    Ok((method, path, token))
+}
+
+fn respond(stream: &mut TcpStream, status: &str, body: &str) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+fn error_body(message: &str) -> String {
+    serde_json::json!({ "error": message }).to_string()
+}
+
+/// 处理一个连接，只接受一个请求；通过鉴权与路由检查后才组装文档
+fn serve(mut stream: TcpStream, token: &str, document: impl FnOnce() -> HealthDocument) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let (method, path, given) = read_head(&stream)?;
+    if !given.as_deref().is_some_and(|given| token_matches(token, given)) {
+        return respond(&mut stream, "401 Unauthorized", &error_body("令牌无效"));
+    }
+    if path.split('?').next() != Some("/health") {
+        return respond(&mut stream, "404 Not Found", &error_body("只提供 /health"));
+    }
+    if method != "GET" {
+        return respond(&mut stream, "405 Method Not Allowed", &error_body("只接受 GET"));
+    }
+    let body = serde_json::to_string(&document()).map_err(io::Error::other)?;
+    respond(&mut stream, "200 OK", &body)
+}
+
+impl HealthServer {
+    fn start(&self, app: &tauri::AppHandle, settings: &HealthSettings) -> Result<(), String> {
+        let mut running = self.running.lock().unwrap();
+        if running.is_some() {
+            return Ok(());
+        }
+        let token = stored_token().ok_or_else(|| "系统钥匙串中没有健康检查令牌".to_string())?;
+        let address = validate(settings, true)?;
+        let listener = TcpListener::bind(address).map_err(|e| format!("启动健康检查失败（{}）: {}", address, e))?;
+        let endpoint = listener.local_addr().unwrap_or(address);
+        let stop = Arc::new(AtomicBool::new(false));
+        let (app, flag, utc_offset_minutes) = (app.clone(), stop.clone(), settings.utc_offset_minutes);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if flag.load(Ordering::SeqCst) {
+                    break;
+                }
+                let Ok(stream) = stream else { continue };
+                let (app, token) = (app.clone(), token.clone());
+                thread::spawn(move || {
+                    let _ = serve(stream, &token, || app.state::<HealthServer>().document(&app, utc_offset_minutes));
+                });
+            }
+        });
+        *running = Some(Running { endpoint, stop });
+        Ok(())
+    }
+
+    /// 停止监听；连接到自身以唤醒阻塞在 accept 上的线程
+    fn stop(&self) {
+        if let Some(running) = self.running.lock().unwrap().take() {
+            running.stop.store(true, Ordering::SeqCst);
+            *self.slow.lock().unwrap() = None;
+            let mut wake = running.endpoint;
+            if wake.ip().is_unspecified() {
+                wake.set_ip(if wake.is_ipv4() { IpAddr::from([127, 0, 0, 1]) } else { IpAddr::from([0u16, 0, 0, 0, 0, 0, 0, 1]) });
+            }
+            let _ = TcpStream::connect_timeout(&wake, Duration::from_millis(500));
+        }
+    }
+
+    fn endpoint(&self) -> Option<String> {
+        self.running.lock().unwrap().as_ref().map(|r| r.endpoint.to_string())
+    }
+}
+
+/// 启动时按设置决定是否开启健康检查
+pub fn start_if_enabled(app: &tauri::AppHandle) {
+    let settings = load_settings(&app.state::<AppPaths>());
+    if settings.enabled {
+        let server = app.state::<HealthServer>();
+        let error = server.start(app, &settings).err();
+        *server.start_error.lock().unwrap() = error;
+    }
+}
+
+/// 应用退出时关闭健康检查
+pub fn shutdown(app: &tauri::AppHandle) {
+    if let Some(server) = app.try_state::<HealthServer>() {
+        server.stop();
+    }
+}
+
+fn status(server: &HealthServer, paths: &AppPaths) -> HealthStatus {
+    let endpoint = server.endpoint();
+    HealthStatus {
+        settings: load_settings(paths),
+        running: endpoint.is_some(),
+        endpoint,
+        token_set: stored_token().is_some(),
+        error: server.start_error.lock().unwrap().clone(),
+    }
+}
+
+/// 获取健康检查的设置与状态
+#[tauri::command]
+pub fn get_health_status(server: tauri::State<'_, HealthServer>, paths: tauri::State<'_, AppPaths>) -> HealthStatus {
+    status(&server, &paths)
+}
+
+/// 保存健康检查设置并按设置重启监听；`token` 不为空时写入系统钥匙串，至少 16 个字符
+#[tauri::command]
+pub fn set_health_settings(
+    app: tauri::AppHandle,
+    server: tauri::State<'_, HealthServer>,
+    paths: tauri::State<'_, AppPaths>,
+    settings: HealthSettings,
+    token: Option<String>,
+) -> Result<HealthStatus, String> {
+    let token = token.map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+    let result = (|| {
+        if token.as_ref().is_some_and(|t| t.len() < MIN_TOKEN_LEN) {
+            return Err(format!("令牌至少需要 {} 个字符", MIN_TOKEN_LEN));
+        }
+        validate(&settings, token.is_some() || stored_token().is_some())?;
+        if let Some(token) = &token {
+            keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)
+                .and_then(|entry| entry.set_password(token))
+                .map_err(|e| format!("保存令牌到系统钥匙串失败: {}", e))?;
+        }
+        let content = serde_json::to_vec_pretty(&settings).map_err(|e| e.to_string())?;
+        paths::write_atomic(&paths.config_dir.join(SETTINGS_FILE), &content).map_err(|e| e.to_string())?;
+        // 地址、端口或令牌可能已变化，先停止再按新设置启动
+        server.stop();
+        *server.start_error.lock().unwrap() = None;
+        if settings.enabled {
+            server.start(&app, &settings)?;
+        }
+        Ok(())
+    })();
+    let mut params = serde_json::to_value(&settings).unwrap_or_default();
+    params["token_changed"] = serde_json::Value::Bool(token.is_some());
+    audit::record(&app, "set_health_settings", params, &result);
+    result.map(|_| status(&server, &paths))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN: &str = "0123456789abcdef";
+
+    fn fake_document() -> HealthDocument {
+        let adb = TrackerSnapshot {
+            tracking: true,
+            transport: Some("track".to_string()),
+            ready_devices: 2,
+            total_devices: 3,
+            error: None,
+            updated_at: Some(1_700_000_000),
+        };
+        let slow = SlowPart {
+            at: Instant::now(),
+            last_job: Some(LastJob { timestamp: 1_700_000_100, success: false, message: Some("签名失败".to_string()) }),
+            today: DayCounts { since: 1_699_920_000, succeeded: 4, failed: 1 },
+            pending_cleanup_bytes: 4096,
+        };
+        build_document(adb, 1, "1.2.3".to_string(), 360, slow)
+    }
+
+    /// 在随机端口上处理一个请求，返回状态行与响应体
+    fn request(head: &str) -> (String, serde_json::Value) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            serve(stream, TOKEN, fake_document).unwrap();
+        });
+        let mut client = TcpStream::connect(address).unwrap();
+        client.write_all(head.as_bytes()).unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+        server.join().unwrap();
+        let (headers, body) = response.split_once("\r\n\r\n").unwrap();
+        (headers.lines().next().unwrap().to_string(), serde_json::from_str(body).unwrap())
+    }
+
+    #[test]
+    fn health_route_returns_the_documented_schema() {
+        let (status, body) = request(&format!("GET /health HTTP/1.1\r\nHost: localhost\r\nAuthorization: Bearer {}\r\n\r\n", TOKEN));
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        let mut keys: Vec<&str> = body.as_object().unwrap().keys().map(String::as_str).collect();
+        keys.sort_unstable();
+        assert_eq!(
+            keys,
+            [
+                "adb",
+                "connected_devices",
+                "last_job",
+                "pending_cleanup_bytes",
+                "running_jobs",
+                "status",
+                "today",
+                "uptime_secs",
+                "version"
+            ]
+        );
+        assert_eq!(body["status"], "ok");
+        assert_eq!(body["version"], "1.2.3");
+        assert_eq!(body["connected_devices"], 2);
+        assert_eq!(body["adb"]["transport"], "track");
+        assert_eq!(body["today"], serde_json::json!({ "since": 1_699_920_000u64, "succeeded": 4, "failed": 1 }));
+        assert_eq!(body["last_job"]["success"], false);
+        let document: HealthDocument = serde_json::from_value(body).unwrap();
+        assert_eq!(document, fake_document());
+    }
+
+    #[test]
+    fn requests_without_the_token_or_on_other_routes_are_refused() {
+        let (status, body) = request("GET /health HTTP/1.1\r\nAuthorization: Bearer wrong-token-value\r\n\r\n");
+        assert_eq!(status, "HTTP/1.1 401 Unauthorized");
+        assert!(body["error"].is_string());
+        let (status, _) = request(&format!("GET /jobs HTTP/1.1\r\nAuthorization: Bearer {}\r\n\r\n", TOKEN));
+        assert_eq!(status, "HTTP/1.1 404 Not Found");
+        let (status, _) = request(&format!("POST /health HTTP/1.1\r\nAuthorization: Bearer {}\r\n\r\n", TOKEN));
+        assert_eq!(status, "HTTP/1.1 405 Method Not Allowed");
+    }
+
+    #[test]
+    fn tracker_errors_degrade_the_status() {
+        let mut adb = fake_document().adb;
+        adb.error = Some("adb server 未运行".to_string());
+        let slow = SlowPart { at: Instant::now(), last_job: None, today: DayCounts::default(), pending_cleanup_bytes: 0 };
+        assert_eq!(build_document(adb, 0, "1.2.3".to_string(), 0, slow).status, "degraded");
+    }
+
+    #[test]
+    fn settings_validation() {
+        let settings = HealthSettings { enabled: true, ..Default::default() };
+        assert_eq!(validate(&settings, true).unwrap(), "127.0.0.1:8765".parse().unwrap());
+        assert!(validate(&settings, false).is_err());
+        assert!(validate(&HealthSettings { bind_address: "localhost".to_string(), ..Default::default() }, true).is_err());
+        assert!(validate(&HealthSettings { port: 0, ..Default::default() }, true).is_err());
+        assert!(validate(&HealthSettings { utc_offset_minutes: 15 * 60, ..Default::default() }, true).is_err());
+    }
+
+    #[test]
+    fn day_start_follows_the_utc_offset() {
+        // 2023-11-15 01:00 UTC，东八区已是 09:00
+        let now = 1_700_010_000;
+        assert_eq!(day_start(now, 0), 1_700_006_400);
+        assert_eq!(day_start(now, 480), 1_700_006_400 - 8 * 3600);
+        assert_eq!(day_start(now, -300), 1_700_006_400 - 86_400 + 5 * 3600);
+    }
+}
@@ -0,0 +1,110 @@
+//! 流水线钩子：在指定步骤之间运行用户提供的可执行文件。
+//!
+//! 约定：
+//! - 钩子以工作目录为当前目录启动，不接收命令行参数；
+//! - 环境变量 `WORK_DIR`（反编译目录）、`ORIGINAL_PACKAGE`、`NEW_PACKAGE` 始终提供，
+//!   `after_sign` 额外提供 `OUTPUT_APK`（签名后的 APK）；
+//! - 退出码为 0 表示成功，其它退出码或超时都会中止本次处理，步骤名为 `hook_<钩子名>`；
+//! - 标准输出逐行写入运行日志，标准错误在失败时附在结果消息中。
+
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::emit_progress;
+
+const DEFAULT_TIMEOUT_SECS: u64 = 300;
+
+/// 各钩子点对应的可执行文件路径
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct PipelineHooks {
+    pub after_decompile: Option<String>,
+    pub before_rebuild: Option<String>,
+    pub after_sign: Option<String>,
+    /// 单个钩子的超时时间（秒），默认 300
+    #[cfg_attr(feature = "export-bindings", ts(type = "number | null"))]
+    pub timeout_secs: Option<u64>,
+}
+
+/// 钩子运行时可用的上下文
+pub struct HookContext<'a> {
+    pub work_dir: &'a Path,
+    pub original_package: &'a str,
+    pub new_package: &'a str,
+    pub output_apk: Option<&'a Path>,
+}
+
+/// 运行指定钩子，失败时返回 (步骤名, 错误信息)
+pub fn run_hook(
+    app: &tauri::AppHandle,
+    name: &str,
+    executable: Option<&str>,
+    timeout_secs: Option<u64>,
+    ctx: &HookContext<'_>,
+) -> Result<(), (String, String)> {
+    let Some(executable) = executable.filter(|e| !e.trim().is_empty()) else {
+        return Ok(());
+    };
+    let step = format!("hook_{}", name);
+    emit_progress(app, &step, format!("运行钩子: {}", executable));
+
+    let mut cmd = Command::new(executable);
+    cmd.current_dir(ctx.work_dir)
+        .env("WORK_DIR", ctx.work_dir)
+        .env("ORIGINAL_PACKAGE", ctx.original_package)
+        .env("NEW_PACKAGE", ctx.new_package)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if let Some(apk) = ctx.output_apk {
+        cmd.env("OUTPUT_APK", apk);
+    }
+
+    let mut child = cmd.spawn().map_err(|e| (step.clone(), format!("钩子启动失败: {}", e)))?;
+
+    let stdout = child.stdout.take();
+    let log_app = app.clone();
+    let log_step = step.clone();
+    let stdout_reader = thread::spawn(move || {
+        if let Some(out) = stdout {
+            for line in BufReader::new(out).lines().map_while(Result::ok) {
+                emit_progress(&log_app, &log_step, line);
+            }
+        }
+    });
+    let mut stderr = child.stderr.take();
+    let stderr_reader = thread::spawn(move || {
+        let mut buf = String::new();
+        if let Some(err) = stderr.as_mut() {
+            let _ = err.read_to_string(&mut buf);
+        }
+        buf
+    });
+
+    let timeout = Duration::from_secs(timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS));
+    let start = Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break Some(status),
+            Ok(None) if start.elapsed() >= timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                break None;
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(100)),
+            Err(e) => return Err((step, format!("等待钩子结束失败: {}", e))),
+        }
+    };
+
+    let _ = stdout_reader.join();
+    let stderr_text = stderr_reader.join().unwrap_or_default();
+    match status {
+        Some(s) if s.success() => Ok(()),
+        Some(s) => Err((step, format!("钩子退出码 {}: {}", s.code().unwrap_or(-1), stderr_text.trim()))),
+        None => Err((step, format!("钩子运行超过 {} 秒被终止: {}", timeout.as_secs(), stderr_text.trim()))),
+    }
+}
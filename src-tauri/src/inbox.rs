@@ -0,0 +1,153 @@
+//! 拖放文件收件箱。macOS 沙盒下拖入窗口的文件只在拖放时短暂可读，
+//! 需要立即复制（或硬链接）到应用私有目录，之后的处理都使用收件箱中的路径。
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tauri::Emitter;
+
+use crate::input_path::{self, Expect};
+use crate::io_pool::{IoPool, Priority};
+use crate::jobs::JobRegistry;
+use crate::paths::AppPaths;
+use crate::{batch, safe_path};
+
+/// 每复制这么多字节推送一次进度
+const PROGRESS_STEP: u64 = 8 * 1024 * 1024;
+/// 刚拖入、还没开始处理的文件不会被清理
+const MIN_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// 复制进度（`inbox-progress` 事件）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct InboxProgress {
+    pub source: String,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub copied_bytes: u64,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub total_bytes: u64,
+}
+
+/// 收件箱清理结果
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct InboxCleanup {
+    pub removed: usize,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub freed_bytes: u64,
+    /// 仍被排队或进行中的任务引用而保留的条目数
+    pub kept: usize,
+}
+
+pub fn inbox_dir(paths: &AppPaths) -> PathBuf {
+    paths.data_dir.join("inbox")
+}
+
+/// 分块复制，推送进度事件
+fn copy_with_progress(app: &tauri::AppHandle, source: &Path, target: &Path) -> io::Result<()> {
+    let total_bytes = fs::metadata(source)?.len();
+    let mut reader = fs::File::open(source)?;
+    let mut writer = fs::File::create(target)?;
+    let mut buf = vec![0u8; 1024 * 1024];
+    let (mut copied_bytes, mut reported) = (0u64, 0u64);
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        writer.write_all(&buf[..n])?;
+        copied_bytes += n as u64;
+        if copied_bytes - reported >= PROGRESS_STEP || copied_bytes == total_bytes {
+            reported = copied_bytes;
+            let progress = InboxProgress { source: source.to_string_lossy().to_string(), copied_bytes, total_bytes };
+            let _ = app.emit("inbox-progress", progress);
+        }
+    }
+    writer.sync_all()
+}
+
+/// 放入收件箱：每个文件一个子目录，保留原文件名（后缀与工作目录名都由文件名决定）
+fn import(app: &tauri::AppHandle, paths: &AppPaths, source: &Path) -> Result<PathBuf, String> {
+    let name = source.file_name().ok_or_else(|| format!("无效的文件路径: {}", source.display()))?;
+    let ts = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+    let entry = inbox_dir(paths).join(format!("drop-{}", ts));
+    fs::create_dir_all(&entry).map_err(|e| format!("创建收件箱目录失败: {}", e))?;
+    let target = entry.join(name);
+    // 同一卷上优先硬链接，不占额外空间
+    if fs::hard_link(source, &target).is_err() {
+        copy_with_progress(app, source, &target).map_err(|e| {
+            let _ = safe_path::remove_dir_all(&entry);
+            format!("复制 {} 失败: {}", source.display(), e)
+        })?;
+    }
+    Ok(target)
+}
+
+/// 前端在拖放时立即调用：macOS 上将文件放入收件箱并返回之后处理使用的路径；其他平台只检查文件存在并原样返回
+#[tauri::command]
+pub async fn register_dropped_file(
+    app: tauri::AppHandle,
+    paths: tauri::State<'_, AppPaths>,
+    pool: tauri::State<'_, IoPool>,
+    path: String,
+) -> Result<String, String> {
+    let path = input_path::arg(&path, Expect::File).map_err(|e| e.to_string())?;
+    let source = Path::new(&path);
+    if !source.is_file() {
+        return Err(format!("文件不存在或不可读: {}", path));
+    }
+    if !cfg!(target_os = "macos") {
+        return Ok(path);
+    }
+    // 用户正在等待拖放结果，复制以交互优先级执行
+    let paths = paths.inner().clone();
+    let source = source.to_path_buf();
+    let target = pool.run(Priority::Interactive, "import_dropped_file", move || import(&app, &paths, &source)).await??;
+    Ok(target.to_string_lossy().to_string())
+}
+
+/// 清理收件箱中没有被排队或进行中任务引用的文件；处理输出（`*_fixed.apk`）与拖入后一小时内的文件保留
+#[tauri::command]
+pub fn cleanup_inbox(paths: tauri::State<'_, AppPaths>, jobs: tauri::State<'_, JobRegistry>) -> Result<InboxCleanup, String> {
+    let in_use: Vec<PathBuf> = jobs.active_inputs().into_iter().chain(batch::pending_inputs(&paths)).map(PathBuf::from).collect();
+    let mut cleanup = InboxCleanup::default();
+    let Ok(entries) = fs::read_dir(inbox_dir(&paths)) else {
+        return Ok(cleanup);
+    };
+    for entry in entries.filter_map(|e| e.ok()).map(|e| e.path()).filter(|p| p.is_dir()) {
+        let young = fs::metadata(&entry)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.elapsed().ok())
+            .is_none_or(|age| age < MIN_AGE);
+        if young || in_use.iter().any(|p| p.starts_with(&entry)) {
+            cleanup.kept += 1;
+            continue;
+        }
+        let has_output = fs::read_dir(&entry)
+            .map(|files| files.filter_map(|f| f.ok()).any(|f| f.file_name().to_string_lossy().ends_with("_fixed.apk")))
+            .unwrap_or(false);
+        if has_output {
+            // 只删除拖入的源文件，保留输出
+            let sources: Vec<fs::DirEntry> = fs::read_dir(&entry)
+                .map_err(|e| e.to_string())?
+                .filter_map(|f| f.ok())
+                .filter(|f| !f.file_name().to_string_lossy().ends_with("_fixed.apk"))
+                .collect();
+            if sources.is_empty() {
+                continue;
+            }
+            for file in sources {
+                cleanup.freed_bytes += file.metadata().map(|m| m.len()).unwrap_or(0);
+                fs::remove_file(file.path()).map_err(|e| e.to_string())?;
+            }
+        } else {
+            cleanup.freed_bytes += safe_path::remove_dir_all(&entry).map_err(|e| e.to_string())?;
+        }
+        cleanup.removed += 1;
+    }
+    Ok(cleanup)
+}
@@ -0,0 +1,306 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tauri::Manager;
+
+use crate::jobs::JobContext;
+use crate::metrics::Recorder;
+use crate::paths::AppPaths;
+use crate::pipeline::{self, ProcessConfig};
+use crate::{artifacts, axml, emit_progress, entries, hash, safe_path, signing, source, stealth, ProcessResult};
+
+const RUNS_FILE: &str = "runs.jsonl";
+
+/// 只影响 Manifest 包名、可以增量处理的字段
+const MANIFEST_ONLY_FIELDS: &[&str] = &["new_prefix", "custom_suffix"];
+
+/// 不影响回编译产物、或增量处理时会重新执行的字段（对齐与签名）
+const IGNORED_FIELDS: &[&str] = &[
+    "apk_path",
+    "hydrate_source",
+    "device_id",
+    "install_after",
+    "post_install_actions",
+    "install_flags",
+    "rollback_on_launch_failure",
+    "apktool_warning_threshold",
+    "log_queue_depth",
+    "customer",
+    "min_output_ratio",
+    "java_path",
+    "zipalign_path",
+    "apksigner_path",
+    "keystore_path",
+    "auto_convert_keystore",
+    "key_alias_override",
+    "key_passwords",
+];
+
+/// 一次成功处理的记录，用于判断之后的处理能否走增量路径
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RunRecord {
+    pub source_sha256: String,
+    /// 去掉 Manifest 字段与无关字段后的处理参数
+    pub options: serde_json::Value,
+    pub package: String,
+    pub output_path: String,
+    pub output_sha256: String,
+    pub timestamp: u64,
+    #[serde(default)]
+    pub job_id: Option<String>,
+    #[serde(default)]
+    pub original_package: Option<String>,
+    /// 处理结果（即处理报告）
+    #[serde(default)]
+    pub report: Option<serde_json::Value>,
+    /// 签名使用的别名与证书指纹；同一密钥库可按别名签出不同身份
+    #[serde(default)]
+    pub signing_identity: Option<signing::SigningIdentity>,
+}
+
+fn normalized_options(config: &ProcessConfig) -> Result<serde_json::Value, String> {
+    let mut value = serde_json::to_value(config).map_err(|e| e.to_string())?;
+    if let Some(map) = value.as_object_mut() {
+        for key in MANIFEST_ONLY_FIELDS.iter().chain(IGNORED_FIELDS) {
+            map.remove(*key);
+        }
+    }
+    Ok(value)
+}
+
+pub fn load_records(paths: &AppPaths) -> Vec<RunRecord> {
+    let Ok(file) = fs::File::open(paths.data_dir.join(RUNS_FILE)) else {
+        return Vec::new();
+    };
+    io::BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect()
+}
+
+/// 处理成功后记录源 APK、参数、输出与处理报告；失败的处理不记录
+pub fn record_success(paths: &AppPaths, config: &ProcessConfig, result: &ProcessResult) -> Result<(), String> {
+    let (true, Some(output_path)) = (result.success, result.output_path.as_deref()) else {
+        return Ok(());
+    };
+    let original_package = axml::read_manifest_from_apk(Path::new(&config.apk_path))
+        .ok()
+        .and_then(|elements| elements.into_iter().find(|e| e.name == "manifest"))
+        .and_then(|manifest| manifest.attr("package").map(axml::AxmlValue::as_string));
+    let record = RunRecord {
+        source_sha256: hash::sha256_file_cached(Path::new(&config.apk_path))?,
+        options: normalized_options(config)?,
+        package: result
+            .new_package
+            .clone()
+            .unwrap_or_else(|| pipeline::new_package_name(&config.apk_path, &config.new_prefix, config.custom_suffix.as_deref())),
+        output_path: output_path.to_string(),
+        output_sha256: hash::sha256_file(Path::new(output_path))?,
+        timestamp: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+        job_id: result.job_id.clone(),
+        original_package,
+        report: serde_json::to_value(result).ok(),
+        signing_identity: result.signing_identity.clone(),
+    };
+    fs::create_dir_all(&paths.data_dir).map_err(|e| e.to_string())?;
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(paths.data_dir.join(RUNS_FILE))
+        .map_err(|e| e.to_string())?;
+    writeln!(file, "{}", serde_json::to_string(&record).map_err(|e| e.to_string())?).map_err(|e| e.to_string())
+}
+
+/// 声明了 ContentProvider 或自定义权限。增量处理只改写 `<manifest package>`，
+/// authorities 与权限名会保留原值，安装时与原应用冲突（INSTALL_FAILED_CONFLICTING_PROVIDER）；无法读取 Manifest 时同样视为声明了
+fn declares_provider_or_permission(apk: &Path) -> bool {
+    axml::read_manifest_from_apk(apk).map_or(true, |elements| elements.iter().any(|e| e.name == "provider" || e.name == "permission"))
+}
+
+/// 查找可作为增量基础的历史记录：同一源 APK、除 Manifest 字段外参数相同、输出文件仍未被改动。
+/// 需要安装、生成 ContentProvider 报告、改写资源中的包名引用，或 Manifest 声明了 ContentProvider 与自定义权限时走完整流程
+pub fn find_base(paths: &AppPaths, config: &ProcessConfig) -> Option<RunRecord> {
+    if config.install_after || config.content_uri_report.unwrap_or(false) || config.rewrite_res_xml_references.unwrap_or(false) {
+        return None;
+    }
+    if declares_provider_or_permission(Path::new(&config.apk_path)) {
+        return None;
+    }
+    let source_sha256 = hash::sha256_file_cached(Path::new(&config.apk_path)).ok()?;
+    let options = normalized_options(config).ok()?;
+    load_records(paths).into_iter().rev().find(|r| {
+        r.source_sha256 == source_sha256
+            && r.options == options
+            && hash::sha256_file(Path::new(&r.output_path)).is_ok_and(|h| h == r.output_sha256)
+    })
+}
+
+/// 去掉签名文件并替换 Manifest，其余条目原样拷贝。`stealth` 时 Manifest 使用与其他条目相同的固定时间
+fn rewrite_apk(src: &Path, dest: &Path, manifest: &[u8], stealth: bool) -> Result<(), String> {
+    let mut archive = zip::ZipArchive::new(fs::File::open(src).map_err(|e| e.to_string())?).map_err(|e| e.to_string())?;
+    let mut writer = zip::ZipWriter::new(fs::File::create(dest).map_err(|e| e.to_string())?);
+    for i in 0..archive.len() {
+        let entry = archive.by_index_raw(i).map_err(|e| e.to_string())?;
+        if signing::is_signature_entry(entry.name()) {
+            continue;
+        }
+        if entry.name() == "AndroidManifest.xml" {
+            let mut options = zip::write::SimpleFileOptions::default().compression_method(entry.compression());
+            if stealth {
+                options = options.last_modified_time(zip::DateTime::default());
+            }
+            drop(entry);
+            writer.start_file("AndroidManifest.xml", options).map_err(|e| e.to_string())?;
+            writer.write_all(manifest).map_err(|e| e.to_string())?;
+        } else {
+            writer.raw_copy_file(entry).map_err(|e| e.to_string())?;
+        }
+    }
+    writer.finish().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// 各 DEX 条目的名称与 CRC32
+fn dex_set(apk: &Path) -> Result<Vec<(String, u32)>, String> {
+    let mut archive = zip::ZipArchive::new(fs::File::open(apk).map_err(|e| e.to_string())?).map_err(|e| e.to_string())?;
+    let mut set = Vec::new();
+    for i in 0..archive.len() {
+        let entry = archive.by_index_raw(i).map_err(|e| e.to_string())?;
+        if entry.name().ends_with(".dex") && !entry.name().contains('/') {
+            set.push((entry.name().to_string(), entry.crc32()));
+        }
+    }
+    set.sort();
+    Ok(set)
+}
+
+fn failure(step: &str, message: String) -> ProcessResult {
+    ProcessResult { success: false, message, step: Some(step.to_string()), mode: Some("incremental".to_string()), ..Default::default() }
+}
+
+/// 增量处理的工作目录
+pub fn stage_dir(apk_path: &str) -> PathBuf {
+    let file_stem = Path::new(apk_path).file_stem().and_then(|s| s.to_str()).unwrap_or("apk");
+    safe_path::work_root().join(format!("{}_incremental", file_stem))
+}
+
+/// 增量处理：以上次的输出 APK 为基础，只改写 Manifest 包名，再对齐、签名并校验，完全跳过 apktool
+pub fn run(
+    app: &tauri::AppHandle,
+    config: &ProcessConfig,
+    base: &RunRecord,
+    new_package: &str,
+    job: &JobContext,
+) -> Result<ProcessResult, String> {
+    let mut recorder = Recorder::for_job(job);
+    let apk_size = fs::metadata(&config.apk_path).map(|m| m.len()).unwrap_or(0);
+    let path = Path::new(&config.apk_path);
+    let file_stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("apk");
+    let parent_dir = path.parent().unwrap_or(Path::new("."));
+    let stage_dir = stage_dir(&config.apk_path);
+    let stealth = config.stealth_output.unwrap_or(false);
+    let final_apk = if stealth {
+        stealth::output_path(path, new_package)
+    } else {
+        parent_dir.join(format!("{}_fixed.apk", file_stem))
+    };
+    let output_dir = app.state::<AppPaths>().data_dir.join("output");
+    let (final_apk, moved) = source::output_location(final_apk, &output_dir);
+    if moved.is_some() {
+        fs::create_dir_all(&output_dir).map_err(|e| format!("创建输出目录失败: {}", e))?;
+    }
+    let ([unsigned_apk, aligned_apk, signed_apk, base_apk], diversions) = artifacts::plan(
+        path,
+        &final_apk,
+        &stage_dir,
+        [
+            ("unsigned", stage_dir.join(format!("{}_unsigned.apk", file_stem))),
+            ("aligned", stage_dir.join(format!("{}_aligned.apk", file_stem))),
+            ("signed", stage_dir.join(format!("{}_signed.apk", file_stem))),
+            ("base", stage_dir.join("base.apk")),
+        ],
+    );
+    for diversion in moved.iter().chain(&diversions) {
+        recorder.note(diversion);
+    }
+
+    safe_path::remove_dir_all(&stage_dir).map_err(|e| e.to_string())?;
+    fs::create_dir_all(&stage_dir).map_err(|e| format!("创建工作目录失败: {}", e))?;
+    // 上次的输出可能就是本次的输出路径，先复制一份
+    fs::copy(&base.output_path, &base_apk).map_err(|e| format!("复制上次输出失败: {}", e))?;
+
+    emit_progress(app, "incremental", format!("仅修改包名: {} -> {}，跳过反编译与回编译", base.package, new_package));
+    let manifest = axml::read_manifest_bytes(&base_apk)
+        .and_then(|data| axml::set_string_attribute(&data, "manifest", "package", new_package));
+    let manifest = match manifest {
+        Ok(manifest) => manifest,
+        Err(e) => return Ok(failure("manifest", format!("改写 Manifest 失败: {}", e))),
+    };
+    if let Err(e) = rewrite_apk(&base_apk, &unsigned_apk, &manifest, stealth) {
+        return Ok(failure("manifest", format!("重建 APK 失败: {}", e)));
+    }
+    let min_ratio = config.min_output_ratio.unwrap_or(entries::DEFAULT_MIN_OUTPUT_RATIO);
+    if let Err(e) = entries::verify_rebuilt_apk(&unsigned_apk, apk_size, min_ratio) {
+        return Ok(failure("rebuild_verify", format!("重建 APK 校验失败: {}", e)));
+    }
+
+    let align = recorder
+        .run(
+            "zipalign",
+            Command::new(&config.zipalign_path).args(["-f", "-v", "4", unsigned_apk.to_str().unwrap(), aligned_apk.to_str().unwrap()]),
+        )
+        .map_err(|e| format!("对齐命令执行失败: {}", e))?;
+    if !align.status.success() {
+        let message = crate::native_deps::diagnose_failure(Path::new(&config.zipalign_path), &align)
+            .unwrap_or_else(|| format!("对齐失败: {}", String::from_utf8_lossy(&align.stderr)));
+        return Ok(failure("zipalign", message));
+    }
+
+    let signing_identity = match signing::identity_for_run(app, config) {
+        Ok(identity) => identity,
+        Err(e) => return Ok(failure("key_alias", e.to_string())),
+    };
+    let key = match signing::KeystoreConfig::for_run(config).and_then(|keystore| signing::SigningKey::open(config.pkcs11_signing.as_ref(), &keystore)) {
+        Ok(key) => key,
+        Err(e) => return Ok(failure("sign", format!("签名失败: {}", e))),
+    };
+    let sign = pipeline::sign_apk(&mut recorder, &config.java_path, &config.apksigner_path, &key, &aligned_apk, &signed_apk)?;
+    if !sign.status.success() {
+        return Ok(failure("sign", format!("签名失败: {}", key.failure_message(&String::from_utf8_lossy(&sign.stderr)))));
+    }
+
+    if let Err(e) = signing::verify_signature(&signed_apk, &config.java_path, &config.apksigner_path) {
+        return Ok(failure("verify", e.to_string()));
+    }
+    if dex_set(&base_apk)? != dex_set(&signed_apk)? {
+        return Ok(failure("verify", "增量输出的 DEX 与上次输出不一致".to_string()));
+    }
+    if let Err(e) = artifacts::promote(&signed_apk, &final_apk) {
+        return Ok(failure("sign", format!("写入输出文件失败: {}", e)));
+    }
+
+    let mut warnings = Vec::new();
+    if let Err(e) = safe_path::remove_dir_all(&stage_dir) {
+        warnings.push(e.to_string());
+    }
+    let run_metrics = recorder.finish(apk_size);
+    if let Err(e) = crate::metrics::append_history(&app.state::<AppPaths>(), &run_metrics) {
+        emit_progress(app, "metrics", format!("写入性能记录失败: {}", e));
+    }
+    Ok(ProcessResult {
+        success: true,
+        message: format!("✅ 增量处理完成（未重新编译）! 新包名: {}", new_package),
+        output_path: Some(final_apk.to_string_lossy().to_string()),
+        metrics: Some(run_metrics),
+        warnings,
+        mode: Some("incremental".to_string()),
+        signing_identity,
+        residual_traces: if stealth { stealth::RESIDUAL_TRACES.iter().map(ToString::to_string).collect() } else { Vec::new() },
+        ..Default::default()
+    })
+}
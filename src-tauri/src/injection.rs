@@ -0,0 +1,236 @@
+//! QA 用的故障注入：按规则让外部命令失败或延迟，以走通真实流程中的各个错误分支。
+//! 只在启用 `failure-injection` feature 时编译，正式构建中 `intercept` 为空函数。
+
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::process::{Command, Output};
+
+/// 一条注入规则
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct InjectionRule {
+    /// 命令行（程序与参数）中需包含的工具名，如 `apksigner`、`adb`
+    pub tool: String,
+    /// 命令行中还需包含的子串，如 `install`
+    pub args_contain: Option<String>,
+    /// 只对第 N 次匹配的调用生效（从 1 开始）；为空时每次都生效
+    pub nth: Option<u32>,
+    /// 执行前等待的毫秒数
+    #[cfg_attr(feature = "export-bindings", ts(type = "number | null"))]
+    pub delay_ms: Option<u64>,
+    /// 不执行命令，直接以该退出码返回；为空时延迟后照常执行
+    pub exit_code: Option<i32>,
+    #[serde(default)]
+    pub stdout: String,
+    #[serde(default)]
+    pub stderr: String,
+}
+
+/// 故障注入设置，可保存为 JSON 场景文件（见 fixtures/failure-injection）
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct FailureInjection {
+    pub enabled: bool,
+    pub rules: Vec<InjectionRule>,
+}
+
+#[cfg(feature = "failure-injection")]
+mod active {
+    use super::*;
+    use std::process::ExitStatus;
+    use std::sync::{Mutex, OnceLock};
+    use std::thread;
+    use std::time::Duration;
+
+    /// 当前设置与每条规则已匹配的次数
+    pub(super) fn state() -> &'static Mutex<(FailureInjection, Vec<u32>)> {
+        static STATE: OnceLock<Mutex<(FailureInjection, Vec<u32>)>> = OnceLock::new();
+        STATE.get_or_init(|| Mutex::new((FailureInjection::default(), Vec::new())))
+    }
+
+    #[cfg(unix)]
+    fn exit_status(code: i32) -> ExitStatus {
+        use std::os::unix::process::ExitStatusExt;
+        ExitStatus::from_raw(code << 8)
+    }
+
+    #[cfg(windows)]
+    fn exit_status(code: i32) -> ExitStatus {
+        use std::os::windows::process::ExitStatusExt;
+        ExitStatus::from_raw(code as u32)
+    }
+
+    pub fn intercept_line(line: &str) -> Option<io::Result<Output>> {
+        let (delay, fake) = {
+            let mut guard = state().lock().unwrap();
+            let (settings, counts) = &mut *guard;
+            if !settings.enabled {
+                return None;
+            }
+            let hit = settings.rules.iter().enumerate().find_map(|(i, rule)| {
+                let matches = line.contains(&rule.tool) && rule.args_contain.as_ref().is_none_or(|a| line.contains(a.as_str()));
+                if !matches {
+                    return None;
+                }
+                counts[i] += 1;
+                rule.nth.is_none_or(|n| n == counts[i]).then(|| rule.clone())
+            })?;
+            let fake = hit.exit_code.map(|code| Output {
+                status: exit_status(code),
+                stdout: hit.stdout.clone().into_bytes(),
+                stderr: hit.stderr.clone().into_bytes(),
+            });
+            (hit.delay_ms, fake)
+        };
+        if let Some(ms) = delay {
+            thread::sleep(Duration::from_millis(ms));
+        }
+        fake.map(Ok)
+    }
+}
+
+/// 命令行的文本形式，供规则匹配
+#[cfg_attr(not(feature = "failure-injection"), allow(dead_code))]
+fn command_line(cmd: &Command) -> String {
+    std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(|s| s.to_string_lossy().to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 执行命令前调用：命中规则时先延迟，再返回伪造的结果（不执行命令）；返回 `None` 时照常执行
+#[cfg(feature = "failure-injection")]
+pub fn intercept(cmd: &Command) -> Option<io::Result<Output>> {
+    active::intercept_line(&command_line(cmd))
+}
+
+#[cfg(not(feature = "failure-injection"))]
+#[inline(always)]
+pub fn intercept(_cmd: &Command) -> Option<io::Result<Output>> {
+    None
+}
+
+/// 设置故障注入规则，`enabled` 为 false 时关闭；每次设置都会重置调用计数
+#[tauri::command]
+pub fn set_failure_injection(settings: FailureInjection) -> Result<(), String> {
+    #[cfg(feature = "failure-injection")]
+    {
+        let mut guard = active::state().lock().unwrap();
+        let counts = vec![0; settings.rules.len()];
+        *guard = (settings, counts);
+        Ok(())
+    }
+    #[cfg(not(feature = "failure-injection"))]
+    {
+        let _ = settings;
+        Err("此版本未编译故障注入功能（需启用 failure-injection feature）".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn scenarios() -> Vec<(String, FailureInjection)> {
+        let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("fixtures/failure-injection");
+        let mut files: Vec<PathBuf> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .filter(|p| p.extension().is_some_and(|e| e == "json"))
+            .collect();
+        files.sort();
+        files
+            .into_iter()
+            .map(|path| {
+                let name = path.file_name().unwrap().to_string_lossy().to_string();
+                let settings = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap_or_else(|e| panic!("{}: {}", name, e));
+                (name, settings)
+            })
+            .collect()
+    }
+
+    /// 能命中规则的命令：工具名加上 `args_contain` 中的参数
+    fn matching_command(rule: &InjectionRule) -> Command {
+        let mut cmd = Command::new(&rule.tool);
+        cmd.args(rule.args_contain.as_deref().unwrap_or_default().split_whitespace()).arg("scenario.apk");
+        cmd
+    }
+
+    #[test]
+    fn scenario_files_are_valid() {
+        let scenarios = scenarios();
+        assert!(scenarios.len() >= 2);
+        for (name, settings) in scenarios {
+            assert!(settings.enabled, "{}", name);
+            assert!(!settings.rules.is_empty(), "{}", name);
+            for rule in &settings.rules {
+                assert!(!rule.tool.is_empty(), "{}", name);
+                assert!(rule.nth != Some(0), "{}: nth 从 1 开始", name);
+                assert!(rule.exit_code.is_some() || rule.delay_ms.is_some(), "{}: 规则既不失败也不延迟", name);
+            }
+        }
+    }
+
+    #[test]
+    fn command_line_joins_program_and_arguments() {
+        let mut cmd = Command::new("adb");
+        cmd.args(["-s", "emulator-5554", "install", "-r"]).arg("my app.apk");
+        assert_eq!(command_line(&cmd), "adb -s emulator-5554 install -r my app.apk");
+    }
+
+    #[cfg(not(feature = "failure-injection"))]
+    #[test]
+    fn disabled_builds_never_intercept() {
+        for (_, settings) in scenarios() {
+            assert!(set_failure_injection(settings.clone()).is_err());
+            for rule in &settings.rules {
+                assert!(intercept(&matching_command(rule)).is_none());
+            }
+        }
+    }
+
+    /// 逐个执行场景：命中的调用返回场景中的退出码与输出，并按 `nth` 只生效一次。
+    /// 规则共用全局状态，所以在同一个测试中依次执行
+    #[cfg(feature = "failure-injection")]
+    #[test]
+    fn runs_every_scenario() {
+        for (name, mut settings) in scenarios() {
+            // 场景中的延迟按比例缩短，只验证确实等待了
+            for rule in &mut settings.rules {
+                rule.delay_ms = rule.delay_ms.map(|ms| ms.min(20));
+            }
+            set_failure_injection(settings.clone()).unwrap();
+
+            for rule in &settings.rules {
+                let mut recorder = crate::metrics::Recorder::new();
+                let started = std::time::Instant::now();
+                let output = recorder.run("scenario", &mut matching_command(rule)).expect(&name);
+                assert!(started.elapsed().as_millis() as u64 >= rule.delay_ms.unwrap_or(0), "{}", name);
+                assert_eq!(output.status.code(), rule.exit_code, "{}", name);
+                assert_eq!(String::from_utf8_lossy(&output.stdout), rule.stdout, "{}", name);
+                assert_eq!(String::from_utf8_lossy(&output.stderr), rule.stderr, "{}", name);
+                assert_eq!(recorder.finish(0).steps[0].exit_code, rule.exit_code, "{}", name);
+
+                if rule.nth.is_some() {
+                    assert!(active::intercept_line(&command_line(&matching_command(rule))).is_none(), "{}: 第二次调用应照常执行", name);
+                }
+                // 其他工具不受影响
+                assert!(intercept(&Command::new("unrelated-tool")).is_none(), "{}", name);
+            }
+
+            match name.as_str() {
+                "sign-keystore-error.json" => {
+                    let stderr = &settings.rules[0].stderr;
+                    assert!(crate::signing::diagnose_keystore_error(stderr).is_some(), "签名失败应被识别为密钥库问题");
+                }
+                "device-disconnect-during-install.json" => {
+                    assert!(settings.rules[0].stderr.contains("device offline"));
+                }
+                _ => {}
+            }
+        }
+        set_failure_injection(FailureInjection::default()).unwrap();
+    }
+}
@@ -0,0 +1,373 @@
+//! 用户输入路径的统一规范化：去掉粘贴时带上的引号与空白、解码 file:// 地址、
+//! Windows 上把 WSL 形式的 /mnt/c/ 转为盘符路径、Unix 上展开 ~，并拒绝控制字符。
+//! 所有接受路径参数的命令都在入口处调用，失败时返回同时包含原始输入与解析结果的 `InvalidPath`。
+//! UNC 路径（\\server\share）与设备路径（\\?\、\\.\）原样保留。
+
+use std::path::PathBuf;
+
+use crate::assets::AssetSource;
+use crate::pipeline::ProcessConfig;
+use crate::signing::PipelineError;
+
+/// 参数对路径存在性的要求
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expect {
+    /// 不检查（输出路径、可在 PATH 中查找的工具名）
+    Any,
+    File,
+    Dir,
+}
+
+fn invalid(raw: &str, normalized: Option<&str>, reason: impl Into<String>) -> PipelineError {
+    PipelineError::InvalidPath { raw: raw.to_string(), normalized: normalized.map(str::to_string), reason: reason.into() }
+}
+
+/// 去掉首尾空白以及成对包裹的引号（可能多层，如 `"'path'"`）
+fn strip_quotes(raw: &str) -> &str {
+    let mut s = raw.trim();
+    loop {
+        let quoted = s.len() >= 2 && ((s.starts_with('"') && s.ends_with('"')) || (s.starts_with('\'') && s.ends_with('\'')));
+        if !quoted {
+            return s;
+        }
+        s = s[1..s.len() - 1].trim();
+    }
+}
+
+/// 解码 URI 中的 %XX 转义
+fn percent_decode(s: &str) -> Option<String> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = s.get(i + 1..i + 3)?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+/// `/C:/x` 或 `C:/x` 形式的盘符路径
+fn has_drive_prefix(s: &str) -> bool {
+    let b = s.as_bytes();
+    b.len() >= 2 && b[0].is_ascii_alphabetic() && b[1] == b':'
+}
+
+/// file:// 地址转为本地路径；`file://server/share` 在 Windows 上转为 UNC 路径
+fn from_file_uri(raw: &str, rest: &str) -> Result<String, PipelineError> {
+    let decoded = percent_decode(rest).ok_or_else(|| invalid(raw, None, "file:// 地址中的 % 转义无效"))?;
+    // 少写了一个斜杠的 file://C:/x
+    if has_drive_prefix(&decoded) {
+        return Ok(decoded);
+    }
+    let (host, path) = match decoded.find('/') {
+        Some(i) => decoded.split_at(i),
+        None => (decoded.as_str(), ""),
+    };
+    if host.is_empty() || host.eq_ignore_ascii_case("localhost") {
+        // file:///C:/x → C:/x
+        let trimmed = path.strip_prefix('/').filter(|p| has_drive_prefix(p));
+        return Ok(trimmed.unwrap_or(path).to_string());
+    }
+    if cfg!(windows) {
+        Ok(format!("//{}{}", host, path))
+    } else {
+        Err(invalid(raw, Some(&decoded), format!("不支持远程主机上的 file:// 地址（{}）", host)))
+    }
+}
+
+/// WSL 形式的 /mnt/c/Users → C:/Users
+fn from_wsl_mount(s: &str) -> Option<String> {
+    let rest = s.strip_prefix("/mnt/")?;
+    let (drive, tail) = match rest.find('/') {
+        Some(i) => rest.split_at(i),
+        None => (rest, ""),
+    };
+    (drive.len() == 1 && drive.as_bytes()[0].is_ascii_alphabetic())
+        .then(|| format!("{}:{}", drive.to_ascii_uppercase(), if tail.is_empty() { "/" } else { tail }))
+}
+
+/// Windows 设备路径（\\?\、\\.\ 及其正斜杠形式）不做任何改写
+fn is_device_path(s: &str) -> bool {
+    ["\\\\?\\", "\\\\.\\", "//?/", "//./"].iter().any(|prefix| s.starts_with(prefix))
+}
+
+fn expand_home(s: &str) -> String {
+    let home = std::env::var("HOME").ok().filter(|h| !h.is_empty());
+    match (home, s) {
+        (Some(home), "~") => home,
+        (Some(home), _) if s.starts_with("~/") => format!("{}{}", home.trim_end_matches('/'), &s[1..]),
+        _ => s.to_string(),
+    }
+}
+
+fn normalize(raw: &str) -> Result<String, PipelineError> {
+    let s = strip_quotes(raw);
+    if s.is_empty() {
+        return Err(invalid(raw, None, "路径为空"));
+    }
+    let s = match s.get(..7).filter(|scheme| scheme.eq_ignore_ascii_case("file://")) {
+        Some(_) => from_file_uri(raw, &s[7..])?,
+        None => s.to_string(),
+    };
+    if let Some(c) = s.chars().find(|c| c.is_control()) {
+        return Err(invalid(raw, Some(&s.escape_debug().to_string()), format!("路径包含控制字符 {:?}", c)));
+    }
+    if cfg!(windows) {
+        if is_device_path(&s) {
+            return Ok(s);
+        }
+        let s = from_wsl_mount(&s).unwrap_or(s);
+        Ok(s.replace('/', "\\"))
+    } else {
+        Ok(expand_home(&s))
+    }
+}
+
+/// 规范化用户输入的路径，不检查是否存在
+pub fn normalize_input_path(raw: &str) -> Result<PathBuf, PipelineError> {
+    normalize(raw).map(PathBuf::from)
+}
+
+/// 规范化路径并按参数语义检查存在性
+pub fn check(raw: &str, expect: Expect) -> Result<PathBuf, PipelineError> {
+    let path = normalize_input_path(raw)?;
+    let missing = match expect {
+        Expect::Any => None,
+        Expect::File if path.is_dir() => Some("需要文件，但这是一个目录"),
+        Expect::File if !path.is_file() => Some("文件不存在"),
+        Expect::Dir if path.is_file() => Some("需要目录，但这是一个文件"),
+        Expect::Dir if !path.is_dir() => Some("目录不存在"),
+        _ => None,
+    };
+    match missing {
+        Some(reason) => Err(invalid(raw, Some(&path.to_string_lossy()), reason)),
+        None => Ok(path),
+    }
+}
+
+/// 与 `check` 相同，返回字符串，便于直接替换命令的 String 参数
+pub fn arg(raw: &str, expect: Expect) -> Result<String, PipelineError> {
+    check(raw, expect).map(|path| path.to_string_lossy().into_owned())
+}
+
+/// 工具路径：可以是 PATH 中的命令名，只有写成路径时才要求文件存在
+pub fn tool_arg(raw: &str) -> Result<String, PipelineError> {
+    let normalized = arg(raw, Expect::Any)?;
+    if !normalized.contains(['/', '\\']) {
+        Ok(normalized)
+    } else {
+        arg(raw, Expect::File)
+    }
+}
+
+/// 规范化处理配置中的所有路径字段；空字段保持为空，由后续默认值补全
+pub fn normalize_config(config: &mut ProcessConfig) -> Result<(), PipelineError> {
+    if !config.apk_path.is_empty() {
+        config.apk_path = arg(&config.apk_path, Expect::File)?;
+    }
+    if !config.keystore_path.is_empty() {
+        config.keystore_path = arg(&config.keystore_path, Expect::File)?;
+    }
+    if let Some(profile) = config.pkcs11_signing.as_mut() {
+        profile.config_path = arg(&profile.config_path, Expect::File)?;
+    }
+    let pin_source = config.pkcs11_signing.iter_mut().map(|profile| &mut profile.pin_source);
+    for source in pin_source.chain(config.key_passwords.iter_mut().flatten().map(|entry| &mut entry.source)) {
+        if let crate::signing::SecretSource::File { path } = source {
+            *path = arg(path, Expect::File)?;
+        }
+    }
+    for tool in [&mut config.java_path, &mut config.apktool_path, &mut config.zipalign_path, &mut config.apksigner_path] {
+        if !tool.is_empty() {
+            *tool = tool_arg(tool)?;
+        }
+    }
+    for asset in config.asset_overrides.iter_mut().flatten() {
+        if let AssetSource::LocalFile(path) = &mut asset.source {
+            *path = arg(path, Expect::File)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 各种粘贴时可能带上的包裹形式
+    fn wrapped(path: &str) -> Vec<String> {
+        let mut out = Vec::new();
+        for pad in ["", " ", "\t", "  \n"] {
+            for (open, close) in [("", ""), ("\"", "\""), ("'", "'"), ("\"'", "'\""), ("' \"", "\" '")] {
+                out.push(format!("{pad}{open}{path}{close}{pad}"));
+            }
+        }
+        out
+    }
+
+    fn sample_paths() -> Vec<&'static str> {
+        if cfg!(windows) {
+            vec![r"C:\apks\app.apk", r"D:\out dir\", r"\\server\share\app.apk", r"C:\带 空格\文件.apk"]
+        } else {
+            vec!["/tmp/app.apk", "/tmp/out dir/", "relative/app.apk", "/data/带 空格/文件.apk", "/tmp/it's.apk"]
+        }
+    }
+
+    #[test]
+    fn quotes_and_whitespace_never_change_the_result() {
+        for path in sample_paths() {
+            let expected = normalize(path).unwrap();
+            for input in wrapped(path) {
+                assert_eq!(normalize(&input).unwrap(), expected, "input {:?}", input);
+            }
+        }
+    }
+
+    #[test]
+    fn normalizing_is_idempotent() {
+        for path in sample_paths() {
+            for input in wrapped(path) {
+                let once = normalize(&input).unwrap();
+                assert_eq!(normalize(&once).unwrap(), once, "input {:?}", input);
+            }
+        }
+    }
+
+    #[test]
+    fn unbalanced_quotes_are_kept() {
+        assert_eq!(strip_quotes("\"/tmp/a.apk"), "\"/tmp/a.apk");
+        assert_eq!(strip_quotes("'/tmp/a.apk\""), "'/tmp/a.apk\"");
+        assert_eq!(strip_quotes("\""), "\"");
+    }
+
+    #[test]
+    fn empty_and_quote_only_inputs_are_rejected() {
+        for input in ["", "   ", "\"\"", "' '", "\"''\""] {
+            assert!(matches!(normalize(input), Err(PipelineError::InvalidPath { .. })), "input {:?}", input);
+        }
+    }
+
+    #[test]
+    fn control_characters_are_rejected() {
+        for input in ["/tmp/a\u{0}b.apk", "/tmp/a\u{7}.apk", "/tmp/a\nb.apk", "file:///tmp/a%0Ab.apk"] {
+            match normalize(input) {
+                Err(PipelineError::InvalidPath { raw, normalized, .. }) => {
+                    assert_eq!(raw, input);
+                    assert!(normalized.is_some_and(|n| !n.chars().any(char::is_control)));
+                }
+                other => panic!("{:?} -> {:?}", input, other),
+            }
+        }
+    }
+
+    #[test]
+    fn file_uris_decode_to_local_paths() {
+        let cases = [
+            ("file:///tmp/app.apk", "/tmp/app.apk"),
+            ("FILE:///tmp/app.apk", "/tmp/app.apk"),
+            ("file://localhost/tmp/app.apk", "/tmp/app.apk"),
+            ("file:///tmp/my%20app.apk", "/tmp/my app.apk"),
+            ("file:///tmp/%E4%B8%AD.apk", "/tmp/中.apk"),
+            ("file:///C:/apks/app.apk", "C:/apks/app.apk"),
+            ("file://C:/apks/app.apk", "C:/apks/app.apk"),
+        ];
+        for (input, decoded) in cases {
+            let expected = normalize(decoded).unwrap();
+            for wrapped in wrapped(input) {
+                assert_eq!(normalize(&wrapped).unwrap(), expected, "input {:?}", wrapped);
+            }
+        }
+    }
+
+    #[test]
+    fn bad_percent_escapes_are_rejected() {
+        for input in ["file:///tmp/%zz.apk", "file:///tmp/%2", "file:///tmp/%FF.apk"] {
+            assert!(normalize(input).is_err(), "input {:?}", input);
+        }
+    }
+
+    #[test]
+    fn remote_file_uris_become_unc_on_windows_only() {
+        let result = normalize("file://server/share/app.apk");
+        if cfg!(windows) {
+            assert_eq!(result.unwrap(), r"\\server\share\app.apk");
+        } else {
+            assert!(matches!(result, Err(PipelineError::InvalidPath { normalized: Some(n), .. }) if n == "server/share/app.apk"));
+        }
+    }
+
+    #[test]
+    fn unc_and_device_paths_are_preserved() {
+        for path in [r"\\server\share\app.apk", r"\\?\C:\very\long\app.apk", r"\\.\pipe\name"] {
+            assert_eq!(normalize(path).unwrap(), path);
+            assert_eq!(normalize(&format!("\"{}\"", path)).unwrap(), path);
+        }
+        if cfg!(windows) {
+            assert_eq!(normalize("//server/share/app.apk").unwrap(), r"\\server\share\app.apk");
+            assert_eq!(normalize("//?/C:/x").unwrap(), "//?/C:/x");
+        }
+    }
+
+    #[test]
+    fn trailing_separators_are_kept() {
+        for path in sample_paths().into_iter().filter(|p| p.ends_with(['/', '\\'])) {
+            let normalized = normalize(path).unwrap();
+            assert!(normalized.ends_with(std::path::MAIN_SEPARATOR), "{:?}", normalized);
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let with_slash = format!("{}{}", dir.path().display(), std::path::MAIN_SEPARATOR);
+        assert_eq!(check(&with_slash, Expect::Dir).unwrap(), PathBuf::from(&with_slash));
+        assert!(check(&format!("{}/", dir.path().join("missing").display()), Expect::Dir).is_err());
+    }
+
+    #[test]
+    fn home_is_expanded_only_as_a_prefix() {
+        if cfg!(windows) {
+            return;
+        }
+        let Some(home) = std::env::var("HOME").ok().filter(|h| !h.is_empty()) else {
+            return;
+        };
+        let home = home.trim_end_matches('/');
+        assert_eq!(normalize("~").unwrap(), std::env::var("HOME").unwrap());
+        assert_eq!(normalize("~/apks/app.apk").unwrap(), format!("{}/apks/app.apk", home));
+        assert_eq!(normalize("'~/apks/app.apk'").unwrap(), format!("{}/apks/app.apk", home));
+        assert_eq!(normalize("~other/app.apk").unwrap(), "~other/app.apk");
+        assert_eq!(normalize("/tmp/~/app.apk").unwrap(), "/tmp/~/app.apk");
+    }
+
+    #[test]
+    fn wsl_mounts_map_to_drive_letters() {
+        assert_eq!(from_wsl_mount("/mnt/c/Users/a.apk").as_deref(), Some("C:/Users/a.apk"));
+        assert_eq!(from_wsl_mount("/mnt/d").as_deref(), Some("D:/"));
+        assert_eq!(from_wsl_mount("/mnt/data/a.apk"), None);
+        assert_eq!(from_wsl_mount("/tmp/mnt/c"), None);
+    }
+
+    #[test]
+    fn check_reports_the_expected_kind() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("app.apk");
+        std::fs::write(&file, b"PK").unwrap();
+        let file = file.to_string_lossy().to_string();
+        let dir_path = dir.path().to_string_lossy().to_string();
+
+        assert_eq!(arg(&format!("\"{}\"", file), Expect::File).unwrap(), file);
+        assert!(arg(&dir_path, Expect::File).is_err());
+        assert!(arg(&file, Expect::Dir).is_err());
+        assert_eq!(arg(&dir_path, Expect::Dir).unwrap(), dir_path);
+        assert!(arg(&format!("{}.missing", file), Expect::Any).is_ok());
+    }
+
+    #[test]
+    fn tool_names_are_looked_up_on_path() {
+        assert_eq!(tool_arg(" \"apksigner\" ").unwrap(), "apksigner");
+        assert!(tool_arg("/nonexistent/bin/apksigner").is_err());
+    }
+}
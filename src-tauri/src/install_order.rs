@@ -0,0 +1,238 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::Path;
+use std::process::Command;
+
+use crate::input_path;
+use crate::signing::PipelineError;
+use crate::{adb, axml};
+
+/// 安装排序所需的 Manifest 信息
+#[derive(Debug, Clone, Default)]
+pub struct ApkDeclarations {
+    pub package: String,
+    pub shared_user_id: Option<String>,
+    /// 提供的共享库 / 静态库名
+    pub provided_libraries: Vec<String>,
+    /// `<uses-library>` / `<uses-static-library>` 中 required 不为 false 的库名
+    pub required_libraries: Vec<String>,
+}
+
+fn from_axml(apk: &Path) -> Result<ApkDeclarations, String> {
+    let elements = axml::read_manifest_from_apk(apk)?;
+    let mut decl = ApkDeclarations::default();
+    for element in &elements {
+        let name = element.attr("name").map(axml::AxmlValue::as_string);
+        match element.name.as_str() {
+            "manifest" => {
+                decl.package = element.attr("package").map(axml::AxmlValue::as_string).unwrap_or_default();
+                decl.shared_user_id = element.attr("sharedUserId").map(axml::AxmlValue::as_string);
+            }
+            "library" | "static-library" => decl.provided_libraries.extend(name),
+            "uses-library" | "uses-static-library" => {
+                let optional = element.attr("required").is_some_and(|v| matches!(v, axml::AxmlValue::Bool(false)));
+                if !optional {
+                    decl.required_libraries.extend(name);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(decl)
+}
+
+/// Manifest 无法直接解析时（如加固处理过）退回 `aapt2 dump badging`，该输出不含 sharedUserId
+fn from_aapt2(aapt2_path: &str, apk: &Path) -> Result<ApkDeclarations, String> {
+    let output = Command::new(aapt2_path)
+        .args(["dump", "badging"])
+        .arg(apk)
+        .output()
+        .map_err(|e| format!("aapt2 执行失败: {}", e))?;
+    let decl = parse_badging(&String::from_utf8_lossy(&output.stdout));
+    if decl.package.is_empty() {
+        return Err(format!("aapt2 无法读取 {} 的包名", apk.display()));
+    }
+    Ok(decl)
+}
+
+/// 从 `aapt2 dump badging` 的输出中读取包名与使用的库
+fn parse_badging(stdout: &str) -> ApkDeclarations {
+    let quoted = regex::Regex::new(r"'([^']*)'").unwrap();
+    let package_name = regex::Regex::new(r"name='([^']*)'").unwrap();
+    let mut decl = ApkDeclarations::default();
+    for line in stdout.lines() {
+        let value = quoted.captures(line).map(|c| c[1].to_string());
+        if line.starts_with("package:") {
+            decl.package = package_name.captures(line).map(|c| c[1].to_string()).unwrap_or_default();
+        } else if line.starts_with("uses-library:") {
+            decl.required_libraries.extend(value);
+        }
+    }
+    decl
+}
+
+/// 读取 APK 中与安装顺序相关的声明
+pub fn read_declarations(apk: &Path, aapt2_path: &str) -> Result<ApkDeclarations, PipelineError> {
+    from_axml(apk).or_else(|axml_error| {
+        from_aapt2(aapt2_path, apk).map_err(|e| PipelineError::InvalidInput(format!("{}: {}; {}", apk.display(), axml_error, e)))
+    })
+}
+
+/// 计算依赖边：`deps[i]` 为第 i 个 APK 之前必须安装的 APK。
+/// 使用的库由同批中提供该库（或包名与之相同）的 APK 满足；sharedUserId 等于同批另一个包名时，该包先装
+pub fn dependencies(decls: &[ApkDeclarations]) -> Vec<BTreeSet<usize>> {
+    let mut providers: HashMap<&str, usize> = HashMap::new();
+    for (i, decl) in decls.iter().enumerate() {
+        providers.insert(decl.package.as_str(), i);
+        for lib in &decl.provided_libraries {
+            providers.insert(lib.as_str(), i);
+        }
+    }
+    decls
+        .iter()
+        .enumerate()
+        .map(|(i, decl)| {
+            decl.required_libraries
+                .iter()
+                .map(String::as_str)
+                .chain(decl.shared_user_id.as_deref())
+                .filter_map(|name| providers.get(name).copied())
+                .filter(|dep| *dep != i)
+                .collect()
+        })
+        .collect()
+}
+
+/// 稳定的拓扑排序：无依赖关系的 APK 保持输入顺序。存在环时返回环上的包名
+pub fn topological_order(decls: &[ApkDeclarations]) -> Result<Vec<usize>, PipelineError> {
+    let deps = dependencies(decls);
+    let mut placed = vec![false; decls.len()];
+    let mut order = Vec::with_capacity(decls.len());
+    while order.len() < decls.len() {
+        let next = (0..decls.len()).find(|i| !placed[*i] && deps[*i].iter().all(|d| placed[*d]));
+        match next {
+            Some(i) => {
+                placed[i] = true;
+                order.push(i);
+            }
+            None => return Err(PipelineError::DependencyCycle { packages: find_cycle(decls, &deps, &placed) }),
+        }
+    }
+    Ok(order)
+}
+
+/// 从未安排的节点出发沿依赖边走，第一次回到走过的节点即找到环
+fn find_cycle(decls: &[ApkDeclarations], deps: &[BTreeSet<usize>], placed: &[bool]) -> Vec<String> {
+    let Some(mut current) = (0..decls.len()).find(|i| !placed[*i]) else {
+        return Vec::new();
+    };
+    let mut path = Vec::new();
+    let mut seen = HashSet::new();
+    while seen.insert(current) {
+        path.push(current);
+        match deps[current].iter().find(|d| !placed[**d]) {
+            Some(next) => current = *next,
+            None => break,
+        }
+    }
+    let start = path.iter().position(|i| *i == current).unwrap_or(0);
+    path[start..].iter().chain(std::iter::once(&current)).map(|i| decls[*i].package.clone()).collect()
+}
+
+/// 按包之间的依赖（共享库、sharedUserId）计算安装顺序。
+/// 设备上已有的共享库视为已满足，同批与设备上都找不到的必需库会报错
+#[tauri::command]
+pub fn compute_install_order(device_id: String, apk_paths: Vec<String>, aapt2_path: String) -> Result<Vec<String>, PipelineError> {
+    let aapt2_path = input_path::tool_arg(&aapt2_path)?;
+    let apk_paths = apk_paths
+        .iter()
+        .map(|p| input_path::arg(p, input_path::Expect::File))
+        .collect::<Result<Vec<_>, _>>()?;
+    let decls = apk_paths
+        .iter()
+        .map(|p| read_declarations(Path::new(p), &aapt2_path))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let in_batch: HashSet<&str> = decls
+        .iter()
+        .flat_map(|d| std::iter::once(d.package.as_str()).chain(d.provided_libraries.iter().map(String::as_str)))
+        .collect();
+    let on_device: HashSet<String> = adb::shell(&device_id, &["pm", "list", "libraries"])
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("library:").map(str::to_string))
+        .collect();
+    for decl in &decls {
+        let missing: Vec<&str> = decl
+            .required_libraries
+            .iter()
+            .map(String::as_str)
+            .filter(|lib| !in_batch.contains(lib) && !on_device.contains(*lib))
+            .collect();
+        if !missing.is_empty() {
+            return Err(PipelineError::InvalidInput(format!(
+                "{} 依赖的库在本批 APK 与设备上都不存在: {}",
+                decl.package,
+                missing.join(", ")
+            )));
+        }
+    }
+
+    Ok(topological_order(&decls)?.into_iter().map(|i| apk_paths[i].clone()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decl(package: &str, required: &[&str], provided: &[&str], shared_user_id: Option<&str>) -> ApkDeclarations {
+        ApkDeclarations {
+            package: package.to_string(),
+            shared_user_id: shared_user_id.map(str::to_string),
+            provided_libraries: provided.iter().map(|s| s.to_string()).collect(),
+            required_libraries: required.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn dependency_is_installed_first() {
+        // A 使用 B 提供的库，B 必须先装；C 与两者无关，保持输入顺序
+        let decls = [
+            decl("com.example.a", &["com.example.lib"], &[], None),
+            decl("com.example.b", &[], &["com.example.lib"], None),
+            decl("com.example.c", &[], &[], None),
+        ];
+        assert_eq!(topological_order(&decls).unwrap(), [1, 0, 2]);
+    }
+
+    #[test]
+    fn shared_user_id_naming_a_batch_package_orders_it_first() {
+        let decls = [decl("com.example.plugin", &[], &[], Some("com.example.host")), decl("com.example.host", &[], &[], None)];
+        assert_eq!(topological_order(&decls).unwrap(), [1, 0]);
+    }
+
+    #[test]
+    fn cycle_is_reported_with_its_packages() {
+        let decls = [
+            decl("com.example.free", &[], &[], None),
+            decl("com.example.a", &["lib.b"], &["lib.a"], None),
+            decl("com.example.b", &["lib.a"], &["lib.b"], None),
+        ];
+        match topological_order(&decls) {
+            Err(PipelineError::DependencyCycle { packages }) => {
+                assert_eq!(packages, ["com.example.a", "com.example.b", "com.example.a"]);
+            }
+            other => panic!("应检测到循环依赖: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn badging_provides_package_and_libraries() {
+        let stdout = "package: name='com.example.app' versionCode='3' versionName='1.2'\n\
+sdkVersion:'21'\n\
+uses-library:'org.apache.http.legacy'\n\
+uses-library-not-required:'com.google.android.maps'\n";
+        let decl = parse_badging(stdout);
+        assert_eq!(decl.package, "com.example.app");
+        assert_eq!(decl.required_libraries, ["org.apache.http.legacy"]);
+    }
+}
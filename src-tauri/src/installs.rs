@@ -0,0 +1,249 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::input_path::{self, Expect};
+use crate::paths::AppPaths;
+use crate::{axml, hash};
+
+const INSTALLS_FILE: &str = "installs.jsonl";
+
+/// 设备上的一次安装或卸载
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct InstallRecord {
+    /// `install` 或 `uninstall`
+    pub kind: String,
+    pub device_serial: String,
+    pub device_alias: Option<String>,
+    pub package: String,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number | null"))]
+    pub version_code: Option<i64>,
+    /// 安装的 APK 的 SHA-256，卸载记录为空
+    pub apk_sha256: Option<String>,
+    /// Unix 时间戳（秒）
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub timestamp: u64,
+    pub operator_note: Option<String>,
+    /// 安装的 APK 是通过 `download_apk` 下载时的来源地址
+    #[serde(default)]
+    pub source_url: Option<String>,
+}
+
+#[derive(Default)]
+struct Store {
+    loaded: bool,
+    records: Vec<InstallRecord>,
+    by_device: HashMap<String, Vec<usize>>,
+    by_package: HashMap<String, Vec<usize>>,
+    by_sha256: HashMap<String, Vec<usize>>,
+}
+
+impl Store {
+    fn index(&mut self, record: InstallRecord) {
+        let idx = self.records.len();
+        self.by_device.entry(record.device_serial.clone()).or_default().push(idx);
+        self.by_package.entry(record.package.clone()).or_default().push(idx);
+        if let Some(sha) = &record.apk_sha256 {
+            self.by_sha256.entry(sha.clone()).or_default().push(idx);
+        }
+        self.records.push(record);
+    }
+
+    fn select(&self, indexes: Option<&Vec<usize>>) -> Vec<InstallRecord> {
+        indexes.map(|ids| ids.iter().map(|i| self.records[*i].clone()).collect()).unwrap_or_default()
+    }
+}
+
+/// 安装历史，首次查询时从 `installs.jsonl` 载入并按设备、包名、APK 哈希建立索引
+#[derive(Default)]
+pub struct InstallHistory {
+    store: Mutex<Store>,
+}
+
+impl InstallHistory {
+    fn with_store<T>(&self, paths: &AppPaths, f: impl FnOnce(&mut Store) -> T) -> T {
+        let mut store = self.store.lock().unwrap();
+        if !store.loaded {
+            store.loaded = true;
+            if let Ok(file) = fs::File::open(paths.data_dir.join(INSTALLS_FILE)) {
+                io::BufReader::new(file)
+                    .lines()
+                    .map_while(Result::ok)
+                    .filter_map(|line| serde_json::from_str(&line).ok())
+                    .for_each(|record| store.index(record));
+            }
+        }
+        f(&mut store)
+    }
+
+    /// 等待正在进行的追加写完（退出时使用）
+    pub fn sync(&self) {
+        drop(self.store.lock().unwrap());
+    }
+
+    /// 追加一条记录
+    pub fn append(&self, paths: &AppPaths, record: InstallRecord) -> Result<(), String> {
+        self.with_store(paths, |store| {
+            fs::create_dir_all(&paths.data_dir).map_err(|e| e.to_string())?;
+            let line = serde_json::to_string(&record).map_err(|e| e.to_string())?;
+            let mut file = fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(paths.data_dir.join(INSTALLS_FILE))
+                .map_err(|e| e.to_string())?;
+            writeln!(file, "{}", line).map_err(|e| e.to_string())?;
+            store.index(record);
+            Ok(())
+        })
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// 根据安装的 APK 生成安装记录
+pub fn install_record(device_serial: &str, package: &str, apk: &Path, operator_note: Option<String>) -> InstallRecord {
+    let version_code = axml::read_manifest_from_apk(apk)
+        .ok()
+        .and_then(|elements| elements.into_iter().find(|e| e.name == "manifest"))
+        .and_then(|manifest| manifest.attr("versionCode").and_then(axml::AxmlValue::as_int));
+    InstallRecord {
+        kind: "install".to_string(),
+        device_serial: device_serial.to_string(),
+        device_alias: None,
+        package: package.to_string(),
+        version_code,
+        apk_sha256: hash::sha256_file_cached(apk).ok(),
+        timestamp: now(),
+        operator_note,
+        source_url: None,
+    }
+}
+
+/// 卸载记录
+pub fn uninstall_record(device_serial: &str, package: &str) -> InstallRecord {
+    InstallRecord {
+        kind: "uninstall".to_string(),
+        device_serial: device_serial.to_string(),
+        device_alias: None,
+        package: package.to_string(),
+        version_code: None,
+        apk_sha256: None,
+        timestamp: now(),
+        operator_note: None,
+        source_url: None,
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// 将记录写成 CSV（带表头）
+pub fn write_csv(path: &Path, records: &[InstallRecord]) -> Result<(), String> {
+    let mut out = String::from("kind,device_serial,device_alias,package,version_code,apk_sha256,timestamp,operator_note,source_url\n");
+    for r in records {
+        let fields = [
+            r.kind.clone(),
+            r.device_serial.clone(),
+            r.device_alias.clone().unwrap_or_default(),
+            r.package.clone(),
+            r.version_code.map(|v| v.to_string()).unwrap_or_default(),
+            r.apk_sha256.clone().unwrap_or_default(),
+            r.timestamp.to_string(),
+            r.operator_note.clone().unwrap_or_default(),
+            r.source_url.clone().unwrap_or_default(),
+        ];
+        let line: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        out.push_str(&line.join(","));
+        out.push('\n');
+    }
+    fs::write(path, out).map_err(|e| e.to_string())
+}
+
+/// 某台设备上的全部安装 / 卸载记录（按时间顺序）
+#[tauri::command]
+pub fn get_device_install_history(
+    paths: tauri::State<'_, AppPaths>,
+    history: tauri::State<'_, InstallHistory>,
+    device_id: String,
+) -> Vec<InstallRecord> {
+    history.with_store(&paths, |store| store.select(store.by_device.get(&device_id)))
+}
+
+/// 某个包在所有设备上的安装 / 卸载记录（按时间顺序）
+#[tauri::command]
+pub fn get_package_install_history(
+    paths: tauri::State<'_, AppPaths>,
+    history: tauri::State<'_, InstallHistory>,
+    package: String,
+) -> Vec<InstallRecord> {
+    history.with_store(&paths, |store| store.select(store.by_package.get(&package)))
+}
+
+/// 某个输出 APK 被安装到过的所有设备（每台设备取最近一次安装）
+#[tauri::command]
+pub fn where_is(
+    paths: tauri::State<'_, AppPaths>,
+    history: tauri::State<'_, InstallHistory>,
+    apk_sha256: String,
+) -> Vec<InstallRecord> {
+    history.with_store(&paths, |store| {
+        let mut latest: HashMap<String, InstallRecord> = HashMap::new();
+        for record in store.select(store.by_sha256.get(&apk_sha256.to_lowercase())) {
+            latest.insert(record.device_serial.clone(), record);
+        }
+        let mut records: Vec<InstallRecord> = latest.into_values().collect();
+        records.sort_by_key(|r| r.timestamp);
+        records
+    })
+}
+
+/// 由本工具安装且之后没有通过本工具卸载的包
+#[tauri::command]
+pub fn get_installed_by_us(
+    paths: tauri::State<'_, AppPaths>,
+    history: tauri::State<'_, InstallHistory>,
+    device_id: String,
+) -> Vec<InstallRecord> {
+    history.with_store(&paths, |store| {
+        let mut current: HashMap<String, InstallRecord> = HashMap::new();
+        for record in store.select(store.by_device.get(&device_id)) {
+            if record.kind == "uninstall" {
+                current.remove(&record.package);
+            } else {
+                current.insert(record.package.clone(), record);
+            }
+        }
+        let mut records: Vec<InstallRecord> = current.into_values().collect();
+        records.sort_by(|a, b| a.package.cmp(&b.package));
+        records
+    })
+}
+
+/// 导出安装历史为 CSV，可按设备过滤
+#[tauri::command]
+pub fn export_install_history_csv(
+    paths: tauri::State<'_, AppPaths>,
+    history: tauri::State<'_, InstallHistory>,
+    output_path: String,
+    device_id: Option<String>,
+) -> Result<usize, String> {
+    let output_path = input_path::arg(&output_path, Expect::Any).map_err(|e| e.to_string())?;
+    let records = history.with_store(&paths, |store| match &device_id {
+        Some(device) => store.select(store.by_device.get(device)),
+        None => store.records.clone(),
+    });
+    write_csv(Path::new(&output_path), &records)?;
+    Ok(records.len())
+}
@@ -0,0 +1,318 @@
+//! 繁重文件操作（复制、整文件哈希、ZIP 重写）的专用线程池。
+//! 命令处理函数把这些操作提交到这里并等待返回的 future，不再占用 Tauri 的异步运行时线程，
+//! 设备列表、设置等其他调用不会因此卡顿。界面上直接等待结果的操作以 `Interactive` 优先级插到后台任务之前。
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+use std::time::Instant;
+
+use crate::paths::{self, AppPaths};
+
+const SETTINGS_FILE: &str = "io_pool.json";
+const MAX_WORKERS: u32 = 16;
+
+/// 任务优先级
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Priority {
+    /// 界面正在等待结果（如单个文件的哈希）
+    Interactive,
+    /// 后台任务（导出、缓存预热等）
+    Background,
+}
+
+/// IO 线程池设置，修改后下次启动生效
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct IoPoolSettings {
+    /// 同时执行的 IO 任务数上限，默认为 CPU 核数（最多 4）
+    pub workers: Option<u32>,
+}
+
+/// 线程池的运行状态
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct RuntimeStatus {
+    pub io_workers: u32,
+    pub queued_interactive: u32,
+    pub queued_background: u32,
+    pub running: u32,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub completed: u64,
+    /// 已开始执行的任务在队列中的平均 / 最长等待时间
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub avg_wait_ms: u64,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub max_wait_ms: u64,
+    /// 正在执行的操作名
+    pub running_operations: Vec<String>,
+}
+
+type Job = Box<dyn FnOnce() + Send>;
+
+struct Queued {
+    operation: &'static str,
+    enqueued: Instant,
+    job: Job,
+}
+
+#[derive(Default)]
+struct Queues {
+    interactive: VecDeque<Queued>,
+    background: VecDeque<Queued>,
+    running: Vec<&'static str>,
+}
+
+#[derive(Default)]
+struct Inner {
+    queues: Mutex<Queues>,
+    available: Condvar,
+    started: AtomicU64,
+    completed: AtomicU64,
+    total_wait_ms: AtomicU64,
+    max_wait_ms: AtomicU64,
+}
+
+/// 专用 IO 线程池（托管状态）
+pub struct IoPool {
+    inner: Arc<Inner>,
+    workers: u32,
+}
+
+fn default_workers() -> u32 {
+    thread::available_parallelism().map(|n| n.get() as u32).unwrap_or(2).min(4)
+}
+
+pub fn load_settings(paths: &AppPaths) -> IoPoolSettings {
+    fs::read_to_string(paths.config_dir.join(SETTINGS_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+impl IoPool {
+    /// 按设置启动工作线程
+    pub fn start(paths: &AppPaths) -> Self {
+        Self::with_workers(load_settings(paths).workers.unwrap_or_else(default_workers))
+    }
+
+    fn with_workers(workers: u32) -> Self {
+        let workers = workers.clamp(1, MAX_WORKERS);
+        let inner = Arc::new(Inner::default());
+        for i in 0..workers {
+            let inner = inner.clone();
+            let _ = thread::Builder::new().name(format!("io-pool-{}", i)).spawn(move || worker(&inner));
+        }
+        IoPool { inner, workers }
+    }
+
+    /// 提交一个阻塞操作，返回等待其结果的 future。操作 panic 时返回错误
+    pub fn run<T, F>(&self, priority: Priority, operation: &'static str, f: F) -> IoTask<T>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let slot = Arc::new(Mutex::new(Slot { value: None, finished: false, waker: None }));
+        let result = slot.clone();
+        let job: Job = Box::new(move || {
+            let value = panic::catch_unwind(AssertUnwindSafe(f)).ok();
+            let mut slot = result.lock().unwrap();
+            slot.value = value;
+            slot.finished = true;
+            if let Some(waker) = slot.waker.take() {
+                waker.wake();
+            }
+        });
+        let queued = Queued { operation, enqueued: Instant::now(), job };
+        let mut queues = self.inner.queues.lock().unwrap();
+        match priority {
+            Priority::Interactive => queues.interactive.push_back(queued),
+            Priority::Background => queues.background.push_back(queued),
+        }
+        drop(queues);
+        self.inner.available.notify_one();
+        IoTask { slot, operation }
+    }
+
+    pub fn status(&self) -> RuntimeStatus {
+        let queues = self.inner.queues.lock().unwrap();
+        let started = self.inner.started.load(Ordering::Relaxed);
+        RuntimeStatus {
+            io_workers: self.workers,
+            queued_interactive: queues.interactive.len() as u32,
+            queued_background: queues.background.len() as u32,
+            running: queues.running.len() as u32,
+            completed: self.inner.completed.load(Ordering::Relaxed),
+            avg_wait_ms: self.inner.total_wait_ms.load(Ordering::Relaxed).checked_div(started).unwrap_or(0),
+            max_wait_ms: self.inner.max_wait_ms.load(Ordering::Relaxed),
+            running_operations: queues.running.iter().map(ToString::to_string).collect(),
+        }
+    }
+}
+
+fn worker(inner: &Inner) {
+    loop {
+        let queued = {
+            let mut queues = inner.queues.lock().unwrap();
+            loop {
+                if let Some(queued) = queues.interactive.pop_front().or_else(|| queues.background.pop_front()) {
+                    queues.running.push(queued.operation);
+                    break queued;
+                }
+                queues = inner.available.wait(queues).unwrap();
+            }
+        };
+        let waited = queued.enqueued.elapsed().as_millis() as u64;
+        inner.started.fetch_add(1, Ordering::Relaxed);
+        inner.total_wait_ms.fetch_add(waited, Ordering::Relaxed);
+        inner.max_wait_ms.fetch_max(waited, Ordering::Relaxed);
+
+        (queued.job)();
+
+        let mut queues = inner.queues.lock().unwrap();
+        if let Some(pos) = queues.running.iter().position(|op| *op == queued.operation) {
+            queues.running.swap_remove(pos);
+        }
+        inner.completed.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+struct Slot<T> {
+    value: Option<T>,
+    finished: bool,
+    waker: Option<Waker>,
+}
+
+/// 提交到线程池的操作的结果
+pub struct IoTask<T> {
+    slot: Arc<Mutex<Slot<T>>>,
+    operation: &'static str,
+}
+
+impl<T> Future for IoTask<T> {
+    type Output = Result<T, String>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut slot = self.slot.lock().unwrap();
+        if let Some(value) = slot.value.take() {
+            return Poll::Ready(Ok(value));
+        }
+        if slot.finished {
+            return Poll::Ready(Err(format!("{} 执行时异常退出", self.operation)));
+        }
+        slot.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+/// IO 线程池的队列长度、等待时间与正在执行的操作
+#[tauri::command]
+pub fn get_runtime_status(pool: tauri::State<'_, IoPool>) -> RuntimeStatus {
+    pool.status()
+}
+
+/// 获取 IO 线程池设置
+#[tauri::command]
+pub fn get_io_pool_settings(paths: tauri::State<'_, AppPaths>) -> IoPoolSettings {
+    load_settings(&paths)
+}
+
+/// 修改 IO 线程池设置，下次启动生效
+#[tauri::command]
+pub fn set_io_pool_settings(app: tauri::AppHandle, paths: tauri::State<'_, AppPaths>, settings: IoPoolSettings) -> Result<(), String> {
+    let result = match settings.workers {
+        Some(workers) if !(1..=MAX_WORKERS).contains(&workers) => Err(format!("线程数应在 1 到 {} 之间", MAX_WORKERS)),
+        _ => serde_json::to_vec_pretty(&settings)
+            .map_err(|e| e.to_string())
+            .and_then(|content| paths::write_atomic(&paths.config_dir.join(SETTINGS_FILE), &content).map_err(|e| e.to_string())),
+    };
+    crate::audit::record(&app, "set_io_pool_settings", serde_json::to_value(&settings).unwrap_or_default(), &result);
+    result
+}
+
+/// 计算文件的 SHA-256，界面等待结果，优先执行
+#[tauri::command]
+pub async fn hash_file(pool: tauri::State<'_, IoPool>, path: String) -> Result<String, String> {
+    let path = crate::input_path::check(&path, crate::input_path::Expect::File).map_err(|e| e.to_string())?;
+    pool.run(Priority::Interactive, "hash_file", move || crate::hash::sha256_file_cached(&path)).await?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{self, Write};
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    #[test]
+    fn interactive_work_jumps_ahead_of_queued_background_work() {
+        let pool = IoPool::with_workers(1);
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let (release, blocked) = mpsc::channel::<()>();
+        let busy = pool.run(Priority::Background, "busy", move || blocked.recv().unwrap());
+        while pool.status().running == 0 {
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        let mut tasks = Vec::new();
+        for name in ["background-1", "background-2", "interactive", "background-3"] {
+            let priority = if name == "interactive" { Priority::Interactive } else { Priority::Background };
+            let order = order.clone();
+            tasks.push(pool.run(priority, name, move || order.lock().unwrap().push(name)));
+        }
+        let status = pool.status();
+        assert_eq!((status.queued_interactive, status.queued_background), (1, 3));
+
+        release.send(()).unwrap();
+        tauri::async_runtime::block_on(async {
+            busy.await.unwrap();
+            for task in tasks {
+                task.await.unwrap();
+            }
+        });
+        assert_eq!(*order.lock().unwrap(), ["interactive", "background-1", "background-2", "background-3"]);
+        assert_eq!(pool.status().completed, 5);
+    }
+
+    #[test]
+    fn large_background_copy_does_not_delay_interactive_calls() {
+        let pool = IoPool::with_workers(2);
+        // 模拟 2 GB 的复制：按 1 MB 分块写入，不实际占用磁盘
+        let copy = pool.run(Priority::Background, "copy_2gb", || {
+            let chunk = vec![0u8; 1 << 20];
+            let mut sink = io::sink();
+            for _ in 0..2048 {
+                sink.write_all(&chunk)?;
+                thread::sleep(Duration::from_micros(100));
+            }
+            io::Result::Ok(2048 * chunk.len() as u64)
+        });
+        while pool.status().running == 0 {
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        // 相当于 get_devices：界面等待的小操作
+        let started = Instant::now();
+        let devices = tauri::async_runtime::block_on(pool.run(Priority::Interactive, "get_devices", || vec!["emulator-5554"])).unwrap();
+        let elapsed = started.elapsed();
+        assert_eq!(devices, ["emulator-5554"]);
+        assert!(elapsed < Duration::from_millis(50), "交互操作等待了 {:?}", elapsed);
+        assert_eq!(pool.status().running_operations, ["copy_2gb"]);
+
+        assert_eq!(tauri::async_runtime::block_on(copy).unwrap().unwrap(), 2 << 30);
+    }
+
+    #[test]
+    fn panicking_operation_reports_an_error() {
+        let pool = IoPool::with_workers(1);
+        let result = tauri::async_runtime::block_on(pool.run(Priority::Interactive, "explode", || -> u32 { panic!("boom") }));
+        assert_eq!(result.unwrap_err(), "explode 执行时异常退出");
+    }
+}
@@ -0,0 +1,561 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tauri::Emitter;
+
+use crate::hash;
+use crate::paths::{self, AppPaths};
+use crate::pipeline::ProcessConfig;
+use crate::run_log::RunLog;
+use crate::ProcessResult;
+
+/// 参与重复判断的最近完成任务数
+const RECENT_LIMIT: usize = 20;
+
+/// 不影响输出结果、不参与任务指纹的字段
+const FINGERPRINT_IGNORED: &[&str] = &["hydrate_source", "log_queue_depth", "min_output_ratio"];
+
+/// 任务的取消标记，由 `Recorder` 在执行外部命令时检查
+pub type CancelToken = Arc<AtomicBool>;
+
+/// 执行任务时传给流程的上下文
+#[derive(Clone, Default)]
+pub struct JobContext {
+    pub cancel: Option<CancelToken>,
+    /// 工具输出的运行日志
+    pub log: Option<Arc<RunLog>>,
+}
+
+impl JobContext {
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.as_ref().is_some_and(|c| c.load(Ordering::Relaxed))
+    }
+}
+
+/// 任务状态
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+    /// 与已有任务重复，未执行
+    Duplicate,
+    /// 执行中应用退出，结果未知
+    Interrupted,
+}
+
+/// 推送给前端的任务状态事件（`job-status`）；批处理的子任务带有 `parent_job_id`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct JobEvent {
+    pub job_id: String,
+    pub parent_job_id: Option<String>,
+    pub status: JobStatus,
+    pub message: Option<String>,
+}
+
+pub fn emit_status(app: &tauri::AppHandle, job_id: &str, parent_job_id: Option<&str>, status: JobStatus, message: Option<String>) {
+    let event = JobEvent { job_id: job_id.to_string(), parent_job_id: parent_job_id.map(str::to_string), status, message };
+    let _ = app.emit("job-status", event);
+}
+
+/// 处理结果的状态，前端据此选择图标，不再解析消息文本
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "snake_case")]
+pub enum ResultStatus {
+    Success,
+    /// 成功，但工具警告超过阈值
+    SuccessWithWarnings,
+    #[default]
+    Failed,
+    Cancelled,
+    /// 新版本启动失败，已恢复安装前的版本
+    RolledBack,
+    /// 未执行：与已有任务重复，或等待确认
+    Skipped,
+}
+
+impl ResultStatus {
+    pub fn of(result: &ProcessResult) -> Self {
+        if result.duplicate_of.is_some() || result.confirmation_token.is_some() {
+            return ResultStatus::Skipped;
+        }
+        match result.step.as_deref() {
+            _ if result.success && result.success_with_warnings => ResultStatus::SuccessWithWarnings,
+            _ if result.success => ResultStatus::Success,
+            Some("cancelled") => ResultStatus::Cancelled,
+            Some("rolled_back") => ResultStatus::RolledBack,
+            Some("duplicate") => ResultStatus::Skipped,
+            _ => ResultStatus::Failed,
+        }
+    }
+}
+
+/// 消息中的状态装饰（表情符号），由前端按 `status` 呈现
+const MESSAGE_DECORATIONS: &[char] = &['✅', '❌', '⚠', '\u{fe0f}'];
+
+/// 兼容旧版前端：消息保留状态表情；关闭后只输出纯文本。下一个版本移除
+static LEGACY_MESSAGES: AtomicBool = AtomicBool::new(true);
+
+const RESULT_SETTINGS_FILE: &str = "result_settings.json";
+
+/// 处理结果的输出设置
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct ResultSettings {
+    /// 消息中保留 ✅ / ⚠️ 等状态装饰，默认开启
+    pub legacy_messages: bool,
+}
+
+impl Default for ResultSettings {
+    fn default() -> Self {
+        ResultSettings { legacy_messages: true }
+    }
+}
+
+/// 去掉每行开头的状态装饰
+pub fn plain_message(message: &str) -> String {
+    message
+        .lines()
+        .map(|line| line.trim_start_matches(|c: char| MESSAGE_DECORATIONS.contains(&c)).trim_start())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// 返回给前端之前补全状态与输出大小；关闭兼容消息时去掉消息中的状态装饰
+pub fn finalize(mut result: ProcessResult) -> ProcessResult {
+    result.status = ResultStatus::of(&result);
+    if result.success && result.output_size_bytes.is_none() {
+        result.output_size_bytes = result.output_path.as_deref().and_then(|p| fs::metadata(p).ok()).map(|m| m.len());
+    }
+    if !LEGACY_MESSAGES.load(Ordering::Relaxed) {
+        result.message = plain_message(&result.message);
+    }
+    result
+}
+
+/// 启动时载入处理结果的输出设置
+pub fn load_result_settings(paths: &AppPaths) -> ResultSettings {
+    let settings: ResultSettings = fs::read_to_string(paths.config_dir.join(RESULT_SETTINGS_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    LEGACY_MESSAGES.store(settings.legacy_messages, Ordering::Relaxed);
+    settings
+}
+
+/// 获取处理结果的输出设置
+#[tauri::command]
+pub fn get_result_settings() -> ResultSettings {
+    ResultSettings { legacy_messages: LEGACY_MESSAGES.load(Ordering::Relaxed) }
+}
+
+/// 修改处理结果的输出设置并持久化
+#[tauri::command]
+pub fn set_result_settings(app: tauri::AppHandle, paths: tauri::State<'_, AppPaths>, settings: ResultSettings) -> Result<(), String> {
+    let result = serde_json::to_vec_pretty(&settings)
+        .map_err(|e| e.to_string())
+        .and_then(|content| paths::write_atomic(&paths.config_dir.join(RESULT_SETTINGS_FILE), &content).map_err(|e| e.to_string()));
+    if result.is_ok() {
+        LEGACY_MESSAGES.store(settings.legacy_messages, Ordering::Relaxed);
+    }
+    crate::audit::record(&app, "set_result_settings", serde_json::to_value(&settings).unwrap_or_default(), &result);
+    result
+}
+
+/// 处理结果对应的任务状态
+pub fn status_of(result: &ProcessResult) -> JobStatus {
+    match result.step.as_deref() {
+        _ if result.success => JobStatus::Succeeded,
+        Some("cancelled") => JobStatus::Cancelled,
+        Some("duplicate") => JobStatus::Duplicate,
+        _ => JobStatus::Failed,
+    }
+}
+
+/// 被取消的任务的结果
+pub fn cancelled_result() -> ProcessResult {
+    ProcessResult {
+        success: false,
+        message: "任务已取消，已清理工作目录".to_string(),
+        step: Some("cancelled".to_string()),
+        ..Default::default()
+    }
+}
+
+/// 根据源 APK 的哈希与规范化后的处理参数计算任务指纹
+pub fn fingerprint(config: &ProcessConfig) -> Result<String, String> {
+    let apk_hash = hash::sha256_file_cached(Path::new(&config.apk_path))?;
+    let mut value = serde_json::to_value(config).map_err(|e| e.to_string())?;
+    if let Some(map) = value.as_object_mut() {
+        map.remove("apk_path");
+        for key in FINGERPRINT_IGNORED {
+            map.remove(*key);
+        }
+    }
+    // serde_json 的 Map 按键排序，序列化结果即为规范形式
+    Ok(hash::sha256_str(&format!("{}:{}", apk_hash, value)))
+}
+
+/// 任务列表中的一项
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct JobSummary {
+    pub job_id: String,
+    pub parent_job_id: Option<String>,
+    pub status: JobStatus,
+    /// 进行中任务的源 APK
+    pub apk_path: Option<String>,
+    pub message: Option<String>,
+}
+
+/// 新任务登记的结果
+pub enum Admission {
+    Started(String),
+    Duplicate(Box<ProcessResult>),
+}
+
+#[derive(Default)]
+struct JobState {
+    running: HashMap<String, String>,
+    completed: VecDeque<(String, String, ProcessResult)>,
+    /// 进行中任务（含批处理父任务）的取消标记
+    tokens: HashMap<String, CancelToken>,
+    /// 批处理父任务 -> 子任务
+    children: HashMap<String, Vec<String>>,
+    /// 进行中任务 -> 源 APK 路径
+    inputs: HashMap<String, String>,
+}
+
+/// 正在进行与最近完成的任务，用于拒绝重复提交与取消
+#[derive(Default)]
+pub struct JobRegistry {
+    state: Mutex<JobState>,
+    counter: AtomicU64,
+}
+
+impl JobRegistry {
+    fn next_id(&self, kind: &str) -> String {
+        let ts = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis()).unwrap_or(0);
+        format!("{}-{}-{}", kind, ts, self.counter.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// 登记批处理父任务，取消它会取消当前子任务并跳过剩余子任务
+    pub fn start_batch(&self) -> (String, CancelToken) {
+        let id = self.next_id("batch");
+        let token = CancelToken::default();
+        let mut state = self.state.lock().unwrap();
+        state.tokens.insert(id.clone(), token.clone());
+        state.children.insert(id.clone(), Vec::new());
+        (id, token)
+    }
+
+    pub fn finish_batch(&self, id: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.tokens.remove(id);
+        state.children.remove(id);
+    }
+
+    /// 进行中任务使用的源 APK
+    pub fn active_inputs(&self) -> Vec<String> {
+        self.state.lock().unwrap().inputs.values().cloned().collect()
+    }
+
+    /// 进行中的任务与批处理，以及最近成功完成的任务
+    pub fn list(&self) -> Vec<JobSummary> {
+        let state = self.state.lock().unwrap();
+        let parent_of = |id: &str| state.children.iter().find(|(_, children)| children.iter().any(|c| c == id)).map(|(p, _)| p.clone());
+        let batches = state.children.keys().map(|id| JobSummary {
+            job_id: id.clone(),
+            parent_job_id: None,
+            status: JobStatus::Running,
+            apk_path: None,
+            message: None,
+        });
+        let running = state.running.values().map(|id| JobSummary {
+            job_id: id.clone(),
+            parent_job_id: parent_of(id),
+            status: JobStatus::Running,
+            apk_path: state.inputs.get(id).cloned(),
+            message: None,
+        });
+        let completed = state.completed.iter().map(|(_, id, result)| JobSummary {
+            job_id: id.clone(),
+            parent_job_id: None,
+            status: status_of(result),
+            apk_path: None,
+            message: Some(result.message.clone()),
+        });
+        let mut jobs: Vec<JobSummary> = batches.chain(running).chain(completed).collect();
+        jobs.sort_by(|a, b| a.job_id.cmp(&b.job_id));
+        jobs
+    }
+
+    /// 进行中任务的取消标记
+    pub fn cancel_token(&self, id: &str) -> Option<CancelToken> {
+        self.state.lock().unwrap().tokens.get(id).cloned()
+    }
+
+    /// 取消任务；取消父任务时同时取消其所有进行中的子任务。任务不存在或已结束时返回 false
+    pub fn cancel(&self, id: &str) -> bool {
+        let state = self.state.lock().unwrap();
+        let Some(token) = state.tokens.get(id) else {
+            return false;
+        };
+        token.store(true, Ordering::Relaxed);
+        for child in state.children.get(id).into_iter().flatten() {
+            if let Some(token) = state.tokens.get(child) {
+                token.store(true, Ordering::Relaxed);
+            }
+        }
+        true
+    }
+
+    /// 取消所有进行中的任务（退出时使用）
+    pub fn cancel_all(&self) {
+        for token in self.state.lock().unwrap().tokens.values() {
+            token.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// 是否还有进行中的任务
+    pub fn has_running(&self) -> bool {
+        !self.state.lock().unwrap().running.is_empty()
+    }
+
+    /// 进行中的任务数，不含批处理父任务
+    pub fn running_count(&self) -> usize {
+        self.state.lock().unwrap().running.len()
+    }
+
+    /// 登记新任务；与进行中或最近完成的任务重复时返回已有结果。`parent` 为所属的批处理任务
+    pub fn admit(&self, fingerprint: &str, apk_path: &str, allow_duplicate: bool, parent: Option<&str>) -> Admission {
+        let mut state = self.state.lock().unwrap();
+        if !allow_duplicate {
+            if let Some(id) = state.running.get(fingerprint) {
+                return Admission::Duplicate(Box::new(ProcessResult {
+                    success: false,
+                    message: "相同的任务正在处理中，已忽略重复提交".to_string(),
+                    step: Some("duplicate".to_string()),
+                    duplicate_of: Some(id.clone()),
+                    ..Default::default()
+                }));
+            }
+            if let Some((_, id, result)) = state.completed.iter().rev().find(|(fp, _, _)| fp == fingerprint) {
+                let mut prior = Box::new(result.clone());
+                prior.duplicate_of = Some(id.clone());
+                return Admission::Duplicate(prior);
+            }
+        }
+        let id = self.next_id("job");
+        state.running.insert(fingerprint.to_string(), id.clone());
+        // 父任务已被取消时，新的子任务一登记就处于取消状态
+        let cancelled = parent.and_then(|p| state.tokens.get(p)).is_some_and(|t| t.load(Ordering::Relaxed));
+        state.tokens.insert(id.clone(), Arc::new(AtomicBool::new(cancelled)));
+        state.inputs.insert(id.clone(), apk_path.to_string());
+        if let Some(children) = parent.and_then(|p| state.children.get_mut(p)) {
+            children.push(id.clone());
+        }
+        Admission::Started(id)
+    }
+
+    /// 任务结束后移出进行中列表，并记入最近完成
+    pub fn finish(&self, fingerprint: &str, id: &str, result: Option<&ProcessResult>) {
+        let mut state = self.state.lock().unwrap();
+        state.running.remove(fingerprint);
+        state.tokens.remove(id);
+        state.inputs.remove(id);
+        if let Some(result) = result.filter(|r| r.success) {
+            state.completed.push_back((fingerprint.to_string(), id.to_string(), result.clone()));
+            while state.completed.len() > RECENT_LIMIT {
+                state.completed.pop_front();
+            }
+        }
+    }
+}
+
+/// 取消进行中的任务或批处理。取消批处理时，当前子任务被结束，尚未开始的子任务标记为已取消，已完成的输出保留
+#[tauri::command]
+pub fn cancel_job(app: tauri::AppHandle, jobs: tauri::State<'_, JobRegistry>, job_id: String) -> bool {
+    let found = jobs.cancel(&job_id);
+    let detail = (!found).then(|| "任务不存在或已结束".to_string());
+    crate::audit::record_outcome(&app, "cancel_job", serde_json::json!({ "job_id": job_id }), found, detail);
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(apk_path: &Path) -> ProcessConfig {
+        serde_json::from_value(serde_json::json!({
+            "apk_path": apk_path,
+            "new_prefix": "com.corp",
+            "install_after": false,
+            "java_path": "",
+            "apktool_path": "",
+            "zipalign_path": "",
+            "apksigner_path": "",
+            "keystore_path": "",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn ignored_fields_do_not_change_the_fingerprint() {
+        let dir = tempfile::tempdir().unwrap();
+        let apk = dir.path().join("app.apk");
+        fs::write(&apk, b"apk").unwrap();
+        let base = config(&apk);
+        let expected = fingerprint(&base).unwrap();
+
+        let tuned = ProcessConfig { hydrate_source: Some(true), log_queue_depth: Some(5), min_output_ratio: Some(0.5), ..base.clone() };
+        assert_eq!(fingerprint(&tuned).unwrap(), expected);
+
+        // 同一内容换个路径仍是同一任务
+        let copy = dir.path().join("copy.apk");
+        fs::copy(&apk, &copy).unwrap();
+        assert_eq!(fingerprint(&ProcessConfig { apk_path: copy.to_string_lossy().to_string(), ..base.clone() }).unwrap(), expected);
+    }
+
+    #[test]
+    fn relevant_fields_change_the_fingerprint() {
+        let dir = tempfile::tempdir().unwrap();
+        let apk = dir.path().join("app.apk");
+        fs::write(&apk, b"apk").unwrap();
+        let base = config(&apk);
+        let expected = fingerprint(&base).unwrap();
+
+        assert_ne!(fingerprint(&ProcessConfig { new_prefix: "com.other".to_string(), ..base.clone() }).unwrap(), expected);
+        assert_ne!(fingerprint(&ProcessConfig { strip_debug_info: Some(true), ..base.clone() }).unwrap(), expected);
+
+        let other = dir.path().join("other.apk");
+        fs::write(&other, b"other apk").unwrap();
+        assert_ne!(fingerprint(&ProcessConfig { apk_path: other.to_string_lossy().to_string(), ..base }).unwrap(), expected);
+    }
+
+    #[test]
+    fn job_status_wire_values_are_snake_case() {
+        let statuses = [
+            (JobStatus::Queued, "queued"),
+            (JobStatus::Running, "running"),
+            (JobStatus::Succeeded, "succeeded"),
+            (JobStatus::Failed, "failed"),
+            (JobStatus::Cancelled, "cancelled"),
+            (JobStatus::Duplicate, "duplicate"),
+            (JobStatus::Interrupted, "interrupted"),
+        ];
+        for (status, wire) in statuses {
+            assert_eq!(serde_json::to_value(status).unwrap(), wire);
+            assert_eq!(serde_json::from_value::<JobStatus>(wire.into()).unwrap(), status);
+        }
+    }
+
+    #[test]
+    fn result_status_wire_values_are_snake_case() {
+        let statuses = [
+            (ResultStatus::Success, "success"),
+            (ResultStatus::SuccessWithWarnings, "success_with_warnings"),
+            (ResultStatus::Failed, "failed"),
+            (ResultStatus::Cancelled, "cancelled"),
+            (ResultStatus::RolledBack, "rolled_back"),
+            (ResultStatus::Skipped, "skipped"),
+        ];
+        for (status, wire) in statuses {
+            assert_eq!(serde_json::to_value(status).unwrap(), wire);
+            assert_eq!(serde_json::from_value::<ResultStatus>(wire.into()).unwrap(), status);
+        }
+    }
+
+    /// 模拟耗时的处理流程：像 `Recorder` 一样轮询取消标记，返回是否因取消而提前结束
+    fn slow_pipeline(jobs: &Arc<JobRegistry>, fingerprint: &str, id: &str) -> std::thread::JoinHandle<bool> {
+        let jobs = jobs.clone();
+        let (fingerprint, id) = (fingerprint.to_string(), id.to_string());
+        let token = jobs.cancel_token(&id).unwrap();
+        std::thread::spawn(move || {
+            let started = std::time::Instant::now();
+            let mut cancelled = false;
+            while started.elapsed() < std::time::Duration::from_secs(5) {
+                if token.load(Ordering::Relaxed) {
+                    cancelled = true;
+                    break;
+                }
+                std::thread::sleep(std::time::Duration::from_millis(5));
+            }
+            let result = if cancelled { cancelled_result() } else { ProcessResult { success: true, ..Default::default() } };
+            jobs.finish(&fingerprint, &id, Some(&result));
+            cancelled
+        })
+    }
+
+    fn started(admission: Admission) -> String {
+        match admission {
+            Admission::Started(id) => id,
+            Admission::Duplicate(_) => panic!("不应判定为重复"),
+        }
+    }
+
+    #[test]
+    fn cancelling_a_batch_cancels_its_running_children() {
+        let jobs = Arc::new(JobRegistry::default());
+        let (batch, batch_token) = jobs.start_batch();
+        let first = started(jobs.admit("fp-1", "a.apk", false, Some(&batch)));
+        let second = started(jobs.admit("fp-2", "b.apk", false, Some(&batch)));
+        let workers = [slow_pipeline(&jobs, "fp-1", &first), slow_pipeline(&jobs, "fp-2", &second)];
+
+        assert!(jobs.cancel(&batch));
+        assert!(batch_token.load(Ordering::Relaxed));
+        for worker in workers {
+            assert!(worker.join().unwrap(), "子任务应当被取消");
+        }
+        assert!(!jobs.has_running());
+        // 被取消的任务不记入最近完成，可以重新提交
+        assert!(matches!(jobs.admit("fp-1", "a.apk", false, None), Admission::Started(_)));
+    }
+
+    #[test]
+    fn children_admitted_after_cancel_start_cancelled() {
+        let jobs = JobRegistry::default();
+        let (batch, _) = jobs.start_batch();
+        assert!(jobs.cancel(&batch));
+        let late = started(jobs.admit("fp-late", "c.apk", false, Some(&batch)));
+        assert!(jobs.cancel_token(&late).unwrap().load(Ordering::Relaxed));
+        // 不属于该批处理的任务不受影响
+        let other = started(jobs.admit("fp-other", "d.apk", false, None));
+        assert!(!jobs.cancel_token(&other).unwrap().load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn cancelling_a_child_leaves_the_batch_running() {
+        let jobs = JobRegistry::default();
+        let (batch, batch_token) = jobs.start_batch();
+        let child = started(jobs.admit("fp-1", "a.apk", false, Some(&batch)));
+        let sibling = started(jobs.admit("fp-2", "b.apk", false, Some(&batch)));
+        assert!(jobs.cancel(&child));
+        assert!(!batch_token.load(Ordering::Relaxed));
+        assert!(!jobs.cancel_token(&sibling).unwrap().load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn cancelling_unknown_or_finished_jobs_returns_false() {
+        let jobs = JobRegistry::default();
+        assert!(!jobs.cancel("job-missing"));
+        let id = started(jobs.admit("fp", "a.apk", false, None));
+        jobs.finish("fp", &id, None);
+        assert!(!jobs.cancel(&id));
+        let (batch, _) = jobs.start_batch();
+        jobs.finish_batch(&batch);
+        assert!(!jobs.cancel(&batch));
+    }
+}
+
@@ -0,0 +1,276 @@
+//! 签名证书使用策略：记录每个原包名用过的证书指纹与客户标签，
+//! 防止同一应用换了证书，或某客户的证书被用于其他客户的应用。
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::confirm::{Confirmation, ConfirmationTokens, DestructiveSummary};
+use crate::paths::AppPaths;
+use crate::pipeline::{PackagePlan, ProcessConfig};
+use crate::{signing, ProcessResult};
+
+const USAGE_FILE: &str = "key_usage.json";
+
+/// 某个证书签过某个原包名的记录
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct KeyUsage {
+    pub original_package: String,
+    /// 证书 SHA-256 指纹（keytool 格式，`AB:CD:...`）
+    pub fingerprint: String,
+    pub keystore_path: String,
+    /// 密钥库中的别名（旧记录没有该字段）
+    #[serde(default)]
+    pub alias: Option<String>,
+    pub customer: Option<String>,
+    /// 最近一次使用的 Unix 时间戳（秒）
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub timestamp: u64,
+}
+
+/// 需要确认的冲突，附带历史上的那次使用
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeyConflict {
+    /// 该原包名上次使用的是另一个证书
+    FingerprintChanged(KeyUsage),
+    /// 该证书上次用于另一个客户的应用
+    CustomerMismatch(KeyUsage),
+}
+
+impl KeyConflict {
+    pub fn describe(&self, fingerprint: &str) -> String {
+        match self {
+            KeyConflict::FingerprintChanged(prev) => format!(
+                "{} 上次（{}）使用密钥库 {} 签名，证书指纹 {}；当前选择的证书指纹为 {}。换用证书后已安装的应用无法覆盖升级，确认要继续吗？",
+                prev.original_package,
+                crate::deliverable::utc_date(prev.timestamp),
+                prev.keystore_path,
+                prev.fingerprint,
+                fingerprint
+            ),
+            KeyConflict::CustomerMismatch(prev) => format!(
+                "证书 {} 上次（{}）用于客户「{}」的应用 {}（密钥库 {}），确认要用它签名其他客户的应用吗？",
+                prev.fingerprint,
+                crate::deliverable::utc_date(prev.timestamp),
+                prev.customer.as_deref().unwrap_or(""),
+                prev.original_package,
+                prev.keystore_path
+            ),
+        }
+    }
+}
+
+fn same_fingerprint(a: &str, b: &str) -> bool {
+    a.replace(':', "").eq_ignore_ascii_case(&b.replace(':', ""))
+}
+
+/// 检查本次签名是否与历史冲突：首次使用、与上次一致或没有历史时返回 `None`。
+/// 客户标签只在本次与历史记录都设置时比较
+pub fn check(history: &[KeyUsage], original_package: &str, fingerprint: &str, customer: Option<&str>) -> Option<KeyConflict> {
+    let last_for_package = history.iter().filter(|u| u.original_package == original_package).max_by_key(|u| u.timestamp);
+    if let Some(prev) = last_for_package.filter(|prev| !same_fingerprint(&prev.fingerprint, fingerprint)) {
+        return Some(KeyConflict::FingerprintChanged(prev.clone()));
+    }
+    let customer = customer?;
+    history
+        .iter()
+        .filter(|u| same_fingerprint(&u.fingerprint, fingerprint))
+        .filter(|u| u.customer.as_deref().is_some_and(|c| c != customer))
+        .max_by_key(|u| u.timestamp)
+        .cloned()
+        .map(KeyConflict::CustomerMismatch)
+}
+
+pub fn load(paths: &AppPaths) -> Vec<KeyUsage> {
+    fs::read_to_string(paths.data_dir.join(USAGE_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// 记录一次使用；同一原包名、证书与客户只保留一条，更新时间戳
+pub fn record(paths: &AppPaths, usage: KeyUsage) -> Result<(), String> {
+    let mut history = load(paths);
+    history.retain(|u| {
+        !(u.original_package == usage.original_package && same_fingerprint(&u.fingerprint, &usage.fingerprint) && u.customer == usage.customer)
+    });
+    history.push(usage);
+    fs::create_dir_all(&paths.data_dir).map_err(|e| e.to_string())?;
+    let content = serde_json::to_string_pretty(&history).map_err(|e| e.to_string())?;
+    fs::write(paths.data_dir.join(USAGE_FILE), content).map_err(|e| e.to_string())
+}
+
+/// 本次所用别名的证书指纹；keytool 不可用或口令不对时为 `None`，此时不做检查
+pub fn current_fingerprint(config: &ProcessConfig) -> Option<String> {
+    // 令牌签名时不检查：额外的一次登录也会计入 PIN 错误次数
+    if config.pkcs11_signing.is_some() {
+        return None;
+    }
+    let keystore = signing::KeystoreConfig::for_run(config).ok()?;
+    signing::keystore_fingerprint(&signing::keytool_path(Some(&config.java_path)), &keystore.path, &keystore.store_password, Some(&keystore.alias))
+}
+
+/// 处理前检查证书使用策略：有冲突且没有有效的确认令牌时返回带令牌的待确认结果
+pub fn gate(
+    paths: &AppPaths,
+    tokens: &ConfirmationTokens,
+    config: &ProcessConfig,
+    plan: &PackagePlan,
+    confirmation_token: Option<String>,
+) -> Result<Option<ProcessResult>, String> {
+    let Some(original_package) = plan.original_package.as_deref() else {
+        return Ok(None);
+    };
+    let Some(fingerprint) = current_fingerprint(config) else {
+        return Ok(None);
+    };
+    let Some(conflict) = check(&load(paths), original_package, &fingerprint, config.customer.as_deref()) else {
+        return Ok(None);
+    };
+    let message = conflict.describe(&fingerprint);
+    let params = (&config.apk_path, &config.keystore_path, &config.key_alias_override, &config.customer, original_package);
+    let pending = tokens.check::<_, ()>("sign_with_key", &params, confirmation_token, || DestructiveSummary {
+        operation: "使用与历史记录不一致的签名证书".to_string(),
+        device_serial: config.device_id.clone().unwrap_or_default(),
+        device_alias: None,
+        package_count: 1,
+        data_loss: false,
+        detail: Some(message.clone()),
+    })?;
+    Ok(match pending {
+        Some(Confirmation::ConfirmationRequired { token, .. }) => Some(ProcessResult {
+            success: false,
+            message,
+            step: Some("key_policy".to_string()),
+            confirmation_token: Some(token),
+            ..Default::default()
+        }),
+        _ => None,
+    })
+}
+
+/// 处理成功后记录本次使用的证书
+pub fn record_run(paths: &AppPaths, config: &ProcessConfig, original_package: &str) -> Result<(), String> {
+    let Some(fingerprint) = current_fingerprint(config) else {
+        return Ok(());
+    };
+    record(
+        paths,
+        KeyUsage {
+            original_package: original_package.to_string(),
+            fingerprint,
+            keystore_path: config.keystore_path.clone(),
+            alias: signing::KeystoreConfig::for_run(config).ok().map(|keystore| keystore.alias),
+            customer: config.customer.clone(),
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(package: &str, fingerprint: &str, customer: Option<&str>, timestamp: u64) -> KeyUsage {
+        KeyUsage {
+            original_package: package.to_string(),
+            fingerprint: fingerprint.to_string(),
+            keystore_path: "/keys/release.jks".to_string(),
+            alias: Some("release".to_string()),
+            customer: customer.map(str::to_string),
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn first_use_has_no_conflict() {
+        assert_eq!(check(&[], "com.example.app", "AB:CD:EF", Some("acme")), None);
+        let other = [usage("com.example.other", "11:22:33", None, 100)];
+        assert_eq!(check(&other, "com.example.app", "AB:CD:EF", None), None);
+    }
+
+    #[test]
+    fn same_fingerprint_ignores_colons_and_case() {
+        let history = [usage("com.example.app", "AB:CD:EF", Some("acme"), 100)];
+        assert_eq!(check(&history, "com.example.app", "abcdef", Some("acme")), None);
+        assert_eq!(check(&history, "com.example.app", "ab:cd:ef", None), None);
+    }
+
+    #[test]
+    fn changed_fingerprint_reports_the_latest_record() {
+        let history = [
+            usage("com.example.app", "11:11:11", None, 100),
+            usage("com.example.app", "22:22:22", None, 200),
+            usage("com.example.other", "33:33:33", None, 300),
+        ];
+        assert_eq!(check(&history, "com.example.app", "44:44:44", None), Some(KeyConflict::FingerprintChanged(history[1].clone())));
+        // 与最近一次一致即可，不与更早的记录比较
+        assert_eq!(check(&history, "com.example.app", "22:22:22", None), None);
+    }
+
+    #[test]
+    fn certificate_used_for_another_customer_is_a_conflict() {
+        let history = [usage("com.acme.app", "AB:CD", Some("acme"), 100), usage("com.acme.tool", "AB:CD", Some("acme"), 200)];
+        let conflict = check(&history, "com.globex.app", "ab:cd", Some("globex")).unwrap();
+        assert_eq!(conflict, KeyConflict::CustomerMismatch(history[1].clone()));
+        assert!(conflict.describe("ab:cd").contains("acme"));
+        assert_eq!(check(&history, "com.acme.new", "AB:CD", Some("acme")), None);
+    }
+
+    #[test]
+    fn missing_customer_skips_the_customer_check() {
+        let history = [usage("com.acme.app", "AB:CD", Some("acme"), 100), usage("com.plain.app", "AB:CD", None, 200)];
+        assert_eq!(check(&history, "com.globex.app", "AB:CD", None), None);
+        // 历史记录没有客户标签时也不比较
+        assert_eq!(check(&history[1..], "com.globex.app", "AB:CD", Some("globex")), None);
+    }
+
+    #[test]
+    fn missing_history_file_loads_empty_and_record_deduplicates() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = AppPaths::portable_at(dir.path());
+        assert!(load(&paths).is_empty());
+
+        record(&paths, usage("com.example.app", "AB:CD", Some("acme"), 100)).unwrap();
+        record(&paths, usage("com.example.app", "ab:cd", Some("acme"), 200)).unwrap();
+        record(&paths, usage("com.example.app", "AB:CD", Some("globex"), 300)).unwrap();
+        let history = load(&paths);
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].timestamp, 200);
+        assert_eq!(history[1].customer.as_deref(), Some("globex"));
+    }
+
+    #[test]
+    fn corrupt_history_file_is_treated_as_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = AppPaths::portable_at(dir.path());
+        fs::create_dir_all(&paths.data_dir).unwrap();
+        fs::write(paths.data_dir.join(USAGE_FILE), "{ not json").unwrap();
+        assert!(load(&paths).is_empty());
+    }
+
+    #[test]
+    fn gate_passes_without_original_package_or_readable_keystore() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = AppPaths::portable_at(dir.path());
+        record(&paths, usage("com.example.app", "AB:CD", None, 100)).unwrap();
+        let tokens = ConfirmationTokens::new(false);
+        let config: ProcessConfig = serde_json::from_value(serde_json::json!({
+            "apk_path": "app.apk",
+            "new_prefix": "com.test",
+            "install_after": false,
+            "java_path": "",
+            "apktool_path": "",
+            "zipalign_path": "",
+            "apksigner_path": "",
+            "keystore_path": dir.path().join("missing.jks").to_string_lossy(),
+        }))
+        .unwrap();
+
+        let unknown = PackagePlan { original_package: None, new_package: "com.test.app".to_string() };
+        assert!(gate(&paths, &tokens, &config, &unknown, None).unwrap().is_none());
+        let known = PackagePlan { original_package: Some("com.example.app".to_string()), new_package: "com.test.app".to_string() };
+        assert!(gate(&paths, &tokens, &config, &known, None).unwrap().is_none());
+    }
+}
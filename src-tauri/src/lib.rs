@@ -1,81 +1,237 @@
 use serde::{Deserialize, Serialize};
-use std::process::Command;
-use std::path::Path;
-use std::fs;
+use tauri::Emitter;
 
-#[derive(Debug, Serialize, Deserialize)]
+mod adb;
+mod adb_coordinator;
+mod analysis;
+mod app_labels;
+mod artifacts;
+mod assets;
+mod audit;
+mod axml;
+mod batch;
+mod capabilities;
+mod collision;
+mod compat;
+mod config;
+mod confirm;
+mod control;
+mod crashes;
+mod decode;
+mod deliverable;
+mod device_cache;
+mod device_files;
+mod device_prefs;
+mod device_storage;
+mod diagnostics;
+mod dex;
+mod download;
+mod entries;
+mod estimate;
+mod fleet;
+mod graph;
+mod hash;
+mod health;
+mod hooks;
+mod http;
+mod inbox;
+mod incremental;
+mod injection;
+mod input_path;
+mod install_order;
+mod installs;
+mod io_pool;
+mod jobs;
+mod key_policy;
+mod library;
+mod licenses;
+mod log_batch;
+mod manifest;
+mod metrics;
+mod naming;
+mod mitm;
+mod native_deps;
+mod paths;
+mod pipeline;
+mod pkcs11;
+mod pm;
+mod policy;
+mod post_install;
+mod prefix_risk;
+mod quarantine;
+mod repackaging;
+mod res_refs;
+mod rollback;
+mod run_log;
+mod safe_path;
+mod selftest;
+mod session;
+mod signing;
+mod shutdown;
+mod source;
+mod startup;
+mod stealth;
+mod support;
+mod system_install;
+mod tool_versions;
+mod uninstall;
+mod validate;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
 pub struct TrustedPrefix {
     pub prefix: String,
     pub count: i32,
     pub source: String,
+    /// 深度检查发现该前缀的应用家族使用 signature 级权限或 sharedUserId，混入其中可能出现权限异常
+    #[serde(default)]
+    pub risky: bool,
+    #[serde(default)]
+    pub risk_reason: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
 pub struct ProcessResult {
+    /// 结果状态；`success` 与消息中的表情符号仅为兼容旧版前端保留
+    #[serde(default)]
+    pub status: jobs::ResultStatus,
     pub success: bool,
+    /// 成功但工具输出的警告超过阈值，前端以警告样式显示
+    pub success_with_warnings: bool,
     pub message: String,
     pub output_path: Option<String>,
+    /// 输出文件的大小（字节），成功时填写
+    #[serde(default)]
+    #[cfg_attr(feature = "export-bindings", ts(type = "number | null"))]
+    pub output_size_bytes: Option<u64>,
     pub step: Option<String>,
+    /// 安装后动作的执行结果（动作失败不影响安装本身的成功状态）
+    pub post_install: Option<Vec<post_install::ActionResult>>,
+    /// 各步骤耗时与资源占用
+    pub metrics: Option<metrics::RunMetrics>,
+    /// ContentProvider URI 报告（开启 content_uri_report 时）
+    pub content_uris: Option<Vec<manifest::ContentUri>>,
+    /// 替换的资源文件及其前后大小
+    pub changes: Vec<assets::AssetChange>,
+    /// 不影响成功状态的警告
+    pub warnings: Vec<String>,
+    /// 开始处理前确定的新包名，失败的处理也会带上
+    pub new_package: Option<String>,
+    /// 处理方式：`full` 或 `incremental`（仅重写 Manifest 并重新签名）
+    pub mode: Option<String>,
+    /// 密钥库签名时实际使用的别名与证书指纹
+    #[serde(default)]
+    pub signing_identity: Option<signing::SigningIdentity>,
+    /// 本次实际使用的工具及版本
+    pub tool_versions: Vec<tool_versions::ToolVersion>,
+    /// 本次任务的 ID
+    pub job_id: Option<String>,
+    /// 与已有任务重复时，指向该任务的 ID
+    pub duplicate_of: Option<String>,
+    /// 需要确认后才能继续时的确认令牌，带上它重新提交即可
+    pub confirmation_token: Option<String>,
+    /// 新包名与历史冲突时推荐的其他后缀
+    pub suggested_suffixes: Vec<String>,
+    /// 隐匿输出时仍无法去除的痕迹
+    pub residual_traces: Vec<String>,
+    /// 成功的反编译方式：`full` 或降级后的 `no_res`
+    pub decode_strategy: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
 pub struct AppInfo {
     pub package_name: String,
     pub app_name: String,
     pub version: String,
     pub is_system: bool,
+    /// 设备在 `pm list packages` 中给出 uid 时可用，便于与 logcat 对照
+    pub uid: Option<u32>,
+    /// 未被停用；停用（`pm disable-user`）的多为只能停用而无法卸载的系统应用
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// 处理过程中推送给前端的进度 / 日志事件
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct ProgressEvent {
+    pub step: String,
+    pub message: String,
 }
 
-/// 检测 ADB 是否可用
+/// 向前端推送一条运行日志
+fn emit_progress(app: &tauri::AppHandle, step: &str, message: impl Into<String>) {
+    let _ = app.emit("process-progress", ProgressEvent { step: step.to_string(), message: message.into() });
+}
+
+/// 检测 ADB 是否可用，返回使用的 adb 路径、来源（用户指定 / 内置 / PATH）与版本
 #[tauri::command]
-fn check_adb() -> Result<bool, String> {
-    let output = Command::new("adb").arg("version").output();
-    match output {
-        Ok(out) => Ok(out.status.success()),
-        Err(_) => Ok(false),
-    }
+fn check_adb() -> adb::AdbCheck {
+    adb::check()
 }
 
-/// 获取已连接的设备列表
+/// 获取已连接的设备列表。`details` 包含未授权与离线的设备，
+/// 可用设备补充 Android 版本与 ABI（读取失败时留空）
 #[tauri::command]
-fn get_devices() -> Result<Vec<String>, String> {
-    let output = Command::new("adb")
-        .args(["devices", "-l"])
-        .output()
-        .map_err(|e| e.to_string())?;
-    
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let devices: Vec<String> = stdout
-        .lines()
-        .skip(1)
-        .filter(|line| !line.trim().is_empty() && line.contains("device"))
-        .map(|line| {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if !parts.is_empty() { parts[0].to_string() } else { line.to_string() }
-        })
-        .collect();
-    
-    Ok(devices)
+fn get_devices(cache: tauri::State<'_, device_cache::DeviceCache>) -> Result<adb::DeviceList, String> {
+    let mut list = adb::device_list()?;
+    for info in list.details.iter_mut().filter(|d| d.is_ready()) {
+        if let Ok(props) = device_cache::cached_props(&cache, &info.id) {
+            info.android_version = props.get("ro.build.version.release").cloned();
+            info.abi = props.get("ro.product.cpu.abi").cloned();
+        }
+    }
+    Ok(list)
 }
 
-/// 扫描设备上已安装应用，提取可信任的包名前缀
+/// 扫描设备上已安装应用，提取可信任的包名前缀。
+/// 传入已反编译的工作目录时，会根据推断的目标市场调整排序（如面向国内的应用优先 `cn.*`）
 #[tauri::command]
-fn scan_trusted_prefixes(device_id: String) -> Result<Vec<TrustedPrefix>, String> {
-    let output = Command::new("adb")
-        .args(["-s", &device_id, "shell", "pm", "list", "packages"])
-        .output()
-        .map_err(|e| e.to_string())?;
-    
-    let stdout = String::from_utf8_lossy(&output.stdout);
+fn scan_trusted_prefixes(
+    cache: tauri::State<'_, device_cache::DeviceCache>,
+    policy: tauri::State<'_, policy::PolicyState>,
+    device_id: String,
+    work_dir: Option<String>,
+    deep: Option<bool>,
+) -> Result<Vec<TrustedPrefix>, String> {
+    let work_dir = work_dir.as_deref().map(|p| input_path::arg(p, input_path::Expect::Dir)).transpose().map_err(|e| e.to_string())?;
+    let cached = work_dir.is_none().then(|| cache.get(&device_id, device_cache::DATASET_TRUSTED_PREFIXES)).flatten();
+    let trusted = match cached {
+        Some(cached) => cached,
+        None => {
+            let trusted = load_trusted_prefixes(&device_id, work_dir.clone())?;
+            if work_dir.is_none() {
+                cache.put(&device_id, device_cache::DATASET_TRUSTED_PREFIXES, &trusted);
+            }
+            trusted
+        }
+    };
+    // 策略在缓存之后合并，刷新策略后立即生效
+    let mut trusted = match policy.current() {
+        Some(policy) => policy::merge_prefixes(&policy, trusted),
+        None => trusted,
+    };
+    if deep.unwrap_or(false) {
+        prefix_risk::mark_risky(&cache, &device_id, &mut trusted)?;
+    }
+    Ok(trusted)
+}
+
+fn load_trusted_prefixes(device_id: &str, work_dir: Option<String>) -> Result<Vec<TrustedPrefix>, String> {
+    let stdout = adb::shell(device_id, &["pm", "list", "packages"])?;
     let mut prefix_map: std::collections::HashMap<String, i32> = std::collections::HashMap::new();
     
-    for line in stdout.lines() {
-        if let Some(pkg) = line.strip_prefix("package:") {
-            let parts: Vec<&str> = pkg.split('.').collect();
-            if parts.len() >= 2 {
-                let prefix = format!("{}.{}", parts[0], parts[1]);
-                *prefix_map.entry(prefix).or_insert(0) += 1;
-            }
+    for entry in pm::parse_package_list(&stdout) {
+        let parts: Vec<&str> = entry.package.split('.').collect();
+        if parts.len() >= 2 {
+            let prefix = format!("{}.{}", parts[0], parts[1]);
+            *prefix_map.entry(prefix).or_insert(0) += 1;
         }
     }
     
@@ -83,278 +239,228 @@ fn scan_trusted_prefixes(device_id: String) -> Result<Vec<TrustedPrefix>, String
     let mut trusted: Vec<TrustedPrefix> = prefix_map
         .into_iter()
         .filter(|(prefix, count)| *count >= 2 && !system_prefixes.iter().any(|sys| prefix.starts_with(sys)))
-        .map(|(prefix, count)| TrustedPrefix { prefix, count, source: "device_scan".to_string() })
+        .map(|(prefix, count)| TrustedPrefix { prefix, count, source: "device_scan".to_string(), ..Default::default() })
         .collect();
     
-    trusted.sort_by(|a, b| b.count.cmp(&a.count));
-    trusted.insert(0, TrustedPrefix { prefix: "cn.chinapost".to_string(), count: 999, source: "recommended".to_string() });
-    trusted.insert(1, TrustedPrefix { prefix: "com.nlscan".to_string(), count: 100, source: "recommended".to_string() });
+    trusted.sort_by_key(|p| std::cmp::Reverse(p.count));
+    
+    let top_market = work_dir
+        .and_then(|dir| analysis::detect_app_target_market(dir).ok())
+        .and_then(|m| m.detected_markets.into_iter().next());
+    if let Some(market) = top_market {
+        let market_prefix = format!("{}.", market);
+        trusted.sort_by_key(|p| !p.prefix.starts_with(&market_prefix));
+    }
+    trusted.insert(0, TrustedPrefix { prefix: "cn.chinapost".to_string(), count: 999, source: "recommended".to_string(), ..Default::default() });
+    trusted.insert(1, TrustedPrefix { prefix: "com.nlscan".to_string(), count: 100, source: "recommended".to_string(), ..Default::default() });
     
     Ok(trusted)
 }
 
 /// 获取设备上已安装的应用列表
 #[tauri::command]
-fn get_installed_apps(device_id: String) -> Result<Vec<AppInfo>, String> {
+fn get_installed_apps(
+    app: tauri::AppHandle,
+    cache: tauri::State<'_, device_cache::DeviceCache>,
+    device_id: String,
+) -> Result<Vec<AppInfo>, String> {
+    if let Some(cached) = cache.get(&device_id, device_cache::DATASET_INSTALLED_APPS) {
+        return Ok(cached);
+    }
+    let apps = load_installed_apps(&app, &device_id)?;
+    cache.put(&device_id, device_cache::DATASET_INSTALLED_APPS, &apps);
+    Ok(apps)
+}
+
+fn load_installed_apps(app: &tauri::AppHandle, device_id: &str) -> Result<Vec<AppInfo>, String> {
     // 获取所有应用（包括系统应用）
-    let all_output = Command::new("adb")
-        .args(["-s", &device_id, "shell", "pm", "list", "packages", "-f"])
-        .output()
-        .map_err(|e| e.to_string())?;
+    let all_stdout = adb::shell(device_id, &["pm", "list", "packages", "-f"])?;
+    for warning in pm::format_warnings(&all_stdout) {
+        emit_progress(app, "apps", warning);
+    }
     
     // 获取系统应用列表
-    let system_output = Command::new("adb")
-        .args(["-s", &device_id, "shell", "pm", "list", "packages", "-s"])
-        .output()
-        .map_err(|e| e.to_string())?;
-    
-    let all_stdout = String::from_utf8_lossy(&all_output.stdout);
-    let system_stdout = String::from_utf8_lossy(&system_output.stdout);
+    let system_stdout = adb::shell(device_id, &["pm", "list", "packages", "-s"])?;
     
     // 解析系统应用包名
-    let system_packages: std::collections::HashSet<String> = system_stdout
-        .lines()
-        .filter_map(|line| line.strip_prefix("package:"))
-        .map(|s| s.trim().to_string())
-        .collect();
+    let system_packages: std::collections::HashSet<String> =
+        pm::parse_package_list(&system_stdout).into_iter().map(|p| p.package).collect();
+
+    // 已停用的应用；部分设备不支持 -d，此时不标记
+    let disabled_packages: std::collections::HashSet<String> = adb::shell(device_id, &["pm", "list", "packages", "-d"])
+        .map(|stdout| pm::parse_package_list(&stdout).into_iter().map(|p| p.package).collect())
+        .unwrap_or_default();
     
     let mut apps: Vec<AppInfo> = Vec::new();
     
-    for line in all_stdout.lines() {
-        // 格式: package:/path/to/app.apk=com.example.app
-        if let Some(content) = line.strip_prefix("package:") {
-            if let Some(eq_pos) = content.rfind('=') {
-                let package_name = content[eq_pos + 1..].trim().to_string();
-                let is_system = system_packages.contains(&package_name);
-                
-                // 尝试获取应用名称（使用包名最后一段作为简化名称）
-                let app_name = package_name
-                    .split('.')
-                    .last()
-                    .map(|s| {
-                        // 将驼峰或下划线转为可读名称
-                        let mut result = String::new();
-                        for (i, c) in s.chars().enumerate() {
-                            if i > 0 && c.is_uppercase() {
-                                result.push(' ');
-                            }
-                            result.push(c);
-                        }
-                        result.replace('_', " ")
-                    })
-                    .unwrap_or_else(|| package_name.clone());
-                
-                apps.push(AppInfo {
-                    package_name: package_name.clone(),
-                    app_name,
-                    version: String::new(), // 版本信息需要额外命令获取，暂时留空
-                    is_system,
-                });
-            }
-        }
+    // 格式: package:/path/to/app.apk=com.example.app，部分系统在行尾附加 uid:10234 等字段
+    for entry in pm::parse_package_list(&all_stdout) {
+        let is_system = system_packages.contains(&entry.package);
+        let enabled = !disabled_packages.contains(&entry.package);
+        
+        // 尝试获取应用名称（使用包名最后一段作为简化名称）
+        let app_name = naming::derive_app_name(&entry.package);
+        
+        apps.push(AppInfo {
+            package_name: entry.package,
+            app_name,
+            version: String::new(), // 版本信息需要额外命令获取，暂时留空
+            is_system,
+            uid: entry.uid,
+            enabled,
+        });
     }
     
     // 按名称排序
-    apps.sort_by(|a, b| a.app_name.to_lowercase().cmp(&b.app_name.to_lowercase()));
+    apps.sort_by_key(|a| a.app_name.to_lowercase());
     
     Ok(apps)
 }
 
-/// 卸载应用
-#[tauri::command]
-fn uninstall_app(device_id: String, package_name: String) -> Result<bool, String> {
-    let output = Command::new("adb")
-        .args(["-s", &device_id, "shell", "pm", "uninstall", &package_name])
-        .output()
-        .map_err(|e| e.to_string())?;
-    
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    Ok(stdout.contains("Success"))
+/// `process_apk_full` 的参数：处理配置，加上只对本次调用有效的确认选项
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export, optional_fields = nullable))]
+pub struct ProcessRequest {
+    #[serde(flatten)]
+    pub config: pipeline::ProcessConfig,
+    /// 相同输入的任务正在处理时仍然再处理一次
+    pub allow_duplicate: Option<bool>,
+    /// 新包名已分配给其他应用时仍然处理
+    pub allow_package_collision: Option<bool>,
+    /// 用户确认签名证书变化后，带回上一次结果中的确认令牌
+    pub confirmation_token: Option<String>,
 }
 
 /// 完整的 APK 处理流程
 #[tauri::command]
 async fn process_apk_full(
-    apk_path: String,
-    new_prefix: String,
-    custom_suffix: Option<String>,
-    device_id: Option<String>,
-    install_after: bool,
-    java_path: String,
-    apktool_path: String,
-    zipalign_path: String,
-    apksigner_path: String,
-    keystore_path: String,
+    app: tauri::AppHandle,
+    request: ProcessRequest,
+    jobs: tauri::State<'_, jobs::JobRegistry>,
+    app_paths: tauri::State<'_, paths::AppPaths>,
+    tokens: tauri::State<'_, confirm::ConfirmationTokens>,
+    policy: tauri::State<'_, policy::PolicyState>,
+    cache: tauri::State<'_, device_cache::DeviceCache>,
 ) -> Result<ProcessResult, String> {
-    let path = Path::new(&apk_path);
-    let file_stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("apk");
-    let parent_dir = path.parent().unwrap_or(Path::new("."));
-    let work_dir = std::env::temp_dir().join(format!("apk_disguise_{}", file_stem));
-    let rebuilt_apk = parent_dir.join(format!("{}_rebuilt.apk", file_stem));
-    let aligned_apk = parent_dir.join(format!("{}_aligned.apk", file_stem));
-    let final_apk = parent_dir.join(format!("{}_fixed.apk", file_stem));
-    
-    let _ = fs::remove_dir_all(&work_dir);
-    
-    // 第一步：反编译
-    let decompile = Command::new(&java_path)
-        .args(["-jar", &apktool_path, "d", &apk_path, "-o", work_dir.to_str().unwrap(), "-f", "-s"])
-        .output()
-        .map_err(|e| format!("反编译命令执行失败: {}", e))?;
-    
-    if !decompile.status.success() {
-        let stderr = String::from_utf8_lossy(&decompile.stderr);
-        let stdout = String::from_utf8_lossy(&decompile.stdout);
-        return Ok(ProcessResult {
-            success: false,
-            message: format!("反编译失败: {} {}", stderr, stdout),
-            output_path: None,
-            step: Some("decompile".to_string()),
-        });
-    }
-    
-    // 第二步：修改包名
-    let manifest_path = work_dir.join("AndroidManifest.xml");
-    let manifest_content = fs::read_to_string(&manifest_path)
-        .map_err(|e| format!("读取 Manifest 失败: {}", e))?;
-    
-    // 使用自定义后缀或从文件名生成
-    let suffix = match &custom_suffix {
-        Some(s) if !s.is_empty() => s.clone(),
-        _ => {
-            let clean: String = file_stem.to_lowercase().chars().filter(|c| c.is_alphanumeric()).collect();
-            if clean.len() > 12 { clean[..12].to_string() } else { clean }
+    let ProcessRequest { mut config, allow_duplicate, allow_package_collision, confirmation_token } = request;
+    input_path::normalize_config(&mut config).map_err(|e| e.to_string())?;
+    let prefix_warning = config::apply_prefix_lock(&app_paths, &mut config).map_err(|e| e.to_string())?;
+    // 在任何耗时步骤之前确定新包名，校验失败直接返回
+    let plan = match pipeline::plan_package(&config) {
+        Ok(plan) => plan,
+        Err(message) => {
+            return Ok(jobs::finalize(ProcessResult { success: false, message, step: Some("package_name".to_string()), ..Default::default() }))
         }
     };
-    let new_package = format!("{}.{}", new_prefix, suffix);
-    
-    let re = regex::Regex::new(r#"package="[^"]+""#).unwrap();
-    let mut new_manifest = re.replace(&manifest_content, &format!("package=\"{}\"", new_package)).to_string();
-    
-    if !new_manifest.contains("<uses-sdk") {
-        if let Some(pos) = new_manifest.find("<application") {
-            new_manifest.insert_str(pos, "    <uses-sdk android:minSdkVersion=\"19\" android:targetSdkVersion=\"27\"/>\n    ");
-        }
+    if let Some(refused) = policy::gate(&policy, &config, &plan) {
+        return Ok(jobs::finalize(refused));
     }
-    
-    fs::write(&manifest_path, &new_manifest).map_err(|e| format!("写入 Manifest 失败: {}", e))?;
-    
-    // 第三步：回编译
-    let rebuild = Command::new(&java_path)
-        .args(["-jar", &apktool_path, "b", work_dir.to_str().unwrap(), "-o", rebuilt_apk.to_str().unwrap()])
-        .output()
-        .map_err(|e| format!("回编译命令执行失败: {}", e))?;
-    
-    if !rebuild.status.success() {
-        let stderr = String::from_utf8_lossy(&rebuild.stderr);
-        let stdout = String::from_utf8_lossy(&rebuild.stdout);
-        return Ok(ProcessResult {
-            success: false,
-            message: format!("回编译失败: {} {}", stderr, stdout),
-            output_path: None,
-            step: Some("rebuild".to_string()),
-        });
+    if let Some(collision) = collision::gate(&app_paths, &cache, &config, &plan, allow_package_collision.unwrap_or(false)) {
+        return Ok(jobs::finalize(collision));
     }
-    
-    // 第四步：对齐
-    let align = Command::new(&zipalign_path)
-        .args(["-f", "-v", "4", rebuilt_apk.to_str().unwrap(), aligned_apk.to_str().unwrap()])
-        .output()
-        .map_err(|e| format!("对齐命令执行失败: {}", e))?;
-    
-    if !align.status.success() {
-        let stderr = String::from_utf8_lossy(&align.stderr);
-        return Ok(ProcessResult {
-            success: false,
-            message: format!("对齐失败: {}", stderr),
-            output_path: Some(rebuilt_apk.to_string_lossy().to_string()),
-            step: Some("zipalign".to_string()),
-        });
+    if let Some(pending) = key_policy::gate(&app_paths, &tokens, &config, &plan, confirmation_token)? {
+        return Ok(jobs::finalize(pending));
     }
     
-    // 第五步：签名
-    let sign = Command::new(&java_path)
-        .args([
-            "-jar", &apksigner_path, "sign",
-            "--ks", &keystore_path,
-            "--ks-pass", "pass:123456",
-            "--ks-key-alias", "my-alias",
-            "--key-pass", "pass:123456",
-            "--v1-signing-enabled", "true",
-            "--v2-signing-enabled", "false",
-            "--out", final_apk.to_str().unwrap(),
-            aligned_apk.to_str().unwrap(),
-        ])
-        .output()
-        .map_err(|e| format!("签名命令执行失败: {}", e))?;
-    
-    if !sign.status.success() {
-        let stderr = String::from_utf8_lossy(&sign.stderr);
-        return Ok(ProcessResult {
-            success: false,
-            message: format!("签名失败: {}", stderr),
-            output_path: Some(aligned_apk.to_string_lossy().to_string()),
-            step: Some("sign".to_string()),
-        });
+    let fingerprint = jobs::fingerprint(&config)?;
+    let job_id = match jobs.admit(&fingerprint, &config.apk_path, allow_duplicate.unwrap_or(false), None) {
+        jobs::Admission::Started(id) => id,
+        jobs::Admission::Duplicate(prior) => return Ok(jobs::finalize(*prior)),
+    };
+    if let Err(e) = session::snapshot_for_job(&app_paths, &config) {
+        emit_progress(&app, "session", e);
     }
-    
-    let _ = fs::remove_file(&rebuilt_apk);
-    let _ = fs::remove_file(&aligned_apk);
-    let _ = fs::remove_dir_all(&work_dir);
-    
-    // 第六步：安装
-    if install_after {
-        if let Some(device) = device_id {
-            let install = Command::new("adb")
-                .args(["-s", &device, "install", "-r", "-t", "-g", final_apk.to_str().unwrap()])
-                .output();
-            
-            match install {
-                Ok(out) => {
-                    let stdout = String::from_utf8_lossy(&out.stdout);
-                    if out.status.success() && stdout.contains("Success") {
-                        return Ok(ProcessResult {
-                            success: true,
-                            message: format!("✅ 安装成功! 新包名: {}", new_package),
-                            output_path: Some(final_apk.to_string_lossy().to_string()),
-                            step: Some("install".to_string()),
-                        });
-                    } else {
-                        return Ok(ProcessResult {
-                            success: false,
-                            message: format!("安装失败: {}", stdout),
-                            output_path: Some(final_apk.to_string_lossy().to_string()),
-                            step: Some("install".to_string()),
-                        });
-                    }
-                }
-                Err(e) => {
-                    return Ok(ProcessResult {
-                        success: false,
-                        message: format!("安装命令执行失败: {}", e),
-                        output_path: Some(final_apk.to_string_lossy().to_string()),
-                        step: Some("install".to_string()),
-                    });
-                }
+    let result = execute_job(&app, &app_paths, config, &plan, prefix_warning.as_deref(), &job_id, None);
+    jobs.finish(&fingerprint, &job_id, result.as_ref().ok());
+    result
+}
+
+/// 执行已登记的任务：能增量处理时先增量处理，失败再走完整流程；写入处理记录与审计日志并推送任务状态
+fn execute_job(
+    app: &tauri::AppHandle,
+    app_paths: &paths::AppPaths,
+    config: pipeline::ProcessConfig,
+    plan: &pipeline::PackagePlan,
+    prefix_warning: Option<&str>,
+    job_id: &str,
+    parent_job_id: Option<&str>,
+) -> Result<ProcessResult, String> {
+    use tauri::Manager;
+    let queue_depth = config.log_queue_depth.map_or(log_batch::DEFAULT_QUEUE_DEPTH, u64::from);
+    let job = jobs::JobContext {
+        cancel: app.state::<jobs::JobRegistry>().cancel_token(job_id),
+        log: Some(run_log::RunLog::start(app, job_id, queue_depth)),
+    };
+    jobs::emit_status(app, job_id, parent_job_id, jobs::JobStatus::Running, None);
+    let record_config = config.clone();
+    let result = match incremental::find_base(app_paths, &config) {
+        Some(base) => match incremental::run(app, &config, &base, &plan.new_package, &job) {
+            Ok(r) if r.success => Ok(r),
+            _ if job.is_cancelled() => Ok(ProcessResult { warnings: pipeline::remove_work_dirs(&config.apk_path), ..jobs::cancelled_result() }),
+            incremental_result => {
+                let reason = match incremental_result {
+                    Ok(r) => r.message,
+                    Err(e) => e,
+                };
+                emit_progress(app, "incremental", format!("增量处理失败，改用完整流程: {}", reason));
+                pipeline::run_planned(app, config, plan, None, job.clone())
+            }
+        },
+        None => pipeline::run_planned(app, config, plan, None, job.clone()),
+    };
+    if let Some(log) = &job.log {
+        log.finish();
+    }
+    let result = result.map(|mut r| {
+        r.job_id = Some(job_id.to_string());
+        r.new_package = Some(plan.new_package.clone());
+        if let Some(warning) = prefix_warning {
+            r.message = format!("{}\n{}", r.message, warning);
+            r.warnings.push(warning.to_string());
+        }
+        jobs::finalize(r)
+    });
+    if let Ok(r) = &result {
+        if let Err(e) = incremental::record_success(app_paths, &record_config, r) {
+            emit_progress(app, "incremental", format!("写入处理记录失败: {}", e));
+        }
+        if let Some(original) = plan.original_package.as_deref().filter(|_| r.success) {
+            if let Err(e) = key_policy::record_run(app_paths, &record_config, original) {
+                emit_progress(app, "key_policy", format!("写入证书使用记录失败: {}", e));
             }
         }
     }
-    
-    Ok(ProcessResult {
-        success: true,
-        message: format!("✅ 处理完成! 新包名: {}", new_package),
-        output_path: Some(final_apk.to_string_lossy().to_string()),
-        step: Some("complete".to_string()),
-    })
+    let audit_params = serde_json::json!({
+        "apk": record_config.apk_path,
+        "device": record_config.device_id,
+        "install_after": record_config.install_after,
+        "package": plan.new_package,
+        "job_id": job_id,
+        "parent_job_id": parent_job_id,
+    });
+    match &result {
+        Ok(r) => {
+            audit::record_outcome(app, "process_apk", audit_params, r.success, Some(r.message.clone()));
+            jobs::emit_status(app, job_id, parent_job_id, jobs::status_of(r), Some(r.message.clone()));
+        }
+        Err(e) => {
+            audit::record_outcome(app, "process_apk", audit_params, false, Some(e.clone()));
+            jobs::emit_status(app, job_id, parent_job_id, jobs::JobStatus::Failed, Some(e.clone()));
+        }
+    }
+    result
 }
 
 #[tauri::command]
-fn resolve_tool_paths(app: tauri::AppHandle) -> Result<serde_json::Value, String> {
-    use tauri::Manager;
-    
-    let resource_dir = app.path().resource_dir().map_err(|e| e.to_string())?;
-    let tools_dir = resource_dir.join("tools");
-    
+fn resolve_tool_paths(app_paths: tauri::State<'_, paths::AppPaths>) -> Result<serde_json::Value, String> {
+    let mut paths = bundled_tool_paths(&app_paths.tools_dir);
+    let versions = tool_versions::discover(&app_paths);
+    paths.insert("versions".to_string(), serde_json::to_value(versions).map_err(|e| e.to_string())?);
+    Ok(serde_json::Value::Object(paths))
+}
+
+/// 内置工具目录中实际存在的工具路径
+fn bundled_tool_paths(tools_dir: &std::path::Path) -> serde_json::Map<String, serde_json::Value> {
     let mut paths = serde_json::Map::new();
     
     // Apktool
@@ -363,6 +469,12 @@ fn resolve_tool_paths(app: tauri::AppHandle) -> Result<serde_json::Value, String
         paths.insert("apktool".to_string(), serde_json::Value::String(apktool.to_string_lossy().to_string()));
     }
     
+    // ADB
+    let adb_path = adb::bundled_adb(tools_dir);
+    if adb_path.exists() {
+        paths.insert("adb".to_string(), serde_json::Value::String(adb_path.to_string_lossy().to_string()));
+    }
+
     // Zipalign
     #[cfg(target_os = "windows")]
     let zipalign = tools_dir.join("zipalign.exe");
@@ -385,25 +497,265 @@ fn resolve_tool_paths(app: tauri::AppHandle) -> Result<serde_json::Value, String
         paths.insert("keystore".to_string(), serde_json::Value::String(keystore.to_string_lossy().to_string()));
     }
 
-    Ok(serde_json::Value::Object(paths))
+    paths
+}
+
+/// 登记退出时的关闭钩子：先停止后台任务，再写完各记录文件
+fn register_shutdown_hooks(app: &tauri::AppHandle) {
+    use shutdown::Phase;
+    use tauri::Manager;
+    let coordinator = app.state::<shutdown::ShutdownCoordinator>();
+    let handle = app.clone();
+    coordinator.register("control_server", Phase::Stop, move || control::shutdown(&handle));
+    let handle = app.clone();
+    coordinator.register("health_server", Phase::Stop, move || health::shutdown(&handle));
+    let handle = app.clone();
+    coordinator.register("device_tracker", Phase::Stop, move || adb::shutdown(&handle));
+    let handle = app.clone();
+    coordinator.register("folder_scans", Phase::Stop, move || handle.state::<library::FolderScans>().cancel_all());
+    // 取消会结束任务正在运行的外部进程，任务随后自行清理并退出
+    let handle = app.clone();
+    coordinator.register("jobs", Phase::Stop, move || {
+        let jobs = handle.state::<jobs::JobRegistry>();
+        jobs.cancel_all();
+        while jobs.has_running() {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
+    });
+    let handle = app.clone();
+    coordinator.register("run_logs", Phase::Flush, move || handle.state::<run_log::RunLogs>().flush_all());
+    let handle = app.clone();
+    coordinator.register("batches", Phase::Flush, move || handle.state::<batch::BatchStore>().sync());
+    let handle = app.clone();
+    coordinator.register("install_history", Phase::Flush, move || handle.state::<installs::InstallHistory>().sync());
+    let handle = app.clone();
+    coordinator.register("folder_scan_cache", Phase::Flush, move || handle.state::<library::FolderScans>().sync());
+    let handle = app.clone();
+    coordinator.register("audit", Phase::Flush, move || handle.state::<audit::AuditLog>().close());
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    use tauri::Manager;
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_fs::init())  // 使用 fs 插件
+        .setup(|app| {
+            let app_paths = paths::AppPaths::resolve(app.handle())?;
+            safe_path::register_root(&safe_path::work_root());
+            safe_path::register_root(&app_paths.cache_dir);
+            safe_path::register_root(&inbox::inbox_dir(&app_paths));
+            safe_path::register_root(&rollback::rollback_root(&app_paths));
+            safe_path::set_log_file(app_paths.log_dir.join("deletions.log"));
+            adb::load_settings(&app_paths);
+            adb::init_program(&app_paths);
+            jobs::load_result_settings(&app_paths);
+            batch::recover(&app_paths);
+            native_deps::suppress_error_dialogs();
+            app.manage(app_paths);
+            app.manage(jobs::JobRegistry::default());
+            app.manage(adb_coordinator::AdbCoordinator::default());
+            app.manage(batch::BatchStore::default());
+            app.manage(run_log::RunLogs::default());
+            app.manage(device_cache::DeviceCache::default());
+            app.manage(installs::InstallHistory::default());
+            app.manage(confirm::ConfirmationTokens::new(confirm::bypass_requested()));
+            app.manage(audit::AuditLog::load(&app.state::<paths::AppPaths>()));
+            app.manage(policy::PolicyState::default());
+            app.manage(library::FolderScans::default());
+            app.manage(control::ControlServer::default());
+            app.manage(health::HealthServer::default());
+            app.manage(adb::DeviceTracker::default());
+            app.manage(io_pool::IoPool::start(&app.state::<paths::AppPaths>()));
+            app.manage(shutdown::ShutdownCoordinator::default());
+            register_shutdown_hooks(app.handle());
+            policy::refresh_in_background(app.handle());
+            control::start_if_enabled(app.handle());
+            health::start_if_enabled(app.handle());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             check_adb,
             get_devices,
             scan_trusted_prefixes,
             get_installed_apps,
-            uninstall_app,
+            pm::get_app_details,
+            pm::set_app_enabled,
+            pm::get_users,
+            device_storage::get_device_storage,
+            app_labels::get_app_labels,
+            uninstall::uninstall_app,
+            uninstall::uninstall_apps,
+            uninstall::remove_system_app,
             process_apk_full,
-            resolve_tool_paths
+            resolve_tool_paths,
+            signing::upgrade_signing_scheme,
+            analysis::get_apk_locales,
+            analysis::get_apk_locales_from_work_dir,
+            paths::get_app_paths,
+            paths::migrate_to_portable,
+            mitm::apply_mitm_profile,
+            metrics::get_performance_stats,
+            estimate::estimate_run,
+            res_refs::scan_res_references,
+            validate::check_for_zip_bomb,
+            validate::validate_apk_file,
+            signing::convert_keystore,
+            signing::list_keystore_certificates,
+            pkcs11::list_pkcs11_certificates,
+            quarantine::clear_quarantine,
+            analysis::detect_app_target_market,
+            graph::export_dependency_graph,
+            manifest::ensure_permissions_present,
+            manifest::downgrade_dangerous_permissions,
+            config::get_effective_config,
+            device_cache::prefetch_device,
+            device_cache::get_device_props,
+            analysis::get_apk_complexity_metrics,
+            analysis::count_native_methods_in_work_dir,
+            manifest::get_content_provider_uris,
+            config::register_prefix_lock,
+            config::get_locked_prefix,
+            config::clear_prefix_lock,
+            adb::get_adb_transport_settings,
+            adb::get_adb_path,
+            adb::set_adb_path,
+            adb::set_adb_transport_settings,
+            adb::start_device_tracking,
+            adb::stop_device_tracking,
+            adb::connect_device,
+            adb::disconnect_device,
+            adb::pair_device,
+            repackaging::detect_previous_repackaging,
+            licenses::extract_open_source_notices,
+            rollback::rollback_package,
+            compat::detect_actual_min_sdk,
+            entries::list_apk_entries,
+            entries::read_apk_entry,
+            dex::strip_dex_debug_info,
+            assets::apply_asset_overrides_to_apk,
+            crashes::get_crash_history,
+            installs::get_device_install_history,
+            installs::get_package_install_history,
+            installs::where_is,
+            installs::get_installed_by_us,
+            installs::export_install_history_csv,
+            install_order::compute_install_order,
+            tool_versions::list_tool_versions,
+            tool_versions::install_tool_version,
+            startup::measure_app_startup_time,
+            capabilities::get_device_capabilities,
+            startup::launch_app,
+            startup::force_stop_app,
+            selftest::run_self_test,
+            deliverable::package_deliverable,
+            audit::set_operator,
+            audit::get_operator,
+            audit::query_audit_log,
+            audit::export_audit_log,
+            native_deps::check_native_tools,
+            jobs::cancel_job,
+            jobs::get_result_settings,
+            jobs::set_result_settings,
+            batch::process_apk_batch,
+            batch::list_batches,
+            batch::export_batch_report,
+            inbox::register_dropped_file,
+            inbox::cleanup_inbox,
+            injection::set_failure_injection,
+            run_log::ack_log_events,
+            run_log::get_log_tail,
+            device_files::list_device_files,
+            device_files::pull_device_file,
+            device_files::pull_apk,
+            policy::refresh_policy,
+            policy::get_active_policy,
+            policy::set_policy_location,
+            download::download_apk,
+            download::ack_download_events,
+            session::save_session_context,
+            session::load_session_context,
+            http::test_connectivity,
+            http::get_http_settings,
+            http::set_http_settings,
+            adb_coordinator::get_adb_activity,
+            adb_coordinator::restart_adb_server,
+            device_prefs::get_device_preferences,
+            device_prefs::list_device_preferences,
+            device_prefs::set_device_preferences,
+            fleet::get_fleet_status,
+            fleet::update_outdated,
+            library::analyze_folder,
+            library::cancel_folder_scan,
+            support::create_support_bundle,
+            system_install::get_root_capability,
+            system_install::install_as_system,
+            control::get_control_status,
+            control::set_control_settings,
+            health::get_health_status,
+            health::set_health_settings,
+            io_pool::get_runtime_status,
+            io_pool::get_io_pool_settings,
+            io_pool::set_io_pool_settings,
+            io_pool::hash_file
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                let report = app.state::<shutdown::ShutdownCoordinator>().run(shutdown::GRACE_PERIOD);
+                shutdown::write_report(&app.state::<paths::AppPaths>(), &report);
+            }
+        });
+}
+
+/// `cargo test --features export-bindings` 时检查已提交的 `src/bindings` 是否过期：
+/// 以 ts-rs 生成的 `export_bindings_*` 测试把类型重新导出到临时目录，再与已提交的文件逐个比较
+#[cfg(all(test, feature = "export-bindings"))]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet};
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
+
+    fn ts_files(root: &Path, dir: &Path, files: &mut BTreeMap<PathBuf, String>) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if path.is_dir() {
+                ts_files(root, &path, files);
+            } else if path.extension().is_some_and(|ext| ext == "ts") {
+                let relative = path.strip_prefix(root).unwrap().to_path_buf();
+                files.insert(relative, fs::read_to_string(&path).unwrap_or_default());
+            }
+        }
+    }
+
+    #[test]
+    fn committed_bindings_are_up_to_date() {
+        let committed = Path::new(env!("CARGO_MANIFEST_DIR")).join("../src/bindings");
+        let fresh = tempfile::tempdir().unwrap();
+        let status = Command::new(std::env::current_exe().unwrap())
+            .args(["export_bindings_", "--quiet", "--test-threads=1"])
+            .env("TS_RS_EXPORT_DIR", fresh.path())
+            .status()
+            .unwrap();
+        assert!(status.success(), "导出类型定义失败");
+
+        let (mut expected, mut actual) = (BTreeMap::new(), BTreeMap::new());
+        ts_files(fresh.path(), fresh.path(), &mut expected);
+        ts_files(&committed, &committed, &mut actual);
+        assert!(!expected.is_empty(), "没有导出任何类型定义");
+        let paths: BTreeSet<&PathBuf> = expected.keys().chain(actual.keys()).collect();
+        let stale: Vec<String> = paths
+            .into_iter()
+            .filter(|path| expected.get(*path) != actual.get(*path))
+            .map(|path| path.display().to_string())
+            .collect();
+        assert!(stale.is_empty(), "src/bindings 与 Rust 类型不一致，请运行 npm run bindings 后提交: {}", stale.join(", "));
+    }
 }
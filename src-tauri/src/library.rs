@@ -0,0 +1,465 @@
+//! 文件夹中 APK 的批量分析（大小、修改时间、SHA-256、包名与版本），供库视图使用。
+//! 多个工作线程并行计算，结果按批推送 `folder-scan-progress` 事件；
+//! 以（路径, 大小, 修改时间）为键持久化缓存，再次打开时未变化的文件直接使用缓存。
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use tauri::{Emitter, Manager};
+
+use crate::input_path::{self, Expect};
+use crate::jobs::CancelToken;
+use crate::log_batch::DEFAULT_MAX_INTERVAL;
+use crate::paths::{self, AppPaths};
+use crate::{axml, hash};
+
+const CACHE_FILE: &str = "apk_metadata_cache.json";
+/// 缓存条目上限，超过时淘汰最久未使用的
+const MAX_CACHE_ENTRIES: usize = 5000;
+/// 工作线程数上限
+const MAX_WORKERS: usize = 8;
+/// 一批事件最多包含的条目
+const MAX_BATCH: usize = 32;
+
+/// 一个 APK 的分析结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct ApkSummary {
+    pub path: String,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub size: u64,
+    /// 修改时间（Unix 时间戳，秒）
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub mtime: u64,
+    pub sha256: Option<String>,
+    pub package: Option<String>,
+    pub version_name: Option<String>,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number | null"))]
+    pub version_code: Option<i64>,
+    /// 读取或解析失败的原因
+    pub error: Option<String>,
+    /// 是否来自缓存
+    #[serde(default)]
+    pub cached: bool,
+}
+
+/// 一批分析结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct FolderScanBatch {
+    pub scan_id: String,
+    pub entries: Vec<ApkSummary>,
+    pub completed: usize,
+    pub total: usize,
+}
+
+/// 一次扫描的汇总
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct FolderScanSummary {
+    pub scan_id: String,
+    pub total: usize,
+    pub cached: usize,
+    pub analyzed: usize,
+    pub failed: usize,
+    pub cancelled: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct CacheEntry {
+    summary: ApkSummary,
+    /// 最近一次使用的时间（Unix 时间戳，秒），用于淘汰
+    last_used: u64,
+}
+
+/// 进行中的扫描（托管状态）
+#[derive(Default)]
+pub struct FolderScans {
+    scans: Mutex<HashMap<String, CancelToken>>,
+    /// 保存缓存时持有，避免并发扫描互相覆盖
+    cache_lock: Mutex<()>,
+}
+
+impl FolderScans {
+    /// 取消所有进行中的扫描（退出时使用）
+    pub fn cancel_all(&self) {
+        for token in self.scans.lock().unwrap().values() {
+            token.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// 等待正在进行的缓存写入完成
+    pub fn sync(&self) {
+        drop(self.cache_lock.lock().unwrap());
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn load_cache(paths: &AppPaths) -> HashMap<String, CacheEntry> {
+    fs::read_to_string(paths.data_dir.join(CACHE_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str::<Vec<CacheEntry>>(&content).ok())
+        .map(|entries| entries.into_iter().map(|e| (e.summary.path.clone(), e)).collect())
+        .unwrap_or_default()
+}
+
+/// 超过上限时按最近使用时间淘汰，然后原子写入
+fn save_cache(paths: &AppPaths, cache: HashMap<String, CacheEntry>) -> Result<(), String> {
+    let mut entries: Vec<CacheEntry> = cache.into_values().collect();
+    if entries.len() > MAX_CACHE_ENTRIES {
+        entries.sort_by_key(|e| std::cmp::Reverse(e.last_used));
+        entries.truncate(MAX_CACHE_ENTRIES);
+    }
+    let content = serde_json::to_vec(&entries).map_err(|e| e.to_string())?;
+    paths::write_atomic(&paths.data_dir.join(CACHE_FILE), &content).map_err(|e| format!("保存分析缓存失败: {}", e))
+}
+
+/// 缓存条目与文件当前的大小、修改时间一致时可用
+fn cache_hit(cache: &HashMap<String, CacheEntry>, path: &str, size: u64, mtime: u64) -> Option<ApkSummary> {
+    cache
+        .get(path)
+        .map(|entry| &entry.summary)
+        .filter(|summary| summary.size == size && summary.mtime == mtime && summary.error.is_none())
+        .map(|summary| ApkSummary { cached: true, ..summary.clone() })
+}
+
+/// 目录下的 APK 文件；`recursive` 时进入子目录（不跟随符号链接）
+fn collect_apks(dir: &Path, recursive: bool, cancel: &AtomicBool, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if cancel.load(Ordering::Relaxed) {
+            return;
+        }
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let path = entry.path();
+        if file_type.is_dir() && recursive {
+            collect_apks(&path, recursive, cancel, out);
+        } else if file_type.is_file() && path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("apk")) {
+            out.push(path);
+        }
+    }
+}
+
+fn file_times(path: &Path) -> Result<(u64, u64), String> {
+    let meta = fs::metadata(path).map_err(|e| format!("读取文件信息失败: {}", e))?;
+    let mtime = meta.modified().ok().and_then(|t| t.duration_since(UNIX_EPOCH).ok()).map(|d| d.as_secs()).unwrap_or(0);
+    Ok((meta.len(), mtime))
+}
+
+/// 计算一个 APK 的摘要；Manifest 直接从二进制读取，不调用外部工具
+fn analyze(path: &Path, size: u64, mtime: u64) -> ApkSummary {
+    let mut summary = ApkSummary {
+        path: path.to_string_lossy().to_string(),
+        size,
+        mtime,
+        sha256: None,
+        package: None,
+        version_name: None,
+        version_code: None,
+        error: None,
+        cached: false,
+    };
+    match hash::sha256_file(path) {
+        Ok(sha256) => summary.sha256 = Some(sha256),
+        Err(e) => {
+            summary.error = Some(e);
+            return summary;
+        }
+    }
+    match axml::read_manifest_from_apk(path) {
+        Ok(elements) => {
+            if let Some(manifest) = elements.into_iter().find(|e| e.name == "manifest") {
+                summary.package = manifest.attr("package").map(axml::AxmlValue::as_string);
+                summary.version_name = manifest.attr("versionName").map(axml::AxmlValue::as_string);
+                summary.version_code = manifest.attr("versionCode").and_then(axml::AxmlValue::as_int);
+            }
+        }
+        Err(e) => summary.error = Some(format!("解析 Manifest 失败: {}", e)),
+    }
+    summary
+}
+
+fn worker_count(files: usize) -> usize {
+    let cpus = thread::available_parallelism().map(|n| n.get()).unwrap_or(2);
+    cpus.min(MAX_WORKERS).min(files).max(1)
+}
+
+/// 由多个工作线程分析待处理的文件，结果发送到 `sender`；取消后各线程在当前文件处理完后退出
+fn analyze_pending(pending: &[(PathBuf, u64, u64)], cancel: &AtomicBool, sender: mpsc::Sender<ApkSummary>) {
+    let next = AtomicUsize::new(0);
+    thread::scope(|scope| {
+        for _ in 0..worker_count(pending.len()) {
+            let (sender, next) = (sender.clone(), &next);
+            scope.spawn(move || loop {
+                if cancel.load(Ordering::Relaxed) {
+                    break;
+                }
+                let Some((path, size, mtime)) = pending.get(next.fetch_add(1, Ordering::Relaxed)) else {
+                    break;
+                };
+                if sender.send(analyze(path, *size, *mtime)).is_err() {
+                    break;
+                }
+            });
+        }
+    });
+}
+
+/// 执行扫描：缓存命中的文件立即返回，其余交给工作线程；收集线程按批推送事件
+fn scan(app: &tauri::AppHandle, paths: &AppPaths, scan_id: &str, dir: &Path, recursive: bool, cancel: &AtomicBool) -> FolderScanSummary {
+    let mut files = Vec::new();
+    collect_apks(dir, recursive, cancel, &mut files);
+    let total = files.len();
+    let cache = load_cache(paths);
+    // 本次扫描得到或用到的条目，保存时合并进缓存
+    let mut updated: HashMap<String, CacheEntry> = HashMap::new();
+    let mut summary = FolderScanSummary { scan_id: scan_id.to_string(), total, cached: 0, analyzed: 0, failed: 0, cancelled: false };
+
+    let (sender, receiver) = mpsc::channel::<ApkSummary>();
+    let mut pending = Vec::new();
+    for path in files {
+        let key = path.to_string_lossy().to_string();
+        match file_times(&path) {
+            Ok((size, mtime)) => match cache_hit(&cache, &key, size, mtime) {
+                Some(hit) => {
+                    let _ = sender.send(hit);
+                }
+                None => pending.push((path, size, mtime)),
+            },
+            Err(e) => {
+                let _ = sender.send(ApkSummary {
+                    path: key,
+                    size: 0,
+                    mtime: 0,
+                    sha256: None,
+                    package: None,
+                    version_name: None,
+                    version_code: None,
+                    error: Some(e),
+                    cached: false,
+                });
+            }
+        }
+    }
+
+    thread::scope(|scope| {
+        let pending = &pending;
+        scope.spawn(move || analyze_pending(pending, cancel, sender));
+
+        // 在当前线程收集结果，按数量或时间间隔合并为一个事件
+        let mut batch: Vec<ApkSummary> = Vec::new();
+        let mut completed = 0;
+        let mut last_emit = Instant::now();
+        let flush = |batch: &mut Vec<ApkSummary>, completed: usize| {
+            if !batch.is_empty() {
+                let entries = std::mem::take(batch);
+                let _ = app.emit("folder-scan-progress", FolderScanBatch { scan_id: scan_id.to_string(), entries, completed, total });
+            }
+        };
+        loop {
+            match receiver.recv_timeout(DEFAULT_MAX_INTERVAL) {
+                Ok(entry) => {
+                    completed += 1;
+                    match (&entry.error, entry.cached) {
+                        (Some(_), _) => summary.failed += 1,
+                        (None, true) => summary.cached += 1,
+                        (None, false) => summary.analyzed += 1,
+                    }
+                    updated.insert(entry.path.clone(), CacheEntry { summary: ApkSummary { cached: false, ..entry.clone() }, last_used: now() });
+                    batch.push(entry);
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+            if batch.len() >= MAX_BATCH || last_emit.elapsed() >= DEFAULT_MAX_INTERVAL {
+                flush(&mut batch, completed);
+                last_emit = Instant::now();
+            }
+        }
+        flush(&mut batch, completed);
+    });
+
+    summary.cancelled = cancel.load(Ordering::Relaxed);
+    // 失败的结果不写入缓存，下次重新分析
+    updated.retain(|_, entry| entry.summary.error.is_none());
+    // 并发的扫描可能已更新过缓存，保存前重新载入再合并
+    let scans = app.state::<FolderScans>();
+    let _guard = scans.cache_lock.lock().unwrap();
+    let mut merged = load_cache(paths);
+    merged.extend(updated);
+    if let Err(e) = save_cache(paths, merged) {
+        crate::emit_progress(app, "library", e);
+    }
+    summary
+}
+
+/// 分析文件夹中的 APK：结果通过 `folder-scan-progress` 事件分批推送，完成后返回汇总。
+/// `scan_id` 由前端生成，用于过滤事件与取消
+#[tauri::command]
+pub async fn analyze_folder(
+    app: tauri::AppHandle,
+    paths: tauri::State<'_, AppPaths>,
+    scans: tauri::State<'_, FolderScans>,
+    scan_id: String,
+    dir: String,
+    recursive: Option<bool>,
+) -> Result<FolderScanSummary, String> {
+    let dir = input_path::arg(&dir, Expect::Dir).map_err(|e| e.to_string())?;
+    let root = PathBuf::from(&dir);
+    if !root.is_dir() {
+        return Err(format!("不是文件夹: {}", dir));
+    }
+    let cancel: CancelToken = Arc::new(AtomicBool::new(false));
+    scans.scans.lock().unwrap().insert(scan_id.clone(), cancel.clone());
+    let paths = paths.inner().clone();
+    let task = {
+        let (app, scan_id) = (app.clone(), scan_id.clone());
+        tauri::async_runtime::spawn_blocking(move || scan(&app, &paths, &scan_id, &root, recursive.unwrap_or(false), &cancel))
+    };
+    let result = task.await.map_err(|e| format!("分析线程异常退出: {}", e));
+    scans.scans.lock().unwrap().remove(&scan_id);
+    result
+}
+
+/// 取消进行中的文件夹分析（如离开库视图时）；工作线程在当前文件处理完后退出
+#[tauri::command]
+pub fn cancel_folder_scan(scans: tauri::State<'_, FolderScans>, scan_id: String) -> bool {
+    match scans.scans.lock().unwrap().get(&scan_id) {
+        Some(cancel) => {
+            cancel.store(true, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const APK: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/repackaging/already-repackaged.apk");
+
+    fn entry(path: &str, size: u64, mtime: u64, last_used: u64) -> CacheEntry {
+        let summary = ApkSummary {
+            path: path.to_string(),
+            size,
+            mtime,
+            sha256: Some("ab".to_string()),
+            package: Some("com.vendor.scanner".to_string()),
+            version_name: None,
+            version_code: None,
+            error: None,
+            cached: false,
+        };
+        CacheEntry { summary, last_used }
+    }
+
+    #[test]
+    fn cache_hits_need_matching_size_and_mtime() {
+        let mut cache = HashMap::new();
+        cache.insert("/a.apk".to_string(), entry("/a.apk", 10, 100, 0));
+        let mut failed = entry("/b.apk", 10, 100, 0);
+        failed.summary.error = Some("解析 Manifest 失败".to_string());
+        cache.insert("/b.apk".to_string(), failed);
+
+        let hit = cache_hit(&cache, "/a.apk", 10, 100).unwrap();
+        assert!(hit.cached);
+        assert_eq!(hit.package.as_deref(), Some("com.vendor.scanner"));
+        assert!(cache_hit(&cache, "/a.apk", 11, 100).is_none());
+        assert!(cache_hit(&cache, "/a.apk", 10, 101).is_none());
+        assert!(cache_hit(&cache, "/b.apk", 10, 100).is_none());
+        assert!(cache_hit(&cache, "/c.apk", 10, 100).is_none());
+    }
+
+    #[test]
+    fn saved_cache_keeps_the_most_recently_used_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = AppPaths::portable_at(dir.path());
+        fs::create_dir_all(&paths.data_dir).unwrap();
+        assert!(load_cache(&paths).is_empty());
+
+        let cache: HashMap<String, CacheEntry> = (0..MAX_CACHE_ENTRIES + 2)
+            .map(|i| {
+                let path = format!("/apks/{}.apk", i);
+                (path.clone(), entry(&path, 1, 1, i as u64))
+            })
+            .collect();
+        save_cache(&paths, cache).unwrap();
+        let loaded = load_cache(&paths);
+        assert_eq!(loaded.len(), MAX_CACHE_ENTRIES);
+        assert!(!loaded.contains_key("/apks/0.apk") && !loaded.contains_key("/apks/1.apk"));
+        assert!(loaded.contains_key("/apks/2.apk"));
+
+        fs::write(paths.data_dir.join(CACHE_FILE), "{").unwrap();
+        assert!(load_cache(&paths).is_empty());
+    }
+
+    #[test]
+    fn collects_apks_recursively_only_when_asked() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("sub")).unwrap();
+        for name in ["a.apk", "B.APK", "notes.txt", "sub/c.apk"] {
+            fs::write(dir.path().join(name), b"").unwrap();
+        }
+        let names = |recursive: bool, cancel: bool| {
+            let mut out = Vec::new();
+            collect_apks(dir.path(), recursive, &AtomicBool::new(cancel), &mut out);
+            let mut names: Vec<String> = out.iter().map(|p| p.strip_prefix(dir.path()).unwrap().to_string_lossy().replace('\\', "/")).collect();
+            names.sort();
+            names
+        };
+        assert_eq!(names(false, false), ["B.APK", "a.apk"]);
+        assert_eq!(names(true, false), ["B.APK", "a.apk", "sub/c.apk"]);
+        assert!(names(true, true).is_empty());
+    }
+
+    #[test]
+    fn analyze_reads_the_manifest_and_reports_failures() {
+        let (size, mtime) = file_times(Path::new(APK)).unwrap();
+        let summary = analyze(Path::new(APK), size, mtime);
+        assert_eq!(summary.error, None);
+        assert_eq!(summary.package.as_deref(), Some("com.nlscan.scanner"));
+        assert_eq!(summary.sha256, Some(hash::sha256_file(Path::new(APK)).unwrap()));
+        assert!(!summary.cached);
+
+        let dir = tempfile::tempdir().unwrap();
+        let broken = dir.path().join("broken.apk");
+        fs::write(&broken, b"not a zip").unwrap();
+        let summary = analyze(&broken, 9, 0);
+        assert!(summary.sha256.is_some());
+        assert!(summary.error.unwrap().starts_with("解析 Manifest 失败"));
+
+        let summary = analyze(&dir.path().join("missing.apk"), 0, 0);
+        assert!(summary.sha256.is_none() && summary.error.is_some());
+        assert!(file_times(&dir.path().join("missing.apk")).is_err());
+    }
+
+    #[test]
+    fn workers_analyze_every_pending_file_until_cancelled() {
+        let (size, mtime) = file_times(Path::new(APK)).unwrap();
+        let pending: Vec<(PathBuf, u64, u64)> = (0..5).map(|_| (PathBuf::from(APK), size, mtime)).collect();
+
+        let (sender, receiver) = mpsc::channel();
+        analyze_pending(&pending, &AtomicBool::new(false), sender);
+        let results: Vec<ApkSummary> = receiver.iter().collect();
+        assert_eq!(results.len(), 5);
+        assert!(results.iter().all(|r| r.package.as_deref() == Some("com.nlscan.scanner")));
+
+        let (sender, receiver) = mpsc::channel();
+        analyze_pending(&pending, &AtomicBool::new(true), sender);
+        assert_eq!(receiver.iter().count(), 0);
+    }
+}
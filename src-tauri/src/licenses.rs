@@ -0,0 +1,238 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Read;
+
+use crate::analysis::contains_bytes;
+use crate::input_path::{self, Expect};
+use crate::signing::PipelineError;
+
+/// 单个第三方库的许可证信息
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct LicenseNotice {
+    pub library_name: String,
+    /// 识别出的许可证类型，如 `Apache-2.0`，无法识别时为 `Unknown`
+    pub license_type: String,
+    /// 许可证原文；从 DEX 推断的条目为检测依据
+    pub text: String,
+}
+
+/// 许可证原文中的关键字 → SPDX 标识，按匹配优先级排列
+const LICENSE_KEYWORDS: &[(&str, &str)] = &[
+    ("apache license", "Apache-2.0"),
+    ("apache-2.0", "Apache-2.0"),
+    ("gnu lesser general public license", "LGPL"),
+    ("gnu general public license", "GPL"),
+    ("mozilla public license", "MPL-2.0"),
+    ("eclipse public license", "EPL"),
+    ("bsd 3-clause", "BSD-3-Clause"),
+    ("bsd 2-clause", "BSD-2-Clause"),
+    ("redistribution and use in source and binary forms", "BSD"),
+    ("mit license", "MIT"),
+    ("permission is hereby granted, free of charge", "MIT"),
+    ("creative commons", "CC"),
+];
+
+/// DEX 中可识别的 SDK 类型描述符：(描述符, 库名, 许可证)
+const KNOWN_SDKS: &[(&str, &str, &str)] = &[
+    ("Lcom/google/android/gms/ads/MobileAds;", "Google Mobile Ads SDK", "Proprietary"),
+    ("Lokhttp3/OkHttpClient;", "OkHttp", "Apache-2.0"),
+    ("Lretrofit2/Retrofit;", "Retrofit", "Apache-2.0"),
+    ("Lcom/google/gson/Gson;", "Gson", "Apache-2.0"),
+    ("Lcom/bumptech/glide/Glide;", "Glide", "BSD-2-Clause"),
+    ("Lcom/squareup/picasso/Picasso;", "Picasso", "Apache-2.0"),
+    ("Lio/reactivex/rxjava3/core/Observable;", "RxJava 3", "Apache-2.0"),
+    ("Lio/reactivex/Observable;", "RxJava 2", "Apache-2.0"),
+    ("Lkotlin/KotlinVersion;", "Kotlin Standard Library", "Apache-2.0"),
+    ("Lkotlinx/coroutines/CoroutineScope;", "kotlinx.coroutines", "Apache-2.0"),
+    ("Lcom/facebook/fresco/", "Fresco", "MIT"),
+    ("Lcom/airbnb/lottie/LottieAnimationView;", "Lottie", "Apache-2.0"),
+    ("Lorg/greenrobot/eventbus/EventBus;", "EventBus", "Apache-2.0"),
+    ("Lcom/tencent/mmkv/MMKV;", "MMKV", "BSD-3-Clause"),
+];
+
+fn license_type_of(text: &str) -> String {
+    let lower = text.to_lowercase();
+    LICENSE_KEYWORDS
+        .iter()
+        .find(|(keyword, _)| lower.contains(keyword))
+        .map(|(_, spdx)| spdx.to_string())
+        .unwrap_or_else(|| "Unknown".to_string())
+}
+
+fn notice(library_name: &str, text: &str) -> LicenseNotice {
+    LicenseNotice {
+        library_name: library_name.trim().to_string(),
+        license_type: license_type_of(text),
+        text: text.trim().to_string(),
+    }
+}
+
+fn is_license_entry(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    let file = lower.rsplit('/').next().unwrap_or(&lower);
+    (lower.starts_with("assets/") && (file.contains("license") || file.contains("notice")))
+        || (lower.starts_with("res/raw") && file.contains("license"))
+}
+
+/// 去掉 HTML 标签并还原常见实体
+fn strip_tags(html: &str) -> String {
+    let text = regex::Regex::new(r"(?s)<[^>]*>").unwrap().replace_all(html, "");
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+/// 按 `<h1>`~`<h4>` 标题切分许可证页面，标题视为库名，其后内容为许可证原文
+pub fn parse_license_html(html: &str) -> Vec<LicenseNotice> {
+    let heading = regex::Regex::new(r"(?is)<h[1-4][^>]*>(.*?)</h[1-4]>").unwrap();
+    let headings: Vec<_> = heading.captures_iter(html).collect();
+    headings
+        .iter()
+        .enumerate()
+        .filter_map(|(i, caps)| {
+            let whole = caps.get(0)?;
+            let end = headings.get(i + 1).and_then(|next| next.get(0)).map(|m| m.start()).unwrap_or(html.len());
+            let name = strip_tags(&caps[1]);
+            let text = strip_tags(&html[whole.end()..end]);
+            (!name.trim().is_empty()).then(|| notice(&name, &text))
+        })
+        .collect()
+}
+
+/// 解析 play-services-oss-licenses 的格式：
+/// `third_party_license_metadata` 每行为 `偏移:长度 库名`，指向 `third_party_licenses` 中的原文
+pub fn parse_oss_metadata(metadata: &str, licenses: &[u8]) -> Vec<LicenseNotice> {
+    metadata
+        .lines()
+        .filter_map(|line| {
+            let (range, name) = line.split_once(' ')?;
+            let (offset, len) = range.split_once(':')?;
+            let start: usize = offset.parse().ok()?;
+            let end = start.checked_add(len.parse().ok()?)?;
+            let text = String::from_utf8_lossy(licenses.get(start..end)?);
+            Some(notice(name, &text))
+        })
+        .collect()
+}
+
+/// 从 APK 中提取第三方库的许可证声明：读取常见的许可证资源，并根据 DEX 中的类型补充已知 SDK
+#[tauri::command]
+pub fn extract_open_source_notices(apk_path: String) -> Result<Vec<LicenseNotice>, PipelineError> {
+    let apk_path = input_path::arg(&apk_path, Expect::File)?;
+    let mut archive = zip::ZipArchive::new(fs::File::open(&apk_path)?)?;
+    let mut notices = Vec::new();
+    let mut oss_metadata = None;
+    let mut oss_licenses = None;
+    let mut dex_data = Vec::new();
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        let name = entry.name().to_string();
+        let file = name.rsplit('/').next().unwrap_or(&name).to_string();
+        let is_dex = name.starts_with("classes") && name.ends_with(".dex") && !name.contains('/');
+        if !(is_dex || is_license_entry(&name)) {
+            continue;
+        }
+
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data)?;
+        if is_dex {
+            dex_data.push(data);
+        } else if file.starts_with("third_party_license_metadata") {
+            oss_metadata = Some(String::from_utf8_lossy(&data).to_string());
+        } else if file.starts_with("third_party_licenses") && name.starts_with("res/raw") {
+            oss_licenses = Some(data);
+        } else {
+            let content = String::from_utf8_lossy(&data);
+            if file.ends_with(".html") || file.ends_with(".htm") {
+                notices.extend(parse_license_html(&content));
+            } else {
+                notices.push(notice(&file, &content));
+            }
+        }
+    }
+
+    if let (Some(metadata), Some(licenses)) = (&oss_metadata, &oss_licenses) {
+        notices.extend(parse_oss_metadata(metadata, licenses));
+    }
+
+    for (descriptor, library, license) in KNOWN_SDKS {
+        let already_listed = notices.iter().any(|n| n.library_name.eq_ignore_ascii_case(library));
+        if !already_listed && dex_data.iter().any(|dex| contains_bytes(dex, descriptor.as_bytes())) {
+            notices.push(LicenseNotice {
+                library_name: library.to_string(),
+                license_type: license.to_string(),
+                text: format!("在 DEX 中检测到 {}", descriptor),
+            });
+        }
+    }
+    Ok(notices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// assets 中的 HTML 许可证页与 NOTICE、res/raw 中的 oss-licenses 数据，DEX 中引用了 OkHttp、Gson 与 Kotlin
+    const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/licenses.apk");
+
+    #[test]
+    fn extracts_notices_from_the_fixture() {
+        let notices = extract_open_source_notices(FIXTURE.to_string()).unwrap();
+        let summary: Vec<(&str, &str)> = notices.iter().map(|n| (n.library_name.as_str(), n.license_type.as_str())).collect();
+        assert_eq!(
+            summary,
+            [
+                ("Gson", "Apache-2.0"),
+                ("Glide & Friends", "BSD"),
+                ("NOTICE.txt", "MPL-2.0"),
+                ("Timber", "MIT"),
+                ("JNA", "LGPL"),
+                ("OkHttp", "Apache-2.0"),
+                ("Kotlin Standard Library", "Apache-2.0"),
+            ]
+        );
+        assert!(notices[0].text.starts_with("Copyright 2008 Google Inc."));
+        assert!(notices[0].text.contains("(the \"License\")"));
+        assert_eq!(notices[4].text, "GNU LESSER GENERAL PUBLIC LICENSE Version 2.1");
+        assert_eq!(notices[5].text, "在 DEX 中检测到 Lokhttp3/OkHttpClient;");
+    }
+
+    #[test]
+    fn recognizes_license_types() {
+        assert_eq!(license_type_of("Licensed under the Apache License"), "Apache-2.0");
+        assert_eq!(license_type_of("GNU General Public License v3"), "GPL");
+        assert_eq!(license_type_of("GNU Lesser General Public License"), "LGPL");
+        assert_eq!(license_type_of("BSD 3-Clause License"), "BSD-3-Clause");
+        assert_eq!(license_type_of("All rights reserved."), "Unknown");
+    }
+
+    #[test]
+    fn license_entries_are_limited_to_assets_and_raw_resources() {
+        assert!(is_license_entry("assets/open_source_licenses.html"));
+        assert!(is_license_entry("assets/third_party_licenses"));
+        assert!(is_license_entry("assets/legal/NOTICE"));
+        assert!(is_license_entry("res/raw/third_party_license_metadata"));
+        assert!(is_license_entry("res/raw-v21/licenses.txt"));
+        assert!(!is_license_entry("res/raw/notice.txt"));
+        assert!(!is_license_entry("res/layout/license_screen.xml"));
+        assert!(!is_license_entry("META-INF/LICENSE"));
+    }
+
+    #[test]
+    fn oss_metadata_skips_out_of_range_lines() {
+        let licenses = b"MIT LicenseApache License";
+        let notices = parse_oss_metadata("0:11 Timber\n11:14 OkHttp\n20:99 Broken\n18446744073709551615:2 Overflow\nnoise", licenses);
+        assert_eq!(notices.iter().map(|n| n.library_name.as_str()).collect::<Vec<_>>(), ["Timber", "OkHttp"]);
+        assert_eq!(notices[1].license_type, "Apache-2.0");
+    }
+
+    #[test]
+    fn html_without_headings_yields_nothing() {
+        assert!(parse_license_html("<p>Apache License</p>").is_empty());
+    }
+}
@@ -0,0 +1,168 @@
+//! 日志行的分批与降采样，不依赖 Tauri，处理流程日志、logcat 流与下载进度都可复用。
+//! 行数达到上限或距第一条待发送行超过时间间隔即发出一批；
+//! 已发出但消费方尚未确认的批次过多时进入采样模式，只统计被省略的行数。
+
+use std::mem;
+use std::time::{Duration, Instant};
+
+/// 每批最多行数
+pub const DEFAULT_MAX_LINES: usize = 200;
+/// 每批最长等待时间
+pub const DEFAULT_MAX_INTERVAL: Duration = Duration::from_millis(100);
+/// 未确认批次达到该数量后进入采样模式
+pub const DEFAULT_QUEUE_DEPTH: u64 = 20;
+
+/// 一批待发送的日志
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogChunk<T = String> {
+    /// 从 0 开始递增，消费方按它确认
+    pub seq: u64,
+    pub lines: Vec<T>,
+    /// 采样模式下省略的行数
+    pub omitted: usize,
+}
+
+#[derive(Debug)]
+pub struct LogBatcher<T = String> {
+    max_lines: usize,
+    max_interval: Duration,
+    queue_depth: u64,
+    pending: Vec<T>,
+    omitted: usize,
+    first_pending_at: Option<Instant>,
+    next_seq: u64,
+    /// 已确认的批次数（最大已确认 seq + 1）
+    acked: u64,
+}
+
+impl<T> Default for LogBatcher<T> {
+    fn default() -> Self {
+        LogBatcher::new(DEFAULT_MAX_LINES, DEFAULT_MAX_INTERVAL, DEFAULT_QUEUE_DEPTH)
+    }
+}
+
+impl<T> LogBatcher<T> {
+    pub fn new(max_lines: usize, max_interval: Duration, queue_depth: u64) -> Self {
+        LogBatcher {
+            max_lines: max_lines.max(1),
+            max_interval,
+            queue_depth: queue_depth.max(1),
+            pending: Vec::new(),
+            omitted: 0,
+            first_pending_at: None,
+            next_seq: 0,
+            acked: 0,
+        }
+    }
+
+    /// 消费方处理不过来，新行只计数不发送
+    pub fn sampling(&self) -> bool {
+        self.next_seq - self.acked >= self.queue_depth
+    }
+
+    /// 加入一行，需要发送时返回一批
+    pub fn push(&mut self, line: T, now: Instant) -> Option<LogChunk<T>> {
+        if self.sampling() {
+            self.omitted += 1;
+        } else {
+            self.pending.push(line);
+        }
+        self.first_pending_at.get_or_insert(now);
+        if self.pending.len() >= self.max_lines {
+            return self.flush();
+        }
+        self.tick(now)
+    }
+
+    /// 定时调用：待发送的行等待超过时间间隔时返回一批
+    pub fn tick(&mut self, now: Instant) -> Option<LogChunk<T>> {
+        match self.first_pending_at {
+            Some(first) if now.duration_since(first) >= self.max_interval => self.flush(),
+            _ => None,
+        }
+    }
+
+    /// 立即发出所有待发送的行（如命令结束时）
+    pub fn flush(&mut self) -> Option<LogChunk<T>> {
+        self.first_pending_at = None;
+        if self.pending.is_empty() && self.omitted == 0 {
+            return None;
+        }
+        let chunk = LogChunk { seq: self.next_seq, lines: mem::take(&mut self.pending), omitted: mem::take(&mut self.omitted) };
+        self.next_seq += 1;
+        Some(chunk)
+    }
+
+    /// 消费方确认已处理到 `seq`（含）
+    pub fn ack(&mut self, seq: u64) {
+        self.acked = self.acked.max((seq + 1).min(self.next_seq));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(n: usize) -> impl Iterator<Item = String> {
+        (0..n).map(|i| format!("line {}", i))
+    }
+
+    #[test]
+    fn burst_is_split_at_the_line_limit() {
+        let mut batcher = LogBatcher::new(200, Duration::from_secs(1), 100);
+        let now = Instant::now();
+        let chunks: Vec<LogChunk> = lines(500).filter_map(|line| batcher.push(line, now)).collect();
+        assert_eq!(chunks.iter().map(|c| c.lines.len()).collect::<Vec<_>>(), [200, 200]);
+        assert_eq!(chunks.iter().map(|c| c.seq).collect::<Vec<_>>(), [0, 1]);
+
+        let rest = batcher.flush().unwrap();
+        assert_eq!((rest.seq, rest.lines.len(), rest.omitted), (2, 100, 0));
+        assert_eq!(rest.lines.last().map(String::as_str), Some("line 499"));
+        assert!(batcher.flush().is_none());
+    }
+
+    #[test]
+    fn steady_output_is_sent_by_interval() {
+        let mut batcher = LogBatcher::new(200, Duration::from_millis(100), 2);
+        let start = Instant::now();
+        let mut chunks = Vec::new();
+        for (i, line) in lines(20).enumerate() {
+            if let Some(chunk) = batcher.push(line, start + Duration::from_millis(30 * i as u64)) {
+                batcher.ack(chunk.seq);
+                chunks.push(chunk);
+            }
+        }
+        // 每 120ms 发出一批（如 0、30、60、90、120 共 5 行），消费方及时确认，不会进入采样
+        assert_eq!(chunks.iter().map(|c| c.lines.len()).collect::<Vec<_>>(), [5, 5, 5, 5]);
+        assert!(chunks.iter().all(|c| c.omitted == 0));
+        assert!(!batcher.sampling());
+
+        // 输出停下后由定时器发出剩余的行
+        assert!(batcher.push("tail".to_string(), start + Duration::from_millis(600)).is_none());
+        assert!(batcher.tick(start + Duration::from_millis(650)).is_none());
+        assert_eq!(batcher.tick(start + Duration::from_millis(700)).unwrap().lines, ["tail"]);
+    }
+
+    #[test]
+    fn unacknowledged_batches_switch_to_sampling() {
+        let mut batcher = LogBatcher::new(10, Duration::from_secs(1), 2);
+        let now = Instant::now();
+        let sent: Vec<LogChunk> = lines(20).filter_map(|line| batcher.push(line, now)).collect();
+        assert_eq!(sent.len(), 2);
+        assert!(batcher.sampling());
+
+        for line in lines(1200) {
+            assert!(batcher.push(line, now).is_none());
+        }
+        let sampled = batcher.flush().unwrap();
+        assert!(sampled.lines.is_empty());
+        assert_eq!(sampled.omitted, 1200);
+
+        // 消费方追上后恢复正常发送
+        batcher.ack(sampled.seq);
+        assert!(!batcher.sampling());
+        batcher.push("after".to_string(), now);
+        let resumed = batcher.flush().unwrap();
+        assert_eq!((resumed.lines.as_slice(), resumed.omitted), (["after".to_string()].as_slice(), 0));
+    }
+}
@@ -0,0 +1,449 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::input_path::{self, Expect};
+use crate::signing::PipelineError;
+
+/// 危险权限及其较低权限的替代
+const PERMISSION_DOWNGRADES: &[(&str, &str)] = &[
+    ("android.permission.ACCESS_FINE_LOCATION", "android.permission.ACCESS_COARSE_LOCATION"),
+    ("android.permission.WRITE_EXTERNAL_STORAGE", "android.permission.READ_EXTERNAL_STORAGE"),
+    ("android.permission.WRITE_CONTACTS", "android.permission.READ_CONTACTS"),
+    ("android.permission.WRITE_CALENDAR", "android.permission.READ_CALENDAR"),
+    ("android.permission.WRITE_CALL_LOG", "android.permission.READ_CALL_LOG"),
+    ("android.permission.BODY_SENSORS_BACKGROUND", "android.permission.BODY_SENSORS"),
+];
+
+/// Android 属性所在的命名空间
+pub const ANDROID_NS: &str = "http://schemas.android.com/apk/res/android";
+
+/// 根元素上绑定到 Android 命名空间的前缀。
+/// 部分工具生成的 APK 反编译后声明的是 `xmlns:n1="..."`，属性写作 `n1:name` 而不是 `android:name`
+pub fn android_prefix(manifest: &str) -> Option<String> {
+    let start = manifest.find("<manifest")?;
+    let end = start + manifest[start..].find('>')?;
+    let re = regex::Regex::new(&format!(r#"xmlns:([A-Za-z_][\w.-]*)\s*=\s*"{}""#, regex::escape(ANDROID_NS))).unwrap();
+    re.captures(&manifest[start..end]).map(|c| c[1].to_string())
+}
+
+/// 带实际前缀的 Android 属性名；未声明命名空间时按 `android` 处理
+pub fn android_attr(manifest: &str, local_name: &str) -> String {
+    format!("{}:{}", android_prefix(manifest).as_deref().unwrap_or("android"), local_name)
+}
+
+fn permission_regex(manifest: &str, permission: &str) -> regex::Regex {
+    regex::Regex::new(&format!(
+        r#"[ \t]*<uses-permission\b[^>]*\b{}="{}"[^>]*/>[ \t]*\r?\n?"#,
+        regex::escape(&android_attr(manifest, "name")),
+        regex::escape(permission)
+    ))
+    .unwrap()
+}
+
+/// Manifest 中是否已声明该权限
+pub fn has_permission(manifest: &str, permission: &str) -> bool {
+    permission_regex(manifest, permission).is_match(manifest)
+}
+
+/// 在 `<application` 之前插入一条权限声明
+fn insert_permission(manifest: &mut String, permission: &str) {
+    let line = format!("<uses-permission {}=\"{}\"/>\n    ", android_attr(manifest, "name"), permission);
+    match manifest.find("<application") {
+        Some(pos) => manifest.insert_str(pos, &line),
+        None => {
+            if let Some(pos) = manifest.rfind("</manifest>") {
+                manifest.insert_str(pos, &line);
+            }
+        }
+    }
+}
+
+/// 补齐缺失的权限声明，返回新增数量（重复调用不会重复添加）
+pub fn add_missing_permissions(manifest: &mut String, required: &[String]) -> u32 {
+    let mut added = 0;
+    for permission in required {
+        let permission = permission.trim();
+        if permission.is_empty() || has_permission(manifest, permission) {
+            continue;
+        }
+        insert_permission(manifest, permission);
+        added += 1;
+    }
+    added
+}
+
+/// 将危险权限替换为较低权限的替代，返回被降级的权限
+pub fn downgrade_permissions(manifest: &mut String) -> Vec<String> {
+    let mut downgraded = Vec::new();
+    for (from, to) in PERMISSION_DOWNGRADES {
+        let re = permission_regex(manifest, from);
+        if !re.is_match(manifest) {
+            continue;
+        }
+        let replaced = re.replace_all(manifest, "").to_string();
+        *manifest = replaced;
+        if !has_permission(manifest, to) {
+            insert_permission(manifest, to);
+        }
+        downgraded.push(from.to_string());
+    }
+    downgraded
+}
+
+/// 编辑后检查：要求的权限都已声明、被降级的权限都已移除。
+/// 命名空间前缀不符等原因导致编辑没有生效时返回错误，而不是当作成功
+pub fn verify_permissions(manifest: &str, required: &[String], downgraded: &[String]) -> Result<(), String> {
+    let missing: Vec<&str> = required.iter().map(|p| p.trim()).filter(|p| !p.is_empty() && !has_permission(manifest, p)).collect();
+    if !missing.is_empty() {
+        return Err(format!("权限声明未能写入 Manifest: {}", missing.join(", ")));
+    }
+    let remaining: Vec<&str> = downgraded.iter().map(String::as_str).filter(|p| has_permission(manifest, p)).collect();
+    if !remaining.is_empty() {
+        return Err(format!("权限未能从 Manifest 中移除: {}", remaining.join(", ")));
+    }
+    Ok(())
+}
+
+/// 确保 Manifest 声明了所有指定权限，返回新增数量
+#[tauri::command]
+pub fn ensure_permissions_present(manifest_path: String, required_permissions: Vec<String>) -> Result<u32, PipelineError> {
+    let manifest_path = input_path::arg(&manifest_path, Expect::File)?;
+    let mut manifest = fs::read_to_string(&manifest_path)?;
+    let added = add_missing_permissions(&mut manifest, &required_permissions);
+    verify_permissions(&manifest, &required_permissions, &[]).map_err(PipelineError::InvalidInput)?;
+    if added > 0 {
+        fs::write(&manifest_path, manifest)?;
+    }
+    Ok(added)
+}
+
+/// 将 Manifest 中的危险权限降级为低权限替代，返回被降级的权限列表
+#[tauri::command]
+pub fn downgrade_dangerous_permissions(manifest_path: String) -> Result<Vec<String>, PipelineError> {
+    let manifest_path = input_path::arg(&manifest_path, Expect::File)?;
+    let mut manifest = fs::read_to_string(&manifest_path)?;
+    let downgraded = downgrade_permissions(&mut manifest);
+    verify_permissions(&manifest, &[], &downgraded).map_err(PipelineError::InvalidInput)?;
+    if !downgraded.is_empty() {
+        fs::write(&manifest_path, manifest)?;
+    }
+    Ok(downgraded)
+}
+
+/// 应用暴露的 ContentProvider URI
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct ContentUri {
+    pub authority: String,
+    pub path_pattern: Option<String>,
+    pub read_permission: Option<String>,
+    pub write_permission: Option<String>,
+}
+
+/// 读取标签文本中的属性值
+pub fn xml_attr(tag: &str, name: &str) -> Option<String> {
+    let re = regex::Regex::new(&format!(r#"\b{}="([^"]*)""#, regex::escape(name))).ok()?;
+    re.captures(tag).map(|c| c[1].to_string())
+}
+
+fn provider_uris(manifest: &str) -> Vec<ContentUri> {
+    let provider_re = regex::Regex::new(r"(?s)<provider\b([^>]*?)(?:/>|>(.*?)</provider>)").unwrap();
+    let path_re = regex::Regex::new(r"<path-permission\b[^>]*>").unwrap();
+    let prefix = android_prefix(manifest).unwrap_or_else(|| "android".to_string());
+    let attr = |local: &str| format!("{}:{}", prefix, local);
+    let mut uris = Vec::new();
+
+    for cap in provider_re.captures_iter(manifest) {
+        let attrs = &cap[1];
+        let Some(authorities) = xml_attr(attrs, &attr("authorities")) else {
+            continue;
+        };
+        let permission = xml_attr(attrs, &attr("permission"));
+        let read = xml_attr(attrs, &attr("readPermission")).or_else(|| permission.clone());
+        let write = xml_attr(attrs, &attr("writePermission")).or_else(|| permission.clone());
+        let body = cap.get(2).map(|m| m.as_str()).unwrap_or("");
+
+        for authority in authorities.split(';').map(str::trim).filter(|a| !a.is_empty()) {
+            uris.push(ContentUri {
+                authority: authority.to_string(),
+                path_pattern: None,
+                read_permission: read.clone(),
+                write_permission: write.clone(),
+            });
+            for path_tag in path_re.find_iter(body).map(|m| m.as_str()) {
+                let path = xml_attr(path_tag, &attr("path"))
+                    .or_else(|| xml_attr(path_tag, &attr("pathPrefix")).map(|p| format!("{}*", p)))
+                    .or_else(|| xml_attr(path_tag, &attr("pathPattern")));
+                let path_permission = xml_attr(path_tag, &attr("permission"));
+                uris.push(ContentUri {
+                    authority: authority.to_string(),
+                    path_pattern: path,
+                    read_permission: xml_attr(path_tag, &attr("readPermission"))
+                        .or_else(|| path_permission.clone())
+                        .or_else(|| read.clone()),
+                    write_permission: xml_attr(path_tag, &attr("writePermission"))
+                        .or(path_permission)
+                        .or_else(|| write.clone()),
+                });
+            }
+        }
+    }
+    uris
+}
+
+fn smali_uris(work_dir: &Path) -> Vec<ContentUri> {
+    let re = regex::Regex::new(r#"const-string(?:/jumbo)? [vp]\d+, "content://([^/"\s]+)(/[^"]*)?""#).unwrap();
+    let mut uris = Vec::new();
+    let Ok(entries) = fs::read_dir(work_dir) else {
+        return uris;
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        if !entry.file_name().to_string_lossy().starts_with("smali") {
+            continue;
+        }
+        for file in walkdir::WalkDir::new(entry.path()).into_iter().filter_map(|e| e.ok()) {
+            if file.path().extension().map(|e| e != "smali").unwrap_or(true) {
+                continue;
+            }
+            let Ok(content) = fs::read_to_string(file.path()) else {
+                continue;
+            };
+            for cap in re.captures_iter(&content) {
+                uris.push(ContentUri {
+                    authority: cap[1].to_string(),
+                    path_pattern: cap.get(2).map(|m| m.as_str().to_string()),
+                    read_permission: None,
+                    write_permission: None,
+                });
+            }
+        }
+    }
+    uris
+}
+
+/// 列出应用的 ContentProvider URI：Manifest 中的 `<provider>`（含 `<path-permission>`）
+/// 以及 smali 中的 `content://` 字符串常量
+#[tauri::command]
+pub fn get_content_provider_uris(work_dir: String) -> Result<Vec<ContentUri>, PipelineError> {
+    let work_dir = input_path::arg(&work_dir, Expect::Dir)?;
+    let root = Path::new(&work_dir);
+    let manifest = fs::read_to_string(root.join("AndroidManifest.xml"))?;
+    let mut uris = provider_uris(&manifest);
+    for uri in smali_uris(root) {
+        if !uris.contains(&uri) {
+            uris.push(uri);
+        }
+    }
+    Ok(uris)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"<?xml version="1.0" encoding="utf-8" standalone="no"?>
+<manifest xmlns:android="http://schemas.android.com/apk/res/android" package="com.example.app">
+    <uses-permission android:name="android.permission.INTERNET"/>
+    <uses-permission android:name="android.permission.ACCESS_FINE_LOCATION"/>
+    <application android:label="@string/app_name">
+        <activity android:name=".MainActivity"/>
+    </application>
+</manifest>
+"#;
+
+    fn write_manifest(dir: &Path, content: &str) -> String {
+        let path = dir.join("AndroidManifest.xml");
+        fs::write(&path, content).unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    fn permissions(list: &[&str]) -> Vec<String> {
+        list.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn ensure_permissions_present_is_idempotent() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_manifest(dir.path(), MANIFEST);
+        let required = permissions(&["android.permission.INTERNET", "android.permission.CAMERA", " android.permission.RECORD_AUDIO ", ""]);
+
+        assert_eq!(ensure_permissions_present(path.clone(), required.clone()).unwrap(), 2);
+        let first = fs::read_to_string(&path).unwrap();
+        assert!(has_permission(&first, "android.permission.CAMERA"));
+        assert!(has_permission(&first, "android.permission.RECORD_AUDIO"));
+        assert_eq!(first.matches("android.permission.INTERNET").count(), 1);
+        // 插在 <application 之前
+        assert!(first.find("android.permission.CAMERA").unwrap() < first.find("<application").unwrap());
+
+        assert_eq!(ensure_permissions_present(path.clone(), required).unwrap(), 0);
+        assert_eq!(fs::read_to_string(&path).unwrap(), first);
+    }
+
+    #[test]
+    fn duplicate_requirements_are_added_once() {
+        let mut manifest = MANIFEST.to_string();
+        let required = permissions(&["android.permission.CAMERA", "android.permission.CAMERA"]);
+        assert_eq!(add_missing_permissions(&mut manifest, &required), 1);
+        assert_eq!(manifest.matches("android.permission.CAMERA").count(), 1);
+    }
+
+    #[test]
+    fn manifest_without_application_gets_permissions_before_the_end_tag() {
+        let mut manifest = r#"<manifest xmlns:android="http://schemas.android.com/apk/res/android" package="a.b"></manifest>"#.to_string();
+        assert_eq!(add_missing_permissions(&mut manifest, &permissions(&["android.permission.CAMERA"])), 1);
+        assert!(manifest.contains(r#"<uses-permission android:name="android.permission.CAMERA"/>"#));
+        assert!(manifest.ends_with("</manifest>"));
+    }
+
+    #[test]
+    fn downgrade_replaces_and_is_idempotent() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_manifest(dir.path(), MANIFEST);
+
+        assert_eq!(downgrade_dangerous_permissions(path.clone()).unwrap(), ["android.permission.ACCESS_FINE_LOCATION"]);
+        let first = fs::read_to_string(&path).unwrap();
+        assert!(!has_permission(&first, "android.permission.ACCESS_FINE_LOCATION"));
+        assert!(has_permission(&first, "android.permission.ACCESS_COARSE_LOCATION"));
+        // 整行移除，不留空行
+        assert!(!first.contains("\n\n"));
+
+        assert!(downgrade_dangerous_permissions(path.clone()).unwrap().is_empty());
+        assert_eq!(fs::read_to_string(&path).unwrap(), first);
+    }
+
+    #[test]
+    fn verify_reports_edits_that_did_not_apply() {
+        let required = permissions(&["android.permission.CAMERA"]);
+        assert!(verify_permissions(MANIFEST, &required, &[]).unwrap_err().contains("android.permission.CAMERA"));
+        let downgraded = permissions(&["android.permission.ACCESS_FINE_LOCATION"]);
+        assert!(verify_permissions(MANIFEST, &[], &downgraded).unwrap_err().contains("ACCESS_FINE_LOCATION"));
+        assert!(verify_permissions(MANIFEST, &permissions(&["android.permission.INTERNET"]), &[]).is_ok());
+    }
+
+    #[test]
+    fn lists_uris_from_several_providers() {
+        let manifest = r#"<manifest xmlns:android="http://schemas.android.com/apk/res/android" package="com.example.app">
+    <application>
+        <provider android:name=".Files" android:authorities="com.example.files;com.example.legacy" android:exported="false"
+            android:grantUriPermissions="true"/>
+        <provider android:name=".Contacts" android:authorities="com.example.contacts"
+            android:readPermission="com.example.READ" android:writePermission="com.example.WRITE">
+            <path-permission android:path="/public" android:readPermission="com.example.READ_PUBLIC"/>
+            <path-permission android:pathPrefix="/admin" android:permission="com.example.ADMIN"/>
+        </provider>
+        <provider android:name=".Shared" android:authorities=" com.example.shared ; " android:permission="com.example.SHARED">
+            <path-permission android:pathPattern="/items/.*"/>
+        </provider>
+        <provider android:name=".Broken"/>
+    </application>
+</manifest>"#;
+        let uri = |authority: &str, path: Option<&str>, read: Option<&str>, write: Option<&str>| ContentUri {
+            authority: authority.to_string(),
+            path_pattern: path.map(str::to_string),
+            read_permission: read.map(str::to_string),
+            write_permission: write.map(str::to_string),
+        };
+        assert_eq!(
+            provider_uris(manifest),
+            [
+                uri("com.example.files", None, None, None),
+                uri("com.example.legacy", None, None, None),
+                uri("com.example.contacts", None, Some("com.example.READ"), Some("com.example.WRITE")),
+                uri("com.example.contacts", Some("/public"), Some("com.example.READ_PUBLIC"), Some("com.example.WRITE")),
+                uri("com.example.contacts", Some("/admin*"), Some("com.example.ADMIN"), Some("com.example.ADMIN")),
+                uri("com.example.shared", None, Some("com.example.SHARED"), Some("com.example.SHARED")),
+                uri("com.example.shared", Some("/items/.*"), Some("com.example.SHARED"), Some("com.example.SHARED")),
+            ]
+        );
+    }
+
+    #[test]
+    fn provider_uris_include_smali_constants_once() {
+        let dir = tempfile::tempdir().unwrap();
+        write_manifest(
+            dir.path(),
+            r#"<manifest xmlns:android="http://schemas.android.com/apk/res/android"><application>
+<provider android:authorities="com.example.files"/><provider android:authorities="com.example.data"/>
+</application></manifest>"#,
+        );
+        let smali = dir.path().join("smali_classes2/com/example");
+        fs::create_dir_all(&smali).unwrap();
+        fs::write(
+            smali.join("Repo.smali"),
+            "const-string v0, \"content://com.example.data/items\"\nconst-string/jumbo p1, \"content://com.other.app\"\nconst-string v2, \"https://example.com\"\n",
+        )
+        .unwrap();
+        fs::write(smali.join("notes.txt"), "const-string v0, \"content://ignored\"").unwrap();
+
+        let uris = get_content_provider_uris(dir.path().to_string_lossy().to_string()).unwrap();
+        let listed: Vec<(&str, Option<&str>)> = uris.iter().map(|u| (u.authority.as_str(), u.path_pattern.as_deref())).collect();
+        assert_eq!(listed, [("com.example.files", None), ("com.example.data", None), ("com.example.data", Some("/items")), ("com.other.app", None)]);
+    }
+
+    const N0: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/namespace/AndroidManifest.n0.xml"));
+    const N1: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/namespace/AndroidManifest.n1.xml"));
+
+    #[test]
+    fn resolves_the_prefix_bound_to_the_android_namespace() {
+        assert_eq!(android_prefix(N0).as_deref(), Some("n0"));
+        assert_eq!(android_prefix(N1).as_deref(), Some("n1"));
+        assert_eq!(android_attr(N1, "name"), "n1:name");
+        assert_eq!(android_prefix(MANIFEST).as_deref(), Some("android"));
+        assert_eq!(android_attr("<manifest package=\"a.b\">", "name"), "android:name");
+    }
+
+    #[test]
+    fn permission_edits_follow_the_resolved_prefix() {
+        for (fixture, prefix) in [(N0, "n0"), (N1, "n1")] {
+            let mut manifest = fixture.to_string();
+            assert!(has_permission(&manifest, "android.permission.INTERNET") == (prefix == "n0"));
+            let required = permissions(&["android.permission.INTERNET", "android.permission.RECORD_AUDIO"]);
+            let expected = if prefix == "n0" { 1 } else { 2 };
+            assert_eq!(add_missing_permissions(&mut manifest, &required), expected);
+            assert!(manifest.contains(&format!(r#"<uses-permission {}:name="android.permission.RECORD_AUDIO"/>"#, prefix)));
+            assert!(!manifest.contains("android:name"));
+
+            let downgraded = downgrade_permissions(&mut manifest);
+            assert_eq!(downgraded.len(), 1, "{}", prefix);
+            assert!(verify_permissions(&manifest, &required, &downgraded).is_ok());
+            // 再执行一次不再有变化
+            let before = manifest.clone();
+            assert_eq!(add_missing_permissions(&mut manifest, &required), 0);
+            assert!(downgrade_permissions(&mut manifest).is_empty());
+            assert_eq!(manifest, before);
+        }
+    }
+
+    #[test]
+    fn literal_android_declarations_do_not_count_in_n1_manifests() {
+        // 按字面 `android:` 写入的声明在 n1 Manifest 中不算数，校验会报错而不是当作成功
+        let mut manifest = N1.replace(r#"<application"#, "<uses-permission android:name=\"android.permission.NFC\"/>\n    <application");
+        let required = permissions(&["android.permission.NFC"]);
+        assert!(!has_permission(&manifest, "android.permission.NFC"));
+        assert_eq!(add_missing_permissions(&mut manifest, &required), 1);
+        assert!(manifest.contains(r#"<uses-permission n1:name="android.permission.NFC"/>"#));
+        assert!(verify_permissions(&manifest, &required, &[]).is_ok());
+    }
+
+    #[test]
+    fn provider_uris_use_the_resolved_prefix() {
+        let n0 = provider_uris(N0);
+        assert_eq!(n0.len(), 1);
+        assert_eq!(n0[0].authority, "com.vendor.scanner.data");
+        assert_eq!(n0[0].read_permission.as_deref(), Some("com.vendor.READ"));
+
+        let n1: Vec<(String, Option<String>, Option<String>)> =
+            provider_uris(N1).into_iter().map(|u| (u.authority, u.path_pattern, u.read_permission)).collect();
+        let share = Some("com.vendor.SHARE".to_string());
+        assert_eq!(
+            n1,
+            [
+                ("com.vendor.terminal.files".to_string(), None, share.clone()),
+                ("com.vendor.terminal.files".to_string(), Some("/logs*".to_string()), Some("com.vendor.READ_LOGS".to_string())),
+                ("com.vendor.terminal.export".to_string(), None, share),
+                ("com.vendor.terminal.export".to_string(), Some("/logs*".to_string()), Some("com.vendor.READ_LOGS".to_string())),
+            ]
+        );
+    }
+}
\ No newline at end of file
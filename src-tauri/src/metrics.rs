@@ -0,0 +1,406 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, BufRead, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Output};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::jobs::JobContext;
+use crate::run_log::RunLog;
+use crate::paths::AppPaths;
+
+const METRICS_FILE: &str = "metrics.jsonl";
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 单个步骤的耗时与资源占用；平台不支持的指标为 null
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct StepMetrics {
+    pub step: String,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub duration_ms: u64,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number | null"))]
+    pub peak_rss_bytes: Option<u64>,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number | null"))]
+    pub bytes_written: Option<u64>,
+    pub exit_code: Option<i32>,
+}
+
+/// 一次处理的完整指标，同时写入历史记录
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct RunMetrics {
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub timestamp: u64,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub apk_size_bytes: u64,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub total_ms: u64,
+    pub steps: Vec<StepMetrics>,
+    /// 本次实际使用的工具版本（旧记录没有该字段）
+    #[serde(default)]
+    pub tool_versions: Vec<crate::tool_versions::ToolVersion>,
+    /// 开始处理前的预估，用于对比预估与实际
+    #[serde(default)]
+    pub estimate: Option<crate::estimate::Estimate>,
+}
+
+/// 按 APK 大小分组的步骤耗时统计
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct StepStats {
+    /// 分组的显示标签（如 `10-50MB`），保留给旧版前端；排序与作图使用下面的字节数
+    pub size_bucket: String,
+    /// 分组的 APK 大小范围 [min, max)，单位字节；最大一组没有上限
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub size_min_bytes: u64,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number | null"))]
+    pub size_max_bytes: Option<u64>,
+    pub step: String,
+    pub runs: usize,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub median_ms: u64,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub p90_ms: u64,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub max_ms: u64,
+}
+
+#[cfg(target_os = "linux")]
+fn sample_peak_rss(pid: u32) -> Option<u64> {
+    let status = fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+    let line = status.lines().find(|l| l.starts_with("VmHWM:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+#[cfg(windows)]
+fn sample_peak_rss(pid: u32) -> Option<u64> {
+    use windows_sys::Win32::Foundation::CloseHandle;
+    use windows_sys::Win32::System::ProcessStatus::{GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS};
+    use windows_sys::Win32::System::Threading::{OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION};
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if handle.is_null() {
+            return None;
+        }
+        let mut counters: PROCESS_MEMORY_COUNTERS = std::mem::zeroed();
+        counters.cb = std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+        let ok = GetProcessMemoryInfo(handle, &mut counters, counters.cb);
+        CloseHandle(handle);
+        (ok != 0).then_some(counters.PeakWorkingSetSize as u64)
+    }
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+fn sample_peak_rss(_pid: u32) -> Option<u64> {
+    None
+}
+
+/// 目录下所有文件的大小之和
+pub fn dir_size(path: &Path) -> u64 {
+    walkdir::WalkDir::new(path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.metadata().ok())
+        .filter(|m| m.is_file())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// 记录每个步骤的指标
+#[derive(Default)]
+pub struct Recorder {
+    started: Option<Instant>,
+    watched: Vec<PathBuf>,
+    steps: Vec<StepMetrics>,
+    job: JobContext,
+}
+
+/// 在后台线程读完子进程的输出管道，避免管道写满阻塞子进程；有运行日志时逐行写入
+fn drain<R: Read + Send + 'static>(pipe: Option<R>, log: Option<Arc<RunLog>>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        let Some(pipe) = pipe else {
+            return buf;
+        };
+        let mut reader = io::BufReader::new(pipe);
+        let mut line = Vec::new();
+        while reader.read_until(b'\n', &mut line).is_ok_and(|n| n > 0) {
+            if let Some(log) = &log {
+                log.line(String::from_utf8_lossy(&line).trim_end());
+            }
+            buf.append(&mut line);
+        }
+        buf
+    })
+}
+
+/// 等待子进程结束；任务被取消时结束子进程并返回 `Interrupted`
+fn wait_child(mut child: Child, job: &JobContext) -> io::Result<Output> {
+    let stdout = drain(child.stdout.take(), job.log.clone());
+    let stderr = drain(child.stderr.take(), job.log.clone());
+    let status = loop {
+        if job.is_cancelled() {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "任务已取消"));
+        }
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        thread::sleep(SAMPLE_INTERVAL);
+    };
+    Ok(Output { status, stdout: stdout.join().unwrap_or_default(), stderr: stderr.join().unwrap_or_default() })
+}
+
+impl Recorder {
+    pub fn new() -> Self {
+        Recorder { started: Some(Instant::now()), ..Default::default() }
+    }
+
+    /// 任务的记录器：取消后正在执行的外部命令会被结束，之后的命令不再启动；工具输出逐行写入运行日志
+    pub fn for_job(job: &JobContext) -> Self {
+        Recorder { job: job.clone(), ..Recorder::new() }
+    }
+
+    /// 设置用于统计写入量的目录
+    pub fn watch_dirs(&mut self, dirs: Vec<PathBuf>) {
+        self.watched = dirs;
+    }
+
+    fn watched_size(&self) -> u64 {
+        self.watched.iter().map(|d| dir_size(d)).sum()
+    }
+
+    /// 向运行日志写入一行说明（非工具输出）
+    pub fn note(&self, line: &str) {
+        if let Some(log) = &self.job.log {
+            log.line(line);
+        }
+    }
+
+    /// 执行外部命令并记录耗时、峰值内存、写入量与退出码
+    pub fn run(&mut self, step: &str, cmd: &mut Command) -> io::Result<Output> {
+        if self.job.is_cancelled() {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "任务已取消"));
+        }
+        if let Some(log) = &self.job.log {
+            log.step(step);
+        }
+        let size_before = self.watched_size();
+        let start = Instant::now();
+        if let Some(output) = crate::injection::intercept(cmd) {
+            self.steps.push(StepMetrics {
+                step: step.to_string(),
+                duration_ms: start.elapsed().as_millis() as u64,
+                peak_rss_bytes: None,
+                bytes_written: None,
+                exit_code: output.as_ref().ok().and_then(|o| o.status.code()),
+            });
+            return output;
+        }
+
+        let child = cmd
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn();
+        let child = match child {
+            Ok(c) => c,
+            Err(e) => {
+                self.steps.push(StepMetrics {
+                    step: step.to_string(),
+                    duration_ms: start.elapsed().as_millis() as u64,
+                    peak_rss_bytes: None,
+                    bytes_written: None,
+                    exit_code: None,
+                });
+                return Err(e);
+            }
+        };
+
+        let pid = child.id();
+        let done = Arc::new(AtomicBool::new(false));
+        let sampler = {
+            let done = done.clone();
+            thread::spawn(move || {
+                let mut peak = None;
+                while !done.load(Ordering::Relaxed) {
+                    if let Some(rss) = sample_peak_rss(pid) {
+                        peak = Some(peak.map_or(rss, |p: u64| p.max(rss)));
+                    }
+                    thread::sleep(SAMPLE_INTERVAL);
+                }
+                peak
+            })
+        };
+
+        let output = match (&self.job.cancel, &self.job.log) {
+            (None, None) => child.wait_with_output(),
+            _ => wait_child(child, &self.job),
+        };
+        done.store(true, Ordering::Relaxed);
+        let peak_rss_bytes = sampler.join().unwrap_or(None);
+
+        let size_after = self.watched_size();
+        self.steps.push(StepMetrics {
+            step: step.to_string(),
+            duration_ms: start.elapsed().as_millis() as u64,
+            peak_rss_bytes,
+            bytes_written: (!self.watched.is_empty()).then(|| size_after.saturating_sub(size_before)),
+            exit_code: output.as_ref().ok().and_then(|o| o.status.code()),
+        });
+        output
+    }
+
+    pub fn finish(&self, apk_size_bytes: u64) -> RunMetrics {
+        RunMetrics {
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+            apk_size_bytes,
+            total_ms: self.started.map(|s| s.elapsed().as_millis() as u64).unwrap_or(0),
+            steps: self.steps.clone(),
+            tool_versions: Vec::new(),
+            estimate: None,
+        }
+    }
+}
+
+/// 追加一条指标到历史记录
+pub fn append_history(paths: &AppPaths, metrics: &RunMetrics) -> Result<(), String> {
+    fs::create_dir_all(&paths.data_dir).map_err(|e| e.to_string())?;
+    let line = serde_json::to_string(metrics).map_err(|e| e.to_string())?;
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(paths.data_dir.join(METRICS_FILE))
+        .map_err(|e| e.to_string())?;
+    writeln!(file, "{}", line).map_err(|e| e.to_string())
+}
+
+fn load_history(paths: &AppPaths) -> Vec<RunMetrics> {
+    let Ok(file) = fs::File::open(paths.data_dir.join(METRICS_FILE)) else {
+        return Vec::new();
+    };
+    io::BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect()
+}
+
+const MB: u64 = 1024 * 1024;
+
+/// APK 大小分组：(标签, 下限, 上限)，单位字节
+const SIZE_BUCKETS: &[(&str, u64, Option<u64>)] = &[
+    ("<10MB", 0, Some(10 * MB)),
+    ("10-50MB", 10 * MB, Some(50 * MB)),
+    ("50-100MB", 50 * MB, Some(100 * MB)),
+    (">=100MB", 100 * MB, None),
+];
+
+/// 所属分组在 `SIZE_BUCKETS` 中的下标
+fn size_bucket(bytes: u64) -> usize {
+    SIZE_BUCKETS
+        .iter()
+        .position(|(_, min, max)| bytes >= *min && max.is_none_or(|max| bytes < max))
+        .unwrap_or(SIZE_BUCKETS.len() - 1)
+}
+
+fn percentile(sorted: &[u64], pct: usize) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let idx = (sorted.len() - 1) * pct / 100;
+    sorted[idx]
+}
+
+/// 最近 N 次处理的步骤耗时统计，用于趋势图
+#[tauri::command]
+pub fn get_performance_stats(paths: tauri::State<'_, AppPaths>, last_n_runs: usize) -> Vec<StepStats> {
+    let history = load_history(&paths);
+    let skip = history.len().saturating_sub(last_n_runs);
+
+    let mut groups: BTreeMap<(usize, String), Vec<u64>> = BTreeMap::new();
+    for run in history.iter().skip(skip) {
+        let bucket = size_bucket(run.apk_size_bytes);
+        for step in &run.steps {
+            groups.entry((bucket, step.step.clone())).or_default().push(step.duration_ms);
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|((bucket, step), mut durations)| {
+            durations.sort_unstable();
+            let (label, size_min_bytes, size_max_bytes) = SIZE_BUCKETS[bucket];
+            StepStats {
+                size_bucket: label.to_string(),
+                size_min_bytes,
+                size_max_bytes,
+                step,
+                runs: durations.len(),
+                median_ms: percentile(&durations, 50),
+                p90_ms: percentile(&durations, 90),
+                max_ms: durations.last().copied().unwrap_or(0),
+            }
+        })
+        .collect()
+}
+
+/// 由历史记录推算的完整处理耗时
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct DurationPrediction {
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub median_ms: u64,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub p90_ms: u64,
+    /// 参与推算的历史处理次数
+    pub runs: usize,
+    /// 是否只用了同一大小分组的记录；为 false 时借用了其他分组，准确度较低
+    pub same_bucket: bool,
+    /// 预计包含安装耗时但历史中没有安装记录
+    pub install_unknown: bool,
+}
+
+/// 按历史完整处理（含 `rebuild` 步骤）每 MB 的耗时推算 `apk_size_bytes` 的处理耗时；
+/// 优先使用同一大小分组的记录，没有时借用全部记录。安装耗时与 APK 大小关系不大，单独取中位数
+pub fn predict_duration(paths: &AppPaths, apk_size_bytes: u64, with_install: bool) -> Option<DurationPrediction> {
+    let history: Vec<RunMetrics> = load_history(paths)
+        .into_iter()
+        .filter(|run| run.total_ms > 0 && run.steps.iter().any(|s| s.step == "rebuild"))
+        .collect();
+    let install_ms = |run: &RunMetrics| run.steps.iter().filter(|s| s.step == "install").map(|s| s.duration_ms).sum::<u64>();
+    let bucket = size_bucket(apk_size_bytes);
+    let same_bucket: Vec<&RunMetrics> = history.iter().filter(|run| size_bucket(run.apk_size_bytes) == bucket).collect();
+    let (basis, same) = if same_bucket.is_empty() { (history.iter().collect(), false) } else { (same_bucket, true) };
+    if basis.is_empty() {
+        return None;
+    }
+
+    let size_mb = (apk_size_bytes as f64 / MB as f64).max(1.0);
+    let mut predicted: Vec<u64> = basis
+        .iter()
+        .map(|run| {
+            let rate = run.total_ms.saturating_sub(install_ms(run)) as f64 / (run.apk_size_bytes as f64 / MB as f64).max(1.0);
+            (rate * size_mb) as u64
+        })
+        .collect();
+    predicted.sort_unstable();
+    let mut installs: Vec<u64> = history.iter().map(install_ms).filter(|ms| *ms > 0).collect();
+    installs.sort_unstable();
+    let install = if with_install { percentile(&installs, 50) } else { 0 };
+    Some(DurationPrediction {
+        median_ms: percentile(&predicted, 50) + install,
+        p90_ms: percentile(&predicted, 90) + install,
+        runs: basis.len(),
+        same_bucket: same,
+        install_unknown: with_install && installs.is_empty(),
+    })
+}
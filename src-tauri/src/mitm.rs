@@ -0,0 +1,219 @@
+use std::fs;
+use std::path::Path;
+
+use crate::input_path::{self, Expect};
+use crate::manifest;
+use crate::pipeline::{self, ProcessConfig};
+use crate::signing::PipelineError;
+use crate::ProcessResult;
+
+const NETWORK_CONFIG_NAME: &str = "apk_disguise_network_security_config";
+const PROXY_CA_NAME: &str = "apk_disguise_proxy_ca";
+
+/// 校验代理证书为 PEM 格式
+pub fn validate_pem(cert_path: &str) -> Result<String, PipelineError> {
+    let content = fs::read_to_string(cert_path)
+        .map_err(|e| PipelineError::InvalidInput(format!("无法读取代理证书 {}: {}", cert_path, e)))?;
+    let begin = content.find("-----BEGIN CERTIFICATE-----");
+    let end = content.find("-----END CERTIFICATE-----");
+    match (begin, end) {
+        (Some(b), Some(e)) if b < e => Ok(content),
+        _ => Err(PipelineError::InvalidInput("代理证书不是有效的 PEM 格式".to_string())),
+    }
+}
+
+/// 读取 `<application>` 标签上的属性
+fn application_attr(manifest: &str, name: &str) -> Option<String> {
+    let start = manifest.find("<application")?;
+    let len = manifest[start..].find('>')?;
+    manifest::xml_attr(&manifest[start..start + len], name)
+}
+
+/// 设置 `<application>` 标签上的属性，已存在则替换
+pub fn set_application_attr(manifest: &str, name: &str, value: &str) -> String {
+    let Some(start) = manifest.find("<application") else {
+        return manifest.to_string();
+    };
+    let Some(len) = manifest[start..].find('>') else {
+        return manifest.to_string();
+    };
+    let tag = &manifest[start..start + len];
+    let re = regex::Regex::new(&format!(r#"{}="[^"]*""#, regex::escape(name))).unwrap();
+    let new_tag = if re.is_match(tag) {
+        re.replace(tag, format!("{}=\"{}\"", name, value).as_str()).to_string()
+    } else {
+        let insert_at = tag.trim_end_matches('/').len();
+        format!("{} {}=\"{}\"{}", &tag[..insert_at], name, value, &tag[insert_at..])
+    };
+    format!("{}{}{}", &manifest[..start], new_tag, &manifest[start + len..])
+}
+
+fn network_security_config() -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<network-security-config>
+    <base-config cleartextTrafficPermitted="true">
+        <trust-anchors>
+            <certificates src="system" />
+            <certificates src="user" />
+            <certificates src="@raw/{}" />
+        </trust-anchors>
+    </base-config>
+    <debug-overrides>
+        <trust-anchors>
+            <certificates src="user" />
+        </trust-anchors>
+    </debug-overrides>
+</network-security-config>
+"#,
+        PROXY_CA_NAME
+    )
+}
+
+/// 对反编译目录应用抓包配置：可调试、允许明文、信任用户证书与代理证书
+fn patch_work_dir(work_dir: &Path, cert_pem: &str) -> Result<(), String> {
+    let manifest_path = work_dir.join("AndroidManifest.xml");
+    let mut manifest = fs::read_to_string(&manifest_path).map_err(|e| format!("读取 Manifest 失败: {}", e))?;
+    let network_config = format!("@xml/{}", NETWORK_CONFIG_NAME);
+    for (name, value) in [("debuggable", "true"), ("usesCleartextTraffic", "true"), ("networkSecurityConfig", network_config.as_str())] {
+        let attr = manifest::android_attr(&manifest, name);
+        manifest = set_application_attr(&manifest, &attr, value);
+        if application_attr(&manifest, &attr).as_deref() != Some(value) {
+            return Err(format!("未能在 <application> 上设置 {}", attr));
+        }
+    }
+    fs::write(&manifest_path, manifest).map_err(|e| format!("写入 Manifest 失败: {}", e))?;
+
+    let xml_dir = work_dir.join("res").join("xml");
+    let raw_dir = work_dir.join("res").join("raw");
+    fs::create_dir_all(&xml_dir).map_err(|e| e.to_string())?;
+    fs::create_dir_all(&raw_dir).map_err(|e| e.to_string())?;
+    fs::write(xml_dir.join(format!("{}.xml", NETWORK_CONFIG_NAME)), network_security_config()).map_err(|e| e.to_string())?;
+    fs::write(raw_dir.join(format!("{}.pem", PROXY_CA_NAME)), cert_pem).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// 一键生成可被代理抓包的 APK。
+/// 代理地址会写入结果消息，供设备端 Wi-Fi 代理设置参考；
+/// 流水线以 `-s` 方式反编译（不解码 smali），因此不注入 ProxySelector 钩子。
+#[tauri::command]
+pub async fn apply_mitm_profile(
+    app: tauri::AppHandle,
+    apk_path: String,
+    proxy_cert_path: String,
+    proxy_host: String,
+    proxy_port: u16,
+    config: ProcessConfig,
+) -> Result<ProcessResult, PipelineError> {
+    let proxy_cert_path = input_path::arg(&proxy_cert_path, Expect::File)?;
+    let cert_pem = validate_pem(&proxy_cert_path)?;
+    if proxy_host.trim().is_empty() || proxy_port == 0 {
+        return Err(PipelineError::InvalidInput("代理地址或端口无效".to_string()));
+    }
+
+    let mut config = ProcessConfig { apk_path, ..config };
+    input_path::normalize_config(&mut config)?;
+    let patch = move |work_dir: &Path| patch_work_dir(work_dir, &cert_pem);
+    let mut result = pipeline::run(&app, config, Some(&patch)).map_err(PipelineError::Io)?;
+    if result.success {
+        result.message = format!("{}（抓包代理: {}:{}）", result.message, proxy_host, proxy_port);
+    }
+    Ok(crate::jobs::finalize(result))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PEM: &str = "-----BEGIN CERTIFICATE-----\nMIIB\n-----END CERTIFICATE-----\n";
+
+    fn work_dir_with(fixture: &str) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/namespace").join(fixture)).unwrap();
+        fs::write(dir.path().join("AndroidManifest.xml"), manifest).unwrap();
+        dir
+    }
+
+    #[test]
+    fn patches_application_attributes_with_the_resolved_prefix() {
+        for (fixture, prefix) in [("AndroidManifest.n0.xml", "n0"), ("AndroidManifest.n1.xml", "n1")] {
+            let dir = work_dir_with(fixture);
+            patch_work_dir(dir.path(), PEM).unwrap();
+            let manifest = fs::read_to_string(dir.path().join("AndroidManifest.xml")).unwrap();
+            let attr = |name: &str| application_attr(&manifest, &format!("{}:{}", prefix, name));
+            assert_eq!(attr("debuggable").as_deref(), Some("true"), "{}", fixture);
+            assert_eq!(attr("usesCleartextTraffic").as_deref(), Some("true"));
+            assert_eq!(attr("networkSecurityConfig"), Some(format!("@xml/{}", NETWORK_CONFIG_NAME)));
+            assert!(!manifest.contains("android:debuggable"));
+            // n1 的 debuggable="false" 被替换而不是重复添加
+            assert_eq!(manifest.matches(":debuggable=").count(), 1);
+        }
+    }
+
+    #[test]
+    fn validates_pem_certificates() {
+        let dir = tempfile::tempdir().unwrap();
+        let good = dir.path().join("ca.pem");
+        fs::write(&good, PEM).unwrap();
+        assert_eq!(validate_pem(&good.to_string_lossy()).unwrap(), PEM);
+
+        let der = dir.path().join("ca.der");
+        fs::write(&der, [0x30, 0x82, 0x01, 0x0a]).unwrap();
+        assert!(matches!(validate_pem(&der.to_string_lossy()), Err(PipelineError::InvalidInput(_))));
+        let reversed = dir.path().join("reversed.pem");
+        fs::write(&reversed, "-----END CERTIFICATE-----\n-----BEGIN CERTIFICATE-----\n").unwrap();
+        assert!(validate_pem(&reversed.to_string_lossy()).is_err());
+        assert!(validate_pem(&dir.path().join("missing.pem").to_string_lossy()).is_err());
+    }
+
+    #[test]
+    fn sets_or_replaces_application_attributes() {
+        let manifest = r#"<manifest><application android:label="A" android:debuggable="false"/></manifest>"#;
+        let patched = set_application_attr(manifest, "android:debuggable", "true");
+        assert_eq!(patched, r#"<manifest><application android:label="A" android:debuggable="true"/></manifest>"#);
+        let patched = set_application_attr(&patched, "android:usesCleartextTraffic", "true");
+        assert_eq!(patched, r#"<manifest><application android:label="A" android:debuggable="true" android:usesCleartextTraffic="true"/></manifest>"#);
+        assert_eq!(set_application_attr("<manifest/>", "android:debuggable", "true"), "<manifest/>");
+    }
+
+    /// 对一个反编译目录应用抓包配置后，检查 Manifest、网络安全配置与证书都已就位
+    #[test]
+    fn patched_work_dir_contains_every_mitm_modification() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("AndroidManifest.xml"),
+            r#"<?xml version="1.0" encoding="utf-8" standalone="no"?>
+<manifest xmlns:android="http://schemas.android.com/apk/res/android" package="com.example.app">
+    <application android:label="@string/app_name" android:networkSecurityConfig="@xml/original_config">
+        <activity android:name=".MainActivity"/>
+    </application>
+</manifest>
+"#,
+        )
+        .unwrap();
+        patch_work_dir(dir.path(), PEM).unwrap();
+
+        let manifest = fs::read_to_string(dir.path().join("AndroidManifest.xml")).unwrap();
+        assert_eq!(application_attr(&manifest, "android:debuggable").as_deref(), Some("true"));
+        assert_eq!(application_attr(&manifest, "android:usesCleartextTraffic").as_deref(), Some("true"));
+        assert_eq!(application_attr(&manifest, "android:networkSecurityConfig"), Some(format!("@xml/{}", NETWORK_CONFIG_NAME)));
+        assert!(!manifest.contains("original_config"));
+        assert!(manifest.contains(r#"<activity android:name=".MainActivity"/>"#));
+
+        let config = fs::read_to_string(dir.path().join("res/xml").join(format!("{}.xml", NETWORK_CONFIG_NAME))).unwrap();
+        assert!(config.contains(r#"cleartextTrafficPermitted="true""#));
+        for src in ["system", "user", &format!("@raw/{}", PROXY_CA_NAME)] {
+            assert!(config.contains(&format!(r#"<certificates src="{}" />"#, src)), "{}", src);
+        }
+        assert_eq!(fs::read_to_string(dir.path().join("res/raw").join(format!("{}.pem", PROXY_CA_NAME))).unwrap(), PEM);
+    }
+
+    #[test]
+    fn missing_application_tag_fails_the_patch() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("AndroidManifest.xml"), r#"<manifest xmlns:android="http://schemas.android.com/apk/res/android"/>"#).unwrap();
+        let error = patch_work_dir(dir.path(), PEM).unwrap_err();
+        assert!(error.contains("android:debuggable"), "{}", error);
+        assert!(!dir.path().join("res").exists());
+    }
+}
\ No newline at end of file
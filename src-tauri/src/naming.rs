@@ -0,0 +1,114 @@
+/// 字符类别，用于判断单词边界
+#[derive(PartialEq, Clone, Copy)]
+enum CharKind {
+    Lower,
+    Upper,
+    Digit,
+    Other,
+}
+
+fn kind(c: char) -> CharKind {
+    if c.is_lowercase() {
+        CharKind::Lower
+    } else if c.is_uppercase() {
+        CharKind::Upper
+    } else if c.is_numeric() {
+        CharKind::Digit
+    } else {
+        CharKind::Other
+    }
+}
+
+fn title_case(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// 将单个包名片段拆分为可读名称：
+/// 仅在小写→大写处断开（连续大写保持在一起，如 `APP`），字母与数字之间断开，
+/// 下划线、连字符视为空格，最后首字母大写。含非 ASCII 字符（如中文）的片段原样返回
+pub fn humanize_segment(segment: &str) -> String {
+    if !segment.is_ascii() {
+        return segment.to_string();
+    }
+
+    let mut words: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut prev: Option<CharKind> = None;
+    for c in segment.chars() {
+        if c == '_' || c == '-' {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            prev = None;
+            continue;
+        }
+        let k = kind(c);
+        let boundary = matches!(
+            (prev, k),
+            (Some(CharKind::Lower), CharKind::Upper)
+                | (Some(CharKind::Digit), CharKind::Lower | CharKind::Upper)
+                | (Some(CharKind::Lower | CharKind::Upper), CharKind::Digit)
+        );
+        if boundary && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        current.push(c);
+        prev = Some(k);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+
+    words.iter().map(|w| title_case(w)).collect::<Vec<_>>().join(" ")
+}
+
+/// 无法获取真实应用名时，由包名最后一段推导显示名称
+pub fn derive_app_name(package_name: &str) -> String {
+    let segment = package_name.rsplit('.').next().unwrap_or(package_name);
+    let name = humanize_segment(segment);
+    if name.is_empty() { package_name.to_string() } else { name }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segments_are_humanized() {
+        let cases = [
+            ("myApp", "My App"),
+            ("MyAPPName", "My APPName"),
+            ("APP", "APP"),
+            ("app2go", "App 2 Go"),
+            ("v2Beta", "V 2 Beta"),
+            ("my_cool-app", "My Cool App"),
+            ("__app__", "App"),
+            ("settings", "Settings"),
+            ("微信", "微信"),
+            ("", ""),
+        ];
+        for (segment, expected) in cases {
+            assert_eq!(humanize_segment(segment), expected, "{}", segment);
+        }
+    }
+
+    #[test]
+    fn app_names_come_from_the_last_segment() {
+        let cases = [
+            ("com.example.myApp", "My App"),
+            ("com.tencent.mm", "Mm"),
+            ("com.android.settings", "Settings"),
+            ("com.example.app_2", "App 2"),
+            ("com.example.___", "com.example.___"),
+            ("com.example.", "com.example."),
+            ("single", "Single"),
+        ];
+        for (package, expected) in cases {
+            assert_eq!(derive_app_name(package), expected, "{}", package);
+        }
+    }
+}
@@ -0,0 +1,187 @@
+//! 内置原生工具（zipalign.exe 等）的运行库依赖检查。
+//! Windows 上缺少 DLL 时系统弹出对话框而不是写入 stderr，这里读取 PE 导入表找出缺失的 DLL。
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Output;
+
+use crate::paths::AppPaths;
+use crate::tool_versions;
+
+/// Windows 进程因找不到 DLL 而无法启动时的退出码
+pub const STATUS_DLL_NOT_FOUND: u32 = 0xC000_0135;
+
+/// DLL 与提供它的运行库
+const KNOWN_RUNTIMES: &[(&str, &str)] = &[
+    ("vcruntime140.dll", "Microsoft Visual C++ 2015-2022 Redistributable"),
+    ("vcruntime140_1.dll", "Microsoft Visual C++ 2015-2022 Redistributable (x64)"),
+    ("msvcp140.dll", "Microsoft Visual C++ 2015-2022 Redistributable"),
+    ("msvcr120.dll", "Microsoft Visual C++ 2013 Redistributable"),
+    ("msvcp120.dll", "Microsoft Visual C++ 2013 Redistributable"),
+    ("msvcr110.dll", "Microsoft Visual C++ 2012 Redistributable"),
+    ("msvcr100.dll", "Microsoft Visual C++ 2010 Redistributable"),
+    ("libwinpthread-1.dll", "MinGW 运行库（应与工具放在同一目录）"),
+    ("libgcc_s_seh-1.dll", "MinGW 运行库（应与工具放在同一目录）"),
+    ("libstdc++-6.dll", "MinGW 运行库（应与工具放在同一目录）"),
+];
+
+/// 一个找不到的 DLL
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct MissingDll {
+    pub dll: String,
+    /// 需要安装的运行库，未知 DLL 为空
+    pub redistributable: Option<String>,
+}
+
+/// 单个原生工具的检查结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct NativeToolCheck {
+    pub path: String,
+    pub missing: Vec<MissingDll>,
+    /// 无法读取导入表时的原因
+    pub error: Option<String>,
+}
+
+fn u16_at(data: &[u8], pos: usize) -> Option<u16> {
+    data.get(pos..pos + 2).map(|b| u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn u32_at(data: &[u8], pos: usize) -> Option<u32> {
+    data.get(pos..pos + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+/// 读取 PE 文件导入表中的 DLL 名
+pub fn imported_dlls(data: &[u8]) -> Result<Vec<String>, String> {
+    let pe = u32_at(data, 0x3c).ok_or("不是 PE 文件")? as usize;
+    if data.get(pe..pe + 4) != Some(b"PE\0\0") {
+        return Err("不是 PE 文件".to_string());
+    }
+    let coff = pe + 4;
+    let sections = u16_at(data, coff + 2).ok_or("PE 头被截断")? as usize;
+    let optional_size = u16_at(data, coff + 16).ok_or("PE 头被截断")? as usize;
+    let optional = coff + 20;
+    // 导入表是第 2 个数据目录；PE32 与 PE32+ 的数据目录起点不同
+    let directories = match u16_at(data, optional).ok_or("PE 头被截断")? {
+        0x10b => optional + 96,
+        0x20b => optional + 112,
+        other => return Err(format!("未知的 PE 可选头类型: {:#x}", other)),
+    };
+    let import_rva = u32_at(data, directories + 8).ok_or("PE 头被截断")?;
+    if import_rva == 0 {
+        return Ok(Vec::new());
+    }
+
+    let section_table = optional + optional_size;
+    let to_offset = |rva: u32| -> Option<usize> {
+        (0..sections).find_map(|i| {
+            let s = section_table + i * 40;
+            let virtual_size = u32_at(data, s + 8)?;
+            let virtual_addr = u32_at(data, s + 12)?;
+            let raw_size = u32_at(data, s + 16)?;
+            let raw_ptr = u32_at(data, s + 20)?;
+            let size = virtual_size.max(raw_size);
+            (rva >= virtual_addr && rva < virtual_addr + size).then(|| (rva - virtual_addr + raw_ptr) as usize)
+        })
+    };
+
+    let mut dlls = Vec::new();
+    let mut descriptor = to_offset(import_rva).ok_or("导入表不在任何节中")?;
+    loop {
+        let name_rva = u32_at(data, descriptor + 12).ok_or("导入表被截断")?;
+        if name_rva == 0 {
+            break;
+        }
+        let name_pos = to_offset(name_rva).ok_or("导入名不在任何节中")?;
+        let end = data[name_pos..].iter().position(|b| *b == 0).ok_or("导入名被截断")?;
+        dlls.push(String::from_utf8_lossy(&data[name_pos..name_pos + end]).to_string());
+        descriptor += 20;
+    }
+    Ok(dlls)
+}
+
+/// DLL 的搜索位置：工具所在目录、System32、PATH
+fn search_dirs(binary: &Path) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = binary.parent().map(Path::to_path_buf).into_iter().collect();
+    if let Some(root) = std::env::var_os("SystemRoot") {
+        dirs.push(PathBuf::from(root).join("System32"));
+    }
+    if let Some(path) = std::env::var_os("PATH") {
+        dirs.extend(std::env::split_paths(&path));
+    }
+    dirs
+}
+
+/// 找出工具依赖但在搜索位置中都不存在的 DLL。API Set（api-ms-* / ext-ms-*）由系统提供，不检查
+pub fn missing_dlls(binary: &Path) -> Result<Vec<MissingDll>, String> {
+    let data = fs::read(binary).map_err(|e| format!("读取 {} 失败: {}", binary.display(), e))?;
+    let dirs = search_dirs(binary);
+    Ok(imported_dlls(&data)?
+        .into_iter()
+        .filter(|dll| {
+            let lower = dll.to_ascii_lowercase();
+            !lower.starts_with("api-ms-") && !lower.starts_with("ext-ms-")
+        })
+        .filter(|dll| !dirs.iter().any(|dir| dir.join(dll).exists()))
+        .map(|dll| MissingDll {
+            redistributable: KNOWN_RUNTIMES
+                .iter()
+                .find(|(name, _)| dll.eq_ignore_ascii_case(name))
+                .map(|(_, runtime)| runtime.to_string()),
+            dll,
+        })
+        .collect())
+}
+
+/// 原生工具失败且像是运行库缺失（退出码为 STATUS_DLL_NOT_FOUND，或没有任何输出）时，给出缺少哪个运行库的提示
+pub fn diagnose_failure(binary: &Path, output: &Output) -> Option<String> {
+    if !cfg!(windows) || output.status.success() {
+        return None;
+    }
+    let dll_not_found = output.status.code().map(|c| c as u32) == Some(STATUS_DLL_NOT_FOUND);
+    let silent = output.stdout.iter().chain(&output.stderr).all(u8::is_ascii_whitespace);
+    if !dll_not_found && !silent {
+        return None;
+    }
+    let missing = missing_dlls(binary).ok()?;
+    if missing.is_empty() {
+        return dll_not_found.then(|| format!("{} 无法启动：缺少依赖的 DLL", binary.display()));
+    }
+    let details: Vec<String> = missing
+        .iter()
+        .map(|m| match &m.redistributable {
+            Some(runtime) => format!("{}（请安装 {}）", m.dll, runtime),
+            None => m.dll.clone(),
+        })
+        .collect();
+    Some(format!("{} 无法启动，缺少: {}", binary.display(), details.join("、")))
+}
+
+/// 阻止子进程在缺少 DLL 等错误时弹出系统对话框，使失败能通过退出码捕获。子进程继承该错误模式
+#[cfg(windows)]
+pub fn suppress_error_dialogs() {
+    use windows_sys::Win32::System::Diagnostics::Debug::{
+        SetErrorMode, SEM_FAILCRITICALERRORS, SEM_NOGPFAULTERRORBOX, SEM_NOOPENFILEERRORBOX,
+    };
+    unsafe {
+        SetErrorMode(SEM_FAILCRITICALERRORS | SEM_NOGPFAULTERRORBOX | SEM_NOOPENFILEERRORBOX);
+    }
+}
+
+#[cfg(not(windows))]
+pub fn suppress_error_dialogs() {}
+
+/// 检查工具目录中所有内置原生工具的运行库依赖
+#[tauri::command]
+pub fn check_native_tools(paths: tauri::State<'_, AppPaths>) -> Vec<NativeToolCheck> {
+    tool_versions::discover(&paths)
+        .into_iter()
+        .filter(|t| t.path.to_ascii_lowercase().ends_with(".exe"))
+        .map(|t| match missing_dlls(Path::new(&t.path)) {
+            Ok(missing) => NativeToolCheck { path: t.path, missing, error: None },
+            Err(e) => NativeToolCheck { path: t.path, missing: Vec::new(), error: Some(e) },
+        })
+        .collect()
+}
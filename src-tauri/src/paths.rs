@@ -0,0 +1,139 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+
+/// 可执行文件旁存在该文件时启用便携模式
+pub const PORTABLE_MARKER: &str = "portable.marker";
+
+/// 所有持久化数据的统一路径解析，作为托管状态供各功能查询
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct AppPaths {
+    pub portable: bool,
+    /// 便携模式下为可执行文件所在目录，否则为应用数据目录
+    pub root: PathBuf,
+    pub config_dir: PathBuf,
+    pub data_dir: PathBuf,
+    pub cache_dir: PathBuf,
+    pub log_dir: PathBuf,
+    pub tools_dir: PathBuf,
+    /// 请求了便携模式但无法启用时的原因
+    pub portable_error: Option<String>,
+}
+
+fn exe_dir() -> Option<PathBuf> {
+    std::env::current_exe().ok()?.parent().map(Path::to_path_buf)
+}
+
+fn portable_requested(dir: &Path) -> bool {
+    dir.join(PORTABLE_MARKER).exists() || std::env::args().any(|a| a == "--portable")
+}
+
+fn is_writable(dir: &Path) -> bool {
+    let probe = dir.join(".apk_disguise_write_test");
+    let ok = fs::write(&probe, b"").is_ok();
+    let _ = fs::remove_file(&probe);
+    ok
+}
+
+/// 先写入同目录下的临时文件再替换，中途崩溃不会留下写了一半的文件
+pub fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_file_name(format!("{}.tmp", path.file_name().unwrap_or_default().to_string_lossy()));
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path)
+}
+
+impl AppPaths {
+    /// 便携模式的目录布局：数据位于 `dir/data` 下，工具位于 `dir/tools`
+    pub fn portable_at(dir: &Path) -> Self {
+        let data = dir.join("data");
+        AppPaths {
+            portable: true,
+            root: dir.to_path_buf(),
+            config_dir: data.join("config"),
+            data_dir: data.clone(),
+            cache_dir: data.join("cache"),
+            log_dir: data.join("logs"),
+            tools_dir: dir.join("tools"),
+            portable_error: None,
+        }
+    }
+
+    /// 启动时解析一次，结果通过 `app.manage` 注入
+    pub fn resolve(app: &tauri::AppHandle) -> Result<Self, String> {
+        let resource_tools = app.path().resource_dir().map_err(|e| e.to_string())?.join("tools");
+
+        let mut portable_error = None;
+        if let Some(dir) = exe_dir().filter(|d| portable_requested(d)) {
+            if is_writable(&dir) {
+                let paths = AppPaths::portable_at(&dir);
+                let tools_dir = if paths.tools_dir.exists() { paths.tools_dir.clone() } else { resource_tools };
+                return Ok(AppPaths { tools_dir, ..paths });
+            }
+            portable_error = Some(format!(
+                "可执行文件所在目录 {} 不可写，无法启用便携模式，已改用系统应用数据目录",
+                dir.display()
+            ));
+        }
+
+        let path = app.path();
+        let data_dir = path.app_data_dir().map_err(|e| e.to_string())?;
+        Ok(AppPaths {
+            portable: false,
+            root: data_dir.clone(),
+            config_dir: path.app_config_dir().map_err(|e| e.to_string())?,
+            data_dir,
+            cache_dir: path.app_cache_dir().map_err(|e| e.to_string())?,
+            log_dir: path.app_log_dir().map_err(|e| e.to_string())?,
+            tools_dir: resource_tools,
+            portable_error,
+        })
+    }
+}
+
+pub fn copy_dir(src: &Path, dest: &Path) -> Result<u32, String> {
+    let mut copied = 0;
+    for entry in walkdir::WalkDir::new(src).into_iter().filter_map(|e| e.ok()) {
+        let rel = entry.path().strip_prefix(src).map_err(|e| e.to_string())?;
+        let target = dest.join(rel);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target).map_err(|e| e.to_string())?;
+        } else if !target.exists() {
+            fs::copy(entry.path(), &target).map_err(|e| e.to_string())?;
+            copied += 1;
+        }
+    }
+    Ok(copied)
+}
+
+/// 获取当前数据路径与运行模式
+#[tauri::command]
+pub fn get_app_paths(paths: tauri::State<'_, AppPaths>) -> AppPaths {
+    paths.inner().clone()
+}
+
+/// 首次进入便携模式时，将系统应用数据目录中已有的数据复制到便携目录（不覆盖已有文件）
+#[tauri::command]
+pub fn migrate_to_portable(app: tauri::AppHandle, paths: tauri::State<'_, AppPaths>) -> Result<u32, String> {
+    if !paths.portable {
+        return Err("当前未处于便携模式".to_string());
+    }
+    let path = app.path();
+    let sources = [
+        (path.app_config_dir().map_err(|e| e.to_string())?, &paths.config_dir),
+        (path.app_data_dir().map_err(|e| e.to_string())?, &paths.data_dir),
+        (path.app_log_dir().map_err(|e| e.to_string())?, &paths.log_dir),
+    ];
+
+    let mut copied = 0;
+    for (src, dest) in sources {
+        if src.exists() && src != *dest {
+            copied += copy_dir(&src, dest)?;
+        }
+    }
+    Ok(copied)
+}
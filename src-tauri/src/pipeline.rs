@@ -0,0 +1,931 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use tauri::Manager;
+
+use crate::adb_coordinator::AdbCoordinator;
+use crate::device_cache::{self, DeviceCache};
+use crate::jobs::{self, JobContext};
+use crate::metrics::{self, Recorder};
+use crate::paths::AppPaths;
+use crate::{artifacts, assets, axml, compat, decode, device_prefs, device_storage, dex, diagnostics, download, emit_progress, entries, estimate, hooks, incremental, installs, manifest, pkcs11, pm, post_install, repackaging, res_refs, rollback, safe_path, signing, source, stealth, tool_versions, validate, ProcessResult};
+
+/// 一次完整处理所需的全部参数
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export, optional_fields = nullable))]
+pub struct ProcessConfig {
+    pub apk_path: String,
+    pub new_prefix: String,
+    pub custom_suffix: Option<String>,
+    pub device_id: Option<String>,
+    pub install_after: bool,
+    pub java_path: String,
+    pub apktool_path: String,
+    pub zipalign_path: String,
+    pub apksigner_path: String,
+    pub keystore_path: String,
+    pub hydrate_source: Option<bool>,
+    pub post_install_actions: Option<Vec<post_install::PostInstallAction>>,
+    /// 签名因密钥库兼容性失败时，自动转换为 PKCS12 后重试一次
+    pub auto_convert_keystore: Option<bool>,
+    /// 步骤之间运行的用户钩子
+    pub hooks: Option<hooks::PipelineHooks>,
+    /// 需要确保声明的权限
+    pub required_permissions: Option<Vec<String>>,
+    /// 将危险权限替换为较低权限的替代
+    pub downgrade_permissions: Option<bool>,
+    /// 显式指定的 minSdkVersion / targetSdkVersion，不指定则保持原值
+    pub min_sdk_override: Option<u32>,
+    pub target_sdk_override: Option<u32>,
+    /// 在结果中附带 ContentProvider URI 报告
+    pub content_uri_report: Option<bool>,
+    /// 安装前备份已安装版本，新版本启动失败时自动恢复
+    pub rollback_on_launch_failure: Option<bool>,
+    /// 回编译后去除 DEX 中的调试信息
+    pub strip_debug_info: Option<bool>,
+    /// 回编译前替换的资源文件
+    pub asset_overrides: Option<Vec<assets::AssetOverride>>,
+    /// apktool 资源类警告超过该数量时结果标记为带警告完成，默认 20
+    pub apktool_warning_threshold: Option<u32>,
+    /// 固定使用的工具版本，优先于上面的工具路径
+    pub tool_versions: Option<tool_versions::ToolPins>,
+    /// 前端未确认的日志批次达到该数量后只推送省略行数，默认 20
+    pub log_queue_depth: Option<u32>,
+    /// 客户标签，用于检查签名证书是否被跨客户使用
+    pub customer: Option<String>,
+    /// 回编译输出至少为输入 APK 大小的比例，默认 0.2
+    pub min_output_ratio: Option<f64>,
+    /// 隐匿输出：不使用 `_fixed` 文件名、统一 ZIP 条目时间，结果中列出无法去除的痕迹
+    pub stealth_output: Option<bool>,
+    /// 严格模式：资源类警告（含 APKTOOL_DUMMY 占位资源）超过阈值时直接失败，不再安装输出
+    pub strict_resource_warnings: Option<bool>,
+    /// `adb install` 参数，显式设置的字段优先于设备偏好
+    pub install_flags: Option<device_prefs::InstallFlags>,
+    /// 使用 PKCS#11 令牌中的密钥签名，设置后忽略 `keystore_path`
+    pub pkcs11_signing: Option<pkcs11::Pkcs11Profile>,
+    /// 本次使用密钥库中的哪个别名，不设置时使用默认别名；处理开始前校验别名存在且口令正确
+    pub key_alias_override: Option<String>,
+    /// 各别名的密钥口令来源，未列出的别名使用默认口令
+    pub key_passwords: Option<Vec<signing::AliasPassword>>,
+    /// 把 res/xml 与 res/layout 中值为原包名的引用改为新包名，默认只报告不修改
+    pub rewrite_res_xml_references: Option<bool>,
+}
+
+/// 在改完包名、回编译之前对工作目录做的额外修改
+pub type WorkDirPatch<'a> = &'a (dyn Fn(&Path) -> Result<(), String> + Send + Sync);
+
+/// 新包名：前缀 + 自定义后缀，未指定后缀时从文件名生成
+pub fn new_package_name(apk_path: &str, new_prefix: &str, custom_suffix: Option<&str>) -> String {
+    let suffix = match custom_suffix {
+        Some(s) if !s.is_empty() => s.to_string(),
+        _ => {
+            let file_stem = Path::new(apk_path).file_stem().and_then(|s| s.to_str()).unwrap_or("apk");
+            let clean: String = file_stem.to_lowercase().chars().filter(|c| c.is_alphanumeric()).collect();
+            if clean.len() > 12 { clean[..12].to_string() } else { clean }
+        }
+    };
+    format!("{}.{}", new_prefix, suffix)
+}
+
+/// 开始处理前即可确定的包名
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct PackagePlan {
+    /// 直接读取二进制 Manifest 得到，读取失败（如加固）时为空
+    pub original_package: Option<String>,
+    pub new_package: String,
+}
+
+/// 只依据文件名与参数确定并校验新包名，不执行任何耗时步骤
+pub fn plan_package(config: &ProcessConfig) -> Result<PackagePlan, String> {
+    let new_package = new_package_name(&config.apk_path, &config.new_prefix, config.custom_suffix.as_deref());
+    if !new_package.contains('.') || !pm::is_package_name(&new_package) {
+        return Err(format!("新包名不合法: {}", new_package));
+    }
+    let original_package = axml::read_manifest_from_apk(Path::new(&config.apk_path))
+        .ok()
+        .and_then(|elements| elements.into_iter().find(|e| e.name == "manifest"))
+        .and_then(|manifest| manifest.attr("package").map(axml::AxmlValue::as_string));
+    Ok(PackagePlan { original_package, new_package })
+}
+
+/// 确定包名后执行完整流程
+pub fn run(app: &tauri::AppHandle, config: ProcessConfig, patch: Option<WorkDirPatch<'_>>) -> Result<ProcessResult, String> {
+    match plan_package(&config) {
+        Ok(plan) => run_planned(app, config, &plan, patch, JobContext::default()),
+        Err(message) => Ok(ProcessResult { success: false, message, step: Some("package_name".to_string()), ..Default::default() }),
+    }
+}
+
+/// 反编译 → 改包名 → 回编译 → 对齐 → 签名 → 安装，并记录各步骤指标。包名使用事先确定的 `plan`
+pub fn run_planned(
+    app: &tauri::AppHandle,
+    mut config: ProcessConfig,
+    plan: &PackagePlan,
+    patch: Option<WorkDirPatch<'_>>,
+    job: JobContext,
+) -> Result<ProcessResult, String> {
+    emit_progress(
+        app,
+        "package",
+        format!("新包名: {}（原包名: {}）", plan.new_package, plan.original_package.as_deref().unwrap_or("未知")),
+    );
+    let apk_size = fs::metadata(&config.apk_path).map(|m| m.len()).unwrap_or(0);
+    let resolved_tools = match tool_versions::apply_pins(&app.state::<AppPaths>(), &mut config) {
+        Ok(tools) => tools,
+        Err(e) => {
+            return Ok(ProcessResult {
+                success: false,
+                message: e.to_string(),
+                step: Some("tools".to_string()),
+                new_package: Some(plan.new_package.clone()),
+                ..Default::default()
+            })
+        }
+    };
+    for tool in &resolved_tools {
+        emit_progress(
+            app,
+            "tools",
+            format!("{} {} ({})", tool.tool, tool.version.as_deref().unwrap_or("未标注版本"), tool.path),
+        );
+    }
+    // 指定的别名不存在或口令不对时，在耗时步骤之前失败
+    let signing_identity = match signing::identity_for_run(app, &config) {
+        Ok(identity) => identity,
+        Err(e) => {
+            return Ok(ProcessResult {
+                success: false,
+                message: e.to_string(),
+                step: Some("key_alias".to_string()),
+                new_package: Some(plan.new_package.clone()),
+                ..Default::default()
+            })
+        }
+    };
+    // 在本次记录写入历史前预估，事后与实际耗时对比
+    let estimate = estimate::estimate(&app.state::<AppPaths>(), Path::new(&config.apk_path), &estimate::EstimateOptions::from(&config))
+        .inspect_err(|e| emit_progress(app, "estimate", format!("预估失败: {}", e)))
+        .ok();
+    let mut recorder = Recorder::for_job(&job);
+    let threshold = config.apktool_warning_threshold.unwrap_or(diagnostics::DEFAULT_RESOURCE_WARNING_THRESHOLD);
+    let apk_path = config.apk_path.clone();
+    let stealth = config.stealth_output.unwrap_or(false);
+    let mut warnings = Vec::new();
+    let mut tool_warnings = Vec::new();
+    let outcome = run_steps(app, config, plan.new_package.clone(), patch, &mut recorder, &mut warnings, &mut tool_warnings);
+    // 取消时已完成的输出保留，未完成的清理掉工作目录
+    if job.is_cancelled() && !outcome.as_ref().is_ok_and(|r| r.success) {
+        return Ok(ProcessResult { warnings: remove_work_dirs(&apk_path), ..jobs::cancelled_result() });
+    }
+    let mut result = outcome?;
+    result.new_package = Some(plan.new_package.clone());
+    if stealth {
+        result.residual_traces = stealth::RESIDUAL_TRACES.iter().map(ToString::to_string).collect();
+    }
+    result.warnings = tool_warnings.iter().map(ToString::to_string).chain(warnings).collect();
+
+    let resource_warnings = diagnostics::resource_warning_count(&tool_warnings);
+    if result.success && resource_warnings > threshold {
+        result.success_with_warnings = true;
+        result.message = format!(
+            "{}\n⚠️ apktool 报告了 {} 条资源相关警告（阈值 {}），输出的 APK 可能显示异常或崩溃，请安装后验证",
+            result.message, resource_warnings, threshold
+        );
+    }
+
+    let mut run_metrics = recorder.finish(apk_size);
+    run_metrics.tool_versions = resolved_tools.clone();
+    run_metrics.estimate = estimate;
+    if let Err(e) = metrics::append_history(&app.state::<AppPaths>(), &run_metrics) {
+        emit_progress(app, "metrics", format!("写入性能记录失败: {}", e));
+    }
+    result.decode_strategy = decode::succeeded_strategy(&run_metrics.steps).map(ToString::to_string);
+    result.metrics = Some(run_metrics);
+    result.tool_versions = resolved_tools;
+    result.mode = Some("full".to_string());
+    result.signing_identity = signing_identity;
+    Ok(result)
+}
+
+/// 完整流程使用的反编译目录与中间产物目录
+pub fn work_dirs(apk_path: &str) -> [PathBuf; 2] {
+    let file_stem = Path::new(apk_path).file_stem().and_then(|s| s.to_str()).unwrap_or("apk");
+    [safe_path::work_root().join(file_stem), safe_path::work_root().join(format!("{}_stage", file_stem))]
+}
+
+/// 删除某个 APK 的所有工作目录（完整流程与增量处理），返回清理失败的信息
+pub fn remove_work_dirs(apk_path: &str) -> Vec<String> {
+    work_dirs(apk_path)
+        .into_iter()
+        .chain([incremental::stage_dir(apk_path)])
+        .filter_map(|dir| safe_path::remove_dir_all(&dir).err().map(|e| e.to_string()))
+        .collect()
+}
+
+fn run_steps(
+    app: &tauri::AppHandle,
+    config: ProcessConfig,
+    new_package: String,
+    patch: Option<WorkDirPatch<'_>>,
+    recorder: &mut Recorder,
+    warnings: &mut Vec<String>,
+    tool_warnings: &mut Vec<diagnostics::ToolWarning>,
+) -> Result<ProcessResult, String> {
+    let ProcessConfig {
+        apk_path,
+        new_prefix: _,
+        custom_suffix: _,
+        device_id,
+        install_after,
+        java_path,
+        apktool_path,
+        zipalign_path,
+        apksigner_path,
+        keystore_path,
+        hydrate_source,
+        post_install_actions,
+        auto_convert_keystore,
+        hooks: hook_cfg,
+        required_permissions,
+        downgrade_permissions,
+        min_sdk_override,
+        target_sdk_override,
+        content_uri_report,
+        rollback_on_launch_failure,
+        strip_debug_info,
+        asset_overrides,
+        apktool_warning_threshold,
+        tool_versions: _,
+        log_queue_depth: _,
+        customer: _,
+        min_output_ratio,
+        stealth_output,
+        strict_resource_warnings,
+        install_flags,
+        pkcs11_signing,
+        key_alias_override,
+        key_passwords,
+        rewrite_res_xml_references,
+    } = config;
+    let stealth = stealth_output.unwrap_or(false);
+    let hook_cfg = hook_cfg.unwrap_or_default();
+
+    let path = Path::new(&apk_path);
+    let file_stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("apk");
+    let parent_dir = path.parent().unwrap_or(Path::new("."));
+    let [work_dir, stage_dir] = work_dirs(&apk_path);
+    let final_apk = if stealth {
+        stealth::output_path(path, &new_package)
+    } else {
+        parent_dir.join(format!("{}_fixed.apk", file_stem))
+    };
+    let output_dir = app.state::<AppPaths>().data_dir.join("output");
+    let (final_apk, moved) = source::output_location(final_apk, &output_dir);
+    if moved.is_some() {
+        fs::create_dir_all(&output_dir).map_err(|e| format!("创建输出目录失败: {}", e))?;
+    }
+    let ([rebuilt_apk, stripped_apk, normalized_apk, aligned_apk, signed_apk], diversions) = artifacts::plan(
+        path,
+        &final_apk,
+        &stage_dir,
+        [
+            ("rebuilt", stage_dir.join(format!("{}_rebuilt.apk", file_stem))),
+            ("stripped", stage_dir.join(format!("{}_stripped.apk", file_stem))),
+            ("normalized", stage_dir.join(format!("{}_normalized.apk", file_stem))),
+            ("aligned", stage_dir.join(format!("{}_aligned.apk", file_stem))),
+            ("signed", stage_dir.join(format!("{}_signed.apk", file_stem))),
+        ],
+    );
+    for diversion in moved.iter().chain(&diversions) {
+        recorder.note(diversion);
+        emit_progress(app, "paths", diversion.clone());
+    }
+    
+    safe_path::remove_dir_all(&work_dir).map_err(|e| e.to_string())?;
+    safe_path::remove_dir_all(&stage_dir).map_err(|e| e.to_string())?;
+    fs::create_dir_all(&stage_dir).map_err(|e| format!("创建工作目录失败: {}", e))?;
+    recorder.watch_dirs(vec![work_dir.clone(), stage_dir.clone()]);
+    
+    
+    // 第零步：检查源文件是否完全在本地（云盘占位 / 网络路径）
+    let check = source::inspect_source(path)?;
+    emit_progress(app, "source", check.summary());
+    let apk_path = if check.needs_local_copy() {
+        if !hydrate_source.unwrap_or(true) {
+            return Ok(ProcessResult {
+                success: false,
+                message: "源文件不在本地（云盘占位文件、脱机文件或网络路径），请先将其下载到本地磁盘后再处理".to_string(),
+                output_path: None,
+                step: Some("source".to_string()),
+                ..Default::default()
+            });
+        }
+        emit_progress(app, "source", "正在将源文件复制到本地工作区...");
+        let local = source::hydrate_to(path, &stage_dir.join("source"))?;
+        emit_progress(app, "source", format!("已复制到 {}", local.display()));
+        local.to_string_lossy().to_string()
+    } else {
+        apk_path.clone()
+    };
+    
+    if let Err(e) = validate::validate_apk_file(apk_path.clone()) {
+        return Ok(ProcessResult {
+            success: false,
+            message: e.to_string(),
+            output_path: None,
+            step: Some("validate".to_string()),
+            ..Default::default()
+        });
+    }
+    
+    let tools_dir = app.state::<AppPaths>().tools_dir.clone();
+    match repackaging::detect(Path::new(&apk_path), &tools_dir, &signing::keytool_path(Some(&java_path))) {
+        Ok(info) if info.was_repackaged => {
+            let warning = format!(
+                "输入 APK 似乎已被本工具处理过（置信度 {:.0}%: {}），重复处理可能导致 APK 损坏",
+                info.confidence * 100.0,
+                info.indicators.join("; ")
+            );
+            emit_progress(app, "repackaging_check", warning.clone());
+            warnings.push(warning);
+        }
+        Ok(_) => {}
+        Err(e) => emit_progress(app, "repackaging_check", format!("重复处理检测失败: {}", e)),
+    }
+    
+    // 第一步：反编译，资源表无法解析时按策略阶梯降级
+    let resource_options = if patch.is_some() { vec!["资源修改（如抓包配置写入 res/xml）".to_string()] } else { Vec::new() };
+    let (decompile, full_resources) = match decode::decompile(recorder, &java_path, &apktool_path, &apk_path, &work_dir, &resource_options)? {
+        decode::Decoded::Success { strategy, output } => {
+            if strategy != "full" {
+                let warning = format!("完整解码资源失败，已改用 {} 方式反编译（资源保持原样）", strategy);
+                emit_progress(app, "decompile", warning.clone());
+                warnings.push(warning);
+            }
+            (output, strategy == "full")
+        }
+        decode::Decoded::Failed(message) => {
+            return Ok(ProcessResult {
+                success: false,
+                message,
+                output_path: None,
+                step: Some("decompile".to_string()),
+                ..Default::default()
+            });
+        }
+    };
+    
+    let decompile_output = format!("{}\n{}", String::from_utf8_lossy(&decompile.stdout), String::from_utf8_lossy(&decompile.stderr));
+    tool_warnings.extend(diagnostics::scan_apktool_output("decompile", &decompile_output));
+    let dummies = diagnostics::scan_dummy_resources(&work_dir);
+    if let Some(warning) = dummies.tool_warning() {
+        let summary = dummies.summary();
+        emit_progress(app, "decompile", summary.clone());
+        warnings.push(summary);
+        tool_warnings.push(warning);
+    }
+    
+    let manifest_path = work_dir.join("AndroidManifest.xml");
+    let original_package = fs::read_to_string(&manifest_path)
+        .ok()
+        .and_then(|m| {
+            regex::Regex::new(r#"package="([^"]+)""#).unwrap().captures(&m).map(|c| c[1].to_string())
+        })
+        .unwrap_or_default();
+    let hook_ctx = hooks::HookContext {
+        work_dir: &work_dir,
+        original_package: &original_package,
+        new_package: &new_package,
+        output_apk: None,
+    };
+    if let Err((step, message)) = hooks::run_hook(app, "after_decompile", hook_cfg.after_decompile.as_deref(), hook_cfg.timeout_secs, &hook_ctx) {
+        return Ok(hook_failure(step, message));
+    }
+    
+    // 第二步：修改包名
+    let manifest_content = fs::read_to_string(&manifest_path)
+        .map_err(|e| format!("读取 Manifest 失败: {}", e))?;
+    
+    let re = regex::Regex::new(r#"package="[^"]+""#).unwrap();
+    let mut new_manifest = re.replace(&manifest_content, &format!("package=\"{}\"", new_package)).to_string();
+    
+    if !new_manifest.contains(&format!("package=\"{}\"", new_package)) {
+        return Ok(manifest_failure(format!("Manifest 中没有找到 package 属性，包名未能修改为 {}", new_package)));
+    }
+    
+    if let Some(required) = &required_permissions {
+        let added = manifest::add_missing_permissions(&mut new_manifest, required);
+        emit_progress(app, "manifest", format!("补充了 {} 个权限声明", added));
+    }
+    let mut downgraded = Vec::new();
+    if downgrade_permissions.unwrap_or(false) {
+        downgraded = manifest::downgrade_permissions(&mut new_manifest);
+        if !downgraded.is_empty() {
+            emit_progress(app, "manifest", format!("已降级权限: {}", downgraded.join(", ")));
+        }
+    }
+    let required = required_permissions.as_deref().unwrap_or_default();
+    if let Err(message) = manifest::verify_permissions(&new_manifest, required, &downgraded) {
+        return Ok(manifest_failure(message));
+    }
+    
+    fs::write(&manifest_path, &new_manifest).map_err(|e| format!("写入 Manifest 失败: {}", e))?;
+    compat::apply_sdk_override(&work_dir, min_sdk_override, target_sdk_override)?;
+    
+    // 资源保持原样（未完整解码）时 res 下是二进制 XML，无从扫描
+    if full_resources {
+        let references = res_refs::scan(&work_dir, &original_package);
+        let rewrite = rewrite_res_xml_references.unwrap_or(false) && references.iter().any(|r| r.rewritable);
+        if rewrite {
+            let changed = res_refs::rewrite(&work_dir, &original_package, &new_package)?;
+            emit_progress(app, "res_references", format!("已改写 {} 个资源文件中的原包名引用", changed));
+        }
+        let reference_warnings = res_refs::warnings(&references, rewrite);
+        if let Some(summary) = reference_warnings.first() {
+            emit_progress(app, "res_references", summary.clone());
+        }
+        warnings.extend(reference_warnings);
+    }
+    
+    let content_uris = if content_uri_report.unwrap_or(false) {
+        Some(manifest::get_content_provider_uris(work_dir.to_string_lossy().to_string()).map_err(|e| e.to_string())?)
+    } else {
+        None
+    };
+    
+    if let Some(patch) = patch {
+        if let Err(e) = patch(work_dir.as_path()) {
+            return Ok(ProcessResult {
+                success: false,
+                message: format!("修改资源失败: {}", e),
+                output_path: None,
+                step: Some("patch".to_string()),
+                ..Default::default()
+            });
+        }
+    }
+    
+    let changes = match &asset_overrides {
+        Some(overrides) if !overrides.is_empty() => match assets::apply_to_work_dir(&work_dir, overrides) {
+            Ok(changes) => {
+                emit_progress(app, "assets", format!("已替换 {} 个资源文件", changes.len()));
+                changes
+            }
+            Err(e) => {
+                return Ok(ProcessResult {
+                    success: false,
+                    message: format!("替换资源文件失败: {}", e),
+                    output_path: None,
+                    step: Some("assets".to_string()),
+                    ..Default::default()
+                });
+            }
+        },
+        _ => Vec::new(),
+    };
+    
+    if let Err((step, message)) = hooks::run_hook(app, "before_rebuild", hook_cfg.before_rebuild.as_deref(), hook_cfg.timeout_secs, &hook_ctx) {
+        return Ok(hook_failure(step, message));
+    }
+    
+    // 第三步：回编译
+    let rebuild = recorder
+        .run(
+            "rebuild",
+            Command::new(&java_path)
+                .args(["-jar", &apktool_path, "b", work_dir.to_str().unwrap(), "-o", rebuilt_apk.to_str().unwrap()]),
+        )
+        .map_err(|e| format!("回编译命令执行失败: {}", e))?;
+    
+    if !rebuild.status.success() {
+        let stderr = String::from_utf8_lossy(&rebuild.stderr);
+        let stdout = String::from_utf8_lossy(&rebuild.stdout);
+        return Ok(ProcessResult {
+            success: false,
+            message: format!("回编译失败: {} {}", stderr, stdout),
+            output_path: None,
+            step: Some("rebuild".to_string()),
+            ..Default::default()
+        });
+    }
+    
+    let min_ratio = min_output_ratio.unwrap_or(entries::DEFAULT_MIN_OUTPUT_RATIO);
+    let input_size = fs::metadata(&apk_path).map(|m| m.len()).unwrap_or(0);
+    if let Err(e) = entries::verify_rebuilt_apk(&rebuilt_apk, input_size, min_ratio) {
+        return Ok(ProcessResult {
+            success: false,
+            message: format!("回编译输出校验失败: {}", e),
+            step: Some("rebuild_verify".to_string()),
+            ..Default::default()
+        });
+    }
+
+    let rebuild_output = format!("{}\n{}", String::from_utf8_lossy(&rebuild.stdout), String::from_utf8_lossy(&rebuild.stderr));
+    tool_warnings.extend(diagnostics::scan_apktool_output("rebuild", &rebuild_output));
+    if !dummies.names.is_empty() {
+        match diagnostics::dummy_names_in_apk(&rebuilt_apk, &dummies.names) {
+            Ok(leaked) if !leaked.is_empty() => {
+                warnings.push(format!("APKTOOL_DUMMY 占位资源进入了输出的 resources.arsc: {}", leaked.join(", ")));
+                tool_warnings.push(diagnostics::ToolWarning {
+                    stage: "rebuild".to_string(),
+                    description: "APKTOOL_DUMMY 占位资源进入了输出",
+                    resource: true,
+                    severity: diagnostics::Severity::High,
+                    count: leaked.len() as u32,
+                });
+            }
+            Ok(_) => {}
+            Err(e) => warnings.push(format!("检查输出中的占位资源失败: {}", e)),
+        }
+    }
+    let threshold = apktool_warning_threshold.unwrap_or(diagnostics::DEFAULT_RESOURCE_WARNING_THRESHOLD);
+    let resource_warnings = diagnostics::resource_warning_count(tool_warnings);
+    if strict_resource_warnings.unwrap_or(false) && resource_warnings > threshold {
+        return Ok(ProcessResult {
+            success: false,
+            message: format!("严格模式：apktool 报告了 {} 条资源相关警告（阈值 {}），输出的 APK 可能显示异常或崩溃，已停止处理", resource_warnings, threshold),
+            step: Some("resource_warnings".to_string()),
+            ..Default::default()
+        });
+    }
+    
+    let align_input = if strip_debug_info.unwrap_or(false) {
+        match dex::strip_apk(&rebuilt_apk, &stripped_apk) {
+            Ok(reduction) => {
+                emit_progress(app, "strip_debug_info", format!("已去除 DEX 调试信息，节省 {} 字节", reduction.saved_bytes));
+                stripped_apk
+            }
+            Err(e) => {
+                warnings.push(format!("去除 DEX 调试信息失败，已使用原始 DEX: {}", e));
+                rebuilt_apk.clone()
+            }
+        }
+    } else {
+        rebuilt_apk.clone()
+    };
+    let align_input = if stealth {
+        match stealth::normalize_apk(&align_input, &normalized_apk) {
+            Ok(removed) => {
+                emit_progress(app, "stealth", format!("已统一条目时间，去掉 {} 个残留签名文件", removed.len()));
+                normalized_apk
+            }
+            Err(e) => {
+                return Ok(ProcessResult {
+                    success: false,
+                    message: format!("统一 ZIP 条目时间失败: {}", e),
+                    step: Some("stealth".to_string()),
+                    ..Default::default()
+                });
+            }
+        }
+    } else {
+        align_input
+    };
+    
+    // 第四步：对齐
+    let align = recorder
+        .run(
+            "zipalign",
+            Command::new(&zipalign_path)
+                .args(["-f", "-v", "4", align_input.to_str().unwrap(), aligned_apk.to_str().unwrap()]),
+        )
+        .map_err(|e| format!("对齐命令执行失败: {}", e))?;
+    
+    if !align.status.success() {
+        let message = crate::native_deps::diagnose_failure(Path::new(&zipalign_path), &align)
+            .unwrap_or_else(|| format!("对齐失败: {}", String::from_utf8_lossy(&align.stderr)));
+        return Ok(ProcessResult {
+            success: false,
+            message,
+            output_path: Some(align_input.to_string_lossy().to_string()),
+            step: Some("zipalign".to_string()),
+            ..Default::default()
+        });
+    }
+    
+    // 第五步：签名
+    let keystore = match signing::KeystoreConfig::resolve(&keystore_path, key_alias_override.as_deref(), key_passwords.as_deref().unwrap_or_default()) {
+        Ok(keystore) => keystore,
+        Err(e) => {
+            return Ok(ProcessResult {
+                success: false,
+                message: format!("签名失败: {}", e),
+                output_path: Some(aligned_apk.to_string_lossy().to_string()),
+                step: Some("sign".to_string()),
+                ..Default::default()
+            })
+        }
+    };
+    let key = match signing::SigningKey::open(pkcs11_signing.as_ref(), &keystore) {
+        Ok(key) => key,
+        Err(e) => {
+            return Ok(ProcessResult {
+                success: false,
+                message: format!("签名失败: {}", e),
+                output_path: Some(aligned_apk.to_string_lossy().to_string()),
+                step: Some("sign".to_string()),
+                ..Default::default()
+            })
+        }
+    };
+    let mut sign = sign_apk(recorder, &java_path, &apksigner_path, &key, &aligned_apk, &signed_apk)?;
+    
+    if !sign.status.success() {
+        let stderr = key.failure_message(&String::from_utf8_lossy(&sign.stderr));
+        // 令牌签名失败不做任何重试，多次 PIN 错误会锁定令牌
+        let hint = match &key {
+            signing::SigningKey::Keystore(_) => signing::diagnose_keystore_error(&stderr),
+            signing::SigningKey::Pkcs11(_) => None,
+        };
+        let mut message = match &hint {
+            Some(h) => format!("签名失败: {}\n建议: {}", stderr, h),
+            None => format!("签名失败: {}", stderr),
+        };
+        
+        if hint.is_some() && auto_convert_keystore.unwrap_or(false) {
+            let profiles_dir = app.state::<AppPaths>().data_dir.join("signing-profiles");
+            match signing::convert_into_profiles(&profiles_dir, Some(&java_path), &keystore.path, &keystore.store_password) {
+                Ok((converted, original_fp, converted_fp)) => {
+                    emit_progress(
+                        app,
+                        "sign",
+                        format!(
+                            "已将密钥库转换为 PKCS12: {} -> {}（原指纹 {}，新指纹 {}）",
+                            keystore.path,
+                            converted.display(),
+                            original_fp.as_deref().unwrap_or("未知"),
+                            converted_fp.as_deref().unwrap_or("未知"),
+                        ),
+                    );
+                    let converted_keystore = signing::KeystoreConfig {
+                        path: converted.to_string_lossy().to_string(),
+                        ..keystore.clone()
+                    };
+                    sign = sign_apk(recorder, &java_path, &apksigner_path, &signing::SigningKey::Keystore(converted_keystore), &aligned_apk, &signed_apk)?;
+                    message = format!("转换密钥库后重新签名失败: {}", String::from_utf8_lossy(&sign.stderr));
+                }
+                Err(e) => message = format!("{}\n自动转换密钥库失败: {}", message, e),
+            }
+        }
+        
+        if !sign.status.success() {
+            return Ok(ProcessResult {
+                success: false,
+                message,
+                output_path: Some(aligned_apk.to_string_lossy().to_string()),
+                step: Some("sign".to_string()),
+                ..Default::default()
+            });
+        }
+    }
+    
+    // 签名输出校验通过后才替换最终输出
+    if let Err(e) = signing::verify_signature(&signed_apk, &java_path, &apksigner_path) {
+        return Ok(ProcessResult {
+            success: false,
+            message: e.to_string(),
+            output_path: Some(signed_apk.to_string_lossy().to_string()),
+            step: Some("verify".to_string()),
+            ..Default::default()
+        });
+    }
+    if let Err(e) = artifacts::promote(&signed_apk, &final_apk) {
+        return Ok(ProcessResult {
+            success: false,
+            message: format!("写入输出文件失败: {}", e),
+            output_path: Some(signed_apk.to_string_lossy().to_string()),
+            step: Some("sign".to_string()),
+            ..Default::default()
+        });
+    }
+    
+    // 兼容性检查：输出 APK 不应悄悄抬高最低系统版本
+    match compat::check_min_sdk(Path::new(&apk_path), &final_apk, min_sdk_override.is_some()) {
+        Ok(Some(warning)) => {
+            emit_progress(app, "compat_check", warning.clone());
+            warnings.push(warning);
+        }
+        Ok(None) => {}
+        Err(message) => {
+            return Ok(ProcessResult {
+                success: false,
+                message,
+                output_path: Some(final_apk.to_string_lossy().to_string()),
+                step: Some("compat_check".to_string()),
+                ..Default::default()
+            });
+        }
+    }
+    
+    let sign_ctx = hooks::HookContext { output_apk: Some(&final_apk), ..hook_ctx };
+    if let Err((step, message)) = hooks::run_hook(app, "after_sign", hook_cfg.after_sign.as_deref(), hook_cfg.timeout_secs, &sign_ctx) {
+        return Ok(hook_failure(step, message));
+    }
+    
+    for dir in [&stage_dir, &work_dir] {
+        match safe_path::remove_dir_all(dir) {
+            Ok(bytes) => emit_progress(app, "cleanup", format!("已清理 {} ({} 字节)", dir.display(), bytes)),
+            Err(e) => warnings.push(e.to_string()),
+        }
+    }
+    
+    // 第六步：安装
+    if install_after {
+        if let Some(device) = device_id {
+            let app_paths = app.state::<AppPaths>();
+            // 安装期间登记传输，重启 adb server 前会等待其结束
+            let coordinator = app.state::<AdbCoordinator>();
+            let _transfer = coordinator.begin_transfer(&device, "install");
+            // 空间明显不足时直接失败，不等 adb install 报 INSTALL_FAILED_INSUFFICIENT_STORAGE
+            let output_size = fs::metadata(&final_apk).map(|m| m.len()).unwrap_or(0);
+            match device_storage::query(&device) {
+                Ok(storage) if storage.free_bytes < output_size => {
+                    const MB: f64 = 1024.0 * 1024.0;
+                    return Ok(ProcessResult {
+                        success: false,
+                        message: format!(
+                            "设备存储空间不足: /data 仅剩 {:.1} MB，安装至少需要 {:.1} MB（输出 APK 的大小）。请清理设备空间后重新安装 {}",
+                            storage.free_bytes as f64 / MB,
+                            output_size as f64 / MB,
+                            final_apk.display()
+                        ),
+                        output_path: Some(final_apk.to_string_lossy().to_string()),
+                        step: Some("precheck".to_string()),
+                        ..Default::default()
+                    });
+                }
+                Ok(_) => {}
+                Err(e) => recorder.note(&format!("无法检查设备存储空间，跳过: {}", e)),
+            }
+            let rollback_enabled = rollback_on_launch_failure.unwrap_or(false);
+            let stashed = if rollback_enabled {
+                match rollback::stash_installed(&app_paths, &device, &new_package) {
+                    Ok(stashed) => stashed,
+                    Err(e) => {
+                        warnings.push(e);
+                        None
+                    }
+                }
+            } else {
+                None
+            };
+            let resolved = device_prefs::resolve_for(
+                &app_paths,
+                &device,
+                &install_flags.unwrap_or_default(),
+                post_install_actions.as_deref().unwrap_or_default(),
+            );
+            if !resolved.applied.is_empty() {
+                let message = format!("应用设备偏好: {}", resolved.applied.join("，"));
+                recorder.note(&message);
+                emit_progress(app, "install", message);
+            }
+            if resolved.keep_awake {
+                device_prefs::wake_screen(&device);
+            }
+            let install = recorder.run(
+                "install",
+                crate::adb::command().args(["-s", &device, "install"]).args(resolved.install_args()).arg(&final_apk),
+            );
+            app.state::<DeviceCache>().invalidate(&device, device_cache::DATASET_INSTALLED_APPS);
+            
+            match install {
+                Ok(out) => {
+                    let stdout = String::from_utf8_lossy(&out.stdout);
+                    if out.status.success() && stdout.contains("Success") {
+                        let record = installs::InstallRecord {
+                            source_url: download::source_url(&app_paths, &apk_path),
+                            ..installs::install_record(&device, &new_package, &final_apk, None)
+                        };
+                        if let Err(e) = app.state::<installs::InstallHistory>().append(&app_paths, record) {
+                            warnings.push(format!("写入安装记录失败: {}", e));
+                        }
+                        if rollback_enabled {
+                            emit_progress(app, "launch_check", "正在启动应用验证安装结果...");
+                            if let Err(launch_error) = rollback::verify_launch(&device, &new_package) {
+                                return Ok(launch_failure(&app_paths, &device, &new_package, stashed.is_some(), &final_apk, launch_error));
+                            }
+                        }
+                        let actions = resolved.post_install_actions;
+                        let post_install = (!actions.is_empty()).then(|| {
+                            emit_progress(app, "post_install", format!("执行 {} 个安装后动作", actions.len()));
+                            post_install::run_actions(&device, &new_package, &actions)
+                        });
+                        return Ok(ProcessResult {
+                            success: true,
+                            message: format!("✅ 安装成功! 新包名: {}", new_package),
+                            output_path: Some(final_apk.to_string_lossy().to_string()),
+                            step: Some("install".to_string()),
+                            post_install,
+                            content_uris,
+                            changes,
+                            ..Default::default()
+                        });
+                    } else {
+                        return Ok(ProcessResult {
+                            success: false,
+                            message: format!("安装失败: {}", stdout),
+                            output_path: Some(final_apk.to_string_lossy().to_string()),
+                            step: Some("install".to_string()),
+                            ..Default::default()
+                        });
+                    }
+                }
+                Err(e) => {
+                    return Ok(ProcessResult {
+                        success: false,
+                        message: format!("安装命令执行失败: {}", e),
+                        output_path: Some(final_apk.to_string_lossy().to_string()),
+                        step: Some("install".to_string()),
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+    }
+    
+    Ok(ProcessResult {
+        success: true,
+        message: format!("✅ 处理完成! 新包名: {}", new_package),
+        output_path: Some(final_apk.to_string_lossy().to_string()),
+        step: Some("complete".to_string()),
+        content_uris,
+        changes,
+        ..Default::default()
+    })
+}
+
+
+/// Manifest 编辑没有生效（如命名空间前缀不是 `android`）
+fn manifest_failure(message: String) -> ProcessResult {
+    ProcessResult { success: false, message, output_path: None, step: Some("manifest".to_string()), ..Default::default() }
+}
+
+fn hook_failure(step: String, message: String) -> ProcessResult {
+    ProcessResult {
+        success: false,
+        message: format!("钩子执行失败: {}", message),
+        output_path: None,
+        step: Some(step),
+        ..Default::default()
+    }
+}
+
+/// 新版本启动失败：有备份时恢复安装并报告 rolled_back，否则报告 launch_check 失败
+fn launch_failure(
+    app_paths: &AppPaths,
+    device: &str,
+    package: &str,
+    has_stash: bool,
+    final_apk: &Path,
+    launch_error: String,
+) -> ProcessResult {
+    let output_path = Some(final_apk.to_string_lossy().to_string());
+    if !has_stash {
+        return ProcessResult {
+            success: false,
+            message: format!("安装成功但启动验证失败: {}（安装前设备上没有该应用，无可回滚的版本）", launch_error),
+            output_path,
+            step: Some("launch_check".to_string()),
+            ..Default::default()
+        };
+    }
+    let restore = match rollback::restore(app_paths, device, package, Some(final_apk)) {
+        Ok(message) => format!("已回滚: {}", message),
+        Err(e) => format!("回滚失败: {}", e),
+    };
+    ProcessResult {
+        success: false,
+        message: format!("新版本启动验证失败: {}\n{}", launch_error, restore),
+        output_path,
+        step: Some("rolled_back".to_string()),
+        ..Default::default()
+    }
+}
+
+pub fn sign_apk(
+    recorder: &mut Recorder,
+    java_path: &str,
+    apksigner_path: &str,
+    key: &signing::SigningKey,
+    aligned_apk: &Path,
+    final_apk: &Path,
+) -> Result<std::process::Output, String> {
+    let mut cmd = Command::new(java_path);
+    cmd.args(["-jar", apksigner_path, "sign"]);
+    key.apply(&mut cmd);
+    recorder
+        .run(
+            "sign",
+            cmd.args([
+                "--v1-signing-enabled", "true",
+                "--v2-signing-enabled", "false",
+                "--out", final_apk.to_str().unwrap(),
+                aligned_apk.to_str().unwrap(),
+            ]),
+        )
+        .map_err(|e| format!("签名命令执行失败: {}", e))
+}
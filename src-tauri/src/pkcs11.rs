@@ -0,0 +1,147 @@
+//! 使用 PKCS#11 令牌（如 YubiHSM）中的密钥签名，密钥不离开硬件。
+//! apksigner / keytool 通过 SunPKCS11 提供者访问令牌：按签名配置生成临时的提供者配置文件，
+//! 签名结束后删除。PIN 通过环境变量传给子进程，不出现在命令行中。
+//! 口令错误不会自动重试——多次失败会锁定令牌。
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::process::Command;
+
+use crate::input_path::{self, Expect};
+use crate::signing::{self, CertificateEntry, PipelineError, SecretSource};
+
+const PROVIDER_CLASS: &str = "sun.security.pkcs11.SunPKCS11";
+/// 传递 PIN 的环境变量，apksigner 使用 `env:` 形式、keytool 使用 `:env` 形式读取
+const PIN_ENV: &str = "APK_DISGUISE_PKCS11_PIN";
+
+/// PKCS#11 签名配置
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct Pkcs11Profile {
+    /// SunPKCS11 配置文件（含 `name` 与 `library`），其中的 slot 设置会被 `slot` 覆盖
+    pub config_path: String,
+    pub slot: Option<u32>,
+    pub pin_source: SecretSource,
+    /// 令牌中签名证书的别名
+    pub cert_alias: String,
+}
+
+/// 可区分处理的 PKCS#11 失败原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pkcs11Failure {
+    TokenNotPresent,
+    WrongPin,
+    PinLocked,
+    /// JRE 中没有 SunPKCS11（旧版或精简 JRE）
+    ProviderUnavailable,
+}
+
+impl Pkcs11Failure {
+    pub fn describe(self) -> &'static str {
+        match self {
+            Pkcs11Failure::TokenNotPresent => "未检测到 PKCS#11 令牌，请确认设备已插入且 slot 设置正确",
+            Pkcs11Failure::WrongPin => "令牌 PIN 错误。为避免锁定令牌不会自动重试，请核对 PIN 来源后再手动重试",
+            Pkcs11Failure::PinLocked => "令牌 PIN 已被锁定，需要使用管理员口令（SO PIN）解锁",
+            Pkcs11Failure::ProviderUnavailable => "当前 Java 不支持 SunPKCS11 提供者，请改用 JDK 9 及以上的完整 JDK",
+        }
+    }
+}
+
+/// 按 apksigner / keytool 的错误输出判断失败原因
+pub fn classify(stderr: &str) -> Option<Pkcs11Failure> {
+    let has = |patterns: &[&str]| patterns.iter().any(|p| stderr.contains(p));
+    if has(&["CKR_PIN_LOCKED"]) {
+        Some(Pkcs11Failure::PinLocked)
+    } else if has(&["CKR_PIN_INCORRECT", "CKR_PIN_LEN_RANGE", "FailedLoginException"]) {
+        Some(Pkcs11Failure::WrongPin)
+    } else if has(&["CKR_TOKEN_NOT_PRESENT", "CKR_SLOT_ID_INVALID", "CKR_DEVICE_REMOVED", "Token not present", "no token"]) {
+        Some(Pkcs11Failure::TokenNotPresent)
+    } else if has(&["ClassNotFoundException: sun.security.pkcs11", "Failed to load provider", "SunPKCS11 not available"]) {
+        Some(Pkcs11Failure::ProviderUnavailable)
+    } else {
+        None
+    }
+}
+
+/// 失败信息：能识别原因时给出处理建议
+pub fn failure_message(stderr: &str) -> String {
+    match classify(stderr) {
+        Some(failure) => format!("{}\n{}", failure.describe(), stderr.trim()),
+        None => stderr.trim().to_string(),
+    }
+}
+
+/// 在用户的配置基础上写入 slot，生成提供者配置
+fn provider_config(profile: &Pkcs11Profile) -> Result<String, PipelineError> {
+    let path = input_path::arg(&profile.config_path, Expect::File)?;
+    let base = fs::read_to_string(&path)?;
+    if !base.lines().any(|line| line.trim_start().starts_with("library")) {
+        return Err(PipelineError::InvalidInput(format!("{} 中缺少 library 设置", path)));
+    }
+    let mut config: String = match profile.slot {
+        Some(_) => base
+            .lines()
+            .filter(|line| !line.trim_start().starts_with("slot"))
+            .map(|line| format!("{}\n", line))
+            .collect(),
+        None => base,
+    };
+    if let Some(slot) = profile.slot {
+        config.push_str(&format!("\nslot = {}\n", slot));
+    }
+    Ok(config)
+}
+
+/// 一次签名所用的临时提供者配置与 PIN，离开作用域时删除配置文件
+pub struct Pkcs11Session {
+    config_file: tempfile::NamedTempFile,
+    pin: String,
+    alias: String,
+}
+
+impl Pkcs11Session {
+    pub fn open(profile: &Pkcs11Profile) -> Result<Self, PipelineError> {
+        let config = provider_config(profile)?;
+        let pin = profile.pin_source.resolve("令牌 PIN")?;
+        let mut config_file = tempfile::Builder::new().prefix("pkcs11-").suffix(".cfg").tempfile()?;
+        config_file.write_all(config.as_bytes())?;
+        config_file.flush()?;
+        Ok(Pkcs11Session { config_file, pin, alias: profile.cert_alias.clone() })
+    }
+
+    fn config_path(&self) -> String {
+        self.config_file.path().to_string_lossy().into_owned()
+    }
+
+    /// 为 apksigner sign 加上令牌参数与 PIN 环境变量
+    pub fn apply_signer_args(&self, cmd: &mut Command) {
+        cmd.args(["--ks", "NONE", "--ks-type", "PKCS11", "--provider-class", PROVIDER_CLASS, "--provider-arg"])
+            .arg(self.config_path())
+            .args(["--ks-pass", &format!("env:{}", PIN_ENV), "--ks-key-alias", &self.alias])
+            .env(PIN_ENV, &self.pin);
+    }
+
+    /// 列出令牌中的证书（一次 keytool 登录）
+    pub fn list_certificates(&self, java_path: Option<&str>) -> Result<Vec<CertificateEntry>, PipelineError> {
+        let output = Command::new(signing::keytool_path(java_path))
+            .args(["-list", "-v", "-keystore", "NONE", "-storetype", "PKCS11", "-providerClass", PROVIDER_CLASS, "-providerArg"])
+            .arg(self.config_path())
+            .args(["-storepass:env", PIN_ENV])
+            .env(PIN_ENV, &self.pin)
+            .output()
+            .map_err(|e| PipelineError::Tool { step: "keytool".to_string(), message: e.to_string() })?;
+        if !output.status.success() {
+            let stderr = format!("{}{}", String::from_utf8_lossy(&output.stderr), String::from_utf8_lossy(&output.stdout));
+            return Err(PipelineError::Tool { step: "keytool".to_string(), message: failure_message(&stderr) });
+        }
+        Ok(signing::parse_keytool_list(&String::from_utf8_lossy(&output.stdout)))
+    }
+}
+
+/// 列出 PKCS#11 令牌中的证书，用于选择签名别名。PIN 错误时直接返回，不重试
+#[tauri::command]
+pub async fn list_pkcs11_certificates(profile: Pkcs11Profile, java_path: Option<String>) -> Result<Vec<CertificateEntry>, PipelineError> {
+    let java_path = java_path.as_deref().map(input_path::tool_arg).transpose()?;
+    Pkcs11Session::open(&profile)?.list_certificates(java_path.as_deref())
+}
@@ -0,0 +1,424 @@
+//! `pm list packages` 输出解析。不同系统版本与厂商 ROM 会在行尾附加 `uid:10234`、`sharedUid:...` 等字段，
+//! 顺序不固定，需要先剥离这些字段再取包名。
+//! 另外解析 `dumpsys package <包名>`，用于按需加载单个应用的详细信息，以及启用 / 停用应用。
+
+use serde::{Deserialize, Serialize};
+
+/// `pm list packages` 的一行
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PackageLine {
+    /// `-f` 时的 APK 路径
+    pub path: Option<String>,
+    pub package: String,
+    pub uid: Option<u32>,
+    pub shared_uid: Option<String>,
+    pub version_code: Option<i64>,
+    pub installer: Option<String>,
+    /// 无法识别的行尾字段，只记录不报错
+    pub unknown: Vec<String>,
+}
+
+pub fn is_package_name(name: &str) -> bool {
+    regex::Regex::new(r"^[A-Za-z][A-Za-z0-9_]*(\.[A-Za-z0-9_]+)*$").unwrap().is_match(name)
+}
+
+/// 行尾的 `key:value` / `key=value` 字段
+fn trailing_field(token: &str) -> Option<(&str, &str)> {
+    let split = token.find([':', '='])?;
+    let (key, value) = (&token[..split], &token[split + 1..]);
+    (!key.is_empty() && key.chars().all(|c| c.is_ascii_alphabetic())).then_some((key, value))
+}
+
+/// 解析一行；不是 `package:` 开头或包名不合法时返回 `None`
+pub fn parse_package_line(line: &str) -> Option<PackageLine> {
+    let mut rest = line.trim().strip_prefix("package:")?.trim();
+    let mut parsed = PackageLine::default();
+
+    // 从行尾逐个剥离字段，直到只剩包名（或 路径=包名）
+    while let Some((head, token)) = rest.rsplit_once(char::is_whitespace) {
+        let Some((key, value)) = trailing_field(token) else {
+            break;
+        };
+        match key {
+            "uid" => parsed.uid = value.parse().ok(),
+            "sharedUid" | "sharedUserId" => parsed.shared_uid = Some(value.to_string()),
+            "versionCode" => parsed.version_code = value.parse().ok(),
+            "installer" => parsed.installer = Some(value.to_string()),
+            _ => parsed.unknown.push(token.to_string()),
+        }
+        rest = head.trim_end();
+    }
+
+    let package = match rest.rfind('=') {
+        Some(pos) => {
+            parsed.path = Some(rest[..pos].to_string());
+            &rest[pos + 1..]
+        }
+        None => rest,
+    };
+    if !is_package_name(package) {
+        return None;
+    }
+    parsed.package = package.to_string();
+    parsed.unknown.reverse();
+    Some(parsed)
+}
+
+/// 解析整段输出，跳过无法识别的行
+pub fn parse_package_list(output: &str) -> Vec<PackageLine> {
+    output.lines().filter_map(parse_package_line).collect()
+}
+
+/// 输出格式变化的提示：包含未知行尾字段或无法解析的 `package:` 行
+pub fn format_warnings(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| match parse_package_line(line) {
+            Some(p) if !p.unknown.is_empty() => Some(format!("pm 输出包含未知字段 {:?}: {}", p.unknown, line.trim())),
+            None if line.trim().starts_with("package:") => Some(format!("无法解析 pm 输出: {}", line.trim())),
+            _ => None,
+        })
+        .collect()
+}
+
+/// 设备上的用户（`pm list users`），如主用户 0 与工作资料 10
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct DeviceUser {
+    pub id: u32,
+    pub name: String,
+    pub running: bool,
+}
+
+/// 解析 `pm list users`：`UserInfo{0:Owner:13} running`（Android 7 起名称可能为 `机主` 等本地化文本）
+pub fn parse_user_list(output: &str) -> Vec<DeviceUser> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            let info = line.strip_prefix("UserInfo{")?;
+            let (info, rest) = info.split_once('}')?;
+            let mut fields = info.split(':');
+            let id = fields.next()?.parse().ok()?;
+            let name = fields.next().unwrap_or_default().to_string();
+            Some(DeviceUser { id, name, running: rest.contains("running") })
+        })
+        .collect()
+}
+
+/// 列出设备上的用户
+#[tauri::command]
+pub fn get_users(device_id: String) -> Result<Vec<DeviceUser>, String> {
+    Ok(parse_user_list(&crate::adb::shell(&device_id, &["pm", "list", "users"])?))
+}
+
+/// 单个已安装应用的详细信息（`dumpsys package`）
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct AppDetails {
+    pub package_name: String,
+    pub version_name: Option<String>,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number | null"))]
+    pub version_code: Option<i64>,
+    /// 设备本地时间，如 `2024-01-02 10:11:12`
+    pub first_install_time: Option<String>,
+    pub last_update_time: Option<String>,
+    pub target_sdk: Option<u32>,
+    pub min_sdk: Option<u32>,
+    /// 安装来源，如 `com.android.vending`；adb 安装时为空
+    pub installer: Option<String>,
+    /// `codePath`：APK 所在目录
+    pub code_path: Option<String>,
+    /// `pm path` 给出的 APK 文件，base.apk 在前
+    pub apk_paths: Vec<String>,
+}
+
+/// `dumpsys package` 中该包的段落：从 `Package [包名]` 开始，到下一个包或顶层段落为止。
+/// Android 14 的 `firstInstallTime` 位于段落内的 `User 0:` 子项中，同样包含在内
+fn package_section<'a>(output: &'a str, package: &str) -> Vec<&'a str> {
+    let header = format!("Package [{}]", package);
+    output
+        .lines()
+        .skip_while(|line| !line.contains(&header))
+        .enumerate()
+        .take_while(|(i, line)| *i == 0 || ((line.is_empty() || line.starts_with(' ')) && !line.contains("Package [")))
+        .map(|(_, line)| line.trim())
+        .collect()
+}
+
+/// 解析 `dumpsys package <包名>`，兼容 Android 7（`versionCode=42 targetSdk=25`）
+/// 到 Android 14（`versionCode=42 minSdk=24 targetSdk=34`、按用户列出安装时间）的格式；每个字段取第一次出现的值
+pub fn parse_dumpsys_package(output: &str, package: &str) -> Option<AppDetails> {
+    let section = package_section(output, package);
+    if section.is_empty() {
+        return None;
+    }
+    let mut details = AppDetails { package_name: package.to_string(), ..Default::default() };
+    for line in section {
+        // 值可能含空格（versionName、时间），整行只有一个字段时取等号后的全部内容
+        if let Some(value) = line.strip_prefix("versionName=") {
+            details.version_name.get_or_insert_with(|| value.to_string());
+            continue;
+        }
+        if let Some(value) = line.strip_prefix("firstInstallTime=") {
+            details.first_install_time.get_or_insert_with(|| value.to_string());
+            continue;
+        }
+        if let Some(value) = line.strip_prefix("lastUpdateTime=") {
+            details.last_update_time.get_or_insert_with(|| value.to_string());
+            continue;
+        }
+        for token in line.split_whitespace() {
+            let Some((key, value)) = token.split_once('=') else {
+                continue;
+            };
+            match key {
+                "versionCode" if details.version_code.is_none() => details.version_code = value.parse().ok(),
+                "targetSdk" if details.target_sdk.is_none() => details.target_sdk = value.parse().ok(),
+                "minSdk" if details.min_sdk.is_none() => details.min_sdk = value.parse().ok(),
+                "installerPackageName" if value != "null" => {
+                    details.installer.get_or_insert_with(|| value.to_string());
+                }
+                "codePath" => {
+                    details.code_path.get_or_insert_with(|| value.to_string());
+                }
+                _ => {}
+            }
+        }
+    }
+    Some(details)
+}
+
+/// 获取单个应用的版本、安装时间、targetSdk、安装来源与 APK 路径，供界面展开某一行时加载
+#[tauri::command]
+pub fn get_app_details(device_id: String, package_name: String) -> Result<AppDetails, String> {
+    if !is_package_name(&package_name) {
+        return Err(format!("无效的包名: {}", package_name));
+    }
+    let output = crate::adb::shell(&device_id, &["dumpsys", "package", &package_name])?;
+    let mut details = parse_dumpsys_package(&output, &package_name).ok_or_else(|| format!("设备上未安装 {}", package_name))?;
+    if let Ok(pm_path) = crate::adb::shell(&device_id, &["pm", "path", &package_name]) {
+        details.apk_paths = crate::device_files::parse_pm_path(&pm_path);
+    }
+    Ok(details)
+}
+
+/// 解析 `pm enable` / `pm disable-user` 的输出：成功时为 `Package <包名> new state: enabled`（或 `disabled-user`）；
+/// 受保护的包会得到 `java.lang.SecurityException: ...`，转为可读的错误
+pub fn parse_state_change(package: &str, output: &str, enabled: bool) -> Result<(), String> {
+    let output = output.trim();
+    if let Some(state) = output.lines().find_map(|line| line.split_once("new state:").map(|(_, state)| state.trim())) {
+        return if (state == "enabled") == enabled {
+            Ok(())
+        } else {
+            Err(format!("{} 的状态未改变，当前为 {}", package, state))
+        };
+    }
+    if let Some((_, detail)) = output.split_once("SecurityException:") {
+        let detail = detail.lines().next().unwrap_or_default().trim();
+        return Err(format!("系统不允许更改受保护应用 {} 的状态: {}", package, detail));
+    }
+    Err(if output.is_empty() { "pm 没有输出".to_string() } else { output.to_string() })
+}
+
+/// 启用或停用应用（`pm enable` / `pm disable-user --user 0`），不卸载、不清除数据
+#[tauri::command]
+pub fn set_app_enabled(
+    app: tauri::AppHandle,
+    cache: tauri::State<'_, crate::device_cache::DeviceCache>,
+    device_id: String,
+    package_name: String,
+    enabled: bool,
+) -> Result<(), String> {
+    if !is_package_name(&package_name) {
+        return Err(format!("无效的包名: {}", package_name));
+    }
+    let action = if enabled { "enable" } else { "disable-user" };
+    cache.invalidate(&device_id, crate::device_cache::DATASET_INSTALLED_APPS);
+    let result = crate::adb::command()
+        .args(["-s", &device_id, "shell", "pm", action, "--user", "0", &package_name])
+        .output()
+        .map_err(|e| e.to_string())
+        .and_then(|output| {
+            let text = format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+            parse_state_change(&package_name, &text, enabled)
+        });
+    let params = serde_json::json!({ "device": device_id, "package": package_name, "enabled": enabled });
+    crate::audit::record(&app, "set_app_enabled", params, &result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ANDROID_9: &str = "package:/data/app/com.example.app-1Xk3QbNz4mYl7dA2pQ==/base.apk=com.example.app\n\
+package:/system/priv-app/Settings/Settings.apk=com.android.settings\n";
+    const ANDROID_12: &str = "package:/data/app/~~aBcD1234efGH==/com.example.app-XyZ987==/base.apk=com.example.app uid:10213\n\
+package:/system/app/Bluetooth/Bluetooth.apk=com.android.bluetooth uid:1002\n";
+    const ANDROID_14: &str = "package:/data/app/~~q1w2e3==/com.example.app-r4t5y6==/base.apk=com.example.app versionCode:34 uid:10234 installer=com.android.vending\n\
+package:/product/app/Chrome/Chrome.apk=com.android.chrome versionCode:612309133 uid:10118 installer=null\n";
+    const ONE_UI: &str = "package:com.samsung.android.app.notes uid:10235\n\
+package:com.sec.android.app.launcher sharedUid:android.uid.system uid:1000\n\
+package:com.samsung.android.knox.containercore uid:5010 sharedUid:android.uid.knox\n";
+
+    #[test]
+    fn android_9_path_and_package() {
+        let lines = parse_package_list(ANDROID_9);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].path.as_deref(), Some("/data/app/com.example.app-1Xk3QbNz4mYl7dA2pQ==/base.apk"));
+        assert_eq!(lines[0].package, "com.example.app");
+        assert_eq!(lines[1].uid, None);
+        assert!(format_warnings(ANDROID_9).is_empty());
+    }
+
+    #[test]
+    fn android_12_uid_is_not_folded_into_the_package() {
+        let lines = parse_package_list(ANDROID_12);
+        assert_eq!(lines[0].path.as_deref(), Some("/data/app/~~aBcD1234efGH==/com.example.app-XyZ987==/base.apk"));
+        assert_eq!((lines[0].package.as_str(), lines[0].uid), ("com.example.app", Some(10213)));
+        assert_eq!((lines[1].package.as_str(), lines[1].uid), ("com.android.bluetooth", Some(1002)));
+    }
+
+    #[test]
+    fn android_14_trailing_fields_in_any_order() {
+        let lines = parse_package_list(ANDROID_14);
+        assert_eq!(
+            lines[0],
+            PackageLine {
+                path: Some("/data/app/~~q1w2e3==/com.example.app-r4t5y6==/base.apk".to_string()),
+                package: "com.example.app".to_string(),
+                uid: Some(10234),
+                version_code: Some(34),
+                installer: Some("com.android.vending".to_string()),
+                ..Default::default()
+            }
+        );
+        assert_eq!(lines[1].version_code, Some(612309133));
+        assert_eq!(lines[1].uid, Some(10118));
+    }
+
+    #[test]
+    fn one_ui_shared_uid_without_path() {
+        let lines = parse_package_list(ONE_UI);
+        assert_eq!(lines.len(), 3);
+        assert_eq!((lines[0].package.as_str(), lines[0].uid), ("com.samsung.android.app.notes", Some(10235)));
+        for line in &lines[1..] {
+            assert!(line.path.is_none());
+            assert!(line.shared_uid.as_deref().is_some_and(|uid| uid.starts_with("android.uid.")));
+            assert!(line.uid.is_some());
+        }
+    }
+
+    #[test]
+    fn unknown_segments_warn_instead_of_failing() {
+        let output = "package:com.example.app uid:10234 apexModule:com.android.foo\npackage:com.example.app uid:10234 trailing words\nnot a package line\n";
+        let lines = parse_package_list(output);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].package, "com.example.app");
+        assert_eq!(lines[0].unknown, ["apexModule:com.android.foo"]);
+        let warnings = format_warnings(output);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].contains("apexModule:com.android.foo"));
+        assert!(warnings[1].starts_with("无法解析 pm 输出"));
+    }
+
+    const DUMPSYS_ANDROID_7: &str = "Activity Resolver Table:
+  Non-Data Actions:
+      android.intent.action.MAIN:
+        5b3c1e2 com.example.app/.MainActivity filter 9d1f0a3
+
+Packages:
+  Package [com.example.app] (3f9a2b1):
+    userId=10087
+    pkg=Package{8e2d4c0 com.example.app}
+    codePath=/data/app/com.example.app-1
+    resourcePath=/data/app/com.example.app-1
+    legacyNativeLibraryDir=/data/app/com.example.app-1/lib
+    primaryCpuAbi=armeabi-v7a
+    versionCode=42 targetSdk=25
+    versionName=2.3.1 beta
+    splits=[base]
+    applicationInfo=ApplicationInfo{5c1a7f9 com.example.app}
+    flags=[ HAS_CODE ALLOW_CLEAR_USER_DATA ALLOW_BACKUP ]
+    dataDir=/data/user/0/com.example.app
+    timeStamp=2018-03-14 09:26:53
+    firstInstallTime=2018-03-14 09:26:55
+    lastUpdateTime=2018-05-02 18:01:07
+    installerPackageName=com.android.vending
+    signatures=PackageSignatures{1b2c3d4 [e5f6a7b8]}
+    User 0: ceDataInode=409714 installed=true hidden=false suspended=false stopped=false notLaunched=false enabled=0
+      runtime permissions:
+
+Dexopt state:
+  [com.example.app]
+    Instruction Set: arm
+";
+
+    const DUMPSYS_ANDROID_14: &str = "Packages:
+  Package [com.example.app] (a7c2e91):
+    appId=10234
+    pkg=Package{4b8d1f2 com.example.app}
+    codePath=/data/app/~~q1w2e3==/com.example.app-r4t5y6==
+    resourcePath=/data/app/~~q1w2e3==/com.example.app-r4t5y6==
+    primaryCpuAbi=arm64-v8a
+    versionCode=340012 minSdk=24 targetSdk=34
+    minExtensionVersions=[]
+    versionName=5.0.0
+    apkSigningVersion=3
+    flags=[ HAS_CODE ALLOW_CLEAR_USER_DATA ]
+    timeStamp=2024-02-01 12:00:03
+    lastUpdateTime=2024-02-10 08:15:44
+    installerPackageName=null
+    installerPackageUid=-1
+    initiatingPackageName=com.android.shell
+    User 0: ceDataInode=123456 installed=true hidden=false suspended=false distractionFlags=0 stopped=false notLaunched=false enabled=0 instant=false virtual=false quarantined=false
+      installReason=0
+      firstInstallTime=2024-02-01 12:00:05
+      uninstallReason=0
+    User 10: ceDataInode=0 installed=true hidden=false suspended=false
+      firstInstallTime=2024-02-05 09:30:00
+  Package [com.example.app.extension] (b1d3f57):
+    appId=10235
+    versionCode=7 minSdk=26 targetSdk=33
+    versionName=0.7
+    installerPackageName=com.android.vending
+
+Queries:
+  system apps queryable: false
+";
+
+    #[test]
+    fn dumpsys_android_7() {
+        let details = parse_dumpsys_package(DUMPSYS_ANDROID_7, "com.example.app").unwrap();
+        assert_eq!(details.version_name.as_deref(), Some("2.3.1 beta"));
+        assert_eq!(details.version_code, Some(42));
+        assert_eq!(details.target_sdk, Some(25));
+        assert_eq!(details.min_sdk, None);
+        assert_eq!(details.first_install_time.as_deref(), Some("2018-03-14 09:26:55"));
+        assert_eq!(details.last_update_time.as_deref(), Some("2018-05-02 18:01:07"));
+        assert_eq!(details.installer.as_deref(), Some("com.android.vending"));
+        assert_eq!(details.code_path.as_deref(), Some("/data/app/com.example.app-1"));
+    }
+
+    #[test]
+    fn dumpsys_android_14() {
+        let details = parse_dumpsys_package(DUMPSYS_ANDROID_14, "com.example.app").unwrap();
+        assert_eq!(details.version_name.as_deref(), Some("5.0.0"));
+        assert_eq!(details.version_code, Some(340012));
+        assert_eq!((details.min_sdk, details.target_sdk), (Some(24), Some(34)));
+        // 按用户列出的安装时间取用户 0
+        assert_eq!(details.first_install_time.as_deref(), Some("2024-02-01 12:00:05"));
+        assert_eq!(details.last_update_time.as_deref(), Some("2024-02-10 08:15:44"));
+        // adb 安装时安装来源为 null，下一个包的字段不会混进来
+        assert_eq!(details.installer, None);
+        assert_eq!(details.code_path.as_deref(), Some("/data/app/~~q1w2e3==/com.example.app-r4t5y6=="));
+    }
+
+    #[test]
+    fn dumpsys_without_the_package() {
+        assert!(parse_dumpsys_package(DUMPSYS_ANDROID_14, "com.example.missing").is_none());
+        let extension = parse_dumpsys_package(DUMPSYS_ANDROID_14, "com.example.app.extension").unwrap();
+        assert_eq!(extension.version_code, Some(7));
+    }
+}
@@ -0,0 +1,382 @@
+//! 部署策略：总部下发的推荐 / 禁用前缀与全局默认值，来自本地文件或 HTTPS 地址。
+//! 策略文件带 Ed25519 签名，使用构建时写入的公钥校验；最近一次校验通过的策略缓存在本地，离线时使用。
+
+use base64::Engine;
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Read;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tauri::Manager;
+
+use crate::paths::AppPaths;
+use crate::pipeline::{PackagePlan, ProcessConfig};
+use crate::TrustedPrefix;
+
+/// 构建时通过环境变量写入的策略公钥（base64，32 字节）；未设置时拒绝所有策略
+const POLICY_PUBLIC_KEY: Option<&str> = option_env!("APK_DISGUISE_POLICY_PUBLIC_KEY");
+/// 策略位置的环境变量，优先于设置文件
+const POLICY_LOCATION_ENV: &str = "APK_DISGUISE_POLICY";
+const SETTINGS_FILE: &str = "policy_settings.json";
+const CACHE_FILE: &str = "policy_cache.json";
+/// 远程策略文件大小上限
+const MAX_POLICY_BYTES: u64 = 1024 * 1024;
+
+/// 策略内容
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct PolicyDocument {
+    pub version: String,
+    /// 推荐的前缀，合并进 `scan_trusted_prefixes`
+    #[serde(default)]
+    pub recommended_prefixes: Vec<String>,
+    /// 禁用的前缀，新包名以其开头时拒绝处理
+    #[serde(default)]
+    pub banned_prefixes: Vec<String>,
+    /// 允许使用的签名密钥库文件名；为空时不限制
+    #[serde(default)]
+    pub signing_profiles: Vec<String>,
+}
+
+/// 策略文件：`policy` 为策略 JSON 原文，`signature` 为对其 UTF-8 字节的 Ed25519 签名（base64）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SignedPolicy {
+    pub policy: String,
+    pub signature: String,
+}
+
+/// 当前生效的策略及其来源
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct ActivePolicy {
+    pub policy: PolicyDocument,
+    /// 本地路径或 HTTPS 地址
+    pub source: String,
+    /// 获取时间（Unix 时间戳，秒）
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub fetched_at: u64,
+    /// 最近一次获取失败，当前使用的是缓存
+    pub from_cache: bool,
+    /// 最近一次获取失败的原因
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct PolicySettings {
+    location: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedPolicy {
+    signed: SignedPolicy,
+    source: String,
+    fetched_at: u64,
+}
+
+/// 当前生效的策略
+#[derive(Default)]
+pub struct PolicyState(Mutex<Option<ActivePolicy>>);
+
+impl PolicyState {
+    pub fn current(&self) -> Option<PolicyDocument> {
+        self.0.lock().unwrap().as_ref().map(|active| active.policy.clone())
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// 解析 base64 公钥
+pub fn parse_public_key(encoded: &str) -> Result<VerifyingKey, String> {
+    let bytes = base64::engine::general_purpose::STANDARD.decode(encoded.trim()).map_err(|e| format!("策略公钥格式错误: {}", e))?;
+    let bytes: [u8; 32] = bytes.try_into().map_err(|_| "策略公钥长度应为 32 字节".to_string())?;
+    VerifyingKey::from_bytes(&bytes).map_err(|e| format!("策略公钥无效: {}", e))
+}
+
+/// 解析策略文件并校验签名，签名不符时拒绝
+pub fn verify(content: &str, key: &VerifyingKey) -> Result<(SignedPolicy, PolicyDocument), String> {
+    let signed: SignedPolicy = serde_json::from_str(content).map_err(|e| format!("策略文件格式错误: {}", e))?;
+    let signature = base64::engine::general_purpose::STANDARD.decode(signed.signature.trim()).map_err(|e| format!("策略签名格式错误: {}", e))?;
+    let signature = Signature::from_slice(&signature).map_err(|e| format!("策略签名格式错误: {}", e))?;
+    key.verify(signed.policy.as_bytes(), &signature).map_err(|_| "策略签名校验失败，文件可能被篡改".to_string())?;
+    let policy: PolicyDocument = serde_json::from_str(&signed.policy).map_err(|e| format!("策略内容格式错误: {}", e))?;
+    Ok((signed, policy))
+}
+
+fn normalize_prefix(prefix: &str) -> &str {
+    prefix.trim().trim_end_matches('.')
+}
+
+/// `package` 等于 `prefix` 或位于其下
+fn under_prefix(package: &str, prefix: &str) -> bool {
+    let prefix = normalize_prefix(prefix);
+    !prefix.is_empty() && (package == prefix || package.strip_prefix(prefix).is_some_and(|rest| rest.starts_with('.')))
+}
+
+/// 将策略合并进扫描得到的前缀：去掉被禁用的，推荐前缀以 `policy` 来源置顶（已存在的改为置顶）
+pub fn merge_prefixes(policy: &PolicyDocument, scanned: Vec<TrustedPrefix>) -> Vec<TrustedPrefix> {
+    let recommended: Vec<&str> = policy.recommended_prefixes.iter().map(|p| normalize_prefix(p)).filter(|p| !p.is_empty()).collect();
+    let mut merged: Vec<TrustedPrefix> = Vec::new();
+    for prefix in &recommended {
+        if !merged.iter().any(|p| p.prefix == *prefix) {
+            let count = scanned.iter().find(|p| p.prefix == *prefix).map_or(0, |p| p.count);
+            merged.push(TrustedPrefix { prefix: prefix.to_string(), count, source: "policy".to_string(), ..Default::default() });
+        }
+    }
+    merged.extend(scanned.into_iter().filter(|p| !recommended.contains(&p.prefix.as_str())));
+    merged.retain(|p| !policy.banned_prefixes.iter().any(|banned| under_prefix(&p.prefix, banned)));
+    merged
+}
+
+/// 检查本次处理是否违反策略：新包名使用了禁用前缀，或密钥库不在允许的签名配置中
+pub fn violation(policy: &PolicyDocument, config: &ProcessConfig, plan: &PackagePlan) -> Option<String> {
+    if let Some(banned) = policy.banned_prefixes.iter().find(|banned| under_prefix(&plan.new_package, banned)) {
+        return Some(format!("部署策略（版本 {}）禁止使用前缀 {}，新包名 {} 不能使用", policy.version, normalize_prefix(banned), plan.new_package));
+    }
+    let keystore_name = std::path::Path::new(&config.keystore_path).file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    if !policy.signing_profiles.is_empty() && !policy.signing_profiles.contains(&keystore_name) {
+        return Some(format!(
+            "部署策略（版本 {}）要求使用以下签名配置之一: {}，当前密钥库为 {}",
+            policy.version,
+            policy.signing_profiles.join("、"),
+            config.keystore_path
+        ));
+    }
+    None
+}
+
+/// 处理前按当前策略校验，违反时返回失败结果
+pub fn gate(state: &PolicyState, config: &ProcessConfig, plan: &PackagePlan) -> Option<crate::ProcessResult> {
+    let message = violation(&state.current()?, config, plan)?;
+    Some(crate::ProcessResult { success: false, message, step: Some("policy".to_string()), ..Default::default() })
+}
+
+fn location(paths: &AppPaths) -> Option<String> {
+    if let Ok(location) = std::env::var(POLICY_LOCATION_ENV) {
+        if !location.trim().is_empty() {
+            return Some(location.trim().to_string());
+        }
+    }
+    fs::read_to_string(paths.config_dir.join(SETTINGS_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str::<PolicySettings>(&content).ok())
+        .and_then(|settings| settings.location)
+        .filter(|location| !location.trim().is_empty())
+}
+
+fn fetch(paths: &AppPaths, location: &str) -> Result<String, String> {
+    if location.starts_with("http://") {
+        return Err("策略地址必须使用 HTTPS".to_string());
+    }
+    if !location.starts_with("https://") {
+        return fs::read_to_string(location).map_err(|e| format!("读取策略文件 {} 失败: {}", location, e));
+    }
+    let response = crate::http::agent_builder(paths, location)?.build().get(location).call().map_err(|e| format!("下载策略失败: {}", e))?;
+    let mut content = String::new();
+    response
+        .into_reader()
+        .take(MAX_POLICY_BYTES)
+        .read_to_string(&mut content)
+        .map_err(|e| format!("下载策略失败: {}", e))?;
+    Ok(content)
+}
+
+fn load_cache(paths: &AppPaths, key: &VerifyingKey) -> Option<(CachedPolicy, PolicyDocument)> {
+    let content = fs::read_to_string(paths.data_dir.join(CACHE_FILE)).ok()?;
+    let cached: CachedPolicy = serde_json::from_str(&content).ok()?;
+    let signed = serde_json::to_string(&cached.signed).ok()?;
+    // 缓存同样校验签名
+    let (_, policy) = verify(&signed, key).ok()?;
+    Some((cached, policy))
+}
+
+fn save_cache(paths: &AppPaths, cached: &CachedPolicy) -> Result<(), String> {
+    fs::create_dir_all(&paths.data_dir).map_err(|e| e.to_string())?;
+    let content = serde_json::to_string_pretty(cached).map_err(|e| e.to_string())?;
+    fs::write(paths.data_dir.join(CACHE_FILE), content).map_err(|e| e.to_string())
+}
+
+/// 从配置的位置获取并校验策略，失败时退回缓存；没有配置位置时返回 `Ok(None)`
+pub fn refresh(paths: &AppPaths, state: &PolicyState) -> Result<Option<ActivePolicy>, String> {
+    let Some(location) = location(paths) else {
+        *state.0.lock().unwrap() = None;
+        return Ok(None);
+    };
+    let key = parse_public_key(POLICY_PUBLIC_KEY.ok_or("此版本未内置策略公钥，无法使用部署策略")?)?;
+    let active = match fetch(paths, &location).and_then(|content| verify(&content, &key)) {
+        Ok((signed, policy)) => {
+            let cached = CachedPolicy { signed, source: location, fetched_at: now() };
+            save_cache(paths, &cached)?;
+            ActivePolicy { policy, source: cached.source, fetched_at: cached.fetched_at, from_cache: false, last_error: None }
+        }
+        Err(e) => {
+            let Some((cached, policy)) = load_cache(paths, &key) else {
+                return Err(e);
+            };
+            ActivePolicy { policy, source: cached.source, fetched_at: cached.fetched_at, from_cache: true, last_error: Some(e) }
+        }
+    };
+    *state.0.lock().unwrap() = Some(active.clone());
+    Ok(Some(active))
+}
+
+/// 启动时在后台加载策略
+pub fn refresh_in_background(app: &tauri::AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        if let Err(e) = refresh(&app.state::<AppPaths>(), &app.state::<PolicyState>()) {
+            crate::emit_progress(&app, "policy", format!("加载部署策略失败: {}", e));
+        }
+    });
+}
+
+/// 重新获取部署策略
+#[tauri::command]
+pub fn refresh_policy(paths: tauri::State<'_, AppPaths>, state: tauri::State<'_, PolicyState>) -> Result<Option<ActivePolicy>, String> {
+    refresh(&paths, &state)
+}
+
+/// 当前生效的部署策略及其来源
+#[tauri::command]
+pub fn get_active_policy(state: tauri::State<'_, PolicyState>) -> Option<ActivePolicy> {
+    state.0.lock().unwrap().clone()
+}
+
+/// 设置策略文件位置（本地路径或 HTTPS 地址，空值表示不使用），并立即获取
+#[tauri::command]
+pub fn set_policy_location(
+    app: tauri::AppHandle,
+    paths: tauri::State<'_, AppPaths>,
+    state: tauri::State<'_, PolicyState>,
+    location: Option<String>,
+) -> Result<Option<ActivePolicy>, String> {
+    let params = serde_json::json!({ "location": location });
+    let result = fs::create_dir_all(&paths.config_dir)
+        .map_err(|e| e.to_string())
+        .and_then(|_| serde_json::to_string_pretty(&PolicySettings { location }).map_err(|e| e.to_string()))
+        .and_then(|content| fs::write(paths.config_dir.join(SETTINGS_FILE), content).map_err(|e| e.to_string()));
+    crate::audit::record(&app, "set_policy_location", params, &result);
+    result?;
+    refresh(&paths, &state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    fn signing_key() -> SigningKey {
+        SigningKey::from_bytes(&[7u8; 32])
+    }
+
+    fn signed_file(policy: &str, key: &SigningKey) -> String {
+        let signature = base64::engine::general_purpose::STANDARD.encode(key.sign(policy.as_bytes()).to_bytes());
+        serde_json::to_string(&SignedPolicy { policy: policy.to_string(), signature }).unwrap()
+    }
+
+    fn document() -> PolicyDocument {
+        PolicyDocument {
+            version: "2024.1".to_string(),
+            recommended_prefixes: vec!["com.corp.".to_string(), "com.google".to_string()],
+            banned_prefixes: vec!["com.tencent".to_string()],
+            signing_profiles: Vec::new(),
+        }
+    }
+
+    fn config(keystore_path: &str) -> ProcessConfig {
+        serde_json::from_value(serde_json::json!({
+            "apk_path": "app.apk",
+            "new_prefix": "com.corp",
+            "install_after": false,
+            "java_path": "",
+            "apktool_path": "",
+            "zipalign_path": "",
+            "apksigner_path": "",
+            "keystore_path": keystore_path,
+        }))
+        .unwrap()
+    }
+
+    fn plan(new_package: &str) -> PackagePlan {
+        PackagePlan { original_package: None, new_package: new_package.to_string() }
+    }
+
+    fn scanned(prefix: &str, count: i32) -> TrustedPrefix {
+        TrustedPrefix { prefix: prefix.to_string(), count, source: "device".to_string(), ..Default::default() }
+    }
+
+    #[test]
+    fn public_key_is_parsed_from_base64() {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(signing_key().verifying_key().to_bytes());
+        assert_eq!(parse_public_key(&format!(" {}\n", encoded)).unwrap(), signing_key().verifying_key());
+        assert!(parse_public_key("AAAA").unwrap_err().contains("32"));
+        assert!(parse_public_key("not base64!").is_err());
+    }
+
+    #[test]
+    fn correctly_signed_policy_is_accepted() {
+        let body = serde_json::to_string(&document()).unwrap();
+        let (signed, policy) = verify(&signed_file(&body, &signing_key()), &signing_key().verifying_key()).unwrap();
+        assert_eq!(signed.policy, body);
+        assert_eq!(policy, document());
+    }
+
+    #[test]
+    fn tampered_policy_body_is_rejected() {
+        let body = serde_json::to_string(&document()).unwrap();
+        let mut signed: SignedPolicy = serde_json::from_str(&signed_file(&body, &signing_key())).unwrap();
+        signed.policy = signed.policy.replace("com.tencent", "com.other");
+        let err = verify(&serde_json::to_string(&signed).unwrap(), &signing_key().verifying_key()).unwrap_err();
+        assert!(err.contains("篡改"));
+    }
+
+    #[test]
+    fn policy_signed_by_another_key_is_rejected() {
+        let body = serde_json::to_string(&document()).unwrap();
+        let other = SigningKey::from_bytes(&[9u8; 32]);
+        assert!(verify(&signed_file(&body, &other), &signing_key().verifying_key()).unwrap_err().contains("篡改"));
+    }
+
+    #[test]
+    fn malformed_signature_is_rejected() {
+        let body = serde_json::to_string(&document()).unwrap();
+        let key = signing_key().verifying_key();
+        let bad_base64 = serde_json::json!({ "policy": body, "signature": "%%%" }).to_string();
+        assert!(verify(&bad_base64, &key).unwrap_err().contains("签名格式错误"));
+        let short = serde_json::json!({ "policy": body, "signature": "AAAA" }).to_string();
+        assert!(verify(&short, &key).unwrap_err().contains("签名格式错误"));
+        assert!(verify("{}", &key).unwrap_err().contains("文件格式错误"));
+    }
+
+    #[test]
+    fn signed_but_invalid_policy_content_is_rejected() {
+        assert!(verify(&signed_file("not json", &signing_key()), &signing_key().verifying_key()).unwrap_err().contains("内容格式错误"));
+    }
+
+    #[test]
+    fn banned_prefix_blocks_matching_packages_only() {
+        let policy = document();
+        let message = violation(&policy, &config("release.jks"), &plan("com.tencent.mm.x")).unwrap();
+        assert!(message.contains("com.tencent") && message.contains("2024.1"));
+        assert!(violation(&policy, &config("release.jks"), &plan("com.tencent")).is_some());
+        // 只是字符串前缀相同的包名不受影响
+        assert!(violation(&policy, &config("release.jks"), &plan("com.tencentx.app")).is_none());
+    }
+
+    #[test]
+    fn keystore_outside_signing_profiles_is_a_violation() {
+        let policy = PolicyDocument { signing_profiles: vec!["corp.jks".to_string()], ..document() };
+        assert!(violation(&policy, &config("/keys/corp.jks"), &plan("com.corp.app")).is_none());
+        assert!(violation(&policy, &config("/keys/debug.jks"), &plan("com.corp.app")).unwrap().contains("corp.jks"));
+    }
+
+    #[test]
+    fn policy_prefixes_merge_with_policy_source() {
+        let merged = merge_prefixes(&document(), vec![scanned("com.google", 12), scanned("com.tencent.mm", 3), scanned("com.android", 40)]);
+        let summary: Vec<(&str, i32, &str)> = merged.iter().map(|p| (p.prefix.as_str(), p.count, p.source.as_str())).collect();
+        assert_eq!(summary, [("com.corp", 0, "policy"), ("com.google", 12, "policy"), ("com.android", 40, "device")]);
+    }
+}
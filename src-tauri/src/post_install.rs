@@ -0,0 +1,158 @@
+use serde::{Deserialize, Serialize};
+
+/// 允许在设备上执行的 shell 命令（首个单词）
+pub const SHELL_WHITELIST: &[&str] = &["am", "pm", "settings", "input", "wm", "svc", "cmd", "monkey", "mkdir", "ls"];
+
+/// 安装成功后执行的单个动作，可随预设一起保存
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+#[serde(tag = "type")]
+pub enum PostInstallAction {
+    /// 推送本地文件到设备，remote_template 中的 `{package}` 会被替换为新包名
+    PushFile { local: String, remote_template: String },
+    /// 执行白名单内的 shell 命令
+    Shell { command: String },
+    /// 通过 `settings put` 修改系统设置
+    PutSetting { namespace: String, key: String, value: String },
+}
+
+/// 单个动作的执行结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct ActionResult {
+    pub action: String,
+    pub success: bool,
+    pub message: String,
+}
+
+/// 展开远程路径模板
+pub fn expand_template(template: &str, package: &str) -> String {
+    template.replace("{package}", package)
+}
+
+/// 检查 shell 命令是否在白名单内且不含命令拼接字符
+pub fn check_shell_command(command: &str) -> Result<(), String> {
+    if command.chars().any(|c| matches!(c, ';' | '|' | '&' | '`' | '$' | '>' | '<' | '\n')) {
+        return Err("命令中包含不允许的特殊字符".to_string());
+    }
+    let program = command.split_whitespace().next().ok_or_else(|| "命令为空".to_string())?;
+    if !SHELL_WHITELIST.contains(&program) {
+        return Err(format!("命令 {} 不在白名单内", program));
+    }
+    Ok(())
+}
+
+/// 生成 `settings put` 的参数：键名只允许字母、数字、`_` 与 `.`，值按设备 shell 规则加引号。
+/// adb 会把参数拼成一条命令交给设备 shell，不加检查时值中的 `;` 等字符可绕过命令白名单
+pub fn setting_args(namespace: &str, key: &str, value: &str) -> Result<Vec<String>, String> {
+    if !matches!(namespace, "system" | "secure" | "global") {
+        return Err(format!("未知的设置命名空间: {}", namespace));
+    }
+    if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.') {
+        return Err(format!("无效的设置键名: {}", key));
+    }
+    let args = ["shell", "settings", "put", namespace, key];
+    Ok(args.iter().map(|s| s.to_string()).chain([crate::device_files::shell_quote(value)]).collect())
+}
+
+fn describe(action: &PostInstallAction) -> String {
+    match action {
+        PostInstallAction::PushFile { local, remote_template } => format!("push {} -> {}", local, remote_template),
+        PostInstallAction::Shell { command } => format!("shell {}", command),
+        PostInstallAction::PutSetting { namespace, key, value } => format!("settings put {} {} {}", namespace, key, value),
+    }
+}
+
+fn run_adb(device_id: &str, args: &[&str]) -> Result<String, String> {
+    let output = crate::adb::command()
+        .args(["-s", device_id])
+        .args(args)
+        .output()
+        .map_err(|e| e.to_string())?;
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    if output.status.success() {
+        Ok(stdout.trim().to_string())
+    } else {
+        Err(format!("{} {}", String::from_utf8_lossy(&output.stderr).trim(), stdout.trim()))
+    }
+}
+
+fn run_action(device_id: &str, package: &str, action: &PostInstallAction) -> Result<String, String> {
+    match action {
+        PostInstallAction::PushFile { local, remote_template } => {
+            let remote = expand_template(remote_template, package);
+            run_adb(device_id, &["push", local, &remote])
+        }
+        PostInstallAction::Shell { command } => {
+            check_shell_command(command)?;
+            let mut args = vec!["shell"];
+            args.extend(command.split_whitespace());
+            run_adb(device_id, &args)
+        }
+        PostInstallAction::PutSetting { namespace, key, value } => {
+            let args = setting_args(namespace, key, value)?;
+            run_adb(device_id, &args.iter().map(String::as_str).collect::<Vec<_>>())
+        }
+    }
+}
+
+/// 依次执行所有动作，单个失败不影响其余动作
+pub fn run_actions(device_id: &str, package: &str, actions: &[PostInstallAction]) -> Vec<ActionResult> {
+    actions
+        .iter()
+        .map(|action| {
+            let (success, message) = match run_action(device_id, package, action) {
+                Ok(out) => (true, out),
+                Err(e) => (false, e),
+            };
+            ActionResult { action: describe(action), success, message }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn template_expands_every_package_placeholder() {
+        assert_eq!(expand_template("/sdcard/Android/data/{package}/files/{package}.cfg", "com.a.b"), "/sdcard/Android/data/com.a.b/files/com.a.b.cfg");
+        assert_eq!(expand_template("/sdcard/fixed.txt", "com.a.b"), "/sdcard/fixed.txt");
+    }
+
+    #[test]
+    fn whitelisted_commands_pass() {
+        assert!(check_shell_command("am start -n com.a.b/.Main").is_ok());
+        assert!(check_shell_command("  settings put global adb_enabled 1").is_ok());
+    }
+
+    #[test]
+    fn commands_outside_the_whitelist_are_rejected() {
+        assert!(check_shell_command("rm -rf /sdcard").unwrap_err().contains("rm"));
+        assert_eq!(check_shell_command("   ").unwrap_err(), "命令为空");
+    }
+
+    #[test]
+    fn command_chaining_is_rejected() {
+        for command in ["am start x; rm -rf /sdcard", "ls | sh", "ls && reboot", "ls `id`", "ls $(id)", "ls > /sdcard/x", "ls\nreboot"] {
+            assert!(check_shell_command(command).is_err(), "{}", command);
+        }
+    }
+
+    #[test]
+    fn setting_value_is_quoted_for_the_device_shell() {
+        let args = setting_args("global", "adb_enabled", "1;rm -rf /sdcard/x").unwrap();
+        assert_eq!(args, ["shell", "settings", "put", "global", "adb_enabled", "'1;rm -rf /sdcard/x'"]);
+        let args = setting_args("system", "font_scale", "it's 1.2").unwrap();
+        assert_eq!(args.last().unwrap(), r"'it'\''s 1.2'");
+    }
+
+    #[test]
+    fn setting_key_injection_is_rejected() {
+        assert!(setting_args("global", "adb_enabled;reboot", "1").is_err());
+        assert!(setting_args("global", "a b", "1").is_err());
+        assert!(setting_args("global", "", "1").is_err());
+        assert!(setting_args("vendor", "adb_enabled", "1").is_err());
+        assert!(setting_args("secure", "accessibility.enabled_1", "1").is_ok());
+    }
+}
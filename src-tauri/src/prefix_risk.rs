@@ -0,0 +1,260 @@
+//! 可信前缀的深度检查。只按数量推荐的前缀，可能属于同一厂商用同一证书签名、互相以 signature 级权限通信的应用家族，
+//! 把签名不同的应用混进去，部分 ROM 上会出现权限授予异常。深度模式对排名靠前的前缀各抽一个包，
+//! 用 `dumpsys package` 检查它是否声明了 signature 级权限、是否使用 sharedUserId，有则标记为有风险。
+
+use std::collections::HashMap;
+use std::thread;
+
+use crate::device_cache::{self, DeviceCache};
+use crate::{adb, pm, TrustedPrefix};
+
+/// 深度检查的前缀数量
+const DEEP_SCAN_CANDIDATES: usize = 8;
+/// 同时执行的 dumpsys 查询数
+const MAX_CONCURRENT: usize = 4;
+
+/// 一个包的风险特征
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PackageRisk {
+    /// 该包声明的 signature 级权限
+    pub signature_permissions: Vec<String>,
+    pub shared_user_id: Option<String>,
+}
+
+impl PackageRisk {
+    /// 有风险时的原因
+    pub fn reason(&self) -> Option<String> {
+        let mut reasons = Vec::new();
+        if let Some(shared) = &self.shared_user_id {
+            reasons.push(format!("家族共用 sharedUserId {}", shared));
+        }
+        if !self.signature_permissions.is_empty() {
+            let shown: Vec<&str> = self.signature_permissions.iter().take(3).map(String::as_str).collect();
+            reasons.push(format!("声明了 signature 级权限 {}", shown.join("、")));
+        }
+        (!reasons.is_empty()).then(|| reasons.join("；"))
+    }
+}
+
+fn is_signature_level(protection: &str) -> bool {
+    protection.split('|').any(|level| level == "signature" || level == "signatureOrSystem")
+}
+
+/// 解析 `dumpsys package <包名>`：`Permissions:` 段中 sourcePackage 为该包、`prot=` 含 signature 的权限
+/// （Android 8 起包段落中还有 `declared permissions:` 列表，格式为 `名称: prot=signature, INSTALLED`），
+/// 以及包段落中的 `sharedUser=SharedUserSetting{... 名称/uid}`。兼容 Android 7 到 14 的格式
+pub fn parse_package_risk(output: &str, package: &str) -> PackageRisk {
+    let mut risk = PackageRisk::default();
+    let mut permission: Option<String> = None;
+    let mut from_package = false;
+    for line in output.lines().map(str::trim) {
+        if let Some((name, rest)) = line.split_once(": prot=") {
+            let protection = rest.split(',').next().unwrap_or_default().trim();
+            if is_signature_level(protection) && !risk.signature_permissions.iter().any(|p| p == name) {
+                risk.signature_permissions.push(name.to_string());
+            }
+            continue;
+        }
+        if let Some(rest) = line.strip_prefix("Permission [") {
+            permission = rest.split_once(']').map(|(name, _)| name.to_string());
+            from_package = false;
+            continue;
+        }
+        if let Some(source) = line.strip_prefix("sourcePackage=") {
+            from_package = source == package;
+            continue;
+        }
+        if let Some(perm) = permission.as_ref().filter(|_| from_package) {
+            let protection = line.split_whitespace().find_map(|token| token.strip_prefix("prot="));
+            if protection.is_some_and(is_signature_level) {
+                if !risk.signature_permissions.contains(perm) {
+                    risk.signature_permissions.push(perm.clone());
+                }
+                permission = None;
+            }
+        }
+        if risk.shared_user_id.is_none() {
+            if let Some(setting) = line.strip_prefix("sharedUser=SharedUserSetting{") {
+                // `{1a2b3c com.example.shared/10123}`
+                risk.shared_user_id = setting
+                    .trim_end_matches('}')
+                    .split_whitespace()
+                    .nth(1)
+                    .and_then(|name| name.split('/').next())
+                    .map(str::to_string);
+            }
+        }
+    }
+    risk
+}
+
+/// 前缀 → 抽查的包（按字母序第一个）
+fn sample_packages(packages: &[String], prefixes: &[&str]) -> HashMap<String, String> {
+    let mut samples = HashMap::new();
+    for prefix in prefixes {
+        let family = format!("{}.", prefix);
+        if let Some(sample) = packages.iter().filter(|p| p.starts_with(&family)).min() {
+            samples.insert(prefix.to_string(), sample.clone());
+        }
+    }
+    samples
+}
+
+/// 对前 `DEEP_SCAN_CANDIDATES` 个前缀做深度检查并标记风险；结果按设备缓存在会话内
+pub fn mark_risky(cache: &DeviceCache, device_id: &str, prefixes: &mut [TrustedPrefix]) -> Result<(), String> {
+    let mut known: HashMap<String, Option<String>> = cache.get(device_id, device_cache::DATASET_PREFIX_RISKS).unwrap_or_default();
+    let candidates: Vec<&str> = prefixes.iter().take(DEEP_SCAN_CANDIDATES).map(|p| p.prefix.as_str()).filter(|p| !known.contains_key(*p)).collect();
+    if !candidates.is_empty() {
+        let packages: Vec<String> = pm::parse_package_list(&adb::shell(device_id, &["pm", "list", "packages"])?).into_iter().map(|p| p.package).collect();
+        let samples = sample_packages(&packages, &candidates);
+        // 设备上没有该前缀的包（如推荐或策略中的前缀），无从检查
+        for prefix in candidates.iter().filter(|p| !samples.contains_key(**p)) {
+            known.insert(prefix.to_string(), None);
+        }
+        let samples: Vec<(String, String)> = samples.into_iter().collect();
+        for chunk in samples.chunks(MAX_CONCURRENT) {
+            let results: Vec<(String, Option<Option<String>>)> = thread::scope(|scope| {
+                let handles: Vec<_> = chunk
+                    .iter()
+                    .map(|(prefix, package)| {
+                        scope.spawn(move || {
+                            let risk = adb::shell(device_id, &["dumpsys", "package", package]).ok().map(|out| parse_package_risk(&out, package).reason());
+                            (prefix.clone(), risk)
+                        })
+                    })
+                    .collect();
+                handles.into_iter().filter_map(|h| h.join().ok()).collect()
+            });
+            // 查询失败的前缀不缓存，下次重试
+            for (prefix, risk) in results {
+                if let Some(reason) = risk {
+                    known.insert(prefix, reason);
+                }
+            }
+        }
+        cache.put(device_id, device_cache::DATASET_PREFIX_RISKS, &known);
+    }
+    for prefix in prefixes.iter_mut() {
+        if let Some(Some(reason)) = known.get(&prefix.prefix) {
+            prefix.risky = true;
+            prefix.risk_reason = Some(reason.clone());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Android 7：只有 `Permissions:` 段，包段落使用 sharedUserId
+    const DUMPSYS_N: &str = "\
+Permissions:
+  Permission [com.vendor.scanner.permission.SCAN] (5d1a2b3):
+    sourcePackage=com.vendor.scanner
+    uid=10087 gids=null type=0 prot=signature
+    perm=Permission{9e8d7c6 com.vendor.scanner.permission.SCAN}
+    packageSetting=PackageSetting{4f3e2d1 com.vendor.scanner/10087}
+  Permission [com.vendor.scanner.permission.READ] (a1b2c3d):
+    sourcePackage=com.vendor.scanner
+    uid=10087 gids=null type=0 prot=normal
+  Permission [com.vendor.shared.permission.BIND] (c0ffee1):
+    sourcePackage=com.vendor.service
+    uid=10087 gids=null type=0 prot=signature
+
+Packages:
+  Package [com.vendor.scanner] (4f3e2d1):
+    userId=10087
+    sharedUser=SharedUserSetting{8c9d0e1 com.vendor.shared/10087}
+    pkg=Package{2b3c4d5 com.vendor.scanner}
+    codePath=/system/app/VendorScanner
+";
+
+    /// Android 14：包段落中还有 `declared permissions:` 列表
+    const DUMPSYS_U: &str = "\
+Permissions:
+  Permission [com.vendor.pos.permission.PAY] (e3f4a5b):
+    sourcePackage=com.vendor.pos
+    uid=10123 gids=[] type=0 prot=signature|privileged
+    perm=PermissionInfo{1f2e3d4 com.vendor.pos.permission.PAY}
+
+Packages:
+  Package [com.vendor.pos] (7a8b9c0):
+    appId=10123
+    pkg=Package{6b5a4f3 com.vendor.pos}
+    codePath=/data/app/~~Qk3x==/com.vendor.pos-1
+    declared permissions:
+      com.vendor.pos.permission.PAY: prot=signature|privileged, INSTALLED
+      com.vendor.pos.permission.SYNC: prot=signatureOrSystem, INSTALLED
+      com.vendor.pos.permission.VIEW: prot=normal, INSTALLED
+    requested permissions:
+      android.permission.INTERNET
+    install permissions:
+      android.permission.INTERNET: granted=true
+";
+
+    /// 普通第三方应用
+    const DUMPSYS_PLAIN: &str = "\
+Packages:
+  Package [com.example.notes] (1a2b3c4):
+    appId=10201
+    declared permissions:
+      com.example.notes.permission.C2D_MESSAGE: prot=normal, INSTALLED
+    install permissions:
+      android.permission.INTERNET: granted=true
+";
+
+    #[test]
+    fn android_7_dump_reports_signature_permissions_and_shared_user() {
+        let risk = parse_package_risk(DUMPSYS_N, "com.vendor.scanner");
+        assert_eq!(risk.signature_permissions, ["com.vendor.scanner.permission.SCAN"]);
+        assert_eq!(risk.shared_user_id.as_deref(), Some("com.vendor.shared"));
+        assert_eq!(
+            risk.reason().as_deref(),
+            Some("家族共用 sharedUserId com.vendor.shared；声明了 signature 级权限 com.vendor.scanner.permission.SCAN")
+        );
+    }
+
+    #[test]
+    fn android_14_dump_merges_both_permission_lists() {
+        let risk = parse_package_risk(DUMPSYS_U, "com.vendor.pos");
+        assert_eq!(risk.signature_permissions, ["com.vendor.pos.permission.PAY", "com.vendor.pos.permission.SYNC"]);
+        assert_eq!(risk.shared_user_id, None);
+    }
+
+    #[test]
+    fn plain_apps_have_no_risk() {
+        let risk = parse_package_risk(DUMPSYS_PLAIN, "com.example.notes");
+        assert_eq!(risk, PackageRisk::default());
+        assert_eq!(risk.reason(), None);
+    }
+
+    #[test]
+    fn reason_shows_at_most_three_permissions() {
+        let risk = PackageRisk { signature_permissions: (1..=5).map(|i| format!("p{}", i)).collect(), shared_user_id: None };
+        assert_eq!(risk.reason().as_deref(), Some("声明了 signature 级权限 p1、p2、p3"));
+    }
+
+    #[test]
+    fn samples_the_first_package_of_each_family() {
+        let packages: Vec<String> = ["com.vendor.pos", "com.vendor.admin", "com.vendorx.tool", "com.acme.a"].iter().map(|p| p.to_string()).collect();
+        let samples = sample_packages(&packages, &["com.vendor", "com.other"]);
+        assert_eq!(samples.len(), 1);
+        assert_eq!(samples["com.vendor"], "com.vendor.admin");
+    }
+
+    #[test]
+    fn cached_results_mark_prefixes_without_querying_the_device() {
+        let cache = DeviceCache::default();
+        let known: HashMap<String, Option<String>> =
+            [("com.vendor".to_string(), Some("家族共用 sharedUserId com.vendor.shared".to_string())), ("com.acme".to_string(), None)].into();
+        cache.put("dev-1", device_cache::DATASET_PREFIX_RISKS, &known);
+        let mut prefixes: Vec<TrustedPrefix> =
+            ["com.vendor", "com.acme"].iter().map(|p| TrustedPrefix { prefix: p.to_string(), count: 3, ..Default::default() }).collect();
+
+        mark_risky(&cache, "dev-1", &mut prefixes).unwrap();
+        assert!(prefixes[0].risky);
+        assert_eq!(prefixes[0].risk_reason.as_deref(), Some("家族共用 sharedUserId com.vendor.shared"));
+        assert!(!prefixes[1].risky && prefixes[1].risk_reason.is_none());
+    }
+}
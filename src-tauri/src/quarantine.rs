@@ -0,0 +1,129 @@
+//! macOS 扩展属性处理。浏览器下载的工具带有 `com.apple.quarantine`，Gatekeeper 会拒绝执行
+//! （“无法打开”），校验工具路径时提前说明；交付给其他平台的文件去掉扩展属性，
+//! 避免打包或拷贝到 Windows 时出现 `._*`（AppleDouble）文件。其他平台上这些操作都不做任何事。
+
+use std::path::Path;
+
+use crate::input_path::{self, Expect};
+use crate::signing::PipelineError;
+
+pub const QUARANTINE_ATTR: &str = "com.apple.quarantine";
+
+/// 文件是否带有隔离属性
+#[cfg(target_os = "macos")]
+pub fn is_quarantined(path: &Path) -> bool {
+    xattr::get(path, QUARANTINE_ATTR).ok().flatten().is_some()
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn is_quarantined(_path: &Path) -> bool {
+    false
+}
+
+/// 去掉隔离属性
+#[cfg(target_os = "macos")]
+pub fn clear_quarantine_attr(path: &Path) -> std::io::Result<()> {
+    match xattr::remove(path, QUARANTINE_ATTR) {
+        Err(e) if is_quarantined(path) => Err(e),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn clear_quarantine_attr(_path: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// 去掉文件的全部扩展属性；失败不影响输出
+#[cfg(target_os = "macos")]
+pub fn strip_xattrs(path: &Path) {
+    if let Ok(names) = xattr::list(path) {
+        for name in names {
+            let _ = xattr::remove(path, &name);
+        }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn strip_xattrs(_path: &Path) {}
+
+/// AppleDouble 文件（`._name`）、`__MACOSX/` 目录与 `.DS_Store`，不应出现在交付的归档中
+pub fn is_apple_double(entry_name: &str) -> bool {
+    let file_name = entry_name.trim_end_matches('/').rsplit('/').next().unwrap_or(entry_name);
+    entry_name.starts_with("__MACOSX/") || file_name.starts_with("._") || file_name == ".DS_Store"
+}
+
+/// 校验用户指定的工具：带隔离属性时返回说明如何解除的错误
+pub fn check_tool(name: &str, path: &str) -> Result<(), PipelineError> {
+    if !is_quarantined(Path::new(path)) {
+        return Ok(());
+    }
+    Err(PipelineError::InvalidInput(format!(
+        "{} 带有 macOS 隔离属性（从浏览器下载），系统会拒绝运行: {}\n可在设置中解除隔离，或在终端执行: xattr -d {} \"{}\"",
+        name, path, QUARANTINE_ATTR, path
+    )))
+}
+
+/// 经用户同意后去掉工具文件的隔离属性
+#[tauri::command]
+pub fn clear_quarantine(app: tauri::AppHandle, path: String) -> Result<(), String> {
+    let path = input_path::arg(&path, Expect::File).map_err(|e| e.to_string())?;
+    let result = clear_quarantine_attr(Path::new(&path)).map_err(|e| format!("解除隔离失败: {}", e));
+    crate::audit::record(&app, "clear_quarantine", serde_json::json!({ "path": path }), &result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_apple_double_entries() {
+        for name in ["__MACOSX/lib/x.so", "._AndroidManifest.xml", "res/._icon.png", ".DS_Store", "assets/.DS_Store", "res/._drawable/"] {
+            assert!(is_apple_double(name), "{}", name);
+        }
+        for name in ["AndroidManifest.xml", "assets/_config.json", "res/.hidden", "lib/__MACOSX.so", "assets/x.DS_Store"] {
+            assert!(!is_apple_double(name), "{}", name);
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn quarantined_tools_are_reported_until_cleared() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = dir.path().join("apktool.jar");
+        std::fs::write(&tool, b"jar").unwrap();
+        let tool_path = tool.to_string_lossy().to_string();
+        assert!(!is_quarantined(&tool));
+        assert!(check_tool("apktool", &tool_path).is_ok());
+
+        xattr::set(&tool, QUARANTINE_ATTR, b"0083;00000000;Safari;").unwrap();
+        xattr::set(&tool, "com.example.note", b"x").unwrap();
+        assert!(is_quarantined(&tool));
+        match check_tool("apktool", &tool_path) {
+            Err(PipelineError::InvalidInput(message)) => assert!(message.contains(&format!("xattr -d {}", QUARANTINE_ATTR))),
+            other => panic!("unexpected: {:?}", other),
+        }
+
+        clear_quarantine_attr(&tool).unwrap();
+        assert!(!is_quarantined(&tool));
+        assert!(clear_quarantine_attr(&tool).is_ok());
+        let has_note = || xattr::list(&tool).unwrap().any(|name| name == "com.example.note");
+        assert!(has_note());
+        strip_xattrs(&tool);
+        assert!(!has_note());
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    fn attributes_are_ignored_off_macos() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = dir.path().join("apktool.jar");
+        std::fs::write(&tool, b"jar").unwrap();
+        assert!(!is_quarantined(&tool));
+        assert!(check_tool("apktool", &tool.to_string_lossy()).is_ok());
+        assert!(clear_quarantine_attr(&tool).is_ok());
+        strip_xattrs(&tool);
+        assert!(tool.exists());
+    }
+}
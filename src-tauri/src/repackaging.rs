@@ -0,0 +1,196 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use crate::axml;
+use crate::input_path::{self, Expect};
+use crate::paths::AppPaths;
+use crate::signing::{self, PipelineError};
+
+/// 本工具推荐的前缀，生成的包名形如 `<前缀>.<文件名派生后缀>`
+const GENERATED_PREFIXES: &[&str] = &["cn.chinapost", "com.nlscan"];
+
+/// 本工具写入 Manifest 的 `<meta-data>` 名称前缀
+pub const MARKER_META_DATA_PREFIX: &str = "apk_disguise.";
+
+/// 达到该置信度即认为已被处理过
+const REPACKAGED_THRESHOLD: f32 = 0.5;
+
+/// 是否已被本工具处理过的检测结果
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct RepackagingInfo {
+    pub was_repackaged: bool,
+    /// 0.0 ~ 1.0
+    pub confidence: f32,
+    pub indicators: Vec<String>,
+}
+
+/// 与流水线生成后缀的规则一致：小写字母数字，最长 12 位
+fn is_generated_package(package: &str) -> bool {
+    GENERATED_PREFIXES.iter().any(|prefix| {
+        package
+            .strip_prefix(prefix)
+            .and_then(|rest| rest.strip_prefix('.'))
+            .is_some_and(|suffix| {
+                !suffix.is_empty() && suffix.len() <= 12 && suffix.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+            })
+    })
+}
+
+/// 用 keytool 读取 APK 签名证书的 SHA-256 指纹
+pub fn apk_cert_fingerprint(keytool: &Path, apk_path: &Path) -> Option<String> {
+    let output = Command::new(keytool)
+        .args(["-printcert", "-jarfile"])
+        .arg(apk_path)
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("SHA256:").map(|f| f.trim().to_string()))
+}
+
+fn add(info: &mut RepackagingInfo, weight: f32, indicator: String) {
+    info.confidence += weight;
+    info.indicators.push(indicator);
+}
+
+/// 检查 APK 是否已经被本工具处理过，`keytool` 用于比对签名证书与内置密钥库
+pub fn detect(apk_path: &Path, tools_dir: &Path, keytool: &Path) -> Result<RepackagingInfo, PipelineError> {
+    let mut info = RepackagingInfo::default();
+
+    let archive = zip::ZipArchive::new(fs::File::open(apk_path)?)?;
+    let artifacts: Vec<String> = archive
+        .file_names()
+        .filter(|name| *name == "apktool.yml" || name.ends_with("/apktool.yml") || name.starts_with("original/META-INF/"))
+        .map(str::to_string)
+        .collect();
+    if !artifacts.is_empty() {
+        add(&mut info, 0.3, format!("残留反编译产物: {}", artifacts.join(", ")));
+    }
+
+    let elements = axml::read_manifest_from_apk(apk_path).map_err(PipelineError::InvalidInput)?;
+    let package = elements
+        .iter()
+        .find(|e| e.name == "manifest")
+        .and_then(|e| e.attr("package"))
+        .map(axml::AxmlValue::as_string)
+        .unwrap_or_default();
+    if is_generated_package(&package) {
+        add(&mut info, 0.2, format!("包名 {} 符合本工具的生成规则", package));
+    }
+    let markers: Vec<String> = elements
+        .iter()
+        .filter(|e| e.name == "meta-data")
+        .filter_map(|e| e.attr("name").map(axml::AxmlValue::as_string))
+        .filter(|name| name.starts_with(MARKER_META_DATA_PREFIX))
+        .collect();
+    if !markers.is_empty() {
+        add(&mut info, 0.6, format!("包含本工具写入的 meta-data: {}", markers.join(", ")));
+    }
+
+    let keystore = tools_dir.join("release-key.jks");
+    if keystore.exists() {
+        let defaults = signing::KeystoreConfig::with_defaults(&keystore.to_string_lossy());
+        let bundled = signing::keystore_fingerprint(keytool, &defaults.path, &defaults.store_password, None);
+        if let (Some(bundled), Some(actual)) = (bundled, apk_cert_fingerprint(keytool, apk_path)) {
+            if bundled.eq_ignore_ascii_case(&actual) {
+                add(&mut info, 0.6, format!("签名证书与内置 release-key.jks 一致 ({})", actual));
+            }
+        }
+    }
+
+    let is_output_name = apk_path.file_stem().and_then(|s| s.to_str()).is_some_and(|s| s.ends_with("_fixed"));
+    if is_output_name {
+        add(&mut info, 0.1, "文件名带有本工具的输出后缀 _fixed".to_string());
+    }
+
+    info.confidence = info.confidence.min(1.0);
+    info.was_repackaged = info.confidence >= REPACKAGED_THRESHOLD;
+    Ok(info)
+}
+
+/// 检测 APK 是否已被本工具处理过。重复处理经常导致 APK 损坏
+#[tauri::command]
+pub fn detect_previous_repackaging(
+    app_paths: tauri::State<'_, AppPaths>,
+    apk_path: String,
+) -> Result<RepackagingInfo, PipelineError> {
+    let apk_path = input_path::arg(&apk_path, Expect::File)?;
+    detect(Path::new(&apk_path), &app_paths.tools_dir, &signing::keytool_path(None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 包名 com.nlscan.scanner、带 `apk_disguise.original_package` meta-data、残留 assets/apktool.yml 与 original/META-INF，
+    /// 并用同目录下的 release-key.jks（默认口令与别名）签名
+    const FIXTURE_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/repackaging");
+
+    fn fixture() -> std::path::PathBuf {
+        Path::new(FIXTURE_DIR).join("already-repackaged.apk")
+    }
+
+    #[test]
+    fn generated_package_names_follow_the_pipeline_rules() {
+        assert!(is_generated_package("com.nlscan.scanner"));
+        assert!(is_generated_package("cn.chinapost.app2024"));
+        assert!(!is_generated_package("com.nlscan"));
+        assert!(!is_generated_package("com.nlscan.Scanner"));
+        assert!(!is_generated_package("com.nlscan.scanner.debug"));
+        assert!(!is_generated_package("com.nlscanner.app"));
+        assert!(!is_generated_package("com.nlscan.abcdefghijklm"));
+    }
+
+    #[test]
+    fn detects_the_already_repackaged_fixture() {
+        let tools_dir = tempfile::tempdir().unwrap();
+        let info = detect(&fixture(), tools_dir.path(), Path::new("keytool")).unwrap();
+        assert!(info.was_repackaged);
+        assert!((info.confidence - 1.0).abs() < f32::EPSILON);
+        assert_eq!(info.indicators.len(), 3, "{:?}", info.indicators);
+        assert!(info.indicators[0].contains("assets/apktool.yml") && info.indicators[0].contains("original/META-INF/MANIFEST.MF"));
+        assert!(info.indicators[1].contains("com.nlscan.scanner"));
+        assert!(info.indicators[2].contains("apk_disguise.original_package"));
+    }
+
+    #[test]
+    fn output_file_name_is_an_indicator() {
+        let dir = tempfile::tempdir().unwrap();
+        let renamed = dir.path().join("scanner_fixed.apk");
+        fs::copy(fixture(), &renamed).unwrap();
+        let info = detect(&renamed, dir.path(), Path::new("keytool")).unwrap();
+        assert!(info.indicators.last().unwrap().contains("_fixed"));
+        assert!(info.confidence <= 1.0);
+    }
+
+    #[test]
+    fn signature_matches_the_bundled_keystore() {
+        let keytool = signing::keytool_path(None);
+        let keystore = Path::new(FIXTURE_DIR).join("release-key.jks");
+        if signing::keystore_fingerprint(&keytool, &keystore.to_string_lossy(), "123456", None).is_none() {
+            eprintln!("keytool 不可用，跳过证书比对");
+            return;
+        }
+        let info = detect(&fixture(), Path::new(FIXTURE_DIR), &keytool).unwrap();
+        let cert = info.indicators.iter().find(|i| i.contains("release-key.jks")).expect("应检测到内置密钥库签名");
+        assert!(cert.contains("5D:05:39:E7"), "{}", cert);
+
+        // 其他密钥库签名的 APK 不算
+        let other_tools = tempfile::tempdir().unwrap();
+        fs::write(other_tools.path().join("release-key.jks"), b"not a keystore").unwrap();
+        let info = detect(&fixture(), other_tools.path(), &keytool).unwrap();
+        assert!(!info.indicators.iter().any(|i| i.contains("release-key.jks")));
+    }
+
+    #[test]
+    fn untouched_apk_is_not_flagged() {
+        let selftest = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/selftest.apk");
+        let tools_dir = tempfile::tempdir().unwrap();
+        let info = detect(&selftest, tools_dir.path(), Path::new("keytool")).unwrap();
+        assert!(!info.was_repackaged, "{:?}", info.indicators);
+        assert!(info.confidence < REPACKAGED_THRESHOLD);
+    }
+}
@@ -0,0 +1,267 @@
+//! res/xml 与 res/layout 中对原包名的引用。改包名只修改 Manifest，快捷方式的 `targetPackage`、
+//! 自定义命名空间 `http://schemas.android.com/apk/res/<包名>` 等仍指向原包名，回编译能通过但运行时找不到目标。
+//! 完整解码资源后逐行扫描并写入结果警告；开启改写时，只替换值恰好为原包名的属性与命名空间，
+//! 类名（代码没有改名）与 authorities（Manifest 中保持原值）只报告不改写。res/values 从不扫描与修改。
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use crate::axml;
+use crate::input_path::{self, Expect};
+
+/// 扫描的资源目录（含限定符，如 `layout-land`）
+const SCANNED_DIRS: &[&str] = &["xml", "layout"];
+/// 结果警告中最多逐条列出的引用数
+const MAX_LISTED: usize = 50;
+
+/// 一处引用
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct ResReference {
+    /// 相对工作目录（或 APK 内）的路径，如 `res/xml/shortcuts.xml`
+    pub file: String,
+    /// 行号从 1 开始；直接读取 APK 中的二进制 XML 时为空
+    pub line: Option<u32>,
+    pub text: String,
+    /// 值恰好为原包名，开启改写时会替换
+    pub rewritable: bool,
+}
+
+impl ResReference {
+    pub fn describe(&self) -> String {
+        match self.line {
+            Some(line) => format!("{}:{}: {}", self.file, line, self.text),
+            None => format!("{}: {}", self.file, self.text),
+        }
+    }
+}
+
+fn is_scanned_dir(name: &str) -> bool {
+    SCANNED_DIRS.iter().any(|dir| name == *dir || name.strip_prefix(dir).is_some_and(|rest| rest.starts_with('-')))
+}
+
+/// 原包名作为完整标识出现（前后不是标识字符，后面可以跟 `.` 接类名）
+fn reference_re(package: &str) -> regex::Regex {
+    regex::Regex::new(&format!(r"(^|[^\w.]){}($|[^\w])", regex::escape(package))).unwrap()
+}
+
+/// 值恰好为原包名：`="包名"`、`>包名<` 或命名空间 URI 的末尾
+fn exact_re(package: &str) -> regex::Regex {
+    regex::Regex::new(&format!(r#"(="|>|/apk/res/){}("|<)"#, regex::escape(package))).unwrap()
+}
+
+fn scanned_files(work_dir: &Path) -> Vec<std::path::PathBuf> {
+    let Ok(entries) = fs::read_dir(work_dir.join("res")) else {
+        return Vec::new();
+    };
+    let mut files: Vec<_> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().is_dir() && is_scanned_dir(&e.file_name().to_string_lossy()))
+        .flat_map(|dir| fs::read_dir(dir.path()).into_iter().flatten().filter_map(|e| e.ok()))
+        .map(|e| e.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "xml"))
+        .collect();
+    files.sort();
+    files
+}
+
+/// 扫描反编译目录中的 res/xml* 与 res/layout*
+pub fn scan(work_dir: &Path, package: &str) -> Vec<ResReference> {
+    if package.is_empty() {
+        return Vec::new();
+    }
+    let (reference, exact) = (reference_re(package), exact_re(package));
+    let mut references = Vec::new();
+    for path in scanned_files(work_dir) {
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let file = path.strip_prefix(work_dir).unwrap_or(&path).to_string_lossy().replace('\\', "/");
+        for (index, line) in content.lines().enumerate().filter(|(_, line)| reference.is_match(line)) {
+            references.push(ResReference {
+                file: file.clone(),
+                line: Some(index as u32 + 1),
+                text: line.trim().to_string(),
+                rewritable: exact.is_match(line),
+            });
+        }
+    }
+    references
+}
+
+/// 把值恰好为原包名的引用替换为新包名，返回修改的文件数
+pub fn rewrite(work_dir: &Path, original: &str, new_package: &str) -> Result<usize, String> {
+    if original.is_empty() {
+        return Ok(0);
+    }
+    let exact = exact_re(original);
+    let replacement = format!("${{1}}{}${{2}}", new_package);
+    let mut changed = 0;
+    for path in scanned_files(work_dir) {
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let rewritten = exact.replace_all(&content, replacement.as_str());
+        if rewritten != content {
+            fs::write(&path, rewritten.as_bytes()).map_err(|e| format!("写入 {} 失败: {}", path.display(), e))?;
+            changed += 1;
+        }
+    }
+    Ok(changed)
+}
+
+/// 把扫描结果整理为结果警告，超出 `MAX_LISTED` 的部分只给出数量
+pub fn warnings(references: &[ResReference], rewritten: bool) -> Vec<String> {
+    if references.is_empty() {
+        return Vec::new();
+    }
+    let rewritable = references.iter().filter(|r| r.rewritable).count();
+    let mut warnings = vec![if rewritten {
+        format!("res/xml 与 res/layout 中有 {} 处引用原包名，已改写 {} 处，其余为类名或 authorities，保持原样", references.len(), rewritable)
+    } else {
+        format!("res/xml 与 res/layout 中有 {} 处引用原包名（其中 {} 处可自动改写），可能导致安装后功能异常", references.len(), rewritable)
+    }];
+    warnings.extend(references.iter().take(MAX_LISTED).map(|r| format!("引用原包名: {}", r.describe())));
+    if references.len() > MAX_LISTED {
+        warnings.push(format!("另有 {} 处引用未列出", references.len() - MAX_LISTED));
+    }
+    warnings
+}
+
+/// 不反编译，直接读取 APK 中 res/xml* 与 res/layout* 的二进制 XML，用于处理前预览
+pub fn scan_apk(apk_path: &Path, package: &str) -> Result<Vec<ResReference>, String> {
+    let mut archive = zip::ZipArchive::new(fs::File::open(apk_path).map_err(|e| e.to_string())?).map_err(|e| format!("无法读取 APK: {}", e))?;
+    let reference = reference_re(package);
+    let names: Vec<String> = archive
+        .file_names()
+        .filter(|name| {
+            let mut parts = name.split('/');
+            parts.next() == Some("res") && parts.next().is_some_and(is_scanned_dir) && name.ends_with(".xml")
+        })
+        .map(str::to_string)
+        .collect();
+    let mut references = Vec::new();
+    for name in names {
+        let mut data = Vec::new();
+        if archive.by_name(&name).map_err(|e| e.to_string())?.read_to_end(&mut data).is_err() {
+            continue;
+        }
+        // 混淆或损坏的条目跳过
+        let Ok(elements) = axml::parse(&data) else {
+            continue;
+        };
+        for element in elements {
+            for attr in &element.attributes {
+                let axml::AxmlValue::String(value) = &attr.value else {
+                    continue;
+                };
+                if reference.is_match(value) {
+                    references.push(ResReference {
+                        file: name.clone(),
+                        line: None,
+                        text: format!("<{} {}=\"{}\">", element.name, attr.name, value),
+                        rewritable: value == package,
+                    });
+                }
+            }
+        }
+    }
+    Ok(references)
+}
+
+/// 处理前预览 res/xml 与 res/layout 中对原包名的引用
+#[tauri::command]
+pub fn scan_res_references(apk_path: String) -> Result<Vec<ResReference>, String> {
+    let apk_path = input_path::arg(&apk_path, Expect::File).map_err(|e| e.to_string())?;
+    let package = axml::read_manifest_from_apk(Path::new(&apk_path))?
+        .into_iter()
+        .find(|e| e.name == "manifest")
+        .and_then(|manifest| manifest.attr("package").map(axml::AxmlValue::as_string))
+        .ok_or_else(|| "无法读取原包名".to_string())?;
+    scan_apk(Path::new(&apk_path), &package)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHORTCUTS: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<shortcuts xmlns:android="http://schemas.android.com/apk/res/android">
+    <shortcut android:shortcutId="scan">
+        <intent android:action="android.intent.action.VIEW" android:targetPackage="com.example.app" android:targetClass="com.example.app.ScanActivity" />
+    </shortcut>
+</shortcuts>
+"#;
+    const FILE_PATHS: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<paths>
+    <external-path name="exports" path="Android/data/com.example.app/files/" />
+    <cache-path name="cache" path="." />
+</paths>
+"#;
+    const LAYOUT: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<LinearLayout xmlns:android="http://schemas.android.com/apk/res/android" xmlns:app="http://schemas.android.com/apk/res/com.example.app">
+    <com.example.app.widget.Badge app:count="3" />
+    <TextView android:text="com.example.application" />
+</LinearLayout>
+"#;
+    const STRINGS: &str = r#"<resources><string name="package">com.example.app</string></resources>
+"#;
+
+    fn work_dir() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for (path, content) in [
+            ("res/xml/shortcuts.xml", SHORTCUTS),
+            ("res/xml-v25/file_paths.xml", FILE_PATHS),
+            ("res/layout/main.xml", LAYOUT),
+            ("res/values/strings.xml", STRINGS),
+        ] {
+            let path = dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn scan_reports_file_and_line_outside_values() {
+        let dir = work_dir();
+        let found: Vec<(String, Option<u32>, bool)> =
+            scan(dir.path(), "com.example.app").into_iter().map(|r| (r.file, r.line, r.rewritable)).collect();
+        assert_eq!(
+            found,
+            [
+                ("res/layout/main.xml".to_string(), Some(2), true),
+                ("res/layout/main.xml".to_string(), Some(3), false),
+                ("res/xml/shortcuts.xml".to_string(), Some(4), true),
+                ("res/xml-v25/file_paths.xml".to_string(), Some(3), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn rewrite_replaces_exact_values_and_keeps_class_names() {
+        let dir = work_dir();
+        assert_eq!(rewrite(dir.path(), "com.example.app", "com.corp.app").unwrap(), 2);
+        let shortcuts = fs::read_to_string(dir.path().join("res/xml/shortcuts.xml")).unwrap();
+        assert!(shortcuts.contains(r#"android:targetPackage="com.corp.app""#));
+        assert!(shortcuts.contains(r#"android:targetClass="com.example.app.ScanActivity""#));
+        let layout = fs::read_to_string(dir.path().join("res/layout/main.xml")).unwrap();
+        assert!(layout.contains("http://schemas.android.com/apk/res/com.corp.app"));
+        assert!(layout.contains("<com.example.app.widget.Badge"));
+        assert_eq!(fs::read_to_string(dir.path().join("res/xml-v25/file_paths.xml")).unwrap(), FILE_PATHS);
+        assert_eq!(fs::read_to_string(dir.path().join("res/values/strings.xml")).unwrap(), STRINGS);
+    }
+
+    #[test]
+    fn warnings_summarize_and_list_references() {
+        let dir = work_dir();
+        let references = scan(dir.path(), "com.example.app");
+        let warnings = warnings(&references, false);
+        assert_eq!(warnings.len(), 1 + references.len());
+        assert!(warnings[0].contains("4 处引用原包名（其中 2 处可自动改写）"));
+        assert_eq!(warnings[3], format!("引用原包名: res/xml/shortcuts.xml:4: {}", SHORTCUTS.lines().nth(3).unwrap().trim()));
+        assert!(super::warnings(&[], true).is_empty());
+    }
+}
@@ -0,0 +1,169 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use tauri::Manager;
+
+use crate::paths::AppPaths;
+use crate::{adb, installs, repackaging, safe_path, signing};
+
+/// 备份 APK 的保留天数，超期的在下次备份时清理
+const RETENTION_DAYS: u64 = 7;
+/// 启动应用后等待多久再检查进程是否存活
+const LAUNCH_SETTLE: Duration = Duration::from_secs(3);
+
+/// 所有设备的回滚备份根目录，需在启动时登记为可删除根目录
+pub fn rollback_root(paths: &AppPaths) -> PathBuf {
+    paths.data_dir.join("rollback")
+}
+
+fn stash_path(paths: &AppPaths, device_id: &str, package: &str) -> PathBuf {
+    let device_dir: String = device_id.chars().map(|c| if c.is_ascii_alphanumeric() || c == '.' { c } else { '_' }).collect();
+    rollback_root(paths).join(device_dir).join(format!("{}.apk", package))
+}
+
+fn run_adb(device_id: &str, args: &[&str]) -> Result<String, String> {
+    let output = crate::adb::command()
+        .args(["-s", device_id])
+        .args(args)
+        .output()
+        .map_err(|e| e.to_string())?;
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    if output.status.success() {
+        Ok(stdout)
+    } else {
+        Err(format!("{} {}", String::from_utf8_lossy(&output.stderr).trim(), stdout.trim()))
+    }
+}
+
+/// 删除超过保留期的备份；删除失败的留到下次备份时再清理
+fn prune(paths: &AppPaths) {
+    let max_age = Duration::from_secs(RETENTION_DAYS * 24 * 3600);
+    let expired = walkdir::WalkDir::new(rollback_root(paths))
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| {
+            e.metadata()
+                .ok()
+                .and_then(|m| m.modified().ok())
+                .and_then(|t| SystemTime::now().duration_since(t).ok())
+                .is_some_and(|age| age > max_age)
+        });
+    for entry in expired {
+        let _ = safe_path::remove_file(entry.path());
+    }
+}
+
+/// 设备上该包的 base APK 路径
+fn remote_base_apk(device_id: &str, package: &str) -> Result<Option<String>, String> {
+    let pm_path = adb::shell(device_id, &["pm", "path", package])?;
+    let paths: Vec<&str> = pm_path.lines().filter_map(|line| line.trim().strip_prefix("package:")).collect();
+    Ok(paths.iter().find(|p| p.ends_with("base.apk")).or(paths.first()).map(|p| p.to_string()))
+}
+
+fn pull_installed(device_id: &str, package: &str, local: &Path) -> Result<bool, String> {
+    let Some(remote) = remote_base_apk(device_id, package)? else {
+        return Ok(false);
+    };
+    if let Some(parent) = local.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    run_adb(device_id, &["pull", &remote, &local.to_string_lossy()])?;
+    Ok(true)
+}
+
+/// 安装前备份设备上当前安装的 base APK，未安装该包时返回 None
+pub fn stash_installed(paths: &AppPaths, device_id: &str, package: &str) -> Result<Option<PathBuf>, String> {
+    prune(paths);
+    let local = stash_path(paths, device_id, package);
+    match pull_installed(device_id, package, &local) {
+        Ok(true) => Ok(Some(local)),
+        Ok(false) => Ok(None),
+        Err(e) => Err(format!("备份已安装的 APK 失败: {}", e)),
+    }
+}
+
+/// 启动应用并确认进程在稍后仍然存活
+pub fn verify_launch(device_id: &str, package: &str) -> Result<(), String> {
+    let monkey = run_adb(device_id, &["shell", "monkey", "-p", package, "-c", "android.intent.category.LAUNCHER", "1"])?;
+    if monkey.contains("No activities found") {
+        return Err("应用没有可启动的 Activity".to_string());
+    }
+    thread::sleep(LAUNCH_SETTLE);
+    let pid = adb::shell(device_id, &["pidof", package])?;
+    if pid.trim().is_empty() {
+        return Err(format!("启动后 {} 秒内进程已退出（可能崩溃）", LAUNCH_SETTLE.as_secs()));
+    }
+    Ok(())
+}
+
+/// 比较两个 APK 的签名证书，不一致时返回说明
+fn signature_mismatch(stashed: &Path, installed: Option<&Path>) -> Option<String> {
+    let keytool = signing::keytool_path(None);
+    let stashed_fp = repackaging::apk_cert_fingerprint(&keytool, stashed)?;
+    let installed_fp = repackaging::apk_cert_fingerprint(&keytool, installed?)?;
+    (!stashed_fp.eq_ignore_ascii_case(&installed_fp)).then(|| {
+        format!(
+            "备份 APK 的签名 ({}) 与当前安装版本 ({}) 不同，无法覆盖安装；需先卸载当前版本（会清除应用数据）后再安装备份 {}",
+            stashed_fp,
+            installed_fp,
+            stashed.display()
+        )
+    })
+}
+
+/// 用备份的 APK 覆盖安装。`installed_apk` 为当前安装版本的本地副本，用于预先检查签名是否一致
+pub fn restore(paths: &AppPaths, device_id: &str, package: &str, installed_apk: Option<&Path>) -> Result<String, String> {
+    let stashed = stash_path(paths, device_id, package);
+    if !stashed.exists() {
+        return Err(format!("没有 {} 在设备 {} 上的备份", package, device_id));
+    }
+    if let Some(reason) = signature_mismatch(&stashed, installed_apk) {
+        return Err(reason);
+    }
+    let out = run_adb(device_id, &["install", "-r", "-d", &stashed.to_string_lossy()])?;
+    if !out.contains("Success") {
+        if out.contains("INSTALL_FAILED_UPDATE_INCOMPATIBLE") {
+            return Err("备份 APK 与当前安装版本签名不同，无法覆盖安装；需先卸载当前版本（会清除应用数据）".to_string());
+        }
+        return Err(format!("恢复安装失败: {}", out.trim()));
+    }
+    Ok(format!("已恢复 {} 的备份版本", package))
+}
+
+/// 手动将应用恢复为最近一次安装前备份的版本
+#[tauri::command]
+pub fn rollback_package(
+    app: tauri::AppHandle,
+    app_paths: tauri::State<'_, AppPaths>,
+    cache: tauri::State<'_, crate::device_cache::DeviceCache>,
+    history: tauri::State<'_, installs::InstallHistory>,
+    device_id: String,
+    package_name: String,
+) -> Result<String, String> {
+    let coordinator = app.state::<crate::adb_coordinator::AdbCoordinator>();
+    let transfer = coordinator.begin_transfer(&device_id, "rollback");
+    let result = rollback_installed(&app_paths, &cache, &history, &device_id, &package_name);
+    drop(transfer);
+    crate::audit::record(&app, "rollback_package", serde_json::json!({ "device": device_id, "package": package_name }), &result);
+    result
+}
+
+fn rollback_installed(
+    app_paths: &AppPaths,
+    cache: &crate::device_cache::DeviceCache,
+    history: &installs::InstallHistory,
+    device_id: &str,
+    package_name: &str,
+) -> Result<String, String> {
+    let current = tempfile::tempdir().map_err(|e| e.to_string())?;
+    let current_apk = current.path().join("current.apk");
+    let pulled = pull_installed(device_id, package_name, &current_apk).unwrap_or(false);
+    let message = restore(app_paths, device_id, package_name, pulled.then_some(current_apk.as_path()))?;
+    cache.invalidate(device_id, crate::device_cache::DATASET_INSTALLED_APPS);
+    let record = installs::install_record(device_id, package_name, &stash_path(app_paths, device_id, package_name), Some("回滚".to_string()));
+    let _ = history.append(app_paths, record);
+    Ok(message)
+}
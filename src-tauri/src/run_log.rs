@@ -0,0 +1,162 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::io::{self, BufRead, BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Instant;
+
+use tauri::{Emitter, Manager};
+
+use crate::log_batch::{LogBatcher, LogChunk, DEFAULT_MAX_INTERVAL, DEFAULT_MAX_LINES};
+use crate::paths::AppPaths;
+
+/// 推送给前端的一批工具输出（`process-log` 事件）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct LogEvent {
+    pub job_id: String,
+    /// 处理完后用 `ack_log_events` 确认，否则之后的输出只推送省略行数
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub seq: u64,
+    pub lines: Vec<String>,
+    /// 省略的行数，完整内容见运行日志文件（`get_log_tail`）
+    pub omitted: usize,
+}
+
+/// 一次任务的运行日志：完整输出写入文件，分批推送给前端
+pub struct RunLog {
+    job_id: String,
+    app: tauri::AppHandle,
+    file: Mutex<Option<BufWriter<fs::File>>>,
+    batcher: Mutex<LogBatcher>,
+    done: AtomicBool,
+}
+
+/// 进行中任务的运行日志
+#[derive(Default)]
+pub struct RunLogs(Mutex<HashMap<String, Arc<RunLog>>>);
+
+fn log_path(paths: &AppPaths, job_id: &str) -> PathBuf {
+    paths.log_dir.join("runs").join(format!("{}.log", job_id))
+}
+
+impl RunLog {
+    /// 创建日志文件并登记；文件无法创建时仍推送事件
+    pub fn start(app: &tauri::AppHandle, job_id: &str, queue_depth: u64) -> Arc<RunLog> {
+        let path = log_path(&app.state::<AppPaths>(), job_id);
+        let file = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::File::create(&path))
+            .map(BufWriter::new)
+            .map_err(|e| crate::emit_progress(app, "log", format!("创建运行日志失败: {}", e)))
+            .ok();
+        let log = Arc::new(RunLog {
+            job_id: job_id.to_string(),
+            app: app.clone(),
+            file: Mutex::new(file),
+            batcher: Mutex::new(LogBatcher::new(DEFAULT_MAX_LINES, DEFAULT_MAX_INTERVAL, queue_depth)),
+            done: AtomicBool::new(false),
+        });
+        app.state::<RunLogs>().0.lock().unwrap().insert(job_id.to_string(), log.clone());
+
+        // 没有新行时也按时间间隔发出积压的行
+        let weak = Arc::downgrade(&log);
+        thread::spawn(move || loop {
+            thread::sleep(DEFAULT_MAX_INTERVAL);
+            let Some(log) = weak.upgrade() else {
+                break;
+            };
+            if log.done.load(Ordering::Relaxed) {
+                break;
+            }
+            let chunk = log.batcher.lock().unwrap().tick(Instant::now());
+            log.emit(chunk);
+        });
+        log
+    }
+
+    fn emit(&self, chunk: Option<LogChunk>) {
+        if let Some(LogChunk { seq, lines, omitted }) = chunk {
+            let _ = self.app.emit("process-log", LogEvent { job_id: self.job_id.clone(), seq, lines, omitted });
+        }
+    }
+
+    fn write(&self, line: &str) {
+        if let Some(file) = self.file.lock().unwrap().as_mut() {
+            let _ = writeln!(file, "{}", line);
+        }
+    }
+
+    /// 新步骤开始，只写入日志文件
+    pub fn step(&self, step: &str) {
+        self.write(&format!("==== {} ====", step));
+    }
+
+    /// 一行工具输出
+    pub fn line(&self, line: &str) {
+        self.write(line);
+        let chunk = self.batcher.lock().unwrap().push(line.to_string(), Instant::now());
+        self.emit(chunk);
+    }
+
+    /// 发出剩余的行、写完文件并注销
+    pub fn finish(&self) {
+        self.done.store(true, Ordering::Relaxed);
+        let chunk = self.batcher.lock().unwrap().flush();
+        self.emit(chunk);
+        if let Some(file) = self.file.lock().unwrap().as_mut() {
+            let _ = file.flush();
+        }
+        self.app.state::<RunLogs>().0.lock().unwrap().remove(&self.job_id);
+    }
+}
+
+impl RunLogs {
+    /// 把所有进行中任务的日志写入文件（退出时使用）
+    pub fn flush_all(&self) {
+        for log in self.0.lock().unwrap().values() {
+            if let Some(file) = log.file.lock().unwrap().as_mut() {
+                let _ = file.flush();
+            }
+        }
+    }
+}
+
+/// 前端确认已处理到 `seq` 的日志批次，积压消除后恢复完整推送
+#[tauri::command]
+pub fn ack_log_events(logs: tauri::State<'_, RunLogs>, job_id: String, seq: u64) {
+    if let Some(log) = logs.0.lock().unwrap().get(&job_id) {
+        log.batcher.lock().unwrap().ack(seq);
+    }
+}
+
+/// 运行日志文件的最后 `lines` 行，用于采样后重新同步
+#[tauri::command]
+pub fn get_log_tail(
+    paths: tauri::State<'_, AppPaths>,
+    logs: tauri::State<'_, RunLogs>,
+    job_id: String,
+    lines: usize,
+) -> Result<Vec<String>, String> {
+    // 进行中的任务先把缓冲写入文件
+    if let Some(log) = logs.0.lock().unwrap().get(&job_id) {
+        if let Some(file) = log.file.lock().unwrap().as_mut() {
+            let _ = file.flush();
+        }
+    }
+    let file = fs::File::open(log_path(&paths, &job_id)).map_err(|e| format!("找不到任务 {} 的运行日志: {}", job_id, e))?;
+    let mut tail = VecDeque::with_capacity(lines.min(10_000));
+    for line in io::BufReader::new(file).split(b'\n').map_while(Result::ok) {
+        if tail.len() == lines {
+            tail.pop_front();
+        }
+        if lines > 0 {
+            tail.push_back(String::from_utf8_lossy(&line).trim_end().to_string());
+        }
+    }
+    Ok(tail.into())
+}
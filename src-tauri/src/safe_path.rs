@@ -0,0 +1,220 @@
+//! 删除操作的安全护栏：只允许删除已登记根目录之内的路径，并记录每次删除。
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 被删除路径至少需要的目录层级（不含根）
+const MIN_DEPTH: usize = 3;
+
+/// 删除请求违反安全规则
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct SafetyViolation {
+    pub path: String,
+    pub reason: String,
+}
+
+impl fmt::Display for SafetyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "拒绝删除 {}: {}", self.path, self.reason)
+    }
+}
+
+impl std::error::Error for SafetyViolation {}
+
+fn roots() -> &'static Mutex<Vec<PathBuf>> {
+    static ROOTS: OnceLock<Mutex<Vec<PathBuf>>> = OnceLock::new();
+    ROOTS.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+fn log_file() -> &'static Mutex<Option<PathBuf>> {
+    static LOG_FILE: OnceLock<Mutex<Option<PathBuf>>> = OnceLock::new();
+    LOG_FILE.get_or_init(|| Mutex::new(None))
+}
+
+/// 设置删除记录文件，每次删除追加一行
+pub fn set_log_file(path: PathBuf) {
+    *log_file().lock().unwrap() = Some(path);
+}
+
+/// 记录一次删除；记录失败不影响删除本身
+fn log_deletion(kind: &str, resolved: &Path, bytes: u64) {
+    let Some(path) = log_file().lock().unwrap().clone() else {
+        return;
+    };
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let _ = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::OpenOptions::new().create(true).append(true).open(&path))
+        .and_then(|mut file| writeln!(file, "{}	{}	{}	{}", timestamp, kind, resolved.display(), bytes));
+}
+
+/// 程序管理的工作目录根（反编译目录、中间文件）
+pub fn work_root() -> PathBuf {
+    std::env::temp_dir().join("apk_disguise")
+}
+
+/// 登记允许删除的根目录
+pub fn register_root(root: &Path) {
+    let _ = fs::create_dir_all(root);
+    let resolved = fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+    let mut roots = roots().lock().unwrap();
+    if !roots.contains(&resolved) {
+        roots.push(resolved);
+    }
+}
+
+fn violation(path: &Path, reason: &str) -> SafetyViolation {
+    SafetyViolation { path: path.display().to_string(), reason: reason.to_string() }
+}
+
+fn depth(path: &Path) -> usize {
+    path.components().filter(|c| matches!(c, Component::Normal(_))).count()
+}
+
+fn is_home_dir(path: &Path) -> bool {
+    ["HOME", "USERPROFILE"]
+        .iter()
+        .filter_map(std::env::var_os)
+        .filter_map(|home| fs::canonicalize(home).ok())
+        .any(|home| home == path)
+}
+
+/// 检查目标路径是否允许删除，返回解析后的真实路径（会跟随符号链接）
+pub fn check_deletable(target: &Path) -> Result<PathBuf, SafetyViolation> {
+    let resolved = fs::canonicalize(target).map_err(|e| violation(target, &format!("无法解析路径: {}", e)))?;
+    if resolved.parent().is_none() || depth(&resolved) == 0 {
+        return Err(violation(&resolved, "目标是文件系统根目录"));
+    }
+    if is_home_dir(&resolved) {
+        return Err(violation(&resolved, "目标是用户主目录"));
+    }
+    if depth(&resolved) < MIN_DEPTH {
+        return Err(violation(&resolved, "目标路径层级过浅"));
+    }
+    let roots = roots().lock().unwrap();
+    if !roots.iter().any(|root| resolved.starts_with(root) && resolved != *root) {
+        return Err(violation(&resolved, "目标不在程序管理的目录之内"));
+    }
+    Ok(resolved)
+}
+
+fn size_of(path: &Path) -> u64 {
+    walkdir::WalkDir::new(path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.metadata().ok())
+        .filter(|m| m.is_file())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// 安全删除目录，返回释放的字节数。目标不存在时视为成功
+pub fn remove_dir_all(target: &Path) -> Result<u64, SafetyViolation> {
+    if fs::symlink_metadata(target).is_err() {
+        return Ok(0);
+    }
+    let resolved = check_deletable(target)?;
+    let bytes = size_of(&resolved);
+    fs::remove_dir_all(&resolved).map_err(|e| violation(&resolved, &format!("删除失败: {}", e)))?;
+    log_deletion("dir", &resolved, bytes);
+    Ok(bytes)
+}
+
+/// 安全删除单个文件，返回释放的字节数。目标不存在时视为成功
+pub fn remove_file(target: &Path) -> Result<u64, SafetyViolation> {
+    if fs::symlink_metadata(target).is_err() {
+        return Ok(0);
+    }
+    let resolved = check_deletable(target)?;
+    let bytes = fs::metadata(&resolved).map(|m| m.len()).unwrap_or(0);
+    fs::remove_file(&resolved).map_err(|e| violation(&resolved, &format!("删除失败: {}", e)))?;
+    log_deletion("file", &resolved, bytes);
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 登记一个临时根目录，返回根目录与其中的一个子目录
+    fn registered_root() -> (tempfile::TempDir, PathBuf, PathBuf) {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().join("managed");
+        register_root(&root);
+        let inner = root.join("job").join("work");
+        fs::create_dir_all(&inner).unwrap();
+        fs::write(inner.join("classes.dex"), b"dex\n").unwrap();
+        (temp, root, inner)
+    }
+
+    #[test]
+    fn deletes_inside_a_registered_root_and_logs() {
+        let (temp, _, inner) = registered_root();
+        let log = temp.path().join("deletions.log");
+        set_log_file(log.clone());
+        assert_eq!(remove_dir_all(&inner).unwrap(), 4);
+        assert!(!inner.exists());
+        assert_eq!(remove_dir_all(&inner).unwrap(), 0);
+        let logged = fs::read_to_string(log).unwrap();
+        assert!(logged.lines().any(|line| line.contains("\tdir\t") && line.ends_with("\t4")));
+    }
+
+    #[test]
+    fn refuses_the_root_itself() {
+        let (_temp, root, _) = registered_root();
+        let err = remove_dir_all(&root).unwrap_err();
+        assert_eq!(err.reason, "目标不在程序管理的目录之内");
+        assert!(root.exists());
+    }
+
+    #[test]
+    fn refuses_unregistered_paths() {
+        let outside = tempfile::tempdir().unwrap();
+        let target = outside.path().join("a").join("b");
+        fs::create_dir_all(&target).unwrap();
+        assert!(remove_dir_all(&target).is_err());
+        assert!(target.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unix_filesystem_root_is_refused() {
+        assert_eq!(check_deletable(Path::new("/")).unwrap_err().reason, "目标是文件系统根目录");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_escaping_the_root_is_refused() {
+        let (_temp, _, inner) = registered_root();
+        let outside = tempfile::tempdir().unwrap();
+        let victim = outside.path().join("keep").join("me");
+        fs::create_dir_all(&victim).unwrap();
+        let link = inner.join("escape");
+        std::os::unix::fs::symlink(&victim, &link).unwrap();
+        assert_eq!(remove_dir_all(&link).unwrap_err().reason, "目标不在程序管理的目录之内");
+        assert!(victim.exists());
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn windows_drive_and_unc_roots_have_no_depth() {
+        assert_eq!(depth(Path::new(r"C:\")), 0);
+        assert_eq!(depth(Path::new(r"\\server\share\")), 0);
+        assert_eq!(depth(Path::new(r"\\?\UNC\server\share\")), 0);
+        assert_eq!(depth(Path::new(r"\\server\share\apk_disguise\work")), 2);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn windows_drive_root_is_refused() {
+        let drive = std::env::temp_dir().components().next().map(|c| PathBuf::from(c.as_os_str()).join(r"\")).unwrap();
+        assert_eq!(check_deletable(&drive).unwrap_err().reason, "目标是文件系统根目录");
+    }
+}
@@ -0,0 +1,213 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use tauri::Manager;
+
+use crate::adb_coordinator::AdbCoordinator;
+use crate::confirm::{Confirmation, ConfirmationTokens, DestructiveSummary};
+use crate::paths::AppPaths;
+use crate::pipeline::{self, ProcessConfig};
+use crate::{config, safe_path};
+
+/// 最小 APK：二进制 Manifest 与只含一个空类的 classes.dex（无资源表）。
+/// 回编译后的输出必须带 dex 才能通过校验，空的 smali 目录不会生成 classes.dex
+const SELF_TEST_APK: &[u8] = include_bytes!("../fixtures/selftest.apk");
+/// 文件名即工作目录名，避免与用户 APK 冲突
+const SELF_TEST_STEM: &str = "apk_disguise_selftest";
+const SELF_TEST_PREFIX: &str = "com.apkdisguise.selftest";
+
+/// 自检中单个步骤的结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct SelfTestStep {
+    pub step: String,
+    pub passed: bool,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub duration_ms: u64,
+    pub message: Option<String>,
+}
+
+/// 环境自检报告
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct SelfTestReport {
+    pub ok: bool,
+    /// 第一个失败的步骤
+    pub failed_step: Option<String>,
+    pub steps: Vec<SelfTestStep>,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub total_ms: u64,
+}
+
+impl SelfTestReport {
+    fn push(&mut self, step: &str, started: Instant, result: Result<String, String>) -> bool {
+        let passed = result.is_ok();
+        self.steps.push(SelfTestStep {
+            step: step.to_string(),
+            passed,
+            duration_ms: started.elapsed().as_millis() as u64,
+            message: Some(result.unwrap_or_else(|e| e)).filter(|m| !m.is_empty()),
+        });
+        if !passed && self.failed_step.is_none() {
+            self.failed_step = Some(step.to_string());
+        }
+        passed
+    }
+}
+
+fn command_output(cmd: &mut Command) -> Result<String, String> {
+    let output = cmd.output().map_err(|e| e.to_string())?;
+    let text = format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+    if output.status.success() {
+        Ok(text.trim().to_string())
+    } else {
+        Err(text.trim().to_string())
+    }
+}
+
+/// 通过流水线处理内置 APK，并把各步骤结果写入报告；成功时返回输出 APK 路径与新包名
+fn run_pipeline(app: &tauri::AppHandle, mut config: ProcessConfig, temp: &Path, report: &mut SelfTestReport) -> Option<(String, String)> {
+    let apk = temp.join(format!("{}.apk", SELF_TEST_STEM));
+    let started = Instant::now();
+    if let Err(e) = fs::write(&apk, SELF_TEST_APK) {
+        report.push("prepare", started, Err(e.to_string()));
+        return None;
+    }
+
+    let suffix = format!("t{}", SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0));
+    let package = format!("{}.{}", SELF_TEST_PREFIX, suffix);
+    config.apk_path = apk.to_string_lossy().to_string();
+    config.new_prefix = SELF_TEST_PREFIX.to_string();
+    config.custom_suffix = Some(suffix);
+    config.install_after = false;
+    config.device_id = None;
+    config.hooks = None;
+    config.post_install_actions = None;
+
+    let result = pipeline::run(app, config, None);
+    for dir in [SELF_TEST_STEM.to_string(), format!("{}_stage", SELF_TEST_STEM)] {
+        let _ = safe_path::remove_dir_all(&safe_path::work_root().join(dir));
+    }
+    let result = match result {
+        Ok(result) => result,
+        Err(e) => {
+            report.push("pipeline", started, Err(e));
+            return None;
+        }
+    };
+
+    for step in result.metrics.iter().flat_map(|m| &m.steps) {
+        let passed = step.exit_code == Some(0);
+        report.steps.push(SelfTestStep { step: step.step.clone(), passed, duration_ms: step.duration_ms, message: None });
+        if !passed && report.failed_step.is_none() {
+            report.failed_step = Some(step.step.clone());
+        }
+    }
+    if !result.success {
+        let step = result.step.clone().unwrap_or_else(|| "pipeline".to_string());
+        match report.steps.iter_mut().find(|s| s.step == step) {
+            Some(existing) => existing.message = Some(result.message),
+            None => {
+                report.steps.push(SelfTestStep { step: step.clone(), passed: false, duration_ms: 0, message: Some(result.message) });
+            }
+        }
+        report.failed_step.get_or_insert(step);
+        return None;
+    }
+    result.output_path.map(|path| (path, package))
+}
+
+/// 用配置的工具把内置的最小 APK 走一遍 反编译 → 改包名 → 回编译 → 对齐 → 签名 → 校验，
+/// 在临时目录中进行并在结束后完全清理。指定设备时额外安装并卸载一次（需要确认）
+#[tauri::command]
+pub async fn run_self_test(
+    app: tauri::AppHandle,
+    app_paths: tauri::State<'_, AppPaths>,
+    tokens: tauri::State<'_, ConfirmationTokens>,
+    partial_config: ProcessConfig,
+    device_id: Option<String>,
+    confirmation_token: Option<String>,
+) -> Result<Confirmation<SelfTestReport>, String> {
+    if let Some(device) = &device_id {
+        let pending = tokens.check("run_self_test", device, confirmation_token, || DestructiveSummary {
+            operation: "在设备上安装并卸载自检 APK".to_string(),
+            device_serial: device.clone(),
+            device_alias: None,
+            package_count: 1,
+            data_loss: false,
+            detail: None,
+        })?;
+        if let Some(pending) = pending {
+            return Ok(pending);
+        }
+    }
+
+    let started = Instant::now();
+    let mut report = SelfTestReport::default();
+    let step_started = Instant::now();
+    let config = match config::effective_config(&app_paths, partial_config) {
+        Ok(config) => config,
+        Err(e) => {
+            report.push("tools", step_started, Err(e.to_string()));
+            report.total_ms = started.elapsed().as_millis() as u64;
+            return Ok(Confirmation::Done { result: report });
+        }
+    };
+    let (java_path, apksigner_path) = (config.java_path.clone(), config.apksigner_path.clone());
+
+    let temp = tempfile::tempdir().map_err(|e| e.to_string())?;
+    if let Some((output, package)) = run_pipeline(&app, config, temp.path(), &mut report) {
+        let verify_started = Instant::now();
+        let verified = command_output(Command::new(&java_path).args(["-jar", &apksigner_path, "verify", &output]));
+        if report.push("verify", verify_started, verified) {
+            if let Some(device) = &device_id {
+                let install_started = Instant::now();
+                let coordinator = app.state::<AdbCoordinator>();
+                let transfer = coordinator.begin_transfer(device, "install");
+                let installed = command_output(crate::adb::command().args(["-s", device, "install", "-r", "-t", &output]));
+                drop(transfer);
+                if report.push("install", install_started, installed) {
+                    let uninstall_started = Instant::now();
+                    let removed = command_output(crate::adb::command().args(["-s", device, "uninstall", &package]));
+                    report.push("uninstall", uninstall_started, removed);
+                }
+            }
+        }
+    }
+    drop(temp);
+
+    report.ok = report.failed_step.is_none();
+    report.total_ms = started.elapsed().as_millis() as u64;
+    Ok(Confirmation::Done { result: report })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn fixture_passes_the_rebuilt_apk_check() {
+        let dir = tempfile::tempdir().unwrap();
+        let apk = dir.path().join("selftest.apk");
+        fs::write(&apk, SELF_TEST_APK).unwrap();
+        crate::entries::verify_rebuilt_apk(&apk, SELF_TEST_APK.len() as u64, crate::entries::DEFAULT_MIN_OUTPUT_RATIO).unwrap();
+    }
+
+    #[test]
+    fn fixture_dex_is_valid() {
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(SELF_TEST_APK)).unwrap();
+        let mut dex = Vec::new();
+        archive.by_name("classes.dex").unwrap().read_to_end(&mut dex).unwrap();
+
+        let mut recomputed = dex.clone();
+        crate::dex::update_header_checksums(&mut recomputed);
+        assert_eq!(recomputed, dex, "DEX 头的校验值不正确");
+        assert_eq!(u32::from_le_bytes([dex[32], dex[33], dex[34], dex[35]]) as usize, dex.len());
+        // (字符串数, 方法引用数, 类定义数)
+        assert_eq!(crate::analysis::dex_header_counts(&dex), Some((2, 0, 1)));
+    }
+}
@@ -0,0 +1,114 @@
+//! 界面上下文（选择的设备、APK、前缀、列表滚动位置等）的持久化，重启或崩溃后恢复。
+//! 内容由前端决定，这里只限制大小、检查版本，并去掉引用了已不存在的设备或文件的字段。
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::paths::{self, AppPaths};
+use crate::pipeline::ProcessConfig;
+
+const SESSION_FILE: &str = "session.json";
+/// 当前的上下文格式版本，不一致时丢弃
+pub const SCHEMA_VERSION: u32 = 1;
+/// 上下文序列化后的大小上限
+const MAX_CONTEXT_BYTES: usize = 64 * 1024;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredSession {
+    schema_version: u32,
+    saved_at: u64,
+    context: serde_json::Map<String, serde_json::Value>,
+}
+
+/// 载入的上下文
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct SessionContext {
+    #[cfg_attr(feature = "export-bindings", ts(type = "Record<string, unknown> | null"))]
+    pub context: Option<serde_json::Map<String, serde_json::Value>>,
+    /// 保存时间（Unix 时间戳，秒）
+    #[cfg_attr(feature = "export-bindings", ts(type = "number | null"))]
+    pub saved_at: Option<u64>,
+    /// 被去掉的字段及原因，供界面提示
+    pub dropped: Vec<String>,
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn read(paths: &AppPaths) -> Option<StoredSession> {
+    let content = fs::read_to_string(paths.data_dir.join(SESSION_FILE)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn write(paths: &AppPaths, context: serde_json::Map<String, serde_json::Value>) -> Result<(), String> {
+    let stored = StoredSession { schema_version: SCHEMA_VERSION, saved_at: now(), context };
+    let content = serde_json::to_vec(&stored).map_err(|e| e.to_string())?;
+    if content.len() > MAX_CONTEXT_BYTES {
+        return Err(format!("界面上下文过大（{} 字节，上限 {} 字节）", content.len(), MAX_CONTEXT_BYTES));
+    }
+    paths::write_atomic(&paths.data_dir.join(SESSION_FILE), &content).map_err(|e| format!("保存界面上下文失败: {}", e))
+}
+
+/// 去掉引用已不存在对象的字段：`device_id` 须在已连接设备中，以 `_path` 结尾的字段须指向存在的文件。
+/// 返回被去掉的字段说明
+pub fn strip_stale(context: &mut serde_json::Map<String, serde_json::Value>, devices: &[String]) -> Vec<String> {
+    let mut dropped = Vec::new();
+    context.retain(|key, value| {
+        let Some(text) = value.as_str().filter(|text| !text.is_empty()) else {
+            return true;
+        };
+        if key == "device_id" && !devices.iter().any(|d| d == text) {
+            dropped.push(format!("{}: 设备 {} 未连接", key, text));
+            return false;
+        }
+        if key.ends_with("_path") && !Path::new(text).exists() {
+            dropped.push(format!("{}: 文件 {} 已不存在", key, text));
+            return false;
+        }
+        true
+    });
+    dropped
+}
+
+/// 开始耗时的处理前合并本次的设置，处理中崩溃也能恢复
+pub fn snapshot_for_job(paths: &AppPaths, config: &ProcessConfig) -> Result<(), String> {
+    let mut context = read(paths).filter(|s| s.schema_version == SCHEMA_VERSION).map(|s| s.context).unwrap_or_default();
+    context.insert("apk_path".to_string(), config.apk_path.clone().into());
+    context.insert("new_prefix".to_string(), config.new_prefix.clone().into());
+    context.insert("custom_suffix".to_string(), config.custom_suffix.clone().into());
+    if let Some(device_id) = &config.device_id {
+        context.insert("device_id".to_string(), device_id.clone().into());
+    }
+    write(paths, context)
+}
+
+/// 保存界面上下文（由前端定时或在变化时调用）
+#[tauri::command]
+pub fn save_session_context(
+    paths: tauri::State<'_, AppPaths>,
+    context: serde_json::Map<String, serde_json::Value>,
+) -> Result<(), String> {
+    write(&paths, context)
+}
+
+/// 载入上次的界面上下文，去掉已失效的字段
+#[tauri::command]
+pub fn load_session_context(paths: tauri::State<'_, AppPaths>) -> SessionContext {
+    let Some(stored) = read(&paths) else {
+        return SessionContext::default();
+    };
+    if stored.schema_version != SCHEMA_VERSION {
+        return SessionContext {
+            dropped: vec![format!("上下文版本 {} 与当前版本 {} 不一致，已忽略", stored.schema_version, SCHEMA_VERSION)],
+            ..Default::default()
+        };
+    }
+    let mut context = stored.context;
+    let devices = crate::adb::devices().unwrap_or_default();
+    let dropped = strip_stale(&mut context, &devices);
+    SessionContext { context: Some(context), saved_at: Some(stored.saved_at), dropped }
+}
@@ -0,0 +1,168 @@
+//! 退出时的协调关闭。各子系统在启动时登记带名称的关闭钩子：
+//! 先并行执行 `Stop` 钩子（取消后台任务、停止监听、结束子进程），最多等待宽限时间；
+//! 无论是否超时，再依次执行 `Flush` 钩子，把日志与记录文件写完整后才允许退出。
+//! 某个钩子 panic 不影响其余钩子。结果写入日志目录下的 `shutdown.log`。
+
+use serde::Serialize;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::paths::{self, AppPaths};
+
+/// 等待后台任务结束的宽限时间
+pub const GRACE_PERIOD: Duration = Duration::from_secs(3);
+
+/// 钩子阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// 通知后台任务停止并等待其结束，超时后放弃等待
+    Stop,
+    /// 写完并同步记录文件，总会执行
+    Flush,
+}
+
+type HookFn = Box<dyn FnOnce() + Send>;
+
+struct Hook {
+    name: &'static str,
+    phase: Phase,
+    run: HookFn,
+}
+
+/// 关闭结果
+#[derive(Debug, Serialize, Clone, Default, PartialEq)]
+pub struct ShutdownReport {
+    /// 按完成顺序
+    pub finished: Vec<String>,
+    pub timed_out: Vec<String>,
+    pub panicked: Vec<String>,
+    pub elapsed_ms: u64,
+}
+
+/// 关闭钩子登记表（托管状态）
+#[derive(Default)]
+pub struct ShutdownCoordinator {
+    hooks: Mutex<Vec<Hook>>,
+}
+
+impl ShutdownCoordinator {
+    /// 登记关闭钩子，同一阶段内按登记顺序执行（`Stop` 钩子并行启动）
+    pub fn register(&self, name: &'static str, phase: Phase, hook: impl FnOnce() + Send + 'static) {
+        self.hooks.lock().unwrap().push(Hook { name, phase, run: Box::new(hook) });
+    }
+
+    /// 执行所有钩子；每个钩子只执行一次，重复调用时返回空结果
+    pub fn run(&self, grace: Duration) -> ShutdownReport {
+        let started = Instant::now();
+        let hooks = std::mem::take(&mut *self.hooks.lock().unwrap());
+        let (stop, flush): (Vec<Hook>, Vec<Hook>) = hooks.into_iter().partition(|h| h.phase == Phase::Stop);
+        let mut report = ShutdownReport::default();
+
+        let (tx, rx) = mpsc::channel();
+        let mut pending: Vec<&'static str> = Vec::new();
+        for hook in stop {
+            let tx = tx.clone();
+            pending.push(hook.name);
+            let spawned = thread::Builder::new().name(format!("shutdown-{}", hook.name)).spawn(move || {
+                let ok = panic::catch_unwind(AssertUnwindSafe(hook.run)).is_ok();
+                let _ = tx.send((hook.name, ok));
+            });
+            if spawned.is_err() {
+                pending.pop();
+                report.panicked.push(hook.name.to_string());
+            }
+        }
+        drop(tx);
+        let deadline = started + grace;
+        while !pending.is_empty() {
+            let Ok((name, ok)) = rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) else {
+                break;
+            };
+            pending.retain(|p| *p != name);
+            if ok {
+                report.finished.push(name.to_string());
+            } else {
+                report.panicked.push(name.to_string());
+            }
+        }
+        report.timed_out = pending.into_iter().map(str::to_string).collect();
+
+        for hook in flush {
+            if panic::catch_unwind(AssertUnwindSafe(hook.run)).is_ok() {
+                report.finished.push(hook.name.to_string());
+            } else {
+                report.panicked.push(hook.name.to_string());
+            }
+        }
+        report.elapsed_ms = started.elapsed().as_millis() as u64;
+        report
+    }
+}
+
+/// 记录关闭结果（含超时与 panic 的钩子），供下次启动后排查
+pub fn write_report(paths: &AppPaths, report: &ShutdownReport) {
+    if let Ok(content) = serde_json::to_vec_pretty(report) {
+        let _ = paths::write_atomic(&paths.log_dir.join("shutdown.log"), &content);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn recorder() -> (Arc<Mutex<Vec<&'static str>>>, impl Fn(&'static str) -> HookFn) {
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let shared = order.clone();
+        let hook = move |name: &'static str| {
+            let order = shared.clone();
+            Box::new(move || order.lock().unwrap().push(name)) as HookFn
+        };
+        (order, hook)
+    }
+
+    #[test]
+    fn flush_hooks_run_after_stop_hooks_in_registration_order() {
+        let coordinator = ShutdownCoordinator::default();
+        let (order, hook) = recorder();
+        coordinator.register("history", Phase::Flush, hook("history"));
+        coordinator.register("jobs", Phase::Stop, hook("jobs"));
+        coordinator.register("audit", Phase::Flush, hook("audit"));
+        let report = coordinator.run(Duration::from_secs(1));
+        assert_eq!(*order.lock().unwrap(), ["jobs", "history", "audit"]);
+        assert_eq!(report.finished, ["jobs", "history", "audit"]);
+        assert!(report.timed_out.is_empty() && report.panicked.is_empty());
+        assert_eq!(coordinator.run(Duration::from_secs(1)).finished, Vec::<String>::new());
+    }
+
+    #[test]
+    fn slow_stop_hooks_time_out_and_flushes_still_run() {
+        let coordinator = ShutdownCoordinator::default();
+        let (order, hook) = recorder();
+        coordinator.register("downloads", Phase::Stop, || thread::sleep(Duration::from_secs(5)));
+        coordinator.register("tracker", Phase::Stop, hook("tracker"));
+        coordinator.register("audit", Phase::Flush, hook("audit"));
+        let started = Instant::now();
+        let report = coordinator.run(Duration::from_millis(200));
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert_eq!(report.timed_out, ["downloads"]);
+        assert_eq!(report.finished, ["tracker", "audit"]);
+        assert_eq!(*order.lock().unwrap(), ["tracker", "audit"]);
+    }
+
+    #[test]
+    fn panicking_hooks_do_not_block_the_remaining_flushes() {
+        let coordinator = ShutdownCoordinator::default();
+        let (order, hook) = recorder();
+        coordinator.register("monitor", Phase::Stop, || panic!("monitor"));
+        coordinator.register("settings", Phase::Flush, || panic!("settings"));
+        coordinator.register("audit", Phase::Flush, hook("audit"));
+        let report = coordinator.run(Duration::from_secs(1));
+        assert_eq!(report.panicked, ["monitor", "settings"]);
+        assert_eq!(report.finished, ["audit"]);
+        assert_eq!(*order.lock().unwrap(), ["audit"]);
+    }
+}
@@ -0,0 +1,743 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::input_path::{self, Expect};
+use crate::io_pool::{IoPool, Priority};
+
+/// 签名 / 流水线操作的结构化错误
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+#[serde(tag = "kind", content = "detail")]
+pub enum PipelineError {
+    /// 输入不符合要求（如 APK 不是仅 V1 签名）
+    InvalidInput(String),
+    /// 文件读写失败
+    Io(String),
+    /// 外部工具执行失败
+    Tool { step: String, message: String },
+    /// 多个 APK 之间存在循环依赖
+    DependencyCycle { packages: Vec<String> },
+    /// 用户输入的路径无效；`normalized` 为规范化后的结果（解析失败时为空）
+    InvalidPath { raw: String, normalized: Option<String>, reason: String },
+}
+
+impl fmt::Display for PipelineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PipelineError::InvalidInput(msg) => write!(f, "输入无效: {}", msg),
+            PipelineError::Io(msg) => write!(f, "文件操作失败: {}", msg),
+            PipelineError::Tool { step, message } => write!(f, "{} 失败: {}", step, message),
+            PipelineError::DependencyCycle { packages } => write!(f, "APK 之间存在循环依赖: {}", packages.join(" -> ")),
+            PipelineError::InvalidPath { raw, normalized: Some(normalized), reason } if normalized != raw => {
+                write!(f, "路径无效: {}（输入 {:?}，解析为 {:?}）", reason, raw, normalized)
+            }
+            PipelineError::InvalidPath { raw, reason, .. } => write!(f, "路径无效: {}（{:?}）", reason, raw),
+        }
+    }
+}
+
+impl std::error::Error for PipelineError {}
+
+impl From<io::Error> for PipelineError {
+    fn from(e: io::Error) -> Self {
+        PipelineError::Io(e.to_string())
+    }
+}
+
+impl From<zip::result::ZipError> for PipelineError {
+    fn from(e: zip::result::ZipError) -> Self {
+        PipelineError::Io(e.to_string())
+    }
+}
+
+/// 签名所用的密钥库配置
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KeystoreConfig {
+    pub path: String,
+    #[serde(default = "default_password")]
+    pub store_password: String,
+    #[serde(default = "default_alias")]
+    pub alias: String,
+    #[serde(default = "default_password")]
+    pub key_password: String,
+}
+
+fn default_password() -> String {
+    "123456".to_string()
+}
+
+fn default_alias() -> String {
+    "my-alias".to_string()
+}
+
+impl KeystoreConfig {
+    /// 内置证书的默认口令与别名
+    pub fn with_defaults(path: &str) -> Self {
+        KeystoreConfig {
+            path: path.to_string(),
+            store_password: default_password(),
+            alias: default_alias(),
+            key_password: default_password(),
+        }
+    }
+
+    /// 本次处理使用的密钥：`alias_override` 替换默认别名，`key_passwords` 中与最终别名对应的来源提供密钥口令
+    pub fn resolve(path: &str, alias_override: Option<&str>, key_passwords: &[AliasPassword]) -> Result<Self, PipelineError> {
+        let mut keystore = KeystoreConfig::with_defaults(path);
+        if let Some(alias) = alias_override.map(str::trim).filter(|a| !a.is_empty()) {
+            keystore.alias = alias.to_string();
+        }
+        if let Some(entry) = key_passwords.iter().find(|p| p.alias == keystore.alias) {
+            keystore.key_password = entry.source.resolve(&format!("别名 {} 的密钥口令", keystore.alias))?;
+        }
+        Ok(keystore)
+    }
+
+    pub fn for_run(config: &crate::pipeline::ProcessConfig) -> Result<Self, PipelineError> {
+        Self::resolve(&config.keystore_path, config.key_alias_override.as_deref(), config.key_passwords.as_deref().unwrap_or_default())
+    }
+
+    /// apksigner sign 所需的密钥参数
+    pub fn signer_args(&self) -> Vec<String> {
+        vec![
+            "--ks".to_string(), self.path.clone(),
+            "--ks-pass".to_string(), format!("pass:{}", self.store_password),
+            "--ks-key-alias".to_string(), self.alias.clone(),
+            "--key-pass".to_string(), format!("pass:{}", self.key_password),
+        ]
+    }
+}
+
+/// 口令 / PIN 的来源，不支持把明文写进配置。用于密钥库中各别名的密钥口令与 PKCS#11 令牌的 PIN
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+#[serde(tag = "type")]
+pub enum SecretSource {
+    /// 从环境变量读取
+    Env { var: String },
+    /// 从文件读取（首行）
+    File { path: String },
+}
+
+impl SecretSource {
+    /// 读取口令；`what` 用于错误信息，如“令牌 PIN”
+    pub fn resolve(&self, what: &str) -> Result<String, PipelineError> {
+        let secret = match self {
+            SecretSource::Env { var } => {
+                std::env::var(var).map_err(|_| PipelineError::InvalidInput(format!("环境变量 {} 未设置，无法取得{}", var, what)))?
+            }
+            SecretSource::File { path } => {
+                let path = input_path::arg(path, Expect::File)?;
+                fs::read_to_string(&path)?.lines().next().unwrap_or_default().to_string()
+            }
+        };
+        if secret.is_empty() {
+            return Err(PipelineError::InvalidInput(format!("{}为空", what)));
+        }
+        Ok(secret)
+    }
+}
+
+/// 某个别名的密钥口令来源
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct AliasPassword {
+    pub alias: String,
+    pub source: SecretSource,
+}
+
+/// 实际签名使用的身份。同一密钥库可按别名签出不同身份，记录中需保存别名与证书指纹
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct SigningIdentity {
+    pub alias: String,
+    pub cert_sha256: Option<String>,
+}
+
+/// 签名使用的密钥：密钥库文件或 PKCS#11 令牌
+pub enum SigningKey {
+    Keystore(KeystoreConfig),
+    Pkcs11(crate::pkcs11::Pkcs11Session),
+}
+
+impl SigningKey {
+    /// 配置了 PKCS#11 令牌时打开令牌，否则使用密钥库
+    pub fn open(pkcs11: Option<&crate::pkcs11::Pkcs11Profile>, keystore: &KeystoreConfig) -> Result<Self, PipelineError> {
+        match pkcs11 {
+            Some(profile) => Ok(SigningKey::Pkcs11(crate::pkcs11::Pkcs11Session::open(profile)?)),
+            None => Ok(SigningKey::Keystore(keystore.clone())),
+        }
+    }
+
+    /// 为 apksigner sign 加上密钥参数
+    pub fn apply(&self, cmd: &mut Command) {
+        match self {
+            SigningKey::Keystore(keystore) => {
+                cmd.args(keystore.signer_args());
+            }
+            SigningKey::Pkcs11(session) => session.apply_signer_args(cmd),
+        }
+    }
+
+    /// 签名失败信息；令牌失败时附带原因说明
+    pub fn failure_message(&self, stderr: &str) -> String {
+        match self {
+            SigningKey::Keystore(_) => stderr.to_string(),
+            SigningKey::Pkcs11(_) => crate::pkcs11::failure_message(stderr),
+        }
+    }
+}
+
+/// APK 签名方案校验结果
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct SignatureInfo {
+    pub v1: bool,
+    pub v2: bool,
+    pub v3: bool,
+    pub signer_sha256: Option<String>,
+}
+
+impl SignatureInfo {
+    /// 仅有 V1（JAR）签名
+    pub fn is_v1_only(&self) -> bool {
+        self.v1 && !self.v2 && !self.v3
+    }
+}
+
+/// 解析 `apksigner verify --verbose --print-certs` 的输出
+pub fn parse_verify_output(stdout: &str) -> SignatureInfo {
+    let mut info = SignatureInfo::default();
+    for line in stdout.lines() {
+        let line = line.trim();
+        let enabled = line.ends_with(": true");
+        if line.starts_with("Verified using v1 scheme") {
+            info.v1 = enabled;
+        } else if line.starts_with("Verified using v2 scheme") {
+            info.v2 = enabled;
+        } else if line.starts_with("Verified using v3 scheme") {
+            info.v3 = enabled;
+        } else if let Some(rest) = line.strip_prefix("Signer #1 certificate SHA-256 digest:") {
+            info.signer_sha256 = Some(rest.trim().to_string());
+        }
+    }
+    info
+}
+
+/// 调用 apksigner 校验 APK 签名
+pub fn verify_signature(apk_path: &Path, java_path: &str, apksigner_path: &str) -> Result<SignatureInfo, PipelineError> {
+    let output = Command::new(java_path)
+        .args(["-jar", apksigner_path, "verify", "--verbose", "--print-certs"])
+        .arg(apk_path)
+        .output()
+        .map_err(|e| PipelineError::Tool { step: "verify".to_string(), message: e.to_string() })?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(PipelineError::Tool { step: "verify".to_string(), message: format!("{} {}", stderr, stdout) });
+    }
+    Ok(parse_verify_output(&stdout))
+}
+
+/// 是否为 JAR 签名相关的 META-INF 条目
+pub fn is_signature_entry(name: &str) -> bool {
+    let Some(file) = name.strip_prefix("META-INF/") else {
+        return false;
+    };
+    if file.contains('/') {
+        return false;
+    }
+    let upper = file.to_ascii_uppercase();
+    upper == "MANIFEST.MF"
+        || upper.ends_with(".SF")
+        || upper.ends_with(".RSA")
+        || upper.ends_with(".DSA")
+        || upper.ends_with(".EC")
+}
+
+/// 重建 ZIP，去掉旧的 JAR 签名文件，其余条目原样拷贝
+pub fn strip_v1_signature(src: &Path, dest: &Path) -> Result<usize, PipelineError> {
+    let mut archive = zip::ZipArchive::new(fs::File::open(src)?)?;
+    let mut writer = zip::ZipWriter::new(fs::File::create(dest)?);
+    let mut removed = 0;
+
+    for i in 0..archive.len() {
+        let entry = archive.by_index_raw(i)?;
+        if is_signature_entry(entry.name()) {
+            removed += 1;
+            continue;
+        }
+        writer.raw_copy_file(entry)?;
+    }
+    writer.finish()?;
+    Ok(removed)
+}
+
+/// 从 apksigner 所在目录推断 zipalign 路径（内置工具目录）
+fn sibling_zipalign(apksigner_path: &str) -> PathBuf {
+    let dir = Path::new(apksigner_path).parent().unwrap_or(Path::new("."));
+    #[cfg(target_os = "windows")]
+    return dir.join("zipalign.exe");
+    #[cfg(not(target_os = "windows"))]
+    return dir.join("zipalign");
+}
+
+/// 升级签名时 `apksigner sign` 的参数（不含输入文件）：同时启用 V1、V2、V3
+fn upgrade_sign_args(keystore: &KeystoreConfig, output_path: &str) -> Vec<String> {
+    let mut args = keystore.signer_args();
+    for scheme in ["--v1-signing-enabled", "--v2-signing-enabled", "--v3-signing-enabled"] {
+        args.extend([scheme.to_string(), "true".to_string()]);
+    }
+    args.extend(["--out".to_string(), output_path.to_string()]);
+    args
+}
+
+/// 将仅 V1 签名的旧 APK 升级为 V2/V3 签名（不反编译，只重做签名信息）
+#[tauri::command]
+pub async fn upgrade_signing_scheme(
+    pool: tauri::State<'_, IoPool>,
+    apk_path: String,
+    output_path: String,
+    keystore: KeystoreConfig,
+    java_path: String,
+    apksigner_path: String,
+) -> Result<SignatureInfo, PipelineError> {
+    let apk_path = input_path::arg(&apk_path, Expect::File)?;
+    let output_path = input_path::arg(&output_path, Expect::Any)?;
+    let java_path = input_path::tool_arg(&java_path)?;
+    let apksigner_path = input_path::tool_arg(&apksigner_path)?;
+    let keystore = KeystoreConfig { path: input_path::arg(&keystore.path, Expect::File)?, ..keystore };
+    // 去签名重写整个 ZIP，与对齐、签名一起放到 IO 线程池
+    pool.run(Priority::Background, "upgrade_signing_scheme", move || upgrade(&apk_path, &output_path, &keystore, &java_path, &apksigner_path))
+        .await
+        .map_err(PipelineError::Io)?
+}
+
+fn upgrade(
+    apk_path: &str,
+    output_path: &str,
+    keystore: &KeystoreConfig,
+    java_path: &str,
+    apksigner_path: &str,
+) -> Result<SignatureInfo, PipelineError> {
+    let before = verify_signature(Path::new(apk_path), java_path, apksigner_path)?;
+    if !before.is_v1_only() {
+        return Err(PipelineError::InvalidInput("该 APK 不是仅 V1 签名，无需升级".to_string()));
+    }
+
+    let tmp = tempfile::tempdir()?;
+    let stripped = tmp.path().join("stripped.apk");
+    let aligned = tmp.path().join("aligned.apk");
+    strip_v1_signature(Path::new(apk_path), &stripped)?;
+
+    let zipalign = sibling_zipalign(apksigner_path);
+    let align = Command::new(&zipalign)
+        .args(["-f", "-p", "4"])
+        .arg(&stripped)
+        .arg(&aligned)
+        .output()
+        .map_err(|e| PipelineError::Tool { step: "zipalign".to_string(), message: e.to_string() })?;
+    if !align.status.success() {
+        return Err(PipelineError::Tool {
+            step: "zipalign".to_string(),
+            message: String::from_utf8_lossy(&align.stderr).to_string(),
+        });
+    }
+
+    let sign = Command::new(java_path)
+        .args(["-jar", apksigner_path, "sign"])
+        .args(upgrade_sign_args(keystore, output_path))
+        .arg(&aligned)
+        .output()
+        .map_err(|e| PipelineError::Tool { step: "sign".to_string(), message: e.to_string() })?;
+    if !sign.status.success() {
+        return Err(PipelineError::Tool {
+            step: "sign".to_string(),
+            message: String::from_utf8_lossy(&sign.stderr).to_string(),
+        });
+    }
+
+    let input_size = fs::metadata(apk_path)?.len();
+    crate::entries::verify_rebuilt_apk(Path::new(output_path), input_size, crate::entries::DEFAULT_MIN_OUTPUT_RATIO)
+        .map_err(|message| PipelineError::Tool { step: "rebuild_verify".to_string(), message })?;
+
+    let after = verify_signature(Path::new(output_path), java_path, apksigner_path)?;
+    if !(after.v2 || after.v3) {
+        return Err(PipelineError::Tool { step: "verify".to_string(), message: "重新签名后仍未检测到 V2/V3 签名".to_string() });
+    }
+    Ok(after)
+}
+
+/// 识别签名失败是否由密钥库兼容性问题引起，返回处理建议
+pub fn diagnose_keystore_error(stderr: &str) -> Option<String> {
+    crate::diagnostics::KEYSTORE_ERROR_SIGNATURES
+        .iter()
+        .find(|(pattern, _)| stderr.contains(pattern))
+        .map(|(_, hint)| format!("{}。可使用“转换密钥库”将其重新导出为 PKCS12 格式，或在处理时开启自动转换", hint))
+}
+
+/// 根据 java 路径推断同目录下的 keytool，找不到时使用 PATH 中的 keytool
+pub fn keytool_path(java_path: Option<&str>) -> PathBuf {
+    let name = if cfg!(target_os = "windows") { "keytool.exe" } else { "keytool" };
+    java_path
+        .and_then(|java| Path::new(java).parent().map(|dir| dir.join(name)))
+        .filter(|p| p.exists())
+        .unwrap_or_else(|| PathBuf::from(name))
+}
+
+/// 读取密钥库中证书的 SHA-256 指纹；未指定别名时取第一个
+pub fn keystore_fingerprint(keytool: &Path, keystore: &str, password: &str, alias: Option<&str>) -> Option<String> {
+    let mut cmd = Command::new(keytool);
+    cmd.args(["-list", "-v", "-keystore", keystore, "-storepass", password]);
+    if let Some(alias) = alias {
+        cmd.args(["-alias", alias]);
+    }
+    let output = cmd.output().ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("SHA256:").map(|f| f.trim().to_string()))
+}
+
+/// 密钥库中的一个证书
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct CertificateEntry {
+    pub alias: String,
+    pub sha256: Option<String>,
+}
+
+/// 解析 `keytool -list -v` 的输出
+pub fn parse_keytool_list(stdout: &str) -> Vec<CertificateEntry> {
+    let mut entries: Vec<CertificateEntry> = Vec::new();
+    for line in stdout.lines().map(str::trim) {
+        if let Some(alias) = line.strip_prefix("Alias name:") {
+            entries.push(CertificateEntry { alias: alias.trim().to_string(), sha256: None });
+        } else if let Some(digest) = line.strip_prefix("SHA256:") {
+            if let Some(entry) = entries.last_mut().filter(|e| e.sha256.is_none()) {
+                entry.sha256 = Some(digest.trim().to_string());
+            }
+        }
+    }
+    entries
+}
+
+/// 列出密钥库文件中的证书，用于选择签名别名
+#[tauri::command]
+pub fn list_keystore_certificates(path: String, store_password: String, java_path: Option<String>) -> Result<Vec<CertificateEntry>, PipelineError> {
+    let path = input_path::arg(&path, Expect::File)?;
+    let java_path = java_path.as_deref().map(input_path::tool_arg).transpose()?;
+    let output = Command::new(keytool_path(java_path.as_deref()))
+        .args(["-list", "-v", "-keystore", &path, "-storepass", &store_password])
+        .output()
+        .map_err(|e| PipelineError::Tool { step: "keytool".to_string(), message: e.to_string() })?;
+    if !output.status.success() {
+        return Err(PipelineError::Tool {
+            step: "keytool".to_string(),
+            message: format!("{} {}", String::from_utf8_lossy(&output.stderr), String::from_utf8_lossy(&output.stdout)),
+        });
+    }
+    Ok(parse_keytool_list(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// 签名前确认别名存在于密钥库中，且密钥口令能解锁其私钥（用 `keytool -certreq` 试读私钥），
+/// 避免到签名步骤才失败。返回别名与证书指纹
+pub fn validate_key(java_path: Option<&str>, keystore: &KeystoreConfig) -> Result<SigningIdentity, PipelineError> {
+    let keytool = keytool_path(java_path);
+    let tool_error = |e: io::Error| PipelineError::Tool { step: "keytool".to_string(), message: e.to_string() };
+    let list = Command::new(&keytool)
+        .args(["-list", "-v", "-keystore", &keystore.path, "-storepass", &keystore.store_password])
+        .output()
+        .map_err(tool_error)?;
+    if !list.status.success() {
+        return Err(PipelineError::InvalidInput(format!(
+            "无法打开密钥库 {}: {}",
+            keystore.path,
+            String::from_utf8_lossy(&list.stdout).trim()
+        )));
+    }
+    let entries = parse_keytool_list(&String::from_utf8_lossy(&list.stdout));
+    let Some(entry) = entries.iter().find(|e| e.alias.eq_ignore_ascii_case(&keystore.alias)) else {
+        let available: Vec<&str> = entries.iter().map(|e| e.alias.as_str()).collect();
+        return Err(PipelineError::InvalidInput(format!(
+            "密钥库 {} 中没有别名 {}（可用: {}）",
+            keystore.path,
+            keystore.alias,
+            available.join("、")
+        )));
+    };
+    let unlock = Command::new(&keytool)
+        .args(["-certreq", "-keystore", &keystore.path, "-storepass", &keystore.store_password])
+        .args(["-alias", &keystore.alias, "-keypass", &keystore.key_password])
+        .output()
+        .map_err(tool_error)?;
+    if !unlock.status.success() {
+        return Err(PipelineError::InvalidInput(format!(
+            "别名 {} 的密钥口令无法解锁私钥: {}",
+            keystore.alias,
+            String::from_utf8_lossy(&unlock.stdout).trim()
+        )));
+    }
+    Ok(SigningIdentity { alias: entry.alias.clone(), cert_sha256: entry.sha256.clone() })
+}
+
+/// 本次处理的签名身份。令牌签名时为 `None`；指定了别名时校验失败返回错误，
+/// 否则校验失败（如找不到 keytool）只推送进度提示，沿用签名步骤的报错
+pub fn identity_for_run(app: &tauri::AppHandle, config: &crate::pipeline::ProcessConfig) -> Result<Option<SigningIdentity>, PipelineError> {
+    if config.pkcs11_signing.is_some() {
+        return Ok(None);
+    }
+    match KeystoreConfig::for_run(config).and_then(|keystore| validate_key(Some(&config.java_path), &keystore)) {
+        Ok(identity) => Ok(Some(identity)),
+        Err(e) if config.key_alias_override.is_some() => Err(e),
+        Err(e) => {
+            crate::emit_progress(app, "sign", format!("无法确认签名身份: {}", e));
+            Ok(None)
+        }
+    }
+}
+
+fn run_import_keystore(keytool: &Path, input: &str, output: &str, password: &str) -> Result<(), PipelineError> {
+    if Path::new(output).exists() {
+        return Err(PipelineError::InvalidInput(format!("目标文件 {} 已存在，不会覆盖", output)));
+    }
+    if Path::new(input) == Path::new(output) {
+        return Err(PipelineError::InvalidInput("输出路径不能与原密钥库相同".to_string()));
+    }
+    let result = Command::new(keytool)
+        .args([
+            "-importkeystore",
+            "-noprompt",
+            "-srckeystore", input,
+            "-srcstorepass", password,
+            "-destkeystore", output,
+            "-deststoretype", "PKCS12",
+            "-deststorepass", password,
+            "-destkeypass", password,
+        ])
+        .output()
+        .map_err(|e| PipelineError::Tool { step: "keytool".to_string(), message: e.to_string() })?;
+    if !result.status.success() {
+        return Err(PipelineError::Tool {
+            step: "keytool".to_string(),
+            message: format!("{} {}", String::from_utf8_lossy(&result.stderr), String::from_utf8_lossy(&result.stdout)),
+        });
+    }
+    Ok(())
+}
+
+/// 将旧格式密钥库转换到应用的签名配置目录，返回新密钥库路径。原文件保持不变
+pub fn convert_into_profiles(
+    profiles_dir: &Path,
+    java_path: Option<&str>,
+    input: &str,
+    password: &str,
+) -> Result<(PathBuf, Option<String>, Option<String>), PipelineError> {
+    fs::create_dir_all(profiles_dir)?;
+    let stem = Path::new(input).file_stem().and_then(|s| s.to_str()).unwrap_or("keystore");
+    let mut output = profiles_dir.join(format!("{}_converted.p12", stem));
+    let mut n = 1;
+    while output.exists() {
+        output = profiles_dir.join(format!("{}_converted_{}.p12", stem, n));
+        n += 1;
+    }
+
+    let keytool = keytool_path(java_path);
+    let original_fp = keystore_fingerprint(&keytool, input, password, None);
+    run_import_keystore(&keytool, input, &output.to_string_lossy(), password)?;
+    let converted_fp = keystore_fingerprint(&keytool, &output.to_string_lossy(), password, None);
+    Ok((output, original_fp, converted_fp))
+}
+
+/// 使用 keytool 将密钥库重新导出为 PKCS12
+#[tauri::command]
+pub fn convert_keystore(input: String, output: String, password: String, java_path: Option<String>) -> Result<String, PipelineError> {
+    let input = input_path::arg(&input, Expect::File)?;
+    let output = input_path::arg(&output, Expect::Any)?;
+    let java_path = java_path.as_deref().map(input_path::tool_arg).transpose()?;
+    let keytool = keytool_path(java_path.as_deref());
+    run_import_keystore(&keytool, &input, &output, &password)?;
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const V1_ONLY_APK: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/v1_only.apk");
+
+    fn entry_names(apk: &Path) -> Vec<String> {
+        let archive = zip::ZipArchive::new(fs::File::open(apk).unwrap()).unwrap();
+        archive.file_names().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn v1_signature_entries_are_stripped_from_the_fixture() {
+        let dir = tempfile::tempdir().unwrap();
+        let stripped = dir.path().join("stripped.apk");
+        assert_eq!(strip_v1_signature(Path::new(V1_ONLY_APK), &stripped).unwrap(), 3);
+
+        let mut names = entry_names(&stripped);
+        names.sort();
+        assert_eq!(names, ["AndroidManifest.xml", "classes.dex"]);
+        let input_size = fs::metadata(V1_ONLY_APK).unwrap().len();
+        crate::entries::verify_rebuilt_apk(&stripped, input_size, crate::entries::DEFAULT_MIN_OUTPUT_RATIO).unwrap();
+    }
+
+    #[test]
+    fn apksigner_output_for_a_v1_only_apk() {
+        let stdout = "Verifies\n\
+Verified using v1 scheme (JAR signing): true\n\
+Verified using v2 scheme (APK Signature Scheme v2): false\n\
+Verified using v3 scheme (APK Signature Scheme v3): false\n\
+Verified using v4 scheme (APK Signature Scheme v4): false\n\
+Number of signers: 1\n\
+Signer #1 certificate SHA-256 digest: 0a1b2c\n";
+        let info = parse_verify_output(stdout);
+        assert!(info.is_v1_only());
+        assert_eq!(info.signer_sha256.as_deref(), Some("0a1b2c"));
+
+        let upgraded = parse_verify_output(&stdout.replace("v2 scheme (APK Signature Scheme v2): false", "v2 scheme (APK Signature Scheme v2): true"));
+        assert!(!upgraded.is_v1_only());
+    }
+
+    #[test]
+    fn only_top_level_jar_signature_files_are_signature_entries() {
+        for name in ["META-INF/MANIFEST.MF", "META-INF/CERT.SF", "META-INF/cert.rsa", "META-INF/KEY.EC", "META-INF/A.DSA"] {
+            assert!(is_signature_entry(name), "{}", name);
+        }
+        for name in ["META-INF/services/CERT.SF", "META-INF/androidx.core_core.version", "assets/CERT.RSA", "classes.dex"] {
+            assert!(!is_signature_entry(name), "{}", name);
+        }
+    }
+
+    fn file_secret(dir: &Path, name: &str, content: &str) -> SecretSource {
+        let path = dir.join(name);
+        fs::write(&path, content).unwrap();
+        SecretSource::File { path: path.to_string_lossy().to_string() }
+    }
+
+    #[test]
+    fn key_password_is_resolved_for_the_selected_alias() {
+        let dir = tempfile::tempdir().unwrap();
+        let passwords = [
+            AliasPassword { alias: "release".to_string(), source: file_secret(dir.path(), "release.txt", "release-secret\nignored\n") },
+            AliasPassword { alias: "upload".to_string(), source: file_secret(dir.path(), "upload.txt", "upload-secret") },
+        ];
+        let keystore = KeystoreConfig::resolve("/keys/app.jks", Some(" upload "), &passwords).unwrap();
+        assert_eq!(keystore.alias, "upload");
+        assert_eq!(keystore.key_password, "upload-secret");
+        assert_eq!(keystore.store_password, default_password());
+
+        let keystore = KeystoreConfig::resolve("/keys/app.jks", Some("release"), &passwords).unwrap();
+        assert_eq!(keystore.key_password, "release-secret");
+    }
+
+    #[test]
+    fn alias_without_a_password_entry_keeps_the_default() {
+        let dir = tempfile::tempdir().unwrap();
+        let passwords = [AliasPassword { alias: "release".to_string(), source: file_secret(dir.path(), "release.txt", "release-secret") }];
+        let keystore = KeystoreConfig::resolve("/keys/app.jks", None, &passwords).unwrap();
+        assert_eq!(keystore.alias, default_alias());
+        assert_eq!(keystore.key_password, default_password());
+        // 空白的别名覆盖视为未指定
+        assert_eq!(KeystoreConfig::resolve("/keys/app.jks", Some("  "), &passwords).unwrap().alias, default_alias());
+    }
+
+    #[test]
+    fn unreadable_or_empty_secrets_name_the_alias() {
+        let dir = tempfile::tempdir().unwrap();
+        let passwords = [
+            AliasPassword { alias: "empty".to_string(), source: file_secret(dir.path(), "empty.txt", "") },
+            AliasPassword { alias: "env".to_string(), source: SecretSource::Env { var: "APK_DISGUISE_TEST_UNSET_KEY_PASSWORD".to_string() } },
+        ];
+        let error = KeystoreConfig::resolve("/keys/app.jks", Some("empty"), &passwords).unwrap_err().to_string();
+        assert!(error.contains("别名 empty 的密钥口令为空"), "{}", error);
+        let error = KeystoreConfig::resolve("/keys/app.jks", Some("env"), &passwords).unwrap_err().to_string();
+        assert!(error.contains("APK_DISGUISE_TEST_UNSET_KEY_PASSWORD"), "{}", error);
+        assert!(error.contains("别名 env 的密钥口令"), "{}", error);
+    }
+
+    #[test]
+    fn secret_source_keeps_its_tagged_format() {
+        let source: SecretSource = serde_json::from_str(r#"{"type":"Env","var":"TOKEN_PIN"}"#).unwrap();
+        assert_eq!(source, SecretSource::Env { var: "TOKEN_PIN".to_string() });
+        let error = source.resolve("令牌 PIN").unwrap_err().to_string();
+        assert!(error.contains("无法取得令牌 PIN"), "{}", error);
+    }
+
+    #[test]
+    fn detects_signature_schemes() {
+        let stdout = "Verifies\n\
+Verified using v1 scheme (JAR signing): true\n\
+Verified using v2 scheme (APK Signature Scheme v2): true\n\
+Verified using v3 scheme (APK Signature Scheme v3): true\n\
+Number of signers: 1\n\
+Signer #1 certificate SHA-256 digest: 5d0539e7\n";
+        let info = parse_verify_output(stdout);
+        assert!(info.v1 && info.v2 && info.v3);
+        assert!(!info.is_v1_only());
+        assert_eq!(info.signer_sha256.as_deref(), Some("5d0539e7"));
+
+        let v3_only = SignatureInfo { v3: true, ..Default::default() };
+        assert!(!v3_only.is_v1_only());
+        assert!(!SignatureInfo::default().is_v1_only());
+        let unsigned = parse_verify_output("DOES NOT VERIFY\nERROR: JAR_SIG_NO_SIGNATURES\n");
+        assert!(!unsigned.v1 && !unsigned.v2 && !unsigned.v3 && unsigned.signer_sha256.is_none());
+    }
+
+    #[test]
+    fn upgrade_signs_with_every_scheme() {
+        let config = KeystoreConfig {
+            path: "/keys/release.jks".to_string(),
+            store_password: "store".to_string(),
+            alias: "release".to_string(),
+            key_password: "key".to_string(),
+        };
+        assert_eq!(
+            upgrade_sign_args(&config, "/out/upgraded.apk"),
+            [
+                "--ks", "/keys/release.jks", "--ks-pass", "pass:store", "--ks-key-alias", "release", "--key-pass", "pass:key",
+                "--v1-signing-enabled", "true", "--v2-signing-enabled", "true", "--v3-signing-enabled", "true",
+                "--out", "/out/upgraded.apk",
+            ]
+        );
+        let zipalign = sibling_zipalign("/sdk/build-tools/34.0.0/lib/apksigner.jar");
+        assert_eq!(zipalign.parent(), Some(Path::new("/sdk/build-tools/34.0.0/lib")));
+        assert!(zipalign.file_stem().is_some_and(|stem| stem == "zipalign"));
+    }
+
+    /// 需要真实的 apksigner：`APKSIGNER_JAR=/path/to/build-tools/lib/apksigner.jar`，同目录下需有 zipalign；
+    /// 未设置时跳过
+    #[test]
+    fn upgrades_the_v1_fixture_to_v2() {
+        let Some(apksigner) = std::env::var("APKSIGNER_JAR").ok().filter(|jar| sibling_zipalign(jar).is_file()) else {
+            eprintln!("未设置 APKSIGNER_JAR 或缺少 zipalign，跳过签名升级");
+            return;
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let keystore = dir.path().join("upgrade.p12");
+        let status = Command::new(keytool_path(None))
+            .args(["-genkeypair", "-storetype", "PKCS12", "-keyalg", "RSA", "-keysize", "2048", "-validity", "1"])
+            .args(["-alias", "upgrade", "-storepass", "upgrade", "-keypass", "upgrade", "-dname", "CN=upgrade", "-keystore"])
+            .arg(&keystore)
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let config = KeystoreConfig {
+            path: keystore.to_string_lossy().to_string(),
+            store_password: "upgrade".to_string(),
+            alias: "upgrade".to_string(),
+            key_password: "upgrade".to_string(),
+        };
+        let output = dir.path().join("upgraded.apk");
+        let info = upgrade(V1_ONLY_APK, &output.to_string_lossy(), &config, "java", &apksigner).unwrap();
+        assert!(info.v2 || info.v3);
+        assert!(!entry_names(&output).iter().any(|name| name == "META-INF/FIXTURE.SF"));
+    }
+}
@@ -0,0 +1,181 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 源文件位置检测结果
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SourceCheck {
+    /// 云盘占位文件（OneDrive/Dropbox 等按需下载，内容尚未完全同步到本地）
+    pub is_placeholder: bool,
+    /// 文件被标记为脱机
+    pub is_offline: bool,
+    /// 位于网络路径（UNC 或映射的网络驱动器）
+    pub is_network: bool,
+    /// 源文件所在目录不可写
+    pub is_read_only_dir: bool,
+    /// 原始文件属性（仅 Windows）
+    pub attributes: Option<u32>,
+}
+
+impl SourceCheck {
+    /// 是否需要先把文件复制到工作目录再处理
+    pub fn needs_local_copy(&self) -> bool {
+        self.is_placeholder || self.is_offline || self.is_network
+    }
+
+    /// 写入运行日志的一行摘要
+    pub fn summary(&self) -> String {
+        format!(
+            "源文件检测: 云占位={} 脱机={} 网络路径={} 目录只读={} 属性={}",
+            self.is_placeholder,
+            self.is_offline,
+            self.is_network,
+            self.is_read_only_dir,
+            self.attributes.map(|a| format!("0x{:08X}", a)).unwrap_or_else(|| "-".to_string()),
+        )
+    }
+}
+
+#[cfg(windows)]
+const FILE_ATTRIBUTE_OFFLINE: u32 = 0x0000_1000;
+#[cfg(windows)]
+const FILE_ATTRIBUTE_RECALL_ON_OPEN: u32 = 0x0004_0000;
+#[cfg(windows)]
+const FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS: u32 = 0x0040_0000;
+
+/// 根据 Windows 文件属性判断是否为云占位文件 / 脱机文件，返回 (占位, 脱机)
+#[cfg(windows)]
+pub fn classify_attributes(attributes: u32) -> (bool, bool) {
+    let placeholder = attributes & (FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS | FILE_ATTRIBUTE_RECALL_ON_OPEN) != 0;
+    let offline = attributes & FILE_ATTRIBUTE_OFFLINE != 0;
+    (placeholder, offline)
+}
+
+#[cfg(windows)]
+fn is_network_path(path: &Path) -> bool {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::GetDriveTypeW;
+
+    let raw = path.to_string_lossy();
+    if raw.starts_with(r"\\") && !raw.starts_with(r"\\?\") {
+        return true;
+    }
+    if raw.starts_with(r"\\?\UNC\") {
+        return true;
+    }
+
+    // 映射的网络驱动器，如 Z:\
+    let trimmed = raw.trim_start_matches(r"\\?\");
+    let mut chars = trimmed.chars();
+    if let (Some(letter), Some(':')) = (chars.next(), chars.next()) {
+        let root: Vec<u16> = std::ffi::OsStr::new(&format!("{}:\\", letter))
+            .encode_wide()
+            .chain(std::iter::once(0))
+            .collect();
+        // DRIVE_REMOTE = 4
+        return unsafe { GetDriveTypeW(root.as_ptr()) } == 4;
+    }
+    false
+}
+
+#[cfg(not(windows))]
+fn is_network_path(_path: &Path) -> bool {
+    false
+}
+
+/// 检测源 APK 是否为云占位文件、脱机文件或位于网络路径
+pub fn inspect_source(path: &Path) -> Result<SourceCheck, String> {
+    let meta = fs::metadata(path).map_err(|e| format!("读取源文件信息失败: {}", e))?;
+    let mut check = SourceCheck {
+        is_network: is_network_path(path),
+        ..Default::default()
+    };
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::MetadataExt;
+        let attributes = meta.file_attributes();
+        let (placeholder, offline) = classify_attributes(attributes);
+        check.is_placeholder = placeholder;
+        check.is_offline = offline;
+        check.attributes = Some(attributes);
+    }
+    #[cfg(not(windows))]
+    let _ = &meta;
+
+    check.is_read_only_dir = path.parent().is_some_and(is_read_only_dir);
+
+    Ok(check)
+}
+
+fn is_read_only_dir(dir: &Path) -> bool {
+    fs::metadata(dir).map(|m| m.permissions().readonly()).unwrap_or(false)
+}
+
+/// 最终输出所在目录（即源文件目录）只读时改放到 `fallback_dir`，返回实际路径；改放时附带说明
+pub fn output_location(output: PathBuf, fallback_dir: &Path) -> (PathBuf, Option<String>) {
+    let Some(file_name) = output.file_name() else {
+        return (output, None);
+    };
+    if !output.parent().is_some_and(is_read_only_dir) {
+        return (output, None);
+    }
+    let moved = fallback_dir.join(file_name);
+    let note = format!("源文件目录只读，输出改为 {}", moved.display());
+    (moved, Some(note))
+}
+
+/// 将源文件复制到工作区，触发云盘文件的完整下载
+pub fn hydrate_to(src: &Path, dest_dir: &Path) -> Result<PathBuf, String> {
+    fs::create_dir_all(dest_dir).map_err(|e| format!("创建工作目录失败: {}", e))?;
+    let file_name = src.file_name().ok_or_else(|| "无效的源文件路径".to_string())?;
+    let dest = dest_dir.join(file_name);
+    fs::copy(src, &dest).map_err(|e| format!("复制源文件到本地失败（文件可能未完全同步）: {}", e))?;
+    // macOS 上 fs::copy 会连同扩展属性一起复制
+    crate::quarantine::strip_xattrs(&dest);
+    Ok(dest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(windows)]
+    #[test]
+    fn classifies_cloud_and_offline_attributes() {
+        const FILE_ATTRIBUTE_ARCHIVE: u32 = 0x0000_0020;
+        assert_eq!(classify_attributes(FILE_ATTRIBUTE_ARCHIVE), (false, false));
+        assert_eq!(classify_attributes(FILE_ATTRIBUTE_ARCHIVE | FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS), (true, false));
+        assert_eq!(classify_attributes(FILE_ATTRIBUTE_RECALL_ON_OPEN), (true, false));
+        assert_eq!(classify_attributes(FILE_ATTRIBUTE_OFFLINE), (false, true));
+        assert_eq!(classify_attributes(FILE_ATTRIBUTE_OFFLINE | FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS), (true, true));
+    }
+
+    #[test]
+    fn output_stays_next_to_a_writable_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("app_fixed.apk");
+        let (location, note) = output_location(output.clone(), &dir.path().join("fallback"));
+        assert_eq!(location, output);
+        assert!(note.is_none());
+    }
+
+    #[test]
+    fn output_moves_out_of_a_read_only_source_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let source_dir = dir.path().join("share");
+        fs::create_dir(&source_dir).unwrap();
+        let mut permissions = fs::metadata(&source_dir).unwrap().permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(&source_dir, permissions.clone()).unwrap();
+
+        let fallback = dir.path().join("output");
+        let (location, note) = output_location(source_dir.join("app_fixed.apk"), &fallback);
+        assert_eq!(location, fallback.join("app_fixed.apk"));
+        assert!(note.unwrap().contains("只读"));
+
+        #[allow(clippy::permissions_set_readonly_false)]
+        permissions.set_readonly(false);
+        fs::set_permissions(&source_dir, permissions).unwrap();
+    }
+}
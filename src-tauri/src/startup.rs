@@ -0,0 +1,211 @@
+use serde::{Deserialize, Serialize};
+use std::thread;
+use std::time::Duration;
+
+use crate::device_cache::DeviceCache;
+use crate::{adb, capabilities, pm};
+use crate::signing::PipelineError;
+
+/// 单次测量的最大次数
+const MAX_ITERATIONS: u8 = 20;
+/// 强制停止后等待进程完全退出的时间
+const SETTLE_DELAY: Duration = Duration::from_millis(500);
+
+/// 冷启动耗时统计
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct StartupBenchmark {
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub median_ms: u64,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub min_ms: u64,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub max_ms: u64,
+    pub std_dev_ms: f64,
+}
+
+fn tool_error(step: &str, message: String) -> PipelineError {
+    PipelineError::Tool { step: step.to_string(), message }
+}
+
+/// 从 `am start-activity -W` 的输出中取 `TotalTime:`
+pub fn parse_total_time(output: &str) -> Option<u64> {
+    output
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("TotalTime:"))
+        .and_then(|value| value.trim().parse().ok())
+}
+
+/// 从 `dumpsys package <包名>` 的 Activity Resolver Table 中取 `android.intent.action.MAIN` 下的第一个组件，
+/// 用于没有 `cmd package` 的旧设备
+pub fn parse_main_activity(dumpsys: &str, package_name: &str) -> Option<String> {
+    let prefix = format!("{}/", package_name);
+    let mut lines = dumpsys.lines().map(str::trim);
+    lines.find(|line| *line == "android.intent.action.MAIN:")?;
+    lines.next()?.split_whitespace().find(|token| token.starts_with(&prefix)).map(str::to_string)
+}
+
+/// 未指定 Activity 时查询启动器入口；指定的 Activity 不含 `/` 时补上包名
+fn component(app: &tauri::AppHandle, cache: &DeviceCache, device_id: &str, package_name: &str, activity: Option<String>) -> Result<String, PipelineError> {
+    match activity {
+        Some(activity) if activity.contains('/') => Ok(activity),
+        Some(activity) => Ok(format!("{}/{}", package_name, activity)),
+        None => {
+            let cmd_package = capabilities::for_device(cache, device_id).map_err(|e| tool_error("capabilities", e))?.cmd_package;
+            let method = if cmd_package { "cmd package resolve-activity" } else { "dumpsys package" };
+            crate::emit_progress(app, "startup", format!("{}: 使用 {} 查询启动器入口", device_id, method));
+            let resolved = if cmd_package {
+                let output = adb::shell(
+                    device_id,
+                    &["cmd", "package", "resolve-activity", "--brief", "-c", "android.intent.category.LAUNCHER", package_name],
+                )
+                .map_err(|e| tool_error("resolve-activity", e))?;
+                output.lines().map(str::trim).rfind(|line| line.contains('/')).map(str::to_string)
+            } else {
+                let output = adb::shell(device_id, &["dumpsys", "package", package_name]).map_err(|e| tool_error("dumpsys package", e))?;
+                parse_main_activity(&output, package_name)
+            };
+            resolved.ok_or_else(|| PipelineError::InvalidInput(format!("{} 没有可启动的 Activity", package_name)))
+        }
+    }
+}
+
+/// 对样本计算中位数、最小、最大值与标准差
+pub fn summarize(samples: &[u64]) -> StartupBenchmark {
+    let mut sorted = samples.to_vec();
+    sorted.sort_unstable();
+    let n = sorted.len();
+    let median_ms = match n {
+        0 => 0,
+        _ if n % 2 == 1 => sorted[n / 2],
+        _ => (sorted[n / 2 - 1] + sorted[n / 2]) / 2,
+    };
+    let mean = sorted.iter().sum::<u64>() as f64 / n.max(1) as f64;
+    let variance = sorted.iter().map(|v| (*v as f64 - mean).powi(2)).sum::<f64>() / n.max(1) as f64;
+    StartupBenchmark {
+        median_ms,
+        min_ms: sorted.first().copied().unwrap_or(0),
+        max_ms: sorted.last().copied().unwrap_or(0),
+        std_dev_ms: variance.sqrt(),
+    }
+}
+
+/// 多次冷启动应用并统计 `TotalTime`，用于确认注入的代码没有明显拖慢启动。次数上限为 20
+#[tauri::command]
+pub async fn measure_app_startup_time(
+    app: tauri::AppHandle,
+    cache: tauri::State<'_, DeviceCache>,
+    device_id: String,
+    package_name: String,
+    activity: Option<String>,
+    iterations: u8,
+) -> Result<StartupBenchmark, PipelineError> {
+    let iterations = iterations.clamp(1, MAX_ITERATIONS);
+    let component = component(&app, &cache, &device_id, &package_name, activity)?;
+
+    let mut samples = Vec::with_capacity(iterations as usize);
+    for _ in 0..iterations {
+        adb::shell(&device_id, &["am", "force-stop", &package_name]).map_err(|e| tool_error("force-stop", e))?;
+        thread::sleep(SETTLE_DELAY);
+        let output = adb::shell(&device_id, &["am", "start-activity", "-W", "-n", &component])
+            .map_err(|e| tool_error("am start-activity", e))?;
+        let total = parse_total_time(&output)
+            .ok_or_else(|| tool_error("am start-activity", format!("输出中没有 TotalTime: {}", output.trim())))?;
+        samples.push(total);
+    }
+    adb::shell(&device_id, &["am", "force-stop", &package_name]).map_err(|e| tool_error("force-stop", e))?;
+
+    Ok(summarize(&samples))
+}
+
+/// 检查 monkey 的输出：没有启动器 Activity 时输出 `No activities found to run, monkey aborted.`
+pub fn check_monkey_output(package_name: &str, output: &str) -> Result<(), PipelineError> {
+    if output.contains("No activities found") {
+        return Err(PipelineError::InvalidInput(format!(
+            "{} 没有可启动的 Activity，Manifest 中启动器的 intent-filter 可能在改写时被破坏",
+            package_name
+        )));
+    }
+    if output.contains("monkey aborted") || output.contains("Error:") {
+        return Err(tool_error("monkey", output.trim().to_string()));
+    }
+    Ok(())
+}
+
+/// 启动应用（通过 monkey 发送一次启动器 intent），可作为处理后的冒烟检查
+#[tauri::command]
+pub fn launch_app(device_id: String, package_name: String) -> Result<(), PipelineError> {
+    if !pm::is_package_name(&package_name) {
+        return Err(PipelineError::InvalidInput(format!("无效的包名: {}", package_name)));
+    }
+    let output = adb::command()
+        .args(["-s", &device_id, "shell", "monkey", "-p", &package_name, "-c", "android.intent.category.LAUNCHER", "1"])
+        .output()
+        .map_err(|e| tool_error("monkey", e.to_string()))?;
+    check_monkey_output(
+        &package_name,
+        &format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr)),
+    )
+}
+
+/// 强制停止应用
+#[tauri::command]
+pub fn force_stop_app(device_id: String, package_name: String) -> Result<(), PipelineError> {
+    if !pm::is_package_name(&package_name) {
+        return Err(PipelineError::InvalidInput(format!("无效的包名: {}", package_name)));
+    }
+    adb::shell(&device_id, &["am", "force-stop", &package_name]).map_err(|e| tool_error("force-stop", e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AM_START_W: &str = "Starting: Intent { act=android.intent.action.MAIN cat=[android.intent.category.LAUNCHER] cmp=com.corp.app/.MainActivity }\r\n\
+Status: ok\r\n\
+LaunchState: COLD\r\n\
+Activity: com.corp.app/.MainActivity\r\n\
+TotalTime: 812\r\n\
+WaitTime: 820\r\n\
+Complete\r\n";
+
+    const LEGACY_AM_START_W: &str = "Starting: Intent { cmp=com.corp.app/.MainActivity }\n\
+Status: ok\n\
+Activity: com.corp.app/.MainActivity\n\
+ThisTime: 640\n\
+TotalTime: 655\n\
+WaitTime: 700\n\
+Complete\n";
+
+    const DUMPSYS_PACKAGE: &str = "Activity Resolver Table:\n\
+  Non-Data Actions:\n\
+      android.intent.action.VIEW:\n\
+        5f1c2a0 com.corp.app/.DeepLinkActivity filter 9d2e1b1\n\
+      android.intent.action.MAIN:\n\
+        1a2b3c4 com.corp.app/.MainActivity filter 7e8f9a0\n\
+          Action: \"android.intent.action.MAIN\"\n\
+          Category: \"android.intent.category.LAUNCHER\"\n\
+\n\
+Packages:\n\
+  Package [com.corp.app] (b4c5d6e):\n";
+
+    #[test]
+    fn total_time_is_read_from_am_start() {
+        assert_eq!(parse_total_time(AM_START_W), Some(812));
+        assert_eq!(parse_total_time(LEGACY_AM_START_W), Some(655));
+    }
+
+    #[test]
+    fn failed_start_has_no_total_time() {
+        let output = "Starting: Intent { cmp=com.corp.app/.Missing }\nError type 3\nError: Activity class {com.corp.app/com.corp.app.Missing} does not exist.\n";
+        assert_eq!(parse_total_time(output), None);
+    }
+
+    #[test]
+    fn main_activity_is_read_from_dumpsys() {
+        assert_eq!(parse_main_activity(DUMPSYS_PACKAGE, "com.corp.app").as_deref(), Some("com.corp.app/.MainActivity"));
+        assert_eq!(parse_main_activity(DUMPSYS_PACKAGE, "com.other"), None);
+        assert_eq!(parse_main_activity("Packages:\n", "com.corp.app"), None);
+    }
+}
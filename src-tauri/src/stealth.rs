@@ -0,0 +1,81 @@
+//! 隐匿输出：不使用 `_fixed` 文件名，统一 ZIP 条目时间，去掉签名前残留的签名文件，
+//! 让输出不那么容易被认出是本工具处理过的。
+//!
+//! 以下痕迹无法去除，开启时会列入处理结果：
+//! - 签名证书本身（证书主体与指纹就是新的签名身份）；
+//! - apktool 回编译带来的资源重排（resources.arsc 的字符串池与条目顺序、res 目录下的文件名）；
+//! - apksigner 写入的 v1 签名文件中的 `Created-By`、`X-Android-APK-Signed`，它们受签名保护，改动会使签名失效；
+//! - v2/v3 的 APK Signing Block，其结构本身表明 APK 经过重新签名。
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::{axml, quarantine};
+use crate::signing::{self, PipelineError};
+
+/// 隐匿模式下无法去除的痕迹，与模块文档一致
+pub const RESIDUAL_TRACES: &[&str] = &[
+    "签名证书（主体与指纹）",
+    "apktool 回编译造成的资源重排（resources.arsc 字符串池与条目顺序）",
+    "apksigner 在 v1 签名文件中写入的 Created-By / X-Android-APK-Signed（受签名保护）",
+    "APK Signing Block（v2/v3 签名）",
+];
+
+/// 只保留字母、数字、点、下划线和连字符
+fn clean(text: &str) -> String {
+    text.chars().filter(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-')).collect()
+}
+
+/// 隐匿模式的输出文件名：新包名最后一段加版本号，如 `scanner_2.1.0.apk`
+pub fn output_file_name(new_package: &str, version_name: Option<&str>) -> String {
+    let last = new_package.rsplit('.').next().filter(|s| !s.is_empty()).unwrap_or("app");
+    match version_name.map(clean).filter(|v| !v.is_empty()) {
+        Some(version) => format!("{}_{}.apk", last, version),
+        None => format!("{}.apk", last),
+    }
+}
+
+/// 与源 APK 同目录的隐匿输出路径；版本号取自源 APK 的 Manifest，读取失败时省略。
+/// 与源文件同名时加序号，避免签名输出覆盖源文件
+pub fn output_path(source_apk: &Path, new_package: &str) -> PathBuf {
+    let version_name = axml::read_manifest_from_apk(source_apk)
+        .ok()
+        .and_then(|elements| elements.into_iter().find(|e| e.name == "manifest"))
+        .and_then(|manifest| manifest.attr("versionName").map(axml::AxmlValue::as_string));
+    let parent_dir = source_apk.parent().unwrap_or(Path::new("."));
+    let output = parent_dir.join(output_file_name(new_package, version_name.as_deref()));
+    if output.file_name() != source_apk.file_name() {
+        return output;
+    }
+    let stem = output.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    parent_dir.join(format!("{}_1.apk", stem))
+}
+
+/// 重写未签名的 APK：所有条目使用固定时间（1980-01-01），去掉残留的签名文件与 macOS 的 `._*` 文件。
+/// 压缩方式保持不变，输出需要重新对齐和签名。返回去掉的条目
+pub fn normalize_apk(src: &Path, dest: &Path) -> Result<Vec<String>, PipelineError> {
+    let mut archive = zip::ZipArchive::new(fs::File::open(src)?)?;
+    let mut writer = zip::ZipWriter::new(fs::File::create(dest)?);
+    let mut removed = Vec::new();
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        let name = entry.name().to_string();
+        if signing::is_signature_entry(&name) || quarantine::is_apple_double(&name) {
+            removed.push(name);
+            continue;
+        }
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(entry.compression())
+            .last_modified_time(zip::DateTime::default());
+        if entry.is_dir() {
+            writer.add_directory(name, options)?;
+            continue;
+        }
+        writer.start_file(name, options)?;
+        io::copy(&mut entry, &mut writer)?;
+    }
+    writer.finish()?;
+    Ok(removed)
+}
@@ -0,0 +1,422 @@
+//! 生成支持包：把环境信息、工具版本、去掉敏感信息的设置与某次任务的日志打包成 ZIP，便于随问题反馈附上。
+//! 路径中的用户名一律替换，设备序列号默认打码；支持包中不会包含密钥库或 APK 的内容。
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::io_pool::{IoPool, Priority};
+use crate::paths::AppPaths;
+use crate::{adb, incremental, tool_versions};
+
+/// 附带的审计日志行数
+const APP_LOG_LINES: usize = 200;
+/// 设置中这些键（不区分大小写，包含即可）的值会被替换
+const SECRET_KEY_MARKERS: &[&str] = &["pass", "secret", "token", "credential", "private", "signature"];
+const REDACTED: &str = "<redacted>";
+
+/// 生成的支持包
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct SupportBundle {
+    pub path: String,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub size: u64,
+}
+
+/// 支持包内容的脱敏规则
+#[derive(Debug, Clone, Default)]
+pub struct Anonymizer {
+    /// 当前用户的主目录，出现处替换为 `~`
+    pub home: Option<String>,
+    /// 当前用户名，出现在路径中时替换为 `<user>`
+    pub user: Option<String>,
+    /// 已知的设备序列号
+    pub serials: Vec<String>,
+    /// 为真时保留序列号原文（用户明确同意时）
+    pub keep_serials: bool,
+}
+
+/// 序列号打码：保留首尾各两个字符，过短时全部打码
+pub fn mask_serial(serial: &str) -> String {
+    let chars: Vec<char> = serial.chars().collect();
+    if chars.len() <= 4 {
+        return "*".repeat(chars.len());
+    }
+    let head: String = chars[..2].iter().collect();
+    let tail: String = chars[chars.len() - 2..].iter().collect();
+    format!("{}{}{}", head, "*".repeat(chars.len() - 4), tail)
+}
+
+impl Anonymizer {
+    /// 以当前进程的环境构造
+    pub fn from_env(serials: Vec<String>, keep_serials: bool) -> Self {
+        let home = ["HOME", "USERPROFILE"].iter().find_map(|var| std::env::var(var).ok()).filter(|h| !h.is_empty());
+        let user = ["USER", "USERNAME"].iter().find_map(|var| std::env::var(var).ok()).filter(|u| !u.is_empty());
+        Anonymizer { home, user, serials, keep_serials }
+    }
+
+    /// 对一段文本应用所有规则：主目录 → `~`，常见的用户目录形式中的用户名 → `<user>`，序列号打码
+    pub fn apply(&self, text: &str) -> String {
+        let mut out = text.to_string();
+        if let Some(home) = &self.home {
+            out = out.replace(home.as_str(), "~");
+            // Windows 路径在 JSON 中会被转义成双反斜杠
+            out = out.replace(&home.replace('\\', "\\\\"), "~");
+        }
+        // /home/<name>/、/Users/<name>/、C:\Users\<name>\（含 JSON 转义形式）
+        let user_dirs = regex::Regex::new(r"(?i)(/home/|/Users/|[A-Z]:\\\\?Users\\\\?)([^/\\\s\x22']+)").unwrap();
+        out = user_dirs.replace_all(&out, "${1}<user>").to_string();
+        if let Some(user) = self.user.as_deref().filter(|u| u.len() >= 3) {
+            let pattern = format!(r"(?i)\b{}\b", regex::escape(user));
+            out = regex::Regex::new(&pattern).unwrap().replace_all(&out, "<user>").to_string();
+        }
+        if !self.keep_serials {
+            for serial in self.serials.iter().filter(|s| !s.is_empty()) {
+                out = out.replace(serial.as_str(), &mask_serial(serial));
+            }
+            // 未登记的设备：adb 命令行中 `-s <序列号>` 的参数
+            let adb_serial = regex::Regex::new(r"(-s\s+)([A-Za-z0-9.:_-]{5,})").unwrap();
+            out = adb_serial.replace_all(&out, |c: &regex::Captures| format!("{}{}", &c[1], mask_serial(&c[2]))).to_string();
+        }
+        out
+    }
+}
+
+/// 把设置中看起来是密码、令牌等的值替换掉
+pub fn strip_secrets(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                let lower = key.to_lowercase();
+                if SECRET_KEY_MARKERS.iter().any(|marker| lower.contains(marker)) && !value.is_null() {
+                    *value = serde_json::Value::String(REDACTED.to_string());
+                } else {
+                    strip_secrets(value);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(strip_secrets),
+        _ => {}
+    }
+}
+
+/// 环境信息
+#[derive(Debug, Serialize)]
+struct Environment {
+    app_version: &'static str,
+    os: &'static str,
+    arch: &'static str,
+    os_version: Option<String>,
+    locale: Option<String>,
+    total_memory_bytes: Option<u64>,
+    portable: bool,
+}
+
+fn command_text(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    let text = format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+    Some(text.trim().to_string()).filter(|t| !t.is_empty())
+}
+
+fn os_version() -> Option<String> {
+    if cfg!(windows) {
+        command_text("cmd", &["/C", "ver"])
+    } else if cfg!(target_os = "macos") {
+        command_text("sw_vers", &["-productVersion"])
+    } else {
+        command_text("uname", &["-sr"])
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn total_memory() -> Option<u64> {
+    let meminfo = fs::read_to_string("/proc/meminfo").ok()?;
+    let line = meminfo.lines().find(|l| l.starts_with("MemTotal:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+#[cfg(target_os = "macos")]
+fn total_memory() -> Option<u64> {
+    command_text("sysctl", &["-n", "hw.memsize"])?.parse().ok()
+}
+
+#[cfg(windows)]
+fn total_memory() -> Option<u64> {
+    use windows_sys::Win32::System::SystemInformation::{GlobalMemoryStatusEx, MEMORYSTATUSEX};
+    let mut status: MEMORYSTATUSEX = unsafe { std::mem::zeroed() };
+    status.dwLength = std::mem::size_of::<MEMORYSTATUSEX>() as u32;
+    (unsafe { GlobalMemoryStatusEx(&mut status) } != 0).then_some(status.ullTotalPhys)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn total_memory() -> Option<u64> {
+    None
+}
+
+fn environment(paths: &AppPaths) -> Environment {
+    Environment {
+        app_version: env!("CARGO_PKG_VERSION"),
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+        os_version: os_version(),
+        locale: ["LC_ALL", "LANG"].iter().find_map(|var| std::env::var(var).ok()).filter(|l| !l.is_empty()),
+        total_memory_bytes: total_memory(),
+        portable: paths.portable,
+    }
+}
+
+/// 工具与环境检查结果：发现的工具、adb 与 java 的版本、设备连接情况
+fn doctor(paths: &AppPaths) -> serde_json::Value {
+    serde_json::json!({
+        "tools": tool_versions::discover(paths),
+        "adb_version": command_text(&adb::program().path, &["version"]),
+        "java_version": command_text("java", &["-version"]),
+        "devices": adb::device_list(),
+        "paths": paths,
+    })
+}
+
+/// 配置目录下的 JSON 设置，去掉敏感值；其他类型的文件（如密钥库）不读取
+fn settings(paths: &AppPaths) -> serde_json::Map<String, serde_json::Value> {
+    let mut all = serde_json::Map::new();
+    let Ok(entries) = fs::read_dir(&paths.config_dir) else {
+        return all;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_file() || path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let value = fs::read_to_string(&path).ok().and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok());
+        if let Some(mut value) = value {
+            strip_secrets(&mut value);
+            all.insert(entry.file_name().to_string_lossy().to_string(), value);
+        }
+    }
+    all
+}
+
+fn tail_lines(path: &Path, count: usize) -> Vec<String> {
+    let Ok(file) = fs::File::open(path) else {
+        return Vec::new();
+    };
+    let mut tail = VecDeque::with_capacity(count.min(10_000));
+    for line in io::BufReader::new(file).split(b'\n').map_while(Result::ok) {
+        if tail.len() == count {
+            tail.pop_front();
+        }
+        tail.push_back(String::from_utf8_lossy(&line).trim_end().to_string());
+    }
+    tail.into()
+}
+
+/// 某次任务的材料：运行日志、审计日志中的结果、成功时的处理报告；任务早期失败时可能只有部分
+fn job_files(paths: &AppPaths, job_id: &str) -> Vec<(String, String)> {
+    let mut files = Vec::new();
+    if let Ok(log) = fs::read_to_string(paths.log_dir.join("runs").join(format!("{}.log", job_id))) {
+        files.push((format!("job/{}.log", job_id), log));
+    }
+    let audit: Vec<String> = tail_lines(&paths.data_dir.join("audit.jsonl"), usize::MAX)
+        .into_iter()
+        .filter(|line| {
+            serde_json::from_str::<serde_json::Value>(line)
+                .is_ok_and(|entry| entry["params"]["job_id"].as_str() == Some(job_id))
+        })
+        .collect();
+    if !audit.is_empty() {
+        files.push(("job/audit.jsonl".to_string(), audit.join("\n")));
+    }
+    if let Some(report) = incremental::load_records(paths).into_iter().find(|r| r.job_id.as_deref() == Some(job_id)).and_then(|r| r.report) {
+        files.push(("job/report.json".to_string(), serde_json::to_string_pretty(&report).unwrap_or_default()));
+    }
+    if files.is_empty() {
+        files.push(("job/README.txt".to_string(), format!("任务 {} 没有留下日志或报告", job_id)));
+    }
+    files
+}
+
+fn pretty<T: Serialize>(value: &T) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_default()
+}
+
+/// 已连接设备与安装记录中出现过的序列号
+fn known_serials(paths: &AppPaths) -> Vec<String> {
+    let mut serials = adb::devices().unwrap_or_default();
+    for line in tail_lines(&paths.data_dir.join("installs.jsonl"), usize::MAX) {
+        let serial = serde_json::from_str::<serde_json::Value>(&line).ok().and_then(|v| v["device_serial"].as_str().map(str::to_string));
+        if let Some(serial) = serial.filter(|s| !serials.contains(s)) {
+            serials.push(serial);
+        }
+    }
+    serials
+}
+
+fn build(paths: &AppPaths, job_id: Option<&str>, anonymizer: &Anonymizer) -> Result<SupportBundle, String> {
+    let mut files: Vec<(String, String)> = vec![
+        ("environment.json".to_string(), pretty(&environment(paths))),
+        ("doctor.json".to_string(), pretty(&doctor(paths))),
+        ("settings.json".to_string(), pretty(&settings(paths))),
+        ("app.log".to_string(), tail_lines(&paths.data_dir.join("audit.jsonl"), APP_LOG_LINES).join("\n")),
+    ];
+    if let Some(job_id) = job_id {
+        files.extend(job_files(paths, job_id));
+    }
+
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let dir = paths.data_dir.join("support");
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(format!("support-{}.zip", timestamp));
+    let mut writer = zip::ZipWriter::new(fs::File::create(&path).map_err(|e| e.to_string())?);
+    let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    for (name, content) in files {
+        writer.start_file(name, options).map_err(|e| e.to_string())?;
+        writer.write_all(anonymizer.apply(&content).as_bytes()).map_err(|e| e.to_string())?;
+    }
+    writer.finish().map_err(|e| e.to_string())?;
+    crate::quarantine::strip_xattrs(&path);
+    let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+    Ok(SupportBundle { path: path.to_string_lossy().to_string(), size })
+}
+
+/// 生成支持包，返回路径与大小。给出 `job_id` 时附带该任务的日志与报告；
+/// 设备序列号默认打码，`include_serials` 为真时保留
+#[tauri::command]
+pub async fn create_support_bundle(
+    paths: tauri::State<'_, AppPaths>,
+    pool: tauri::State<'_, IoPool>,
+    job_id: Option<String>,
+    include_serials: Option<bool>,
+) -> Result<SupportBundle, String> {
+    let anonymizer = Anonymizer::from_env(known_serials(&paths), include_serials.unwrap_or(false));
+    let paths = paths.inner().clone();
+    pool.run(Priority::Background, "create_support_bundle", move || build(&paths, job_id.as_deref(), &anonymizer)).await?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn anonymizer(keep_serials: bool) -> Anonymizer {
+        Anonymizer {
+            home: Some("/home/alice".to_string()),
+            user: Some("alice".to_string()),
+            serials: vec!["R58M123ABC".to_string(), "emulator-5554".to_string()],
+            keep_serials,
+        }
+    }
+
+    #[test]
+    fn masks_serials_keeping_two_characters_each_side() {
+        assert_eq!(mask_serial("R58M123ABC"), "R5******BC");
+        assert_eq!(mask_serial("abcd"), "****");
+        assert_eq!(mask_serial(""), "");
+    }
+
+    #[test]
+    fn redacts_known_and_unknown_serials() {
+        let text = "device R58M123ABC connected\nadb -s 192.168.1.20:5555 install app.apk\nadb -s emulator-5554 shell";
+        let out = anonymizer(false).apply(text);
+        assert!(!out.contains("R58M123ABC"));
+        assert!(out.contains("R5******BC"));
+        assert!(!out.contains("192.168.1.20:5555"));
+        assert!(out.contains(&format!("-s {} install", mask_serial("192.168.1.20:5555"))));
+        assert!(!out.contains("emulator-5554"));
+    }
+
+    #[test]
+    fn keeps_serials_when_allowed() {
+        let out = anonymizer(true).apply("adb -s R58M123ABC install");
+        assert_eq!(out, "adb -s R58M123ABC install");
+    }
+
+    #[test]
+    fn replaces_home_and_user_directories() {
+        let a = anonymizer(false);
+        assert_eq!(a.apply("/home/alice/work/app.apk"), "~/work/app.apk");
+        assert_eq!(a.apply("/Users/bob/Downloads/a.apk"), "/Users/<user>/Downloads/a.apk");
+        assert_eq!(a.apply(r"C:\Users\carol\Desktop\a.apk"), r"C:\Users\<user>\Desktop\a.apk");
+        // JSON 中转义的 Windows 路径
+        assert_eq!(a.apply(r#"{"path":"C:\\Users\\carol\\a.apk"}"#), r#"{"path":"C:\\Users\\<user>\\a.apk"}"#);
+        assert_eq!(a.apply("signed by alice on /tmp"), "signed by <user> on /tmp");
+        // 只替换完整的单词
+        assert_eq!(a.apply("malice"), "malice");
+    }
+
+    #[test]
+    fn escaped_windows_home_is_replaced() {
+        let a = Anonymizer { home: Some(r"D:\Profiles\dave".to_string()), ..Default::default() };
+        assert_eq!(a.apply(r"D:\Profiles\dave\keys"), r"~\keys");
+        assert_eq!(a.apply(r#""D:\\Profiles\\dave\\keys""#), r#""~\\keys""#);
+    }
+
+    #[test]
+    fn short_usernames_are_not_replaced_outside_paths() {
+        let a = Anonymizer { user: Some("al".to_string()), ..Default::default() };
+        assert_eq!(a.apply("al is here"), "al is here");
+    }
+
+    #[test]
+    fn strips_secret_values_recursively() {
+        let mut value = serde_json::json!({
+            "keystore_path": "/keys/release.jks",
+            "store_password": "hunter2",
+            "profiles": [{ "api_token": "abc", "name": "default", "private_key": null }],
+            "nested": { "Client_Secret": 42 },
+        });
+        strip_secrets(&mut value);
+        assert_eq!(value["keystore_path"], "/keys/release.jks");
+        assert_eq!(value["store_password"], REDACTED);
+        assert_eq!(value["profiles"][0]["api_token"], REDACTED);
+        assert_eq!(value["profiles"][0]["name"], "default");
+        assert!(value["profiles"][0]["private_key"].is_null());
+        assert_eq!(value["nested"]["Client_Secret"], REDACTED);
+    }
+
+    #[test]
+    fn settings_reads_only_json_files_without_secrets() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = AppPaths::portable_at(dir.path());
+        fs::create_dir_all(&paths.config_dir).unwrap();
+        fs::write(paths.config_dir.join("signing.json"), r#"{"alias":"release","key_password":"secret"}"#).unwrap();
+        fs::write(paths.config_dir.join("release.jks"), b"\xfe\xed\xfe\xed").unwrap();
+        fs::write(paths.config_dir.join("broken.json"), "{").unwrap();
+
+        let all = settings(&paths);
+        assert_eq!(all.keys().collect::<Vec<_>>(), ["signing.json"]);
+        assert_eq!(all["signing.json"]["alias"], "release");
+        assert_eq!(all["signing.json"]["key_password"], REDACTED);
+    }
+
+    #[test]
+    fn job_files_collect_only_the_requested_job() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = AppPaths::portable_at(dir.path());
+        assert_eq!(job_files(&paths, "job-1")[0].0, "job/README.txt");
+
+        fs::create_dir_all(paths.log_dir.join("runs")).unwrap();
+        fs::write(paths.log_dir.join("runs").join("job-1.log"), "step 1").unwrap();
+        fs::create_dir_all(&paths.data_dir).unwrap();
+        let audit = [r#"{"params":{"job_id":"job-1"},"ok":true}"#, r#"{"params":{"job_id":"job-2"},"ok":false}"#, "garbage"];
+        fs::write(paths.data_dir.join("audit.jsonl"), audit.join("\n")).unwrap();
+
+        let files = job_files(&paths, "job-1");
+        assert_eq!(files[0], ("job/job-1.log".to_string(), "step 1".to_string()));
+        assert_eq!(files[1], ("job/audit.jsonl".to_string(), audit[0].to_string()));
+        assert_eq!(files.len(), 2);
+    }
+
+    #[test]
+    fn tail_lines_keeps_the_last_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log");
+        fs::write(&path, "1\n2\n3\n4\n").unwrap();
+        assert_eq!(tail_lines(&path, 2), ["3", "4"]);
+        assert!(tail_lines(&dir.path().join("missing"), 2).is_empty());
+    }
+}
@@ -0,0 +1,251 @@
+//! 在工程版（userdebug / eng）设备上把应用安装为 /system/priv-app 下的特权应用，
+//! 以获得 signature 级权限：adb root → remount → 推送 APK 与权限白名单 → 重启。
+//! 生产版本设备无法 adb root，会在执行任何修改前拒绝并说明原因。
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+use tauri::Manager;
+
+use crate::adb_coordinator::AdbCoordinator;
+use crate::confirm::{Confirmation, ConfirmationTokens, DestructiveSummary};
+use crate::device_cache::{self, DeviceCache};
+use crate::input_path::{self, Expect};
+use crate::{axml, installs};
+
+const PRIV_APP_DIR: &str = "/system/priv-app";
+const PERMISSIONS_DIR: &str = "/system/etc/permissions";
+
+/// 设备能否以 root 身份运行 adbd
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct RootCapability {
+    /// `ro.build.type`：user / userdebug / eng
+    pub build_type: Option<String>,
+    pub debuggable: bool,
+    pub root_capable: bool,
+    /// 不支持时的原因
+    pub reason: Option<String>,
+}
+
+/// 安装过程中的一个步骤
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct SystemInstallStep {
+    /// `root`、`remount`、`push_apk`、`push_permissions`、`reboot` 等
+    pub step: String,
+    pub success: bool,
+    pub detail: String,
+}
+
+/// 安装为系统应用的结果
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct SystemInstallReport {
+    pub success: bool,
+    pub steps: Vec<SystemInstallStep>,
+    /// 设备上的 APK 路径
+    pub installed_path: Option<String>,
+    /// 需要重启设备才会生效（未要求自动重启时）
+    pub reboot_required: bool,
+}
+
+impl SystemInstallReport {
+    fn step(&mut self, step: &str, result: Result<String, String>) -> bool {
+        let success = result.is_ok();
+        let detail = result.unwrap_or_else(|e| e);
+        self.steps.push(SystemInstallStep { step: step.to_string(), success, detail });
+        success
+    }
+}
+
+/// 由设备属性判断：只有 userdebug / eng 版本或 ro.debuggable=1 的设备能 adb root
+pub fn root_capability(props: &HashMap<String, String>) -> RootCapability {
+    let build_type = props.get("ro.build.type").cloned();
+    let debuggable = props.get("ro.debuggable").is_some_and(|v| v == "1");
+    let engineering = matches!(build_type.as_deref(), Some("userdebug") | Some("eng"));
+    let root_capable = engineering || debuggable;
+    let reason = (!root_capable).then(|| {
+        format!(
+            "设备为生产版本（ro.build.type={}，ro.debuggable={}），adbd 不能以 root 运行，无法写入 /system",
+            build_type.as_deref().unwrap_or("未知"),
+            if debuggable { "1" } else { "0" }
+        )
+    });
+    RootCapability { build_type, debuggable, root_capable, reason }
+}
+
+fn device_props(cache: &DeviceCache, device_id: &str) -> Result<HashMap<String, String>, String> {
+    if let Some(props) = cache.get(device_id, device_cache::DATASET_PROPS) {
+        return Ok(props);
+    }
+    let props = device_cache::load_device_props(device_id)?;
+    cache.put(device_id, device_cache::DATASET_PROPS, &props);
+    Ok(props)
+}
+
+/// adb 命令的合并输出；退出码非零时返回错误
+fn run_adb(device_id: &str, args: &[&str]) -> Result<String, String> {
+    let output = crate::adb::command().args(["-s", device_id]).args(args).output().map_err(|e| e.to_string())?;
+    let text = format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr)).trim().to_string();
+    if output.status.success() {
+        Ok(text)
+    } else {
+        Err(text)
+    }
+}
+
+/// 系统目录名：包名最后一段，只保留字母数字
+fn dir_name(package: &str) -> String {
+    let last = package.rsplit('.').next().unwrap_or(package);
+    let name: String = last.chars().filter(|c| c.is_ascii_alphanumeric()).collect();
+    if name.is_empty() {
+        "App".to_string()
+    } else {
+        name
+    }
+}
+
+fn restart_as_root(device_id: &str) -> Result<String, String> {
+    let out = run_adb(device_id, &["root"])?;
+    if out.contains("cannot run as root") {
+        return Err(format!("设备拒绝 adb root（生产版本）: {}", out));
+    }
+    // adbd 重启后需要等设备重新连上
+    run_adb(device_id, &["wait-for-device"])?;
+    Ok(out)
+}
+
+fn remount(device_id: &str) -> Result<String, String> {
+    let out = run_adb(device_id, &["remount"])?;
+    let lower = out.to_lowercase();
+    if lower.contains("verity") && !lower.contains("remount succeeded") {
+        return Err(format!("dm-verity 已启用，需要先执行 adb disable-verity 并重启设备后再试: {}", out));
+    }
+    if lower.contains("failed") || lower.contains("not permitted") {
+        return Err(format!("重新挂载 /system 失败: {}", out));
+    }
+    Ok(out)
+}
+
+fn push_file(device_id: &str, local: &Path, remote: &str, mode: &str) -> Result<String, String> {
+    run_adb(device_id, &["push", &local.to_string_lossy(), remote])?;
+    run_adb(device_id, &["shell", "chmod", mode, remote])?;
+    Ok(format!("{} -> {}（{}）", local.display(), remote, mode))
+}
+
+fn install(
+    device_id: &str,
+    apk_path: &Path,
+    package: &str,
+    permissions_xml: Option<&Path>,
+    reboot: bool,
+    capability: &RootCapability,
+) -> SystemInstallReport {
+    let mut report = SystemInstallReport::default();
+    if !report.step("capability", capability.reason.clone().map_or(Ok("设备支持 adb root".to_string()), Err)) {
+        return report;
+    }
+    if !report.step("root", restart_as_root(device_id)) || !report.step("remount", remount(device_id)) {
+        return report;
+    }
+    let name = dir_name(package);
+    let dir = format!("{}/{}", PRIV_APP_DIR, name);
+    let remote_apk = format!("{}/{}.apk", dir, name);
+    let mkdir = run_adb(device_id, &["shell", "mkdir", "-p", &dir]).and_then(|_| run_adb(device_id, &["shell", "chmod", "755", &dir]));
+    if !report.step("mkdir", mkdir.map(|_| dir.clone())) || !report.step("push_apk", push_file(device_id, apk_path, &remote_apk, "644")) {
+        return report;
+    }
+    report.installed_path = Some(remote_apk);
+    if let Some(xml) = permissions_xml {
+        let remote_xml = format!("{}/privapp-permissions-{}.xml", PERMISSIONS_DIR, package);
+        if !report.step("push_permissions", push_file(device_id, xml, &remote_xml, "644")) {
+            return report;
+        }
+    }
+    if reboot {
+        if !report.step("reboot", run_adb(device_id, &["reboot"]).map(|_| "设备正在重启".to_string())) {
+            report.reboot_required = true;
+            return report;
+        }
+    } else {
+        report.reboot_required = true;
+        report.step("reboot", Ok("已跳过，重启设备后系统应用才会生效".to_string()));
+    }
+    report.success = true;
+    report
+}
+
+/// 设备能否 adb root（只读属性，不做任何修改），界面据此决定是否提供"安装为系统应用"
+#[tauri::command]
+pub fn get_root_capability(cache: tauri::State<'_, DeviceCache>, device_id: String) -> Result<RootCapability, String> {
+    Ok(root_capability(&device_props(&cache, &device_id)?))
+}
+
+/// 把 APK 安装为 /system/priv-app 下的特权应用；需先取得确认令牌，再带令牌调用才会执行。
+/// `permissions_xml` 为特权权限白名单，`reboot` 为真时完成后重启设备
+#[tauri::command]
+pub async fn install_as_system(
+    app: tauri::AppHandle,
+    tokens: tauri::State<'_, ConfirmationTokens>,
+    device_id: String,
+    apk_path: String,
+    permissions_xml: Option<String>,
+    reboot: Option<bool>,
+    confirmation_token: Option<String>,
+) -> Result<Confirmation<SystemInstallReport>, String> {
+    let apk_path = input_path::arg(&apk_path, Expect::File).map_err(|e| e.to_string())?;
+    let permissions_xml = permissions_xml.as_deref().map(|p| input_path::arg(p, Expect::File)).transpose().map_err(|e| e.to_string())?;
+    let apk = Path::new(&apk_path);
+    if !apk.is_file() {
+        return Err(format!("APK 不存在: {}", apk_path));
+    }
+    if let Some(xml) = permissions_xml.as_deref() {
+        let content = std::fs::read_to_string(xml).map_err(|e| format!("读取权限白名单失败: {}", e))?;
+        if !content.contains("<privapp-permissions") {
+            return Err(format!("{} 不是特权权限白名单（缺少 <privapp-permissions>）", xml));
+        }
+    }
+    let package = axml::read_manifest_from_apk(apk)
+        .ok()
+        .and_then(|elements| elements.into_iter().find(|e| e.name == "manifest"))
+        .and_then(|manifest| manifest.attr("package").map(axml::AxmlValue::as_string))
+        .ok_or_else(|| format!("无法读取 {} 的包名", apk_path))?;
+    let cache = app.state::<DeviceCache>();
+    let capability = root_capability(&device_props(&cache, &device_id)?);
+    let reboot = reboot.unwrap_or(false);
+
+    let params = (&device_id, &apk_path, &permissions_xml, reboot);
+    let pending = tokens.check("install_as_system", &params, confirmation_token, || DestructiveSummary {
+        operation: "安装为系统特权应用".to_string(),
+        device_serial: device_id.clone(),
+        device_alias: None,
+        package_count: 1,
+        data_loss: false,
+        detail: Some(format!(
+            "将以 root 重新挂载 /system 并写入 {}/{}{}",
+            PRIV_APP_DIR,
+            dir_name(&package),
+            if reboot { "，完成后重启设备" } else { "，需手动重启后生效" }
+        )),
+    })?;
+    if let Some(pending) = pending {
+        return Ok(pending);
+    }
+
+    let coordinator = app.state::<AdbCoordinator>();
+    let transfer = coordinator.begin_transfer(&device_id, "system-install");
+    let report = install(&device_id, apk, &package, permissions_xml.as_deref().map(Path::new), reboot, &capability);
+    drop(transfer);
+    cache.invalidate(&device_id, device_cache::DATASET_INSTALLED_APPS);
+    if report.success {
+        let paths = app.state::<crate::paths::AppPaths>();
+        let record = installs::install_record(&device_id, &package, apk, Some("system".to_string()));
+        let _ = app.state::<installs::InstallHistory>().append(&paths, record);
+    }
+    let failed = report.steps.iter().find(|s| !s.success).map(|s| format!("{}: {}", s.step, s.detail));
+    let params = serde_json::json!({ "device": device_id, "package": package, "apk": apk_path, "reboot": reboot });
+    crate::audit::record_outcome(&app, "install_as_system", params, report.success, failed);
+    Ok(Confirmation::Done { result: report })
+}
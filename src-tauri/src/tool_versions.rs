@@ -0,0 +1,194 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::input_path::{self, Expect};
+use crate::paths::{self, AppPaths};
+use crate::pipeline::ProcessConfig;
+use crate::signing::PipelineError;
+
+/// 工具目录中共存的某个版本，如 `apktool-2.6.1.jar`、`build-tools/34.0.0/`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct ToolVersion {
+    /// `apktool`、`zipalign` 或 `apksigner`
+    pub tool: String,
+    /// 版本标签；无版本号的 `apktool.jar` 等为空
+    pub version: Option<String>,
+    pub path: String,
+}
+
+/// 固定使用的工具版本，处理时解析为具体路径
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct ToolPins {
+    pub apktool: Option<String>,
+    /// 同时决定 zipalign 与 apksigner
+    pub build_tools: Option<String>,
+}
+
+/// 并行安装的工具所在目录（内置工具目录可能只读）
+pub fn managed_tools_dir(paths: &AppPaths) -> PathBuf {
+    paths.data_dir.join("tools")
+}
+
+fn zipalign_name() -> &'static str {
+    if cfg!(target_os = "windows") {
+        "zipalign.exe"
+    } else {
+        "zipalign"
+    }
+}
+
+fn is_valid_label(version: &str) -> bool {
+    regex::Regex::new(r"^[0-9A-Za-z][0-9A-Za-z._-]*$").unwrap().is_match(version)
+}
+
+fn entry(tool: &str, version: Option<String>, path: &Path) -> ToolVersion {
+    ToolVersion { tool: tool.to_string(), version, path: path.to_string_lossy().to_string() }
+}
+
+fn scan_dir(dir: &Path, found: &mut Vec<ToolVersion>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let apktool_jar = regex::Regex::new(r"^apktool[-_]v?(.+)\.jar$").unwrap();
+    for item in entries.filter_map(|e| e.ok()) {
+        let name = item.file_name().to_string_lossy().to_string();
+        if name == "apktool.jar" {
+            found.push(entry("apktool", None, &item.path()));
+        } else if let Some(caps) = apktool_jar.captures(&name) {
+            found.push(entry("apktool", Some(caps[1].to_string()), &item.path()));
+        }
+    }
+
+    let Ok(build_tools) = fs::read_dir(dir.join("build-tools")) else {
+        return;
+    };
+    for item in build_tools.filter_map(|e| e.ok()).filter(|e| e.path().is_dir()) {
+        let version = item.file_name().to_string_lossy().to_string();
+        let zipalign = item.path().join(zipalign_name());
+        if zipalign.exists() {
+            found.push(entry("zipalign", Some(version.clone()), &zipalign));
+        }
+        let apksigner = item.path().join("lib").join("apksigner.jar");
+        if apksigner.exists() {
+            found.push(entry("apksigner", Some(version), &apksigner));
+        }
+    }
+}
+
+/// 枚举内置工具目录与托管工具目录中发现的全部工具版本
+pub fn discover(paths: &AppPaths) -> Vec<ToolVersion> {
+    let mut found = Vec::new();
+    scan_dir(&paths.tools_dir, &mut found);
+    scan_dir(&managed_tools_dir(paths), &mut found);
+    found.sort_by(|a, b| (&a.tool, &a.version).cmp(&(&b.tool, &b.version)));
+    found.dedup_by(|a, b| a.tool == b.tool && a.version == b.version);
+    found
+}
+
+/// 从路径推断版本标签：`apktool-2.9.3.jar` 或 `build-tools/<版本>/...`
+pub fn version_from_path(path: &str) -> Option<String> {
+    let path = Path::new(path);
+    let name = path.file_name()?.to_string_lossy();
+    if let Some(caps) = regex::Regex::new(r"^apktool[-_]v?(.+)\.jar$").unwrap().captures(&name) {
+        return Some(caps[1].to_string());
+    }
+    let components: Vec<String> = path.components().map(|c| c.as_os_str().to_string_lossy().to_string()).collect();
+    let pos = components.iter().rposition(|c| c == "build-tools")?;
+    components.get(pos + 1).cloned()
+}
+
+fn find(available: &[ToolVersion], tool: &str, version: &str) -> Result<String, PipelineError> {
+    available
+        .iter()
+        .find(|t| t.tool == tool && t.version.as_deref() == Some(version))
+        .map(|t| t.path.clone())
+        .ok_or_else(|| {
+            let installed: Vec<&str> = available.iter().filter(|t| t.tool == tool).filter_map(|t| t.version.as_deref()).collect();
+            PipelineError::InvalidInput(format!(
+                "固定的 {} 版本 {} 不存在（已安装: {}），请先通过 install_tool_version 安装",
+                tool,
+                version,
+                if installed.is_empty() { "无".to_string() } else { installed.join(", ") }
+            ))
+        })
+}
+
+/// 将固定版本解析为具体路径写入配置，返回本次实际使用的工具及版本
+pub fn apply_pins(paths: &AppPaths, config: &mut ProcessConfig) -> Result<Vec<ToolVersion>, PipelineError> {
+    let pins = config.tool_versions.clone().unwrap_or_default();
+    let available = discover(paths);
+    if let Some(version) = &pins.apktool {
+        config.apktool_path = find(&available, "apktool", version)?;
+    }
+    if let Some(version) = &pins.build_tools {
+        config.zipalign_path = find(&available, "zipalign", version)?;
+        config.apksigner_path = find(&available, "apksigner", version)?;
+    }
+    Ok([
+        ("apktool", &config.apktool_path),
+        ("zipalign", &config.zipalign_path),
+        ("apksigner", &config.apksigner_path),
+    ]
+    .into_iter()
+    .map(|(tool, path)| ToolVersion { tool: tool.to_string(), version: version_from_path(path), path: path.clone() })
+    .collect())
+}
+
+/// 列出所有已发现的工具版本
+#[tauri::command]
+pub fn list_tool_versions(paths: tauri::State<'_, AppPaths>) -> Vec<ToolVersion> {
+    discover(&paths)
+}
+
+/// 与已有版本并行安装一个工具版本，不替换任何现有文件。
+/// `tool` 为 `apktool` 时 `source_path` 是 jar 文件，为 `build-tools` 时是包含 zipalign 与 lib/apksigner.jar 的目录
+#[tauri::command]
+pub fn install_tool_version(
+    app: tauri::AppHandle,
+    paths: tauri::State<'_, AppPaths>,
+    tool: String,
+    version: String,
+    source_path: String,
+) -> Result<Vec<ToolVersion>, PipelineError> {
+    let source_path = input_path::arg(&source_path, Expect::Any)?;
+    let params = serde_json::json!({ "tool": tool, "version": version, "source": source_path });
+    let result = install_side_by_side(&paths, &tool, &version, &source_path);
+    crate::audit::record(&app, "install_tool_version", params, &result);
+    result
+}
+
+fn install_side_by_side(paths: &AppPaths, tool: &str, version: &str, source_path: &str) -> Result<Vec<ToolVersion>, PipelineError> {
+    if !is_valid_label(version) {
+        return Err(PipelineError::InvalidInput(format!("版本标签只能包含字母、数字、点、下划线和连字符: {}", version)));
+    }
+    let source = Path::new(source_path);
+    let managed = managed_tools_dir(paths);
+    match tool {
+        "apktool" => {
+            if !source.is_file() {
+                return Err(PipelineError::InvalidInput(format!("{} 不是 jar 文件", source_path)));
+            }
+            let target = managed.join(format!("apktool-{}.jar", version));
+            if target.exists() {
+                return Err(PipelineError::InvalidInput(format!("apktool {} 已安装", version)));
+            }
+            fs::create_dir_all(&managed)?;
+            fs::copy(source, &target)?;
+        }
+        "build-tools" => {
+            if !source.join(zipalign_name()).exists() || !source.join("lib").join("apksigner.jar").exists() {
+                return Err(PipelineError::InvalidInput(format!("{} 中缺少 {} 或 lib/apksigner.jar", source_path, zipalign_name())));
+            }
+            let target = managed.join("build-tools").join(version);
+            if target.exists() {
+                return Err(PipelineError::InvalidInput(format!("build-tools {} 已安装", version)));
+            }
+            paths::copy_dir(source, &target).map_err(PipelineError::Io)?;
+        }
+        _ => return Err(PipelineError::InvalidInput(format!("未知工具: {}", tool))),
+    }
+    Ok(discover(paths))
+}
@@ -0,0 +1,492 @@
+//! 卸载应用。批量卸载逐个执行 `pm uninstall`，失败不影响后续的包，最后汇总每个包的结果；
+//! 失败时解析 `pm` 给出的失败代码，说明原因。无法卸载的系统应用可只为用户 0 移除，或退而停用。
+//! 只安装在其他用户（如工作资料）中的包，默认卸载会失败，此时查出安装了它的用户，按需为这些用户卸载。
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use tauri::{Emitter, Manager};
+
+use crate::confirm::{Confirmation, ConfirmationTokens, DestructiveSummary};
+use crate::device_cache::{self, DeviceCache};
+use crate::installs::{self, InstallHistory};
+use crate::paths::AppPaths;
+use crate::{adb, audit, pm};
+
+/// 单个包的卸载结果
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct UninstallResult {
+    pub package: String,
+    pub success: bool,
+    /// `pm uninstall` 给出的失败代码，如 `DELETE_FAILED_DEVICE_POLICY_MANAGER`
+    pub failure_code: Option<String>,
+    pub message: Option<String>,
+    /// `pm uninstall` 的原始输出
+    #[serde(default)]
+    pub output: String,
+    /// 失败时的处理建议
+    #[serde(default)]
+    pub hint: Option<String>,
+    /// 包不在用户 0 中时，实际安装了它的用户；`any_user` 时为已为其卸载的用户
+    #[serde(default)]
+    pub installed_for_users: Vec<u32>,
+}
+
+/// 包不在用户 0 中时使用的失败代码
+pub const NOT_INSTALLED_FOR_USER: &str = "NOT_INSTALLED_FOR_USER";
+
+/// 批量卸载进度（`uninstall-progress` 事件），每完成一个包推送一次
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct UninstallProgress {
+    pub device_id: String,
+    pub completed: usize,
+    pub total: usize,
+    pub result: UninstallResult,
+}
+
+/// 批量卸载的汇总
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct UninstallSummary {
+    pub succeeded: usize,
+    pub failed: usize,
+    pub results: Vec<UninstallResult>,
+}
+
+/// 常见失败代码的说明
+fn describe_failure(code: &str) -> Option<&'static str> {
+    Some(match code {
+        "DELETE_FAILED_DEVICE_POLICY_MANAGER" => "应用是设备管理器，需先在设置中取消激活",
+        "DELETE_FAILED_OWNER_BLOCKED" => "设备所有者（MDM）禁止卸载该应用",
+        "DELETE_FAILED_USER_RESTRICTED" => "当前用户被限制卸载应用",
+        "DELETE_FAILED_INTERNAL_ERROR" => "系统内部错误，通常是系统应用无法卸载",
+        "DELETE_FAILED_ABORTED" => "卸载被取消",
+        _ => return None,
+    })
+}
+
+/// 失败代码对应的处理建议
+fn failure_hint(code: &str) -> Option<&'static str> {
+    Some(match code {
+        "DELETE_FAILED_INTERNAL_ERROR" => "系统应用可改用 `pm uninstall --user 0` 只为当前用户卸载",
+        "DELETE_FAILED_DEVICE_POLICY_MANAGER" => "在 设置 → 安全 → 设备管理应用 中取消激活后重试",
+        _ => return None,
+    })
+}
+
+/// 解析 `pm uninstall` 的输出：`Success`，或 `Failure [DELETE_FAILED_...]`
+pub fn parse_uninstall_output(package: &str, output: &str) -> UninstallResult {
+    let output = output.trim();
+    if output.lines().any(|line| line.trim() == "Success") {
+        return UninstallResult { package: package.to_string(), success: true, output: output.to_string(), ..Default::default() };
+    }
+    let failure_code = output
+        .split_once("Failure [")
+        .and_then(|(_, rest)| rest.split_once(']'))
+        .map(|(code, _)| code.trim().to_string())
+        .filter(|code| !code.is_empty());
+    let message = match failure_code.as_deref().and_then(describe_failure) {
+        Some(description) => format!("{}: {}", description, output),
+        None if output.is_empty() => "pm uninstall 没有输出".to_string(),
+        None => output.to_string(),
+    };
+    let hint = failure_code.as_deref().and_then(failure_hint).map(str::to_string);
+    UninstallResult {
+        package: package.to_string(),
+        success: false,
+        failure_code,
+        message: Some(message),
+        output: output.to_string(),
+        hint,
+        installed_for_users: Vec::new(),
+    }
+}
+
+/// 包没有为用户 0 安装：Android 5–9 输出 `Failure [not installed for 0]`，
+/// 之后的版本输出 `Failure [DELETE_FAILED_INTERNAL_ERROR]` 并在 stderr 中给出 `Unknown package`
+pub fn is_missing_for_user(result: &UninstallResult) -> bool {
+    !result.success
+        && (result.failure_code.as_deref().is_some_and(|code| code.starts_with("not installed for"))
+            || result.output.contains("Unknown package"))
+}
+
+/// 在设备上执行 `pm`，返回标准输出与标准错误；测试中替换为模拟的输出
+fn device_pm(device_id: &str) -> impl FnMut(&[&str]) -> Result<String, String> + '_ {
+    move |args| {
+        let output = adb::command().args(["-s", device_id, "shell", "pm"]).args(args).output().map_err(|e| e.to_string())?;
+        Ok(format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr)))
+    }
+}
+
+/// 执行 `pm uninstall`；`keep_data` 对应 `-k`，保留应用数据与缓存；`user` 为空时卸载所有用户中的该包
+fn run_uninstall(pm: &mut impl FnMut(&[&str]) -> Result<String, String>, package: &str, keep_data: bool, user: Option<u32>) -> UninstallResult {
+    let user = user.map(|user| user.to_string());
+    let mut args = vec!["uninstall"];
+    if keep_data {
+        args.push("-k");
+    }
+    if let Some(user) = &user {
+        args.extend(["--user", user.as_str()]);
+    }
+    args.push(package);
+    match pm(&args) {
+        Ok(output) => parse_uninstall_output(package, &output),
+        Err(e) => UninstallResult { package: package.to_string(), success: false, message: Some(e), ..Default::default() },
+    }
+}
+
+/// 除用户 0 外安装了该包的用户
+fn users_with_package(pm: &mut impl FnMut(&[&str]) -> Result<String, String>, package: &str) -> Vec<u32> {
+    let users = pm(&["list", "users"]).map(|out| pm::parse_user_list(&out)).unwrap_or_default();
+    users
+        .into_iter()
+        .filter(|user| user.id != 0)
+        .filter(|user| {
+            pm(&["list", "packages", "--user", &user.id.to_string(), package])
+                .is_ok_and(|out| pm::parse_package_list(&out).iter().any(|p| p.package == package))
+        })
+        .map(|user| user.id)
+        .collect()
+}
+
+/// 卸载一个包。包只安装在其他用户中时，`any_user` 为 true 则逐个为这些用户卸载，
+/// 否则返回 `NOT_INSTALLED_FOR_USER` 并列出这些用户，由界面提供针对性的卸载
+fn uninstall_with(pm: &mut impl FnMut(&[&str]) -> Result<String, String>, package: &str, keep_data: bool, any_user: bool) -> UninstallResult {
+    let result = run_uninstall(pm, package, keep_data, None);
+    if !is_missing_for_user(&result) {
+        return result;
+    }
+    let users = users_with_package(pm, package);
+    if users.is_empty() {
+        return result;
+    }
+    let listed = users.iter().map(u32::to_string).collect::<Vec<_>>().join("、");
+    if !any_user {
+        return UninstallResult {
+            failure_code: Some(NOT_INSTALLED_FOR_USER.to_string()),
+            message: Some(format!("{} 只安装在用户 {} 中", package, listed)),
+            hint: Some("可为这些用户卸载（any_user）".to_string()),
+            installed_for_users: users,
+            ..result
+        };
+    }
+    for user in &users {
+        let per_user = run_uninstall(pm, package, keep_data, Some(*user));
+        if !per_user.success {
+            return UninstallResult {
+                message: Some(format!("为用户 {} 卸载失败: {}", user, per_user.message.unwrap_or_default())),
+                installed_for_users: users,
+                ..per_user
+            };
+        }
+    }
+    UninstallResult {
+        package: package.to_string(),
+        success: true,
+        message: Some(format!("{} 不在用户 0 中，已为用户 {} 卸载", package, listed)),
+        output: result.output,
+        installed_for_users: users,
+        ..Default::default()
+    }
+}
+
+fn uninstall_one(device_id: &str, package: &str, keep_data: bool, any_user: bool) -> UninstallResult {
+    uninstall_with(&mut device_pm(device_id), package, keep_data, any_user)
+}
+
+fn device_alias(cache: &DeviceCache, device_id: &str) -> Option<String> {
+    cache.get::<HashMap<String, String>>(device_id, device_cache::DATASET_PROPS).and_then(|props| props.get("ro.product.model").cloned())
+}
+
+/// 卸载应用；需先取得确认令牌，再带令牌调用才会执行。
+/// `keep_data` 为 true 时保留应用数据，便于测试时反复重装伪装后的版本
+#[tauri::command]
+pub fn uninstall_app(
+    app: tauri::AppHandle,
+    tokens: tauri::State<'_, ConfirmationTokens>,
+    device_id: String,
+    package_name: String,
+    keep_data: Option<bool>,
+    any_user: Option<bool>,
+    confirmation_token: Option<String>,
+) -> Result<Confirmation<UninstallResult>, String> {
+    let cache = app.state::<DeviceCache>();
+    let app_paths = app.state::<AppPaths>();
+    let history = app.state::<InstallHistory>();
+    if !pm::is_package_name(&package_name) {
+        return Err(format!("无效的包名: {}", package_name));
+    }
+    let keep_data = keep_data.unwrap_or(false);
+    let any_user = any_user.unwrap_or(false);
+    let pending = tokens.check("uninstall_app", &(&device_id, &package_name, keep_data, any_user), confirmation_token, || DestructiveSummary {
+        operation: if keep_data { "卸载应用（保留数据）" } else { "卸载应用" }.to_string(),
+        device_serial: device_id.clone(),
+        device_alias: device_alias(&cache, &device_id),
+        package_count: 1,
+        data_loss: !keep_data,
+        detail: None,
+    })?;
+    if let Some(pending) = pending {
+        return Ok(pending);
+    }
+
+    cache.invalidate(&device_id, device_cache::DATASET_INSTALLED_APPS);
+    let result = uninstall_one(&device_id, &package_name, keep_data, any_user);
+    let params = serde_json::json!({ "device": device_id, "package": package_name, "keep_data": keep_data });
+    audit::record_outcome(&app, "uninstall_app", params, result.success, result.message.clone());
+    if result.success {
+        history.append(&app_paths, installs::uninstall_record(&device_id, &package_name))?;
+    }
+    Ok(Confirmation::Done { result })
+}
+
+/// 批量卸载应用；需先取得确认令牌，再带令牌调用才会执行。
+/// 逐个卸载并推送进度，某个包失败时继续卸载其余的包，最后返回每个包的结果
+#[tauri::command]
+pub async fn uninstall_apps(
+    app: tauri::AppHandle,
+    tokens: tauri::State<'_, ConfirmationTokens>,
+    device_id: String,
+    package_names: Vec<String>,
+    any_user: Option<bool>,
+    confirmation_token: Option<String>,
+) -> Result<Confirmation<UninstallSummary>, String> {
+    let cache = app.state::<DeviceCache>();
+    let app_paths = app.state::<AppPaths>();
+    let history = app.state::<InstallHistory>();
+    if package_names.is_empty() {
+        return Ok(Confirmation::Done { result: UninstallSummary::default() });
+    }
+    if let Some(invalid) = package_names.iter().find(|p| !pm::is_package_name(p)) {
+        return Err(format!("无效的包名: {}", invalid));
+    }
+    let any_user = any_user.unwrap_or(false);
+    let pending = tokens.check("uninstall_apps", &(&device_id, &package_names, any_user), confirmation_token, || DestructiveSummary {
+        operation: "批量卸载应用".to_string(),
+        device_serial: device_id.clone(),
+        device_alias: device_alias(&cache, &device_id),
+        package_count: package_names.len(),
+        data_loss: true,
+        detail: Some(package_names.join("\n")),
+    })?;
+    if let Some(pending) = pending {
+        return Ok(pending);
+    }
+
+    cache.invalidate(&device_id, device_cache::DATASET_INSTALLED_APPS);
+    let total = package_names.len();
+    let mut summary = UninstallSummary::default();
+    for (i, package) in package_names.iter().enumerate() {
+        let mut result = uninstall_one(&device_id, package, false, any_user);
+        let params = serde_json::json!({ "device": device_id, "package": package });
+        audit::record_outcome(&app, "uninstall_app", params, result.success, result.message.clone());
+        if result.success {
+            summary.succeeded += 1;
+            if let Err(e) = history.append(&app_paths, installs::uninstall_record(&device_id, package)) {
+                result.message = Some(format!("已卸载，但写入安装历史失败: {}", e));
+            }
+        } else {
+            summary.failed += 1;
+        }
+        let _ = app.emit("uninstall-progress", UninstallProgress { device_id: device_id.clone(), completed: i + 1, total, result: result.clone() });
+        summary.results.push(result);
+    }
+    Ok(Confirmation::Done { result: summary })
+}
+
+/// 移除系统应用的结果
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+#[serde(rename_all = "snake_case")]
+pub enum SystemRemovalOutcome {
+    /// 已为用户 0 卸载，不再出现在应用列表中（系统分区中的 APK 仍在，恢复出厂设置后重新出现）
+    Removed,
+    /// 卸载失败，已停用
+    Disabled,
+    Failed,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct SystemRemovalResult {
+    pub package: String,
+    pub outcome: SystemRemovalOutcome,
+    /// `pm uninstall --user 0` 的结果
+    pub uninstall: UninstallResult,
+    /// 卸载失败后 `pm disable-user --user 0` 的输出
+    pub disable_output: Option<String>,
+}
+
+/// 为用户 0 移除系统应用：先 `pm uninstall --user 0`，失败时退而 `pm disable-user --user 0`，
+/// 结果中说明最终采用的方式。需先取得确认令牌，再带令牌调用才会执行
+#[tauri::command]
+pub fn remove_system_app(
+    app: tauri::AppHandle,
+    tokens: tauri::State<'_, ConfirmationTokens>,
+    device_id: String,
+    package_name: String,
+    confirmation_token: Option<String>,
+) -> Result<Confirmation<SystemRemovalResult>, String> {
+    let cache = app.state::<DeviceCache>();
+    let app_paths = app.state::<AppPaths>();
+    let history = app.state::<InstallHistory>();
+    if !pm::is_package_name(&package_name) {
+        return Err(format!("无效的包名: {}", package_name));
+    }
+    let pending = tokens.check("remove_system_app", &(&device_id, &package_name), confirmation_token, || DestructiveSummary {
+        operation: "为用户 0 移除系统应用（失败时停用）".to_string(),
+        device_serial: device_id.clone(),
+        device_alias: device_alias(&cache, &device_id),
+        package_count: 1,
+        data_loss: true,
+        detail: None,
+    })?;
+    if let Some(pending) = pending {
+        return Ok(pending);
+    }
+
+    cache.invalidate(&device_id, device_cache::DATASET_INSTALLED_APPS);
+    let output = adb::command()
+        .args(["-s", &device_id, "shell", "pm", "uninstall", "--user", "0", &package_name])
+        .output()
+        .map_err(|e| e.to_string())?;
+    let uninstall = parse_uninstall_output(
+        &package_name,
+        &format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr)),
+    );
+    let (outcome, disable_output) = if uninstall.success {
+        (SystemRemovalOutcome::Removed, None)
+    } else {
+        match adb::shell(&device_id, &["pm", "disable-user", "--user", "0", &package_name]) {
+            Ok(out) if pm::parse_state_change(&package_name, &out, false).is_ok() => (SystemRemovalOutcome::Disabled, Some(out.trim().to_string())),
+            Ok(out) => (SystemRemovalOutcome::Failed, Some(out.trim().to_string())),
+            Err(e) => (SystemRemovalOutcome::Failed, Some(e)),
+        }
+    };
+
+    let params = serde_json::json!({ "device": device_id, "package": package_name, "outcome": outcome });
+    let message = (outcome == SystemRemovalOutcome::Failed).then(|| disable_output.clone().unwrap_or_default());
+    audit::record_outcome(&app, "remove_system_app", params, outcome != SystemRemovalOutcome::Failed, message);
+    if outcome == SystemRemovalOutcome::Removed {
+        history.append(&app_paths, installs::uninstall_record(&device_id, &package_name))?;
+    }
+    Ok(Confirmation::Done { result: SystemRemovalResult { package: package_name, outcome, uninstall, disable_output } })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USERS: &str = "Users:\n\tUserInfo{0:Owner:c13} running\n\tUserInfo{10:Work profile:1030} running\n";
+    const ANDROID_9_MISSING: &str = "Failure [not installed for 0]\n";
+    const ANDROID_14_MISSING: &str = "Failure [DELETE_FAILED_INTERNAL_ERROR]\n\
+Exception occurred while executing 'uninstall':\n\
+java.lang.IllegalArgumentException: Unknown package: com.example.app\n";
+
+    /// 模拟设备上的 `pm`：按命令行返回预设的输出，并记录收到的命令
+    struct MockPm {
+        responses: Vec<(&'static str, &'static str)>,
+        calls: Vec<String>,
+    }
+
+    impl MockPm {
+        fn new(responses: &[(&'static str, &'static str)]) -> Self {
+            MockPm { responses: responses.to_vec(), calls: Vec::new() }
+        }
+
+        fn uninstall(&mut self, keep_data: bool, any_user: bool) -> UninstallResult {
+            let (responses, calls) = (&self.responses, &mut self.calls);
+            let mut pm = |args: &[&str]| {
+                let line = args.join(" ");
+                calls.push(line.clone());
+                responses.iter().find(|(command, _)| *command == line).map(|(_, output)| output.to_string()).ok_or(format!("未预设: {}", line))
+            };
+            uninstall_with(&mut pm, "com.example.app", keep_data, any_user)
+        }
+    }
+
+    #[test]
+    fn single_user_uninstall_needs_one_call() {
+        let mut pm = MockPm::new(&[("uninstall -k com.example.app", "Success\n")]);
+        let result = pm.uninstall(true, false);
+        assert!(result.success);
+        assert!(result.installed_for_users.is_empty());
+        assert_eq!(pm.calls, ["uninstall -k com.example.app"]);
+    }
+
+    #[test]
+    fn work_profile_only_reports_the_users_without_any_user() {
+        for missing in [ANDROID_9_MISSING, ANDROID_14_MISSING] {
+            let mut pm = MockPm::new(&[
+                ("uninstall com.example.app", missing),
+                ("list users", USERS),
+                ("list packages --user 10 com.example.app", "package:com.example.app\n"),
+            ]);
+            let result = pm.uninstall(false, false);
+            assert!(!result.success);
+            assert_eq!(result.failure_code.as_deref(), Some(NOT_INSTALLED_FOR_USER));
+            assert_eq!(result.installed_for_users, [10]);
+            assert!(!pm.calls.iter().any(|call| call.contains("--user 10 com.example.app") && call.starts_with("uninstall")));
+        }
+    }
+
+    #[test]
+    fn work_profile_only_retries_per_user_with_any_user() {
+        for missing in [ANDROID_9_MISSING, ANDROID_14_MISSING] {
+            let mut pm = MockPm::new(&[
+                ("uninstall com.example.app", missing),
+                ("list users", USERS),
+                ("list packages --user 10 com.example.app", "package:com.example.app uid:1010234\n"),
+                ("uninstall --user 10 com.example.app", "Success\n"),
+            ]);
+            let result = pm.uninstall(false, true);
+            assert!(result.success, "{:?}", result);
+            assert_eq!(result.installed_for_users, [10]);
+            assert_eq!(pm.calls.last().map(String::as_str), Some("uninstall --user 10 com.example.app"));
+        }
+    }
+
+    #[test]
+    fn all_users_are_removed_by_the_plain_uninstall() {
+        let mut pm = MockPm::new(&[("uninstall com.example.app", "Success\n")]);
+        assert!(pm.uninstall(false, true).success);
+        assert_eq!(pm.calls.len(), 1);
+    }
+
+    #[test]
+    fn missing_everywhere_keeps_the_original_failure() {
+        let mut pm = MockPm::new(&[
+            ("uninstall com.example.app", ANDROID_14_MISSING),
+            ("list users", USERS),
+            ("list packages --user 10 com.example.app", ""),
+        ]);
+        let result = pm.uninstall(false, true);
+        assert!(!result.success);
+        assert_eq!(result.failure_code.as_deref(), Some("DELETE_FAILED_INTERNAL_ERROR"));
+        assert!(result.installed_for_users.is_empty());
+    }
+
+    #[test]
+    fn per_user_failure_is_reported_with_the_user() {
+        let mut pm = MockPm::new(&[
+            ("uninstall com.example.app", ANDROID_9_MISSING),
+            ("list users", USERS),
+            ("list packages --user 10 com.example.app", "package:com.example.app\n"),
+            ("uninstall --user 10 com.example.app", "Failure [DELETE_FAILED_USER_RESTRICTED]\n"),
+        ]);
+        let result = pm.uninstall(false, true);
+        assert!(!result.success);
+        assert_eq!(result.failure_code.as_deref(), Some("DELETE_FAILED_USER_RESTRICTED"));
+        assert!(result.message.unwrap().starts_with("为用户 10 卸载失败"));
+    }
+
+    #[test]
+    fn failure_codes_are_described() {
+        let result = parse_uninstall_output("com.example.app", "Failure [DELETE_FAILED_DEVICE_POLICY_MANAGER]");
+        assert!(result.hint.is_some());
+        assert!(result.message.as_deref().unwrap().starts_with("应用是设备管理器"));
+        assert!(!is_missing_for_user(&result));
+    }
+}
@@ -0,0 +1,177 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+use crate::input_path::{self, Expect};
+use crate::signing::PipelineError;
+
+pub const DEFAULT_MAX_RATIO: f32 = 100.0;
+pub const DEFAULT_MAX_TOTAL_MB: u32 = 2000;
+/// 解压后不足 1 MiB 的条目不检查压缩比：小的资源文件（如全零的占位图）压缩比常超过 100，但不构成威胁
+const RATIO_CHECK_MIN_BYTES: u64 = 1024 * 1024;
+
+/// 压缩比异常的 ZIP 条目
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct SuspiciousEntry {
+    pub name: String,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub compressed_bytes: u64,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub uncompressed_bytes: u64,
+    pub ratio: f32,
+}
+
+/// ZIP 炸弹检测结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "export-bindings", derive(ts_rs::TS), ts(export))]
+pub struct ZipBombCheck {
+    pub safe: bool,
+    #[cfg_attr(feature = "export-bindings", ts(type = "number"))]
+    pub total_uncompressed_bytes: u64,
+    pub suspicious_entries: Vec<SuspiciousEntry>,
+}
+
+/// 根据中央目录记录的大小检测 ZIP 炸弹，不解压任何内容
+#[tauri::command]
+pub fn check_for_zip_bomb(
+    apk_path: String,
+    max_uncompressed_ratio: Option<f32>,
+    max_total_uncompressed_mb: Option<u32>,
+) -> Result<ZipBombCheck, PipelineError> {
+    let apk_path = input_path::arg(&apk_path, Expect::File)?;
+    let max_ratio = max_uncompressed_ratio.unwrap_or(DEFAULT_MAX_RATIO);
+    let max_total = max_total_uncompressed_mb.unwrap_or(DEFAULT_MAX_TOTAL_MB) as u64 * 1024 * 1024;
+
+    let mut archive = zip::ZipArchive::new(fs::File::open(&apk_path)?)?;
+    let mut total: u64 = 0;
+    let mut suspicious_entries = Vec::new();
+
+    for i in 0..archive.len() {
+        let entry = archive.by_index_raw(i)?;
+        let compressed = entry.compressed_size();
+        let uncompressed = entry.size();
+        total = total.saturating_add(uncompressed);
+
+        let ratio = if compressed == 0 {
+            if uncompressed == 0 { 0.0 } else { f32::INFINITY }
+        } else {
+            uncompressed as f32 / compressed as f32
+        };
+        if uncompressed > RATIO_CHECK_MIN_BYTES && ratio > max_ratio {
+            suspicious_entries.push(SuspiciousEntry {
+                name: entry.name().to_string(),
+                compressed_bytes: compressed,
+                uncompressed_bytes: uncompressed,
+                ratio,
+            });
+        }
+    }
+
+    Ok(ZipBombCheck {
+        safe: suspicious_entries.is_empty() && total <= max_total,
+        total_uncompressed_bytes: total,
+        suspicious_entries,
+    })
+}
+
+/// 处理前校验 APK：必须是包含 AndroidManifest.xml 的 ZIP，且不是 ZIP 炸弹
+#[tauri::command]
+pub fn validate_apk_file(apk_path: String) -> Result<ZipBombCheck, PipelineError> {
+    let apk_path = input_path::arg(&apk_path, Expect::File)?;
+    let archive = zip::ZipArchive::new(fs::File::open(&apk_path)?)
+        .map_err(|e| PipelineError::InvalidInput(format!("不是有效的 APK 文件: {}", e)))?;
+    if !archive.file_names().any(|n| n == "AndroidManifest.xml") {
+        return Err(PipelineError::InvalidInput("APK 中缺少 AndroidManifest.xml".to_string()));
+    }
+
+    let check = check_for_zip_bomb(apk_path, None, None)?;
+    if !check.safe {
+        let names: Vec<&str> = check.suspicious_entries.iter().map(|e| e.name.as_str()).take(5).collect();
+        return Err(PipelineError::InvalidInput(format!(
+            "APK 疑似 ZIP 炸弹（解压后共 {} MB，可疑条目: {}），已拒绝处理",
+            check.total_uncompressed_bytes / 1024 / 1024,
+            names.join(", ")
+        )));
+    }
+    Ok(check)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::path::Path;
+
+    /// 写入一个 ZIP；`reported` 中的条目在中央目录里被改成指定的解压后大小
+    fn write_zip(path: &Path, entries: &[(&str, &[u8], zip::CompressionMethod)], reported: &[(&str, u32)]) {
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        for (name, data, method) in entries {
+            writer.start_file(*name, zip::write::SimpleFileOptions::default().compression_method(*method)).unwrap();
+            writer.write_all(data).unwrap();
+        }
+        let mut bytes = writer.finish().unwrap().into_inner();
+
+        // 中央目录记录：签名 PK\x01\x02，偏移 24 为解压后大小，28 为文件名长度，46 起为文件名
+        let mut pos = 0;
+        while let Some(found) = bytes[pos..].windows(4).position(|w| w == b"PK\x01\x02") {
+            let record = pos + found;
+            let name_len = u16::from_le_bytes([bytes[record + 28], bytes[record + 29]]) as usize;
+            let name = String::from_utf8_lossy(&bytes[record + 46..record + 46 + name_len]).to_string();
+            if let Some((_, size)) = reported.iter().find(|(n, _)| *n == name) {
+                bytes[record + 24..record + 28].copy_from_slice(&size.to_le_bytes());
+            }
+            pos = record + 46 + name_len;
+        }
+        fs::write(path, bytes).unwrap();
+    }
+
+    fn check(path: &Path, max_total_mb: Option<u32>) -> ZipBombCheck {
+        check_for_zip_bomb(path.to_string_lossy().to_string(), None, max_total_mb).unwrap()
+    }
+
+    #[test]
+    fn falsified_uncompressed_size_is_flagged() {
+        let dir = tempfile::tempdir().unwrap();
+        let apk = dir.path().join("bomb.apk");
+        let stored = zip::CompressionMethod::Stored;
+        write_zip(&apk, &[("AndroidManifest.xml", b"manifest", stored), ("assets/bomb.bin", &[0u8; 1024], stored)], &[("assets/bomb.bin", 3_000_000_000)]);
+
+        let result = check(&apk, None);
+        assert!(!result.safe);
+        assert_eq!(result.suspicious_entries.len(), 1);
+        let entry = &result.suspicious_entries[0];
+        assert_eq!(entry.name, "assets/bomb.bin");
+        assert_eq!(entry.compressed_bytes, 1024);
+        assert_eq!(entry.uncompressed_bytes, 3_000_000_000);
+        assert!(entry.ratio > DEFAULT_MAX_RATIO);
+        assert!(result.total_uncompressed_bytes > DEFAULT_MAX_TOTAL_MB as u64 * 1024 * 1024);
+
+        let error = validate_apk_file(apk.to_string_lossy().to_string()).unwrap_err();
+        assert!(error.to_string().contains("assets/bomb.bin"));
+    }
+
+    #[test]
+    fn small_entries_skip_the_ratio_check() {
+        let dir = tempfile::tempdir().unwrap();
+        let apk = dir.path().join("small.apk");
+        let zeros = vec![0u8; 512 * 1024];
+        write_zip(&apk, &[("AndroidManifest.xml", b"manifest", zip::CompressionMethod::Stored), ("res/raw/blank.bin", &zeros, zip::CompressionMethod::Deflated)], &[]);
+
+        let result = check(&apk, None);
+        assert!(result.safe, "{:?}", result);
+        assert!(result.suspicious_entries.is_empty());
+    }
+
+    #[test]
+    fn total_size_above_the_limit_is_unsafe_without_suspicious_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let apk = dir.path().join("large.apk");
+        let data = vec![7u8; 2 * 1024 * 1024];
+        write_zip(&apk, &[("AndroidManifest.xml", b"manifest", zip::CompressionMethod::Stored), ("assets/data.bin", &data, zip::CompressionMethod::Stored)], &[]);
+
+        let result = check(&apk, Some(1));
+        assert!(!result.safe);
+        assert!(result.suspicious_entries.is_empty());
+        assert!(check(&apk, None).safe);
+    }
+}
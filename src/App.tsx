@@ -1,12 +1,21 @@
 import { useState, useEffect, useCallback, useRef } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { getCurrentWebview } from "@tauri-apps/api/webview";
 import { open } from "@tauri-apps/plugin-dialog";
+import type { AdbCheck } from "./bindings/AdbCheck";
+import type { AppInfo } from "./bindings/AppInfo";
+import type { Confirmation } from "./bindings/Confirmation";
+import type { DeviceList } from "./bindings/DeviceList";
+import type { LogEvent } from "./bindings/LogEvent";
+import type { ProcessRequest } from "./bindings/ProcessRequest";
+import type { ProcessResult } from "./bindings/ProcessResult";
+import type { SessionContext } from "./bindings/SessionContext";
+import type { SystemRemovalResult } from "./bindings/SystemRemovalResult";
+import type { TrustedPrefix } from "./bindings/TrustedPrefix";
+import type { UninstallResult } from "./bindings/UninstallResult";
 import "./App.css";
 
-interface TrustedPrefix { prefix: string; count: number; source: string; }
-interface ProcessResult { success: boolean; message: string; output_path: string | null; }
-interface AppInfo { package_name: string; app_name: string; version: string; is_system: boolean; }
-
 type LogLevel = "info" | "success" | "error" | "warning" | "verbose";
 type LogMode = "simple" | "verbose";
 type Theme = "light" | "dark";
@@ -90,10 +99,14 @@ function App() {
   // ADB
   const checkAdb = useCallback(async () => {
     try {
-      const connected = await invoke<boolean>("check_adb");
-      setAdbConnected(connected);
-      if (connected) {
-        const list = await invoke<string[]>("get_devices");
+      const adb = await invoke<AdbCheck>("check_adb");
+      setAdbConnected(adb.available);
+      if (!adb.available && adb.error) addLog(adb.error, "warning");
+      if (adb.direct_error) addLog(`${adb.direct_error}，已改用命令行`, "warning");
+      if (adb.available) {
+        const { devices: list, daemon_started } = await invoke<DeviceList>("get_devices");
+        if (daemon_started) addLog("ADB 服务未运行，已自动启动（首次检测较慢）", "info");
+        if (list.length === 0) addLog("未检测到设备，请确认已开启 USB 调试", "warning");
         setDevices(list);
         if (list.length > 0 && !selectedDevice) {
           setSelectedDevice(list[0]);
@@ -170,15 +183,48 @@ function App() {
     }
   };
 
+  const doRemoveSystemApp = async (app: AppInfo) => {
+    const args = { deviceId: selectedDevice, packageName: app.package_name };
+    let reply = await invoke<Confirmation<SystemRemovalResult>>("remove_system_app", args);
+    if (reply.status === "confirmation_required") {
+      reply = await invoke<Confirmation<SystemRemovalResult>>("remove_system_app", { ...args, confirmationToken: reply.token });
+    }
+    if (reply.status !== "done" || reply.result.outcome === "failed") {
+      addLog(`卸载失败: ${reply.status === "done" ? reply.result.disable_output ?? "" : ""}`, "error");
+    } else if (reply.result.outcome === "removed") {
+      addLog(`${app.app_name} 已为当前用户卸载`, "success");
+      setInstalledApps(prev => prev.filter(a => a.package_name !== app.package_name));
+    } else {
+      addLog(`${app.app_name} 无法卸载，已停用`, "warning");
+      setInstalledApps(prev => prev.map(a => a.package_name === app.package_name ? { ...a, enabled: false } : a));
+    }
+  };
+
   const doUninstall = async (app: AppInfo) => {
     addLog(`正在卸载 ${app.package_name}...`, "info");
     try {
-      const success = await invoke<boolean>("uninstall_app", { deviceId: selectedDevice, packageName: app.package_name });
-      if (success) {
+      if (app.is_system) {
+        await doRemoveSystemApp(app);
+        setDeleteConfirm({ app: null, step: 0, inputValue: "" });
+        return;
+      }
+      const args = { deviceId: selectedDevice, packageName: app.package_name };
+      // 对话框中已完成确认，取得令牌后立即执行
+      let reply = await invoke<Confirmation<UninstallResult>>("uninstall_app", args);
+      if (reply.status === "confirmation_required") {
+        addLog(`确认卸载: ${reply.summary.device_alias ?? reply.summary.device_serial} 上 ${reply.summary.package_count} 个应用，数据将被清除`, "verbose");
+        reply = await invoke<Confirmation<UninstallResult>>("uninstall_app", { ...args, confirmationToken: reply.token });
+      }
+      if (reply.status !== "done") {
+        addLog(`卸载失败`, "error");
+      } else if (reply.result.success) {
         addLog(`${app.app_name} 卸载成功`, "success");
         setInstalledApps(prev => prev.filter(a => a.package_name !== app.package_name));
       } else {
-        addLog(`卸载失败`, "error");
+        addLog(`卸载失败: ${reply.result.message ?? reply.result.output}`, "error");
+        if (reply.result.hint) {
+          addLog(reply.result.hint, "info");
+        }
       }
     } catch (e) {
       addLog(`卸载失败: ${e}`, "error");
@@ -186,11 +232,78 @@ function App() {
     setDeleteConfirm({ app: null, step: 0, inputValue: "" });
   };
 
+  // 停用不会清除数据，便于测试伪装版本时暂时隐藏原应用
+  const toggleEnabled = async (app: AppInfo) => {
+    try {
+      await invoke("set_app_enabled", { deviceId: selectedDevice, packageName: app.package_name, enabled: !app.enabled });
+      addLog(`${app.app_name} 已${app.enabled ? "停用" : "启用"}`, "success");
+      setInstalledApps(prev => prev.map(a => a.package_name === app.package_name ? { ...a, enabled: !app.enabled } : a));
+    } catch (e) {
+      addLog(`${app.enabled ? "停用" : "启用"}失败: ${e}`, "error");
+    }
+  };
+
   const cancelUninstall = () => {
     setDeleteConfirm({ app: null, step: 0, inputValue: "" });
   };
 
+  // 恢复上次的界面状态（设备、APK、前缀、应用列表滚动位置）
+  const sessionLoadedRef = useRef(false);
+  const appsGridRef = useRef<HTMLDivElement>(null);
+  const appsScrollRef = useRef(0);
+  const saveTimerRef = useRef<number>();
+  useEffect(() => {
+    invoke<SessionContext>("load_session_context").then(({ context, dropped }) => {
+      if (context) {
+        if (typeof context.device_id === "string" && context.device_id) setSelectedDevice(context.device_id);
+        if (typeof context.apk_path === "string" && context.apk_path) {
+          setApkPath(context.apk_path);
+          setApkName(context.apk_path.split(/[/\\]/).pop() || "");
+        }
+        if (typeof context.new_prefix === "string" && context.new_prefix) setPackagePrefix(context.new_prefix);
+        if (typeof context.custom_suffix === "string" && context.custom_suffix) {
+          setCustomSuffix(context.custom_suffix);
+          setUseCustomSuffix(true);
+        }
+        if (context.active_view === "disguise" || context.active_view === "apps" || context.active_view === "settings") setActiveView(context.active_view);
+        if (typeof context.apps_scroll === "number") appsScrollRef.current = context.apps_scroll;
+        addLog("已恢复上次的界面状态", "info");
+      }
+      dropped.forEach(d => addLog(`未恢复 ${d}`, "warning"));
+    }).catch(e => console.error("Session restore error:", e)).finally(() => { sessionLoadedRef.current = true; });
+  }, [addLog]);
+
+  const sessionRef = useRef<Record<string, unknown>>({});
+  sessionRef.current = {
+    device_id: selectedDevice,
+    apk_path: apkPath,
+    new_prefix: packagePrefix,
+    custom_suffix: useCustomSuffix ? customSuffix : null,
+    active_view: activeView,
+  };
+  const scheduleSessionSave = useCallback(() => {
+    if (!sessionLoadedRef.current) return;
+    window.clearTimeout(saveTimerRef.current);
+    saveTimerRef.current = window.setTimeout(() => {
+      invoke("save_session_context", { context: { ...sessionRef.current, apps_scroll: appsScrollRef.current } })
+        .catch(e => console.error("Session save error:", e));
+    }, 1000);
+  }, []);
+  useEffect(scheduleSessionSave, [selectedDevice, apkPath, packagePrefix, customSuffix, useCustomSuffix, activeView, scheduleSessionSave]);
+
+  // 应用列表加载后恢复滚动位置
+  useEffect(() => {
+    if (activeView === "apps" && installedApps.length > 0 && appsGridRef.current && appsScrollRef.current) {
+      appsGridRef.current.scrollTop = appsScrollRef.current;
+    }
+  }, [activeView, installedApps]);
+
   useEffect(() => { checkAdb(); }, [checkAdb]);
+  useEffect(() => {
+    invoke<{ portable_error: string | null }>("get_app_paths")
+      .then(paths => { if (paths.portable_error) addLog(paths.portable_error, "warning"); })
+      .catch(() => {});
+  }, [addLog]);
   useEffect(() => { if (selectedDevice) scanPrefixes(); }, [selectedDevice, scanPrefixes]);
 
   // 当选择 APK 时，自动生成默认后缀
@@ -215,6 +328,33 @@ function App() {
     } catch (e) { addLog(`选择失败: ${e}`, "error"); }
   };
 
+  // 工具输出：逐批显示并确认，后端在积压时只推送省略行数
+  useEffect(() => {
+    const unlisten = listen<LogEvent>("process-log", ({ payload }) => {
+      payload.lines.forEach(line => addLog(line, "verbose"));
+      if (payload.omitted > 0) addLog(`… ${payload.omitted} 行已省略，完整内容见运行日志`, "warning");
+      invoke("ack_log_events", { jobId: payload.job_id, seq: payload.seq }).catch(() => {});
+    });
+    return () => { unlisten.then(f => f()); };
+  }, [addLog]);
+
+  // 拖放：立即交给后端登记（macOS 沙盒下只有此时可读），之后使用返回的路径
+  useEffect(() => {
+    const unlisten = getCurrentWebview().onDragDropEvent(async (event) => {
+      if (event.payload.type !== "drop") return;
+      const dropped = event.payload.paths.find(p => p.toLowerCase().endsWith(".apk"));
+      if (!dropped) { addLog("请拖入 .apk 文件", "error"); return; }
+      try {
+        const path = await invoke<string>("register_dropped_file", { path: dropped });
+        setApkPath(path);
+        const name = dropped.split(/[/\\]/).pop() || "unknown.apk";
+        setApkName(name);
+        addLog(`已选择: ${name}`, "success");
+      } catch (e) { addLog(`读取拖入的文件失败: ${e}`, "error"); }
+    });
+    return () => { unlisten.then(f => f()); };
+  }, [addLog]);
+
   // Process
   const handleProcess = async () => {
     if (!apkPath) { addLog("请先选择 APK 文件", "error"); return; }
@@ -235,21 +375,33 @@ function App() {
       setProgress(10);
       addLog("[1/6] 反编译 APK...", "verbose");
 
-      const result = await invoke<ProcessResult>("process_apk_full", {
-        apkPath,
-        newPrefix: packagePrefix,
-        customSuffix: finalSuffix,
-        deviceId: installAfter && selectedDevice ? selectedDevice : null,
-        installAfter,
-        javaPath,
-        apktoolPath,
-        zipalignPath,
-        apksignerPath,
-        keystorePath,
-      });
+      const request: ProcessRequest = {
+        apk_path: apkPath,
+        new_prefix: packagePrefix,
+        custom_suffix: finalSuffix,
+        device_id: installAfter && selectedDevice ? selectedDevice : null,
+        install_after: installAfter,
+        java_path: javaPath,
+        apktool_path: apktoolPath,
+        zipalign_path: zipalignPath,
+        apksigner_path: apksignerPath,
+        keystore_path: keystorePath,
+      };
+      let result = await invoke<ProcessResult>("process_apk_full", { request });
+      // 签名证书与历史记录不一致时需要确认
+      if (result.confirmation_token && window.confirm(result.message)) {
+        result = await invoke<ProcessResult>("process_apk_full", { request: { ...request, confirmation_token: result.confirmation_token } });
+      }
       setProgress(100);
-      addLog(result.message, result.success ? "success" : "error");
+      const level: LogLevel = result.status === "success" ? "success" : result.status === "failed" ? "error" : "warning";
+      addLog(result.message, level);
       if (result.output_path) addLog(`输出: ${result.output_path}`, "verbose");
+      // 新包名曾分配给其他应用：预填第一个候选后缀，由用户确认后重新处理
+      if (result.suggested_suffixes?.length) {
+        addLog(`可用后缀: ${result.suggested_suffixes.join("、")}`, "info");
+        setCustomSuffix(result.suggested_suffixes[0]);
+        setUseCustomSuffix(true);
+      }
     } catch (e) { addLog(`失败: ${e}`, "error"); }
     finally {
       setProcessing(false);
@@ -464,7 +616,7 @@ function App() {
               </div>
             )}
 
-            <div className="apps-grid">
+            <div className="apps-grid" ref={appsGridRef} onScroll={e => { appsScrollRef.current = e.currentTarget.scrollTop; scheduleSessionSave(); }}>
               {filteredApps.length === 0 ? (
                 <div className="empty">
                   <div className="empty-icon">📱</div>
@@ -478,10 +630,14 @@ function App() {
                     <div className="app-name">
                       {a.app_name}
                       {a.is_system && <span className="app-badge">系统</span>}
+                      {!a.enabled && <span className="app-badge">已停用</span>}
                     </div>
                     <div className="app-pkg">{a.package_name}</div>
                   </div>
                   <div className="app-btns">
+                    <button className="btn-sm" onClick={() => toggleEnabled(a)}>
+                      {a.enabled ? "停用" : "启用"}
+                    </button>
                     <button className="btn-sm danger" onClick={() => startUninstall(a)}>
                       卸载
                     </button>
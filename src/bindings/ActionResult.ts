@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 单个动作的执行结果
+ */
+export type ActionResult = { action: string, success: boolean, message: string, };
@@ -0,0 +1,23 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PolicyDocument } from "./PolicyDocument";
+
+/**
+ * 当前生效的策略及其来源
+ */
+export type ActivePolicy = { policy: PolicyDocument, 
+/**
+ * 本地路径或 HTTPS 地址
+ */
+source: string, 
+/**
+ * 获取时间（Unix 时间戳，秒）
+ */
+fetched_at: number, 
+/**
+ * 最近一次获取失败，当前使用的是缓存
+ */
+from_cache: boolean, 
+/**
+ * 最近一次获取失败的原因
+ */
+last_error: string | null, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ApiUsage } from "./ApiUsage";
+
+/**
+ * 按代码实际使用的 API 推断的最低系统版本
+ */
+export type ActualMinSdkInfo = { declared_min_sdk: number, detected_min_sdk: number, api_calls_requiring_higher: Array<ApiUsage>, };
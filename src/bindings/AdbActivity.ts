@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Transfer } from "./Transfer";
+
+/**
+ * adb 当前的活动
+ */
+export type AdbActivity = { transfers: Array<Transfer>, 
+/**
+ * 正在等待或执行的全局操作
+ */
+server_operation: string | null, };
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AdbProgram } from "./AdbProgram";
+
+/**
+ * `check_adb` 的结果
+ */
+export type AdbCheck = { available: boolean, program: AdbProgram, 
+/**
+ * `adb version` 的第一行，如 `Android Debug Bridge version 1.0.41`
+ */
+version: string | null, error: string | null, 
+/**
+ * 开启直连时最近一次直连失败的原因，此后的查询已改用命令行
+ */
+direct_error: string | null, };
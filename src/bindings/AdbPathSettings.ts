@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 用户指定的 adb 路径设置
+ */
+export type AdbPathSettings = { 
+/**
+ * 为空时依次使用内置工具目录中的 adb 与 PATH 中的 adb
+ */
+path: string | null, };
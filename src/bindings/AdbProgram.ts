@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 当前使用的 adb 可执行文件
+ */
+export type AdbProgram = { path: string, 
+/**
+ * `custom`（用户指定）、`bundled`（内置工具目录）或 `path`（PATH 中查找）
+ */
+source: string, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * adb 传输层设置
+ */
+export type AdbTransportSettings = { 
+/**
+ * 高频只读查询直连 adb server
+ */
+direct: boolean, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SecretSource } from "./SecretSource";
+
+/**
+ * 某个别名的密钥口令来源
+ */
+export type AliasPassword = { alias: string, source: SecretSource, };
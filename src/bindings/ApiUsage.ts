@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 引入版本高于声明 minSdk 的 API 调用
+ */
+export type ApiUsage = { 
+/**
+ * smali 类型描述符，如 `Landroid/app/NotificationChannel;`
+ */
+class_name: string, 
+/**
+ * 方法或字段名
+ */
+method: string, min_sdk_required: number, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * APK 中的单个 ZIP 条目
+ */
+export type ApkEntry = { name: string, size: number, compressed_size: number, 
+/**
+ * 压缩方式，如 `Stored`、`Deflated`
+ */
+compression: string, is_dir: boolean, 
+/**
+ * 条目名包含 `..`、绝对路径等可能逃逸解压目录的写法
+ */
+unsafe_path: boolean, };
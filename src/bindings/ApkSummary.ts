@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 一个 APK 的分析结果
+ */
+export type ApkSummary = { path: string, size: number, 
+/**
+ * 修改时间（Unix 时间戳，秒）
+ */
+mtime: number, sha256: string | null, package: string | null, version_name: string | null, version_code: number | null, 
+/**
+ * 读取或解析失败的原因
+ */
+error: string | null, 
+/**
+ * 是否来自缓存
+ */
+cached: boolean, };
@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 单个已安装应用的详细信息（`dumpsys package`）
+ */
+export type AppDetails = { package_name: string, version_name: string | null, version_code: number | null, 
+/**
+ * 设备本地时间，如 `2024-01-02 10:11:12`
+ */
+first_install_time: string | null, last_update_time: string | null, target_sdk: number | null, min_sdk: number | null, 
+/**
+ * 安装来源，如 `com.android.vending`；adb 安装时为空
+ */
+installer: string | null, 
+/**
+ * `codePath`：APK 所在目录
+ */
+code_path: string | null, 
+/**
+ * `pm path` 给出的 APK 文件，base.apk 在前
+ */
+apk_paths: Array<string>, };
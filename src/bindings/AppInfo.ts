@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type AppInfo = { package_name: string, app_name: string, version: string, is_system: boolean, 
+/**
+ * 设备在 `pm list packages` 中给出 uid 时可用，便于与 logcat 对照
+ */
+uid: number | null, 
+/**
+ * 未被停用；停用（`pm disable-user`）的多为只能停用而无法卸载的系统应用
+ */
+enabled: boolean, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 所有持久化数据的统一路径解析，作为托管状态供各功能查询
+ */
+export type AppPaths = { portable: boolean, 
+/**
+ * 便携模式下为可执行文件所在目录，否则为应用数据目录
+ */
+root: string, config_dir: string, data_dir: string, cache_dir: string, log_dir: string, tools_dir: string, 
+/**
+ * 请求了便携模式但无法启用时的原因
+ */
+portable_error: string | null, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 一次替换的结果
+ */
+export type AssetChange = { entry_path: string, 
+/**
+ * 原条目大小，新建的条目为空
+ */
+old_size: number | null, new_size: number, };
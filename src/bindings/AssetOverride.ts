@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AssetSource } from "./AssetSource";
+
+/**
+ * 处理时替换 APK 中的一个资源文件
+ */
+export type AssetOverride = { 
+/**
+ * APK 内路径，只能位于 `assets/` 或 `res/raw/` 下
+ */
+entry_path: string, source: AssetSource, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 替换内容的来源
+ */
+export type AssetSource = { "LocalFile": string } | { "InlineText": string };
@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 一条审计记录：只记录会改变设备或设置状态的操作
+ */
+export type AuditEntry = { 
+/**
+ * Unix 时间戳（秒）
+ */
+timestamp: number, operator: string | null, operation: string, 
+/**
+ * 参数摘要，如设备、包名
+ */
+params: Record<string, unknown>, success: boolean, 
+/**
+ * 失败原因或结果说明
+ */
+detail: string | null, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 查询条件，均为可选
+ */
+export type AuditFilter = { operator: string | null, operation: string | null, 
+/**
+ * 匹配参数中的 `device`
+ */
+device: string | null, from: number | null, to: number | null, };
@@ -0,0 +1,20 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JobStatus } from "./JobStatus";
+import type { ResultStatus } from "./ResultStatus";
+
+/**
+ * 批处理中的一项
+ */
+export type BatchItem = { apk_path: string, 
+/**
+ * 开始执行后才分配；重复任务为已有任务的 ID
+ */
+job_id: string | null, status: JobStatus, message: string | null, output_path: string | null, 
+/**
+ * 失败时所在的步骤
+ */
+step: string | null, warnings: Array<string>, 
+/**
+ * 处理结果的状态，完成后才有值
+ */
+result_status: ResultStatus | null, };
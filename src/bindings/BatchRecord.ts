@@ -0,0 +1,13 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BatchItem } from "./BatchItem";
+import type { DigestEntry } from "./DigestEntry";
+import type { JobStatus } from "./JobStatus";
+
+/**
+ * 一次批处理及各项的状态，每次状态变化都会写入磁盘
+ */
+export type BatchRecord = { id: string, created_at: number, status: JobStatus, items: Array<BatchItem>, 
+/**
+ * 跨项合并的失败与警告，失败在前
+ */
+summary: Array<DigestEntry>, };
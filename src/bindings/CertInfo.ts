@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 证书链中的一张证书
+ */
+export type CertInfo = { subject: string, issuer: string, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 密钥库中的一个证书
+ */
+export type CertificateEntry = { alias: string, sha256: string | null, };
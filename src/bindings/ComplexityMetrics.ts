@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 不借助外部工具即可得到的 APK 复杂度指标
+ */
+export type ComplexityMetrics = { dex_method_count: number, dex_class_count: number, string_count: number, resource_count: number, native_methods: number, multidex: boolean, };
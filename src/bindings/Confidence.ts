@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 预估的可信程度
+ */
+export type Confidence = "high" | "medium" | "low" | "none";
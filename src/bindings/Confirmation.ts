@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DestructiveSummary } from "./DestructiveSummary";
+
+/**
+ * 破坏性操作的返回：首次调用返回令牌，带令牌再次调用才真正执行
+ */
+export type Confirmation<T> = { "status": "confirmation_required", token: string, summary: DestructiveSummary, expires_in_secs: number, } | { "status": "done", result: T, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * `adb connect` 的结果
+ */
+export type ConnectResult = { connected: boolean, 
+/**
+ * 连接前就已连接
+ */
+already_connected: boolean, 
+/**
+ * 设备在 `get_devices` 中的 ID（`host:port`）
+ */
+device_id: string, message: string, };
@@ -0,0 +1,20 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CertInfo } from "./CertInfo";
+import type { ResolvedProxy } from "./ResolvedProxy";
+
+/**
+ * `test_connectivity` 的诊断结果
+ */
+export type ConnectivityReport = { url: string, proxy: ResolvedProxy, 
+/**
+ * 建立 TCP 连接（含代理隧道）耗时
+ */
+connect_ms: number | null, tls_ms: number | null, 
+/**
+ * 完整请求耗时
+ */
+request_ms: number | null, status: number | null, 
+/**
+ * 服务器返回的证书链，从服务器证书到上级
+ */
+tls_chain: Array<CertInfo>, error: string | null, };
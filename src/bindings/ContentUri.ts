@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 应用暴露的 ContentProvider URI
+ */
+export type ContentUri = { authority: string, path_pattern: string | null, read_permission: string | null, write_permission: string | null, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 本地控制接口设置
+ */
+export type ControlSettings = { enabled: boolean, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 本地控制接口的当前状态
+ */
+export type ControlStatus = { enabled: boolean, running: boolean, 
+/**
+ * 套接字路径或命名管道名
+ */
+endpoint: string | null, token_file: string, 
+/**
+ * 启动或监听失败的原因
+ */
+error: string | null, };
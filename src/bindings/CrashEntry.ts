@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 设备上记录的一次崩溃
+ */
+export type CrashEntry = { 
+/**
+ * dropbox 记录时间，如 `2024-01-15 10:23:45`
+ */
+timestamp: string, 
+/**
+ * Java 异常类名；native 崩溃为信号名，如 `SIGSEGV`
+ */
+exception_class: string, message: string | null, stack_trace: string, };
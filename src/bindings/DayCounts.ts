@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 当天的处理次数
+ */
+export type DayCounts = { 
+/**
+ * 当天开始的 Unix 时间戳（秒）
+ */
+since: number, succeeded: number, failed: number, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 打包好的交付文件
+ */
+export type Deliverable = { zip_path: string, sha256: string, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 破坏性操作将影响的范围，供用户确认
+ */
+export type DestructiveSummary = { operation: string, device_serial: string, device_alias: string | null, package_count: number, 
+/**
+ * 是否会丢失应用数据
+ */
+data_loss: boolean, 
+/**
+ * 需要用户了解的详细说明
+ */
+detail: string | null, };
@@ -0,0 +1,27 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Userland } from "./Userland";
+
+/**
+ * 设备支持的功能
+ */
+export type DeviceCapabilities = { sdk: number | null, 
+/**
+ * `adb features` 列出的功能（如 `shell_v2`、`cmd`、`abb_exec`）
+ */
+adb_features: Array<string>, 
+/**
+ * 设备上有 `cmd` 命令（Android 7 起）
+ */
+has_cmd: boolean, 
+/**
+ * `cmd package` 可以调用；部分定制系统有 `cmd` 但限制了 package 服务
+ */
+cmd_package: boolean, exec_out: boolean, 
+/**
+ * 支持 `pm install-create` 安装会话
+ */
+install_sessions: boolean, 
+/**
+ * 支持 `logcat --pid`
+ */
+logcat_pid: boolean, userland: Userland, };
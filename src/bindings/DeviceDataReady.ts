@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 单个数据集预取完成事件
+ */
+export type DeviceDataReady = { device_id: string, dataset: string, generation: number, duration_ms: number, error: string | null, };
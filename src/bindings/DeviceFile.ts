@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 设备上的一个文件或目录
+ */
+export type DeviceFile = { name: string, size: number, 
+/**
+ * 目录，或指向目录的符号链接（如 /sdcard -> /storage/self/primary）
+ */
+is_dir: boolean, 
+/**
+ * `ls` 显示的修改时间，格式随设备而异
+ */
+mtime: string, };
@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * `adb devices -l` 中的一台设备
+ */
+export type DeviceInfo = { id: string, 
+/**
+ * `device`、`unauthorized`（需在设备上允许 USB 调试）、`offline` 等
+ */
+state: string, model: string | null, product: string | null, 
+/**
+ * `usb`、`tcp`（无线调试）或 `emulator`
+ */
+transport: string | null, 
+/**
+ * `ro.build.version.release`，仅状态为 device 时读取
+ */
+android_version: string | null, 
+/**
+ * `ro.product.cpu.abi`
+ */
+abi: string | null, };
@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DeviceInfo } from "./DeviceInfo";
+
+/**
+ * 设备列表查询结果
+ */
+export type DeviceList = { 
+/**
+ * 状态为 device 的序列号
+ */
+devices: Array<string>, 
+/**
+ * 所有设备（含 unauthorized / offline）及其型号等信息
+ */
+details: Array<DeviceInfo>, 
+/**
+ * 本次查询启动了 adb server（首次查询较慢的原因）
+ */
+daemon_started: boolean, };
@@ -0,0 +1,20 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { InstallFlags } from "./InstallFlags";
+import type { PostInstallAction } from "./PostInstallAction";
+
+/**
+ * 单台设备的安装偏好
+ */
+export type DevicePreferences = { 
+/**
+ * 显示名（如“扫描枪 7 号”）
+ */
+alias: string | null, install_flags: InstallFlags, 
+/**
+ * 安装前唤醒屏幕，避免确认对话框在熄屏时超时
+ */
+keep_awake: boolean, 
+/**
+ * 在处理参数的安装后动作之后追加执行
+ */
+post_install_actions: Array<PostInstallAction>, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * /data 分区的空间，单位字节
+ */
+export type DeviceStorage = { total_bytes: number, used_bytes: number, free_bytes: number, };
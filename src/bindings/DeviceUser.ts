@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 设备上的用户（`pm list users`），如主用户 0 与工作资料 10
+ */
+export type DeviceUser = { id: number, name: string, running: boolean, };
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DeviceInfo } from "./DeviceInfo";
+
+/**
+ * 设备列表变化事件
+ */
+export type DevicesChanged = { devices: Array<string>, 
+/**
+ * 所有设备及其状态，设备在 device / unauthorized / offline 之间变化时也会推送
+ */
+details: Array<DeviceInfo>, 
+/**
+ * `direct`（server 长连接）、`track`（`adb track-devices` 子进程）或 `poll`（轮询）
+ */
+transport: string, };
@@ -0,0 +1,26 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 摘要中的一组相同问题
+ */
+export type DigestEntry = { 
+/**
+ * `failure` 或 `warning`
+ */
+kind: string, 
+/**
+ * 分组键：失败为步骤名，警告为去掉各项自身路径、文件名与数字后的文本
+ */
+code: string, 
+/**
+ * 第一项的原始信息
+ */
+example: string, 
+/**
+ * 涉及的项（`items` 中的下标）
+ */
+items: Array<number>, 
+/**
+ * 如 `18/20 项: ...`
+ */
+summary: string, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 一个下载的进度；所有进行中的下载分批合并为 `download-progress` 事件
+ */
+export type DownloadProgress = { url: string, path: string, downloaded: number, 
+/**
+ * 服务器返回 Content-Length 时可用
+ */
+total: number | null, percent: number | null, bytes_per_sec: number, done: boolean, };
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DownloadProgress } from "./DownloadProgress";
+
+/**
+ * 一批下载进度（`download-progress` 事件），与处理日志使用相同的分批与降采样
+ */
+export type DownloadProgressEvent = { 
+/**
+ * 处理完后用 `ack_download_events` 确认，否则之后的进度只推送省略条数
+ */
+seq: number, 
+/**
+ * 每个下载只保留这一批中的最新进度
+ */
+downloads: Array<DownloadProgress>, omitted: number, };
@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 由历史记录推算的完整处理耗时
+ */
+export type DurationPrediction = { median_ms: number, p90_ms: number, 
+/**
+ * 参与推算的历史处理次数
+ */
+runs: number, 
+/**
+ * 是否只用了同一大小分组的记录；为 false 时借用了其他分组，准确度较低
+ */
+same_bucket: boolean, 
+/**
+ * 预计包含安装耗时但历史中没有安装记录
+ */
+install_unknown: boolean, };
@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 条目内容，文本以 UTF-8 返回，二进制以 base64 返回
+ */
+export type EntryContent = { name: string, 
+/**
+ * 条目解压后的完整大小
+ */
+size: number, 
+/**
+ * `text` 或 `base64`
+ */
+encoding: string, content: string, 
+/**
+ * 内容超过读取上限被截断
+ */
+truncated: boolean, };
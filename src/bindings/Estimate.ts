@@ -0,0 +1,33 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Confidence } from "./Confidence";
+import type { DurationPrediction } from "./DurationPrediction";
+import type { RiskLevel } from "./RiskLevel";
+
+/**
+ * 处理前的预估
+ */
+export type Estimate = { apk_size_bytes: number, 
+/**
+ * 预计耗时；没有历史记录时为 null
+ */
+duration: DurationPrediction | null, duration_confidence: Confidence, 
+/**
+ * 工作目录与中间产物的峰值占用
+ */
+temp_space_bytes: number, 
+/**
+ * 工作目录所在磁盘的可用空间，无法读取时为 null
+ */
+available_space_bytes: number | null, output_size_bytes: number, risk: RiskLevel, 
+/**
+ * 风险来源，如「检测到 360 加固」「2 个 ContentProvider 需要改写」
+ */
+risk_factors: Array<string>, packer: string | null, provider_count: number, 
+/**
+ * 影响可信程度的说明，如「该大小分组没有历史记录」
+ */
+notes: Array<string>, 
+/**
+ * 本次预估自身的耗时
+ */
+elapsed_ms: number, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 预估所需的处理选项，与 `ProcessConfig` 中的同名字段含义相同
+ */
+export type EstimateOptions = { install_after: boolean, strip_debug_info: boolean, stealth_output: boolean, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { InjectionRule } from "./InjectionRule";
+
+/**
+ * 故障注入设置，可保存为 JSON 场景文件（见 fixtures/failure-injection）
+ */
+export type FailureInjection = { enabled: boolean, rules: Array<InjectionRule>, };
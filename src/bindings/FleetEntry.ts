@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FleetStatus } from "./FleetStatus";
+
+/**
+ * 一台设备上的一个应用
+ */
+export type FleetEntry = { device_id: string, package: string, installed_version_code: number | null, 
+/**
+ * 最新一次处理输出的 versionCode
+ */
+latest_version_code: number | null, output_path: string, status: FleetStatus, };
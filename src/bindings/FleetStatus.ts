@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 某台设备上某个应用的状态
+ */
+export type FleetStatus = "up_to_date" | "outdated" | "not_installed" | "output_missing" | "output_modified";
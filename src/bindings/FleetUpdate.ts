@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 对一台设备执行更新的结果
+ */
+export type FleetUpdate = { device_id: string, 
+/**
+ * `installed`、`skipped` 或 `failed`
+ */
+outcome: string, message: string, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ApkSummary } from "./ApkSummary";
+
+/**
+ * 一批分析结果
+ */
+export type FolderScanBatch = { scan_id: string, entries: Array<ApkSummary>, completed: number, total: number, };
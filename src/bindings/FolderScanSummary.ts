@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 一次扫描的汇总
+ */
+export type FolderScanSummary = { scan_id: string, total: number, cached: number, analyzed: number, failed: number, cancelled: boolean, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 导出依赖图的统计信息
+ */
+export type GraphStats = { node_count: number, edge_count: number, strongly_connected_components: number, output_size_bytes: number, };
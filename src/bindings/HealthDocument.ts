@@ -0,0 +1,17 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DayCounts } from "./DayCounts";
+import type { LastJob } from "./LastJob";
+import type { TrackerSnapshot } from "./TrackerSnapshot";
+
+/**
+ * `GET /health` 返回的文档
+ */
+export type HealthDocument = { 
+/**
+ * adb 跟踪正常时为 `ok`，否则为 `degraded`
+ */
+status: string, version: string, uptime_secs: number, adb: TrackerSnapshot, connected_devices: number, running_jobs: number, last_job: LastJob | null, today: DayCounts, 
+/**
+ * 残留的工作目录与收件箱占用
+ */
+pending_cleanup_bytes: number, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 健康检查设置，令牌不写入文件而是保存在系统钥匙串
+ */
+export type HealthSettings = { enabled: boolean, 
+/**
+ * 监听地址，默认 127.0.0.1；设为局域网地址时监控机才能访问
+ */
+bind_address: string, port: number, 
+/**
+ * 计算「当天」使用的时区，相对 UTC 的分钟数（东八区为 480）
+ */
+utc_offset_minutes: number, };
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { HealthSettings } from "./HealthSettings";
+
+/**
+ * 健康检查的当前状态
+ */
+export type HealthStatus = { settings: HealthSettings, running: boolean, 
+/**
+ * 正在监听的地址，如 `127.0.0.1:8765`
+ */
+endpoint: string | null, token_set: boolean, 
+/**
+ * 启动时按设置开启失败的原因
+ */
+error: string | null, };
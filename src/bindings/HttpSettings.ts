@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 网络设置，代理密码不写入文件而是保存在系统钥匙串
+ */
+export type HttpSettings = { 
+/**
+ * 显式指定的代理（如 `http://proxy.corp:8080`），优先于环境变量与系统设置
+ */
+proxy_url: string | null, proxy_username: string | null, };
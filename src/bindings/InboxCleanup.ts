@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 收件箱清理结果
+ */
+export type InboxCleanup = { removed: number, freed_bytes: number, 
+/**
+ * 仍被排队或进行中的任务引用而保留的条目数
+ */
+kept: number, };
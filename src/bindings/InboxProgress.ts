@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 复制进度（`inbox-progress` 事件）
+ */
+export type InboxProgress = { source: string, copied_bytes: number, total_bytes: number, };
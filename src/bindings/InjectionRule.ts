@@ -0,0 +1,26 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 一条注入规则
+ */
+export type InjectionRule = { 
+/**
+ * 命令行（程序与参数）中需包含的工具名，如 `apksigner`、`adb`
+ */
+tool: string, 
+/**
+ * 命令行中还需包含的子串，如 `install`
+ */
+args_contain: string | null, 
+/**
+ * 只对第 N 次匹配的调用生效（从 1 开始）；为空时每次都生效
+ */
+nth: number | null, 
+/**
+ * 执行前等待的毫秒数
+ */
+delay_ms: number | null, 
+/**
+ * 不执行命令，直接以该退出码返回；为空时延迟后照常执行
+ */
+exit_code: number | null, stdout: string, stderr: string, };
@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * `adb install` 参数；未设置的字段沿用下一层的值
+ */
+export type InstallFlags = { 
+/**
+ * `-d`，默认否
+ */
+allow_downgrade: boolean | null, 
+/**
+ * `-g`，默认是
+ */
+grant_permissions: boolean | null, 
+/**
+ * `-t`，默认是
+ */
+allow_test: boolean | null, 
+/**
+ * `--user`，默认不指定
+ */
+user_id: number | null, };
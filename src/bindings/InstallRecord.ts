@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 设备上的一次安装或卸载
+ */
+export type InstallRecord = { 
+/**
+ * `install` 或 `uninstall`
+ */
+kind: string, device_serial: string, device_alias: string | null, package: string, version_code: number | null, 
+/**
+ * 安装的 APK 的 SHA-256，卸载记录为空
+ */
+apk_sha256: string | null, 
+/**
+ * Unix 时间戳（秒）
+ */
+timestamp: number, operator_note: string | null, 
+/**
+ * 安装的 APK 是通过 `download_apk` 下载时的来源地址
+ */
+source_url: string | null, };
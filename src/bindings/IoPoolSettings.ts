@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * IO 线程池设置，修改后下次启动生效
+ */
+export type IoPoolSettings = { 
+/**
+ * 同时执行的 IO 任务数上限，默认为 CPU 核数（最多 4）
+ */
+workers: number | null, };
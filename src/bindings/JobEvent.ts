@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JobStatus } from "./JobStatus";
+
+/**
+ * 推送给前端的任务状态事件（`job-status`）；批处理的子任务带有 `parent_job_id`
+ */
+export type JobEvent = { job_id: string, parent_job_id: string | null, status: JobStatus, message: string | null, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 任务状态
+ */
+export type JobStatus = "queued" | "running" | "succeeded" | "failed" | "cancelled" | "duplicate" | "interrupted";
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JobStatus } from "./JobStatus";
+
+/**
+ * 任务列表中的一项
+ */
+export type JobSummary = { job_id: string, parent_job_id: string | null, status: JobStatus, 
+/**
+ * 进行中任务的源 APK
+ */
+apk_path: string | null, message: string | null, };
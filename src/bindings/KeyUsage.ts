@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 某个证书签过某个原包名的记录
+ */
+export type KeyUsage = { original_package: string, 
+/**
+ * 证书 SHA-256 指纹（keytool 格式，`AB:CD:...`）
+ */
+fingerprint: string, keystore_path: string, 
+/**
+ * 密钥库中的别名（旧记录没有该字段）
+ */
+alias: string | null, customer: string | null, 
+/**
+ * 最近一次使用的 Unix 时间戳（秒）
+ */
+timestamp: number, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 最近一次处理的结果
+ */
+export type LastJob = { timestamp: number, success: boolean, message: string | null, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 单个第三方库的许可证信息
+ */
+export type LicenseNotice = { library_name: string, 
+/**
+ * 识别出的许可证类型，如 `Apache-2.0`，无法识别时为 `Unknown`
+ */
+license_type: string, 
+/**
+ * 许可证原文；从 DEX 推断的条目为检测依据
+ */
+text: string, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 资源目录中出现的语言 / 地区限定符
+ */
+export type LocaleInfo = { 
+/**
+ * 目录名中的原始限定符，如 `zh-rCN`、`b+sr+Latn`
+ */
+qualifier: string, language: string, region: string | null, resource_file_count: number, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 推送给前端的一批工具输出（`process-log` 事件）
+ */
+export type LogEvent = { job_id: string, 
+/**
+ * 处理完后用 `ack_log_events` 确认，否则之后的输出只推送省略行数
+ */
+seq: number, lines: Array<string>, 
+/**
+ * 省略的行数，完整内容见运行日志文件（`get_log_tail`）
+ */
+omitted: number, };
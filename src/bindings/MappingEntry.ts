@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SigningIdentity } from "./SigningIdentity";
+
+/**
+ * 一次处理的包名映射与输出位置
+ */
+export type MappingEntry = { job_id: string | null, original_package: string | null, package: string, output_path: string, output_sha256: string, timestamp: number, signing_identity: SigningIdentity | null, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 目标市场推断结果
+ */
+export type MarketDetection = { 
+/**
+ * 按置信度从高到低排列的市场代码，如 `cn`、`us`
+ */
+detected_markets: Array<string>, confidence: { [key in string]?: number }, evidence: Array<string>, };
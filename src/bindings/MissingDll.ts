@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 一个找不到的 DLL
+ */
+export type MissingDll = { dll: string, 
+/**
+ * 需要安装的运行库，未知 DLL 为空
+ */
+redistributable: string | null, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MissingDll } from "./MissingDll";
+
+/**
+ * 单个原生工具的检查结果
+ */
+export type NativeToolCheck = { path: string, missing: Array<MissingDll>, 
+/**
+ * 无法读取导入表时的原因
+ */
+error: string | null, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 原包名与新包名的对应关系
+ */
+export type PackageMapping = { original_package: string | null, new_package: string, version_name: string | null, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 开始处理前即可确定的包名
+ */
+export type PackagePlan = { 
+/**
+ * 直接读取二进制 Manifest 得到，读取失败（如加固）时为空
+ */
+original_package: string | null, new_package: string, };
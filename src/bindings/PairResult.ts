@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * `adb pair` 的结果
+ */
+export type PairResult = { 
+/**
+ * 配对成功的地址
+ */
+address: string, 
+/**
+ * adb 输出的设备 GUID（`adb-<serial>-<id>`）
+ */
+guid: string | null, message: string, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 签名 / 流水线操作的结构化错误
+ */
+export type PipelineError = { "kind": "InvalidInput", "detail": string } | { "kind": "Io", "detail": string } | { "kind": "Tool", "detail": { step: string, message: string, } } | { "kind": "DependencyCycle", "detail": { packages: Array<string>, } } | { "kind": "InvalidPath", "detail": { raw: string, normalized: string | null, reason: string, } };
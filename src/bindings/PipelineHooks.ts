@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 各钩子点对应的可执行文件路径
+ */
+export type PipelineHooks = { after_decompile: string | null, before_rebuild: string | null, after_sign: string | null, 
+/**
+ * 单个钩子的超时时间（秒），默认 300
+ */
+timeout_secs: number | null, };
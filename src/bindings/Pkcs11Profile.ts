@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SecretSource } from "./SecretSource";
+
+/**
+ * PKCS#11 签名配置
+ */
+export type Pkcs11Profile = { 
+/**
+ * SunPKCS11 配置文件（含 `name` 与 `library`），其中的 slot 设置会被 `slot` 覆盖
+ */
+config_path: string, slot: number | null, pin_source: SecretSource, 
+/**
+ * 令牌中签名证书的别名
+ */
+cert_alias: string, };
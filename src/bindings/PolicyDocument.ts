@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 策略内容
+ */
+export type PolicyDocument = { version: string, 
+/**
+ * 推荐的前缀，合并进 `scan_trusted_prefixes`
+ */
+recommended_prefixes: Array<string>, 
+/**
+ * 禁用的前缀，新包名以其开头时拒绝处理
+ */
+banned_prefixes: Array<string>, 
+/**
+ * 允许使用的签名密钥库文件名；为空时不限制
+ */
+signing_profiles: Array<string>, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 安装成功后执行的单个动作，可随预设一起保存
+ */
+export type PostInstallAction = { "type": "PushFile", local: string, remote_template: string, } | { "type": "Shell", command: string, } | { "type": "PutSetting", namespace: string, key: string, value: string, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 企业部署时固定使用的包名前缀
+ */
+export type PrefixLock = { prefix: string, description: string, };
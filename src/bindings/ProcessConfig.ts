@@ -0,0 +1,97 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AliasPassword } from "./AliasPassword";
+import type { AssetOverride } from "./AssetOverride";
+import type { InstallFlags } from "./InstallFlags";
+import type { PipelineHooks } from "./PipelineHooks";
+import type { Pkcs11Profile } from "./Pkcs11Profile";
+import type { PostInstallAction } from "./PostInstallAction";
+import type { ToolPins } from "./ToolPins";
+
+/**
+ * 一次完整处理所需的全部参数
+ */
+export type ProcessConfig = { apk_path: string, new_prefix: string, custom_suffix?: string | null, device_id?: string | null, install_after: boolean, java_path: string, apktool_path: string, zipalign_path: string, apksigner_path: string, keystore_path: string, hydrate_source?: boolean | null, post_install_actions?: Array<PostInstallAction> | null, 
+/**
+ * 签名因密钥库兼容性失败时，自动转换为 PKCS12 后重试一次
+ */
+auto_convert_keystore?: boolean | null, 
+/**
+ * 步骤之间运行的用户钩子
+ */
+hooks?: PipelineHooks | null, 
+/**
+ * 需要确保声明的权限
+ */
+required_permissions?: Array<string> | null, 
+/**
+ * 将危险权限替换为较低权限的替代
+ */
+downgrade_permissions?: boolean | null, 
+/**
+ * 显式指定的 minSdkVersion / targetSdkVersion，不指定则保持原值
+ */
+min_sdk_override?: number | null, target_sdk_override?: number | null, 
+/**
+ * 在结果中附带 ContentProvider URI 报告
+ */
+content_uri_report?: boolean | null, 
+/**
+ * 安装前备份已安装版本，新版本启动失败时自动恢复
+ */
+rollback_on_launch_failure?: boolean | null, 
+/**
+ * 回编译后去除 DEX 中的调试信息
+ */
+strip_debug_info?: boolean | null, 
+/**
+ * 回编译前替换的资源文件
+ */
+asset_overrides?: Array<AssetOverride> | null, 
+/**
+ * apktool 资源类警告超过该数量时结果标记为带警告完成，默认 20
+ */
+apktool_warning_threshold?: number | null, 
+/**
+ * 固定使用的工具版本，优先于上面的工具路径
+ */
+tool_versions?: ToolPins | null, 
+/**
+ * 前端未确认的日志批次达到该数量后只推送省略行数，默认 20
+ */
+log_queue_depth?: number | null, 
+/**
+ * 客户标签，用于检查签名证书是否被跨客户使用
+ */
+customer?: string | null, 
+/**
+ * 回编译输出至少为输入 APK 大小的比例，默认 0.2
+ */
+min_output_ratio?: number | null, 
+/**
+ * 隐匿输出：不使用 `_fixed` 文件名、统一 ZIP 条目时间，结果中列出无法去除的痕迹
+ */
+stealth_output?: boolean | null, 
+/**
+ * 严格模式：资源类警告（含 APKTOOL_DUMMY 占位资源）超过阈值时直接失败，不再安装输出
+ */
+strict_resource_warnings?: boolean | null, 
+/**
+ * `adb install` 参数，显式设置的字段优先于设备偏好
+ */
+install_flags?: InstallFlags | null, 
+/**
+ * 使用 PKCS#11 令牌中的密钥签名，设置后忽略 `keystore_path`
+ */
+pkcs11_signing?: Pkcs11Profile | null, 
+/**
+ * 本次使用密钥库中的哪个别名，不设置时使用默认别名；处理开始前校验别名存在且口令正确
+ */
+key_alias_override?: string | null, 
+/**
+ * 各别名的密钥口令来源，未列出的别名使用默认口令
+ */
+key_passwords?: Array<AliasPassword> | null, 
+/**
+ * 把 res/xml 与 res/layout 中值为原包名的引用改为新包名，默认只报告不修改
+ */
+rewrite_res_xml_references?: boolean | null, };
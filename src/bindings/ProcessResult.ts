@@ -0,0 +1,82 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ActionResult } from "./ActionResult";
+import type { AssetChange } from "./AssetChange";
+import type { ContentUri } from "./ContentUri";
+import type { ResultStatus } from "./ResultStatus";
+import type { RunMetrics } from "./RunMetrics";
+import type { SigningIdentity } from "./SigningIdentity";
+import type { ToolVersion } from "./ToolVersion";
+
+export type ProcessResult = { 
+/**
+ * 结果状态；`success` 与消息中的表情符号仅为兼容旧版前端保留
+ */
+status: ResultStatus, success: boolean, 
+/**
+ * 成功但工具输出的警告超过阈值，前端以警告样式显示
+ */
+success_with_warnings: boolean, message: string, output_path: string | null, 
+/**
+ * 输出文件的大小（字节），成功时填写
+ */
+output_size_bytes: number | null, step: string | null, 
+/**
+ * 安装后动作的执行结果（动作失败不影响安装本身的成功状态）
+ */
+post_install: Array<ActionResult> | null, 
+/**
+ * 各步骤耗时与资源占用
+ */
+metrics: RunMetrics | null, 
+/**
+ * ContentProvider URI 报告（开启 content_uri_report 时）
+ */
+content_uris: Array<ContentUri> | null, 
+/**
+ * 替换的资源文件及其前后大小
+ */
+changes: Array<AssetChange>, 
+/**
+ * 不影响成功状态的警告
+ */
+warnings: Array<string>, 
+/**
+ * 开始处理前确定的新包名，失败的处理也会带上
+ */
+new_package: string | null, 
+/**
+ * 处理方式：`full` 或 `incremental`（仅重写 Manifest 并重新签名）
+ */
+mode: string | null, 
+/**
+ * 密钥库签名时实际使用的别名与证书指纹
+ */
+signing_identity: SigningIdentity | null, 
+/**
+ * 本次实际使用的工具及版本
+ */
+tool_versions: Array<ToolVersion>, 
+/**
+ * 本次任务的 ID
+ */
+job_id: string | null, 
+/**
+ * 与已有任务重复时，指向该任务的 ID
+ */
+duplicate_of: string | null, 
+/**
+ * 需要确认后才能继续时的确认令牌，带上它重新提交即可
+ */
+confirmation_token: string | null, 
+/**
+ * 新包名与历史冲突时推荐的其他后缀
+ */
+suggested_suffixes: Array<string>, 
+/**
+ * 隐匿输出时仍无法去除的痕迹
+ */
+residual_traces: Array<string>, 
+/**
+ * 成功的反编译方式：`full` 或降级后的 `no_res`
+ */
+decode_strategy: string | null, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 处理过程中推送给前端的进度 / 日志事件
+ */
+export type ProgressEvent = { step: string, message: string, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 拉取进度（`device-pull-progress` 事件）
+ */
+export type PullProgress = { remote_path: string, pulled_bytes: number, total_bytes: number, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 从设备拉取的一个已安装 APK
+ */
+export type PulledApk = { remote_path: string, local_path: string, size: number, 
+/**
+ * base.apk（其余为 split APK）
+ */
+is_base: boolean, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 是否已被本工具处理过的检测结果
+ */
+export type RepackagingInfo = { was_repackaged: boolean, 
+/**
+ * 0.0 ~ 1.0
+ */
+confidence: number, indicators: Array<string>, };
@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 一处引用
+ */
+export type ResReference = { 
+/**
+ * 相对工作目录（或 APK 内）的路径，如 `res/xml/shortcuts.xml`
+ */
+file: string, 
+/**
+ * 行号从 1 开始；直接读取 APK 中的二进制 XML 时为空
+ */
+line: number | null, text: string, 
+/**
+ * 值恰好为原包名，开启改写时会替换
+ */
+rewritable: boolean, };
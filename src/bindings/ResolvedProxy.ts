@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 某个地址实际使用的代理
+ */
+export type ResolvedProxy = { 
+/**
+ * 不含认证信息的代理地址，直连时为空
+ */
+url: string | null, 
+/**
+ * `settings`、`env`、`system` 或 `direct`
+ */
+source: string, note: string | null, };
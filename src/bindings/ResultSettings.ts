@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 处理结果的输出设置
+ */
+export type ResultSettings = { 
+/**
+ * 消息中保留 ✅ / ⚠️ 等状态装饰，默认开启
+ */
+legacy_messages: boolean, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 处理结果的状态，前端据此选择图标，不再解析消息文本
+ */
+export type ResultStatus = "success" | "success_with_warnings" | "failed" | "cancelled" | "rolled_back" | "skipped";
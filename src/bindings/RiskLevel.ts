@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 风险等级，按从低到高的顺序声明
+ */
+export type RiskLevel = "low" | "medium" | "high";
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 设备能否以 root 身份运行 adbd
+ */
+export type RootCapability = { 
+/**
+ * `ro.build.type`：user / userdebug / eng
+ */
+build_type: string | null, debuggable: boolean, root_capable: boolean, 
+/**
+ * 不支持时的原因
+ */
+reason: string | null, };
@@ -0,0 +1,17 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Estimate } from "./Estimate";
+import type { StepMetrics } from "./StepMetrics";
+import type { ToolVersion } from "./ToolVersion";
+
+/**
+ * 一次处理的完整指标，同时写入历史记录
+ */
+export type RunMetrics = { timestamp: number, apk_size_bytes: number, total_ms: number, steps: Array<StepMetrics>, 
+/**
+ * 本次实际使用的工具版本（旧记录没有该字段）
+ */
+tool_versions: Array<ToolVersion>, 
+/**
+ * 开始处理前的预估，用于对比预估与实际
+ */
+estimate: Estimate | null, };
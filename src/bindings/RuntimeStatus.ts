@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 线程池的运行状态
+ */
+export type RuntimeStatus = { io_workers: number, queued_interactive: number, queued_background: number, running: number, completed: number, 
+/**
+ * 已开始执行的任务在队列中的平均 / 最长等待时间
+ */
+avg_wait_ms: number, max_wait_ms: number, 
+/**
+ * 正在执行的操作名
+ */
+running_operations: Array<string>, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 删除请求违反安全规则
+ */
+export type SafetyViolation = { path: string, reason: string, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 口令 / PIN 的来源，不支持把明文写进配置。用于密钥库中各别名的密钥口令与 PKCS#11 令牌的 PIN
+ */
+export type SecretSource = { "type": "Env", var: string, } | { "type": "File", path: string, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SelfTestStep } from "./SelfTestStep";
+
+/**
+ * 环境自检报告
+ */
+export type SelfTestReport = { ok: boolean, 
+/**
+ * 第一个失败的步骤
+ */
+failed_step: string | null, steps: Array<SelfTestStep>, total_ms: number, };
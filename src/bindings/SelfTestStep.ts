@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 自检中单个步骤的结果
+ */
+export type SelfTestStep = { step: string, passed: boolean, duration_ms: number, message: string | null, };
@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DeviceList } from "./DeviceList";
+import type { Transfer } from "./Transfer";
+
+/**
+ * adb server 重启结果
+ */
+export type ServerRestart = { 
+/**
+ * 强制重启时被中断的传输
+ */
+interrupted: Array<Transfer>, 
+/**
+ * 重启后的设备列表，界面可直接刷新
+ */
+devices: DeviceList, };
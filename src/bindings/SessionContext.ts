@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 载入的上下文
+ */
+export type SessionContext = { context: Record<string, unknown> | null, 
+/**
+ * 保存时间（Unix 时间戳，秒）
+ */
+saved_at: number | null, 
+/**
+ * 被去掉的字段及原因，供界面提示
+ */
+dropped: Array<string>, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * APK 签名方案校验结果
+ */
+export type SignatureInfo = { v1: boolean, v2: boolean, v3: boolean, signer_sha256: string | null, };
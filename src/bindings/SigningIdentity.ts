@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 实际签名使用的身份。同一密钥库可按别名签出不同身份，记录中需保存别名与证书指纹
+ */
+export type SigningIdentity = { alias: string, cert_sha256: string | null, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 去除调试信息前后的大小
+ */
+export type SizeReduction = { original_bytes: number, new_bytes: number, saved_bytes: number, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 冷启动耗时统计
+ */
+export type StartupBenchmark = { median_ms: number, min_ms: number, max_ms: number, std_dev_ms: number, };
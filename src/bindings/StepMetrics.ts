@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 单个步骤的耗时与资源占用；平台不支持的指标为 null
+ */
+export type StepMetrics = { step: string, duration_ms: number, peak_rss_bytes: number | null, bytes_written: number | null, exit_code: number | null, };
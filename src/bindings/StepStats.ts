@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 按 APK 大小分组的步骤耗时统计
+ */
+export type StepStats = { 
+/**
+ * 分组的显示标签（如 `10-50MB`），保留给旧版前端；排序与作图使用下面的字节数
+ */
+size_bucket: string, 
+/**
+ * 分组的 APK 大小范围 [min, max)，单位字节；最大一组没有上限
+ */
+size_min_bytes: number, size_max_bytes: number | null, step: string, runs: number, median_ms: number, p90_ms: number, max_ms: number, };
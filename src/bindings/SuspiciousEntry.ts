@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 压缩比异常的 ZIP 条目
+ */
+export type SuspiciousEntry = { name: string, compressed_bytes: number, uncompressed_bytes: number, ratio: number, };
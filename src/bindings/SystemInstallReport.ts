@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SystemInstallStep } from "./SystemInstallStep";
+
+/**
+ * 安装为系统应用的结果
+ */
+export type SystemInstallReport = { success: boolean, steps: Array<SystemInstallStep>, 
+/**
+ * 设备上的 APK 路径
+ */
+installed_path: string | null, 
+/**
+ * 需要重启设备才会生效（未要求自动重启时）
+ */
+reboot_required: boolean, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 安装过程中的一个步骤
+ */
+export type SystemInstallStep = { 
+/**
+ * `root`、`remount`、`push_apk`、`push_permissions`、`reboot` 等
+ */
+step: string, success: boolean, detail: string, };
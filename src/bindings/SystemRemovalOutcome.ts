@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 移除系统应用的结果
+ */
+export type SystemRemovalOutcome = "removed" | "disabled" | "failed";
@@ -0,0 +1,13 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SystemRemovalOutcome } from "./SystemRemovalOutcome";
+import type { UninstallResult } from "./UninstallResult";
+
+export type SystemRemovalResult = { package: string, outcome: SystemRemovalOutcome, 
+/**
+ * `pm uninstall --user 0` 的结果
+ */
+uninstall: UninstallResult, 
+/**
+ * 卸载失败后 `pm disable-user --user 0` 的输出
+ */
+disable_output: string | null, };
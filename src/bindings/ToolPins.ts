@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 固定使用的工具版本，处理时解析为具体路径
+ */
+export type ToolPins = { apktool: string | null, 
+/**
+ * 同时决定 zipalign 与 apksigner
+ */
+build_tools: string | null, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 工具目录中共存的某个版本，如 `apktool-2.6.1.jar`、`build-tools/34.0.0/`
+ */
+export type ToolVersion = { 
+/**
+ * `apktool`、`zipalign` 或 `apksigner`
+ */
+tool: string, 
+/**
+ * 版本标签；无版本号的 `apktool.jar` 等为空
+ */
+version: string | null, path: string, };
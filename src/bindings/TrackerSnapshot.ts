@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 设备跟踪最近一次观察到的状态，读取时不会调用 adb
+ */
+export type TrackerSnapshot = { tracking: boolean, 
+/**
+ * `direct`、`track` 或 `poll`
+ */
+transport: string | null, 
+/**
+ * 状态为 device 的设备数
+ */
+ready_devices: number, total_devices: number, 
+/**
+ * 最近一次跟踪或轮询失败的原因，恢复后清空
+ */
+error: string | null, 
+/**
+ * Unix 时间戳（秒），从未观察到时为空
+ */
+updated_at: number | null, };
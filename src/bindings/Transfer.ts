@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 一个进行中的设备传输
+ */
+export type Transfer = { id: number, device_id: string, 
+/**
+ * 如 `install`、`pull`、`rollback`
+ */
+operation: string, 
+/**
+ * 开始时间（Unix 时间戳，秒）
+ */
+started_at: number, };
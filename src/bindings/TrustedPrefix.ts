@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type TrustedPrefix = { prefix: string, count: number, source: string, 
+/**
+ * 深度检查发现该前缀的应用家族使用 signature 级权限或 sharedUserId，混入其中可能出现权限异常
+ */
+risky: boolean, risk_reason: string | null, };
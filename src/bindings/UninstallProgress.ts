@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { UninstallResult } from "./UninstallResult";
+
+/**
+ * 批量卸载进度（`uninstall-progress` 事件），每完成一个包推送一次
+ */
+export type UninstallProgress = { device_id: string, completed: number, total: number, result: UninstallResult, };
@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 单个包的卸载结果
+ */
+export type UninstallResult = { package: string, success: boolean, 
+/**
+ * `pm uninstall` 给出的失败代码，如 `DELETE_FAILED_DEVICE_POLICY_MANAGER`
+ */
+failure_code: string | null, message: string | null, 
+/**
+ * `pm uninstall` 的原始输出
+ */
+output: string, 
+/**
+ * 失败时的处理建议
+ */
+hint: string | null, 
+/**
+ * 包不在用户 0 中时，实际安装了它的用户；`any_user` 时为已为其卸载的用户
+ */
+installed_for_users: Array<number>, };
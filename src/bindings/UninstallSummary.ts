@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { UninstallResult } from "./UninstallResult";
+
+/**
+ * 批量卸载的汇总
+ */
+export type UninstallSummary = { succeeded: number, failed: number, results: Array<UninstallResult>, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 设备 shell 的基础命令集
+ */
+export type Userland = "toybox" | "toolbox" | "busybox" | "unknown";
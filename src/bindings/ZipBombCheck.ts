@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SuspiciousEntry } from "./SuspiciousEntry";
+
+/**
+ * ZIP 炸弹检测结果
+ */
+export type ZipBombCheck = { safe: boolean, total_uncompressed_bytes: number, suspicious_entries: Array<SuspiciousEntry>, };